@@ -0,0 +1,288 @@
+use std::{collections::hash_map::DefaultHasher, hash::Hasher};
+
+use ahash::AHasher;
+use bloom_filter_simple::{
+    params::FilterParams, Bitset, BloomFilter, CountingBloomFilter, CuckooFilter, KMBloomFilter,
+    QuotientFilter, SeededBloomFilter, StableXxh3Hasher, StableXxh64Hasher,
+};
+use criterion::{
+    black_box, criterion_group, criterion_main, measurement::WallTime, BenchmarkGroup, BenchmarkId,
+    Criterion, Throughput,
+};
+use fnv::FnvHasher;
+
+/// Number of operations measured by a single iteration.
+const OPERATIONS: u64 = 1_000;
+
+/// Desired capacities of the measured filters. The largest filter does not fit into the CPU
+/// caches.
+const SIZES: [usize; 3] = [1_000, 100_000, 10_000_000];
+
+const FALSE_POSITIVE_PROBABILITY: f64 = 0.001;
+
+// Calls *$function* for every combination of hashers that is compared.
+macro_rules! for_each_hasher_pair {
+    ($function:ident($($argument:expr),*)) => {
+        $function::<AHasher, DefaultHasher>("ahash+sip", $($argument),*);
+        $function::<StableXxh3Hasher, StableXxh64Hasher>("xxh3+xxh64", $($argument),*);
+        $function::<AHasher, FnvHasher>("ahash+fnv", $($argument),*);
+        $function::<FnvHasher, DefaultHasher>("fnv+sip", $($argument),*);
+    };
+}
+
+fn filled_filter<H1, H2>(size: usize) -> KMBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    let mut filter = KMBloomFilter::new(size, FALSE_POSITIVE_PROBABILITY);
+    filter.insert_all(0..size as u64);
+    filter
+}
+
+fn insert<H1, H2>(hashers: &str, group: &mut BenchmarkGroup<WallTime>, size: usize)
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    let mut filter: KMBloomFilter<H1, H2> = KMBloomFilter::new(size, FALSE_POSITIVE_PROBABILITY);
+    let mut next = 0u64;
+    group.bench_function(BenchmarkId::new(hashers, size), |b| {
+        b.iter(|| {
+            for _ in 0..OPERATIONS {
+                filter.insert(&next);
+                next += 1;
+                // Keep the number of elements within the capacity
+                if next == size as u64 {
+                    filter.clear();
+                    next = 0;
+                }
+            }
+        })
+    });
+}
+
+fn contains_hit<H1, H2>(hashers: &str, group: &mut BenchmarkGroup<WallTime>, size: usize)
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    let filter: KMBloomFilter<H1, H2> = filled_filter(size);
+    let mut next = 0u64;
+    group.bench_function(BenchmarkId::new(hashers, size), |b| {
+        b.iter(|| {
+            for _ in 0..OPERATIONS {
+                black_box(filter.contains(&next));
+                next = (next + 1) % size as u64;
+            }
+        })
+    });
+}
+
+fn contains_miss<H1, H2>(hashers: &str, group: &mut BenchmarkGroup<WallTime>, size: usize)
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    let filter: KMBloomFilter<H1, H2> = filled_filter(size);
+    let mut next = size as u64;
+    group.bench_function(BenchmarkId::new(hashers, size), |b| {
+        b.iter(|| {
+            for _ in 0..OPERATIONS {
+                black_box(filter.contains(&next));
+                next += 1;
+            }
+        })
+    });
+}
+
+fn union<H1, H2>(hashers: &str, group: &mut BenchmarkGroup<WallTime>, size: usize)
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    let filter_one: KMBloomFilter<H1, H2> = filled_filter(size);
+    let mut filter_two: KMBloomFilter<H1, H2> =
+        KMBloomFilter::new(size, FALSE_POSITIVE_PROBABILITY);
+    filter_two.insert_all(size as u64..2 * size as u64);
+    group.bench_function(BenchmarkId::new(hashers, size), |b| {
+        b.iter(|| black_box(filter_one.union(&filter_two)))
+    });
+}
+
+fn hashers(c: &mut Criterion) {
+    for &size in SIZES.iter() {
+        let mut group = c.benchmark_group("km_insert");
+        group.throughput(Throughput::Elements(OPERATIONS));
+        for_each_hasher_pair!(insert(&mut group, size));
+        group.finish();
+
+        let mut group = c.benchmark_group("km_contains_hit");
+        group.throughput(Throughput::Elements(OPERATIONS));
+        for_each_hasher_pair!(contains_hit(&mut group, size));
+        group.finish();
+
+        let mut group = c.benchmark_group("km_contains_miss");
+        group.throughput(Throughput::Elements(OPERATIONS));
+        for_each_hasher_pair!(contains_miss(&mut group, size));
+        group.finish();
+
+        let mut group = c.benchmark_group("km_union");
+        let params = FilterParams::for_capacity(size, FALSE_POSITIVE_PROBABILITY);
+        group.throughput(Throughput::Bytes(params.byte_count() as u64));
+        for_each_hasher_pair!(union(&mut group, size));
+        group.finish();
+    }
+}
+
+fn insert_filter<F, C>(
+    name: &str,
+    group: &mut BenchmarkGroup<WallTime>,
+    size: usize,
+    mut filter: F,
+    clear: C,
+) where
+    F: BloomFilter,
+    C: Fn(&mut F),
+{
+    let mut next = 0u64;
+    group.bench_function(BenchmarkId::new(name, size), |b| {
+        b.iter(|| {
+            for _ in 0..OPERATIONS {
+                filter.insert(&next);
+                next += 1;
+                // Keep the number of elements within the capacity
+                if next == size as u64 {
+                    clear(&mut filter);
+                    next = 0;
+                }
+            }
+        })
+    });
+}
+
+fn filters(c: &mut Criterion) {
+    let mut group = c.benchmark_group("filter_insert");
+    group.throughput(Throughput::Elements(OPERATIONS));
+    for &size in SIZES.iter() {
+        insert_filter(
+            "km",
+            &mut group,
+            size,
+            KMBloomFilter::<AHasher, DefaultHasher>::new(size, FALSE_POSITIVE_PROBABILITY),
+            KMBloomFilter::clear,
+        );
+        insert_filter(
+            "seeded",
+            &mut group,
+            size,
+            SeededBloomFilter::new(size, FALSE_POSITIVE_PROBABILITY),
+            SeededBloomFilter::clear,
+        );
+        insert_filter(
+            "counting",
+            &mut group,
+            size,
+            CountingBloomFilter::<AHasher, DefaultHasher>::new(size, FALSE_POSITIVE_PROBABILITY),
+            CountingBloomFilter::clear,
+        );
+        insert_filter(
+            "cuckoo",
+            &mut group,
+            size,
+            CuckooFilter::<AHasher>::new(size, FALSE_POSITIVE_PROBABILITY),
+            CuckooFilter::clear,
+        );
+        insert_filter(
+            "quotient",
+            &mut group,
+            size,
+            QuotientFilter::<AHasher>::new(size, FALSE_POSITIVE_PROBABILITY),
+            QuotientFilter::clear,
+        );
+    }
+    group.finish();
+}
+
+fn strings(c: &mut Criterion) {
+    let keys: Vec<String> = (0..OPERATIONS).map(|i| format!("user-{:08}", i)).collect();
+    let mut filter: KMBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        KMBloomFilter::new(OPERATIONS as usize, FALSE_POSITIVE_PROBABILITY);
+    let mut group = c.benchmark_group("str_insert");
+    group.throughput(Throughput::Elements(OPERATIONS));
+    group.bench_function("hash", |b| {
+        b.iter(|| {
+            filter.clear();
+            for key in &keys {
+                filter.insert(&key.as_str());
+            }
+        })
+    });
+    group.bench_function("insert_str", |b| {
+        b.iter(|| {
+            filter.clear();
+            for key in &keys {
+                filter.insert_str(key);
+            }
+        })
+    });
+    group.finish();
+}
+
+fn power_of_two(c: &mut Criterion) {
+    for &size in SIZES.iter() {
+        // Misses check fewer bits than hits, so the reduction of the hashes is a larger share
+        let mut group = c.benchmark_group("km_contains_miss_sizing");
+        group.throughput(Throughput::Elements(OPERATIONS));
+        for &(name, power_of_two) in [("exact", false), ("power_of_two", true)].iter() {
+            let mut filter: KMBloomFilter<StableXxh3Hasher, StableXxh64Hasher> = if power_of_two {
+                KMBloomFilter::new_power_of_two(size, FALSE_POSITIVE_PROBABILITY)
+            } else {
+                KMBloomFilter::new(size, FALSE_POSITIVE_PROBABILITY)
+            };
+            filter.insert_all(0..size as u64);
+            let mut next = size as u64;
+            group.bench_function(BenchmarkId::new(name, size), |b| {
+                b.iter(|| {
+                    for _ in 0..OPERATIONS {
+                        black_box(filter.contains(&next));
+                        next += 1;
+                    }
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+/// Bitset with every *step*-th bit set.
+fn bitset(length: usize, step: usize) -> Bitset {
+    let mut bitset = Bitset::new(length);
+    for index in (0..length).step_by(step) {
+        bitset.set(index, true);
+    }
+    bitset
+}
+
+fn bitsets(c: &mut Criterion) {
+    for &size in SIZES.iter() {
+        let params = FilterParams::for_capacity(size, FALSE_POSITIVE_PROBABILITY);
+        let other = bitset(params.bit_count(), 3);
+
+        // Merging is dominated by combining the words and counting the ones of the result
+        let mut group = c.benchmark_group("bitset_merge");
+        group.throughput(Throughput::Bytes(params.byte_count() as u64));
+        let mut target = bitset(params.bit_count(), 2);
+        group.bench_function(BenchmarkId::new("union_with", size), |b| {
+            b.iter(|| target.union_with(black_box(&other)))
+        });
+        let mut target = bitset(params.bit_count(), 2);
+        group.bench_function(BenchmarkId::new("intersect_with", size), |b| {
+            b.iter(|| target.intersect_with(black_box(&other)))
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, hashers, power_of_two, filters, strings, bitsets);
+criterion_main!(benches);
@@ -0,0 +1,62 @@
+//! Apply untrusted deltas to bitsets, and check the result against a model of the bits as a
+//! vector of booleans. Deltas created from two bitsets must survive encoding and restore the
+//! newer bitset.
+#![no_main]
+
+use arbitrary::Arbitrary;
+use bloom_filter_simple::{Bitset, BitsetDelta};
+use libfuzzer_sys::fuzz_target;
+
+/// Bitsets of the target are at most this long, so that hostile lengths do not exhaust memory.
+const MAX_LENGTH: usize = 1 << 16;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    old: Vec<u8>,
+    new: Vec<u8>,
+    delta: Vec<u8>,
+}
+
+fn bitset(bytes: &[u8], length: usize) -> Bitset {
+    let mut bytes = bytes.to_vec();
+    bytes.resize(length.div_ceil(8), 0);
+    Bitset::from_bytes(length, bytes)
+}
+
+fn model(bitset: &Bitset) -> Vec<bool> {
+    (0..bitset.len()).map(|index| bitset.get(index)).collect()
+}
+
+fn check(bitset: &Bitset, expected: &[bool]) {
+    assert_eq!(model(bitset), expected);
+    assert_eq!(
+        bitset.count_ones(),
+        expected.iter().filter(|&&bit| bit).count()
+    );
+}
+
+fuzz_target!(|input: Input| {
+    // Deltas between two bitsets of the same length
+    let length = input.old.len().max(input.new.len()).min(MAX_LENGTH / 8) * 8;
+    let old = bitset(&input.old, length);
+    let new = bitset(&input.new, length);
+    let delta = BitsetDelta::from_bytes(Bitset::delta(&old, &new).as_bytes().to_vec()).unwrap();
+    let mut replica = old.clone();
+    replica.apply_delta(&delta);
+    assert_eq!(replica, old.union(&new));
+    check(&replica, &model(&old.union(&new)));
+
+    // Untrusted deltas are either rejected or set exactly the bits they describe
+    if let Ok(delta) = BitsetDelta::from_bytes(input.delta) {
+        if delta.bitset_len() > MAX_LENGTH {
+            return;
+        }
+        let mut bitset = bitset(&input.old, delta.bitset_len());
+        let mut expected = model(&bitset);
+        for index in delta.iter_ones() {
+            expected[index] = true;
+        }
+        bitset.apply_delta(&delta);
+        check(&bitset, &expected);
+    }
+});
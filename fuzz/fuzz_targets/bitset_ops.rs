@@ -0,0 +1,119 @@
+//! Run random sequences of Bitset operations and compare every result with a model of the bits as
+//! a vector of booleans.
+#![no_main]
+
+use arbitrary::Arbitrary;
+use bloom_filter_simple::Bitset;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+enum Operation {
+    Set(u16, bool),
+    Get(u16),
+    // The other bitset has the same length, with the bits of the bytes
+    Union(Vec<u8>),
+    Intersect(Vec<u8>),
+    Difference(Vec<u8>),
+    SymmetricDifference(Vec<u8>),
+    Complement,
+    Clear,
+    FoldInHalf,
+    Resize(u16),
+}
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    length: u16,
+    operations: Vec<Operation>,
+}
+
+fn other(bytes: &[u8], length: usize) -> (Bitset, Vec<bool>) {
+    let mut bytes = bytes.to_vec();
+    bytes.resize(length.div_ceil(8), 0);
+    let bitset = Bitset::from_bytes(length, bytes.clone());
+    let model = (0..length)
+        .map(|index| bytes[index / 8] & (1 << (index % 8)) != 0)
+        .collect();
+    (bitset, model)
+}
+
+fn combine(model: &mut [bool], other: &[bool], op: fn(bool, bool) -> bool) {
+    for (bit, &other) in model.iter_mut().zip(other) {
+        *bit = op(*bit, other);
+    }
+}
+
+fuzz_target!(|input: Input| {
+    let mut bitset = Bitset::new(input.length as usize);
+    let mut model = vec![false; input.length as usize];
+    for operation in input.operations {
+        let length = model.len();
+        match operation {
+            Operation::Set(index, value) => {
+                let index = index as usize;
+                assert_eq!(index < length, bitset.try_set(index, value).is_ok());
+                if index < length {
+                    model[index] = value;
+                }
+            }
+            Operation::Get(index) => {
+                let index = index as usize;
+                assert_eq!(model.get(index).copied(), bitset.try_get(index).ok());
+            }
+            Operation::Union(bytes) => {
+                let (other, other_model) = other(&bytes, length);
+                assert_eq!(
+                    bitset.union(&other).count_ones(),
+                    bitset.count_ones_union(&other)
+                );
+                bitset.union_with(&other);
+                combine(&mut model, &other_model, |a, b| a | b);
+            }
+            Operation::Intersect(bytes) => {
+                let (other, other_model) = other(&bytes, length);
+                bitset.intersect_with(&other);
+                combine(&mut model, &other_model, |a, b| a & b);
+            }
+            Operation::Difference(bytes) => {
+                let (other, other_model) = other(&bytes, length);
+                bitset = bitset.difference(&other);
+                combine(&mut model, &other_model, |a, b| a & !b);
+            }
+            Operation::SymmetricDifference(bytes) => {
+                let (other, other_model) = other(&bytes, length);
+                bitset.symmetric_difference_with(&other);
+                combine(&mut model, &other_model, |a, b| a ^ b);
+            }
+            Operation::Complement => {
+                bitset = bitset.complement();
+                for bit in model.iter_mut() {
+                    *bit = !*bit;
+                }
+            }
+            Operation::Clear => {
+                bitset.clear();
+                model.fill(false);
+            }
+            Operation::FoldInHalf => {
+                if !length.is_multiple_of(2) {
+                    continue;
+                }
+                bitset.fold_in_half();
+                let (low, high) = model.split_at(length / 2);
+                model = low.iter().zip(high).map(|(&a, &b)| a | b).collect();
+            }
+            Operation::Resize(length) => {
+                bitset.resize(length as usize);
+                model.resize(length as usize, false);
+            }
+        }
+
+        assert_eq!(model.len(), bitset.len());
+        assert_eq!(
+            model.iter().filter(|&&bit| bit).count(),
+            bitset.count_ones()
+        );
+        let ones: Vec<usize> = (0..model.len()).filter(|&index| model[index]).collect();
+        assert_eq!(ones, bitset.iter_ones().collect::<Vec<_>>());
+    }
+});
@@ -0,0 +1,43 @@
+//! Parse untrusted bytes with every deserializer of the crate. Parsing must never panic or
+//! allocate more memory than the input describes, and whatever parses must survive a round trip.
+#![no_main]
+
+use bloom_filter_simple::{
+    BitsetDelta, BloomFilter, BloomFilterView, GolombCodedSet, StableBloomFilterDefault,
+    StableXxh3Hasher, StableXxh64Hasher,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let from_bytes = StableBloomFilterDefault::from_bytes(data);
+    let read_from = StableBloomFilterDefault::read_from(data);
+    let view = BloomFilterView::<StableXxh3Hasher, StableXxh64Hasher>::read_from(data);
+    // from_bytes and the view accept exactly the filters read_from accepts without trailing bytes
+    assert_eq!(from_bytes.is_ok(), view.is_ok());
+    if let Ok(filter) = from_bytes {
+        let read = read_from.expect("read_from rejected a filter accepted by from_bytes");
+        assert_eq!(filter, read);
+        let restored = StableBloomFilterDefault::from_bytes(&filter.to_bytes()).unwrap();
+        assert_eq!(filter, restored);
+        assert_eq!(filter.contains(&data), view.unwrap().contains(&data));
+    }
+
+    if let Ok(delta) = BitsetDelta::from_bytes(data.to_vec()) {
+        assert_eq!(data, delta.as_bytes());
+        let mut count = 0;
+        let mut previous = None;
+        for index in delta.iter_ones().take(1 << 16) {
+            assert!(index < delta.bitset_len());
+            assert!(previous.is_none_or(|previous| previous < index));
+            previous = Some(index);
+            count += 1;
+        }
+        assert!(count == delta.count_ones() || count == 1 << 16);
+    }
+
+    if let Ok(gcs) = GolombCodedSet::<StableXxh3Hasher>::from_bytes(data.to_vec()) {
+        assert_eq!(data, gcs.as_bytes());
+        gcs.contains(&data);
+        gcs.approximate_false_positive_probability();
+    }
+});
@@ -0,0 +1,93 @@
+#ifndef BLOOM_FILTER_SIMPLE_H
+#define BLOOM_FILTER_SIMPLE_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Opaque handle of a StableBloomFilterDefault.
+typedef struct FfiBloomFilter FfiBloomFilter;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Create a new, empty filter for up to *desired_capacity* elements with a false positive
+// probability of less than *desired_false_positive_probability*.
+//
+// Returns a null pointer if desired_capacity == 0 or if desired_false_positive_probability is not
+// in the interval (0,1). Release the filter with *bloom_filter_free*.
+struct FfiBloomFilter *bloom_filter_create(size_t desired_capacity,
+                                           double desired_false_positive_probability);
+
+// Release a filter. Null pointers are ignored.
+//
+// # Safety
+//
+// *filter* must be null or a filter returned by *bloom_filter_create* or
+// *bloom_filter_deserialize* that has not been released yet.
+void bloom_filter_free(struct FfiBloomFilter *filter);
+
+// Insert the *length* bytes at *data* into *filter*, see *KMBloomFilter::insert_bytes*.
+//
+// Does nothing if *filter* is null.
+//
+// # Safety
+//
+// *filter* must be null or a valid filter that is not accessed concurrently, and *data* must
+// point to *length* readable bytes, or may be null if *length* is 0.
+void bloom_filter_insert_bytes(struct FfiBloomFilter *filter, const uint8_t *data, size_t length);
+
+// Check whether the *length* bytes at *data* are contained in *filter*, see
+// *KMBloomFilter::contains_bytes*.
+//
+// Returns false if *filter* is null.
+//
+// # Safety
+//
+// *filter* must be null or a valid filter that is not modified concurrently, and *data* must
+// point to *length* readable bytes, or may be null if *length* is 0.
+bool bloom_filter_contains_bytes(const struct FfiBloomFilter *filter,
+                                 const uint8_t *data,
+                                 size_t length);
+
+// Serialize *filter* in the format of *KMBloomFilter::write_to* and store the number of bytes
+// in *length*.
+//
+// Returns a null pointer if *filter* or *length* is null. Release the bytes with
+// *bloom_filter_bytes_free*.
+//
+// # Safety
+//
+// *filter* must be null or a valid filter that is not modified concurrently, and *length* must
+// be null or point to a writable size.
+uint8_t *bloom_filter_serialize(const struct FfiBloomFilter *filter, size_t *length);
+
+// Restore a filter from the *length* bytes at *data*, as returned by *bloom_filter_serialize* or
+// written by *KMBloomFilter::write_to* with the hashers of StableBloomFilterDefault.
+//
+// Returns a null pointer if the bytes do not contain such a filter (see
+// *KMBloomFilter::from_bytes*). Release the filter with *bloom_filter_free*.
+//
+// # Safety
+//
+// *data* must point to *length* readable bytes, or may be null if *length* is 0.
+struct FfiBloomFilter *bloom_filter_deserialize(const uint8_t *data, size_t length);
+
+// Release bytes returned by *bloom_filter_serialize*. Null pointers are ignored.
+//
+// # Safety
+//
+// *data* must be null or bytes returned by *bloom_filter_serialize* that have not been released
+// yet, and *length* must be the length that has been stored for them.
+void bloom_filter_bytes_free(uint8_t *data, size_t length);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* BLOOM_FILTER_SIMPLE_H */
@@ -0,0 +1,210 @@
+use alloc::vec::Vec;
+use core::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+};
+
+use crate::{BloomFilter, HashedKey, KMBloomFilter};
+
+/// Array of KMBloomFilter levels that records how far away an element is, e.g., the number of
+/// hops to the nodes of a peer-to-peer network that store it (attenuated bloom filter).
+///
+/// Level *i* contains the elements that are reachable at distance *i*, e.g., level 0 contains the
+/// elements of the node itself, level 1 the elements of its neighbors, and so on. *lookup* returns
+/// the nearest level that may contain an element, which is the direction a query is routed to.
+/// A node builds the levels from the filters of its neighbors with *merge_neighbor*, which merges
+/// level *i* of a neighbor into level *i + 1*.
+///
+/// All levels have the same configuration, so every level has about the false positive
+/// probability of a single KMBloomFilter, and *lookup* returns a level that is too near, or a
+/// level for an element that is not reachable at all, with about the sum of the false positive
+/// probabilities of the levels it checks.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{AttenuatedBloomFilter,StableXxh3Hasher,StableXxh64Hasher};
+///
+/// fn main() {
+///     type Routes = AttenuatedBloomFilter<StableXxh3Hasher, StableXxh64Hasher>;
+///
+///     // A neighbor stores "song.mp3", and its neighbor stores "movie.mkv"
+///     let mut neighbor = Routes::new(3, 1_000, 0.001);
+///     neighbor.insert_at(0, &"song.mp3");
+///     neighbor.insert_at(1, &"movie.mkv");
+///
+///     let mut routes = Routes::new(3, 1_000, 0.001);
+///     routes.insert_at(0, &"notes.txt");
+///     routes.merge_neighbor(&neighbor);
+///
+///     assert_eq!(Some(0), routes.lookup(&"notes.txt"));
+///     assert_eq!(Some(1), routes.lookup(&"song.mp3"));
+///     assert_eq!(Some(2), routes.lookup(&"movie.mkv"));
+///     assert_eq!(None, routes.lookup(&"unknown.bin"));
+/// }
+/// ```
+pub struct AttenuatedBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    // The nearest level is at the front
+    levels: Vec<KMBloomFilter<H1, H2>>,
+}
+
+impl<H1, H2> AttenuatedBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Initialize a new instance of AttenuatedBloomFilter with *depth* levels, each of which
+    /// guarantees that the false positive rate is less than *desired_false_positive_probability*
+    /// for up to *desired_capacity* elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if depth == 0, desired_capacity == 0, or if desired_false_positive_probability is
+    /// not in the interval (0,1).
+    pub fn new(
+        depth: usize,
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self {
+        let desired_false_positive_probability = desired_false_positive_probability.into();
+        if depth == 0 {
+            panic!("an attenuated bloom filter requires at least one level");
+        }
+        Self {
+            levels: (0..depth)
+                .map(|_| KMBloomFilter::new(desired_capacity, desired_false_positive_probability))
+                .collect(),
+        }
+    }
+
+    /// Insert data into the level *level*, i.e., record that it is reachable at this distance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if level >= *depth*.
+    pub fn insert_at<T>(&mut self, level: usize, data: &T)
+    where
+        T: Hash,
+    {
+        self.level_mut(level).insert(data);
+    }
+
+    /// Insert the element *key* has been computed for into the level *level*.
+    ///
+    /// See *KMBloomFilter::hash* for how to compute a key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if level >= *depth*.
+    pub fn insert_hashed_at(&mut self, level: usize, key: &HashedKey<H1, H2>) {
+        self.level_mut(level).insert_hashed(key);
+    }
+
+    /// Return the nearest level that may contain data, or none if no level contains it.
+    pub fn lookup<T>(&self, data: &T) -> Option<usize>
+    where
+        T: Hash,
+    {
+        self.lookup_hashed(&self.levels[0].hash(data))
+    }
+
+    /// Return the nearest level that may contain the element *key* has been computed for, or none
+    /// if no level contains it.
+    pub fn lookup_hashed(&self, key: &HashedKey<H1, H2>) -> Option<usize> {
+        self.levels
+            .iter()
+            .position(|level| level.contains_hashed(key))
+    }
+
+    /// Check whether data is contained in any level.
+    pub fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        self.lookup(data).is_some()
+    }
+
+    /// Merge the filter of a neighbor into this filter: level *i* of *neighbor* is merged into
+    /// level *i + 1*, because the elements of the neighbor are one step further away. The last
+    /// level of *neighbor* is beyond the depth of this filter and dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the depth or the configuration of the levels of *neighbor* differ from this
+    /// filter.
+    pub fn merge_neighbor(&mut self, neighbor: &Self) {
+        if self.depth() != neighbor.depth() {
+            panic!(
+                "unable to merge an attenuated bloom filter of depth {} into one of depth {}",
+                neighbor.depth(),
+                self.depth()
+            );
+        }
+        for (level, neighbor_level) in self.levels[1..].iter_mut().zip(neighbor.levels.iter()) {
+            level.union_with(neighbor_level);
+        }
+    }
+
+    /// Number of levels of the filter.
+    pub fn depth(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// The filter of level *level*, e.g., to send it to a neighbor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if level >= *depth*.
+    pub fn level(&self, level: usize) -> &KMBloomFilter<H1, H2> {
+        self.check_level(level);
+        &self.levels[level]
+    }
+
+    /// The mutable filter of level *level*, e.g., to clear the elements of a neighbor that has
+    /// left and merge the remaining neighbors again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if level >= *depth*.
+    pub fn level_mut(&mut self, level: usize) -> &mut KMBloomFilter<H1, H2> {
+        self.check_level(level);
+        &mut self.levels[level]
+    }
+
+    /// Remove all elements from all levels.
+    pub fn clear(&mut self) {
+        for level in self.levels.iter_mut() {
+            level.clear();
+        }
+    }
+
+    fn check_level(&self, level: usize) {
+        if level >= self.levels.len() {
+            panic!(
+                "unable to access level {} of an attenuated bloom filter of depth {}",
+                level,
+                self.levels.len()
+            );
+        }
+    }
+}
+
+impl<H1, H2> Debug for AttenuatedBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AttenuatedBloomFilter{{levels: [")?;
+        for (i, level) in self.levels.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{:?}", level)?;
+        }
+        write!(f, "]}}")
+    }
+}
@@ -1,28 +1,166 @@
-use std::fmt::Debug;
+use alloc::{string::String, vec, vec::Vec};
+use core::{
+    fmt::{self, Debug},
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Index, Not},
+};
 
+use crate::{
+    bitset_delta::DeltaEncoder,
+    simd::{self, count_ones},
+    BitsetDelta, BloomFilterError,
+};
+
+/// Number of bits stored in a single word of a bitset.
+const WORD_BITS: usize = 64;
+
+/// Number of words that are converted to bytes at once when a bitset is written.
+#[cfg(feature = "std")]
+pub(crate) const CHUNK_WORDS: usize = 1024;
+
+/// Fixed-size set of bits, which stores the state of KMBloomFilter and SeededBloomFilter.
+///
+/// A bitset can be obtained from a filter with *bitset* to analyze it, e.g., to find the bits
+/// in which two snapshots of a filter differ. Bitsets can be combined with the bitwise operators
+/// on references (`&a | &b`, `&a & &b`, `&a ^ &b`, `!&a`) and their assigning variants. All
+/// binary operations panic if the lengths of the bitsets differ. The checked variants *try_set*,
+/// *try_get*, *try_union* and *try_intersect* (and their assigning variants) return a
+/// BloomFilterError instead, e.g., for bitsets received from untrusted sources.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+///
+/// let mut snapshot = StableBloomFilterDefault::new(1_000, 0.001);
+/// snapshot.insert(&1);
+/// let mut current = StableBloomFilterDefault::new(1_000, 0.001);
+/// current.insert(&1);
+/// current.insert(&2);
+///
+/// // Bits that have been set since the snapshot
+/// let difference = current.bitset() ^ snapshot.bitset();
+/// assert_eq!(snapshot.hasher_count(), difference.iter_ones().count());
+/// assert!(difference.iter_ones().all(|index| current.bitset()[index]));
+/// ```
+#[derive(Clone, PartialEq, Eq)]
 pub struct Bitset {
-    bytes: Vec<u8>,
+    words: Vec<u64>,
     length: usize,
+    // Number of set bits, kept up to date by all operations
+    ones: usize,
 }
 
 impl Bitset {
+    /// Create a bitset of *length* bits, all of which are cleared.
     pub fn new(length: usize) -> Self {
-        let byte_length = if length % 8 == 0 {
-            length / 8
-        } else {
-            1 + length / 8
-        };
+        Self {
+            length,
+            words: vec![0; length.div_ceil(WORD_BITS)],
+            ones: 0,
+        }
+    }
 
+    /// Create a bitset of *length* bits from the bytes written by *write_bytes*.
+    ///
+    /// Bits in the last byte that exceed *length* are cleared.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of bytes does not match *length*.
+    pub fn from_bytes(length: usize, bytes: Vec<u8>) -> Self {
+        if bytes.len() != length.div_ceil(8) {
+            panic!(
+                "unable to create a bitset of length {} from {} bytes",
+                length,
+                bytes.len()
+            );
+        }
+        let mut words: Vec<u64> = bytes
+            .chunks(8)
+            .map(|chunk| {
+                let mut word = [0; 8];
+                word[..chunk.len()].copy_from_slice(chunk);
+                u64::from_le_bytes(word)
+            })
+            .collect();
+        let trailing_bits = length % WORD_BITS;
+        if trailing_bits > 0 {
+            if let Some(last) = words.last_mut() {
+                *last &= (1u64 << trailing_bits) - 1;
+            }
+        }
+        Self::from_words(words, length)
+    }
+
+    /// The bits of the bitset in the format of *write_bytes*, which can be restored with
+    /// *from_bytes*.
+    ///
+    /// The bits are stored in words, so the bytes are a copy rather than a view.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self.words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        bytes.truncate(self.length.div_ceil(8));
+        bytes
+    }
+
+    pub(crate) fn from_words(words: Vec<u64>, length: usize) -> Self {
+        let ones = count_ones(&words);
         Self {
+            words,
             length,
-            bytes: vec![0; byte_length],
+            ones,
         }
     }
 
+    /// Write the bits of the bitset to *writer*. Bit *i* is written to byte *i / 8* at position
+    /// *i % 8*, counting from the least significant bit.
+    #[cfg(feature = "std")]
+    pub fn write_bytes<W>(&self, writer: &mut W) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        // Words are converted in chunks to avoid both a copy of the whole bitset and a call to
+        // the writer for every single word.
+        let mut buffer = Vec::with_capacity(CHUNK_WORDS * 8);
+        for chunk_index in 0..self.chunk_count() {
+            self.chunk_bytes(chunk_index, &mut buffer);
+            writer.write_all(&buffer)?;
+        }
+        Ok(())
+    }
+
+    /// Number of chunks of *CHUNK_WORDS* words that the bits are written in.
+    #[cfg(feature = "std")]
+    pub(crate) fn chunk_count(&self) -> usize {
+        self.words.len().div_ceil(CHUNK_WORDS)
+    }
+
+    /// Replace the contents of *buffer* with the bytes of chunk *chunk_index* in the format of
+    /// *write_bytes*.
+    #[cfg(feature = "std")]
+    pub(crate) fn chunk_bytes(&self, chunk_index: usize, buffer: &mut Vec<u8>) {
+        let start = chunk_index * CHUNK_WORDS;
+        let end = (start + CHUNK_WORDS).min(self.words.len());
+        buffer.clear();
+        for word in &self.words[start..end] {
+            buffer.extend_from_slice(&word.to_le_bytes());
+        }
+        buffer.truncate(self.length.div_ceil(8) - start * 8);
+    }
+
+    /// Number of bits of the bitset.
     pub fn len(&self) -> usize {
         self.length
     }
 
+    /// Whether the bitset has a length of zero.
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Set bit *index* to *value*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if index >= len
     pub fn set(&mut self, index: usize, value: bool) {
         if index >= self.len() {
             panic!(
@@ -31,16 +169,22 @@ impl Bitset {
                 index,
             )
         }
-        let byte_index = index / 8;
-        let mut mask = 0x01 << index % 8;
+        let word = &mut self.words[index / WORD_BITS];
+        let mask = 1u64 << (index % WORD_BITS);
+        let was_set = *word & mask != 0;
         if value {
-            self.bytes[byte_index] |= mask;
+            *word |= mask;
         } else {
-            mask = !mask;
-            self.bytes[byte_index] &= mask;
+            *word &= !mask;
         }
+        self.ones = self.ones + value as usize - was_set as usize;
     }
 
+    /// Whether bit *index* is set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if index >= len
     pub fn get(&self, index: usize) -> bool {
         if index >= self.len() {
             panic!(
@@ -49,20 +193,192 @@ impl Bitset {
                 index,
             )
         }
-        let byte_index = index / 8;
-        let mask = 0x01 << index % 8;
-        self.bytes[byte_index] & mask == mask
+        let mask = 1u64 << (index % WORD_BITS);
+        self.words[index / WORD_BITS] & mask != 0
+    }
+
+    /// Set bit *index* to *value*, or return an error if index >= len.
+    pub fn try_set(&mut self, index: usize, value: bool) -> Result<(), BloomFilterError> {
+        self.check_index(index)?;
+        self.set(index, value);
+        Ok(())
+    }
+
+    /// Whether bit *index* is set, or an error if index >= len.
+    pub fn try_get(&self, index: usize) -> Result<bool, BloomFilterError> {
+        self.check_index(index)?;
+        Ok(self.get(index))
+    }
+
+    /// Hint the CPU to load the word that contains bit *index* into its caches, so that a following
+    /// *get* of a bit that is not cached does not wait for memory.
+    ///
+    /// Prefetching is only a hint and has no effect on other architectures than x86_64.
+    #[cfg(feature = "prefetch")]
+    #[inline]
+    pub(crate) fn prefetch(&self, index: usize) {
+        #[cfg(target_arch = "x86_64")]
+        if let Some(word) = self.words.get(index / WORD_BITS) {
+            use core::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+            // Safety: SSE is available on every x86_64 CPU, and prefetching never faults
+            unsafe { _mm_prefetch::<_MM_HINT_T0>(word as *const u64 as *const i8) };
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        let _ = index;
+    }
+
+    /// Change the length of the bitset to *length* bits. Added bits are cleared, and removed bits
+    /// are dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::Bitset;
+    ///
+    /// let mut bitset = Bitset::new(100);
+    /// bitset.set(10, true);
+    /// bitset.set(90, true);
+    ///
+    /// bitset.resize(50);
+    /// assert_eq!(1, bitset.count_ones());
+    /// bitset.resize(100);
+    /// assert_eq!(false, bitset.get(90));
+    /// ```
+    pub fn resize(&mut self, length: usize) {
+        self.words.resize(length.div_ceil(WORD_BITS), 0);
+        let trailing_bits = length % WORD_BITS;
+        if trailing_bits > 0 {
+            if let Some(last) = self.words.last_mut() {
+                *last &= (1u64 << trailing_bits) - 1;
+            }
+        }
+        self.length = length;
+        self.ones = count_ones(&self.words);
     }
 
+    /// Halve the length of the bitset by OR-ing the upper half of the bits onto the lower half,
+    /// i.e., bit *i* of the result is set if bit *i* or bit *i + len / 2* has been set.
+    ///
+    /// Folding preserves membership for indices that are reduced modulo the length, which is how
+    /// *KMBloomFilter::compact* shrinks the bits of every hasher.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the bitset is odd.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::Bitset;
+    ///
+    /// let mut bitset = Bitset::new(100);
+    /// bitset.set(10, true);
+    /// bitset.set(60, true);
+    /// bitset.set(99, true);
+    ///
+    /// bitset.fold_in_half();
+    /// assert_eq!(50, bitset.len());
+    /// assert_eq!(vec![10, 49], bitset.iter_ones().collect::<Vec<_>>());
+    /// ```
+    pub fn fold_in_half(&mut self) {
+        if !self.length.is_multiple_of(2) {
+            panic!("unable to fold a bitset of odd length {}", self.length);
+        }
+        let half = self.length / 2;
+        // The words of the upper half are read before the lower words they are OR-ed onto are
+        // written, and bits beyond the lower half are cleared by resize
+        for word in 0..half.div_ceil(WORD_BITS) {
+            let upper = self.word_at(half + word * WORD_BITS);
+            self.words[word] |= upper;
+        }
+        self.resize(half);
+    }
+
+    /// The 64 bits starting at bit *start*, where bits beyond the length are cleared.
+    fn word_at(&self, start: usize) -> u64 {
+        let word = start / WORD_BITS;
+        let shift = start % WORD_BITS;
+        let low = self.words.get(word).map_or(0, |w| w >> shift);
+        let high = if shift == 0 {
+            0
+        } else {
+            self.words
+                .get(word + 1)
+                .map_or(0, |w| w << (WORD_BITS - shift))
+        };
+        low | high
+    }
+
+    /// Clear all bits.
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+        self.ones = 0;
+    }
+
+    /// Number of set bits. The number is kept up to date by all operations, so this is a constant
+    /// time operation.
     pub fn count_ones(&self) -> usize {
-        self.bytes.iter().map(|b| b.count_ones() as usize).sum()
+        self.ones
     }
 
-    #[allow(dead_code)]
+    /// Number of cleared bits.
     pub fn count_zeros(&self) -> usize {
         self.len() - self.count_ones()
     }
 
+    /// All bits as a string of '0' and '1', starting with bit 0, e.g., to compare small bitsets in
+    /// tests. The Debug output only summarizes the bitset, because it is written to logs.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::Bitset;
+    ///
+    /// let mut bitset = Bitset::new(6);
+    /// bitset.set(1, true);
+    /// bitset.set(4, true);
+    /// assert_eq!("010010", bitset.dump_bits());
+    /// ```
+    pub fn dump_bits(&self) -> String {
+        (0..self.length)
+            .map(|i| if self.get(i) { '1' } else { '0' })
+            .collect()
+    }
+
+    /// Fill ratio, i.e., the share of set bits, of each of *buckets* consecutive regions of the
+    /// bitset, e.g., to detect hashers that set some regions of a filter more often than others.
+    ///
+    /// The regions have *len / buckets* bits, rounded up, so the last region may be smaller.
+    ///
+    /// # Panics
+    ///
+    /// Panics if buckets == 0 or buckets > len.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::Bitset;
+    ///
+    /// let mut bitset = Bitset::new(100);
+    /// for index in 0..30 {
+    ///     bitset.set(index, true);
+    /// }
+    /// assert_eq!(vec![0.6, 0.0], bitset.density_histogram(2));
+    /// ```
+    pub fn density_histogram(&self, buckets: usize) -> Vec<f64> {
+        if buckets == 0 || buckets > self.length {
+            panic!(
+                "unable to split a bitset of {} bits into {} buckets",
+                self.length, buckets
+            );
+        }
+        let region = self.length.div_ceil(buckets);
+        (0..self.length)
+            .step_by(region)
+            .map(|start| {
+                let end = (start + region).min(self.length);
+                self.count_ones_in(start, end) as f64 / (end - start) as f64
+            })
+            .collect()
+    }
+
+    /// Bitset in which all bits are set that are set in this bitset or in 'other'.
     pub fn union(&self, other: &Self) -> Self {
         if self.length != other.length {
             panic!(
@@ -70,17 +386,53 @@ impl Bitset {
                 self.length, other.length
             );
         }
-        Self {
-            bytes: self
-                .bytes
-                .iter()
-                .zip(other.bytes.iter())
-                .map(|(a, b)| a | b)
-                .collect(),
-            length: self.length,
+        let mut union = self.clone();
+        union.union_with(other);
+        union
+    }
+
+    /// Set all bits of this bitset that are set in 'other'.
+    pub fn union_with(&mut self, other: &Self) {
+        if self.length != other.length {
+            panic!(
+                "unable to union bitsets with different lengths: {} and {}",
+                self.length, other.length
+            );
+        }
+        self.ones = simd::union_with(&mut self.words, &other.words);
+    }
+
+    /// Bitset in which all bits are set that are set in this bitset or in 'other', or an error if
+    /// the lengths of the bitsets differ.
+    pub fn try_union(&self, other: &Self) -> Result<Self, BloomFilterError> {
+        self.check_length(other)?;
+        Ok(self.union(other))
+    }
+
+    /// Set all bits of this bitset that are set in 'other', or return an error if the lengths of
+    /// the bitsets differ.
+    pub fn try_union_with(&mut self, other: &Self) -> Result<(), BloomFilterError> {
+        self.check_length(other)?;
+        self.union_with(other);
+        Ok(())
+    }
+
+    /// Number of ones in the union of both bitsets, without creating the union.
+    pub fn count_ones_union(&self, other: &Self) -> usize {
+        if self.length != other.length {
+            panic!(
+                "unable to union bitsets with different lengths: {} and {}",
+                self.length, other.length
+            );
         }
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .map(|(a, b)| (a | b).count_ones() as usize)
+            .sum()
     }
 
+    /// Bitset in which all bits are set that are set in both this bitset and 'other'.
     pub fn intersect(&self, other: &Self) -> Self {
         if self.length != other.length {
             panic!(
@@ -88,26 +440,429 @@ impl Bitset {
                 self.length, other.length
             );
         }
-        Self {
-            bytes: self
-                .bytes
+        let mut intersection = self.clone();
+        intersection.intersect_with(other);
+        intersection
+    }
+
+    /// Whether all bits that are set in this bitset are also set in 'other'.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        if self.length != other.length {
+            panic!(
+                "unable to compare bitsets with different lengths: {} and {}",
+                self.length, other.length
+            );
+        }
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .all(|(a, b)| a & !b == 0)
+    }
+
+    /// Whether any bit in the range from *start* (inclusive) to *end* (exclusive) is set in both
+    /// bitsets.
+    pub fn intersects_in(&self, other: &Self, start: usize, end: usize) -> bool {
+        if self.length != other.length {
+            panic!(
+                "unable to compare bitsets with different lengths: {} and {}",
+                self.length, other.length
+            );
+        }
+        if end > self.length {
+            panic!(
+                "range out of bounds: the len is {} but the range ends at {}",
+                self.length, end
+            );
+        }
+        let mut index = start;
+        while index < end {
+            let offset = index % WORD_BITS;
+            let bits = (WORD_BITS - offset).min(end - index);
+            let mask = if bits == WORD_BITS {
+                u64::MAX
+            } else {
+                ((1u64 << bits) - 1) << offset
+            };
+            let word_index = index / WORD_BITS;
+            if self.words[word_index] & other.words[word_index] & mask != 0 {
+                return true;
+            }
+            index += bits;
+        }
+        false
+    }
+
+    /// Clear all bits of this bitset that are not set in 'other'.
+    pub fn intersect_with(&mut self, other: &Self) {
+        if self.length != other.length {
+            panic!(
+                "unable to intersect bitsets with different lengths: {} and {}",
+                self.length, other.length
+            );
+        }
+        self.ones = simd::intersect_with(&mut self.words, &other.words);
+    }
+
+    /// Bitset in which all bits are set that are set in both this bitset and 'other', or an error
+    /// if the lengths of the bitsets differ.
+    pub fn try_intersect(&self, other: &Self) -> Result<Self, BloomFilterError> {
+        self.check_length(other)?;
+        Ok(self.intersect(other))
+    }
+
+    /// Clear all bits of this bitset that are not set in 'other', or return an error if the
+    /// lengths of the bitsets differ.
+    pub fn try_intersect_with(&mut self, other: &Self) -> Result<(), BloomFilterError> {
+        self.check_length(other)?;
+        self.intersect_with(other);
+        Ok(())
+    }
+
+    /// Bitset in which all bits are set that are set in this bitset but not in 'other'.
+    pub fn difference(&self, other: &Self) -> Self {
+        if self.length != other.length {
+            panic!(
+                "unable to subtract bitsets with different lengths: {} and {}",
+                self.length, other.length
+            );
+        }
+        Self::from_words(
+            self.words
+                .iter()
+                .zip(other.words.iter())
+                .map(|(a, b)| a & !b)
+                .collect(),
+            self.length,
+        )
+    }
+
+    /// Bitset in which all bits are set that are set in exactly one of this bitset and 'other'.
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        if self.length != other.length {
+            panic!(
+                "unable to xor bitsets with different lengths: {} and {}",
+                self.length, other.length
+            );
+        }
+        Self::from_words(
+            self.words
                 .iter()
-                .zip(other.bytes.iter())
-                .map(|(a, b)| a & b)
+                .zip(other.words.iter())
+                .map(|(a, b)| a ^ b)
+                .collect(),
+            self.length,
+        )
+    }
+
+    /// Flip all bits of this bitset that are set in 'other'.
+    pub fn symmetric_difference_with(&mut self, other: &Self) {
+        if self.length != other.length {
+            panic!(
+                "unable to xor bitsets with different lengths: {} and {}",
+                self.length, other.length
+            );
+        }
+        for (a, b) in self.words.iter_mut().zip(other.words.iter()) {
+            *a ^= b;
+        }
+        self.ones = count_ones(&self.words);
+    }
+
+    /// Bitset in which exactly the bits are set that are cleared in this bitset.
+    pub fn complement(&self) -> Self {
+        let mut words: Vec<u64> = self.words.iter().map(|word| !word).collect();
+        let trailing_bits = self.length % WORD_BITS;
+        if trailing_bits > 0 {
+            if let Some(last) = words.last_mut() {
+                *last &= (1u64 << trailing_bits) - 1;
+            }
+        }
+        Self::from_words(words, self.length)
+    }
+
+    /// The bits that are set in 'new' but not in 'old', encoded compactly, e.g., to send the
+    /// changes of a bitset to a replica of 'old' (see *BitsetDelta*).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths of 'old' and 'new' differ.
+    pub fn delta(old: &Self, new: &Self) -> BitsetDelta {
+        if old.length != new.length {
+            panic!(
+                "unable to compute the delta of bitsets with different lengths: {} and {}",
+                old.length, new.length
+            );
+        }
+        let mut encoder = DeltaEncoder::new(old.length);
+        for (word_index, (old_word, new_word)) in old.words.iter().zip(new.words.iter()).enumerate()
+        {
+            let bits = SetBits {
+                word: new_word & !old_word,
+                offset: word_index * WORD_BITS,
+            };
+            for index in bits {
+                encoder.push(index);
+            }
+        }
+        encoder.finish()
+    }
+
+    /// Set all bits that are set by 'delta', so that a replica of the older bitset of the delta
+    /// becomes the union of both versions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the bitset differs from the length of the bitsets of 'delta'.
+    pub fn apply_delta(&mut self, delta: &BitsetDelta) {
+        if self.length != delta.bitset_len() {
+            panic!(
+                "unable to apply a delta of a bitset of length {} to a bitset of length {}",
+                delta.bitset_len(),
+                self.length
+            );
+        }
+        for (start, length) in delta.runs() {
+            let end = start + length;
+            let mut index = start;
+            while index < end {
+                let bit = index % WORD_BITS;
+                let bits = (WORD_BITS - bit).min(end - index);
+                let mask = (u64::MAX >> (WORD_BITS - bits)) << bit;
+                let word = &mut self.words[index / WORD_BITS];
+                self.ones += (mask & !*word).count_ones() as usize;
+                *word |= mask;
+                index += bits;
+            }
+        }
+    }
+
+    /// Iterate over the indices of all set bits in ascending order.
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words
+            .iter()
+            .enumerate()
+            .flat_map(|(word_index, &word)| SetBits {
+                word,
+                offset: word_index * WORD_BITS,
+            })
+    }
+
+    /// Iterate over the indices of all cleared bits in ascending order.
+    pub fn iter_zeros(&self) -> impl Iterator<Item = usize> + '_ {
+        let length = self.length;
+        self.words
+            .iter()
+            .enumerate()
+            .flat_map(|(word_index, &word)| SetBits {
+                word: !word,
+                offset: word_index * WORD_BITS,
+            })
+            .take_while(move |&index| index < length)
+    }
+
+    /// Number of set bits in the range from *start* (inclusive) to *end* (exclusive).
+    fn count_ones_in(&self, start: usize, end: usize) -> usize {
+        let mut ones = 0;
+        let mut index = start;
+        while index < end {
+            let offset = index % WORD_BITS;
+            let bits = (WORD_BITS - offset).min(end - index);
+            let mask = if bits == WORD_BITS {
+                u64::MAX
+            } else {
+                ((1u64 << bits) - 1) << offset
+            };
+            ones += (self.words[index / WORD_BITS] & mask).count_ones() as usize;
+            index += bits;
+        }
+        ones
+    }
+
+    fn check_index(&self, index: usize) -> Result<(), BloomFilterError> {
+        if index >= self.length {
+            return Err(BloomFilterError::IndexOutOfBounds {
+                index,
+                len: self.length,
+            });
+        }
+        Ok(())
+    }
+
+    fn check_length(&self, other: &Self) -> Result<(), BloomFilterError> {
+        if self.length != other.length {
+            return Err(BloomFilterError::LengthMismatch {
+                left: self.length,
+                right: other.length,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over the indices of the set bits of a single word.
+struct SetBits {
+    word: u64,
+    offset: usize,
+}
+
+impl Iterator for SetBits {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.word == 0 {
+            return None;
+        }
+        let bit = self.word.trailing_zeros() as usize;
+        // Clear the lowest set bit
+        self.word &= self.word - 1;
+        Some(self.offset + bit)
+    }
+}
+
+impl Index<usize> for Bitset {
+    type Output = bool;
+
+    /// Whether bit *index* is set (see *get*).
+    fn index(&self, index: usize) -> &bool {
+        if self.get(index) {
+            &true
+        } else {
+            &false
+        }
+    }
+}
+
+impl BitOr for &Bitset {
+    type Output = Bitset;
+
+    fn bitor(self, other: Self) -> Bitset {
+        self.union(other)
+    }
+}
+
+impl BitAnd for &Bitset {
+    type Output = Bitset;
+
+    fn bitand(self, other: Self) -> Bitset {
+        self.intersect(other)
+    }
+}
+
+impl BitXor for &Bitset {
+    type Output = Bitset;
+
+    fn bitxor(self, other: Self) -> Bitset {
+        self.symmetric_difference(other)
+    }
+}
+
+impl Not for &Bitset {
+    type Output = Bitset;
+
+    fn not(self) -> Bitset {
+        self.complement()
+    }
+}
+
+impl BitOrAssign<&Bitset> for Bitset {
+    fn bitor_assign(&mut self, other: &Bitset) {
+        self.union_with(other);
+    }
+}
+
+impl BitAndAssign<&Bitset> for Bitset {
+    fn bitand_assign(&mut self, other: &Bitset) {
+        self.intersect_with(other);
+    }
+}
+
+impl BitXorAssign<&Bitset> for Bitset {
+    fn bitxor_assign(&mut self, other: &Bitset) {
+        self.symmetric_difference_with(other);
+    }
+}
+
+/// Bitset whose bits can be set by multiple threads at the same time.
+#[cfg(feature = "rayon")]
+pub struct AtomicBitset {
+    words: Vec<core::sync::atomic::AtomicU64>,
+    length: usize,
+}
+
+#[cfg(feature = "rayon")]
+impl AtomicBitset {
+    pub fn new(length: usize) -> Self {
+        Self {
+            words: (0..length.div_ceil(WORD_BITS))
+                .map(|_| core::sync::atomic::AtomicU64::new(0))
                 .collect(),
-            length: self.length,
+            length,
+        }
+    }
+
+    pub fn set(&self, index: usize) {
+        if index >= self.length {
+            panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                self.length, index,
+            )
         }
+        // Bits are only ever set, so the order of the updates does not matter
+        self.words[index / WORD_BITS].fetch_or(
+            1u64 << (index % WORD_BITS),
+            core::sync::atomic::Ordering::Relaxed,
+        );
     }
+
+    pub fn into_bitset(self) -> Bitset {
+        let words = self
+            .words
+            .into_iter()
+            .map(core::sync::atomic::AtomicU64::into_inner)
+            .collect();
+        Bitset::from_words(words, self.length)
+    }
+}
+
+/// Whether bit *index* is set in the bytes written by *Bitset::write_bytes*.
+pub(crate) fn bit_is_set(bytes: &[u8], index: usize) -> bool {
+    let byte_index = index / 8;
+    let mask = 0x01 << (index % 8);
+    bytes[byte_index] & mask == mask
 }
 
+/// Number of words that the Debug output of a bitset shows.
+const DEBUG_WORDS: usize = 4;
+
+/// Summary of the bitset: its length, the number of set bits, the fill ratio, and the first
+/// words in hex, so that bitsets of any size can be logged. Use *dump_bits* for all bits.
 impl Debug for Bitset {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let bits: Vec<bool> = (0..self.length).map(|i| self.get(i)).collect();
-        write!(f, "Bitset{{length: {}, data: {:?}}}", self.len(), bits)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fill_ratio = if self.is_empty() {
+            0.0
+        } else {
+            self.ones as f64 / self.length as f64
+        };
+        write!(
+            f,
+            "Bitset{{length: {}, ones: {}, fill_ratio: {:.4}, words: [",
+            self.length, self.ones, fill_ratio
+        )?;
+        for (i, word) in self.words.iter().take(DEBUG_WORDS).enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{:#018x}", word)?;
+        }
+        if self.words.len() > DEBUG_WORDS {
+            write!(f, ", ... {} more", self.words.len() - DEBUG_WORDS)?;
+        }
+        write!(f, "]}}")
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
@@ -116,17 +871,103 @@ mod tests {
         let bitset = Bitset::new(1);
         assert_eq!(1, bitset.length);
         assert_eq!(1, bitset.len());
-        assert_eq!(1, bitset.bytes.len());
+        assert_eq!(1, bitset.words.len());
 
-        let bitset = Bitset::new(8);
-        assert_eq!(8, bitset.length);
-        assert_eq!(8, bitset.len());
-        assert_eq!(1, bitset.bytes.len());
+        let bitset = Bitset::new(64);
+        assert_eq!(64, bitset.length);
+        assert_eq!(64, bitset.len());
+        assert_eq!(1, bitset.words.len());
 
-        let bitset = Bitset::new(9);
-        assert_eq!(9, bitset.length);
-        assert_eq!(9, bitset.len());
-        assert_eq!(2, bitset.bytes.len());
+        let bitset = Bitset::new(65);
+        assert_eq!(65, bitset.length);
+        assert_eq!(65, bitset.len());
+        assert_eq!(2, bitset.words.len());
+    }
+
+    #[test]
+    fn bitset_from_bytes() {
+        let mut bitset = Bitset::new(75);
+        bitset.set(0, true);
+        bitset.set(9, true);
+        bitset.set(74, true);
+
+        let bytes = bitset.to_bytes();
+        assert_eq!(vec![0x01, 0x02, 0, 0, 0, 0, 0, 0, 0, 0x04], bytes);
+        #[cfg(feature = "std")]
+        {
+            let mut written = Vec::new();
+            bitset.write_bytes(&mut written).unwrap();
+            assert_eq!(bytes, written);
+        }
+
+        let copy = Bitset::from_bytes(75, bytes);
+        assert_eq!(75, copy.len());
+        assert_eq!(true, copy.get(0));
+        assert_eq!(true, copy.get(9));
+        assert_eq!(true, copy.get(74));
+        assert_eq!(3, copy.count_ones());
+
+        // Bits exceeding the length are ignored
+        let bitset = Bitset::from_bytes(11, vec![0xFF, 0xFF]);
+        assert_eq!(11, bitset.count_ones());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn write_bytes_of_large_bitset() {
+        let mut bitset = Bitset::new(100_003);
+        for i in (0..bitset.len()).step_by(7) {
+            bitset.set(i, true);
+        }
+        let mut bytes = Vec::new();
+        bitset.write_bytes(&mut bytes).unwrap();
+        assert_eq!(100_003usize.div_ceil(8), bytes.len());
+
+        let copy = Bitset::from_bytes(100_003, bytes);
+        for i in 0..bitset.len() {
+            assert_eq!(i % 7 == 0, copy.get(i));
+        }
+    }
+
+    #[test]
+    fn fold_in_half_and_resize() {
+        for length in (2..300).step_by(2) {
+            let mut bitset = Bitset::new(length);
+            for index in (0..length).filter(|i| i % 7 == 0 || i % 11 == 3) {
+                bitset.set(index, true);
+            }
+            let half = length / 2;
+            let expected: Vec<usize> = (0..half)
+                .filter(|&i| bitset.get(i) || bitset.get(i + half))
+                .collect();
+
+            let mut folded = bitset.clone();
+            folded.fold_in_half();
+            assert_eq!(half, folded.len());
+            assert_eq!(expected, folded.iter_ones().collect::<Vec<_>>());
+            assert_eq!(expected.len(), folded.count_ones());
+
+            let mut resized = bitset.clone();
+            resized.resize(length * 2);
+            assert_eq!(bitset.count_ones(), resized.count_ones());
+            resized.resize(half);
+            let expected: Vec<usize> = bitset.iter_ones().filter(|&i| i < half).collect();
+            assert_eq!(expected, resized.iter_ones().collect::<Vec<_>>());
+            resized.resize(length);
+            assert_eq!(expected, resized.iter_ones().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "unable to fold a bitset")]
+    fn must_fold_even_length() {
+        Bitset::new(75).fold_in_half();
+    }
+
+    #[test]
+    #[should_panic(expected = "unable to create a bitset")]
+    fn must_create_from_correct_number_of_bytes() {
+        Bitset::from_bytes(9, vec![0]);
     }
 
     #[test]
@@ -160,6 +1001,80 @@ mod tests {
         Bitset::new(12).get(12);
     }
 
+    #[test]
+    fn density_histogram_of_unaligned_regions() {
+        let mut bitset = Bitset::new(301);
+        for index in (0..301).step_by(3) {
+            bitset.set(index, true);
+        }
+        let histogram = bitset.density_histogram(7);
+        assert_eq!(7, histogram.len());
+        // Regions of 43 bits, which start at multiples of 43
+        for (bucket, &ratio) in histogram.iter().enumerate() {
+            let start = bucket * 43;
+            let ones = (start..start + 43).filter(|index| index % 3 == 0).count();
+            assert_eq!(ones as f64 / 43.0, ratio);
+        }
+        assert_eq!(vec![101.0 / 301.0], bitset.density_histogram(1));
+        assert_eq!(301, bitset.density_histogram(301).len());
+    }
+
+    #[test]
+    #[should_panic(expected = "unable to split a bitset")]
+    fn density_histogram_needs_buckets() {
+        Bitset::new(10).density_histogram(0);
+    }
+
+    #[test]
+    fn checked_operations_return_errors() {
+        let mut bitset = Bitset::new(12);
+        assert_eq!(true, bitset.try_set(11, true).is_ok());
+        assert_eq!(Some(true), bitset.try_get(11).ok());
+        assert!(matches!(
+            bitset.try_set(12, true),
+            Err(BloomFilterError::IndexOutOfBounds { index: 12, len: 12 })
+        ));
+        assert!(matches!(
+            bitset.try_get(100),
+            Err(BloomFilterError::IndexOutOfBounds {
+                index: 100,
+                len: 12
+            })
+        ));
+
+        let other = Bitset::new(13);
+        assert!(matches!(
+            bitset.try_union(&other),
+            Err(BloomFilterError::LengthMismatch {
+                left: 12,
+                right: 13
+            })
+        ));
+        assert!(matches!(
+            bitset.try_intersect_with(&other),
+            Err(BloomFilterError::LengthMismatch {
+                left: 12,
+                right: 13
+            })
+        ));
+        // Failed operations leave the bitset unchanged
+        assert_eq!(1, bitset.count_ones());
+
+        let mut other = Bitset::new(12);
+        other.set(0, true);
+        assert_eq!(
+            Some(2),
+            bitset.try_union(&other).ok().map(|b| b.count_ones())
+        );
+        assert_eq!(
+            Some(0),
+            bitset.try_intersect(&other).ok().map(|b| b.count_ones())
+        );
+        assert_eq!(true, bitset.try_union_with(&other).is_ok());
+        assert_eq!(true, bitset.try_intersect_with(&other).is_ok());
+        assert_eq!(vec![0], bitset.iter_ones().collect::<Vec<_>>());
+    }
+
     #[test]
     fn set_and_unset_possible() {
         let mut bitset = Bitset::new(24);
@@ -329,6 +1244,7 @@ mod tests {
         assert_eq!(3, bitset_b.count_ones());
         assert_eq!(3, bitset_b.count_zeros());
 
+        assert_eq!(4, bitset_a.count_ones_union(&bitset_b));
         let bitset = bitset_a.union(&bitset_b);
         assert_eq!(4, bitset.count_ones());
         assert_eq!(2, bitset.count_zeros());
@@ -338,6 +1254,28 @@ mod tests {
         assert_eq!(true, bitset.get(5));
     }
 
+    #[test]
+    fn bitset_subset_and_intersection_ranges() {
+        let mut bitset_a = Bitset::new(200);
+        let mut bitset_b = Bitset::new(200);
+        bitset_a.set(3, true);
+        bitset_a.set(130, true);
+        bitset_b.set(3, true);
+        bitset_b.set(130, true);
+        bitset_b.set(199, true);
+
+        assert_eq!(true, bitset_a.is_subset(&bitset_b));
+        assert_eq!(false, bitset_b.is_subset(&bitset_a));
+        assert_eq!(true, bitset_a.is_subset(&bitset_a));
+
+        assert_eq!(true, bitset_a.intersects_in(&bitset_b, 0, 4));
+        assert_eq!(false, bitset_a.intersects_in(&bitset_b, 4, 130));
+        assert_eq!(true, bitset_a.intersects_in(&bitset_b, 60, 131));
+        assert_eq!(false, bitset_a.intersects_in(&bitset_b, 131, 200));
+        assert_eq!(false, bitset_a.intersects_in(&bitset_b, 5, 5));
+        assert_eq!(true, bitset_b.intersects_in(&bitset_b, 199, 200));
+    }
+
     #[test]
     fn bitset_intersect_test() {
         let mut bitset_a = Bitset::new(6);
@@ -381,4 +1319,81 @@ mod tests {
         assert_eq!(true, bitset.get(3));
         assert_eq!(false, bitset.get(5));
     }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn atomic_bitset() {
+        let bitset = AtomicBitset::new(130);
+        bitset.set(0);
+        bitset.set(64);
+        bitset.set(129);
+        bitset.set(64);
+        let bitset = bitset.into_bitset();
+        assert_eq!(130, bitset.len());
+        assert_eq!(3, bitset.count_ones());
+        assert_eq!(true, bitset.get(64));
+        assert_eq!(true, bitset.get(129));
+    }
+
+    #[test]
+    fn operators() {
+        let mut bitset_a = Bitset::new(70);
+        let mut bitset_b = Bitset::new(70);
+        bitset_a.set(1, true);
+        bitset_a.set(65, true);
+        bitset_b.set(65, true);
+        bitset_b.set(69, true);
+
+        assert_eq!(
+            vec![1, 65, 69],
+            (&bitset_a | &bitset_b).iter_ones().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![65],
+            (&bitset_a & &bitset_b).iter_ones().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![1, 69],
+            (&bitset_a ^ &bitset_b).iter_ones().collect::<Vec<_>>()
+        );
+
+        let complement = !&bitset_a;
+        assert_eq!(68, complement.count_ones());
+        assert_eq!(false, complement[1]);
+        assert_eq!(true, complement[69]);
+        assert_eq!(
+            complement.iter_ones().collect::<Vec<_>>(),
+            bitset_a.iter_zeros().collect::<Vec<_>>()
+        );
+
+        let mut bitset = bitset_a.clone();
+        bitset ^= &bitset_b;
+        assert_eq!(2, bitset.count_ones());
+        bitset |= &bitset_b;
+        assert_eq!(3, bitset.count_ones());
+        bitset &= &bitset_a;
+        assert_eq!(bitset_a, bitset);
+        assert_eq!(true, bitset[65]);
+    }
+
+    #[test]
+    fn bitset_debug_is_summarized() {
+        let mut bitset = Bitset::new(1_000_000);
+        bitset.set(0, true);
+        bitset.set(65, true);
+        assert_eq!(
+            "Bitset{length: 1000000, ones: 2, fill_ratio: 0.0000, words: [0x0000000000000001, \
+             0x0000000000000002, 0x0000000000000000, 0x0000000000000000, ... 15621 more]}",
+            format!("{:?}", bitset)
+        );
+        assert_eq!(
+            "Bitset{length: 0, ones: 0, fill_ratio: 0.0000, words: []}",
+            format!("{:?}", Bitset::new(0))
+        );
+
+        let mut bitset = Bitset::new(3);
+        bitset.set(2, true);
+        assert_eq!("001", bitset.dump_bits());
+        assert_eq!("", Bitset::new(0).dump_bits());
+    }
 }
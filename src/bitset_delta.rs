@@ -0,0 +1,321 @@
+use alloc::vec::Vec;
+use core::{
+    convert::TryFrom,
+    fmt::{self, Debug, Display},
+};
+
+/// Number of bytes of the header of an encoded delta: the length of the bitset (u64).
+const HEADER_LENGTH: usize = 8;
+
+/// Maximum number of bytes of a LEB128 encoded u64.
+const MAX_VARINT_LENGTH: usize = 10;
+
+/// Error returned by *BitsetDelta::from_bytes* if the bytes are not a valid delta.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidBitsetDelta;
+
+impl Display for InvalidBitsetDelta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid bitset delta")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidBitsetDelta {}
+
+/// Compact encoding of the bits that have been set between two versions of a bitset, e.g., to
+/// send the changes of a filter to its replicas instead of the whole filter.
+///
+/// A delta is created with *Bitset::delta* or *KMBloomFilter::delta* and applied with
+/// *apply_delta*. Only bits that are set in the newer version but not in the older one are
+/// recorded. Bits that have been cleared in the meantime are not, so applying a delta to the older
+/// version results in the union of both versions.
+///
+/// Deltas can be sent as bytes (see *as_bytes* and *from_bytes*). The encoding is:
+/// * the length of the bitset as u64 in little-endian byte order,
+/// * for each run of consecutive set bits, the number of bits between the end of the previous run
+///   and the start of the run, followed by the number of bits of the run minus one, both as
+///   unsigned LEB128.
+///
+/// The new bits of a bloom filter are spread evenly, so most runs consist of a single bit, which
+/// needs one byte for its length and one byte per 7 bits of its distance to the previous run,
+/// e.g., 3 bytes per new bit if one in 10,000 bits is new.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{Bitset,BitsetDelta};
+///
+/// let old = Bitset::new(1_000);
+/// let mut new = old.clone();
+/// new.set(10, true);
+/// new.set(11, true);
+/// new.set(500, true);
+///
+/// let delta = Bitset::delta(&old, &new);
+/// assert_eq!(3, delta.count_ones());
+/// assert_eq!(vec![10, 11, 500], delta.iter_ones().collect::<Vec<_>>());
+///
+/// // Send the delta to a replica of the old bitset
+/// let delta = BitsetDelta::from_bytes(delta.as_bytes().to_vec()).unwrap();
+/// let mut replica = Bitset::new(1_000);
+/// replica.apply_delta(&delta);
+/// assert_eq!(new, replica);
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct BitsetDelta {
+    bytes: Vec<u8>,
+    length: usize,
+    ones: usize,
+}
+
+impl BitsetDelta {
+    /// Restore a delta from the bytes returned by *as_bytes*.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if *bytes* are not a valid encoding, e.g., because they are truncated or
+    /// a run exceeds the length of the bitset.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, InvalidBitsetDelta> {
+        if bytes.len() < HEADER_LENGTH {
+            return Err(InvalidBitsetDelta);
+        }
+        let mut length = [0u8; 8];
+        length.copy_from_slice(&bytes[..HEADER_LENGTH]);
+        let length = usize::try_from(u64::from_le_bytes(length)).map_err(|_| InvalidBitsetDelta)?;
+
+        // Validate all runs once, so that applying the delta cannot fail
+        let mut position = HEADER_LENGTH;
+        let mut end = 0usize;
+        let mut ones = 0;
+        while position < bytes.len() {
+            let gap = read_varint(&bytes, &mut position).ok_or(InvalidBitsetDelta)?;
+            let run_length = read_varint(&bytes, &mut position).ok_or(InvalidBitsetDelta)?;
+            let run_length = usize::try_from(run_length)
+                .ok()
+                .and_then(|run_length| run_length.checked_add(1))
+                .ok_or(InvalidBitsetDelta)?;
+            end = usize::try_from(gap)
+                .ok()
+                .and_then(|gap| end.checked_add(gap))
+                .and_then(|start| start.checked_add(run_length))
+                .filter(|&end| end <= length)
+                .ok_or(InvalidBitsetDelta)?;
+            ones += run_length;
+        }
+        Ok(Self {
+            bytes,
+            length,
+            ones,
+        })
+    }
+
+    /// The encoded delta, including its header.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Length of the bitsets the delta has been created from and can be applied to.
+    pub fn bitset_len(&self) -> usize {
+        self.length
+    }
+
+    /// Number of bits that are set by the delta.
+    pub fn count_ones(&self) -> usize {
+        self.ones
+    }
+
+    /// Iterate over the indices of all bits that are set by the delta in ascending order.
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.runs()
+            .flat_map(|(start, length)| start..start + length)
+    }
+
+    /// Iterate over the runs of consecutive set bits as pairs of the index of their first bit and
+    /// their number of bits.
+    pub(crate) fn runs(&self) -> Runs<'_> {
+        Runs {
+            bytes: &self.bytes,
+            position: HEADER_LENGTH,
+            end: 0,
+        }
+    }
+}
+
+impl Debug for BitsetDelta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "BitsetDelta{{bitset_len: {}, ones: {}, bytes: {}}}",
+            self.length,
+            self.ones,
+            self.bytes.len()
+        )
+    }
+}
+
+/// Builds a delta from the indices of the set bits in ascending order.
+pub(crate) struct DeltaEncoder {
+    bytes: Vec<u8>,
+    length: usize,
+    ones: usize,
+    // End of the last run that has been written
+    end: usize,
+    run_start: usize,
+    run_length: usize,
+}
+
+impl DeltaEncoder {
+    pub fn new(length: usize) -> Self {
+        let mut bytes = Vec::with_capacity(HEADER_LENGTH);
+        bytes.extend_from_slice(&(length as u64).to_le_bytes());
+        Self {
+            bytes,
+            length,
+            ones: 0,
+            end: 0,
+            run_start: 0,
+            run_length: 0,
+        }
+    }
+
+    /// Add bit *index*, which must be greater than all bits that have been added before.
+    pub fn push(&mut self, index: usize) {
+        if self.run_length > 0 && index == self.run_start + self.run_length {
+            self.run_length += 1;
+        } else {
+            self.write_run();
+            self.run_start = index;
+            self.run_length = 1;
+        }
+        self.ones += 1;
+    }
+
+    pub fn finish(mut self) -> BitsetDelta {
+        self.write_run();
+        BitsetDelta {
+            bytes: self.bytes,
+            length: self.length,
+            ones: self.ones,
+        }
+    }
+
+    fn write_run(&mut self) {
+        if self.run_length == 0 {
+            return;
+        }
+        write_varint(&mut self.bytes, (self.run_start - self.end) as u64);
+        write_varint(&mut self.bytes, (self.run_length - 1) as u64);
+        self.end = self.run_start + self.run_length;
+    }
+}
+
+/// Iterator over the runs of a delta that has been validated.
+pub(crate) struct Runs<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    end: usize,
+}
+
+impl Iterator for Runs<'_> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        if self.position >= self.bytes.len() {
+            return None;
+        }
+        let gap = read_varint(self.bytes, &mut self.position)? as usize;
+        let run_length = read_varint(self.bytes, &mut self.position)? as usize + 1;
+        let start = self.end + gap;
+        self.end = start + run_length;
+        Some((start, run_length))
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Read the varint at *position* and move *position* behind it, or return None if the bytes end
+/// before the varint or it exceeds 64 bits.
+fn read_varint(bytes: &[u8], position: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    for i in 0..MAX_VARINT_LENGTH {
+        let byte = *bytes.get(*position)?;
+        *position += 1;
+        let bits = (byte & 0x7f) as u64;
+        if i == MAX_VARINT_LENGTH - 1 && bits > 1 {
+            return None;
+        }
+        value |= bits << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(length: usize, ones: &[usize]) -> BitsetDelta {
+        let mut encoder = DeltaEncoder::new(length);
+        for &index in ones {
+            encoder.push(index);
+        }
+        encoder.finish()
+    }
+
+    #[test]
+    fn runs_roundtrip() {
+        let ones = [0, 1, 2, 100, 200, 201, 1_000_000, 9_999_999];
+        let delta = delta(10_000_000, &ones);
+        assert_eq!(
+            vec![(0, 3), (100, 1), (200, 2), (1_000_000, 1), (9_999_999, 1)],
+            delta.runs().collect::<Vec<_>>()
+        );
+
+        let decoded = BitsetDelta::from_bytes(delta.as_bytes().to_vec()).unwrap();
+        assert_eq!(delta, decoded);
+        assert_eq!(ones.len(), decoded.count_ones());
+        assert_eq!(ones.to_vec(), decoded.iter_ones().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn empty_delta() {
+        let delta = delta(100, &[]);
+        assert_eq!(HEADER_LENGTH, delta.as_bytes().len());
+        assert_eq!(
+            delta,
+            BitsetDelta::from_bytes(delta.as_bytes().to_vec()).unwrap()
+        );
+        assert_eq!(0, delta.iter_ones().count());
+    }
+
+    #[test]
+    fn invalid_bytes() {
+        let bytes = delta(100, &[10, 99]).as_bytes().to_vec();
+        // Truncated header and truncated run
+        assert_eq!(
+            Err(InvalidBitsetDelta),
+            BitsetDelta::from_bytes(bytes[..4].to_vec())
+        );
+        assert_eq!(
+            Err(InvalidBitsetDelta),
+            BitsetDelta::from_bytes(bytes[..bytes.len() - 1].to_vec())
+        );
+        // Run beyond the length of the bitset
+        let mut bytes = delta(100, &[10]).as_bytes().to_vec();
+        bytes.extend_from_slice(&[89, 0]);
+        assert_eq!(Err(InvalidBitsetDelta), BitsetDelta::from_bytes(bytes));
+        // Varint with more than 64 bits
+        let mut bytes = delta(100, &[]).as_bytes().to_vec();
+        bytes.extend_from_slice(&[0xff; 10]);
+        bytes.extend_from_slice(&[0x01, 0]);
+        assert_eq!(Err(InvalidBitsetDelta), BitsetDelta::from_bytes(bytes));
+    }
+}
@@ -0,0 +1,259 @@
+use core::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use crate::{
+    approximate_element_count, approximate_false_positive_probability, bitset::bit_is_set, params,
+    HashedKey, KMBloomFilter,
+};
+#[cfg(feature = "std")]
+use crate::{
+    file_format::{check_length, read_header, verify_checksum, HEADER_LENGTH},
+    ReadFilterError,
+};
+
+/// Read-only KMBloomFilter that answers queries directly from borrowed bytes, without owning or
+/// copying its bits.
+///
+/// A view makes it possible to query a filter in place, e.g., a filter that is embedded in a
+/// larger index or archive file that has been read or memory-mapped as a whole. The bytes are
+/// either the bits of a filter as written by *Bitset::write_bytes* together with the number of
+/// hashers and bits per hasher (see *new*), or a whole filter as written by
+/// *KMBloomFilter::write_to* (see *read_from*).
+///
+/// The hashers *H1* and *H2* must be the same hashers that were used to create the filter, and
+/// their hash values must not change between processes (e.g., the hashers of
+/// StableBloomFilterDefault).
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{
+///     BloomFilter,BloomFilterView,StableBloomFilterDefault,StableXxh3Hasher,StableXxh64Hasher
+/// };
+///
+/// # #[cfg(not(feature = "std"))]
+/// # fn main() {}
+/// # #[cfg(feature = "std")]
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+///     filter.insert(&"Hello!");
+///
+///     // An archive that contains the filter after some other data
+///     let mut archive = b"other data".to_vec();
+///     filter.write_to(&mut archive)?;
+///
+///     let view: BloomFilterView<StableXxh3Hasher, StableXxh64Hasher> =
+///         BloomFilterView::read_from(&archive[10..])?;
+///     assert_eq!(true, view.contains(&"Hello!"));
+///     assert_eq!(false, view.contains(&"Goodbye!"));
+///     Ok(())
+/// }
+/// ```
+pub struct BloomFilterView<'a, H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    number_of_hashers: usize,
+    bits_per_hasher: usize,
+    bytes: &'a [u8],
+    // Phantom data for saving which concrete Hasher types are used
+    _phantom: PhantomData<fn() -> (H1, H2)>,
+}
+
+impl<'a, H1, H2> BloomFilterView<'a, H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Initialize a new instance of BloomFilterView over the bits *bytes* of a filter with
+    /// *number_of_hashers* hashers and *bits_per_hasher* bits per hasher.
+    ///
+    /// Bit *i* of the filter is bit *i % 8* of byte *i / 8*, counting from the least significant
+    /// bit, as written by *Bitset::write_bytes*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if number_of_hashers == 0, bits_per_hasher == 0, if the number of bits does not fit
+    /// into usize, or if the number of bytes does not match the number of bits.
+    pub fn new(bytes: &'a [u8], number_of_hashers: usize, bits_per_hasher: usize) -> Self {
+        if number_of_hashers == 0 || bits_per_hasher == 0 {
+            panic!("an empty bloom filter is not defined");
+        }
+        let bit_count = params::checked_bit_count(number_of_hashers, bits_per_hasher);
+        if bytes.len() != bit_count.div_ceil(8) {
+            panic!(
+                "unable to view a bloom filter of {} bits in {} bytes",
+                bit_count,
+                bytes.len()
+            );
+        }
+        Self {
+            number_of_hashers,
+            bits_per_hasher,
+            bytes,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Initialize a new instance of BloomFilterView over a filter written by
+    /// *KMBloomFilter::write_to*.
+    ///
+    /// *bytes* must contain exactly the written filter. Reading the view verifies the checksum, so
+    /// it reads all bytes once.
+    ///
+    /// Only available with the *std* feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if *bytes* do not contain a filter in the format of
+    /// *KMBloomFilter::write_to*, if the filter has been written with different hashers, or if its
+    /// checksum does not match.
+    #[cfg(feature = "std")]
+    pub fn read_from(bytes: &'a [u8]) -> Result<Self, ReadFilterError> {
+        let (number_of_hashers, bits_per_hasher) = read_header::<H1, H2, _>(&mut &bytes[..])?;
+        let byte_count = check_length(number_of_hashers, bits_per_hasher, bytes.len())?;
+        let (bits, checksum) = bytes[HEADER_LENGTH..].split_at(byte_count);
+        verify_checksum(bits, checksum)?;
+        Ok(Self::new(bits, number_of_hashers, bits_per_hasher))
+    }
+
+    /// Check whether data is contained in the bloom filter.
+    ///
+    /// See *BloomFilter::contains* for details.
+    pub fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        let (hash_a, hash_b) = KMBloomFilter::<H1, H2>::generate_hashes(data);
+        self.contains_hashed(&HashedKey::from_hashes(hash_a, hash_b))
+    }
+
+    /// Check whether the element *key* has been computed for is contained in the bloom filter.
+    ///
+    /// See *KMBloomFilter::hash* for how to compute a key.
+    pub fn contains_hashed(&self, key: &HashedKey<H1, H2>) -> bool {
+        let (hash_a, hash_b) = key.hashes();
+        (0..self.number_of_hashers).all(|i| {
+            let index = KMBloomFilter::<H1, H2>::index(i, self.bits_per_hasher, hash_a, hash_b);
+            bit_is_set(self.bytes, index)
+        })
+    }
+
+    /// Check whether the raw bytes *bytes* are contained in the bloom filter.
+    ///
+    /// See *KMBloomFilter::insert_bytes* for details.
+    pub fn contains_bytes(&self, bytes: &[u8]) -> bool {
+        self.contains_hashed(&HashedKey::from_bytes(bytes))
+    }
+
+    /// Check whether *text* is contained in the bloom filter.
+    ///
+    /// See *KMBloomFilter::insert_str* for details.
+    pub fn contains_str(&self, text: &str) -> bool {
+        self.contains_bytes(text.as_bytes())
+    }
+
+    /// Check whether an element with the base hashes *hash_a* and *hash_b* is contained in the
+    /// bloom filter.
+    ///
+    /// See *KMBloomFilter::insert_hash64* for details.
+    pub fn contains_hash64(&self, hash_a: u64, hash_b: u64) -> bool {
+        self.contains_hashed(&HashedKey::from_hashes(hash_a, hash_b))
+    }
+
+    /// Approximate number of elements stored.
+    ///
+    /// In contrast to *contains*, this reads all bytes.
+    pub fn approximate_element_count(&self) -> f64 {
+        approximate_element_count(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.count_ones(),
+        )
+    }
+
+    /// Return the current approximate false positive probability which depends on the current
+    /// number of elements in the filter.
+    ///
+    /// In contrast to *contains*, this reads all bytes.
+    pub fn approximate_current_false_positive_probability(&self) -> f64 {
+        approximate_false_positive_probability(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.approximate_element_count(),
+        )
+    }
+
+    /// Fraction of bits that are set, given as a value in the interval [0,1].
+    ///
+    /// In contrast to *contains*, this reads all bytes.
+    pub fn fill_ratio(&self) -> f64 {
+        self.count_ones() as f64 / self.bit_count() as f64
+    }
+
+    /// Total number of bits of the filter.
+    pub fn bit_count(&self) -> usize {
+        self.number_of_hashers * self.bits_per_hasher
+    }
+
+    /// Number of hash functions used for every element.
+    pub fn hasher_count(&self) -> usize {
+        self.number_of_hashers
+    }
+
+    /// Whether the fill ratio has reached *threshold*.
+    ///
+    /// In contrast to *contains*, this reads all bytes.
+    pub fn is_saturated(&self, threshold: f64) -> bool {
+        self.fill_ratio() >= threshold
+    }
+
+    /// The bits of the filter, which are borrowed from the bytes the view has been created for.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Number of hashers and number of bits per hasher.
+    #[cfg(feature = "mmap")]
+    pub(crate) fn configuration(&self) -> (usize, usize) {
+        (self.number_of_hashers, self.bits_per_hasher)
+    }
+
+    fn count_ones(&self) -> usize {
+        self.bytes.iter().map(|b| b.count_ones() as usize).sum()
+    }
+}
+
+impl<H1, H2> Clone for BloomFilterView<'_, H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<H1, H2> Copy for BloomFilterView<'_, H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+}
+
+impl<H1, H2> Debug for BloomFilterView<'_, H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "BloomFilterView{{number_of_hashers: {}, bits_per_hasher: {}}}",
+            self.number_of_hashers, self.bits_per_hasher
+        )
+    }
+}
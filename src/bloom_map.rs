@@ -0,0 +1,247 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use crate::{
+    math, packed_array::PackedArray, params::checked_false_positive_probability,
+    xor_filter::XorLayout,
+};
+
+/// Immutable approximate map from keys to a small set of values, built like an xor filter
+/// (Bloomier filter), e.g., to find the shard that most likely owns a key without storing the
+/// keys.
+///
+/// Every key is mapped to three slots of the map like in XorFilter, and the slots are assigned so
+/// that their XOR is the fingerprint of the key followed by the index of its value in the table of
+/// distinct values. *get* returns the value of every key the map has been built from. For other
+/// keys, it returns none unless the fingerprints match by chance, which happens with at most the
+/// false positive probability the map has been built with; in this case, an arbitrary value is
+/// returned.
+///
+/// Every slot needs *fingerprint_bits* bits for the fingerprint and log2 of the number of distinct
+/// values bits for the value index, and the map needs about 1.23 slots per key. Since slots are
+/// at most 32 bits wide, the map is meant for small sets of values, such as shard ids.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomMap,StableXxh3Hasher};
+///
+/// fn main() {
+///     let owners = (0..10_000u32).map(|user| (format!("user-{}", user), user % 8));
+///     let shards: BloomMap<u32, StableXxh3Hasher> = BloomMap::from_pairs(owners, 0.001);
+///
+///     assert_eq!(Some(&2), shards.get(&"user-42"));
+///     assert_eq!(Some(&7), shards.get(&"user-9999"));
+///     // Unknown keys most likely have no shard
+///     assert_eq!(None, shards.get(&"user-10000"));
+/// }
+/// ```
+pub struct BloomMap<V, H>
+where
+    H: Hasher + Default,
+{
+    slots: PackedArray,
+    layout: XorLayout,
+    values: Vec<V>,
+    fingerprint_bits: u32,
+    value_bits: u32,
+    length: usize,
+    // Phantom data for saving which concrete Hasher type is used
+    _phantom: PhantomData<fn() -> H>,
+}
+
+impl<V, H> BloomMap<V, H>
+where
+    H: Hasher + Default,
+{
+    /// Build a map from the keys and values of *pairs*, which returns none with a probability of at
+    /// least 1 - *false_positive_probability* for keys that are not contained.
+    ///
+    /// If a key occurs multiple times, the last value is kept. Construction hashes the keys with
+    /// *H* and collects the hashes first, so it temporarily needs memory for all hashes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if false_positive_probability is not in the interval (0,1) or if the fingerprints and
+    /// value indices need more than 32 bits per slot.
+    pub fn from_pairs<K, I>(pairs: I, false_positive_probability: impl Into<f64>) -> Self
+    where
+        K: Hash,
+        I: IntoIterator<Item = (K, V)>,
+        V: Ord,
+    {
+        Self::from_hashes(
+            pairs.into_iter().map(|(key, value)| {
+                let mut hasher = H::default();
+                key.hash(&mut hasher);
+                (hasher.finish(), value)
+            }),
+            false_positive_probability,
+        )
+    }
+
+    /// Build a map from the keys whose hashes, as returned by *H*, are given by *pairs* together
+    /// with their values, e.g., because the hashes have been computed elsewhere.
+    ///
+    /// If a hash occurs multiple times, the last value is kept.
+    ///
+    /// # Panics
+    ///
+    /// Panics if false_positive_probability is not in the interval (0,1) or if the fingerprints and
+    /// value indices need more than 32 bits per slot.
+    pub fn from_hashes<I>(pairs: I, false_positive_probability: impl Into<f64>) -> Self
+    where
+        I: IntoIterator<Item = (u64, V)>,
+        V: Ord,
+    {
+        let false_positive_probability =
+            checked_false_positive_probability(false_positive_probability);
+        let fingerprint_bits =
+            (math::ceil(-math::ln(false_positive_probability) / core::f64::consts::LN_2) as u32)
+                .max(1);
+
+        // Keep the last value of duplicate hashes: the stable sort keeps the reversed order of
+        // equal hashes, and dedup keeps the first of them
+        let mut pairs: Vec<(u64, V)> = pairs.into_iter().collect();
+        pairs.reverse();
+        pairs.sort_by_key(|&(hash, _)| hash);
+        pairs.dedup_by_key(|&mut (hash, _)| hash);
+
+        // Table of distinct values, and the index of the value of every hash
+        pairs.sort_by(|(_, a), (_, b)| a.cmp(b));
+        let mut values: Vec<V> = Vec::new();
+        let mut hashes = Vec::with_capacity(pairs.len());
+        let mut value_indices = Vec::with_capacity(pairs.len());
+        for (hash, value) in pairs {
+            if values.last() != Some(&value) {
+                values.push(value);
+            }
+            hashes.push(hash);
+            value_indices.push(values.len() as u32 - 1);
+        }
+        let value_bits = match values.len() {
+            0 | 1 => 0,
+            count => u32::BITS - (count as u32 - 1).leading_zeros(),
+        };
+        if fingerprint_bits + value_bits > 32 {
+            panic!(
+                "unable to build a bloom map with {} fingerprint bits and {} distinct values",
+                fingerprint_bits,
+                values.len()
+            );
+        }
+
+        let (layout, stack) = XorLayout::build(&hashes);
+        // Mixing is a bijection, so the mixed hashes identify the keys as well
+        let value_indices: BTreeMap<u64, u32> = hashes
+            .iter()
+            .map(|&hash| layout.mix(hash))
+            .zip(value_indices)
+            .collect();
+        let mut map = Self {
+            slots: PackedArray::new(layout.slot_count(), fingerprint_bits + value_bits),
+            layout,
+            values,
+            fingerprint_bits,
+            value_bits,
+            length: hashes.len(),
+            _phantom: PhantomData,
+        };
+
+        // Assign the slots in reverse peeling order like XorFilter
+        for &(hash, slot) in stack.iter().rev() {
+            let [a, b, c] = map.layout.slots(hash);
+            let entry = (map.fingerprint(hash) << value_bits) | value_indices[&hash];
+            map.slots.set(
+                slot,
+                entry ^ map.slots.get(a) ^ map.slots.get(b) ^ map.slots.get(c),
+            );
+        }
+        map
+    }
+
+    /// Return the value of *key*, or none if the map does not contain it.
+    ///
+    /// For keys that are not contained, the result is an arbitrary value with at most the false
+    /// positive probability of the map.
+    pub fn get<K>(&self, key: &K) -> Option<&V>
+    where
+        K: Hash + ?Sized,
+    {
+        let mut hasher = H::default();
+        key.hash(&mut hasher);
+        self.get_hash64(hasher.finish())
+    }
+
+    /// Return the value of the key for which *H* returns *hash*, or none if the map does not
+    /// contain it.
+    pub fn get_hash64(&self, hash: u64) -> Option<&V> {
+        if self.length == 0 {
+            return None;
+        }
+        let hash = self.layout.mix(hash);
+        let [a, b, c] = self.layout.slots(hash);
+        let entry = self.slots.get(a) ^ self.slots.get(b) ^ self.slots.get(c);
+        if entry >> self.value_bits != self.fingerprint(hash) {
+            return None;
+        }
+        // Index bits that do not belong to a value can only be a false positive
+        self.values
+            .get((entry & ((1u64 << self.value_bits) - 1) as u32) as usize)
+    }
+
+    /// Number of distinct keys in the map.
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Whether the map has been built without keys.
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// The distinct values of the map in ascending order.
+    pub fn values(&self) -> &[V] {
+        &self.values
+    }
+
+    /// Number of bits of the fingerprint of every key.
+    pub fn fingerprint_bits(&self) -> u32 {
+        self.fingerprint_bits
+    }
+
+    /// Number of bytes of the slots, without the table of values.
+    pub fn byte_count(&self) -> usize {
+        (self.slots.len() * self.slots.bits() as usize).div_ceil(8)
+    }
+
+    /// Return the approximate probability that *get* returns a value for a key that is not
+    /// contained.
+    ///
+    /// The probability is given as a value in the interval [0,1]
+    pub fn approximate_false_positive_probability(&self) -> f64 {
+        math::powf(2.0, -(self.fingerprint_bits as f64))
+    }
+
+    fn fingerprint(&self, hash: u64) -> u32 {
+        ((hash ^ (hash >> 32)) & ((1u64 << self.fingerprint_bits) - 1)) as u32
+    }
+}
+
+impl<V, H> Debug for BloomMap<V, H>
+where
+    H: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "BloomMap{{length: {}, values: {}, bits_per_slot: {}}}",
+            self.length,
+            self.values.len(),
+            self.slots.bits()
+        )
+    }
+}
@@ -0,0 +1,204 @@
+use core::{
+    fmt::{self, Debug},
+    hash::{BuildHasher, Hash},
+};
+
+use crate::{
+    approximate_element_count, approximate_false_positive_probability, bitset::Bitset,
+    km_bloom_filter::bit_index, params::FilterParams, BloomFilter,
+};
+
+/// Bloom filter that hashes with two *BuildHasher*s instead of two *Hasher* types, e.g., to reuse
+/// the *std::collections::hash_map::RandomState* or *ahash::RandomState* of an application without
+/// writing hasher types that implement *Default*.
+///
+/// The filter simulates its hash functions like KMBloomFilter: the build hashers compute the base
+/// hashes of an element, which are combined into one bit per hash function. The build hashers are
+/// stored in the filter, so build hashers with keys, such as RandomState, hash every element the
+/// same way for the lifetime of the filter. With *BuildHasherDefault<H1>* and
+/// *BuildHasherDefault<H2>*, the bits are the same as those of a *KMBloomFilter<H1, H2>* with the
+/// same configuration.
+///
+/// ***You have to use two build hashers whose hash values are independent!*** Two RandomStates
+/// created with *RandomState::new* have different keys.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,BuildHasherBloomFilter};
+/// use std::collections::hash_map::RandomState;
+///
+/// fn main() {
+///     let mut filter =
+///         BuildHasherBloomFilter::with_build_hashers(1_000, 0.001, RandomState::new(), RandomState::new());
+///     filter.insert(&"Hello!");
+///
+///     assert_eq!(true, filter.contains(&"Hello!"));
+///     assert_eq!(false, filter.contains(&"Goodbye!"));
+/// }
+/// ```
+pub struct BuildHasherBloomFilter<S1, S2>
+where
+    S1: BuildHasher,
+    S2: BuildHasher,
+{
+    number_of_hashers: usize,
+    bitset: Bitset,
+    bits_per_hasher: usize,
+    build_hasher_a: S1,
+    build_hasher_b: S2,
+}
+
+impl<S1, S2> BuildHasherBloomFilter<S1, S2>
+where
+    S1: BuildHasher,
+    S2: BuildHasher,
+{
+    /// Initialize a new instance of BuildHasherBloomFilter with the configuration of
+    /// *KMBloomFilter::new* that hashes with *build_hasher_a* and *build_hasher_b*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0 or if desired_false_positive_probability is not in the
+    /// interval (0,1).
+    pub fn with_build_hashers(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+        build_hasher_a: S1,
+        build_hasher_b: S2,
+    ) -> Self {
+        let params =
+            FilterParams::for_capacity(desired_capacity, desired_false_positive_probability);
+        Self {
+            number_of_hashers: params.number_of_hashers(),
+            bitset: Bitset::new(params.bit_count()),
+            bits_per_hasher: params.bits_per_hasher(),
+            build_hasher_a,
+            build_hasher_b,
+        }
+    }
+
+    /// Initialize a new instance of BuildHasherBloomFilter with the configuration of
+    /// *KMBloomFilter::new* and the default build hashers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0 or if desired_false_positive_probability is not in the
+    /// interval (0,1).
+    pub fn new(desired_capacity: usize, desired_false_positive_probability: impl Into<f64>) -> Self
+    where
+        S1: Default,
+        S2: Default,
+    {
+        Self::with_build_hashers(
+            desired_capacity,
+            desired_false_positive_probability,
+            S1::default(),
+            S2::default(),
+        )
+    }
+
+    /// The build hashers of the filter.
+    pub fn build_hashers(&self) -> (&S1, &S2) {
+        (&self.build_hasher_a, &self.build_hasher_b)
+    }
+
+    /// The bits of the filter, laid out like the bits of KMBloomFilter.
+    pub fn bitset(&self) -> &Bitset {
+        &self.bitset
+    }
+
+    /// Total number of bits of the filter.
+    pub fn bit_count(&self) -> usize {
+        self.bitset.len()
+    }
+
+    /// Number of hash functions used for every element.
+    pub fn hasher_count(&self) -> usize {
+        self.number_of_hashers
+    }
+
+    /// Approximate number of elements stored (see *KMBloomFilter::approximate_element_count*).
+    pub fn approximate_element_count(&self) -> f64 {
+        approximate_element_count(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.bitset.count_ones(),
+        )
+    }
+
+    /// Return the current approximate false positive probability which depends on the current
+    /// number of elements in the filter.
+    ///
+    /// The probability is given as a value in the interval [0,1]
+    pub fn approximate_current_false_positive_probability(&self) -> f64 {
+        approximate_false_positive_probability(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.approximate_element_count(),
+        )
+    }
+
+    /// Remove all elements, keeping the build hashers.
+    pub fn clear(&mut self) {
+        self.bitset.clear();
+    }
+
+    fn hashes<T>(&self, data: &T) -> (u64, u64)
+    where
+        T: Hash,
+    {
+        (
+            self.build_hasher_a.hash_one(data),
+            self.build_hasher_b.hash_one(data),
+        )
+    }
+}
+
+impl<S1, S2> Debug for BuildHasherBloomFilter<S1, S2>
+where
+    S1: BuildHasher,
+    S2: BuildHasher,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BuildHasherBloomFilter{{{:?}}}", self.bitset)
+    }
+}
+
+impl<S1, S2> BloomFilter for BuildHasherBloomFilter<S1, S2>
+where
+    S1: BuildHasher,
+    S2: BuildHasher,
+{
+    fn insert<T>(&mut self, data: &T)
+    where
+        T: Hash,
+    {
+        let (hash_a, hash_b) = self.hashes(data);
+        for i in 0..self.number_of_hashers {
+            self.bitset
+                .set(bit_index(i, self.bits_per_hasher, hash_a, hash_b), true);
+        }
+    }
+
+    fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        let (hash_a, hash_b) = self.hashes(data);
+        (0..self.number_of_hashers).all(|i| {
+            self.bitset
+                .get(bit_index(i, self.bits_per_hasher, hash_a, hash_b))
+        })
+    }
+}
+
+impl<T, S1, S2> Extend<T> for BuildHasherBloomFilter<S1, S2>
+where
+    T: Hash,
+    S1: BuildHasher,
+    S2: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.insert_all(iter);
+    }
+}
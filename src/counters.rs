@@ -0,0 +1,340 @@
+use alloc::{vec, vec::Vec};
+use core::fmt::{self, Debug};
+
+/// Storage for the counters of a CountingBloomFilter.
+///
+/// The width of a single counter determines how often an index can be hit before the counter
+/// saturates. Saturated counters are never decremented again, because their true value is unknown.
+/// The crate offers three implementations with different memory/overflow trade-offs:
+/// * *Counters4*: 4 bits per counter, saturates at 15
+/// * *Counters8*: 8 bits per counter, saturates at 255
+/// * *Counters16*: 16 bits per counter, saturates at 65,535
+pub trait Counters {
+    /// The largest value a single counter can hold.
+    const MAX: u16;
+
+    /// Create a new storage holding *length* counters that are all set to zero.
+    fn new(length: usize) -> Self;
+
+    /// Number of counters in the storage.
+    fn len(&self) -> usize;
+
+    /// Whether the storage holds no counters at all.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the value of the counter at *index*.
+    fn get(&self, index: usize) -> u16;
+
+    /// Set the counter at *index* to *value*.
+    ///
+    /// Values larger than *MAX* are truncated to *MAX*.
+    fn set(&mut self, index: usize, value: u16);
+
+    /// Reset all counters to zero.
+    fn clear(&mut self) {
+        for index in 0..self.len() {
+            self.set(index, 0);
+        }
+    }
+
+    /// Increment the counter at *index* by one, saturating at *MAX*.
+    fn increment(&mut self, index: usize) {
+        let value = self.get(index);
+        if value < Self::MAX {
+            self.set(index, value + 1);
+        }
+    }
+
+    /// Decrement the counter at *index* by one.
+    ///
+    /// Counters that are zero or saturated are left untouched.
+    fn decrement(&mut self, index: usize) {
+        let value = self.get(index);
+        if value > 0 && value < Self::MAX {
+            self.set(index, value - 1);
+        }
+    }
+
+    /// All counters, starting with counter 0, e.g., to compare small storages in tests. The Debug
+    /// output only summarizes the counters, because it is written to logs.
+    fn dump_counters(&self) -> Vec<u16> {
+        (0..self.len()).map(|i| self.get(i)).collect()
+    }
+}
+
+fn check_index(index: usize, length: usize) {
+    if index >= length {
+        panic!(
+            "index out of bounds: the len is {} but the index is {}",
+            length, index,
+        )
+    }
+}
+
+/// Counters with a width of four bits. Two counters are packed into a single byte.
+pub struct Counters4 {
+    bytes: Vec<u8>,
+    length: usize,
+}
+
+impl Counters for Counters4 {
+    const MAX: u16 = 0x0F;
+
+    fn new(length: usize) -> Self {
+        Self {
+            bytes: vec![0; length.div_ceil(2)],
+            length,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.length
+    }
+
+    fn clear(&mut self) {
+        self.bytes.fill(0);
+    }
+
+    fn get(&self, index: usize) -> u16 {
+        check_index(index, self.length);
+        let shift = (index % 2) * 4;
+        ((self.bytes[index / 2] >> shift) & 0x0F) as u16
+    }
+
+    fn set(&mut self, index: usize, value: u16) {
+        check_index(index, self.length);
+        let shift = (index % 2) * 4;
+        let value = value.min(Self::MAX) as u8;
+        let byte = &mut self.bytes[index / 2];
+        *byte = (*byte & !(0x0F << shift)) | (value << shift);
+    }
+}
+
+/// Counters with a width of eight bits.
+pub struct Counters8 {
+    counters: Vec<u8>,
+}
+
+impl Counters for Counters8 {
+    const MAX: u16 = u8::MAX as u16;
+
+    fn new(length: usize) -> Self {
+        Self {
+            counters: vec![0; length],
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.counters.len()
+    }
+
+    fn clear(&mut self) {
+        self.counters.fill(0);
+    }
+
+    fn get(&self, index: usize) -> u16 {
+        check_index(index, self.len());
+        self.counters[index] as u16
+    }
+
+    fn set(&mut self, index: usize, value: u16) {
+        check_index(index, self.len());
+        self.counters[index] = value.min(Self::MAX) as u8;
+    }
+}
+
+/// Counters with a width of sixteen bits.
+pub struct Counters16 {
+    counters: Vec<u16>,
+}
+
+impl Counters for Counters16 {
+    const MAX: u16 = u16::MAX;
+
+    fn new(length: usize) -> Self {
+        Self {
+            counters: vec![0; length],
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.counters.len()
+    }
+
+    fn clear(&mut self) {
+        self.counters.fill(0);
+    }
+
+    fn get(&self, index: usize) -> u16 {
+        check_index(index, self.len());
+        self.counters[index]
+    }
+
+    fn set(&mut self, index: usize, value: u16) {
+        check_index(index, self.len());
+        self.counters[index] = value;
+    }
+}
+
+/// Number of counters that the Debug output of counters shows.
+const DEBUG_COUNTERS: usize = 16;
+
+/// Summary of *counters*: their length, the number of non-zero counters, and the first counters,
+/// so that counters of any size can be logged. Use *dump_counters* for all counters.
+fn fmt_summary<C: Counters>(counters: &C, name: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let non_zero = (0..counters.len())
+        .filter(|&i| counters.get(i) != 0)
+        .count();
+    write!(
+        f,
+        "{}{{length: {}, non_zero: {}, counters: [",
+        name,
+        counters.len(),
+        non_zero
+    )?;
+    for i in 0..counters.len().min(DEBUG_COUNTERS) {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", counters.get(i))?;
+    }
+    if counters.len() > DEBUG_COUNTERS {
+        write!(f, ", ... {} more", counters.len() - DEBUG_COUNTERS)?;
+    }
+    write!(f, "]}}")
+}
+
+impl Debug for Counters4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_summary(self, "Counters4", f)
+    }
+}
+
+impl Debug for Counters8 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_summary(self, "Counters8", f)
+    }
+}
+
+impl Debug for Counters16 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_summary(self, "Counters16", f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saturates_at_max<C: Counters>() {
+        let mut counters = C::new(3);
+        for _ in 0..(C::MAX as usize + 10) {
+            counters.increment(1);
+        }
+        assert_eq!(0, counters.get(0));
+        assert_eq!(C::MAX, counters.get(1));
+        assert_eq!(0, counters.get(2));
+
+        // A saturated counter must never be decremented
+        counters.decrement(1);
+        assert_eq!(C::MAX, counters.get(1));
+    }
+
+    fn increment_and_decrement<C: Counters>() {
+        let mut counters = C::new(5);
+        counters.increment(0);
+        counters.increment(4);
+        counters.increment(4);
+        assert_eq!(1, counters.get(0));
+        assert_eq!(0, counters.get(3));
+        assert_eq!(2, counters.get(4));
+
+        counters.decrement(4);
+        counters.decrement(3);
+        assert_eq!(1, counters.get(4));
+        assert_eq!(0, counters.get(3));
+    }
+
+    #[test]
+    fn counters4_saturate() {
+        saturates_at_max::<Counters4>();
+    }
+
+    #[test]
+    fn counters8_saturate() {
+        saturates_at_max::<Counters8>();
+    }
+
+    #[test]
+    fn counters16_saturate() {
+        saturates_at_max::<Counters16>();
+    }
+
+    #[test]
+    fn counters4_increment_and_decrement() {
+        increment_and_decrement::<Counters4>();
+    }
+
+    #[test]
+    fn counters8_increment_and_decrement() {
+        increment_and_decrement::<Counters8>();
+    }
+
+    #[test]
+    fn counters16_increment_and_decrement() {
+        increment_and_decrement::<Counters16>();
+    }
+
+    #[test]
+    fn counters4_neighbours_are_independent() {
+        let mut counters = Counters4::new(4);
+        counters.set(1, 9);
+        counters.set(2, 15);
+        assert_eq!(0, counters.get(0));
+        assert_eq!(9, counters.get(1));
+        assert_eq!(15, counters.get(2));
+        assert_eq!(0, counters.get(3));
+        assert_eq!(2, counters.bytes.len());
+    }
+
+    #[test]
+    fn clear_resets_saturated_counters() {
+        let mut counters = Counters4::new(3);
+        for _ in 0..20 {
+            counters.increment(2);
+        }
+        counters.increment(0);
+        counters.clear();
+        assert_eq!(0, counters.get(0));
+        assert_eq!(0, counters.get(2));
+    }
+
+    #[test]
+    fn debug_is_summarized() {
+        let mut counters = Counters4::new(1_000_000);
+        counters.increment(0);
+        counters.increment(17);
+        counters.increment(17);
+        assert_eq!(
+            "Counters4{length: 1000000, non_zero: 2, counters: [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, \
+             0, 0, 0, 0, ... 999984 more]}",
+            format!("{:?}", counters)
+        );
+        assert_eq!(
+            "Counters16{length: 0, non_zero: 0, counters: []}",
+            format!("{:?}", Counters16::new(0))
+        );
+
+        let mut counters = Counters8::new(3);
+        counters.increment(2);
+        assert_eq!(vec![0, 0, 1], counters.dump_counters());
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn must_get_with_correct_index() {
+        Counters4::new(3).get(3);
+    }
+}
@@ -0,0 +1,686 @@
+use alloc::vec::Vec;
+use core::{
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use crate::{
+    approximate_element_count, approximate_element_count_bounds,
+    approximate_false_positive_probability,
+    counters::{Counters, Counters8},
+    filter_from_iter,
+    params::{self, FilterParams, DEFAULT_FALSE_POSITIVE_PROBABILITY},
+    BloomFilter, HashedKey, INSERT_BATCH_SIZE,
+};
+
+/// Counting bloom filter that replaces each bit of a KMBloomFilter with a counter, which makes it
+/// possible to remove elements again.
+///
+/// The width of the counters is chosen with the type parameter *C* (see [Counters](crate::Counters)).
+/// Wider counters need more memory but saturate later. Once a counter is saturated, it is never
+/// decremented again, because its true value is unknown. Hence, removing elements can never result
+/// in false negatives for the remaining elements.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,CountingBloomFilter,Counters4};
+/// use ahash::AHasher;
+/// use std::collections::hash_map::DefaultHasher;
+///
+/// fn main() {
+///     // We plan on storing at most 10,000 elements
+///     let desired_capacity = 10_000;
+///     // We want to assure that the chance of a false positive is less than 0.0001.
+///     let desired_fp_probability = 0.0001;
+///
+///     // We initialize a new CountingBloomFilter with 4-bit counters.
+///     let mut filter: CountingBloomFilter<AHasher, DefaultHasher, Counters4> =
+///         CountingBloomFilter::new(desired_capacity, desired_fp_probability);
+///
+///     filter.insert(&"Some text");
+///     filter.insert(&"Some text");
+///     filter.insert(&5);
+///     assert_eq!(2, filter.approximate_count(&"Some text"));
+///
+///     // Elements can be removed again.
+///     filter.remove(&5);
+///     assert_eq!(false, filter.contains(&5));
+///     assert_eq!(true, filter.contains(&"Some text"));
+/// }
+/// ```
+pub struct CountingBloomFilter<H1, H2, C = Counters8>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+    C: Counters,
+{
+    number_of_hashers: usize,
+    counters: C,
+    counters_per_hasher: usize,
+    // Phantom data for saving which concrete Hasher types are used
+    _phantom: PhantomData<fn() -> (H1, H2)>,
+}
+
+impl<H1, H2, C> CountingBloomFilter<H1, H2, C>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+    C: Counters,
+{
+    /// Initialize a new instance of CountingBloomFilter that guarantees that the false positive rate
+    /// is less than *desired_false_positive_probability* for up to *desired_capacity*
+    /// elements.
+    ///
+    /// CountingBloomFilter uses two hash functions *H1* and *H2* to simulate an arbitrary number of
+    /// hash functions, just like KMBloomFilter. The counter width is specified by *C*.
+    ///
+    /// ***You have to use two different hash functions for *H1* and *H2*!***
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0 or if desired_false_positive_probability is not in the
+    /// interval (0,1).
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{CountingBloomFilter,Counters16};
+    /// use ahash::AHasher;
+    /// use std::collections::hash_map::DefaultHasher;
+    ///
+    /// fn main() {
+    ///     // The counter width can be omitted, in which case 8-bit counters are used.
+    ///     let filter: CountingBloomFilter<AHasher, DefaultHasher> =
+    ///         CountingBloomFilter::new(10_000, 0.0001);
+    ///
+    ///     // Specifying the counter width explicitly.
+    ///     let filter: CountingBloomFilter<AHasher, DefaultHasher, Counters16> =
+    ///         CountingBloomFilter::new(10_000, 0.0001);
+    /// }
+    /// ```
+    pub fn new(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self {
+        let params =
+            FilterParams::for_capacity(desired_capacity, desired_false_positive_probability);
+        Self {
+            counters: C::new(params.bit_count()),
+            number_of_hashers: params.number_of_hashers(),
+            counters_per_hasher: params.bits_per_hasher(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Initialize a new instance of CountingBloomFilter for up to *desired_capacity* elements with the
+    /// default false positive probability of 1% (see
+    /// *params::DEFAULT_FALSE_POSITIVE_PROBABILITY*).
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0
+    pub fn with_capacity(desired_capacity: usize) -> Self {
+        Self::new(desired_capacity, DEFAULT_FALSE_POSITIVE_PROBABILITY)
+    }
+
+    /// Initialize a new instance of CountingBloomFilter with at least *counter_count* counters
+    /// that hashes every element with exactly *number_of_hashers* hash functions, instead of
+    /// deriving both from a desired capacity and false positive probability.
+    ///
+    /// Fewer hash functions mean fewer probes per lookup at the cost of a higher false positive
+    /// probability for the same number of counters. *params::bit_count_for_hashers* calculates
+    /// the number of counters that keeps the false positive probability for a given number of
+    /// hashers. The counters are split evenly between the hashers, so the counter count is
+    /// rounded up to a multiple of *number_of_hashers*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if counter_count == 0, number_of_hashers == 0, or if rounding counter_count up to a
+    /// multiple of number_of_hashers overflows usize.
+    pub fn with_parameters(counter_count: usize, number_of_hashers: usize) -> Self {
+        let counters_per_hasher = params::bits_per_hasher(counter_count, number_of_hashers);
+        Self {
+            counters: C::new(number_of_hashers * counters_per_hasher),
+            number_of_hashers,
+            counters_per_hasher,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Create a new CountingBloomFilter for the elements of *iter* that guarantees a false
+    /// positive rate of less than *desired_false_positive_probability* and insert all elements.
+    ///
+    /// The desired capacity is the number of elements of *iter*. It is taken from the size hint of
+    /// *iter* if the hint is exact. Otherwise, the elements are collected first to count them.
+    /// Use *new* and *extend* if you want to leave room for more elements.
+    pub fn from_iter_with_fp<T, I>(
+        iter: I,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        filter_from_iter(iter, |desired_capacity| {
+            Self::new(desired_capacity, desired_false_positive_probability)
+        })
+    }
+
+    /// Remove data from the filter.
+    ///
+    /// The counters of *data* are only decremented if the filter (possibly) contains *data*.
+    /// Returns whether the counters have been decremented.
+    ///
+    /// Only remove elements that have been inserted before. Removing an element that has not been
+    /// inserted, but is reported as contained due to a false positive, decrements the counters of
+    /// other elements and can therefore cause false negatives.
+    pub fn remove<T>(&mut self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        if !self.contains(data) {
+            return false;
+        }
+        let (hash_a, hash_b) = self.generate_hashes(data);
+        for i in 0..self.number_of_hashers {
+            self.counters
+                .decrement(Self::index(i, self.counters_per_hasher, hash_a, hash_b));
+        }
+        true
+    }
+
+    /// Compute the two base hashes of *data*, which can be used to insert or look up *data* in
+    /// this and any other filter with the same hashers (see *HashedKey*).
+    pub fn hash<T>(&self, data: &T) -> HashedKey<H1, H2>
+    where
+        T: Hash,
+    {
+        let (hash_a, hash_b) = self.generate_hashes(data);
+        HashedKey::from_hashes(hash_a, hash_b)
+    }
+
+    /// Indices of the counters that *data* is mapped to, one for every hash function.
+    ///
+    /// The indices are the same as the indices of the bits of a KMBloomFilter with the same
+    /// hashers, desired capacity, and desired false positive probability (see
+    /// *KMBloomFilter::probe_indices*).
+    pub fn probe_indices<T>(&self, data: &T) -> Vec<usize>
+    where
+        T: Hash,
+    {
+        let (hash_a, hash_b) = self.generate_hashes(data);
+        (0..self.number_of_hashers)
+            .map(|i| Self::index(i, self.counters_per_hasher, hash_a, hash_b))
+            .collect()
+    }
+
+    /// Insert the element *key* has been computed for.
+    ///
+    /// The result is the same as calling *insert* with the element.
+    pub fn insert_hashed(&mut self, key: &HashedKey<H1, H2>) {
+        let (hash_a, hash_b) = key.hashes();
+        for i in 0..self.number_of_hashers {
+            self.counters
+                .increment(Self::index(i, self.counters_per_hasher, hash_a, hash_b));
+        }
+    }
+
+    /// Check whether the element *key* has been computed for is contained in the bloom filter.
+    ///
+    /// The result is the same as calling *contains* with the element.
+    pub fn contains_hashed(&self, key: &HashedKey<H1, H2>) -> bool {
+        let (hash_a, hash_b) = key.hashes();
+        for i in 0..self.number_of_hashers {
+            if self
+                .counters
+                .get(Self::index(i, self.counters_per_hasher, hash_a, hash_b))
+                == 0
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Insert the raw bytes *bytes*.
+    ///
+    /// In contrast to *insert*, the bytes are written to *H1* and *H2* as they are, without the
+    /// length prefix of the *Hash* implementation of slices. This makes it possible to hash the same
+    /// payload in other languages: with the hashers of StableBloomFilterDefault, the base hashes are
+    /// XXH3 (64 bit) with seed *STABLE_XXH3_SEED* and XXH64 with seed *STABLE_XXH64_SEED* of *bytes*,
+    /// which can be inserted with *insert_hash64*.
+    ///
+    /// Elements inserted with *insert_bytes* are in general only found by *contains_bytes*.
+    pub fn insert_bytes(&mut self, bytes: &[u8]) {
+        self.insert_hashed(&HashedKey::from_bytes(bytes));
+    }
+
+    /// Check whether the raw bytes *bytes* have been inserted with *insert_bytes* (or as their
+    /// base hashes with *insert_hash64*).
+    pub fn contains_bytes(&self, bytes: &[u8]) -> bool {
+        self.contains_hashed(&HashedKey::from_bytes(bytes))
+    }
+
+    /// Insert the UTF-8 bytes of *text*, which is the same element as *insert_bytes* of
+    /// *text.as_bytes()*.
+    ///
+    /// See *KMBloomFilter::insert_str* for details.
+    pub fn insert_str(&mut self, text: &str) {
+        self.insert_bytes(text.as_bytes());
+    }
+
+    /// Check whether *text* has been inserted with *insert_str* (or as its UTF-8 bytes with
+    /// *insert_bytes*).
+    pub fn contains_str(&self, text: &str) -> bool {
+        self.contains_bytes(text.as_bytes())
+    }
+
+    /// Insert an element by its base hashes *hash_a* and *hash_b*, which have been computed
+    /// elsewhere, e.g., by a producer that is not written in Rust.
+    ///
+    /// The result is the same as inserting an element for which *H1* returns *hash_a* and *H2*
+    /// returns *hash_b*.
+    pub fn insert_hash64(&mut self, hash_a: u64, hash_b: u64) {
+        self.insert_hashed(&HashedKey::from_hashes(hash_a, hash_b));
+    }
+
+    /// Check whether an element with the base hashes *hash_a* and *hash_b* is contained in the
+    /// bloom filter.
+    pub fn contains_hash64(&self, hash_a: u64, hash_b: u64) -> bool {
+        self.contains_hashed(&HashedKey::from_hashes(hash_a, hash_b))
+    }
+
+    /// Approximate how often *data* has been inserted into the filter.
+    ///
+    /// The approximation is the minimum of all counters *data* is mapped to. It is never smaller than
+    /// the number of times *data* has actually been inserted (minus the number of times it has been
+    /// removed), unless the counters saturated at *C::MAX*.
+    pub fn approximate_count<T>(&self, data: &T) -> u16
+    where
+        T: Hash,
+    {
+        let (hash_a, hash_b) = self.generate_hashes(data);
+        (0..self.number_of_hashers)
+            .map(|i| {
+                self.counters
+                    .get(Self::index(i, self.counters_per_hasher, hash_a, hash_b))
+            })
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Approximate number of elements stored.
+    /// Approximation technique taken from Wikipedia:
+    /// > Wikipedia, ["Bloom filter"](https://en.wikipedia.org/wiki/Bloom_filter#Approximating_the_number_of_items_in_a_Bloom_filter) [Accessed: 02.12.2020]
+    pub fn approximate_element_count(&self) -> f64 {
+        approximate_element_count(
+            self.number_of_hashers,
+            self.counters_per_hasher,
+            self.number_of_non_zeros(),
+        )
+    }
+
+    /// Return the current approximate false positive probability which depends on the current
+    /// number of elements in the filter.
+    ///
+    /// The probability is given as a value in the interval [0,1]
+    /// Approximation technique taken from Sagi Kedmi:
+    /// > S. Kedmi, ["Bloom Filters for the Perplexed"](https://sagi.io/bloom-filters-for-the-perplexed/), July 2017 [Accessed: 02.12.2020]
+    pub fn approximate_current_false_positive_probability(&self) -> f64 {
+        approximate_false_positive_probability(
+            self.number_of_hashers,
+            self.counters_per_hasher,
+            self.approximate_element_count(),
+        )
+    }
+
+    /// Lower and upper bound of an approximate 95% confidence interval for the number of elements
+    /// stored, based on the variance of the number of counters that are set.
+    ///
+    /// The upper bound is infinite if the filter is (almost) full. See
+    /// *params::approximate_element_count_bounds* for details.
+    pub fn approximate_element_count_bounds(&self) -> (f64, f64) {
+        approximate_element_count_bounds(
+            self.number_of_hashers,
+            self.counters_per_hasher,
+            self.number_of_non_zeros(),
+        )
+    }
+
+    /// Lower and upper bound of an approximate 95% confidence interval for the current false
+    /// positive probability, i.e., the false positive probabilities for the bounds of
+    /// *approximate_element_count_bounds*.
+    pub fn approximate_current_false_positive_probability_bounds(&self) -> (f64, f64) {
+        let (lower, upper) = self.approximate_element_count_bounds();
+        (
+            approximate_false_positive_probability(
+                self.number_of_hashers,
+                self.counters_per_hasher,
+                lower,
+            ),
+            approximate_false_positive_probability(
+                self.number_of_hashers,
+                self.counters_per_hasher,
+                upper,
+            ),
+        )
+    }
+
+    /// Creates a union of this counting bloom filter and 'other', which contains every element
+    /// that has been inserted into either filter, with the larger of both counts.
+    ///
+    /// Every counter of the union is the maximum of the corresponding counters, so the union
+    /// contains exactly the elements that the union of KMBloomFilters with the same configuration
+    /// and hashers contains, and an element that has been inserted into both filters is removed
+    /// from the union by as many calls of *remove* as from the filter with the larger count.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,CountingBloomFilter};
+    /// use ahash::AHasher;
+    /// use std::collections::hash_map::DefaultHasher;
+    ///
+    /// fn main() {
+    ///     let mut filter_one: CountingBloomFilter<AHasher, DefaultHasher> =
+    ///         CountingBloomFilter::new(1_000, 0.001);
+    ///     let mut filter_two: CountingBloomFilter<AHasher, DefaultHasher> =
+    ///         CountingBloomFilter::new(1_000, 0.001);
+    ///     filter_one.insert(&0);
+    ///     filter_one.insert(&1);
+    ///     filter_two.insert(&1);
+    ///     filter_two.insert(&2);
+    ///
+    ///     let union = filter_one.union(&filter_two);
+    ///     assert_eq!(true, (0..3).all(|i| union.contains(&i)));
+    ///     let intersection = filter_one.intersect(&filter_two);
+    ///     assert_eq!(false, intersection.contains(&0));
+    ///     assert_eq!(true, intersection.contains(&1));
+    ///     assert_eq!(false, intersection.contains(&2));
+    /// }
+    /// ```
+    pub fn union(&self, other: &Self) -> Self {
+        self.combine(other, "union", u16::max)
+    }
+
+    /// Adds all elements of 'other' to this counting bloom filter, so that it becomes the union of
+    /// both filters (see *union*).
+    ///
+    /// In contrast to *union*, no new filter is allocated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    pub fn union_with(&mut self, other: &Self) {
+        self.combine_with(other, "union", u16::max);
+    }
+
+    /// Creates an intersection of this counting bloom filter and 'other', which contains every
+    /// element that has been inserted into both filters, with the smaller of both counts.
+    ///
+    /// Every counter of the intersection is the minimum of the corresponding counters, so the
+    /// intersection contains exactly the elements that the intersection of KMBloomFilters with the
+    /// same configuration and hashers contains. Like the intersection of KMBloomFilters, it may
+    /// contain elements that have not been inserted into both filters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    pub fn intersect(&self, other: &Self) -> Self {
+        self.combine(other, "intersect", u16::min)
+    }
+
+    /// Turns this counting bloom filter into the intersection of both filters (see *intersect*).
+    ///
+    /// In contrast to *intersect*, no new filter is allocated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.combine_with(other, "intersect", u16::min);
+    }
+
+    /// Fraction of counters that are not zero, given as a value in the interval [0,1].
+    ///
+    /// With the optimal number of hashers, about half of the counters are not zero once the
+    /// filter holds its desired capacity.
+    pub fn fill_ratio(&self) -> f64 {
+        self.number_of_non_zeros() as f64 / self.counters.len() as f64
+    }
+
+    /// Total number of counters of the filter.
+    pub fn counter_count(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// All counters of the filter, starting with counter 0, e.g., to compare small filters in
+    /// tests. The Debug output only summarizes the counters, because it is written to logs.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,CountingBloomFilter,Counters8,StableXxh3Hasher,StableXxh64Hasher};
+    ///
+    /// let mut filter: CountingBloomFilter<StableXxh3Hasher, StableXxh64Hasher, Counters8> =
+    ///     CountingBloomFilter::with_parameters(12, 3);
+    /// filter.insert(&"Hello!");
+    /// assert_eq!(3, filter.dump_counters().iter().sum::<u16>());
+    /// ```
+    pub fn dump_counters(&self) -> Vec<u16> {
+        self.counters.dump_counters()
+    }
+
+    /// Number of hash functions used for every element.
+    pub fn hasher_count(&self) -> usize {
+        self.number_of_hashers
+    }
+
+    /// Whether the fill ratio has reached *threshold*, i.e., the filter should be replaced by a
+    /// larger or empty one.
+    pub fn is_saturated(&self, threshold: f64) -> bool {
+        self.fill_ratio() >= threshold
+    }
+
+    /// Remove all elements from the bloom filter by resetting all counters to zero, including
+    /// saturated ones.
+    ///
+    /// The memory of the filter is reused, which makes clearing a filter much cheaper than creating
+    /// a new one with the same configuration.
+    pub fn clear(&mut self) {
+        self.counters.clear();
+    }
+
+    /// Checks whether two bloom filters were created with the same desired capacity and desired false
+    /// positive probability.
+    ///
+    /// The hashers are not compared, because they are part of the type: filters with different
+    /// hashers cannot be compared or combined, since all methods that take another filter only
+    /// accept filters of the same type.
+    pub fn eq_configuration(&self, other: &Self) -> bool {
+        self.number_of_hashers == other.number_of_hashers
+            && self.counters_per_hasher == other.counters_per_hasher
+    }
+
+    /// New filter whose counters are the results of *combine* for the counters of both filters.
+    fn combine(&self, other: &Self, operation: &str, combine: fn(u16, u16) -> u16) -> Self {
+        self.check_configuration(other, operation);
+        let mut counters = C::new(self.counters.len());
+        for i in 0..counters.len() {
+            counters.set(i, combine(self.counters.get(i), other.counters.get(i)));
+        }
+        Self {
+            number_of_hashers: self.number_of_hashers,
+            counters,
+            counters_per_hasher: self.counters_per_hasher,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Replace every counter with the result of *combine* for it and the counter of *other*.
+    fn combine_with(&mut self, other: &Self, operation: &str, combine: fn(u16, u16) -> u16) {
+        self.check_configuration(other, operation);
+        for i in 0..self.counters.len() {
+            let value = combine(self.counters.get(i), other.counters.get(i));
+            self.counters.set(i, value);
+        }
+    }
+
+    fn check_configuration(&self, other: &Self, operation: &str) {
+        if !self.eq_configuration(other) {
+            panic!(
+                "unable to {} counting bloom filters with different configurations",
+                operation
+            );
+        }
+    }
+
+    fn number_of_non_zeros(&self) -> usize {
+        (0..self.counters.len())
+            .filter(|&i| self.counters.get(i) > 0)
+            .count()
+    }
+
+    fn generate_hashes<T>(&self, data: &T) -> (u64, u64)
+    where
+        T: Hash,
+    {
+        let mut hasher = H1::default();
+        data.hash(&mut hasher);
+        let hash_a = hasher.finish();
+
+        let mut hasher = H2::default();
+        data.hash(&mut hasher);
+        let hash_b = hasher.finish();
+
+        (hash_a, hash_b)
+    }
+
+    fn increment_indices(&mut self, indices: &mut Vec<usize>) {
+        for &index in indices.iter() {
+            self.counters.increment(index);
+        }
+        indices.clear();
+    }
+
+    fn index(i: usize, counters_per_hash: usize, hash_a: u64, hash_b: u64) -> usize {
+        let hash = hash_a.wrapping_add((i as u64).wrapping_mul(hash_b));
+        i * counters_per_hash + (hash % counters_per_hash as u64) as usize
+    }
+}
+
+/// Summary of the counters, see the Debug output of Counters4, Counters8, and Counters16. Use
+/// *dump_counters* for all counters.
+impl<H1, H2, C> Debug for CountingBloomFilter<H1, H2, C>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+    C: Counters + Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CountingBloomFilter{{{:?}}}", self.counters)
+    }
+}
+
+/// One-line summary of the configuration and the estimates of the filter, e.g., for logs.
+impl<H1, H2, C> Display for CountingBloomFilter<H1, H2, C>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+    C: Counters,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "CountingBloomFilter: {} hashers, {} counters ({:.2}% non-zero), about {:.1} \
+             elements, false positive probability about {:.2e}",
+            self.number_of_hashers,
+            self.counters.len(),
+            100.0 * self.number_of_non_zeros() as f64 / self.counters.len() as f64,
+            self.approximate_element_count(),
+            self.approximate_current_false_positive_probability()
+        )
+    }
+}
+
+/// Two counting bloom filters are equal if they have the same configuration and all counters have
+/// the same values.
+impl<H1, H2, C> PartialEq for CountingBloomFilter<H1, H2, C>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+    C: Counters,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_configuration(other)
+            && (0..self.counters.len()).all(|i| self.counters.get(i) == other.counters.get(i))
+    }
+}
+
+impl<H1, H2, C> Eq for CountingBloomFilter<H1, H2, C>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+    C: Counters,
+{
+}
+
+impl<H1, H2, C> BloomFilter for CountingBloomFilter<H1, H2, C>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+    C: Counters,
+{
+    fn insert<T>(&mut self, data: &T)
+    where
+        T: Hash,
+    {
+        self.insert_hashed(&self.hash(data));
+    }
+
+    fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        self.contains_hashed(&self.hash(data))
+    }
+
+    fn insert_all<T, I>(&mut self, iter: I)
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        let batch_length = INSERT_BATCH_SIZE * self.number_of_hashers;
+        let mut indices = Vec::with_capacity(batch_length);
+        for data in iter {
+            let (hash_a, hash_b) = self.generate_hashes(&data);
+            indices.extend(
+                (0..self.number_of_hashers)
+                    .map(|i| Self::index(i, self.counters_per_hasher, hash_a, hash_b)),
+            );
+            if indices.len() >= batch_length {
+                self.increment_indices(&mut indices);
+            }
+        }
+        self.increment_indices(&mut indices);
+    }
+}
+
+impl<T, H1, H2, C> Extend<T> for CountingBloomFilter<H1, H2, C>
+where
+    T: Hash,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+    C: Counters,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.insert_all(iter);
+    }
+}
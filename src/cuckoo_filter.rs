@@ -0,0 +1,488 @@
+use core::{
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use crate::{
+    math, packed_array::PackedArray, params::checked_false_positive_probability, BloomFilter,
+};
+
+/// Maximum number of fingerprints that are relocated before an insertion gives up.
+const MAX_KICKS: usize = 500;
+
+/// Maximum load factor that is assumed when deriving the number of buckets from the desired
+/// capacity.
+const MAX_LOAD_FACTOR: f64 = 0.95;
+
+/// Bucket size used by *CuckooFilter::new*.
+const DEFAULT_BUCKET_SIZE: usize = 4;
+
+/// Error returned by *CuckooFilter::try_insert* if the filter is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CuckooFilterFull;
+
+impl Display for CuckooFilterFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the cuckoo filter is full")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CuckooFilterFull {}
+
+/// Cuckoo filter as described by Fan et al.:
+///
+/// > Bin Fan, Dave G. Andersen, Michael Kaminsky, and Michael D. Mitzenmacher. 2014. Cuckoo Filter:
+/// > Practically Better Than Bloom. In Proceedings of the 10th ACM International on Conference on
+/// > emerging Networking Experiments and Technologies (CoNEXT '14). Association for Computing
+/// > Machinery, New York, NY, USA, 75–88. DOI: [https://doi.org/10.1145/2674005.2674994](https://doi.org/10.1145/2674005.2674994)
+///
+/// A cuckoo filter stores a small fingerprint of each inserted element in one of two candidate
+/// buckets. In contrast to bloom filters, elements can be removed again, and for low false positive
+/// probabilities the filter needs less space than a bloom filter.
+///
+/// The filter uses a single hash function *H*. The width of the fingerprints and the number of
+/// fingerprints per bucket can be configured with *with_parameters*.
+///
+/// In contrast to the bloom filters of this crate, a cuckoo filter can run full. Use *try_insert*
+/// if you cannot guarantee that the desired capacity is never exceeded.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,CuckooFilter};
+/// use ahash::AHasher;
+///
+/// fn main() {
+///     // We plan on storing at most 10,000 elements
+///     let desired_capacity = 10_000;
+///     // We want to assure that the chance of a false positive is less than 0.0001.
+///     let desired_fp_probability = 0.0001;
+///
+///     let mut filter: CuckooFilter<AHasher> =
+///         CuckooFilter::new(desired_capacity, desired_fp_probability);
+///
+///     filter.insert(&5i32);
+///     filter.insert(&"Some text");
+///     assert_eq!(true, filter.contains(&5));
+///     assert_eq!(true, filter.contains(&"Some text"));
+///
+///     // Elements can be removed again.
+///     assert_eq!(true, filter.remove(&5));
+///     assert_eq!(false, filter.contains(&5));
+/// }
+/// ```
+pub struct CuckooFilter<H>
+where
+    H: Hasher + Default,
+{
+    fingerprints: PackedArray,
+    bucket_size: usize,
+    number_of_buckets: usize,
+    element_count: usize,
+    // Fingerprint (and one of its buckets) that could not be placed during the last insertion
+    victim: Option<(usize, u32)>,
+    // State of the xorshift generator that decides which fingerprint is relocated
+    rng_state: u64,
+    // Phantom data for saving which concrete Hasher type is used
+    _phantom: PhantomData<fn() -> H>,
+}
+
+impl<H> CuckooFilter<H>
+where
+    H: Hasher + Default,
+{
+    /// Initialize a new instance of CuckooFilter that guarantees that the false positive rate
+    /// is less than *desired_false_positive_probability* for up to *desired_capacity*
+    /// elements.
+    ///
+    /// Buckets hold four fingerprints each, and the fingerprint width is derived from the desired
+    /// false positive probability.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0, if desired_false_positive_probability is not in the
+    /// interval (0,1), or if it requires fingerprints wider than 32 bits.
+    pub fn new(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self {
+        let desired_false_positive_probability =
+            checked_false_positive_probability(desired_false_positive_probability);
+        let fingerprint_bits = math::ceil(
+            math::ln(2.0 * DEFAULT_BUCKET_SIZE as f64 / desired_false_positive_probability)
+                / math::ln(2.0),
+        ) as u32;
+        Self::with_parameters(
+            desired_capacity,
+            fingerprint_bits.max(1),
+            DEFAULT_BUCKET_SIZE,
+        )
+    }
+
+    /// Initialize a new instance of CuckooFilter that can hold at least *desired_capacity*
+    /// elements, using fingerprints of *fingerprint_bits* bits and buckets that hold *bucket_size*
+    /// fingerprints.
+    ///
+    /// The false positive probability of a full filter is approximately
+    /// *2 \* bucket_size / 2^fingerprint_bits*. Larger buckets allow higher load factors but
+    /// require longer fingerprints for the same false positive probability.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0, bucket_size == 0, or if fingerprint_bits is not in the
+    /// interval [1, 32].
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,CuckooFilter};
+    /// use ahash::AHasher;
+    ///
+    /// fn main() {
+    ///     // 12-bit fingerprints in buckets of two fingerprints
+    ///     let mut filter: CuckooFilter<AHasher> = CuckooFilter::with_parameters(1_000, 12, 2);
+    ///     filter.insert(&"Hello!");
+    ///     assert_eq!(true, filter.contains(&"Hello!"));
+    /// }
+    /// ```
+    pub fn with_parameters(
+        desired_capacity: usize,
+        fingerprint_bits: u32,
+        bucket_size: usize,
+    ) -> Self {
+        if desired_capacity == 0 {
+            panic!("an empty cuckoo filter is not defined");
+        }
+        if bucket_size == 0 {
+            panic!("a cuckoo filter requires buckets with at least one slot");
+        }
+        if fingerprint_bits == 0 || fingerprint_bits > 32 {
+            panic!(
+                "unsupported fingerprint width of {} bits, it must be in [1, 32]",
+                fingerprint_bits
+            );
+        }
+        let number_of_buckets =
+            math::ceil(desired_capacity as f64 / (bucket_size as f64 * MAX_LOAD_FACTOR)) as usize;
+        // The alternate bucket is computed with XOR, which requires a power of two
+        let number_of_buckets = number_of_buckets.max(2).next_power_of_two();
+        Self {
+            fingerprints: PackedArray::new(number_of_buckets * bucket_size, fingerprint_bits),
+            bucket_size,
+            number_of_buckets,
+            element_count: 0,
+            victim: None,
+            rng_state: 0x2545_f491_4f6c_dd1d,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Insert data into the filter, or return an error if the filter is full.
+    ///
+    /// If no slot is free in the two candidate buckets of *data*, other fingerprints are relocated
+    /// to their alternative bucket. If that fails, too, the last relocated fingerprint is kept
+    /// aside, so that no element is lost, and the filter is considered full. All following
+    /// insertions fail until removing elements made room for the fingerprint kept aside.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{CuckooFilter,CuckooFilterFull};
+    /// use ahash::AHasher;
+    ///
+    /// fn main() {
+    ///     let mut filter: CuckooFilter<AHasher> = CuckooFilter::with_parameters(4, 8, 1);
+    ///     let mut result = Ok(());
+    ///     for i in 0..100 {
+    ///         result = filter.try_insert(&i);
+    ///         if result.is_err() {
+    ///             break;
+    ///         }
+    ///     }
+    ///     assert_eq!(Err(CuckooFilterFull), result);
+    /// }
+    /// ```
+    pub fn try_insert<T>(&mut self, data: &T) -> Result<(), CuckooFilterFull>
+    where
+        T: Hash,
+    {
+        if self.victim.is_some() {
+            return Err(CuckooFilterFull);
+        }
+        let (index, fingerprint) = self.index_and_fingerprint(data);
+        self.element_count += 1;
+        self.victim = self.place(index, fingerprint);
+        Ok(())
+    }
+
+    /// Remove data from the filter.
+    ///
+    /// Returns whether a fingerprint of *data* has been found and removed.
+    ///
+    /// Only remove elements that have been inserted before. Removing an element that has not been
+    /// inserted, but is reported as contained due to a false positive, removes the fingerprint of
+    /// another element and can therefore cause false negatives.
+    pub fn remove<T>(&mut self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        let (index, fingerprint) = self.index_and_fingerprint(data);
+        let alternate_index = self.alternate_index(index, fingerprint);
+
+        if let Some((victim_index, victim_fingerprint)) = self.victim {
+            if victim_fingerprint == fingerprint
+                && (victim_index == index || victim_index == alternate_index)
+            {
+                self.victim = None;
+                self.element_count -= 1;
+                return true;
+            }
+        }
+
+        if self.remove_from_bucket(index, fingerprint)
+            || self.remove_from_bucket(alternate_index, fingerprint)
+        {
+            self.element_count -= 1;
+            // Now that there is a free slot, try to place the fingerprint that has been kept aside
+            if let Some((victim_index, victim_fingerprint)) = self.victim.take() {
+                self.victim = self.place(victim_index, victim_fingerprint);
+            }
+            return true;
+        }
+        false
+    }
+
+    /// Fraction of occupied slots, given as a value in the interval [0,1].
+    ///
+    /// Insertions usually start to fail at a fill ratio of about 0.95 for buckets of four
+    /// fingerprints.
+    pub fn fill_ratio(&self) -> f64 {
+        self.element_count.min(self.slot_count()) as f64 / self.slot_count() as f64
+    }
+
+    /// Total number of bits used for fingerprints.
+    pub fn bit_count(&self) -> usize {
+        self.slot_count() * self.fingerprint_bits() as usize
+    }
+
+    /// Whether the fill ratio has reached *threshold*, i.e., the filter should be replaced by a
+    /// larger or empty one.
+    pub fn is_saturated(&self, threshold: f64) -> bool {
+        self.fill_ratio() >= threshold
+    }
+
+    /// Remove all elements from the filter.
+    ///
+    /// The memory of the filter is reused, which makes clearing a filter much cheaper than creating
+    /// a new one with the same configuration. A full filter accepts insertions again afterwards.
+    pub fn clear(&mut self) {
+        self.fingerprints.clear();
+        self.element_count = 0;
+        self.victim = None;
+    }
+
+    /// Number of elements stored in the filter.
+    ///
+    /// In contrast to bloom filters, the number of elements is known exactly. Elements that have
+    /// been inserted multiple times are counted multiple times.
+    pub fn len(&self) -> usize {
+        self.element_count
+    }
+
+    /// Whether no elements are stored in the filter.
+    pub fn is_empty(&self) -> bool {
+        self.element_count == 0
+    }
+
+    /// Number of fingerprints the filter can store at most.
+    ///
+    /// Insertions usually start to fail before all slots are occupied.
+    pub fn slot_count(&self) -> usize {
+        self.fingerprints.len()
+    }
+
+    /// Whether the filter is full, i.e., the next insertion will fail.
+    pub fn is_full(&self) -> bool {
+        self.victim.is_some()
+    }
+
+    /// Width of the fingerprints in bits.
+    pub fn fingerprint_bits(&self) -> u32 {
+        self.fingerprints.bits()
+    }
+
+    /// Number of fingerprints per bucket.
+    pub fn bucket_size(&self) -> usize {
+        self.bucket_size
+    }
+
+    /// Return the current approximate false positive probability which depends on the current
+    /// number of elements in the filter.
+    ///
+    /// A lookup compares the fingerprint of an element with all fingerprints in its two candidate
+    /// buckets, each of which matches with a probability of *1 / 2^fingerprint_bits*.
+    ///
+    /// The probability is given as a value in the interval [0,1]
+    pub fn approximate_current_false_positive_probability(&self) -> f64 {
+        let compared_fingerprints = 2.0 * self.bucket_size as f64 * self.fill_ratio();
+        1.0 - math::powf(
+            1.0 - 1.0 / math::powf(2.0, self.fingerprint_bits() as f64),
+            compared_fingerprints,
+        )
+    }
+
+    /// Place a fingerprint in one of its candidate buckets, relocating other fingerprints if
+    /// necessary. Returns the fingerprint that could not be placed, if any.
+    fn place(&mut self, index: usize, fingerprint: u32) -> Option<(usize, u32)> {
+        if self.insert_into_bucket(index, fingerprint)
+            || self.insert_into_bucket(self.alternate_index(index, fingerprint), fingerprint)
+        {
+            return None;
+        }
+
+        let mut index = if self.next_random() & 1 == 0 {
+            index
+        } else {
+            self.alternate_index(index, fingerprint)
+        };
+        let mut fingerprint = fingerprint;
+        for _ in 0..MAX_KICKS {
+            let slot = index * self.bucket_size + (self.next_random() as usize % self.bucket_size);
+            let evicted = self.fingerprints.get(slot);
+            self.fingerprints.set(slot, fingerprint);
+            fingerprint = evicted;
+            index = self.alternate_index(index, fingerprint);
+            if self.insert_into_bucket(index, fingerprint) {
+                return None;
+            }
+        }
+        Some((index, fingerprint))
+    }
+
+    fn insert_into_bucket(&mut self, index: usize, fingerprint: u32) -> bool {
+        for slot in self.bucket(index) {
+            if self.fingerprints.get(slot) == 0 {
+                self.fingerprints.set(slot, fingerprint);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn remove_from_bucket(&mut self, index: usize, fingerprint: u32) -> bool {
+        for slot in self.bucket(index) {
+            if self.fingerprints.get(slot) == fingerprint {
+                self.fingerprints.set(slot, 0);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn bucket_contains(&self, index: usize, fingerprint: u32) -> bool {
+        self.bucket(index)
+            .any(|slot| self.fingerprints.get(slot) == fingerprint)
+    }
+
+    fn bucket(&self, index: usize) -> core::ops::Range<usize> {
+        index * self.bucket_size..(index + 1) * self.bucket_size
+    }
+
+    fn index_and_fingerprint<T>(&self, data: &T) -> (usize, u32)
+    where
+        T: Hash,
+    {
+        let mut hasher = H::default();
+        data.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let index = hash as usize & (self.number_of_buckets - 1);
+        let mask = (1u64 << self.fingerprint_bits()) - 1;
+        // A fingerprint of zero marks an empty slot
+        let fingerprint = ((hash >> 32) & mask).max(1) as u32;
+        (index, fingerprint)
+    }
+
+    fn alternate_index(&self, index: usize, fingerprint: u32) -> usize {
+        (index ^ (fingerprint as usize).wrapping_mul(0x5bd1_e995)) & (self.number_of_buckets - 1)
+    }
+
+    fn next_random(&mut self) -> u64 {
+        // xorshift64
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 7;
+        self.rng_state ^= self.rng_state << 17;
+        self.rng_state
+    }
+}
+
+impl<H> Debug for CuckooFilter<H>
+where
+    H: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "CuckooFilter{{buckets: {}, bucket_size: {}, fingerprint_bits: {}, length: {}}}",
+            self.number_of_buckets,
+            self.bucket_size,
+            self.fingerprint_bits(),
+            self.element_count
+        )
+    }
+}
+
+/// Two cuckoo filters are equal if they have the same configuration and store the same
+/// fingerprints in the same slots.
+///
+/// Cuckoo filters containing the same elements are not necessarily equal, because the slot of a
+/// fingerprint depends on the order of insertions.
+impl<H> PartialEq for CuckooFilter<H>
+where
+    H: Hasher + Default,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.bucket_size == other.bucket_size
+            && self.number_of_buckets == other.number_of_buckets
+            && self.element_count == other.element_count
+            && self.victim == other.victim
+            && self.fingerprints == other.fingerprints
+    }
+}
+
+impl<H> Eq for CuckooFilter<H> where H: Hasher + Default {}
+
+impl<H> BloomFilter for CuckooFilter<H>
+where
+    H: Hasher + Default,
+{
+    /// Insert data into the filter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filter is full. Use *try_insert* to handle this case.
+    fn insert<T>(&mut self, data: &T)
+    where
+        T: Hash,
+    {
+        if let Err(error) = self.try_insert(data) {
+            panic!("{}", error);
+        }
+    }
+
+    fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        let (index, fingerprint) = self.index_and_fingerprint(data);
+        let alternate_index = self.alternate_index(index, fingerprint);
+        if let Some((victim_index, victim_fingerprint)) = self.victim {
+            if victim_fingerprint == fingerprint
+                && (victim_index == index || victim_index == alternate_index)
+            {
+                return true;
+            }
+        }
+        self.bucket_contains(index, fingerprint)
+            || self.bucket_contains(alternate_index, fingerprint)
+    }
+}
@@ -0,0 +1,483 @@
+use core::{
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use crate::{
+    math, packed_array::PackedArray, params::checked_false_positive_probability, BloomFilter,
+};
+
+/// Number of subtables, i.e., the number of candidate buckets of every element.
+const SUBTABLES: usize = 4;
+
+/// Number of cells per bucket.
+const BUCKET_SIZE: usize = 8;
+
+/// Average fraction of occupied cells that is assumed when deriving the number of buckets from the
+/// desired capacity. The spare cells make it unlikely that all candidate buckets of an element are
+/// full.
+const MAX_LOAD_FACTOR: f64 = 0.75;
+
+/// Counter width used by *DLeftCountingFilter::new*.
+const DEFAULT_COUNTER_BITS: u32 = 2;
+
+/// Error returned by *DLeftCountingFilter::try_insert* if all candidate buckets of an element are
+/// full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DLeftCountingFilterFull;
+
+impl Display for DLeftCountingFilterFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "all candidate buckets of the d-left counting filter are full"
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DLeftCountingFilterFull {}
+
+/// Counting filter based on d-left hashing as described by Bonomi et al.:
+///
+/// > Flavio Bonomi, Michael Mitzenmacher, Rina Panigrahy, Sushil Singh, and George Varghese. 2006.
+/// > An Improved Construction for Counting Bloom Filters. In: Azar Y., Erlebach T. (eds)
+/// > Algorithms – ESA 2006. ESA 2006. Lecture Notes in Computer Science, vol 4168. Springer,
+/// > Berlin, Heidelberg. DOI: [https://doi.org/10.1007/11841036_61](https://doi.org/10.1007/11841036_61)
+///
+/// The filter consists of four subtables of buckets with eight cells each. Every element has one
+/// candidate bucket per subtable, and its fingerprint is stored together with a small counter in the
+/// least loaded of them. Like CountingBloomFilter, the filter supports removing elements, but it
+/// stores a single cell per element instead of one counter per hash function. For the same false
+/// positive probability, it therefore needs about half the memory of a CountingBloomFilter with
+/// 4-bit counters.
+///
+/// The position of a fingerprint in its bucket determines the element's other candidate buckets.
+/// Hence, a fingerprint is never stored twice, and removing an inserted element always removes its
+/// own fingerprint. Once a counter is saturated, it is never decremented again, because its true
+/// value is unknown.
+///
+/// In contrast to the bloom filters of this crate, a d-left counting filter can run full. Use
+/// *try_insert* if you cannot guarantee that the desired capacity is never exceeded.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,DLeftCountingFilter};
+/// use ahash::AHasher;
+///
+/// fn main() {
+///     // We plan on storing at most 10,000 elements
+///     let desired_capacity = 10_000;
+///     // We want to assure that the chance of a false positive is less than 0.0001.
+///     let desired_fp_probability = 0.0001;
+///
+///     let mut filter: DLeftCountingFilter<AHasher> =
+///         DLeftCountingFilter::new(desired_capacity, desired_fp_probability);
+///
+///     filter.insert(&"Some text");
+///     filter.insert(&"Some text");
+///     filter.insert(&5);
+///     assert_eq!(2, filter.approximate_count(&"Some text"));
+///
+///     // Elements can be removed again.
+///     assert_eq!(true, filter.remove(&5));
+///     assert_eq!(false, filter.contains(&5));
+///     assert_eq!(true, filter.contains(&"Some text"));
+/// }
+/// ```
+pub struct DLeftCountingFilter<H>
+where
+    H: Hasher + Default,
+{
+    // Every cell holds a fingerprint in the upper bits and a counter in the lower bits. A counter
+    // of zero marks an empty cell.
+    cells: PackedArray,
+    counter_bits: u32,
+    buckets_per_subtable: usize,
+    occupied_cells: usize,
+    // Phantom data for saving which concrete Hasher type is used
+    _phantom: PhantomData<fn() -> H>,
+}
+
+impl<H> DLeftCountingFilter<H>
+where
+    H: Hasher + Default,
+{
+    /// Initialize a new instance of DLeftCountingFilter that guarantees that the false positive
+    /// rate is less than *desired_false_positive_probability* for up to *desired_capacity*
+    /// elements.
+    ///
+    /// Counters are two bits wide, and the fingerprint width is derived from the desired false
+    /// positive probability.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0, if desired_false_positive_probability is not in the
+    /// interval (0,1), or if it requires fingerprints wider than 30 bits.
+    pub fn new(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self {
+        let desired_false_positive_probability =
+            checked_false_positive_probability(desired_false_positive_probability);
+        let fingerprint_bits = math::ceil(
+            math::ln((SUBTABLES * BUCKET_SIZE) as f64 / desired_false_positive_probability)
+                / math::ln(2.0),
+        ) as u32;
+        Self::with_parameters(
+            desired_capacity,
+            fingerprint_bits.max(1),
+            DEFAULT_COUNTER_BITS,
+        )
+    }
+
+    /// Initialize a new instance of DLeftCountingFilter that can hold at least *desired_capacity*
+    /// distinct elements, using fingerprints of *fingerprint_bits* bits and counters of
+    /// *counter_bits* bits.
+    ///
+    /// The false positive probability of a full filter is approximately
+    /// *24 / 2^fingerprint_bits*. Wider counters saturate later, which matters if elements are
+    /// inserted many times.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0, if fingerprint_bits or counter_bits is zero, or if
+    /// fingerprint_bits + counter_bits > 32.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,DLeftCountingFilter};
+    /// use ahash::AHasher;
+    ///
+    /// fn main() {
+    ///     // 12-bit fingerprints and 4-bit counters
+    ///     let mut filter: DLeftCountingFilter<AHasher> =
+    ///         DLeftCountingFilter::with_parameters(1_000, 12, 4);
+    ///     filter.insert(&"Hello!");
+    ///     assert_eq!(true, filter.contains(&"Hello!"));
+    /// }
+    /// ```
+    pub fn with_parameters(
+        desired_capacity: usize,
+        fingerprint_bits: u32,
+        counter_bits: u32,
+    ) -> Self {
+        if desired_capacity == 0 {
+            panic!("an empty d-left counting filter is not defined");
+        }
+        if fingerprint_bits == 0
+            || counter_bits == 0
+            || fingerprint_bits.saturating_add(counter_bits) > 32
+        {
+            panic!(
+                "unsupported cell width of {} fingerprint bits and {} counter bits, both must be at least one and their sum at most 32",
+                fingerprint_bits, counter_bits
+            );
+        }
+        let buckets_per_subtable = math::ceil(
+            desired_capacity as f64 / ((SUBTABLES * BUCKET_SIZE) as f64 * MAX_LOAD_FACTOR),
+        ) as usize;
+        let buckets_per_subtable = buckets_per_subtable.max(1);
+        Self {
+            cells: PackedArray::new(
+                SUBTABLES * buckets_per_subtable * BUCKET_SIZE,
+                fingerprint_bits + counter_bits,
+            ),
+            counter_bits,
+            buckets_per_subtable,
+            occupied_cells: 0,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Insert data into the filter, or return an error if all candidate buckets of *data* are
+    /// full.
+    ///
+    /// If the fingerprint of *data* is already stored, its counter is incremented. Otherwise, the
+    /// fingerprint is stored in the candidate bucket with the fewest occupied cells, preferring the
+    /// leftmost subtable on ties.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{DLeftCountingFilter,DLeftCountingFilterFull};
+    /// use ahash::AHasher;
+    ///
+    /// fn main() {
+    ///     let mut filter: DLeftCountingFilter<AHasher> = DLeftCountingFilter::new(8, 0.01);
+    ///     let mut result = Ok(());
+    ///     for i in 0..1_000 {
+    ///         result = filter.try_insert(&i);
+    ///         if result.is_err() {
+    ///             break;
+    ///         }
+    ///     }
+    ///     assert_eq!(Err(DLeftCountingFilterFull), result);
+    /// }
+    /// ```
+    pub fn try_insert<T>(&mut self, data: &T) -> Result<(), DLeftCountingFilterFull>
+    where
+        T: Hash,
+    {
+        let (bucket, fingerprint) = self.bucket_and_fingerprint(data);
+        if let Some(cell) = self.find(bucket, fingerprint) {
+            let counter = self.counter(cell);
+            if counter < self.max_counter() {
+                self.cells.set(cell, self.cells.get(cell) + 1);
+            }
+            return Ok(());
+        }
+
+        let mut target: Option<(usize, usize)> = None;
+        for subtable in 0..SUBTABLES {
+            let index = self.bucket_index(subtable, bucket, fingerprint);
+            let load = self.load(index);
+            if load < BUCKET_SIZE && target.is_none_or(|(_, min_load)| load < min_load) {
+                target = Some((index, load));
+            }
+        }
+        let (index, load) = target.ok_or(DLeftCountingFilterFull)?;
+        // Occupied cells are kept at the front of a bucket
+        self.cells.set(
+            index * BUCKET_SIZE + load,
+            (fingerprint << self.counter_bits) | 1,
+        );
+        self.occupied_cells += 1;
+        Ok(())
+    }
+
+    /// Remove data from the filter.
+    ///
+    /// The counter of *data* is only decremented if the filter (possibly) contains *data*. Returns
+    /// whether the counter has been decremented or is saturated.
+    ///
+    /// Only remove elements that have been inserted before. Removing an element that has not been
+    /// inserted, but is reported as contained due to a false positive, decrements the counter of
+    /// another element and can therefore cause false negatives.
+    pub fn remove<T>(&mut self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        let (bucket, fingerprint) = self.bucket_and_fingerprint(data);
+        let cell = match self.find(bucket, fingerprint) {
+            Some(cell) => cell,
+            None => return false,
+        };
+        let counter = self.counter(cell);
+        if counter == self.max_counter() {
+            return true;
+        }
+        if counter > 1 {
+            self.cells.set(cell, self.cells.get(cell) - 1);
+            return true;
+        }
+
+        // Move the last occupied cell of the bucket into the freed cell
+        let first = cell - cell % BUCKET_SIZE;
+        let last = first + self.load(cell / BUCKET_SIZE) - 1;
+        self.cells.set(cell, self.cells.get(last));
+        self.cells.set(last, 0);
+        self.occupied_cells -= 1;
+        true
+    }
+
+    /// Approximate how often *data* has been inserted into the filter.
+    ///
+    /// The approximation is the counter stored with the fingerprint of *data*. It is never smaller
+    /// than the number of times *data* has actually been inserted (minus the number of times it has
+    /// been removed), unless the counter saturated at *2^counter_bits - 1*.
+    pub fn approximate_count<T>(&self, data: &T) -> u32
+    where
+        T: Hash,
+    {
+        let (bucket, fingerprint) = self.bucket_and_fingerprint(data);
+        self.find(bucket, fingerprint)
+            .map_or(0, |cell| self.counter(cell))
+    }
+
+    /// Fraction of occupied cells, given as a value in the interval [0,1].
+    ///
+    /// The filter holds its desired capacity at a fill ratio of about 0.75.
+    pub fn fill_ratio(&self) -> f64 {
+        self.occupied_cells as f64 / self.cell_count() as f64
+    }
+
+    /// Total number of bits used for fingerprints and counters.
+    pub fn bit_count(&self) -> usize {
+        self.cell_count() * self.cells.bits() as usize
+    }
+
+    /// Whether the fill ratio has reached *threshold*, i.e., the filter should be replaced by a
+    /// larger or empty one.
+    pub fn is_saturated(&self, threshold: f64) -> bool {
+        self.fill_ratio() >= threshold
+    }
+
+    /// Remove all elements from the filter, including those with saturated counters.
+    ///
+    /// The memory of the filter is reused, which makes clearing a filter much cheaper than creating
+    /// a new one with the same configuration.
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.occupied_cells = 0;
+    }
+
+    /// Number of distinct fingerprints stored in the filter.
+    pub fn len(&self) -> usize {
+        self.occupied_cells
+    }
+
+    /// Whether no elements are stored in the filter.
+    pub fn is_empty(&self) -> bool {
+        self.occupied_cells == 0
+    }
+
+    /// Number of fingerprints the filter can store at most.
+    ///
+    /// Insertions usually start to fail before all cells are occupied.
+    pub fn cell_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Width of the fingerprints in bits.
+    pub fn fingerprint_bits(&self) -> u32 {
+        self.cells.bits() - self.counter_bits
+    }
+
+    /// Width of the counters in bits.
+    pub fn counter_bits(&self) -> u32 {
+        self.counter_bits
+    }
+
+    /// Return the current approximate false positive probability which depends on the current
+    /// number of elements in the filter.
+    ///
+    /// A lookup compares the fingerprint of an element with the occupied cells of its candidate
+    /// buckets, each of which matches with a probability of *1 / 2^fingerprint_bits*.
+    ///
+    /// The probability is given as a value in the interval [0,1]
+    pub fn approximate_current_false_positive_probability(&self) -> f64 {
+        let compared_fingerprints = (SUBTABLES * BUCKET_SIZE) as f64 * self.fill_ratio();
+        1.0 - math::powf(
+            1.0 - 1.0 / math::powf(2.0, self.fingerprint_bits() as f64),
+            compared_fingerprints,
+        )
+    }
+
+    /// Find the cell that stores *fingerprint* in one of the candidate buckets.
+    fn find(&self, bucket: usize, fingerprint: u32) -> Option<usize> {
+        (0..SUBTABLES).find_map(|subtable| {
+            let index = self.bucket_index(subtable, bucket, fingerprint);
+            (index * BUCKET_SIZE..(index + 1) * BUCKET_SIZE).find(|&cell| {
+                self.counter(cell) > 0 && self.cells.get(cell) >> self.counter_bits == fingerprint
+            })
+        })
+    }
+
+    fn load(&self, index: usize) -> usize {
+        (index * BUCKET_SIZE..(index + 1) * BUCKET_SIZE)
+            .take_while(|&cell| self.counter(cell) > 0)
+            .count()
+    }
+
+    fn counter(&self, cell: usize) -> u32 {
+        self.cells.get(cell) & self.max_counter()
+    }
+
+    fn max_counter(&self) -> u32 {
+        ((1u64 << self.counter_bits) - 1) as u32
+    }
+
+    /// Split the hash of *data* into a bucket and a fingerprint. Together, they identify the
+    /// element, and every subtable maps them to a different candidate bucket.
+    fn bucket_and_fingerprint<T>(&self, data: &T) -> (usize, u32)
+    where
+        T: Hash,
+    {
+        let mut hasher = H::default();
+        data.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let mask = (1u64 << self.fingerprint_bits()) - 1;
+        let fingerprint = (hash & mask) as u32;
+        let bucket = ((hash as u128 * self.buckets_per_subtable as u128) >> 64) as usize;
+        (bucket, fingerprint)
+    }
+
+    /// Index of the candidate bucket in *subtable*.
+    ///
+    /// The offset only depends on the fingerprint, so that a fingerprint stored in a bucket
+    /// identifies the bucket and fingerprint of the element, and therefore all its candidate
+    /// buckets.
+    fn bucket_index(&self, subtable: usize, bucket: usize, fingerprint: u32) -> usize {
+        // Finalizer of SplitMix64, which makes the offsets of the subtables independent
+        let mut offset =
+            (((fingerprint as u64) << 8) | subtable as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+        offset = (offset ^ (offset >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        offset = (offset ^ (offset >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        offset ^= offset >> 31;
+        let offset = ((offset as u128 * self.buckets_per_subtable as u128) >> 64) as usize;
+        subtable * self.buckets_per_subtable + (bucket + offset) % self.buckets_per_subtable
+    }
+}
+
+impl<H> Debug for DLeftCountingFilter<H>
+where
+    H: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DLeftCountingFilter{{buckets: {}, fingerprint_bits: {}, counter_bits: {}, length: {}}}",
+            SUBTABLES * self.buckets_per_subtable,
+            self.fingerprint_bits(),
+            self.counter_bits,
+            self.occupied_cells
+        )
+    }
+}
+
+/// Two d-left counting filters are equal if they have the same configuration and store the same
+/// fingerprints and counters in the same cells.
+///
+/// Filters containing the same elements are not necessarily equal, because the cell of a
+/// fingerprint depends on the order of insertions and removals.
+impl<H> PartialEq for DLeftCountingFilter<H>
+where
+    H: Hasher + Default,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.counter_bits == other.counter_bits
+            && self.buckets_per_subtable == other.buckets_per_subtable
+            && self.cells == other.cells
+    }
+}
+
+impl<H> Eq for DLeftCountingFilter<H> where H: Hasher + Default {}
+
+impl<H> BloomFilter for DLeftCountingFilter<H>
+where
+    H: Hasher + Default,
+{
+    /// Insert data into the filter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if all candidate buckets of *data* are full. Use *try_insert* to handle this case.
+    fn insert<T>(&mut self, data: &T)
+    where
+        T: Hash,
+    {
+        if let Err(error) = self.try_insert(data) {
+            panic!("{}", error);
+        }
+    }
+
+    fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        let (bucket, fingerprint) = self.bucket_and_fingerprint(data);
+        self.find(bucket, fingerprint).is_some()
+    }
+}
@@ -0,0 +1,292 @@
+use std::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll},
+};
+
+use futures_util::future::{self, Either, Ready};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{BloomFilter, RotatingBloomFilter, StableXxh3Hasher, StableXxh64Hasher};
+
+/// Behavior of a BloomDedup service for a request whose idempotency key has recently been seen.
+pub enum OnDuplicate<Req, Res> {
+    /// Pass the request on to the inner service after calling the function with it, e.g., to
+    /// insert a marker into the extensions of an *http::Request*.
+    Mark(fn(&mut Req)),
+    /// Do not call the inner service, and respond with the result of the function instead, e.g.,
+    /// a response with the status 409 Conflict.
+    Reject(fn(Req) -> Res),
+}
+
+impl<Req, Res> Clone for OnDuplicate<Req, Res> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Req, Res> Copy for OnDuplicate<Req, Res> {}
+
+impl<Req, Res> Debug for OnDuplicate<Req, Res> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OnDuplicate::Mark(_) => write!(f, "Mark"),
+            OnDuplicate::Reject(_) => write!(f, "Reject"),
+        }
+    }
+}
+
+/// Tower layer that detects requests whose idempotency key has recently been seen, e.g., to drop
+/// retried webhooks or payment requests in an axum router.
+///
+/// *extract_key* returns the idempotency key of a request, or none for requests that are never
+/// duplicates. The keys of the requests are recorded in a RotatingBloomFilter that is shared by
+/// all services created by the layer, so a key is remembered for as long as the filter keeps its
+/// generation. Requests whose key is contained in the filter are handled according to the
+/// *OnDuplicate* policy; all other requests are passed on unchanged.
+///
+/// Like any bloom filter, the filter has false positives: a request with a new key is treated as
+/// a duplicate with about the false positive probability of the filter. Choose the capacity and
+/// false positive probability of the generations for the number of requests per rotation
+/// interval.
+///
+/// BloomDedupLayer is only available with the *tower* feature.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{
+///     BloomDedupLayer,OnDuplicate,RotatingBloomFilter,StableXxh3Hasher,StableXxh64Hasher
+/// };
+/// use futures_util::{future::{self, Ready}, FutureExt};
+/// use std::{convert::Infallible, task::{Context, Poll}, time::Duration};
+/// use tower_layer::Layer;
+/// use tower_service::Service;
+///
+/// struct Request {
+///     idempotency_key: Option<String>,
+///     body: &'static str,
+/// }
+///
+/// // Service that responds with the body of every request
+/// struct Echo;
+///
+/// impl Service<Request> for Echo {
+///     type Response = String;
+///     type Error = Infallible;
+///     type Future = Ready<Result<String, Infallible>>;
+///
+///     fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+///         Poll::Ready(Ok(()))
+///     }
+///
+///     fn call(&mut self, request: Request) -> Self::Future {
+///         future::ready(Ok(request.body.to_string()))
+///     }
+/// }
+///
+/// fn main() {
+///     // Remember idempotency keys for 23-24 hours
+///     let filter: RotatingBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+///         RotatingBloomFilter::new(24, 100_000, 0.0001)
+///             .with_rotation_interval(Duration::from_secs(60 * 60));
+///     let layer = BloomDedupLayer::new(
+///         filter,
+///         |request: &Request| request.idempotency_key.clone(),
+///         OnDuplicate::Reject(|_: Request| "duplicate".to_string()),
+///     );
+///     let mut service = layer.layer(Echo);
+///
+///     let mut send = |key: &str, body| {
+///         let request = Request { idempotency_key: Some(key.to_string()), body };
+///         service.call(request).now_or_never().unwrap().unwrap()
+///     };
+///     assert_eq!("charge", send("c5a1", "charge"));
+///     assert_eq!("duplicate", send("c5a1", "charge"));
+///     assert_eq!("refund", send("9e2f", "refund"));
+/// }
+/// ```
+pub struct BloomDedupLayer<E, K, Req, Res, H1 = StableXxh3Hasher, H2 = StableXxh64Hasher>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    filter: Arc<Mutex<RotatingBloomFilter<H1, H2>>>,
+    extract_key: E,
+    on_duplicate: OnDuplicate<Req, Res>,
+    _phantom: PhantomData<fn() -> K>,
+}
+
+impl<E, K, Req, Res, H1, H2> BloomDedupLayer<E, K, Req, Res, H1, H2>
+where
+    E: Fn(&Req) -> Option<K>,
+    K: Hash,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Initialize a new instance of BloomDedupLayer that records the keys returned by
+    /// *extract_key* in *filter* and handles duplicates according to *on_duplicate*.
+    pub fn new(
+        filter: RotatingBloomFilter<H1, H2>,
+        extract_key: E,
+        on_duplicate: OnDuplicate<Req, Res>,
+    ) -> Self {
+        Self {
+            filter: Arc::new(Mutex::new(filter)),
+            extract_key,
+            on_duplicate,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The filter that is shared by all services created by the layer, e.g., to clear it or to
+    /// monitor its false positive probability.
+    pub fn filter(&self) -> &Arc<Mutex<RotatingBloomFilter<H1, H2>>> {
+        &self.filter
+    }
+}
+
+impl<E, K, Req, Res, H1, H2> Clone for BloomDedupLayer<E, K, Req, Res, H1, H2>
+where
+    E: Clone,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn clone(&self) -> Self {
+        Self {
+            filter: Arc::clone(&self.filter),
+            extract_key: self.extract_key.clone(),
+            on_duplicate: self.on_duplicate,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<E, K, Req, Res, H1, H2> Debug for BloomDedupLayer<E, K, Req, Res, H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "BloomDedupLayer{{on_duplicate: {:?}, filter: {:?}}}",
+            self.on_duplicate, self.filter
+        )
+    }
+}
+
+impl<S, E, K, Req, Res, H1, H2> Layer<S> for BloomDedupLayer<E, K, Req, Res, H1, H2>
+where
+    E: Clone,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    type Service = BloomDedup<S, E, K, Req, Res, H1, H2>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BloomDedup {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Service created by BloomDedupLayer, see there.
+pub struct BloomDedup<S, E, K, Req, Res, H1 = StableXxh3Hasher, H2 = StableXxh64Hasher>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    inner: S,
+    layer: BloomDedupLayer<E, K, Req, Res, H1, H2>,
+}
+
+impl<S, E, K, Req, Res, H1, H2> BloomDedup<S, E, K, Req, Res, H1, H2>
+where
+    E: Fn(&Req) -> Option<K>,
+    K: Hash,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// The wrapped service.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Record the key of *request* and return whether it has been seen before.
+    fn is_duplicate(&self, request: &Req) -> bool {
+        let key = match (self.layer.extract_key)(request) {
+            Some(key) => key,
+            None => return false,
+        };
+        // A panic while the filter was locked cannot leave it inconsistent
+        let mut filter = self
+            .layer
+            .filter
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        filter.check_and_insert(&key)
+    }
+}
+
+impl<S, E, K, Req, Res, H1, H2> Clone for BloomDedup<S, E, K, Req, Res, H1, H2>
+where
+    S: Clone,
+    E: Clone,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            layer: self.layer.clone(),
+        }
+    }
+}
+
+impl<S, E, K, Req, Res, H1, H2> Debug for BloomDedup<S, E, K, Req, Res, H1, H2>
+where
+    S: Debug,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "BloomDedup{{inner: {:?}, layer: {:?}}}",
+            self.inner, self.layer
+        )
+    }
+}
+
+impl<S, E, K, Req, Res, H1, H2> Service<Req> for BloomDedup<S, E, K, Req, Res, H1, H2>
+where
+    S: Service<Req, Response = Res>,
+    E: Fn(&Req) -> Option<K>,
+    K: Hash,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    type Response = Res;
+    type Error = S::Error;
+    type Future = Either<S::Future, Ready<Result<Res, S::Error>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Req) -> Self::Future {
+        if self.is_duplicate(&request) {
+            match self.layer.on_duplicate {
+                OnDuplicate::Mark(mark) => mark(&mut request),
+                OnDuplicate::Reject(reject) => {
+                    return Either::Right(future::ready(Ok(reject(request))))
+                }
+            }
+        }
+        Either::Left(self.inner.call(request))
+    }
+}
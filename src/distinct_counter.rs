@@ -0,0 +1,249 @@
+use alloc::{vec, vec::Vec};
+use core::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use crate::math;
+
+/// Smallest and largest supported precision, i.e., number of hash bits used to select a register.
+const MIN_PRECISION: u8 = 4;
+const MAX_PRECISION: u8 = 18;
+
+/// Precision of a counter created with *DistinctCounter::default*.
+const DEFAULT_PRECISION: u8 = 14;
+
+/// HyperLogLog counter that approximates the number of distinct elements, as described by Flajolet
+/// et al.:
+///
+/// > Philippe Flajolet, Éric Fusy, Olivier Gandouet, and Frédéric Meunier. 2007. HyperLogLog: the
+/// > analysis of a near-optimal cardinality estimation algorithm. In AofA: Analysis of Algorithms,
+/// > DMTCS Proceedings, 137–156.
+///
+/// The counter uses *2^precision* registers of one byte each, and its relative standard error is
+/// about *1.04 / sqrt(2^precision)*, independent of the number of elements. Use it next to a bloom
+/// filter if the number of distinct elements must be known accurately, since the approximate
+/// element count of a bloom filter degrades as the filter fills up.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,DistinctCounter,StableBloomFilterDefault,StableXxh3Hasher};
+///
+/// fn main() {
+///     let mut filter = StableBloomFilterDefault::new(1_000, 0.01);
+///     let mut counter: DistinctCounter<StableXxh3Hasher> = DistinctCounter::new(0.01);
+///
+///     // The filter is saturated far beyond its capacity, but the counter is not
+///     for i in 0..100_000 {
+///         filter.insert(&i);
+///         counter.insert(&i);
+///     }
+///     assert!((counter.approximate_distinct_count() - 100_000.0).abs() < 3_000.0);
+/// }
+/// ```
+pub struct DistinctCounter<H>
+where
+    H: Hasher + Default,
+{
+    registers: Vec<u8>,
+    precision: u8,
+    // Phantom data for saving which concrete Hasher type is used
+    _phantom: PhantomData<fn() -> H>,
+}
+
+impl<H> DistinctCounter<H>
+where
+    H: Hasher + Default,
+{
+    /// Initialize a new instance of DistinctCounter with the smallest number of registers that
+    /// achieves a relative standard error of at most *desired_relative_error*.
+    ///
+    /// The precision is limited to the range from 4 to 18, i.e., the relative standard error is
+    /// at least about 0.002 and at most 0.26.
+    pub fn new(desired_relative_error: f64) -> Self {
+        let register_count = (1.04 / desired_relative_error) * (1.04 / desired_relative_error);
+        let precision = math::ceil(math::ln(register_count) / math::ln(2.0));
+        Self::with_precision(precision.clamp(MIN_PRECISION as f64, MAX_PRECISION as f64) as u8)
+    }
+
+    /// Initialize a new instance of DistinctCounter with *2^precision* registers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if *precision* is less than 4 or greater than 18.
+    pub fn with_precision(precision: u8) -> Self {
+        if !(MIN_PRECISION..=MAX_PRECISION).contains(&precision) {
+            panic!(
+                "the precision of a distinct counter must be between {} and {}",
+                MIN_PRECISION, MAX_PRECISION
+            );
+        }
+        Self {
+            registers: vec![0; 1 << precision],
+            precision,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Count *data*.
+    pub fn insert<T>(&mut self, data: &T)
+    where
+        T: Hash,
+    {
+        let mut hasher = H::default();
+        data.hash(&mut hasher);
+        self.insert_hash64(hasher.finish());
+    }
+
+    /// Count all elements of *iter*.
+    pub fn insert_all<T, I>(&mut self, iter: I)
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        for data in iter {
+            self.insert(&data);
+        }
+    }
+
+    /// Count an element for which *H* returns *hash*, e.g., because the hash has already been
+    /// computed for a bloom filter.
+    pub fn insert_hash64(&mut self, hash: u64) {
+        let index = (hash >> (64 - self.precision)) as usize;
+        // Position of the first one bit after the index bits, bounded by the number of bits left
+        let rank = ((hash << self.precision).leading_zeros()).min(64 - self.precision as u32) + 1;
+        let register = &mut self.registers[index];
+        *register = (*register).max(rank as u8);
+    }
+
+    /// Approximate number of distinct elements counted.
+    ///
+    /// Small counts are approximated with linear counting, which is more accurate as long as some
+    /// registers are still empty.
+    pub fn approximate_distinct_count(&self) -> f64 {
+        let register_count = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / register_count),
+        };
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&register| 1.0 / (1u64 << register) as f64)
+            .sum();
+        let estimate = alpha * register_count * register_count / sum;
+
+        let empty_registers = self
+            .registers
+            .iter()
+            .filter(|&&register| register == 0)
+            .count();
+        if estimate <= 2.5 * register_count && empty_registers > 0 {
+            register_count * math::ln(register_count / empty_registers as f64)
+        } else {
+            estimate
+        }
+    }
+
+    /// Relative standard error of *approximate_distinct_count*.
+    pub fn relative_standard_error(&self) -> f64 {
+        1.04 / math::sqrt(self.registers.len() as f64)
+    }
+
+    /// Number of hash bits used to select a register.
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    /// Number of registers, which is also the number of bytes used.
+    pub fn register_count(&self) -> usize {
+        self.registers.len()
+    }
+
+    /// Whether no elements have been counted.
+    pub fn is_empty(&self) -> bool {
+        self.registers.iter().all(|&register| register == 0)
+    }
+
+    /// Remove all counted elements.
+    pub fn clear(&mut self) {
+        self.registers.iter_mut().for_each(|register| *register = 0);
+    }
+
+    /// Creates a counter of all elements counted by this counter or 'other'.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the precision of 'self' and 'other' differ.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = Self {
+            registers: self.registers.clone(),
+            precision: self.precision,
+            _phantom: PhantomData,
+        };
+        union.union_with(other);
+        union
+    }
+
+    /// Add all elements counted by 'other' to this counter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the precision of 'self' and 'other' differ.
+    pub fn union_with(&mut self, other: &Self) {
+        if self.precision != other.precision {
+            panic!("unable to union distinct counters with different configurations");
+        }
+        for (register, &other) in self.registers.iter_mut().zip(other.registers.iter()) {
+            *register = (*register).max(other);
+        }
+    }
+}
+
+impl<H> Debug for DistinctCounter<H>
+where
+    H: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DistinctCounter{{precision: {}, approximate_distinct_count: {}}}",
+            self.precision,
+            self.approximate_distinct_count()
+        )
+    }
+}
+
+/// A counter with *2^14* registers, i.e., 16 KiB and a relative standard error of about 0.8%.
+impl<H> Default for DistinctCounter<H>
+where
+    H: Hasher + Default,
+{
+    fn default() -> Self {
+        Self::with_precision(DEFAULT_PRECISION)
+    }
+}
+
+impl<H> PartialEq for DistinctCounter<H>
+where
+    H: Hasher + Default,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.precision == other.precision && self.registers == other.registers
+    }
+}
+
+impl<H> Eq for DistinctCounter<H> where H: Hasher + Default {}
+
+impl<T, H> Extend<T> for DistinctCounter<H>
+where
+    T: Hash,
+    H: Hasher + Default,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.insert_all(iter);
+    }
+}
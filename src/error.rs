@@ -0,0 +1,210 @@
+use core::fmt::{self, Display};
+
+#[cfg(feature = "interop-redisbloom")]
+use crate::InvalidRedisBloomChunk;
+#[cfg(feature = "interop-bloomfilter")]
+use crate::InvalidSipBloomFilter;
+#[cfg(feature = "std")]
+use crate::ReadFilterError;
+use crate::{
+    test_vectors::TestVectorMismatch, CapacityExceeded, CuckooFilterFull, DLeftCountingFilterFull,
+    FilterCascadeConflict, InvalidBitsetDelta, InvalidFalsePositiveProbability,
+    InvalidFilterParams, InvalidGolombCodedSet, MergeError, QuotientFilterFull,
+};
+
+/// Error of any fallible operation of this crate, e.g., to handle the errors of all filters that
+/// are embedded in an application with a single type.
+///
+/// The checked operations of Bitset, such as *Bitset::try_set* and *Bitset::try_union*, return
+/// BloomFilterError directly. The errors of the other fallible operations convert into it, so
+/// they can be propagated with `?`.
+///
+/// New variants may be added in minor releases.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{Bitset,BloomFilterError};
+///
+/// fn merge(target: &mut Bitset, bits: &Bitset) -> Result<(), BloomFilterError> {
+///     target.try_union_with(bits)?;
+///     target.try_set(0, true)?;
+///     Ok(())
+/// }
+///
+/// let mut bitset = Bitset::new(100);
+/// assert!(merge(&mut bitset, &Bitset::new(100)).is_ok());
+/// assert!(matches!(
+///     merge(&mut bitset, &Bitset::new(50)),
+///     Err(BloomFilterError::LengthMismatch { left: 100, right: 50 })
+/// ));
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BloomFilterError {
+    /// A bit index is not less than the length of the bitset.
+    IndexOutOfBounds { index: usize, len: usize },
+    /// Two bitsets that are combined have different lengths.
+    LengthMismatch { left: usize, right: usize },
+    /// See *CapacityExceeded*.
+    CapacityExceeded,
+    /// See *CuckooFilterFull*.
+    CuckooFilterFull,
+    /// See *QuotientFilterFull*.
+    QuotientFilterFull,
+    /// See *DLeftCountingFilterFull*.
+    DLeftCountingFilterFull,
+    /// See *InvalidFalsePositiveProbability*.
+    InvalidFalsePositiveProbability(f64),
+    /// See *InvalidFilterParams*.
+    InvalidFilterParams(InvalidFilterParams),
+    /// See *InvalidGolombCodedSet*.
+    InvalidGolombCodedSet,
+    /// See *InvalidBitsetDelta*.
+    InvalidBitsetDelta,
+    /// See *MergeError*.
+    Merge(MergeError),
+    /// See *FilterCascadeConflict*.
+    FilterCascadeConflict,
+    /// See *test_vectors::TestVectorMismatch*.
+    TestVectorMismatch(TestVectorMismatch),
+    /// See *ReadFilterError*. Only available with the *std* feature.
+    #[cfg(feature = "std")]
+    Read(ReadFilterError),
+    /// See *InvalidSipBloomFilter*. Only available with the *interop-bloomfilter* feature.
+    #[cfg(feature = "interop-bloomfilter")]
+    InvalidSipBloomFilter,
+    /// See *InvalidRedisBloomChunk*. Only available with the *interop-redisbloom* feature.
+    #[cfg(feature = "interop-redisbloom")]
+    InvalidRedisBloomChunk,
+}
+
+impl Display for BloomFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BloomFilterError::IndexOutOfBounds { index, len } => write!(
+                f,
+                "index out of bounds: the len is {} but the index is {}",
+                len, index
+            ),
+            BloomFilterError::LengthMismatch { left, right } => {
+                write!(f, "bitsets have different lengths: {} and {}", left, right)
+            }
+            BloomFilterError::CapacityExceeded => CapacityExceeded.fmt(f),
+            BloomFilterError::CuckooFilterFull => CuckooFilterFull.fmt(f),
+            BloomFilterError::QuotientFilterFull => QuotientFilterFull.fmt(f),
+            BloomFilterError::DLeftCountingFilterFull => DLeftCountingFilterFull.fmt(f),
+            BloomFilterError::InvalidFalsePositiveProbability(probability) => {
+                InvalidFalsePositiveProbability(*probability).fmt(f)
+            }
+            BloomFilterError::InvalidFilterParams(error) => error.fmt(f),
+            BloomFilterError::InvalidGolombCodedSet => InvalidGolombCodedSet.fmt(f),
+            BloomFilterError::InvalidBitsetDelta => InvalidBitsetDelta.fmt(f),
+            BloomFilterError::Merge(error) => error.fmt(f),
+            BloomFilterError::FilterCascadeConflict => FilterCascadeConflict.fmt(f),
+            BloomFilterError::TestVectorMismatch(mismatch) => mismatch.fmt(f),
+            #[cfg(feature = "std")]
+            BloomFilterError::Read(error) => error.fmt(f),
+            #[cfg(feature = "interop-bloomfilter")]
+            BloomFilterError::InvalidSipBloomFilter => InvalidSipBloomFilter.fmt(f),
+            #[cfg(feature = "interop-redisbloom")]
+            BloomFilterError::InvalidRedisBloomChunk => InvalidRedisBloomChunk.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BloomFilterError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BloomFilterError::Read(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<CapacityExceeded> for BloomFilterError {
+    fn from(_: CapacityExceeded) -> Self {
+        BloomFilterError::CapacityExceeded
+    }
+}
+
+impl From<CuckooFilterFull> for BloomFilterError {
+    fn from(_: CuckooFilterFull) -> Self {
+        BloomFilterError::CuckooFilterFull
+    }
+}
+
+impl From<QuotientFilterFull> for BloomFilterError {
+    fn from(_: QuotientFilterFull) -> Self {
+        BloomFilterError::QuotientFilterFull
+    }
+}
+
+impl From<DLeftCountingFilterFull> for BloomFilterError {
+    fn from(_: DLeftCountingFilterFull) -> Self {
+        BloomFilterError::DLeftCountingFilterFull
+    }
+}
+
+impl From<InvalidFalsePositiveProbability> for BloomFilterError {
+    fn from(error: InvalidFalsePositiveProbability) -> Self {
+        BloomFilterError::InvalidFalsePositiveProbability(error.0)
+    }
+}
+
+impl From<InvalidFilterParams> for BloomFilterError {
+    fn from(error: InvalidFilterParams) -> Self {
+        BloomFilterError::InvalidFilterParams(error)
+    }
+}
+
+impl From<InvalidGolombCodedSet> for BloomFilterError {
+    fn from(_: InvalidGolombCodedSet) -> Self {
+        BloomFilterError::InvalidGolombCodedSet
+    }
+}
+
+impl From<InvalidBitsetDelta> for BloomFilterError {
+    fn from(_: InvalidBitsetDelta) -> Self {
+        BloomFilterError::InvalidBitsetDelta
+    }
+}
+
+impl From<MergeError> for BloomFilterError {
+    fn from(error: MergeError) -> Self {
+        BloomFilterError::Merge(error)
+    }
+}
+
+impl From<FilterCascadeConflict> for BloomFilterError {
+    fn from(_: FilterCascadeConflict) -> Self {
+        BloomFilterError::FilterCascadeConflict
+    }
+}
+
+impl From<TestVectorMismatch> for BloomFilterError {
+    fn from(mismatch: TestVectorMismatch) -> Self {
+        BloomFilterError::TestVectorMismatch(mismatch)
+    }
+}
+
+#[cfg(feature = "interop-bloomfilter")]
+impl From<InvalidSipBloomFilter> for BloomFilterError {
+    fn from(_: InvalidSipBloomFilter) -> Self {
+        BloomFilterError::InvalidSipBloomFilter
+    }
+}
+
+#[cfg(feature = "interop-redisbloom")]
+impl From<InvalidRedisBloomChunk> for BloomFilterError {
+    fn from(_: InvalidRedisBloomChunk) -> Self {
+        BloomFilterError::InvalidRedisBloomChunk
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<ReadFilterError> for BloomFilterError {
+    fn from(error: ReadFilterError) -> Self {
+        BloomFilterError::Read(error)
+    }
+}
@@ -0,0 +1,124 @@
+use core::{
+    fmt::{self, Debug},
+    hash::Hash,
+};
+
+use crate::BloomFilter;
+
+/// Bloom filter that counts the inserted elements exactly instead of estimating their number from
+/// the bits that are set.
+///
+/// Every insertion of an element that has not been contained before increments the count, so
+/// inserting the same element again does not change it. Hence, *inserted_count* is the number of
+/// distinct inserted elements, except for elements that were false positives when they were
+/// inserted. Up to the desired capacity, these are at most about a
+/// *desired_false_positive_probability* share of the elements.
+///
+/// In contrast, *approximate_element_count* of a filter is a statistical estimate from the number
+/// of set bits. New elements set fewer and fewer new bits the fuller the filter gets, so the error
+/// of the estimate grows once more than about half of the bits are set (see
+/// *approximate_element_count_bounds*), and the estimate is infinite for a full filter. Beyond the
+/// desired capacity, more and more elements are false positives when they are inserted, so both
+/// numbers should be taken with a grain of salt.
+///
+/// Elements that have been inserted into the filter before it has been wrapped are not counted.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,ExactCountBloomFilter,StableBloomFilterDefault};
+///
+/// fn main() {
+///     let mut filter = ExactCountBloomFilter::new(StableBloomFilterDefault::new(1_000, 0.001));
+///     filter.insert_all(0..100);
+///     filter.insert_all(0..100);
+///     filter.insert(&"Hello!");
+///
+///     assert_eq!(101, filter.inserted_count());
+///     assert_eq!(true, filter.contains(&"Hello!"));
+/// }
+/// ```
+pub struct ExactCountBloomFilter<F>
+where
+    F: BloomFilter,
+{
+    filter: F,
+    inserted_count: usize,
+}
+
+impl<F> ExactCountBloomFilter<F>
+where
+    F: BloomFilter,
+{
+    /// Initialize a new instance of ExactCountBloomFilter that counts the elements inserted into
+    /// *filter* from now on.
+    pub fn new(filter: F) -> Self {
+        Self {
+            filter,
+            inserted_count: 0,
+        }
+    }
+
+    /// Number of inserted elements that had not been contained before their insertion.
+    pub fn inserted_count(&self) -> usize {
+        self.inserted_count
+    }
+
+    /// The wrapped filter.
+    pub fn filter(&self) -> &F {
+        &self.filter
+    }
+
+    /// Return the wrapped filter, dropping the count.
+    pub fn into_inner(self) -> F {
+        self.filter
+    }
+}
+
+impl<F> Debug for ExactCountBloomFilter<F>
+where
+    F: BloomFilter + Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ExactCountBloomFilter{{inserted_count: {}, filter: {:?}}}",
+            self.inserted_count, self.filter
+        )
+    }
+}
+
+impl<F> BloomFilter for ExactCountBloomFilter<F>
+where
+    F: BloomFilter,
+{
+    /// Insert data into the filter, and count it if it has not been contained before.
+    ///
+    /// The element is inserted in any case, so filters that store duplicates, e.g.,
+    /// CountingBloomFilter, still record every insertion.
+    fn insert<T>(&mut self, data: &T)
+    where
+        T: Hash,
+    {
+        if !self.filter.contains(data) {
+            self.inserted_count += 1;
+        }
+        self.filter.insert(data);
+    }
+
+    fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        self.filter.contains(data)
+    }
+}
+
+impl<T, F> Extend<T> for ExactCountBloomFilter<F>
+where
+    T: Hash,
+    F: BloomFilter,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.insert_all(iter);
+    }
+}
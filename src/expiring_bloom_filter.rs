@@ -0,0 +1,228 @@
+use std::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    io,
+    time::Duration,
+};
+
+use crate::{
+    rotating_bloom_filter::invalid_data, BloomFilter, ReadFilterError, RotatingBloomFilter,
+};
+
+/// Bloom filter that only contains the elements that have been inserted within a time-to-live,
+/// e.g., to remove duplicates among the requests of the last minutes.
+///
+/// An ExpiringBloomFilter is a RotatingBloomFilter whose rotation interval is derived from the
+/// time-to-live *ttl*: with *number_of_generations* generations, the generations rotate every
+/// *ttl / (number_of_generations - 1)*. An element is therefore contained for at least *ttl* after
+/// it has been inserted, i.e., *contains* never misses an element that has been inserted within
+/// the time-to-live. It expires at most *ttl / (number_of_generations - 1)* later, so more
+/// generations make expiration more precise at the cost of more memory.
+///
+/// Every generation contributes to the false positive probability, see RotatingBloomFilter.
+///
+/// ExpiringBloomFilter is only available with the *std* feature.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,ExpiringBloomFilter};
+/// use ahash::AHasher;
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::time::Duration;
+///
+/// fn main() {
+///     // Remove duplicates among the elements of the last 10 minutes, which expire after at most
+///     // 12 minutes
+///     let mut filter: ExpiringBloomFilter<AHasher, DefaultHasher> =
+///         ExpiringBloomFilter::new(Duration::from_secs(10 * 60), 6, 10_000, 0.0001);
+///
+///     filter.insert(&"Hello!");
+///     assert_eq!(true, filter.contains(&"Hello!"));
+/// }
+/// ```
+pub struct ExpiringBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    ttl: Duration,
+    filter: RotatingBloomFilter<H1, H2>,
+}
+
+impl<H1, H2> ExpiringBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Initialize a new instance of ExpiringBloomFilter whose elements expire after *ttl*, with
+    /// *number_of_generations* generations, each of which guarantees that the false positive rate
+    /// is less than *desired_false_positive_probability* for up to *desired_capacity* elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if number_of_generations < 2, desired_capacity == 0,
+    /// desired_false_positive_probability is not in the interval (0,1), or the rotation interval
+    /// *ttl / (number_of_generations - 1)* is zero.
+    pub fn new(
+        ttl: Duration,
+        number_of_generations: usize,
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self {
+        if number_of_generations < 2 {
+            panic!("an expiring bloom filter requires at least two generations");
+        }
+        // Round up, so that elements are never dropped before their time-to-live has passed
+        let interval = ttl
+            .as_nanos()
+            .div_ceil((number_of_generations - 1) as u128)
+            .min(u64::MAX as u128) as u64;
+        Self {
+            ttl,
+            filter: RotatingBloomFilter::new(
+                number_of_generations,
+                desired_capacity,
+                desired_false_positive_probability,
+            )
+            .with_rotation_interval(Duration::from_nanos(interval)),
+        }
+    }
+
+    /// Time-to-live of the elements.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Number of generations of the filter.
+    pub fn number_of_generations(&self) -> usize {
+        self.filter.number_of_generations()
+    }
+
+    /// Drop all generations whose elements have expired, and return their number.
+    ///
+    /// Expired elements are never contained, even if this method has not been called. *insert*
+    /// calls this method automatically to reuse the memory of expired generations.
+    pub fn expire(&mut self) -> usize {
+        self.filter.rotate_if_due()
+    }
+
+    /// Write the time-to-live in nanoseconds as a little-endian u64 and the generations in the
+    /// format of *RotatingBloomFilter::write_to* to *writer*, e.g., to resume the same window
+    /// after a restart.
+    pub fn write_to<W>(&self, mut writer: W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        writer.write_all(&(self.ttl.as_nanos().min(u64::MAX as u128) as u64).to_le_bytes())?;
+        self.filter.write_to(writer)
+    }
+
+    /// Read a filter that has been written with *write_to* from *reader*. The elements that have
+    /// expired since the filter has been written are dropped, see *RotatingBloomFilter::read_from*
+    /// for how the time of the last rotation is compared with the system clock.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of *RotatingBloomFilter::read_from*, and *ReadFilterError::Io* with
+    /// *io::ErrorKind::InvalidData* if the filter has less than two generations or no rotation
+    /// interval.
+    pub fn read_from<R>(mut reader: R) -> Result<Self, ReadFilterError>
+    where
+        R: io::Read,
+    {
+        let mut ttl = [0u8; 8];
+        reader.read_exact(&mut ttl)?;
+        let filter = RotatingBloomFilter::read_from(reader)?;
+        if filter.number_of_generations() < 2 || filter.rotation_interval().is_none() {
+            return Err(invalid_data(
+                "an expiring bloom filter requires at least two generations and a rotation interval",
+            ));
+        }
+        Ok(Self {
+            ttl: Duration::from_nanos(u64::from_le_bytes(ttl)),
+            filter,
+        })
+    }
+
+    /// Approximate number of elements stored that have not expired.
+    ///
+    /// See *RotatingBloomFilter::approximate_element_count* for details.
+    pub fn approximate_element_count(&self) -> f64 {
+        self.filter.approximate_element_count()
+    }
+
+    /// Return the current approximate false positive probability which depends on the current
+    /// number of elements that have not expired.
+    ///
+    /// The probability is given as a value in the interval [0,1]
+    pub fn approximate_current_false_positive_probability(&self) -> f64 {
+        self.filter.approximate_current_false_positive_probability()
+    }
+
+    /// Remove all elements.
+    pub fn clear(&mut self) {
+        self.filter.clear();
+    }
+}
+
+impl<H1, H2> Debug for ExpiringBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ExpiringBloomFilter{{ttl: {:?}, filter: {:?}}}",
+            self.ttl, self.filter
+        )
+    }
+}
+
+impl<H1, H2> BloomFilter for ExpiringBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Insert data, which expires after the time-to-live.
+    fn insert<T>(&mut self, data: &T)
+    where
+        T: Hash,
+    {
+        self.filter.insert(data);
+    }
+
+    /// Check whether data has been inserted and has not expired.
+    fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        self.filter.contains(data)
+    }
+
+    fn insert_all<T, I>(&mut self, iter: I)
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        self.filter.insert_all(iter);
+    }
+
+    fn check_and_insert<T>(&mut self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        self.filter.check_and_insert(data)
+    }
+}
+
+impl<T, H1, H2> Extend<T> for ExpiringBloomFilter<H1, H2>
+where
+    T: Hash,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.insert_all(iter);
+    }
+}
@@ -0,0 +1,226 @@
+//! C interface to StableBloomFilterDefault, e.g., to query the filters of a Rust service from C,
+//! C++, or Python services with the same hashing scheme and file format.
+//!
+//! The functions are exported unmangled with the prefix `bloom_filter_`, and the header
+//! `include/bloom_filter_simple.h` declares them. It is generated with
+//! [cbindgen](https://github.com/mozilla/cbindgen) from this module:
+//! ```sh
+//! cbindgen --config cbindgen.toml --output include/bloom_filter_simple.h
+//! ```
+//!
+//! Filters are opaque pointers that are created with *bloom_filter_create* or
+//! *bloom_filter_deserialize* and released with *bloom_filter_free*. Serialized filters are in the
+//! format of *KMBloomFilter::write_to* and are released with *bloom_filter_bytes_free*. No function
+//! panics across the interface: invalid arguments return a null pointer or false.
+//!
+//! Only available with the *ffi* feature. Build the crate as a C library with
+//! `cargo rustc --release --lib --features ffi --crate-type cdylib` (or `staticlib`).
+
+use std::{ptr, slice};
+
+use crate::StableBloomFilterDefault;
+
+/// Opaque handle of a StableBloomFilterDefault.
+pub struct FfiBloomFilter(StableBloomFilterDefault);
+
+/// Create a new, empty filter for up to *desired_capacity* elements with a false positive
+/// probability of less than *desired_false_positive_probability*.
+///
+/// Returns a null pointer if desired_capacity == 0 or if desired_false_positive_probability is not
+/// in the interval (0,1). Release the filter with *bloom_filter_free*.
+#[no_mangle]
+pub extern "C" fn bloom_filter_create(
+    desired_capacity: usize,
+    desired_false_positive_probability: f64,
+) -> *mut FfiBloomFilter {
+    match StableBloomFilterDefault::try_new(desired_capacity, desired_false_positive_probability) {
+        Ok(filter) => Box::into_raw(Box::new(FfiBloomFilter(filter))),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Release a filter. Null pointers are ignored.
+///
+/// # Safety
+///
+/// *filter* must be null or a filter returned by *bloom_filter_create* or
+/// *bloom_filter_deserialize* that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn bloom_filter_free(filter: *mut FfiBloomFilter) {
+    if !filter.is_null() {
+        drop(Box::from_raw(filter));
+    }
+}
+
+/// Insert the *length* bytes at *data* into *filter*, see *KMBloomFilter::insert_bytes*.
+///
+/// Does nothing if *filter* is null.
+///
+/// # Safety
+///
+/// *filter* must be null or a valid filter that is not accessed concurrently, and *data* must
+/// point to *length* readable bytes, or may be null if *length* is 0.
+#[no_mangle]
+pub unsafe extern "C" fn bloom_filter_insert_bytes(
+    filter: *mut FfiBloomFilter,
+    data: *const u8,
+    length: usize,
+) {
+    if let Some(filter) = filter.as_mut() {
+        filter.0.insert_bytes(bytes(data, length));
+    }
+}
+
+/// Check whether the *length* bytes at *data* are contained in *filter*, see
+/// *KMBloomFilter::contains_bytes*.
+///
+/// Returns false if *filter* is null.
+///
+/// # Safety
+///
+/// *filter* must be null or a valid filter that is not modified concurrently, and *data* must
+/// point to *length* readable bytes, or may be null if *length* is 0.
+#[no_mangle]
+pub unsafe extern "C" fn bloom_filter_contains_bytes(
+    filter: *const FfiBloomFilter,
+    data: *const u8,
+    length: usize,
+) -> bool {
+    match filter.as_ref() {
+        Some(filter) => filter.0.contains_bytes(bytes(data, length)),
+        None => false,
+    }
+}
+
+/// Serialize *filter* in the format of *KMBloomFilter::write_to* and store the number of bytes
+/// in *length*.
+///
+/// Returns a null pointer if *filter* or *length* is null. Release the bytes with
+/// *bloom_filter_bytes_free*.
+///
+/// # Safety
+///
+/// *filter* must be null or a valid filter that is not modified concurrently, and *length* must
+/// be null or point to a writable size.
+#[no_mangle]
+pub unsafe extern "C" fn bloom_filter_serialize(
+    filter: *const FfiBloomFilter,
+    length: *mut usize,
+) -> *mut u8 {
+    let (filter, length) = match (filter.as_ref(), length.as_mut()) {
+        (Some(filter), Some(length)) => (filter, length),
+        _ => return ptr::null_mut(),
+    };
+    let serialized = filter.0.to_bytes().into_boxed_slice();
+    *length = serialized.len();
+    Box::into_raw(serialized) as *mut u8
+}
+
+/// Restore a filter from the *length* bytes at *data*, as returned by *bloom_filter_serialize* or
+/// written by *KMBloomFilter::write_to* with the hashers of StableBloomFilterDefault.
+///
+/// Returns a null pointer if the bytes do not contain such a filter (see
+/// *KMBloomFilter::from_bytes*). Release the filter with *bloom_filter_free*.
+///
+/// # Safety
+///
+/// *data* must point to *length* readable bytes, or may be null if *length* is 0.
+#[no_mangle]
+pub unsafe extern "C" fn bloom_filter_deserialize(
+    data: *const u8,
+    length: usize,
+) -> *mut FfiBloomFilter {
+    match StableBloomFilterDefault::from_bytes(bytes(data, length)) {
+        Ok(filter) => Box::into_raw(Box::new(FfiBloomFilter(filter))),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Release bytes returned by *bloom_filter_serialize*. Null pointers are ignored.
+///
+/// # Safety
+///
+/// *data* must be null or bytes returned by *bloom_filter_serialize* that have not been released
+/// yet, and *length* must be the length that has been stored for them.
+#[no_mangle]
+pub unsafe extern "C" fn bloom_filter_bytes_free(data: *mut u8, length: usize) {
+    if !data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(data, length)));
+    }
+}
+
+/// The *length* bytes at *data*, which may be null if *length* is 0.
+unsafe fn bytes<'a>(data: *const u8, length: usize) -> &'a [u8] {
+    if length == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(data, length)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_round_trip_through_the_c_interface() {
+        unsafe {
+            let filter = bloom_filter_create(1_000, 0.001);
+            assert_eq!(false, filter.is_null());
+            bloom_filter_insert_bytes(filter, b"Hello!".as_ptr(), 6);
+            bloom_filter_insert_bytes(filter, ptr::null(), 0);
+            assert_eq!(
+                true,
+                bloom_filter_contains_bytes(filter, b"Hello!".as_ptr(), 6)
+            );
+            assert_eq!(true, bloom_filter_contains_bytes(filter, ptr::null(), 0));
+            assert_eq!(
+                false,
+                bloom_filter_contains_bytes(filter, b"Goodbye!".as_ptr(), 8)
+            );
+
+            let mut length = 0;
+            let serialized = bloom_filter_serialize(filter, &mut length);
+            assert_eq!(
+                (*filter).0.to_bytes(),
+                slice::from_raw_parts(serialized, length)
+            );
+            let restored = bloom_filter_deserialize(serialized, length);
+            assert_eq!(
+                true,
+                bloom_filter_contains_bytes(restored, b"Hello!".as_ptr(), 6)
+            );
+            // Truncated filters are rejected
+            assert_eq!(
+                true,
+                bloom_filter_deserialize(serialized, length - 1).is_null()
+            );
+
+            bloom_filter_bytes_free(serialized, length);
+            bloom_filter_free(restored);
+            bloom_filter_free(filter);
+        }
+    }
+
+    #[test]
+    fn invalid_arguments_return_null() {
+        unsafe {
+            assert_eq!(true, bloom_filter_create(0, 0.01).is_null());
+            assert_eq!(true, bloom_filter_create(1_000, f64::NAN).is_null());
+            assert_eq!(true, bloom_filter_deserialize(ptr::null(), 0).is_null());
+            let mut length = 0;
+            assert_eq!(
+                true,
+                bloom_filter_serialize(ptr::null(), &mut length).is_null()
+            );
+            assert_eq!(
+                false,
+                bloom_filter_contains_bytes(ptr::null(), b"Hello!".as_ptr(), 6)
+            );
+            bloom_filter_insert_bytes(ptr::null_mut(), b"Hello!".as_ptr(), 6);
+            bloom_filter_free(ptr::null_mut());
+            bloom_filter_bytes_free(ptr::null_mut(), 0);
+        }
+    }
+}
@@ -0,0 +1,395 @@
+use std::{
+    fmt::{self, Display},
+    hash::Hasher,
+    io::{self, Read, Write},
+};
+
+use xxhash_rust::xxh64::{xxh64, Xxh64};
+
+use crate::IndexLayout;
+
+/// Magic bytes at the start of every file written by *KMBloomFilter::write_to*.
+const MAGIC: [u8; 4] = *b"BFKM";
+
+/// Version of the file format written by *KMBloomFilter::write_to*.
+const FORMAT_VERSION: u32 = 1;
+
+/// Version of the file format written by *KMBloomFilter::write_to* for filters with the layout
+/// *IndexLayout::Global*. The format is the same, but the bits are addressed differently, so
+/// readers that only support the partitioned layout reject it as an unsupported version.
+const GLOBAL_LAYOUT_VERSION: u32 = 2;
+
+/// Size of the header in bytes: magic bytes, version, two hasher identifiers, and the number of
+/// hashers and bits per hasher.
+pub(crate) const HEADER_LENGTH: usize = 4 + 4 + 8 + 8 + 8 + 8;
+
+/// Size of the checksum that follows the bits of the filter in bytes.
+pub(crate) const CHECKSUM_LENGTH: usize = 8;
+
+/// Input that is hashed to identify a hasher. Two hashers with the same hash value for it are
+/// assumed to be the same hasher with the same seeds.
+const HASHER_PROBE: &[u8] = b"bloom_filter_simple";
+
+/// Magic bytes at the start of every gzip stream.
+#[cfg(feature = "gzip")]
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Magic bytes at the start of every Zstandard frame.
+#[cfg(feature = "zstd")]
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression of the filters written by *KMBloomFilter::save_compressed*.
+///
+/// Only available with the *gzip* or *zstd* feature, which enable the respective variant.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// gzip with the default compression level. Only available with the *gzip* feature.
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// Zstandard with the default compression level. Only available with the *zstd* feature.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+impl Compression {
+    /// The compression of a stream that starts with *magic*, or None if the stream is not
+    /// compressed with one of the enabled compressions.
+    pub(crate) fn detect(magic: &[u8; 4]) -> Option<Self> {
+        #[cfg(feature = "gzip")]
+        if magic.starts_with(&GZIP_MAGIC) {
+            return Some(Compression::Gzip);
+        }
+        #[cfg(feature = "zstd")]
+        if *magic == ZSTD_MAGIC {
+            return Some(Compression::Zstd);
+        }
+        None
+    }
+}
+
+/// Error returned when reading a filter that has been written with *KMBloomFilter::write_to*.
+#[derive(Debug)]
+pub enum ReadFilterError {
+    /// Reading failed, e.g., because the input ended before the whole filter has been read.
+    Io(io::Error),
+    /// The input does not start with the magic bytes of a filter file.
+    InvalidMagic,
+    /// The file has been written in a format version that is not supported.
+    UnsupportedVersion(u32),
+    /// The filter has been written with different hashers, or hashers with different seeds.
+    HasherMismatch,
+    /// The header describes a filter without any bits, or with more bits than can be addressed.
+    InvalidHeader {
+        number_of_hashers: u64,
+        bits_per_hasher: u64,
+    },
+    /// The file is longer or shorter than described by its header.
+    InvalidLength { expected: usize, found: usize },
+    /// The checksum of the bits of the filter does not match, e.g., because the file is corrupted.
+    ChecksumMismatch,
+}
+
+impl Display for ReadFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadFilterError::Io(error) => write!(f, "unable to read k-m bloom filter: {}", error),
+            ReadFilterError::InvalidMagic => write!(f, "not a k-m bloom filter file"),
+            ReadFilterError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported k-m bloom filter file format version {}",
+                version
+            ),
+            ReadFilterError::HasherMismatch => write!(
+                f,
+                "the k-m bloom filter has been written with different hashers"
+            ),
+            ReadFilterError::InvalidHeader {
+                number_of_hashers,
+                bits_per_hasher,
+            } => write!(
+                f,
+                "invalid k-m bloom filter header: {} hashers with {} bits each",
+                number_of_hashers, bits_per_hasher
+            ),
+            ReadFilterError::InvalidLength { expected, found } => write!(
+                f,
+                "expected a k-m bloom filter file of {} bytes, found {} bytes",
+                expected, found
+            ),
+            ReadFilterError::ChecksumMismatch => {
+                write!(f, "the checksum of the k-m bloom filter does not match")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReadFilterError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadFilterError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ReadFilterError {
+    fn from(error: io::Error) -> Self {
+        ReadFilterError::Io(error)
+    }
+}
+
+/// Write the header of a filter with the given number of hashers, bits per hasher, and index
+/// layout.
+pub(crate) fn write_header<H1, H2, W>(
+    writer: &mut W,
+    number_of_hashers: usize,
+    bits_per_hasher: usize,
+    layout: IndexLayout,
+) -> io::Result<()>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+    W: Write,
+{
+    let version = match layout {
+        IndexLayout::Partitioned => FORMAT_VERSION,
+        IndexLayout::Global => GLOBAL_LAYOUT_VERSION,
+    };
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&version.to_le_bytes())?;
+    writer.write_all(&hasher_id::<H1>().to_le_bytes())?;
+    writer.write_all(&hasher_id::<H2>().to_le_bytes())?;
+    writer.write_all(&(number_of_hashers as u64).to_le_bytes())?;
+    writer.write_all(&(bits_per_hasher as u64).to_le_bytes())
+}
+
+/// Read and validate the header written by *write_header* and return the number of hashers and
+/// the number of bits per hasher.
+///
+/// Headers of filters with the layout *IndexLayout::Global* are rejected as an unsupported
+/// version.
+pub(crate) fn read_header<H1, H2, R>(reader: &mut R) -> Result<(usize, usize), ReadFilterError>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+    R: Read,
+{
+    match read_layout_header::<H1, H2, R>(reader)? {
+        (number_of_hashers, bits_per_hasher, IndexLayout::Partitioned) => {
+            Ok((number_of_hashers, bits_per_hasher))
+        }
+        (_, _, IndexLayout::Global) => {
+            Err(ReadFilterError::UnsupportedVersion(GLOBAL_LAYOUT_VERSION))
+        }
+    }
+}
+
+/// Read and validate the header written by *write_header* and return the number of
+/// hashers, the number of bits per hasher, and the index layout.
+pub(crate) fn read_layout_header<H1, H2, R>(
+    reader: &mut R,
+) -> Result<(usize, usize, IndexLayout), ReadFilterError>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+    R: Read,
+{
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(ReadFilterError::InvalidMagic);
+    }
+    let layout = match read_u32(reader)? {
+        FORMAT_VERSION => IndexLayout::Partitioned,
+        GLOBAL_LAYOUT_VERSION => IndexLayout::Global,
+        version => return Err(ReadFilterError::UnsupportedVersion(version)),
+    };
+    let hasher_a = read_u64(reader)?;
+    let hasher_b = read_u64(reader)?;
+    if hasher_a != hasher_id::<H1>() || hasher_b != hasher_id::<H2>() {
+        return Err(ReadFilterError::HasherMismatch);
+    }
+
+    let number_of_hashers = read_u64(reader)?;
+    let bits_per_hasher = read_u64(reader)?;
+    match number_of_hashers.checked_mul(bits_per_hasher) {
+        Some(bit_count)
+            if number_of_hashers > 0 && bits_per_hasher > 0 && bit_count <= usize::MAX as u64 =>
+        {
+            Ok((number_of_hashers as usize, bits_per_hasher as usize, layout))
+        }
+        _ => Err(ReadFilterError::InvalidHeader {
+            number_of_hashers,
+            bits_per_hasher,
+        }),
+    }
+}
+
+/// Check that a filter file with the configuration of its header has *found* bytes, and return
+/// the number of bytes of its bits.
+pub(crate) fn check_length(
+    number_of_hashers: usize,
+    bits_per_hasher: usize,
+    found: usize,
+) -> Result<usize, ReadFilterError> {
+    let byte_count = (number_of_hashers * bits_per_hasher).div_ceil(8);
+    let expected = HEADER_LENGTH + byte_count + CHECKSUM_LENGTH;
+    if found == expected {
+        Ok(byte_count)
+    } else {
+        Err(ReadFilterError::InvalidLength { expected, found })
+    }
+}
+
+/// Checksum of the bits of a filter that is written after them.
+pub(crate) fn checksum(bytes: &[u8]) -> u64 {
+    xxh64(bytes, 0)
+}
+
+/// Hasher that computes the checksum of the bits of a filter incrementally, e.g., while they are
+/// written in chunks.
+pub(crate) fn checksum_hasher() -> Xxh64 {
+    Xxh64::new(0)
+}
+
+/// Compare the checksum in *expected*, as written after the bits of a filter, with the checksum of
+/// *bytes*.
+pub(crate) fn verify_checksum(bytes: &[u8], expected: &[u8]) -> Result<(), ReadFilterError> {
+    verify_digest(checksum(bytes), expected)
+}
+
+/// Compare the checksum in *expected* with the digest of a *checksum_hasher*.
+pub(crate) fn verify_digest(digest: u64, expected: &[u8]) -> Result<(), ReadFilterError> {
+    if digest.to_le_bytes()[..] == *expected {
+        Ok(())
+    } else {
+        Err(ReadFilterError::ChecksumMismatch)
+    }
+}
+
+/// Writer that forwards all bytes to *inner* and computes their checksum on the way.
+pub(crate) struct ChecksumWriter<W> {
+    inner: W,
+    hasher: Xxh64,
+}
+
+impl<W> ChecksumWriter<W>
+where
+    W: Write,
+{
+    pub(crate) fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: checksum_hasher(),
+        }
+    }
+
+    /// Write the checksum of all bytes written so far.
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        self.inner.write_all(&self.hasher.digest().to_le_bytes())
+    }
+}
+
+impl<W> Write for ChecksumWriter<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn hasher_id<H>() -> u64
+where
+    H: Hasher + Default,
+{
+    let mut hasher = H::default();
+    hasher.write(HASHER_PROBE);
+    hasher.finish()
+}
+
+fn read_u32<R>(reader: &mut R) -> io::Result<u32>
+where
+    R: Read,
+{
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R>(reader: &mut R) -> io::Result<u64>
+where
+    R: Read,
+{
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StableXxh3Hasher, StableXxh64Hasher};
+
+    #[test]
+    fn header_round_trip() {
+        let mut bytes = Vec::new();
+        write_header::<StableXxh3Hasher, StableXxh64Hasher, _>(
+            &mut bytes,
+            7,
+            1_000,
+            IndexLayout::Partitioned,
+        )
+        .unwrap();
+        assert_eq!(HEADER_LENGTH, bytes.len());
+
+        let header = read_header::<StableXxh3Hasher, StableXxh64Hasher, _>(&mut &bytes[..]);
+        assert_eq!((7, 1_000), header.unwrap());
+
+        // Swapped hashers are detected
+        let header = read_header::<StableXxh64Hasher, StableXxh3Hasher, _>(&mut &bytes[..]);
+        assert!(matches!(header, Err(ReadFilterError::HasherMismatch)));
+    }
+
+    #[test]
+    fn global_layout_header_round_trip() {
+        let mut bytes = Vec::new();
+        write_header::<StableXxh3Hasher, StableXxh64Hasher, _>(
+            &mut bytes,
+            7,
+            1_000,
+            IndexLayout::Global,
+        )
+        .unwrap();
+
+        let header = read_layout_header::<StableXxh3Hasher, StableXxh64Hasher, _>(&mut &bytes[..]);
+        assert_eq!((7, 1_000, IndexLayout::Global), header.unwrap());
+        // Readers of the partitioned layout reject the header
+        let header = read_header::<StableXxh3Hasher, StableXxh64Hasher, _>(&mut &bytes[..]);
+        assert!(matches!(
+            header,
+            Err(ReadFilterError::UnsupportedVersion(GLOBAL_LAYOUT_VERSION))
+        ));
+    }
+
+    #[test]
+    fn checksum_writer() {
+        let mut bytes = Vec::new();
+        let mut writer = ChecksumWriter::new(&mut bytes);
+        writer.write_all(b"payload").unwrap();
+        writer.finish().unwrap();
+
+        let (payload, expected) = bytes.split_at(bytes.len() - CHECKSUM_LENGTH);
+        assert_eq!(b"payload", payload);
+        assert!(verify_checksum(payload, expected).is_ok());
+        assert!(verify_checksum(b"pAyload", expected).is_err());
+    }
+}
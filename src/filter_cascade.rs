@@ -0,0 +1,242 @@
+use alloc::vec::Vec;
+use core::{
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+};
+
+#[cfg(feature = "std")]
+use crate::ReadFilterError;
+use crate::{math, BloomFilter, KMBloomFilter};
+
+/// Maximum number of levels of a filter cascade. Disjoint sets need about
+/// *log2(number of elements)* levels with the default false positive probabilities.
+const MAX_LEVELS: usize = 128;
+
+/// False positive probability of all levels but the first one, which minimizes the size of the
+/// cascade.
+const LEVEL_FALSE_POSITIVE_PROBABILITY: f64 = 0.5;
+
+/// Error returned by *FilterCascade::build* if the included and the excluded elements cannot be
+/// separated, because an element is contained in both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FilterCascadeConflict;
+
+impl Display for FilterCascadeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unable to build a filter cascade of elements that are both included and excluded"
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FilterCascadeConflict {}
+
+/// Chain of KMBloomFilter levels that answers membership queries without false positives for a
+/// known universe of elements, e.g., to publish a compact list of revoked certificates among all
+/// issued certificates (CRLite).
+///
+/// Level 0 contains the included elements. Level 1 contains the excluded elements that are false
+/// positives of level 0, level 2 the included elements that are false positives of level 1, and so
+/// on, until a level has no false positives. *contains* checks the levels in order, and the first
+/// level that does not contain an element decides: the element is included if the level has an
+/// odd index. Every level hashes the elements with its index as a salt, so the false positives of
+/// different levels are independent.
+///
+/// The answers are exact for all included and excluded elements the cascade has been built from.
+/// Other elements are contained with about the false positive probability of level 0.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{FilterCascade,StableXxh3Hasher,StableXxh64Hasher};
+///
+/// fn main() {
+///     let revoked: Vec<u64> = (0..1_000).collect();
+///     let valid: Vec<u64> = (1_000..100_000).collect();
+///     let cascade: FilterCascade<StableXxh3Hasher, StableXxh64Hasher> =
+///         FilterCascade::build(&revoked, &valid).unwrap();
+///
+///     assert_eq!(true, revoked.iter().all(|id| cascade.contains(id)));
+///     assert_eq!(false, valid.iter().any(|id| cascade.contains(id)));
+/// }
+/// ```
+pub struct FilterCascade<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    levels: Vec<KMBloomFilter<H1, H2>>,
+}
+
+impl<H1, H2> FilterCascade<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Build a cascade that contains exactly the *included* elements among the *included* and
+    /// *excluded* elements.
+    ///
+    /// The false positive probability of level 0 is *|included| / |excluded| \* sqrt(1/2)*, at
+    /// most 1/2, and the one of all other levels is 1/2, which minimizes the size of the cascade
+    /// (see Larisch et al., "CRLite: A Scalable System for Pushing All TLS Revocations to All
+    /// Browsers", 2017).
+    ///
+    /// # Errors
+    ///
+    /// Returns *FilterCascadeConflict* if an element is both included and excluded.
+    pub fn build<T>(included: &[T], excluded: &[T]) -> Result<Self, FilterCascadeConflict>
+    where
+        T: Hash,
+    {
+        let first_level_false_positive_probability = if included.is_empty() || excluded.is_empty() {
+            LEVEL_FALSE_POSITIVE_PROBABILITY
+        } else {
+            (included.len() as f64 / excluded.len() as f64 * math::sqrt(0.5))
+                .min(LEVEL_FALSE_POSITIVE_PROBABILITY)
+        };
+        Self::build_with_fp(
+            included,
+            excluded,
+            first_level_false_positive_probability,
+            LEVEL_FALSE_POSITIVE_PROBABILITY,
+        )
+    }
+
+    /// Build a cascade that contains exactly the *included* elements among the *included* and
+    /// *excluded* elements, with the false positive probability
+    /// *first_level_false_positive_probability* for level 0 and *level_false_positive_probability*
+    /// for all other levels.
+    ///
+    /// A lower false positive probability of level 0 makes the cascade larger, but lowers the
+    /// probability with which elements outside of the universe are contained.
+    ///
+    /// # Errors
+    ///
+    /// Returns *FilterCascadeConflict* if an element is both included and excluded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a false positive probability is not in the interval (0,1).
+    pub fn build_with_fp<T>(
+        included: &[T],
+        excluded: &[T],
+        first_level_false_positive_probability: impl Into<f64>,
+        level_false_positive_probability: impl Into<f64>,
+    ) -> Result<Self, FilterCascadeConflict>
+    where
+        T: Hash,
+    {
+        let level_false_positive_probability = level_false_positive_probability.into();
+        let mut false_positive_probability = first_level_false_positive_probability.into();
+        let mut inserted: Vec<&T> = included.iter().collect();
+        let mut checked: Vec<&T> = excluded.iter().collect();
+        let mut levels = Vec::new();
+        loop {
+            // Elements that are both included and excluded are false positives of every level
+            if levels.len() == MAX_LEVELS {
+                return Err(FilterCascadeConflict);
+            }
+            let salt = levels.len() as u32;
+            let mut level = KMBloomFilter::new(inserted.len().max(1), false_positive_probability);
+            for data in inserted.iter() {
+                level.insert(&(salt, data));
+            }
+            let false_positives: Vec<&T> = checked
+                .into_iter()
+                .filter(|data| level.contains(&(salt, data)))
+                .collect();
+            levels.push(level);
+            if false_positives.is_empty() {
+                return Ok(Self { levels });
+            }
+            checked = inserted;
+            inserted = false_positives;
+            false_positive_probability = level_false_positive_probability;
+        }
+    }
+
+    /// Restore a cascade from its levels, e.g., after they have been sent to a client one by one.
+    pub fn from_levels(levels: Vec<KMBloomFilter<H1, H2>>) -> Self {
+        Self { levels }
+    }
+
+    /// Check whether data is included. The result is exact for the elements the cascade has been
+    /// built from.
+    pub fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        let salted = |salt: usize| (salt as u32, data);
+        match (0..self.levels.len()).find(|&i| !self.levels[i].contains(&salted(i))) {
+            Some(i) => i % 2 == 1,
+            None => self.levels.len() % 2 == 1,
+        }
+    }
+
+    /// The levels of the cascade, starting with level 0.
+    pub fn levels(&self) -> &[KMBloomFilter<H1, H2>] {
+        &self.levels
+    }
+
+    /// Total number of bits of all levels.
+    pub fn bit_count(&self) -> usize {
+        self.levels.iter().map(KMBloomFilter::bit_count).sum()
+    }
+
+    /// Write the number of levels as a little-endian u32 and all levels in the format of
+    /// *KMBloomFilter::write_to* to *writer*, e.g., to publish the cascade.
+    ///
+    /// Only available with the *std* feature.
+    #[cfg(feature = "std")]
+    pub fn write_to<W>(&self, mut writer: W) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        writer.write_all(&(self.levels.len() as u32).to_le_bytes())?;
+        for level in self.levels.iter() {
+            level.write_to(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    /// Read a cascade that has been written with *write_to* from *reader*.
+    ///
+    /// Only available with the *std* feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of *KMBloomFilter::read_from* for every level.
+    #[cfg(feature = "std")]
+    pub fn read_from<R>(mut reader: R) -> Result<Self, ReadFilterError>
+    where
+        R: std::io::Read,
+    {
+        let mut count = [0u8; 4];
+        reader.read_exact(&mut count)?;
+        // The levels are read one by one, so a hostile count cannot allocate more levels than
+        // the input contains
+        let mut levels = Vec::new();
+        for _ in 0..u32::from_le_bytes(count) {
+            levels.push(KMBloomFilter::read_from(&mut reader)?);
+        }
+        Ok(Self { levels })
+    }
+}
+
+impl<H1, H2> Debug for FilterCascade<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FilterCascade{{levels: [")?;
+        for (i, level) in self.levels.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{:?}", level)?;
+        }
+        write!(f, "]}}")
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    borrow::Borrow,
+    collections::HashSet,
+    fmt::{self, Debug},
+    hash::Hash,
+};
+
+use crate::BloomFilter;
+
+/// Bloom filter with a small, exact set of elements that are known to be false positives, e.g.,
+/// to stop a deny-list filter from blocking a key that has been confirmed not to be on the list,
+/// without rebuilding the filter.
+///
+/// *might_contain* is false for all elements that the wrapped filter does not contain and for all
+/// elements that have been marked with *mark_false_positive*. Other false positives of the wrapped
+/// filter are still reported, so an element for which *might_contain* is true is not necessarily
+/// contained; like any bloom filter, FilterWithExceptions can only rule elements out. Inserting an
+/// element removes it from the exceptions again.
+///
+/// Every exception stores the element itself, so the exceptions should stay a small fraction of
+/// the elements. If many false positives are marked, the filter is too full and should be rebuilt
+/// with a larger capacity.
+///
+/// FilterWithExceptions is only available with the *std* feature.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{FilterWithExceptions,StableBloomFilterDefault};
+///
+/// fn main() {
+///     let mut deny_list = FilterWithExceptions::new(StableBloomFilterDefault::new(1_000, 0.01));
+///     deny_list.insert("203.0.113.7".to_string());
+///
+///     // Find an address that the filter blocks, but that is not on the deny list
+///     let false_positive = (0..)
+///         .map(|i| format!("198.51.{}.{}", i / 256, i % 256))
+///         .find(|address| deny_list.might_contain(address.as_str()))
+///         .unwrap();
+///
+///     // The authoritative list confirms it is not denied, so stop blocking it
+///     assert_eq!(true, deny_list.mark_false_positive(false_positive.clone()));
+///     assert_eq!(false, deny_list.might_contain(false_positive.as_str()));
+///     assert_eq!(true, deny_list.might_contain("203.0.113.7"));
+/// }
+/// ```
+pub struct FilterWithExceptions<F, T>
+where
+    F: BloomFilter,
+    T: Hash + Eq,
+{
+    filter: F,
+    exceptions: HashSet<T>,
+}
+
+impl<F, T> FilterWithExceptions<F, T>
+where
+    F: BloomFilter,
+    T: Hash + Eq,
+{
+    /// Initialize a new instance of FilterWithExceptions that answers queries from *filter*
+    /// without any exceptions.
+    pub fn new(filter: F) -> Self {
+        Self {
+            filter,
+            exceptions: HashSet::new(),
+        }
+    }
+
+    /// Insert *data* into the filter, and remove it from the exceptions if it has been marked as
+    /// false positive.
+    pub fn insert(&mut self, data: T) {
+        self.filter.insert(&data);
+        self.exceptions.remove(&data);
+    }
+
+    /// Whether *data* might be contained, i.e., it is contained in the wrapped filter and has not
+    /// been marked as false positive.
+    ///
+    /// *data* can be any borrowed form of the element type, e.g., `&str` for *String* elements,
+    /// because borrowed forms hash like the element itself.
+    pub fn might_contain<Q>(&self, data: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.filter.contains(&data) && !self.exceptions.contains(data)
+    }
+
+    /// Whether *data* has been marked as false positive.
+    pub fn is_marked_false_positive<Q>(&self, data: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.exceptions.contains(data)
+    }
+
+    /// Record that *data* is not contained, although the wrapped filter contains it, so that
+    /// *might_contain* returns false for it from now on.
+    ///
+    /// Returns whether *data* has been added to the exceptions. Elements that the wrapped filter
+    /// does not contain are not added, since they need no exception.
+    pub fn mark_false_positive(&mut self, data: T) -> bool {
+        if !self.filter.contains(&data) {
+            return false;
+        }
+        self.exceptions.insert(data)
+    }
+
+    /// Remove *data* from the exceptions, and return whether it has been marked as false positive.
+    pub fn unmark_false_positive<Q>(&mut self, data: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.exceptions.remove(data)
+    }
+
+    /// Number of elements that have been marked as false positive.
+    pub fn exception_count(&self) -> usize {
+        self.exceptions.len()
+    }
+
+    /// Remove all exceptions, e.g., after the wrapped filter has been rebuilt.
+    pub fn clear_exceptions(&mut self) {
+        self.exceptions.clear();
+    }
+
+    /// The wrapped filter.
+    pub fn filter(&self) -> &F {
+        &self.filter
+    }
+
+    /// Return the wrapped filter, dropping the exceptions.
+    pub fn into_inner(self) -> F {
+        self.filter
+    }
+}
+
+impl<F, T> Debug for FilterWithExceptions<F, T>
+where
+    F: BloomFilter + Debug,
+    T: Hash + Eq + Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "FilterWithExceptions{{exceptions: {:?}, filter: {:?}}}",
+            self.exceptions, self.filter
+        )
+    }
+}
+
+impl<F, T> Extend<T> for FilterWithExceptions<F, T>
+where
+    F: BloomFilter,
+    T: Hash + Eq,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for data in iter {
+            self.insert(data);
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use alloc::sync::Arc;
+use core::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use crate::{
+    approximate_element_count, approximate_false_positive_probability, Bitset, HashedKey,
+    IndexLayout, KMBloomFilter,
+};
+
+/// Immutable KMBloomFilter whose bits are shared, e.g., to let many reader threads query a filter
+/// that has been built once, without copying it and without a lock.
+///
+/// A frozen filter is created with *KMBloomFilter::freeze* and only answers queries. Its bits are
+/// stored in an *Arc*, so cloning a frozen filter only increments a reference count, and the type
+/// system guarantees that no clone changes the bits after freezing. Use *thaw* to get a mutable
+/// copy of the filter.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+/// use std::thread;
+///
+/// fn main() {
+///     let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+///     filter.insert(&"Hello!");
+///     let frozen = filter.freeze();
+///
+///     let readers: Vec<_> = (0..4)
+///         .map(|_| {
+///             let frozen = frozen.clone();
+///             thread::spawn(move || frozen.contains(&"Hello!") && !frozen.contains(&"Goodbye!"))
+///         })
+///         .collect();
+///     for reader in readers {
+///         assert_eq!(true, reader.join().unwrap());
+///     }
+/// }
+/// ```
+pub struct FrozenBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    number_of_hashers: usize,
+    bitset: Arc<Bitset>,
+    bits_per_hasher: usize,
+    layout: IndexLayout,
+    // Phantom data for saving which concrete Hasher types are used
+    _phantom: PhantomData<fn() -> (H1, H2)>,
+}
+
+impl<H1, H2> FrozenBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    pub(crate) fn new(
+        bitset: Bitset,
+        number_of_hashers: usize,
+        bits_per_hasher: usize,
+        layout: IndexLayout,
+    ) -> Self {
+        Self {
+            number_of_hashers,
+            bitset: Arc::new(bitset),
+            bits_per_hasher,
+            layout,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Check whether data is contained in the bloom filter.
+    ///
+    /// See *BloomFilter::contains* for details.
+    pub fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        let (hash_a, hash_b) = KMBloomFilter::<H1, H2>::generate_hashes(data);
+        self.contains_hash64(hash_a, hash_b)
+    }
+
+    /// Check whether the element *key* has been computed for is contained in the bloom filter.
+    ///
+    /// See *KMBloomFilter::hash* for how to compute a key.
+    pub fn contains_hashed(&self, key: &HashedKey<H1, H2>) -> bool {
+        let (hash_a, hash_b) = key.hashes();
+        self.contains_hash64(hash_a, hash_b)
+    }
+
+    /// Check whether the raw bytes *bytes* are contained in the bloom filter.
+    ///
+    /// See *KMBloomFilter::insert_bytes* for details.
+    pub fn contains_bytes(&self, bytes: &[u8]) -> bool {
+        self.contains_hashed(&HashedKey::from_bytes(bytes))
+    }
+
+    /// Check whether *text* is contained in the bloom filter.
+    ///
+    /// See *KMBloomFilter::insert_str* for details.
+    pub fn contains_str(&self, text: &str) -> bool {
+        self.contains_bytes(text.as_bytes())
+    }
+
+    /// Check whether an element with the base hashes *hash_a* and *hash_b* is contained in the
+    /// bloom filter.
+    ///
+    /// See *KMBloomFilter::insert_hash64* for details.
+    pub fn contains_hash64(&self, hash_a: u64, hash_b: u64) -> bool {
+        (0..self.number_of_hashers).all(|i| {
+            self.bitset.get(self.layout.bit_index(
+                i,
+                self.number_of_hashers,
+                self.bits_per_hasher,
+                hash_a,
+                hash_b,
+            ))
+        })
+    }
+
+    /// Approximate number of elements stored.
+    pub fn approximate_element_count(&self) -> f64 {
+        approximate_element_count(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.bitset.count_ones(),
+        )
+    }
+
+    /// Return the approximate false positive probability which depends on the number of elements
+    /// in the filter.
+    ///
+    /// The probability is given as a value in the interval [0,1]
+    pub fn approximate_current_false_positive_probability(&self) -> f64 {
+        approximate_false_positive_probability(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.approximate_element_count(),
+        )
+    }
+
+    /// Fraction of bits that are set, given as a value in the interval [0,1].
+    pub fn fill_ratio(&self) -> f64 {
+        self.bitset.count_ones() as f64 / self.bitset.len() as f64
+    }
+
+    /// Total number of bits of the filter.
+    pub fn bit_count(&self) -> usize {
+        self.bitset.len()
+    }
+
+    /// Number of hash functions used for every element.
+    pub fn hasher_count(&self) -> usize {
+        self.number_of_hashers
+    }
+
+    /// The bits of the filter, which are shared by all clones.
+    pub fn bitset(&self) -> &Bitset {
+        &self.bitset
+    }
+
+    /// Return a mutable KMBloomFilter with a copy of the bits, which keeps the number of hashers,
+    /// the bits per hasher, and the index layout, but not the capacity and saturation settings of
+    /// the frozen filter.
+    pub fn thaw(&self) -> KMBloomFilter<H1, H2> {
+        KMBloomFilter::from_bitset(Bitset::clone(&self.bitset), self.number_of_hashers)
+            .with_layout_of_bits(self.layout)
+    }
+}
+
+impl<H1, H2> Clone for FrozenBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn clone(&self) -> Self {
+        Self {
+            number_of_hashers: self.number_of_hashers,
+            bitset: Arc::clone(&self.bitset),
+            bits_per_hasher: self.bits_per_hasher,
+            layout: self.layout,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<H1, H2> Debug for FrozenBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FrozenBloomFilter{{{:?}}}", self.bitset)
+    }
+}
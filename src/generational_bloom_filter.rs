@@ -0,0 +1,229 @@
+use core::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    mem,
+};
+
+use crate::{BloomFilter, HashedKey, KMBloomFilter};
+
+/// Pair of KMBloomFilter generations that keeps the recently used elements, e.g., as the
+/// doorkeeper of a cache admission policy (double buffering).
+///
+/// Elements are inserted into the current generation, and *contains* checks the current and the
+/// previous generation. *advance_generation* drops the previous generation, turns the current
+/// generation into the previous one, and starts a new, empty current generation. An element is
+/// therefore contained until the second call of *advance_generation* after it has been inserted.
+/// *touch* inserts an element that is only contained in the previous generation into the current
+/// one again, so elements that are used in every generation are retained, while all others are
+/// forgotten, without the counters of a CountingBloomFilter.
+///
+/// The filter behaves like a RotatingBloomFilter with two generations, but hashes every element
+/// once for both generations, and swaps the generations instead of rotating a queue. Both
+/// generations contribute to the false positive probability, see *RotatingBloomFilter*.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,GenerationalBloomFilter,StableXxh3Hasher,StableXxh64Hasher};
+///
+/// fn main() {
+///     let mut filter: GenerationalBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+///         GenerationalBloomFilter::new(1_000, 0.001);
+///
+///     filter.insert(&"popular");
+///     filter.insert(&"rare");
+///     filter.advance_generation();
+///
+///     // "popular" is used again and retained, "rare" is forgotten
+///     assert_eq!(true, filter.touch(&"popular"));
+///     filter.advance_generation();
+///     assert_eq!(true, filter.contains(&"popular"));
+///     assert_eq!(false, filter.contains(&"rare"));
+/// }
+/// ```
+pub struct GenerationalBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    current: KMBloomFilter<H1, H2>,
+    previous: KMBloomFilter<H1, H2>,
+    generation: u64,
+}
+
+impl<H1, H2> GenerationalBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Initialize a new instance of GenerationalBloomFilter with two generations, each of which
+    /// guarantees that the false positive rate is less than *desired_false_positive_probability*
+    /// for up to *desired_capacity* elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0 or if desired_false_positive_probability is not in the
+    /// interval (0,1).
+    pub fn new(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self {
+        let desired_false_positive_probability = desired_false_positive_probability.into();
+        Self {
+            current: KMBloomFilter::new(desired_capacity, desired_false_positive_probability),
+            previous: KMBloomFilter::new(desired_capacity, desired_false_positive_probability),
+            generation: 0,
+        }
+    }
+
+    /// Drop the previous generation, turn the current generation into the previous one, and start
+    /// a new, empty current generation.
+    ///
+    /// The memory of the previous generation is reused for the new generation.
+    pub fn advance_generation(&mut self) {
+        mem::swap(&mut self.current, &mut self.previous);
+        self.current.clear();
+        self.generation += 1;
+    }
+
+    /// Number of calls of *advance_generation* since the filter has been created.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Check whether data is contained in any generation, and insert it into the current
+    /// generation if it is only contained in the previous one, so that it is retained by the next
+    /// call of *advance_generation*.
+    pub fn touch<T>(&mut self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        self.touch_hashed(&self.current.hash(data))
+    }
+
+    /// Check whether the element *key* has been computed for is contained in any generation, and
+    /// insert it into the current generation if it is only contained in the previous one (see
+    /// *touch*).
+    pub fn touch_hashed(&mut self, key: &HashedKey<H1, H2>) -> bool {
+        if self.current.contains_hashed(key) {
+            true
+        } else if self.previous.contains_hashed(key) {
+            self.current.insert_hashed(key);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Insert the element *key* has been computed for into the current generation.
+    pub fn insert_hashed(&mut self, key: &HashedKey<H1, H2>) {
+        self.current.insert_hashed(key);
+    }
+
+    /// Check whether the element *key* has been computed for is contained in any generation.
+    pub fn contains_hashed(&self, key: &HashedKey<H1, H2>) -> bool {
+        self.current.contains_hashed(key) || self.previous.contains_hashed(key)
+    }
+
+    /// Check whether data is contained in the current generation, i.e., has been inserted or
+    /// touched since the last call of *advance_generation*.
+    pub fn contains_current<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        self.current.contains(data)
+    }
+
+    /// The current generation, into which elements are inserted.
+    pub fn current(&self) -> &KMBloomFilter<H1, H2> {
+        &self.current
+    }
+
+    /// The previous generation, which is dropped by the next call of *advance_generation*.
+    pub fn previous(&self) -> &KMBloomFilter<H1, H2> {
+        &self.previous
+    }
+
+    /// Approximate number of distinct elements stored in both generations.
+    ///
+    /// Elements that are contained in both generations, e.g., because they have been touched, are
+    /// counted once.
+    pub fn approximate_element_count(&self) -> f64 {
+        self.current.approximate_union_count(&self.previous)
+    }
+
+    /// Return the current approximate false positive probability which depends on the current
+    /// number of elements in both generations.
+    ///
+    /// The probability is given as a value in the interval [0,1]
+    pub fn approximate_current_false_positive_probability(&self) -> f64 {
+        1.0 - (1.0
+            - self
+                .current
+                .approximate_current_false_positive_probability())
+            * (1.0
+                - self
+                    .previous
+                    .approximate_current_false_positive_probability())
+    }
+
+    /// Remove all elements from both generations. The generation number is kept.
+    pub fn clear(&mut self) {
+        self.current.clear();
+        self.previous.clear();
+    }
+}
+
+impl<H1, H2> Debug for GenerationalBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "GenerationalBloomFilter{{generation: {}, current: {:?}, previous: {:?}}}",
+            self.generation, self.current, self.previous
+        )
+    }
+}
+
+impl<H1, H2> BloomFilter for GenerationalBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Insert data into the current generation.
+    fn insert<T>(&mut self, data: &T)
+    where
+        T: Hash,
+    {
+        self.current.insert(data);
+    }
+
+    /// Check whether data is contained in any generation.
+    fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        self.contains_hashed(&self.current.hash(data))
+    }
+
+    fn insert_all<T, I>(&mut self, iter: I)
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        self.current.insert_all(iter);
+    }
+}
+
+impl<T, H1, H2> Extend<T> for GenerationalBloomFilter<H1, H2>
+where
+    T: Hash,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.insert_all(iter);
+    }
+}
@@ -0,0 +1,475 @@
+use alloc::{collections::BTreeSet, vec::Vec};
+use core::{
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use crate::{
+    math,
+    params::{checked_false_positive_probability, DEFAULT_FALSE_POSITIVE_PROBABILITY},
+    BloomFilter, XorFilter,
+};
+
+/// Number of bytes of the header of an encoded set: the number of values (u64) and the number of
+/// remainder bits (u8).
+const HEADER_LENGTH: usize = 9;
+
+/// Error returned by *GolombCodedSet::from_bytes* if the bytes do not start with a valid header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidGolombCodedSet;
+
+impl Display for InvalidGolombCodedSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid golomb-coded set header")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidGolombCodedSet {}
+
+/// Filter that records the hashes of the inserted elements in order to export them as a compact
+/// GolombCodedSet.
+///
+/// GcsFilter is the mutable side of a golomb-coded set. It keeps the 64-bit hash of every inserted
+/// element, so it needs much more memory than a bloom filter and is meant for building the set on
+/// a server. Call *to_gcs* once all elements have been inserted to encode the set for distribution.
+///
+/// The filter uses a single hash function *H*. Use a hasher whose hash values are stable across
+/// processes and platforms, e.g., *StableXxh3Hasher*, if the encoded set is queried elsewhere.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,GcsFilter,GolombCodedSet,StableXxh3Hasher};
+///
+/// fn main() {
+///     // We want to assure that the chance of a false positive is less than 0.0001.
+///     let mut filter: GcsFilter<StableXxh3Hasher> = GcsFilter::new(0.0001);
+///     filter.insert(&5i32);
+///     filter.insert(&"Some text");
+///
+///     // Encode the set, e.g., to send it to clients.
+///     let gcs = filter.to_gcs();
+///     let bytes = gcs.as_bytes().to_vec();
+///
+///     // The encoded set can be queried without decoding it first.
+///     let gcs: GolombCodedSet<StableXxh3Hasher> = GolombCodedSet::from_bytes(bytes).unwrap();
+///     assert_eq!(true, gcs.contains(&5i32));
+///     assert_eq!(true, gcs.contains(&"Some text"));
+/// }
+/// ```
+pub struct GcsFilter<H>
+where
+    H: Hasher + Default,
+{
+    hashes: BTreeSet<u64>,
+    remainder_bits: u32,
+    // Phantom data for saving which concrete Hasher type is used
+    _phantom: PhantomData<fn() -> H>,
+}
+
+impl<H> GcsFilter<H>
+where
+    H: Hasher + Default,
+{
+    /// Initialize a new, empty instance of GcsFilter whose encoded sets have a false positive
+    /// rate of less than *desired_false_positive_probability*.
+    ///
+    /// In contrast to bloom filters, the capacity does not need to be known in advance, because
+    /// the encoded set is sized for the number of elements at the time *to_gcs* is called.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_false_positive_probability is not in the interval (0,1).
+    pub fn new(desired_false_positive_probability: impl Into<f64>) -> Self {
+        let desired_false_positive_probability =
+            checked_false_positive_probability(desired_false_positive_probability);
+        let remainder_bits =
+            math::ceil(-math::ln(desired_false_positive_probability) / math::ln(2.0)) as u32;
+        Self {
+            hashes: BTreeSet::new(),
+            remainder_bits: remainder_bits.clamp(1, 32),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Insert an element by its hash *hash*, which has been computed elsewhere, e.g., by a
+    /// producer that is not written in Rust.
+    ///
+    /// The result is the same as inserting an element for which *H* returns *hash*.
+    pub fn insert_hash64(&mut self, hash: u64) {
+        self.hashes.insert(hash);
+    }
+
+    /// Number of distinct hashes recorded by the filter.
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Whether no elements have been inserted into the filter.
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// Remove all elements from the filter.
+    pub fn clear(&mut self) {
+        self.hashes.clear();
+    }
+
+    /// Encode the recorded hashes as a golomb-coded set.
+    ///
+    /// Each hash is mapped to the interval [0, n \* 2^r), where *n* is the number of recorded
+    /// hashes and *r* the number of remainder bits. The sorted values are stored as differences to
+    /// their predecessor, each of which takes about *r + 1.5* bits after Golomb-Rice coding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if n \* 2^r exceeds the range of u64.
+    pub fn to_gcs(&self) -> GolombCodedSet<H> {
+        let length = self.hashes.len() as u64;
+        let range = range(length, self.remainder_bits)
+            .unwrap_or_else(|| panic!("too many elements for a golomb-coded set: {}", length));
+
+        let mut writer = BitWriter::new();
+        writer.bytes.extend_from_slice(&length.to_le_bytes());
+        writer.bytes.push(self.remainder_bits as u8);
+        let mut previous = 0;
+        let mut values: Vec<u64> = self
+            .hashes
+            .iter()
+            .map(|&hash| map_to_range(hash, range))
+            .collect();
+        // Mapping preserves the order of the hashes, but distinct hashes can collide
+        values.dedup();
+        for value in values {
+            let delta = value - previous;
+            writer.write_unary(delta >> self.remainder_bits);
+            writer.write_bits(delta, self.remainder_bits);
+            previous = value;
+        }
+
+        GolombCodedSet {
+            bytes: writer.bytes,
+            length,
+            remainder_bits: self.remainder_bits,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Build an xor filter from the recorded hashes.
+    ///
+    /// In contrast to golomb-coded sets, xor filters answer queries in constant time, but their
+    /// false positive probability is fixed at about 1 / 256.
+    pub fn to_xor_filter(&self) -> XorFilter<H> {
+        XorFilter::from_hashes(self.hashes.iter().copied())
+    }
+
+    fn hash<T>(data: &T) -> u64
+    where
+        T: Hash,
+    {
+        let mut hasher = H::default();
+        data.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl<H> Debug for GcsFilter<H>
+where
+    H: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "GcsFilter{{length: {}, remainder_bits: {}}}",
+            self.hashes.len(),
+            self.remainder_bits
+        )
+    }
+}
+
+/// A GcsFilter with the default false positive probability of 1% (see
+/// *params::DEFAULT_FALSE_POSITIVE_PROBABILITY*).
+impl<H> Default for GcsFilter<H>
+where
+    H: Hasher + Default,
+{
+    fn default() -> Self {
+        Self::new(DEFAULT_FALSE_POSITIVE_PROBABILITY)
+    }
+}
+
+impl<H> BloomFilter for GcsFilter<H>
+where
+    H: Hasher + Default,
+{
+    fn insert<T>(&mut self, data: &T)
+    where
+        T: Hash,
+    {
+        self.insert_hash64(Self::hash(data));
+    }
+
+    /// Check whether the hash of data has been recorded.
+    ///
+    /// False positives only occur for colliding 64-bit hashes. The encoded set has a much higher
+    /// false positive probability.
+    fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        self.hashes.contains(&Self::hash(data))
+    }
+}
+
+impl<T, H> Extend<T> for GcsFilter<H>
+where
+    T: Hash,
+    H: Hasher + Default,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.insert_all(iter);
+    }
+}
+
+/// Read-only golomb-coded set as described by Putze et al.:
+///
+/// > Felix Putze, Peter Sanders, and Johannes Singler. 2009. Cache-, Hash-, and Space-Efficient
+/// > Bloom Filters. ACM J. Exp. Algorithmics 14, Article 4 (2009). DOI: [https://doi.org/10.1145/1498698.1594230](https://doi.org/10.1145/1498698.1594230)
+///
+/// A golomb-coded set stores the sorted hash values of its elements as Golomb-Rice coded
+/// differences. For a false positive probability of *2^-r*, this needs about *r + 2* bits per
+/// element, whereas a bloom filter needs about *1.44 \* r* bits, e.g., the set is about 20% smaller
+/// for a false positive probability of 0.0001. The price is that queries decode the set from its
+/// beginning, which takes time linear in the number of elements, and that elements cannot be added
+/// afterwards.
+///
+/// Golomb-coded sets are created with *GcsFilter::to_gcs* and can be sent as bytes (see
+/// *as_bytes* and *from_bytes*). The encoding is:
+/// * the number of values as u64 in little-endian byte order,
+/// * the number of remainder bits *r* as a single byte,
+/// * for each value, the quotient of its difference to the previous value divided by *2^r* in
+///   unary (ones terminated by a zero), followed by the lowest *r* bits of the difference. Bits
+///   are written starting with the most significant bit of each byte.
+pub struct GolombCodedSet<H>
+where
+    H: Hasher + Default,
+{
+    bytes: Vec<u8>,
+    length: u64,
+    remainder_bits: u32,
+    // Phantom data for saving which concrete Hasher type is used
+    _phantom: PhantomData<fn() -> H>,
+}
+
+impl<H> GolombCodedSet<H>
+where
+    H: Hasher + Default,
+{
+    /// Restore a golomb-coded set from the bytes returned by *as_bytes*.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if *bytes* do not start with a valid header. The encoded values are only
+    /// decoded by queries, which treat missing bytes as the end of the set.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, InvalidGolombCodedSet> {
+        if bytes.len() < HEADER_LENGTH {
+            return Err(InvalidGolombCodedSet);
+        }
+        let mut length = [0u8; 8];
+        length.copy_from_slice(&bytes[..8]);
+        let length = u64::from_le_bytes(length);
+        let remainder_bits = bytes[8] as u32;
+        if remainder_bits == 0 || remainder_bits > 32 || range(length, remainder_bits).is_none() {
+            return Err(InvalidGolombCodedSet);
+        }
+        Ok(Self {
+            bytes,
+            length,
+            remainder_bits,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// The encoded set, including its header.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Check whether data is contained in the set.
+    ///
+    /// The result can be a false positive, but never a false negative.
+    pub fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        self.contains_hash64(GcsFilter::<H>::hash(data))
+    }
+
+    /// Check whether an element with the hash *hash* is contained in the set.
+    pub fn contains_hash64(&self, hash: u64) -> bool {
+        let target = match range(self.length, self.remainder_bits) {
+            Some(range) if self.length > 0 => map_to_range(hash, range),
+            _ => return false,
+        };
+        let mut reader = BitReader::new(&self.bytes[HEADER_LENGTH..]);
+        let mut value = 0u64;
+        for _ in 0..self.length {
+            let quotient = match reader.read_unary() {
+                Some(quotient) => quotient,
+                None => return false,
+            };
+            let remainder = match reader.read_bits(self.remainder_bits) {
+                Some(remainder) => remainder,
+                None => return false,
+            };
+            value = value.saturating_add((quotient << self.remainder_bits) | remainder);
+            if value >= target {
+                return value == target;
+            }
+        }
+        false
+    }
+
+    /// Number of elements the set has been created for.
+    ///
+    /// Elements whose hashes collided after mapping them to the range of the set are stored once.
+    pub fn len(&self) -> usize {
+        self.length as usize
+    }
+
+    /// Whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Number of bytes of the encoded set, including its header.
+    pub fn byte_count(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Return the approximate false positive probability of the set.
+    ///
+    /// The probability is given as a value in the interval [0,1]
+    pub fn approximate_false_positive_probability(&self) -> f64 {
+        1.0 / math::powf(2.0, self.remainder_bits as f64)
+    }
+}
+
+impl<H> Debug for GolombCodedSet<H>
+where
+    H: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "GolombCodedSet{{length: {}, remainder_bits: {}, bytes: {}}}",
+            self.length,
+            self.remainder_bits,
+            self.bytes.len()
+        )
+    }
+}
+
+/// Size of the interval the hashes of a set with *length* values are mapped to.
+fn range(length: u64, remainder_bits: u32) -> Option<u64> {
+    length.checked_mul(1u64 << remainder_bits)
+}
+
+fn map_to_range(hash: u64, range: u64) -> u64 {
+    ((hash as u128 * range as u128) >> 64) as u64
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    // Number of bits used in the last byte, zero if a new byte has to be started
+    used_bits: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            used_bits: 0,
+        }
+    }
+
+    fn write_bit(&mut self, bit: bool) {
+        if self.used_bits == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            *self.bytes.last_mut().unwrap() |= 0x80 >> self.used_bits;
+        }
+        self.used_bits = (self.used_bits + 1) % 8;
+    }
+
+    fn write_unary(&mut self, value: u64) {
+        for _ in 0..value {
+            self.write_bit(true);
+        }
+        self.write_bit(false);
+    }
+
+    fn write_bits(&mut self, value: u64, bits: u32) {
+        for i in (0..bits).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn read_bit(&mut self) -> Option<bool> {
+        let byte = self.bytes.get(self.position / 8)?;
+        let bit = byte & (0x80 >> (self.position % 8)) != 0;
+        self.position += 1;
+        Some(bit)
+    }
+
+    fn read_unary(&mut self) -> Option<u64> {
+        let mut value = 0;
+        while self.read_bit()? {
+            value += 1;
+        }
+        Some(value)
+    }
+
+    fn read_bits(&mut self, bits: u32) -> Option<u64> {
+        let mut value = 0;
+        for _ in 0..bits {
+            value = (value << 1) | self.read_bit()? as u64;
+        }
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bits_round_trip() {
+        let mut writer = BitWriter::new();
+        writer.write_unary(3);
+        writer.write_bits(0b1011, 4);
+        writer.write_unary(0);
+        writer.write_bits(u32::MAX as u64, 32);
+        assert_eq!(6, writer.bytes.len());
+
+        let mut reader = BitReader::new(&writer.bytes);
+        assert_eq!(Some(3), reader.read_unary());
+        assert_eq!(Some(0b1011), reader.read_bits(4));
+        assert_eq!(Some(0), reader.read_unary());
+        assert_eq!(Some(u32::MAX as u64), reader.read_bits(32));
+        assert_eq!(None, reader.read_bits(8));
+    }
+}
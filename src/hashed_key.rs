@@ -0,0 +1,93 @@
+use core::{
+    fmt::{self, Debug},
+    hash::Hasher,
+    marker::PhantomData,
+};
+
+/// The two base hashes of an element, computed once with the hashers *H1* and *H2* of a
+/// KMBloomFilter or CountingBloomFilter.
+///
+/// All indices of an element are derived from its two base hashes. A HashedKey can therefore be
+/// used to insert or look up an element in any number of filters with the same hashers, without
+/// hashing the element again. The hashers are part of the type, so a key can not be used with a
+/// filter that hashes differently.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+///
+/// let shards: Vec<StableBloomFilterDefault> = (0..16)
+///     .map(|shard| StableBloomFilterDefault::from_iter_with_fp(shard * 100..(shard + 1) * 100, 0.001))
+///     .collect();
+///
+/// // Hash the element once and look it up in all shards
+/// let key = shards[0].hash(&1_234);
+/// let found = shards.iter().filter(|shard| shard.contains_hashed(&key)).count();
+/// assert_eq!(1, found);
+/// ```
+pub struct HashedKey<H1, H2> {
+    hash_a: u64,
+    hash_b: u64,
+    // Phantom data for saving which concrete Hasher types are used
+    _phantom: PhantomData<fn() -> (H1, H2)>,
+}
+
+impl<H1, H2> HashedKey<H1, H2> {
+    pub(crate) fn from_hashes(hash_a: u64, hash_b: u64) -> Self {
+        Self {
+            hash_a,
+            hash_b,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub(crate) fn hashes(&self) -> (u64, u64) {
+        (self.hash_a, self.hash_b)
+    }
+}
+
+impl<H1, H2> HashedKey<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Hash *bytes* by writing them to *H1* and *H2* as they are, without the length prefix the
+    /// *Hash* implementation of slices adds.
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Self {
+        let mut hasher = H1::default();
+        hasher.write(bytes);
+        let hash_a = hasher.finish();
+
+        let mut hasher = H2::default();
+        hasher.write(bytes);
+        let hash_b = hasher.finish();
+
+        Self::from_hashes(hash_a, hash_b)
+    }
+}
+
+impl<H1, H2> Clone for HashedKey<H1, H2> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<H1, H2> Copy for HashedKey<H1, H2> {}
+
+impl<H1, H2> PartialEq for HashedKey<H1, H2> {
+    fn eq(&self, other: &Self) -> bool {
+        self.hashes() == other.hashes()
+    }
+}
+
+impl<H1, H2> Eq for HashedKey<H1, H2> {}
+
+impl<H1, H2> Debug for HashedKey<H1, H2> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "HashedKey{{hash_a: {:#018x}, hash_b: {:#018x}}}",
+            self.hash_a, self.hash_b
+        )
+    }
+}
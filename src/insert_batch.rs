@@ -0,0 +1,126 @@
+use alloc::vec::Vec;
+use core::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+};
+
+use crate::{CapacityExceeded, HashedKey, KMBloomFilter};
+
+/// Insertions into a KMBloomFilter that are staged and only applied to the filter by *commit*,
+/// e.g., to update a filter together with a database transaction once the transaction has been
+/// committed. Create a batch with *KMBloomFilter::batch*.
+///
+/// The batch stores the bit indices of the staged elements and does not modify the filter before
+/// *commit*. Dropping the batch, or calling *rollback*, discards the staged elements. The batch
+/// borrows the filter mutably, so the filter cannot be modified in another way until the batch
+/// is committed or discarded.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+///
+/// fn main() {
+///     let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+///
+///     let mut batch = filter.batch();
+///     batch.insert(&"Hello!");
+///     // The database transaction fails, so the insertion is discarded
+///     batch.rollback();
+///     assert_eq!(false, filter.contains(&"Hello!"));
+///
+///     let mut batch = filter.batch();
+///     batch.insert(&"Hello!");
+///     batch.insert(&"Goodbye!");
+///     // The database transaction succeeds
+///     batch.commit();
+///     assert_eq!(true, filter.contains(&"Hello!"));
+///     assert_eq!(true, filter.contains(&"Goodbye!"));
+/// }
+/// ```
+pub struct InsertBatch<'a, H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    filter: &'a mut KMBloomFilter<H1, H2>,
+    indices: Vec<usize>,
+    len: usize,
+}
+
+impl<'a, H1, H2> InsertBatch<'a, H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    pub(crate) fn new(filter: &'a mut KMBloomFilter<H1, H2>) -> Self {
+        Self {
+            filter,
+            indices: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Stage the insertion of data.
+    pub fn insert<T>(&mut self, data: &T)
+    where
+        T: Hash,
+    {
+        let key = self.filter.hash(data);
+        self.insert_hashed(&key);
+    }
+
+    /// Stage the insertion of the element *key* has been computed for.
+    pub fn insert_hashed(&mut self, key: &HashedKey<H1, H2>) {
+        self.filter.push_indices(key.hashes(), &mut self.indices);
+        self.len += 1;
+    }
+
+    /// Number of staged elements.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no element has been staged.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Insert all staged elements into the filter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filter has a strict capacity that the staged elements would exceed, or if
+    /// they would exceed the target false positive probability of the saturation policy
+    /// *OnSaturation::Error*. No element is inserted in this case.
+    pub fn commit(self) {
+        if let Err(error) = self.try_commit() {
+            panic!("{}", error);
+        }
+    }
+
+    /// Insert all staged elements into the filter, unless they would exceed the strict capacity of
+    /// the filter (see *KMBloomFilter::with_strict_capacity*) or the target false positive
+    /// probability of the saturation policy *OnSaturation::Error* together. No element is
+    /// inserted in this case.
+    ///
+    /// Without a strict capacity or the saturation policy *OnSaturation::Error*, the commit always
+    /// succeeds, so a batch can be committed after a database transaction without a chance of
+    /// failure.
+    pub fn try_commit(mut self) -> Result<(), CapacityExceeded> {
+        self.filter.insert_indices(&mut self.indices, self.len)
+    }
+
+    /// Discard all staged elements without modifying the filter, which is the same as dropping
+    /// the batch.
+    pub fn rollback(self) {}
+}
+
+impl<H1, H2> Debug for InsertBatch<'_, H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "InsertBatch{{len: {}}}", self.len)
+    }
+}
@@ -1,19 +1,147 @@
-use std::{
-    fmt::Debug,
+use alloc::{vec, vec::Vec};
+use core::{
+    fmt::{self, Debug, Display},
     hash::{Hash, Hasher},
     marker::PhantomData,
 };
 
 use crate::{
-    approximate_element_count, approximate_false_positive_probability, bitset::Bitset,
-    optimal_bit_count, optimal_number_of_hashers, BloomFilter,
+    approximate_difference_count, approximate_element_count_bounds, approximate_intersection_count,
+    approximate_jaccard_similarity,
+    bitset::Bitset,
+    bitset_delta::BitsetDelta,
+    filter_from_iter, math,
+    metrics::Metrics,
+    params::{
+        self, approximate_element_count_with_noise,
+        approximate_false_positive_probability_with_noise, FilterParams,
+        DEFAULT_FALSE_POSITIVE_PROBABILITY,
+    },
+    BloomFilter, DistinctCounter, FrozenBloomFilter, HashedKey, InsertBatch, InvalidFilterParams,
+    INSERT_BATCH_SIZE,
 };
 
+#[cfg(feature = "rayon")]
+use crate::bitset::AtomicBitset;
+#[cfg(feature = "std")]
+use crate::file_format::{
+    check_length, read_layout_header, verify_checksum, write_header, ChecksumWriter,
+    ReadFilterError, CHECKSUM_LENGTH, HEADER_LENGTH,
+};
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+use crate::Compression;
+#[cfg(feature = "metrics")]
+use crate::FilterStats;
+#[cfg(feature = "tokio")]
+use crate::{
+    bitset::CHUNK_WORDS,
+    file_format::{checksum_hasher, verify_digest},
+};
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Number of bits whose words are prefetched at once by *contains* with the *prefetch* feature,
+/// which covers the hashers of all filters with a false positive probability above 1e-4 in a
+/// single batch.
+#[cfg(feature = "prefetch")]
+const PREFETCH_BATCH_SIZE: usize = 16;
+
+/// Error returned by *KMBloomFilter::try_insert* if a bloom filter with a strict capacity is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityExceeded;
+
+impl Display for CapacityExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the capacity of the bloom filter has been exceeded")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CapacityExceeded {}
+
+/// Behavior of a KMBloomFilter whose approximate false positive probability exceeds its target,
+/// see *KMBloomFilter::with_saturation_policy*.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum OnSaturation {
+    /// Keep inserting elements, and let the false positive probability grow silently.
+    #[default]
+    Ignore,
+    /// Keep inserting elements, and call the function with the approximate false positive
+    /// probability whenever an insertion makes it exceed the target, e.g., to log a warning or
+    /// increment a counter of an operator dashboard.
+    Warn(fn(f64)),
+    /// Refuse insertions that would make the approximate false positive probability exceed the
+    /// target: *try_insert* returns *CapacityExceeded*, and all other ways of inserting elements
+    /// panic.
+    Error,
+}
+
+/// Which bits the simulated hash functions of a KMBloomFilter address, selected with
+/// *KMBloomFilter::with_index_layout*.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum IndexLayout {
+    /// Every hash function addresses its own range of *bits_per_hasher* bits, so every element
+    /// sets exactly one bit in the range of every hash function.
+    #[default]
+    Partitioned,
+    /// Every hash function addresses all bits of the filter, as in the classic bloom filter and
+    /// most other implementations. Two hash functions may set the same bit for an element, so
+    /// fewer bits are set per element, and the false positive probability is slightly lower for
+    /// the same number of bits.
+    Global,
+}
+
+impl IndexLayout {
+    /// Index of the bit of the *i*-th simulated hash function of a filter with this layout, see
+    /// *bit_index* and *global_bit_index*.
+    pub(crate) fn bit_index(
+        self,
+        i: usize,
+        number_of_hashers: usize,
+        bits_per_hasher: usize,
+        hash_a: u64,
+        hash_b: u64,
+    ) -> usize {
+        match self {
+            IndexLayout::Partitioned => bit_index(i, bits_per_hasher, hash_a, hash_b),
+            IndexLayout::Global => {
+                global_bit_index(i, number_of_hashers * bits_per_hasher, hash_a, hash_b)
+            }
+        }
+    }
+}
+
+/// Error returned by *KMBloomFilter::merge_many* if there is nothing to merge or the filters cannot
+/// be merged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeError {
+    /// No filters have been passed.
+    NoFilters,
+    /// The filter at position *filter* has a different configuration than the first filter.
+    ConfigurationMismatch { filter: usize },
+}
+
+impl Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeError::NoFilters => write!(f, "unable to merge zero k-m bloom filters"),
+            MergeError::ConfigurationMismatch { filter } => write!(
+                f,
+                "unable to merge k-m bloom filters with different configurations: filter {} differs from the first filter",
+                filter
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MergeError {}
+
 /// Bloom filter implementation using the improvements described by Kirsch and Mitzenmacher:
 ///
 /// > Kirsch A., Mitzenmacher M. (2006) Less Hashing, Same Performance: Building a Better Bloom Filter.
-/// In: Azar Y., Erlebach T. (eds) Algorithms – ESA 2006. ESA 2006. Lecture Notes in Computer Science, vol 4168.
-/// Springer, Berlin, Heidelberg. https://doi.org/10.1007/11841036_42
+/// > In: Azar Y., Erlebach T. (eds) Algorithms – ESA 2006. ESA 2006. Lecture Notes in Computer Science, vol 4168.
+/// > Springer, Berlin, Heidelberg. https://doi.org/10.1007/11841036_42
 ///
 /// # Examples
 /// ```
@@ -50,8 +178,15 @@ where
     number_of_hashers: usize,
     bitset: Bitset,
     bits_per_hasher: usize,
+    layout: IndexLayout,
+    strict_capacity: Option<usize>,
+    // Target false positive probability and what to do if it is exceeded
+    saturation: Option<(f64, OnSaturation)>,
+    // Fraction of the bits that have been set at random by add_noise, which the estimates discount
+    noise: f64,
+    metrics: Metrics,
     // Phantom data for saving which concrete Hasher types are used
-    _phantom: PhantomData<(H1, H2)>,
+    _phantom: PhantomData<fn() -> (H1, H2)>,
 }
 
 impl<H1, H2> KMBloomFilter<H1, H2>
@@ -70,7 +205,9 @@ where
     ///
     /// # Panics
     ///
-    /// Panics if desired_capacity == 0
+    /// Panics if desired_capacity == 0, if desired_false_positive_probability is not in the
+    /// interval (0,1), e.g., if it is NaN, or if the number of bits does not fit into usize. Use
+    /// *try_new* to handle these cases as errors.
     ///
     /// # Examples
     /// ```
@@ -89,249 +226,2706 @@ where
     ///         KMBloomFilter::new(desired_capacity, desired_fp_probability);
     /// }
     /// ```
-    pub fn new(desired_capacity: usize, desired_false_positive_probability: f64) -> Self {
-        if desired_capacity == 0 {
-            panic!("an empty bloom filter is not defined");
+    pub fn new(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self {
+        match Self::try_new(desired_capacity, desired_false_positive_probability) {
+            Ok(filter) => filter,
+            Err(error) => panic!("{}", error),
         }
-        let bit_count = optimal_bit_count(desired_capacity, desired_false_positive_probability);
-        let number_of_hashers = optimal_number_of_hashers(desired_capacity, bit_count);
-        let bits_per_hasher = (bit_count as f64 / number_of_hashers as f64).ceil() as usize;
+    }
+
+    /// Initialize a new instance of KMBloomFilter like *new*, but return an error instead of
+    /// panicking for invalid arguments, e.g., arguments that are passed in from a configuration
+    /// file.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of *FilterParams::try_for_capacity*.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{InvalidFilterParams,StableBloomFilterDefault};
+    ///
+    /// assert!(StableBloomFilterDefault::try_new(1_000, 0.01).is_ok());
+    /// assert!(matches!(
+    ///     StableBloomFilterDefault::try_new(1_000, f64::NAN),
+    ///     Err(InvalidFilterParams::FalsePositiveProbability(_))
+    /// ));
+    /// ```
+    pub fn try_new(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Result<Self, InvalidFilterParams> {
+        let params =
+            FilterParams::try_for_capacity(desired_capacity, desired_false_positive_probability)?;
+        Ok(Self {
+            bitset: Bitset::new(params.bit_count()),
+            number_of_hashers: params.number_of_hashers(),
+            bits_per_hasher: params.bits_per_hasher(),
+            layout: IndexLayout::Partitioned,
+            strict_capacity: None,
+            saturation: None,
+            noise: 0.0,
+            metrics: Metrics::default(),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Initialize a new instance of KMBloomFilter for up to *desired_capacity* elements with the
+    /// default false positive probability of 1% (see
+    /// *params::DEFAULT_FALSE_POSITIVE_PROBABILITY*).
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// let mut filter = StableBloomFilterDefault::with_capacity(1_000);
+    /// filter.insert(&"Hello!");
+    ///
+    /// assert_eq!(true, filter.contains(&"Hello!"));
+    /// ```
+    pub fn with_capacity(desired_capacity: usize) -> Self {
+        Self::new(desired_capacity, DEFAULT_FALSE_POSITIVE_PROBABILITY)
+    }
+
+    /// Initialize a new instance of KMBloomFilter with at least *bit_count* bits that hashes every
+    /// element with exactly *number_of_hashers* hash functions, instead of deriving both from a
+    /// desired capacity and false positive probability.
+    ///
+    /// Fewer hash functions mean fewer probes per lookup at the cost of a higher false positive
+    /// probability for the same number of bits. *params::bit_count_for_hashers* calculates the
+    /// number of bits that keeps the false positive probability for a given number of hashers.
+    /// The bits are split evenly between the hashers, so the bit count is rounded up to a
+    /// multiple of *number_of_hashers*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if bit_count == 0, number_of_hashers == 0, or if rounding bit_count up to a multiple
+    /// of number_of_hashers overflows usize.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{params,BloomFilter,StableBloomFilterDefault};
+    ///
+    /// // Three probes per lookup instead of ten for a false positive probability of 0.001
+    /// let bit_count = params::bit_count_for_hashers(10_000, 0.001, 3);
+    /// let mut filter = StableBloomFilterDefault::with_parameters(bit_count, 3);
+    /// filter.insert_all(0..10_000);
+    ///
+    /// assert_eq!(3, filter.hasher_count());
+    /// assert!(filter.approximate_current_false_positive_probability() < 0.0011);
+    /// ```
+    pub fn with_parameters(bit_count: usize, number_of_hashers: usize) -> Self {
+        let bits_per_hasher = params::bits_per_hasher(bit_count, number_of_hashers);
         Self {
-            bitset: Bitset::new(bits_per_hasher * number_of_hashers),
+            bitset: Bitset::new(number_of_hashers * bits_per_hasher),
             number_of_hashers,
             bits_per_hasher,
+            layout: IndexLayout::Partitioned,
+            strict_capacity: None,
+            saturation: None,
+            noise: 0.0,
+            metrics: Metrics::default(),
             _phantom: PhantomData,
         }
     }
 
-    /// Approximate number of elements stored.
-    /// Approximation technique taken from Wikipedia:
-    /// > Wikipedia, ["Bloom filter"](https://en.wikipedia.org/wiki/Bloom_filter#Approximating_the_number_of_items_in_a_Bloom_filter) [Accessed: 02.12.2020]
-    pub fn approximate_element_count(&self) -> f64 {
-        approximate_element_count(
-            self.number_of_hashers,
-            self.bits_per_hasher,
-            self.bitset.count_ones(),
-        )
+    /// Initialize a new instance of KMBloomFilter with *number_of_hashers* hashers whose bits are
+    /// *bitset*, e.g., a bitset that has been obtained with *bitset* and transformed with the
+    /// operations of Bitset.
+    ///
+    /// The bits of the *i*-th hasher are laid out as described for *bitset*. The filter only
+    /// contains the elements of the original filter if the transformation preserved the bits each
+    /// element maps to, e.g., folding the bits of every hasher in half.
+    ///
+    /// # Panics
+    ///
+    /// Panics if number_of_hashers == 0 or if the length of *bitset* is zero or not a multiple of
+    /// number_of_hashers.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// let mut filter = StableBloomFilterDefault::with_parameters(64_000, 1);
+    /// filter.insert_all(0..1_000);
+    ///
+    /// // Custom compaction of a filter with a single hasher
+    /// let mut bitset = filter.bitset().clone();
+    /// bitset.fold_in_half();
+    /// let compacted = StableBloomFilterDefault::from_bitset(bitset, 1);
+    /// assert_eq!(32_000, compacted.bit_count());
+    /// assert_eq!(true, compacted.contains(&999));
+    /// ```
+    pub fn from_bitset(bitset: Bitset, number_of_hashers: usize) -> Self {
+        if number_of_hashers == 0
+            || bitset.is_empty()
+            || !bitset.len().is_multiple_of(number_of_hashers)
+        {
+            panic!(
+                "unable to create a k-m bloom filter with {} hashers from {} bits",
+                number_of_hashers,
+                bitset.len()
+            );
+        }
+        Self {
+            bits_per_hasher: bitset.len() / number_of_hashers,
+            bitset,
+            number_of_hashers,
+            layout: IndexLayout::Partitioned,
+            strict_capacity: None,
+            saturation: None,
+            noise: 0.0,
+            metrics: Metrics::default(),
+            _phantom: PhantomData,
+        }
     }
 
-    /// Return the current approximate false positive probability which depends on the current
-    /// number of elements in the filter.
+    /// Initialize a new instance of KMBloomFilter like *new*, but round the number of bits per
+    /// hasher up to a multiple of *max_compaction_factor*, so that the filter can be compacted by
+    /// every power of two up to *max_compaction_factor* with *compact*.
     ///
-    /// The probability is given as a value in the interval [0,1]
-    /// Approximation technique taken from Sagi Kedmi:
-    /// > S. Kedmi, ["Bloom Filters for the Perplexed"](https://sagi.io/bloom-filters-for-the-perplexed/), July 2017 [Accessed: 02.12.2020]
-    pub fn approximate_current_false_positive_probability(&self) -> f64 {
-        approximate_false_positive_probability(
-            self.number_of_hashers,
-            self.bits_per_hasher,
-            self.approximate_element_count(),
-        )
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0, if desired_false_positive_probability is not in the
+    /// interval (0,1), if *max_compaction_factor* is not a power of two, or if the rounded number
+    /// of bits does not fit into usize.
+    pub fn new_compactable(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+        max_compaction_factor: usize,
+    ) -> Self {
+        if !max_compaction_factor.is_power_of_two() {
+            panic!(
+                "compaction factor {} of k-m bloom filter is not a power of two",
+                max_compaction_factor
+            );
+        }
+        let params =
+            FilterParams::for_capacity(desired_capacity, desired_false_positive_probability);
+        let bits_per_hasher = params
+            .bits_per_hasher()
+            .div_ceil(max_compaction_factor)
+            .checked_mul(max_compaction_factor)
+            .unwrap_or_else(|| {
+                params::bit_count_overflow(params.number_of_hashers(), params.bits_per_hasher())
+            });
+        Self {
+            bitset: Bitset::new(params::checked_bit_count(
+                params.number_of_hashers(),
+                bits_per_hasher,
+            )),
+            number_of_hashers: params.number_of_hashers(),
+            bits_per_hasher,
+            layout: IndexLayout::Partitioned,
+            strict_capacity: None,
+            saturation: None,
+            noise: 0.0,
+            metrics: Metrics::default(),
+            _phantom: PhantomData,
+        }
     }
 
-    /// Creates a union of this bloom filter and 'other', which means 'contains' of the resulting
-    /// bloom filter will always return true for elements inserted in either this bloom filter or in
-    /// 'other' before creation.
+    /// Initialize a new instance of KMBloomFilter like *new*, but round the number of bits per
+    /// hasher up to a power of two, e.g., for query-heavy workloads.
+    ///
+    /// With a power of two, the bit of every hasher is selected with a bitmask instead of a
+    /// division, which makes *insert* and *contains* faster at the cost of up to twice the memory.
+    /// The false positive probability is lower than desired, since the filter has more bits. The
+    /// bits are the same as those of a filter with the same configuration created with
+    /// *with_parameters*, and the filter can be compacted by every power of two with *compact*.
     ///
     /// # Panics
     ///
-    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
-    /// differ.
-    /// 
+    /// Panics if desired_capacity == 0, if desired_false_positive_probability is not in the
+    /// interval (0,1), or if the rounded number of bits does not fit into usize.
+    ///
     /// # Examples
-    /// 
-    /// Union of two bloom filters with the same configuration.
     /// ```
-    /// use bloom_filter_simple::{BloomFilter,KMBloomFilter};
-    /// use ahash::AHasher;
-    /// use std::collections::hash_map::DefaultHasher;
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
     ///
-    /// fn main() {
-    ///     // The configuration of both bloom filters has to be the same
-    ///     let desired_capacity = 10_000;
-    ///     let desired_fp_probability = 0.0001;
-    ///
-    ///     // We initialize two new KMBloomFilter 
-    ///     let mut filter_one: KMBloomFilter<AHasher, DefaultHasher> = KMBloomFilter::new(
-    ///         desired_capacity,
-    ///         desired_fp_probability
-    ///     );
-    /// 
-    ///     let mut filter_two: KMBloomFilter<AHasher, DefaultHasher> = KMBloomFilter::new(
-    ///         desired_capacity,
-    ///         desired_fp_probability
-    ///     );
-    /// 
-    ///     // Insert elements into the first filter
-    ///     filter_one.insert(&0);
-    ///     filter_one.insert(&1);
-    /// 
-    ///     // Insert elements into the second filter
-    ///     filter_two.insert(&2);
-    ///     filter_two.insert(&3);
-    ///     
-    ///     // Now we retrieve the union of both filters
-    ///     let filter_union = filter_one.union(&filter_two);
+    /// let mut filter = StableBloomFilterDefault::new_power_of_two(1_000, 0.001);
+    /// filter.insert(&"Hello!");
     ///
-    ///     // The union will return true for a 'contains' check for the elements inserted 
-    ///     // previously into at least one of the constituent filters.
-    ///     assert_eq!(true, filter_union.contains(&0));
-    ///     assert_eq!(true, filter_union.contains(&1));
-    ///     assert_eq!(true, filter_union.contains(&2));
-    ///     assert_eq!(true, filter_union.contains(&3));
-    /// }
+    /// assert_eq!(true, filter.contains(&"Hello!"));
+    /// assert_eq!(true, (filter.bit_count() / filter.hasher_count()).is_power_of_two());
     /// ```
-    pub fn union(&self, other: &Self) -> Self {
-        if !self.eq_configuration(other) {
-            panic!("unable to union k-m bloom filters with different configurations");
-        }
+    pub fn new_power_of_two(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self {
+        let params =
+            FilterParams::for_capacity(desired_capacity, desired_false_positive_probability);
+        let bits_per_hasher = params
+            .bits_per_hasher()
+            .checked_next_power_of_two()
+            .unwrap_or_else(|| {
+                params::bit_count_overflow(params.number_of_hashers(), params.bits_per_hasher())
+            });
         Self {
-            number_of_hashers: self.number_of_hashers,
-            bitset: self.bitset.union(&other.bitset),
-            bits_per_hasher: self.bits_per_hasher,
-            _phantom: self._phantom,
+            bitset: Bitset::new(params::checked_bit_count(
+                params.number_of_hashers(),
+                bits_per_hasher,
+            )),
+            number_of_hashers: params.number_of_hashers(),
+            bits_per_hasher,
+            layout: IndexLayout::Partitioned,
+            strict_capacity: None,
+            saturation: None,
+            noise: 0.0,
+            metrics: Metrics::default(),
+            _phantom: PhantomData,
         }
     }
 
-    /// Creates a intersection of this bloom filter and 'other', which means 'contains' of the resulting
-    /// bloom filter will always return true for elements inserted both in this bloom filter and in
-    /// 'other' before creation.
-    /// The false positive probability of the resulting bloom filter is at most the false positive
-    /// probability of 'other' or 'self'.
-    /// The false positive probability of the resulting bloom filter may be bigger than the false
-    /// positive probability of a new empty bloom filter with the intersecting elements inserted.
-    /// The functions 'approximate_current_false_positive_probability' and 'approximate_element_count'
-    /// called on the resulting bloom filter may return too big approximations.
+    /// Address all bits of the filter with every simulated hash function according to *layout*,
+    /// instead of giving every hash function its own range of bits, see *IndexLayout*.
+    ///
+    /// The layout is part of the configuration: filters with different layouts cannot be combined,
+    /// and *write_to* writes filters with the layout *IndexLayout::Global* in format version 2,
+    /// which only *read_from* and *from_bytes* can read. The estimates of the number of elements
+    /// and the false positive probability are the same for both layouts. Use
+    /// *Formula::Unpartitioned* to size a filter for the global layout exactly.
     ///
     /// # Panics
     ///
-    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
-    /// differ.
-    /// 
+    /// Panics if a bit of the filter is set, since the elements would be lost.
+    ///
     /// # Examples
-    /// 
-    /// Intersection of two bloom filters with the same configuration.
     /// ```
-    /// use bloom_filter_simple::{BloomFilter,KMBloomFilter};
-    /// use ahash::AHasher;
-    /// use std::collections::hash_map::DefaultHasher;
-    ///
-    /// fn main() {
-    ///     // The configuration of both bloom filters has to be the same
-    ///     let desired_capacity = 10_000;
-    ///     let desired_fp_probability = 0.0001;
+    /// use bloom_filter_simple::{BloomFilter,IndexLayout,StableBloomFilterDefault};
     ///
-    ///     // We initialize two new KMBloomFilter 
-    ///     let mut filter_one: KMBloomFilter<AHasher, DefaultHasher> = KMBloomFilter::new(
-    ///         desired_capacity,
-    ///         desired_fp_probability
-    ///     );
-    /// 
-    ///     let mut filter_two: KMBloomFilter<AHasher, DefaultHasher> = KMBloomFilter::new(
-    ///         desired_capacity,
-    ///         desired_fp_probability
-    ///     );
-    /// 
-    ///     // Insert elements into the first filter
-    ///     filter_one.insert(&0);
-    ///     filter_one.insert(&1);
-    /// 
-    ///     // Insert elements into the second filter
-    ///     filter_two.insert(&1);
-    ///     filter_two.insert(&2);
-    ///     
-    ///     // Now we retrieve the intersection of both filters
-    ///     let filter_intersection = filter_one.intersect(&filter_two);
+    /// let mut filter =
+    ///     StableBloomFilterDefault::new(1_000, 0.001).with_index_layout(IndexLayout::Global);
+    /// filter.insert(&"Hello!");
     ///
-    ///     // The intersection will return true for a 'contains' check for the elements inserted 
-    ///     // previously into both constituent filters.
-    ///     assert_eq!(false, filter_intersection.contains(&0));
-    ///     assert_eq!(true, filter_intersection.contains(&1));
-    ///     assert_eq!(false, filter_intersection.contains(&2));
-    /// }
+    /// assert_eq!(true, filter.contains(&"Hello!"));
+    /// assert_eq!(IndexLayout::Global, filter.index_layout());
     /// ```
-    pub fn intersect(&self, other: &Self) -> Self {
-        if !self.eq_configuration(other) {
-            panic!("unable to intersect k-m bloom filters with different configurations");
-        }
-        Self {
-            number_of_hashers: self.number_of_hashers,
-            bitset: self.bitset.intersect(&other.bitset),
-            bits_per_hasher: self.bits_per_hasher,
-            _phantom: self._phantom,
+    pub fn with_index_layout(mut self, layout: IndexLayout) -> Self {
+        if layout != self.layout && self.bitset.count_ones() > 0 {
+            panic!("unable to change the index layout of a k-m bloom filter with bits set");
         }
+        self.layout = layout;
+        self
     }
 
-    /// Checks whether two bloom filters were created with the same desired capacity and desired false
-    /// positive probability.
-    pub fn eq_configuration(&self, other: &Self) -> bool {
-        self.number_of_hashers == other.number_of_hashers
-            && self.bits_per_hasher == other.bits_per_hasher
+    /// Which bits the simulated hash functions address, see *with_index_layout*.
+    pub fn index_layout(&self) -> IndexLayout {
+        self.layout
     }
 
-    fn generate_hashes<T>(&self, data: &T) -> (u64, u64)
-    where
-        T: Hash,
-    {
-        let mut hasher = H1::default();
-        data.hash(&mut hasher);
-        let hash_a = hasher.finish();
-
-        let mut hasher = H2::default();
-        data.hash(&mut hasher);
-        let hash_b = hasher.finish();
-
-        (hash_a, hash_b)
+    /// Interpret the bits of the filter with *layout*, e.g., bits that have been read from a file
+    /// that has been written with *layout*.
+    pub(crate) fn with_layout_of_bits(mut self, layout: IndexLayout) -> Self {
+        self.layout = layout;
+        self
     }
 
-    fn index(i: usize, bits_per_hash: usize, hash_a: u64, hash_b: u64) -> usize {
-        i * bits_per_hash
-            + hash_a.wrapping_add((i as u64).wrapping_mul(hash_b)) as usize % bits_per_hash
+    /// Refuse insertions that would make the approximate number of elements (see
+    /// *approximate_element_count*) exceed *capacity*, usually the desired capacity the filter has
+    /// been created for. This guarantees that the approximate false positive probability of the
+    /// filter never silently grows beyond the desired false positive probability.
+    ///
+    /// In strict mode, *try_insert* returns *CapacityExceeded* instead of inserting an element that
+    /// would exceed the capacity, and all other ways of inserting elements panic in this case.
+    /// The capacity is not checked by *union_with*, and it is not persisted by *write_to*.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{CapacityExceeded,StableBloomFilterDefault};
+    ///
+    /// let mut filter = StableBloomFilterDefault::new(100, 0.01).with_strict_capacity(100);
+    /// let mut inserted = 0;
+    /// while filter.try_insert(&inserted).is_ok() {
+    ///     inserted += 1;
+    /// }
+    ///
+    /// assert!(inserted > 90 && inserted < 110);
+    /// assert!(filter.approximate_element_count() <= 100.0);
+    /// assert_eq!(Err(CapacityExceeded), filter.try_insert(&inserted));
+    /// ```
+    pub fn with_strict_capacity(mut self, capacity: usize) -> Self {
+        self.strict_capacity = Some(capacity);
+        self
     }
-}
 
-impl<H1, H2> Debug for KMBloomFilter<H1, H2>
-where
-    H1: Hasher + Default,
-    H2: Hasher + Default,
-{
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "KMBloomFilter{{{:?}}}", self.bitset)
+    /// Handle insertions that make the approximate false positive probability (see
+    /// *approximate_current_false_positive_probability*) exceed *target_false_positive_probability*
+    /// according to *on_saturation*, instead of silently degrading, e.g., to alert operators when
+    /// more elements arrive than the filter has been sized for.
+    ///
+    /// The target is usually the desired false positive probability the filter has been created
+    /// for. With *OnSaturation::Warn*, the function is called once when an insertion makes the
+    /// probability exceed the target, and again only after the filter has been cleared; for
+    /// *insert_all* and *extend*, it is called after all elements have been inserted. With
+    /// *OnSaturation::Error*, insertions are refused like with *with_strict_capacity*.
+    ///
+    /// Every insertion counts the bits that are set, which makes insertions slower, unless the
+    /// policy is *OnSaturation::Ignore*. The policy is not applied by *union_with*, and it is not
+    /// persisted by *write_to*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if target_false_positive_probability is not in the interval (0,1).
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,OnSaturation,StableBloomFilterDefault};
+    ///
+    /// fn alert(false_positive_probability: f64) {
+    ///     eprintln!("filter saturated: false positive probability {}", false_positive_probability);
+    /// }
+    ///
+    /// let mut filter =
+    ///     StableBloomFilterDefault::new(100, 0.01).with_saturation_policy(0.01, OnSaturation::Warn(alert));
+    /// // Prints a single warning once the filter holds about 100 elements
+    /// filter.insert_all(0..200);
+    ///
+    /// let mut filter =
+    ///     StableBloomFilterDefault::new(100, 0.01).with_saturation_policy(0.01, OnSaturation::Error);
+    /// let mut inserted = 0;
+    /// while filter.try_insert(&inserted).is_ok() {
+    ///     inserted += 1;
+    /// }
+    /// assert!(filter.approximate_current_false_positive_probability() <= 0.01);
+    /// ```
+    pub fn with_saturation_policy(
+        mut self,
+        target_false_positive_probability: impl Into<f64>,
+        on_saturation: OnSaturation,
+    ) -> Self {
+        let target = params::checked_false_positive_probability(target_false_positive_probability);
+        self.saturation = Some((target, on_saturation));
+        self
     }
-}
 
-impl<H1, H2> BloomFilter for KMBloomFilter<H1, H2>
-where
-    H1: Hasher + Default,
-    H2: Hasher + Default,
-{
-    fn insert<T>(&mut self, data: &T)
+    /// Set every bit with probability *fraction* at random (see *add_noise*) right after
+    /// construction, so every element inserted later is hidden among the noise.
+    ///
+    /// Only available with the *rand* feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if fraction is not in the interval [0,1).
+    #[cfg(feature = "rand")]
+    pub fn with_noise<R>(mut self, fraction: f64, rng: &mut R) -> Self
+    where
+        R: rand::Rng + ?Sized,
+    {
+        self.add_noise(fraction, rng);
+        self
+    }
+
+    /// Set every bit with probability *fraction* at random, e.g., before a filter is shared with a
+    /// partner, so that no element is known to be a member for certain: every element that has not
+    /// been inserted is contained with at least the probability *fraction^k* for *k* hashers, and
+    /// more with every element that has been inserted. Noise cannot be removed, and it raises the
+    /// false positive probability of the filter for its owner as well.
+    ///
+    /// The filter remembers the noise, and *approximate_element_count*,
+    /// *approximate_current_false_positive_probability*, and the estimates based on them discount
+    /// it. Combining filters combines their noise, e.g., the noise of a union is the probability that
+    /// either filter has set a bit at random. The noise is not persisted by *write_to*: a filter
+    /// that is read back, e.g., by a partner, counts the noise as elements.
+    ///
+    /// Only available with the *rand* feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if fraction is not in the interval [0,1).
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    /// use rand::{rngs::StdRng, SeedableRng};
+    ///
+    /// let mut filter = StableBloomFilterDefault::new(10_000, 0.001);
+    /// filter.insert_all(0..5_000);
+    /// let before = filter.approximate_current_false_positive_probability();
+    ///
+    /// filter.add_noise(0.2, &mut StdRng::seed_from_u64(42));
+    /// assert!((filter.approximate_element_count() - 5_000.0).abs() < 150.0);
+    /// assert!(filter.approximate_current_false_positive_probability() > 10.0 * before);
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn add_noise<R>(&mut self, fraction: f64, rng: &mut R)
+    where
+        R: rand::Rng + ?Sized,
+    {
+        if !(0.0..1.0).contains(&fraction) {
+            panic!(
+                "unable to add noise with a fraction of {} to a k-m bloom filter",
+                fraction
+            );
+        }
+        if fraction > 0.0 {
+            // The gaps between the bits that are set are geometrically distributed, which draws
+            // one random number per set bit instead of one per bit
+            let ln_miss = math::ln(1.0 - fraction);
+            let mut index = 0;
+            loop {
+                let gap = math::ln(1.0 - rng.gen::<f64>()) / ln_miss;
+                if gap >= (self.bitset.len() - index) as f64 {
+                    break;
+                }
+                index += gap as usize;
+                self.bitset.set(index, true);
+                index += 1;
+            }
+        }
+        self.noise = union_noise(self.noise, fraction);
+    }
+
+    /// Probability with which every bit has been set at random by *add_noise*, given as a value in
+    /// the interval [0,1). The probability is 0 for filters without noise.
+    pub fn noise_fraction(&self) -> f64 {
+        self.noise
+    }
+
+    /// Insert data into the bloom filter, unless the filter has a strict capacity (see
+    /// *with_strict_capacity*) that the insertion would exceed.
+    ///
+    /// Without a strict capacity or the saturation policy *OnSaturation::Error* (see
+    /// *with_saturation_policy*), the insertion always succeeds.
+    pub fn try_insert<T>(&mut self, data: &T) -> Result<(), CapacityExceeded>
+    where
+        T: Hash,
+    {
+        self.try_insert_hashed(&self.hash(data))
+    }
+
+    /// Insert the element *key* has been computed for, unless the insertion would exceed the
+    /// strict capacity or the target of the saturation policy *OnSaturation::Error*, see
+    /// *try_insert*.
+    ///
+    /// See *hash* for how to compute a key.
+    pub fn try_insert_hashed(&mut self, key: &HashedKey<H1, H2>) -> Result<(), CapacityExceeded> {
+        self.check_capacity(key.hashes())?;
+        let previous_ones = self.ones_before_insertion();
+        self.set_hashes(key.hashes());
+        self.warn_if_saturated(previous_ones);
+        self.metrics.record_inserts(1);
+        Ok(())
+    }
+
+    /// Start a batch of insertions that are only applied to the filter when the batch is
+    /// committed, see InsertBatch.
+    pub fn batch(&mut self) -> InsertBatch<'_, H1, H2> {
+        InsertBatch::new(self)
+    }
+
+    /// Create a new KMBloomFilter for the elements of *iter* that guarantees a false positive rate
+    /// of less than *desired_false_positive_probability* and insert all elements.
+    ///
+    /// The desired capacity is the number of elements of *iter*. It is taken from the size hint of
+    /// *iter* if the hint is exact. Otherwise, the elements are collected first to count them.
+    /// Use *new* and *extend* if you want to leave room for more elements.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// let urls = vec!["https://example.com", "https://example.org"];
+    /// let filter = StableBloomFilterDefault::from_iter_with_fp(urls.iter(), 0.001);
+    ///
+    /// assert_eq!(true, filter.contains(&&"https://example.com"));
+    /// assert_eq!(false, filter.contains(&&"https://example.net"));
+    /// ```
+    pub fn from_iter_with_fp<T, I>(
+        iter: I,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        filter_from_iter(iter, |desired_capacity| {
+            Self::new(desired_capacity, desired_false_positive_probability)
+        })
+    }
+
+    /// Create a new, empty KMBloomFilter that is sized from a *sample* of the elements that will be
+    /// inserted, e.g., a random subset of a stream or dataset, if the number of distinct elements
+    /// is not known in advance.
+    ///
+    /// The distinct elements of the sample are counted with a DistinctCounter. The elements are
+    /// assumed to be drawn at random from a fixed set of distinct elements, whose size is derived
+    /// from how many elements of the sample repeat. The desired capacity is the expected number of
+    /// distinct elements among *expected_total_hint* elements drawn from this set, where the hint
+    /// includes duplicates. The distinct count is raised by three standard errors of the counter
+    /// first, so that the filter is rarely undersized.
+    ///
+    /// If *expected_total_hint* is less than the length of the sample, the sample is taken as the
+    /// whole data. An empty sample provides no information on duplicates, so the desired capacity is
+    /// *expected_total_hint*. The elements of the sample are not inserted.
+    ///
+    /// The sample should be spread over the whole data: duplicates that follow each other closely,
+    /// e.g., bursts of events of the same user, make the set of distinct elements look smaller than
+    /// it is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_false_positive_probability is not in the interval (0,1).
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::StableBloomFilterDefault;
+    ///
+    /// // Every element of the sample appears twice, so the stream repeats a small set of elements
+    /// let sample: Vec<u64> = (0..5_000).chain(0..5_000).collect();
+    /// let filter = StableBloomFilterDefault::new_from_sample(&sample, 0.01, 1_000_000);
+    ///
+    /// let filter_for_all = StableBloomFilterDefault::new(1_000_000, 0.01);
+    /// assert!(filter.bit_count() < filter_for_all.bit_count() / 10);
+    /// ```
+    pub fn new_from_sample<T, I>(
+        sample: I,
+        desired_false_positive_probability: impl Into<f64>,
+        expected_total_hint: usize,
+    ) -> Self
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        let mut counter: DistinctCounter<H1> = DistinctCounter::default();
+        let mut sample_length = 0usize;
+        for data in sample {
+            counter.insert(&data);
+            sample_length += 1;
+        }
+        let total = expected_total_hint.max(sample_length) as f64;
+        let desired_capacity = if sample_length == 0 {
+            total
+        } else {
+            let sample_length = sample_length as f64;
+            let distinct_count = counter.approximate_distinct_count()
+                * (1.0 + 3.0 * counter.relative_standard_error());
+            if distinct_count >= sample_length {
+                total
+            } else {
+                let population = population_size(sample_length, distinct_count);
+                population * (1.0 - math::exp(-total / population))
+            }
+        };
+        Self::new(
+            (math::ceil(desired_capacity) as usize).max(1),
+            desired_false_positive_probability,
+        )
+    }
+
+    /// Create a new KMBloomFilter that guarantees a false positive rate of less than
+    /// *desired_false_positive_probability* for up to *desired_capacity* elements and insert the
+    /// elements of the parallel iterator *iter*.
+    ///
+    /// The elements are hashed on the threads of the current rayon thread pool, which set the bits
+    /// of a single shared bitset with atomic operations. Hence, building a filter needs no more
+    /// memory than the filter itself, regardless of the number of threads. The result is the same
+    /// as inserting all elements sequentially.
+    ///
+    /// Only available with the *rayon* feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0 or if desired_false_positive_probability is not in the
+    /// interval (0,1).
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,DefaultBloomFilter};
+    ///
+    /// let filter = DefaultBloomFilter::par_from_iter(0..100_000, 100_000, 0.001);
+    ///
+    /// assert_eq!(true, filter.contains(&5));
+    /// assert_eq!(true, filter.contains(&99_999));
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn par_from_iter<T, I>(
+        iter: I,
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self
+    where
+        T: Hash,
+        I: rayon::iter::IntoParallelIterator<Item = T>,
+    {
+        use rayon::iter::ParallelIterator;
+
+        let params =
+            FilterParams::for_capacity(desired_capacity, desired_false_positive_probability);
+        let number_of_hashers = params.number_of_hashers();
+        let bits_per_hasher = params.bits_per_hasher();
+        let bitset = AtomicBitset::new(params.bit_count());
+        let metrics = Metrics::default();
+        iter.into_par_iter().for_each(|data| {
+            let (hash_a, hash_b) = Self::generate_hashes(&data);
+            for i in 0..number_of_hashers {
+                bitset.set(Self::index(i, bits_per_hasher, hash_a, hash_b));
+            }
+            metrics.record_inserts(1);
+        });
+        Self {
+            number_of_hashers,
+            bitset: bitset.into_bitset(),
+            bits_per_hasher,
+            layout: IndexLayout::Partitioned,
+            strict_capacity: None,
+            saturation: None,
+            noise: 0.0,
+            metrics,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Create a new KMBloomFilter that guarantees a false positive rate of less than
+    /// *desired_false_positive_probability* for up to *desired_capacity* elements and insert
+    /// every line of *reader* (see *BloomFilter::insert_lines*).
+    ///
+    /// Only available with the *io* feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind *InvalidData* if a line is not valid UTF-8, and forwards errors
+    /// of *reader*.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::BufReader;
+    /// use bloom_filter_simple::{BloomFilter,DefaultBloomFilter};
+    ///
+    /// let keywords = BufReader::new("apple\nbanana\n".as_bytes());
+    /// let filter = DefaultBloomFilter::from_lines(keywords, 1_000, 0.001).unwrap();
+    ///
+    /// assert_eq!(true, filter.contains(&"apple"));
+    /// ```
+    #[cfg(feature = "io")]
+    pub fn from_lines<R>(
+        reader: R,
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> std::io::Result<Self>
+    where
+        R: std::io::BufRead,
+    {
+        let mut filter = Self::new(desired_capacity, desired_false_positive_probability);
+        filter.insert_lines(reader)?;
+        Ok(filter)
+    }
+
+    /// Create a new KMBloomFilter that guarantees a false positive rate of less than
+    /// *desired_false_positive_probability* for up to *desired_capacity* elements and insert
+    /// every key of *reader*, which are separated by *delimiter* (see
+    /// *BloomFilter::insert_delimited*).
+    ///
+    /// Only available with the *io* feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind *InvalidData* if a key is not valid UTF-8, and forwards errors
+    /// of *reader*.
+    #[cfg(feature = "io")]
+    pub fn from_delimited<R>(
+        reader: R,
+        delimiter: u8,
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> std::io::Result<Self>
+    where
+        R: std::io::BufRead,
+    {
+        let mut filter = Self::new(desired_capacity, desired_false_positive_probability);
+        filter.insert_delimited(reader, delimiter)?;
+        Ok(filter)
+    }
+
+    /// Approximate number of elements stored.
+    /// Approximation technique taken from Wikipedia:
+    /// > Wikipedia, ["Bloom filter"](https://en.wikipedia.org/wiki/Bloom_filter#Approximating_the_number_of_items_in_a_Bloom_filter) [Accessed: 02.12.2020]
+    pub fn approximate_element_count(&self) -> f64 {
+        self.element_count_for_ones(self.bitset.count_ones())
+    }
+
+    /// Return the current approximate false positive probability which depends on the current
+    /// number of elements in the filter.
+    ///
+    /// The probability is given as a value in the interval [0,1]
+    /// Approximation technique taken from Sagi Kedmi:
+    /// > S. Kedmi, ["Bloom Filters for the Perplexed"](https://sagi.io/bloom-filters-for-the-perplexed/), July 2017 [Accessed: 02.12.2020]
+    pub fn approximate_current_false_positive_probability(&self) -> f64 {
+        self.false_positive_probability_for(self.approximate_element_count())
+    }
+
+    /// Lower and upper bound of an approximate 95% confidence interval for the number of elements
+    /// stored, based on the variance of the number of bits that are set.
+    ///
+    /// The upper bound is infinite if the filter is (almost) full. See
+    /// *params::approximate_element_count_bounds* for details.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// fn main() {
+    ///     let mut filter = StableBloomFilterDefault::new(100_000, 0.001);
+    ///     filter.insert_all(0..50_000);
+    ///
+    ///     let (lower, upper) = filter.approximate_element_count_bounds();
+    ///     assert!(lower <= filter.approximate_element_count());
+    ///     assert!(filter.approximate_element_count() <= upper);
+    ///     assert!(upper - lower < 2_000.0);
+    /// }
+    /// ```
+    pub fn approximate_element_count_bounds(&self) -> (f64, f64) {
+        let (lower, upper) = approximate_element_count_bounds(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.bitset.count_ones(),
+        );
+        // The noise shifts the counts of all numbers of ones by the same amount
+        let noise = self.bits_per_hasher as f64 * math::ln(1.0 - self.noise);
+        ((lower + noise).max(0.0), (upper + noise).max(0.0))
+    }
+
+    /// Lower and upper bound of an approximate 95% confidence interval for the current false
+    /// positive probability, i.e., the false positive probabilities for the bounds of
+    /// *approximate_element_count_bounds*.
+    pub fn approximate_current_false_positive_probability_bounds(&self) -> (f64, f64) {
+        let (lower, upper) = self.approximate_element_count_bounds();
+        (
+            self.false_positive_probability_for(lower),
+            self.false_positive_probability_for(upper),
+        )
+    }
+
+    /// Approximate number of elements stored in the union of this bloom filter and 'other', without
+    /// creating the union.
+    ///
+    /// The approximation is equal to calling 'approximate_element_count' on the result of 'union'.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,KMBloomFilter};
+    /// use ahash::AHasher;
+    /// use std::collections::hash_map::DefaultHasher;
+    ///
+    /// fn main() {
+    ///     let mut filter_one: KMBloomFilter<AHasher, DefaultHasher> = KMBloomFilter::new(1_000, 0.0001);
+    ///     let mut filter_two: KMBloomFilter<AHasher, DefaultHasher> = KMBloomFilter::new(1_000, 0.0001);
+    ///
+    ///     for i in 0..100 {
+    ///         filter_one.insert(&i);
+    ///     }
+    ///     for i in 50..150 {
+    ///         filter_two.insert(&i);
+    ///     }
+    ///
+    ///     // There are 150 distinct elements in both filters
+    ///     assert!((filter_one.approximate_union_count(&filter_two) - 150.0).abs() < 5.0);
+    ///     // Of which 50 elements are contained in both filters
+    ///     assert!((filter_one.approximate_intersection_count(&filter_two) - 50.0).abs() < 5.0);
+    /// }
+    /// ```
+    pub fn approximate_union_count(&self, other: &Self) -> f64 {
+        if !self.eq_configuration(other) {
+            panic!("unable to union k-m bloom filters with different configurations");
+        }
+        approximate_element_count_with_noise(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.bitset.count_ones_union(&other.bitset),
+            union_noise(self.noise, other.noise),
+        )
+    }
+
+    /// Approximate number of elements stored in both this bloom filter and 'other', without creating
+    /// the intersection.
+    ///
+    /// In contrast to calling 'approximate_element_count' on the result of 'intersect', which
+    /// overestimates the number of elements, the approximation is computed from the approximate
+    /// element counts of both filters and their union:
+    /// *n(A ∩ B) = n(A) + n(B) - n(A ∪ B)*.
+    ///
+    /// Formula taken from Swamidass and Baldi:
+    /// > S. J. Swamidass, P. Baldi, "Mathematical correction for fingerprint similarity measures to improve chemical retrieval", Journal of Chemical Information and Modeling 47(3), 2007, 952–964. DOI: [https://doi.org/10.1021/ci600526a](https://doi.org/10.1021/ci600526a)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    pub fn approximate_intersection_count(&self, other: &Self) -> f64 {
+        if !self.eq_configuration(other) {
+            panic!("unable to intersect k-m bloom filters with different configurations");
+        }
+        approximate_intersection_count(
+            self.approximate_element_count(),
+            other.approximate_element_count(),
+            self.approximate_union_count(other),
+        )
+    }
+
+    /// Approximate number of elements stored in this bloom filter but not in 'other', without
+    /// creating the difference.
+    ///
+    /// The approximation is computed from the approximate element counts of 'other' and of the
+    /// union of both filters: *n(A \\ B) = n(A ∪ B) - n(B)*. Like all approximations of this
+    /// crate, it is only reliable as long as both filters hold at most their desired capacity.
+    /// Calling 'approximate_element_count' on the result of 'difference' gives no meaningful result.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// fn main() {
+    ///     let mut shard = StableBloomFilterDefault::new(1_000, 0.001);
+    ///     let mut aggregate = StableBloomFilterDefault::new(1_000, 0.001);
+    ///
+    ///     shard.insert_all(0..150);
+    ///     aggregate.insert_all(0..100);
+    ///
+    ///     // The shard contains about 50 elements that are missing in the aggregate
+    ///     assert!((shard.approximate_difference_count(&aggregate) - 50.0).abs() < 5.0);
+    /// }
+    /// ```
+    pub fn approximate_difference_count(&self, other: &Self) -> f64 {
+        if !self.eq_configuration(other) {
+            panic!("unable to subtract k-m bloom filters with different configurations");
+        }
+        approximate_difference_count(
+            other.approximate_element_count(),
+            self.approximate_union_count(other),
+        )
+    }
+
+    /// Approximate Jaccard similarity of the elements stored in this bloom filter and 'other',
+    /// i.e., *|A ∩ B| / |A ∪ B|*, given as a value in the interval [0,1].
+    ///
+    /// The similarity is computed from the numbers of set bits of both filters and of their union,
+    /// see *approximate_intersection_count*. The similarity of two empty filters is 1. The estimate
+    /// is accurate as long as the union holds at most the desired capacity of the filters, e.g.,
+    /// the absolute error is typically below 0.01 for unions of tens of thousands of elements. It
+    /// grows as the union exceeds the capacity and the filters saturate. For small similarities,
+    /// the intersection is the difference of two large counts, so the relative error is large.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// fn main() {
+    ///     let mut filter_one = StableBloomFilterDefault::new(10_000, 0.001);
+    ///     let mut filter_two = StableBloomFilterDefault::new(10_000, 0.001);
+    ///
+    ///     filter_one.insert_all(0..3_000);
+    ///     filter_two.insert_all(1_000..4_000);
+    ///
+    ///     // 2,000 of 4,000 distinct elements are contained in both filters
+    ///     assert!((filter_one.jaccard_similarity(&filter_two) - 0.5).abs() < 0.02);
+    /// }
+    /// ```
+    pub fn jaccard_similarity(&self, other: &Self) -> f64 {
+        if !self.eq_configuration(other) {
+            panic!("unable to compare k-m bloom filters with different configurations");
+        }
+        approximate_jaccard_similarity(
+            self.approximate_element_count(),
+            other.approximate_element_count(),
+            self.approximate_union_count(other),
+        )
+    }
+
+    /// Approximate number of elements stored in both filters for every pair of *filters*, e.g.,
+    /// to cluster per-user or per-day filters by their overlap.
+    ///
+    /// Entry *[i][j]* is *filters[i].approximate_intersection_count(&filters[j])*, and entry
+    /// *[i][i]* is *filters[i].approximate_element_count()*. The matrix is symmetric. The element
+    /// count of every filter is computed only once, and the set bits of the union of every pair
+    /// are counted without creating the union, so no filter is allocated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of the filters differ.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// fn main() {
+    ///     let days: Vec<StableBloomFilterDefault> = (0..3)
+    ///         .map(|day| {
+    ///             let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+    ///             filter.insert_all(day * 100..day * 100 + 200);
+    ///             filter
+    ///         })
+    ///         .collect();
+    ///
+    ///     let overlap = StableBloomFilterDefault::overlap_matrix(&days);
+    ///     // Consecutive days share 100 elements, the first and the last day none
+    ///     assert!((overlap[0][1] - 100.0).abs() < 5.0);
+    ///     assert!(overlap[0][2].abs() < 5.0);
+    ///     assert_eq!(overlap[1][2], overlap[2][1]);
+    /// }
+    /// ```
+    pub fn overlap_matrix(filters: &[Self]) -> Vec<Vec<f64>> {
+        if let Some(first) = filters.first() {
+            if filters.iter().any(|filter| !first.eq_configuration(filter)) {
+                panic!("unable to intersect k-m bloom filters with different configurations");
+            }
+        }
+        let element_counts: Vec<f64> = filters
+            .iter()
+            .map(|filter| filter.approximate_element_count())
+            .collect();
+        let mut matrix = vec![vec![0.0; filters.len()]; filters.len()];
+        for i in 0..filters.len() {
+            matrix[i][i] = element_counts[i];
+            for j in i + 1..filters.len() {
+                let overlap = approximate_intersection_count(
+                    element_counts[i],
+                    element_counts[j],
+                    filters[i].approximate_union_count(&filters[j]),
+                );
+                matrix[i][j] = overlap;
+                matrix[j][i] = overlap;
+            }
+        }
+        matrix
+    }
+
+    /// Creates a bloom filter in which exactly the bits are set that are set in this bloom filter
+    /// but not in 'other' (bitwise *A AND NOT B*).
+    ///
+    /// In contrast to *union* and *intersect*, the result is **not** a bloom filter of the elements
+    /// that have been inserted into this filter but not into 'other':
+    /// * 'contains' of the result returns false for all elements inserted into 'other', even if
+    ///   they have been inserted into this filter, too.
+    /// * 'contains' of the result can return false for elements that have only been inserted into
+    ///   this filter, i.e., the result has false negatives. This happens whenever a bit of such an
+    ///   element is also set in 'other', which gets more likely the fuller 'other' is.
+    ///
+    /// Use the result to analyze which bits are new, not as a filter. To estimate the number of
+    /// elements in the difference, use *approximate_difference_count*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    pub fn difference(&self, other: &Self) -> Self {
+        if !self.eq_configuration(other) {
+            panic!("unable to subtract k-m bloom filters with different configurations");
+        }
+        Self {
+            number_of_hashers: self.number_of_hashers,
+            bitset: self.bitset.difference(&other.bitset),
+            bits_per_hasher: self.bits_per_hasher,
+            layout: self.layout,
+            strict_capacity: self.strict_capacity,
+            saturation: self.saturation,
+            noise: self.noise * (1.0 - other.noise),
+            metrics: Metrics::default(),
+            _phantom: self._phantom,
+        }
+    }
+
+    /// Shrink the filter by *factor* by folding the bits of every hasher in half until only one
+    /// *factor*-th of them remain, where folding OR-s the upper half of the bits onto the lower
+    /// half. With the layout *IndexLayout::Global*, all bits of the filter are folded at once.
+    ///
+    /// The compacted filter contains all elements of this filter, and behaves exactly like a filter
+    /// with one *factor*-th of the bits per hasher that all elements have been inserted into. Its
+    /// false positive probability is higher accordingly, see
+    /// *approximate_current_false_positive_probability*. Only filters with the same number of bits
+    /// per hasher can be combined, so compact all filters by the same factor before combining
+    /// them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if *factor* is not a power of two or does not divide the number of bits per hasher.
+    /// Use *new_compactable* to create filters that can be compacted.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// fn main() {
+    ///     let mut filter = StableBloomFilterDefault::new_compactable(100_000, 0.0001, 4);
+    ///     filter.insert_all(0..1_000);
+    ///
+    ///     let bit_count = filter.bit_count();
+    ///     let compacted = filter.compact(4);
+    ///     assert_eq!(bit_count / 4, compacted.bit_count());
+    ///     assert_eq!(true, compacted.contains(&999));
+    /// }
+    /// ```
+    pub fn compact(self, factor: usize) -> Self {
+        if !factor.is_power_of_two() || !self.bits_per_hasher.is_multiple_of(factor) {
+            panic!(
+                "unable to compact k-m bloom filter with {} bits per hasher by a factor of {}",
+                self.bits_per_hasher, factor
+            );
+        }
+        // Indices within the range of a hasher are reduced modulo the number of bits per hasher,
+        // and reducing them modulo a divisor of that number gives the same result as folding. The
+        // same holds for the indices of the global layout and the number of bits.
+        let bits_per_hasher = self.bits_per_hasher / factor;
+        let bit_count = self.number_of_hashers * bits_per_hasher;
+        let mut bitset = Bitset::new(bit_count);
+        for index in self.bitset.iter_ones() {
+            let folded = match self.layout {
+                IndexLayout::Partitioned => {
+                    let hasher = index / self.bits_per_hasher;
+                    hasher * bits_per_hasher + index % self.bits_per_hasher % bits_per_hasher
+                }
+                IndexLayout::Global => index % bit_count,
+            };
+            bitset.set(folded, true);
+        }
+        Self {
+            number_of_hashers: self.number_of_hashers,
+            bitset,
+            bits_per_hasher,
+            layout: self.layout,
+            strict_capacity: self.strict_capacity,
+            saturation: self.saturation,
+            // A folded bit is set by the noise if any of the bits folded onto it is
+            noise: 1.0 - math::powf(1.0 - self.noise, factor as f64),
+            metrics: Metrics::default(),
+            _phantom: self._phantom,
+        }
+    }
+
+    /// Creates a union of this bloom filter and 'other', which means 'contains' of the resulting
+    /// bloom filter will always return true for elements inserted in either this bloom filter or in
+    /// 'other' before creation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    ///
+    /// # Examples
+    ///
+    /// Union of two bloom filters with the same configuration.
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,KMBloomFilter};
+    /// use ahash::AHasher;
+    /// use std::collections::hash_map::DefaultHasher;
+    ///
+    /// fn main() {
+    ///     // The configuration of both bloom filters has to be the same
+    ///     let desired_capacity = 10_000;
+    ///     let desired_fp_probability = 0.0001;
+    ///
+    ///     // We initialize two new KMBloomFilter
+    ///     let mut filter_one: KMBloomFilter<AHasher, DefaultHasher> = KMBloomFilter::new(
+    ///         desired_capacity,
+    ///         desired_fp_probability
+    ///     );
+    ///
+    ///     let mut filter_two: KMBloomFilter<AHasher, DefaultHasher> = KMBloomFilter::new(
+    ///         desired_capacity,
+    ///         desired_fp_probability
+    ///     );
+    ///
+    ///     // Insert elements into the first filter
+    ///     filter_one.insert(&0);
+    ///     filter_one.insert(&1);
+    ///
+    ///     // Insert elements into the second filter
+    ///     filter_two.insert(&2);
+    ///     filter_two.insert(&3);
+    ///
+    ///     // Now we retrieve the union of both filters
+    ///     let filter_union = filter_one.union(&filter_two);
+    ///
+    ///     // The union will return true for a 'contains' check for the elements inserted
+    ///     // previously into at least one of the constituent filters.
+    ///     assert_eq!(true, filter_union.contains(&0));
+    ///     assert_eq!(true, filter_union.contains(&1));
+    ///     assert_eq!(true, filter_union.contains(&2));
+    ///     assert_eq!(true, filter_union.contains(&3));
+    /// }
+    /// ```
+    pub fn union(&self, other: &Self) -> Self {
+        if !self.eq_configuration(other) {
+            panic!("unable to union k-m bloom filters with different configurations");
+        }
+        Self {
+            number_of_hashers: self.number_of_hashers,
+            bitset: self.bitset.union(&other.bitset),
+            bits_per_hasher: self.bits_per_hasher,
+            layout: self.layout,
+            strict_capacity: self.strict_capacity,
+            saturation: self.saturation,
+            noise: union_noise(self.noise, other.noise),
+            metrics: Metrics::default(),
+            _phantom: self._phantom,
+        }
+    }
+
+    /// Adds all elements of 'other' to this bloom filter, so that it becomes the union of both
+    /// filters (see *union*).
+    ///
+    /// In contrast to *union*, no new filter is allocated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// let mut shards = Vec::new();
+    /// for shard in 0..4 {
+    ///     let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+    ///     filter.insert(&shard);
+    ///     shards.push(filter);
+    /// }
+    ///
+    /// let mut merged = StableBloomFilterDefault::new(1_000, 0.001);
+    /// for shard in shards.iter() {
+    ///     merged.union_with(shard);
+    /// }
+    /// assert_eq!(true, (0..4).all(|shard| merged.contains(&shard)));
+    /// ```
+    pub fn union_with(&mut self, other: &Self) {
+        if !self.eq_configuration(other) {
+            panic!("unable to union k-m bloom filters with different configurations");
+        }
+        self.bitset.union_with(&other.bitset);
+        self.noise = union_noise(self.noise, other.noise);
+    }
+
+    /// Merge all *filters* into a single filter that contains the elements of all of them, e.g.,
+    /// to aggregate the shards of a distributed filter.
+    ///
+    /// The bits of the first filter are reused for the result, and every other filter is merged
+    /// into them in a single pass over its bits and dropped right after. Hence, no filter is
+    /// allocated, and if *filters* loads the filters lazily, e.g., from files, no more than two
+    /// filters are in memory at a time. The result has the strict capacity of the first filter and
+    /// the combined noise of all filters, like with *union_with*.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if *filters* is empty or the configuration of a filter differs from the
+    /// first one. The filters that have been taken from *filters* up to then are dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// let shards = (0..512).map(|shard| {
+    ///     let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+    ///     filter.insert(&shard);
+    ///     filter
+    /// });
+    ///
+    /// let merged = StableBloomFilterDefault::merge_many(shards).unwrap();
+    /// assert_eq!(true, (0..512).all(|shard| merged.contains(&shard)));
+    /// ```
+    pub fn merge_many<I>(filters: I) -> Result<Self, MergeError>
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        let mut filters = filters.into_iter();
+        let mut merged = filters.next().ok_or(MergeError::NoFilters)?;
+        for (position, filter) in filters.enumerate() {
+            if !merged.eq_configuration(&filter) {
+                return Err(MergeError::ConfigurationMismatch {
+                    filter: position + 1,
+                });
+            }
+            merged.bitset.union_with(&filter.bitset);
+            merged.noise = union_noise(merged.noise, filter.noise);
+        }
+        merged.metrics = Metrics::default();
+        Ok(merged)
+    }
+
+    /// The bits that have been set in 'new' since the snapshot 'old', encoded compactly, so that
+    /// replicas of 'old' can catch up with *apply_delta* instead of receiving the whole filter.
+    ///
+    /// The delta only contains the bits, so the replicas have to use the same hashers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'old' and 'new'
+    /// differ.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BitsetDelta,BloomFilter,StableBloomFilterDefault};
+    ///
+    /// let mut filter = StableBloomFilterDefault::new(1_000_000, 0.001);
+    /// filter.insert_all(0..1_000);
+    /// let snapshot = StableBloomFilterDefault::from_bitset(filter.bitset().clone(), filter.hasher_count());
+    /// let mut replica = StableBloomFilterDefault::from_bitset(filter.bitset().clone(), filter.hasher_count());
+    ///
+    /// filter.insert_all(1_000..1_010);
+    /// let delta = StableBloomFilterDefault::delta(&snapshot, &filter);
+    /// assert!(delta.as_bytes().len() < 1_000);
+    ///
+    /// let delta = BitsetDelta::from_bytes(delta.as_bytes().to_vec()).unwrap();
+    /// replica.apply_delta(&delta);
+    /// assert_eq!(filter, replica);
+    /// ```
+    pub fn delta(old: &Self, new: &Self) -> BitsetDelta {
+        if !old.eq_configuration(new) {
+            panic!(
+                "unable to compute the delta of k-m bloom filters with different configurations"
+            );
+        }
+        Bitset::delta(&old.bitset, &new.bitset)
+    }
+
+    /// Set the bits of 'delta' (see *delta*), so that the filter contains all elements of the newer
+    /// version of the delta in addition to its own.
+    ///
+    /// # Panics
+    ///
+    /// Panics if 'delta' has been created from filters with a different number of bits.
+    pub fn apply_delta(&mut self, delta: &BitsetDelta) {
+        if delta.bitset_len() != self.bitset.len() {
+            panic!(
+                "unable to apply a delta of {} bits to a k-m bloom filter of {} bits",
+                delta.bitset_len(),
+                self.bitset.len()
+            );
+        }
+        self.bitset.apply_delta(delta);
+    }
+
+    /// Creates a intersection of this bloom filter and 'other', which means 'contains' of the resulting
+    /// bloom filter will always return true for elements inserted both in this bloom filter and in
+    /// 'other' before creation.
+    /// The false positive probability of the resulting bloom filter is at most the false positive
+    /// probability of 'other' or 'self'.
+    /// The false positive probability of the resulting bloom filter may be bigger than the false
+    /// positive probability of a new empty bloom filter with the intersecting elements inserted.
+    /// The functions 'approximate_current_false_positive_probability' and 'approximate_element_count'
+    /// called on the resulting bloom filter may return too big approximations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    ///
+    /// # Examples
+    ///
+    /// Intersection of two bloom filters with the same configuration.
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,KMBloomFilter};
+    /// use ahash::AHasher;
+    /// use std::collections::hash_map::DefaultHasher;
+    ///
+    /// fn main() {
+    ///     // The configuration of both bloom filters has to be the same
+    ///     let desired_capacity = 10_000;
+    ///     let desired_fp_probability = 0.0001;
+    ///
+    ///     // We initialize two new KMBloomFilter
+    ///     let mut filter_one: KMBloomFilter<AHasher, DefaultHasher> = KMBloomFilter::new(
+    ///         desired_capacity,
+    ///         desired_fp_probability
+    ///     );
+    ///
+    ///     let mut filter_two: KMBloomFilter<AHasher, DefaultHasher> = KMBloomFilter::new(
+    ///         desired_capacity,
+    ///         desired_fp_probability
+    ///     );
+    ///
+    ///     // Insert elements into the first filter
+    ///     filter_one.insert(&0);
+    ///     filter_one.insert(&1);
+    ///
+    ///     // Insert elements into the second filter
+    ///     filter_two.insert(&1);
+    ///     filter_two.insert(&2);
+    ///
+    ///     // Now we retrieve the intersection of both filters
+    ///     let filter_intersection = filter_one.intersect(&filter_two);
+    ///
+    ///     // The intersection will return true for a 'contains' check for the elements inserted
+    ///     // previously into both constituent filters.
+    ///     assert_eq!(false, filter_intersection.contains(&0));
+    ///     assert_eq!(true, filter_intersection.contains(&1));
+    ///     assert_eq!(false, filter_intersection.contains(&2));
+    /// }
+    /// ```
+    pub fn intersect(&self, other: &Self) -> Self {
+        if !self.eq_configuration(other) {
+            panic!("unable to intersect k-m bloom filters with different configurations");
+        }
+        Self {
+            number_of_hashers: self.number_of_hashers,
+            bitset: self.bitset.intersect(&other.bitset),
+            bits_per_hasher: self.bits_per_hasher,
+            layout: self.layout,
+            strict_capacity: self.strict_capacity,
+            saturation: self.saturation,
+            noise: self.noise * other.noise,
+            metrics: Metrics::default(),
+            _phantom: self._phantom,
+        }
+    }
+
+    /// Turns this bloom filter into the intersection of both filters (see *intersect*).
+    ///
+    /// In contrast to *intersect*, no new filter is allocated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    pub fn intersect_with(&mut self, other: &Self) {
+        if !self.eq_configuration(other) {
+            panic!("unable to intersect k-m bloom filters with different configurations");
+        }
+        self.bitset.intersect_with(&other.bitset);
+        self.noise *= other.noise;
+    }
+
+    /// Fraction of bits that are set, given as a value in the interval [0,1].
+    ///
+    /// With the optimal number of hashers, about half of the bits are set once the filter holds
+    /// its desired capacity. The fill ratio keeps growing with every insertion, and the false
+    /// positive probability grows with it.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// let mut filter = StableBloomFilterDefault::new(1_000, 0.01);
+    /// filter.insert_all(0..1_000);
+    /// assert!(filter.fill_ratio() > 0.4 && filter.fill_ratio() < 0.6);
+    ///
+    /// filter.insert_all(1_000..5_000);
+    /// assert_eq!(true, filter.is_saturated(0.9));
+    /// ```
+    pub fn fill_ratio(&self) -> f64 {
+        self.bitset.count_ones() as f64 / self.bitset.len() as f64
+    }
+
+    /// Fill ratio of each of *buckets* consecutive regions of the bits of the filter (see
+    /// *Bitset::density_histogram*).
+    ///
+    /// Every hasher sets bits in its own region of *bit_count / hasher_count* bits (or in all bits
+    /// with the layout *IndexLayout::Global*), so the fill ratios of all regions are close to
+    /// *fill_ratio* if the hashers spread the elements evenly.
+    /// Regions that are much fuller than others indicate hashers that cluster similar elements,
+    /// and raise the false positive probability above its approximation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if buckets == 0 or buckets > bit_count.
+    pub fn density_histogram(&self, buckets: usize) -> Vec<f64> {
+        self.bitset.density_histogram(buckets)
+    }
+
+    /// Total number of bits of the filter.
+    pub fn bit_count(&self) -> usize {
+        self.bitset.len()
+    }
+
+    /// The bits of the filter, e.g., to compare them with the bits of another filter.
+    ///
+    /// The bits of the *i*-th simulated hash function are the bits from *i \* bit_count /
+    /// hasher_count* (inclusive) to *(i + 1) \* bit_count / hasher_count* (exclusive).
+    pub fn bitset(&self) -> &Bitset {
+        &self.bitset
+    }
+
+    /// Number of hash functions used for every element.
+    pub fn hasher_count(&self) -> usize {
+        self.number_of_hashers
+    }
+
+    /// Whether the fill ratio has reached *threshold*, i.e., the filter should be replaced by a
+    /// larger or empty one.
+    pub fn is_saturated(&self, threshold: f64) -> bool {
+        self.fill_ratio() >= threshold
+    }
+
+    /// Snapshot of the number of insertions and queries of the filter, together with its current
+    /// fill ratio.
+    ///
+    /// Every inserted element and every query is counted, regardless of how it has been hashed.
+    /// Filters created by combining other filters, e.g., with *union*, start with new counters.
+    /// The counters are atomic, so that queries through shared references are counted, too.
+    ///
+    /// Only available with the *metrics* feature.
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> FilterStats {
+        FilterStats::new(
+            &self.metrics,
+            self.approximate_current_false_positive_probability(),
+            self.fill_ratio(),
+        )
+    }
+
+    /// Reset the counters of *stats* to zero, e.g., after they have been exported.
+    ///
+    /// Only available with the *metrics* feature.
+    #[cfg(feature = "metrics")]
+    pub fn reset_stats(&self) {
+        self.metrics.reset();
+    }
+
+    /// Compute the two base hashes of *data*, which can be used to insert or look up *data* in
+    /// this and any other filter with the same hashers (see *HashedKey*).
+    pub fn hash<T>(&self, data: &T) -> HashedKey<H1, H2>
+    where
+        T: Hash,
+    {
+        let (hash_a, hash_b) = Self::generate_hashes(data);
+        HashedKey::from_hashes(hash_a, hash_b)
+    }
+
+    /// Indices of the bits that *data* is mapped to, one for every hash function, e.g., to verify
+    /// an implementation of the hashing scheme in another language.
+    ///
+    /// The index of the *i*-th hash function is *i \* m + (h1 + i \* h2) mod m*, where *m* is
+    /// the number of bits per hash function, *h1* and *h2* are the hash values of *H1* and *H2*,
+    /// and *h1 + i \* h2* is computed with wrapping 64-bit arithmetic. With the layout
+    /// *IndexLayout::Global*, the index is *(h1 + i \* h2) mod n*, where *n* is the number of
+    /// bits of the filter.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// fn main() {
+    ///     let mut filter = StableBloomFilterDefault::new(10, 0.01);
+    ///     filter.insert(&"Hello!");
+    ///
+    ///     let indices = filter.probe_indices(&"Hello!");
+    ///     assert_eq!(filter.hasher_count(), indices.len());
+    ///     assert_eq!(true, indices.iter().all(|&index| filter.bitset().get(index)));
+    /// }
+    /// ```
+    pub fn probe_indices<T>(&self, data: &T) -> Vec<usize>
+    where
+        T: Hash,
+    {
+        let (hash_a, hash_b) = Self::generate_hashes(data);
+        (0..self.number_of_hashers)
+            .map(|i| self.bit_of(i, hash_a, hash_b))
+            .collect()
+    }
+
+    /// Insert the element *key* has been computed for.
+    ///
+    /// The result is the same as calling *insert* with the element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filter has a strict capacity that the insertion would exceed, or if the
+    /// insertion would exceed the target false positive probability of the saturation policy
+    /// *OnSaturation::Error*.
+    pub fn insert_hashed(&mut self, key: &HashedKey<H1, H2>) {
+        if let Err(error) = self.check_capacity(key.hashes()) {
+            panic!("{}", error);
+        }
+        let previous_ones = self.ones_before_insertion();
+        self.set_hashes(key.hashes());
+        self.warn_if_saturated(previous_ones);
+        self.metrics.record_inserts(1);
+    }
+
+    /// Insert the element *key* has been computed for and return whether it has been contained in
+    /// the bloom filter before.
+    ///
+    /// The result is the same as calling *check_and_insert* with the element: every bit is
+    /// checked and set in the same pass.
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as *insert_hashed*.
+    pub fn check_and_insert_hashed(&mut self, key: &HashedKey<H1, H2>) -> bool {
+        if let Err(error) = self.check_capacity(key.hashes()) {
+            panic!("{}", error);
+        }
+        let previous_ones = self.ones_before_insertion();
+        let (hash_a, hash_b) = key.hashes();
+        let mut contained = true;
+        for i in 0..self.number_of_hashers {
+            let index = self.bit_of(i, hash_a, hash_b);
+            contained &= self.bitset.get(index);
+            self.bitset.set(index, true);
+        }
+        self.warn_if_saturated(previous_ones);
+        self.metrics.record_query(contained);
+        self.metrics.record_inserts(1);
+        contained
+    }
+
+    /// Check whether the element *key* has been computed for is contained in the bloom filter.
+    ///
+    /// The result is the same as calling *contains* with the element.
+    pub fn contains_hashed(&self, key: &HashedKey<H1, H2>) -> bool {
+        let (hash_a, hash_b) = key.hashes();
+        #[cfg(not(feature = "prefetch"))]
+        let contained =
+            (0..self.number_of_hashers).all(|i| self.bitset.get(self.bit_of(i, hash_a, hash_b)));
+        #[cfg(feature = "prefetch")]
+        let contained = self.contains_prefetched(hash_a, hash_b);
+        self.metrics.record_query(contained);
+        contained
+    }
+
+    /// Check the bits of an element in batches: compute the indices of a batch of hashers,
+    /// prefetch their words, and then test all bits of the batch without branching on them, so
+    /// that the cache misses of a batch overlap instead of following each other.
+    #[cfg(feature = "prefetch")]
+    fn contains_prefetched(&self, hash_a: u64, hash_b: u64) -> bool {
+        let mut indices = [0; PREFETCH_BATCH_SIZE];
+        (0..self.number_of_hashers)
+            .step_by(PREFETCH_BATCH_SIZE)
+            .all(|start| {
+                let end = (start + PREFETCH_BATCH_SIZE).min(self.number_of_hashers);
+                let batch = &mut indices[..end - start];
+                for (i, index) in (start..end).zip(batch.iter_mut()) {
+                    *index = self.bit_of(i, hash_a, hash_b);
+                    self.bitset.prefetch(*index);
+                }
+                batch
+                    .iter()
+                    .fold(true, |contained, &index| contained & self.bitset.get(index))
+            })
+    }
+
+    /// Insert the raw bytes *bytes*.
+    ///
+    /// In contrast to *insert*, the bytes are written to *H1* and *H2* as they are, without the
+    /// length prefix of the *Hash* implementation of slices. This makes it possible to hash the same
+    /// payload in other languages: with the hashers of StableBloomFilterDefault, the base hashes are
+    /// XXH3 (64 bit) with seed *STABLE_XXH3_SEED* and XXH64 with seed *STABLE_XXH64_SEED* of *bytes*,
+    /// which can be inserted with *insert_hash64*.
+    ///
+    /// Elements inserted with *insert_bytes* are in general only found by *contains_bytes*.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{StableBloomFilterDefault,STABLE_XXH3_SEED,STABLE_XXH64_SEED};
+    /// use xxhash_rust::{xxh3::xxh3_64_with_seed,xxh64::xxh64};
+    ///
+    /// let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+    /// filter.insert_bytes(b"Hello!");
+    ///
+    /// // The same payload, hashed by another producer
+    /// let hash_a = xxh3_64_with_seed(b"Goodbye!", STABLE_XXH3_SEED);
+    /// let hash_b = xxh64(b"Goodbye!", STABLE_XXH64_SEED);
+    /// filter.insert_hash64(hash_a, hash_b);
+    ///
+    /// assert_eq!(true, filter.contains_bytes(b"Hello!"));
+    /// assert_eq!(true, filter.contains_bytes(b"Goodbye!"));
+    /// ```
+    pub fn insert_bytes(&mut self, bytes: &[u8]) {
+        self.insert_hashed(&HashedKey::from_bytes(bytes));
+    }
+
+    /// Check whether the raw bytes *bytes* have been inserted with *insert_bytes* (or as their
+    /// base hashes with *insert_hash64*).
+    pub fn contains_bytes(&self, bytes: &[u8]) -> bool {
+        self.contains_hashed(&HashedKey::from_bytes(bytes))
+    }
+
+    /// Insert the UTF-8 bytes of *text*, which is the same element as *insert_bytes* of
+    /// *text.as_bytes()*.
+    ///
+    /// This is faster than *insert*, because the *Hash* implementation of str writes an additional
+    /// terminating byte to the hashers, which is a second call for short strings. Hence, *insert*
+    /// and *insert_str* of the same string set different bits: elements inserted with *insert_str*
+    /// are in general only found by *contains_str* and *contains_bytes*.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::StableBloomFilterDefault;
+    ///
+    /// let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+    /// filter.insert_str("Hello!");
+    ///
+    /// assert_eq!(true, filter.contains_str("Hello!"));
+    /// assert_eq!(true, filter.contains_bytes(b"Hello!"));
+    /// ```
+    pub fn insert_str(&mut self, text: &str) {
+        self.insert_bytes(text.as_bytes());
+    }
+
+    /// Check whether *text* has been inserted with *insert_str* (or as its UTF-8 bytes with
+    /// *insert_bytes*).
+    pub fn contains_str(&self, text: &str) -> bool {
+        self.contains_bytes(text.as_bytes())
+    }
+
+    /// Insert an element by its base hashes *hash_a* and *hash_b*, which have been computed
+    /// elsewhere, e.g., by a producer that is not written in Rust.
+    ///
+    /// The result is the same as inserting an element for which *H1* returns *hash_a* and *H2*
+    /// returns *hash_b*.
+    ///
+    /// Systems that already compute a 128-bit content hash of their elements, e.g., a truncated
+    /// BLAKE3 digest, can pass its two 64-bit halves and skip hashing entirely. The halves of a
+    /// good hash are independent, which is all the simulated hash functions need. *H1* and *H2*
+    /// are not called, so such elements are only found by *contains_hash64* and not by *contains*.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::StableBloomFilterDefault;
+    ///
+    /// let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+    ///
+    /// // A 128-bit content hash computed elsewhere
+    /// let digest: u128 = 0x7d1a_54f2_9c3b_e801_46aa_0b93_f2c5_1d77;
+    /// filter.insert_hash64((digest >> 64) as u64, digest as u64);
+    ///
+    /// assert_eq!(true, filter.contains_hash64((digest >> 64) as u64, digest as u64));
+    /// ```
+    pub fn insert_hash64(&mut self, hash_a: u64, hash_b: u64) {
+        self.insert_hashed(&HashedKey::from_hashes(hash_a, hash_b));
+    }
+
+    /// Check whether an element with the base hashes *hash_a* and *hash_b* is contained in the
+    /// bloom filter.
+    pub fn contains_hash64(&self, hash_a: u64, hash_b: u64) -> bool {
+        self.contains_hashed(&HashedKey::from_hashes(hash_a, hash_b))
+    }
+
+    /// Remove all elements from the bloom filter.
+    ///
+    /// The memory of the filter is reused, which makes clearing a filter much cheaper than creating
+    /// a new one with the same configuration.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+    /// filter.insert(&"Hello!");
+    /// filter.clear();
+    ///
+    /// assert_eq!(false, filter.contains(&"Hello!"));
+    /// assert_eq!(0.0, filter.approximate_element_count());
+    /// ```
+    pub fn clear(&mut self) {
+        self.bitset.clear();
+        self.noise = 0.0;
+    }
+
+    /// Build a new filter with the configuration of this filter that contains all elements of
+    /// *all* except those of *removed*, which is the only way to remove elements from a bloom
+    /// filter without false negatives.
+    ///
+    /// *all* must yield every element that has been inserted into this filter, or the rebuilt
+    /// filter misses them. The rebuilt filter keeps the number of bits and hashers of this filter,
+    /// so it can still be combined with filters of the same configuration, e.g., with *union_with*,
+    /// and its false positive probability does not grow beyond the one of this filter. Create a
+    /// new filter with *from_iter_with_fp* instead to size it for the remaining elements.
+    ///
+    /// Elements are compared by their two base hashes, so an element of *all* is also dropped if
+    /// its hashes equal those of a removed element. Such elements set the same bits and cannot be
+    /// told apart by any bloom filter.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// let users = vec!["alice", "bob", "carol"];
+    /// let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+    /// filter.insert_all(users.iter());
+    ///
+    /// let filter = filter.rebuild_without(&["bob"], users.iter());
+    /// assert_eq!(false, filter.contains(&"bob"));
+    /// assert_eq!(true, filter.contains(&"alice"));
+    /// assert_eq!(true, filter.contains(&"carol"));
+    /// ```
+    pub fn rebuild_without<'a, T, R, A>(&self, removed: R, all: A) -> Self
+    where
+        T: Hash + 'a,
+        R: IntoIterator<Item = &'a T>,
+        A: IntoIterator<Item = &'a T>,
+    {
+        let mut removed: Vec<(u64, u64)> = removed
+            .into_iter()
+            .map(|data| Self::generate_hashes(data))
+            .collect();
+        removed.sort_unstable();
+        let mut filter = Self {
+            number_of_hashers: self.number_of_hashers,
+            bitset: Bitset::new(self.bitset.len()),
+            bits_per_hasher: self.bits_per_hasher,
+            layout: self.layout,
+            strict_capacity: self.strict_capacity,
+            saturation: self.saturation,
+            noise: 0.0,
+            metrics: Metrics::default(),
+            _phantom: self._phantom,
+        };
+        for data in all {
+            let hashes = Self::generate_hashes(data);
+            if removed.binary_search(&hashes).is_err() {
+                filter.set_hashes(hashes);
+            }
+        }
+        filter
+    }
+
+    /// Return an immutable FrozenBloomFilter with the bits of this filter, which can be cloned
+    /// cheaply and shared by many reader threads.
+    ///
+    /// Capacity and saturation settings and the metrics are dropped, since a frozen filter only
+    /// answers queries.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+    /// filter.insert(&"Hello!");
+    ///
+    /// let frozen = filter.freeze();
+    /// let shared = frozen.clone();
+    /// assert_eq!(true, shared.contains(&"Hello!"));
+    /// assert!(std::ptr::eq(frozen.bitset(), shared.bitset()));
+    /// ```
+    pub fn freeze(self) -> FrozenBloomFilter<H1, H2> {
+        FrozenBloomFilter::new(
+            self.bitset,
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.layout,
+        )
+    }
+
+    /// Whether all elements of this bloom filter are probably contained in 'other'.
+    ///
+    /// The check is exact on the level of bits: if it returns false, this bloom filter certainly
+    /// contains an element that has not been inserted into 'other'. If it returns true, all elements
+    /// of this bloom filter are reported as contained by 'other', i.e., 'other.union(self)' equals
+    /// 'other'.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        if !self.eq_configuration(other) {
+            panic!("unable to compare k-m bloom filters with different configurations");
+        }
+        self.bitset.is_subset(&other.bitset)
+    }
+
+    /// Whether this bloom filter and 'other' probably have no element in common.
+    ///
+    /// Every element sets exactly one bit in the range of bits of each hasher. If the filters have
+    /// no bit in common in the range of any hasher, they certainly share no element. With the
+    /// layout *IndexLayout::Global*, they certainly share no element if they have no bit in common
+    /// at all. Otherwise,
+    /// the filters are considered disjoint if the approximate number of common elements (see
+    /// *approximate_intersection_count*) is less than one half.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    pub fn is_disjoint_probably(&self, other: &Self) -> bool {
+        if !self.eq_configuration(other) {
+            panic!("unable to compare k-m bloom filters with different configurations");
+        }
+        let certainly_disjoint = match self.layout {
+            IndexLayout::Partitioned => (0..self.number_of_hashers).any(|i| {
+                !self.bitset.intersects_in(
+                    &other.bitset,
+                    i * self.bits_per_hasher,
+                    (i + 1) * self.bits_per_hasher,
+                )
+            }),
+            IndexLayout::Global => !self
+                .bitset
+                .intersects_in(&other.bitset, 0, self.bit_count()),
+        };
+        certainly_disjoint || self.approximate_intersection_count(other) < 0.5
+    }
+
+    /// Checks whether two bloom filters were created with the same desired capacity, desired false
+    /// positive probability, and index layout (see *with_index_layout*).
+    ///
+    /// The hashers are not compared, because they are part of the type: filters with different
+    /// hashers cannot be combined, since *union* and *intersect* only accept filters of the same
+    /// type. Filters that have been written with different hashers are rejected when they are
+    /// read (see *read_from*).
+    pub fn eq_configuration(&self, other: &Self) -> bool {
+        self.number_of_hashers == other.number_of_hashers
+            && self.bits_per_hasher == other.bits_per_hasher
+            && self.layout == other.layout
+    }
+
+    /// Write the filter to *writer*.
+    ///
+    /// The filter is written as a header, followed by the bits of the filter and a checksum. The
+    /// header consists of the magic bytes 'BFKM', the format version as little-endian 32-bit
+    /// integer, and four little-endian 64-bit integers: an identifier of *H1*, an identifier of
+    /// *H2*, the number of hashers, and the number of bits per hasher. Bit *i* of the filter is
+    /// stored in byte *i / 8* at position *i % 8*, counting from the least significant bit. The
+    /// checksum is the XXH64 hash of the bits with seed 0 as little-endian 64-bit integer.
+    ///
+    /// The format version is 1 for the layout *IndexLayout::Partitioned* and 2 for the layout
+    /// *IndexLayout::Global*. BloomFilterView, MmapBloomFilter, and LazyBloomFilter only read
+    /// version 1 and reject version 2 with *ReadFilterError::UnsupportedVersion*.
+    ///
+    /// The identifier of a hasher is its hash value for a fixed input, which depends on both the
+    /// hasher and its seeds. *read_from* rejects filters that have been written with different
+    /// hashers, so use hashers whose hash values do not change between processes, such as the
+    /// hashers of StableBloomFilterDefault.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+    ///     filter.insert(&"Hello!");
+    ///
+    ///     let mut bytes = Vec::new();
+    ///     filter.write_to(&mut bytes)?;
+    ///
+    ///     let filter = StableBloomFilterDefault::read_from(&bytes[..])?;
+    ///     assert_eq!(true, filter.contains(&"Hello!"));
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn write_to<W>(&self, mut writer: W) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        write_header::<H1, H2, _>(
+            &mut writer,
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.layout,
+        )?;
+        let mut writer = ChecksumWriter::new(writer);
+        self.bitset.write_bytes(&mut writer)?;
+        writer.finish()
+    }
+
+    /// Read a filter that has been written with *write_to* from *reader*.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if *reader* does not contain a filter in the format of *write_to*, if the
+    /// filter has been written with different hashers, or if its checksum does not match. Errors
+    /// of *reader* are forwarded as *ReadFilterError::Io*, e.g., if it ends before the whole
+    /// filter has been read.
+    #[cfg(feature = "std")]
+    pub fn read_from<R>(mut reader: R) -> Result<Self, ReadFilterError>
+    where
+        R: std::io::Read,
+    {
+        let (number_of_hashers, bits_per_hasher, layout) =
+            read_layout_header::<H1, H2, _>(&mut reader)?;
+        let byte_count = (number_of_hashers * bits_per_hasher).div_ceil(8);
+        // Grow the bits while they are read instead of allocating them up front, so that a header
+        // of a hostile peer cannot allocate more memory than the peer sends
+        let mut bytes = Vec::new();
+        std::io::Read::read_to_end(
+            &mut std::io::Read::take(&mut reader, byte_count as u64),
+            &mut bytes,
+        )?;
+        if bytes.len() != byte_count {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        let mut checksum = [0u8; CHECKSUM_LENGTH];
+        reader.read_exact(&mut checksum)?;
+        verify_checksum(&bytes, &checksum)?;
+        Ok(Self {
+            bitset: Bitset::from_bytes(number_of_hashers * bits_per_hasher, bytes),
+            number_of_hashers,
+            bits_per_hasher,
+            layout,
+            strict_capacity: None,
+            saturation: None,
+            noise: 0.0,
+            metrics: Metrics::default(),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// The filter in the format of *write_to*, e.g., to pass it from a backend to a WebAssembly
+    /// module as a byte array.
+    ///
+    /// The bits of a filter only answer queries correctly with the same hashers, so share filters
+    /// with hashers whose hash values are the same on every platform, e.g., StableBloomFilterDefault.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+    /// filter.insert(&"Hello!");
+    ///
+    /// let bytes = filter.to_bytes();
+    /// let filter = StableBloomFilterDefault::from_bytes(&bytes).unwrap();
+    /// assert_eq!(true, filter.contains(&"Hello!"));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity(HEADER_LENGTH + self.bitset.len().div_ceil(8) + CHECKSUM_LENGTH);
+        self.write_to(&mut bytes)
+            .expect("writing to a vector never fails");
+        bytes
+    }
+
+    /// Restore a filter from the bytes returned by *to_bytes* or written by *write_to*.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as *read_from*, and *ReadFilterError::InvalidLength* if *bytes*
+    /// are longer or shorter than described by the header of the filter.
+    #[cfg(feature = "std")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReadFilterError> {
+        let (number_of_hashers, bits_per_hasher, layout) =
+            read_layout_header::<H1, H2, _>(&mut &bytes[..])?;
+        let byte_count = check_length(number_of_hashers, bits_per_hasher, bytes.len())?;
+        let (bits, checksum) = bytes[HEADER_LENGTH..].split_at(byte_count);
+        verify_checksum(bits, checksum)?;
+        Ok(Self::from_bitset(
+            Bitset::from_bytes(number_of_hashers * bits_per_hasher, bits.to_vec()),
+            number_of_hashers,
+        )
+        .with_layout_of_bits(layout))
+    }
+
+    /// Write the filter to *writer* in the format of *write_to* without blocking the executor, and
+    /// flush *writer*.
+    ///
+    /// The bits are converted and written in chunks, so that the task yields to the executor
+    /// between them when *writer* is not ready, e.g., to checkpoint a large filter while a service
+    /// keeps answering requests. The filter is borrowed until the returned future completes.
+    ///
+    /// Only available with the *tokio* feature.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+    ///
+    /// # let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// # runtime.block_on(async {
+    /// let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+    /// filter.insert(&"Hello!");
+    ///
+    /// let mut bytes = Vec::new();
+    /// filter.save_to(&mut bytes).await?;
+    ///
+    /// let filter = StableBloomFilterDefault::load_from(&bytes[..]).await?;
+    /// assert_eq!(true, filter.contains(&"Hello!"));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// # }).unwrap();
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub async fn save_to<W>(&self, mut writer: W) -> std::io::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut header = Vec::with_capacity(HEADER_LENGTH);
+        write_header::<H1, H2, _>(
+            &mut header,
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.layout,
+        )?;
+        writer.write_all(&header).await?;
+        let mut hasher = checksum_hasher();
+        let mut buffer = Vec::with_capacity(CHUNK_WORDS * 8);
+        for chunk_index in 0..self.bitset.chunk_count() {
+            self.bitset.chunk_bytes(chunk_index, &mut buffer);
+            hasher.update(&buffer);
+            writer.write_all(&buffer).await?;
+        }
+        writer.write_all(&hasher.digest().to_le_bytes()).await?;
+        writer.flush().await
+    }
+
+    /// Read a filter that has been written with *write_to* or *save_to* from *reader* without
+    /// blocking the executor.
+    ///
+    /// The bits are read and their checksum is computed in chunks, so that the task yields to the
+    /// executor between them when *reader* is not ready.
+    ///
+    /// Only available with the *tokio* feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as *read_from*.
+    #[cfg(feature = "tokio")]
+    pub async fn load_from<R>(mut reader: R) -> Result<Self, ReadFilterError>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut header = [0u8; HEADER_LENGTH];
+        reader.read_exact(&mut header).await?;
+        let (number_of_hashers, bits_per_hasher, layout) =
+            read_layout_header::<H1, H2, _>(&mut &header[..])?;
+        let bit_count = number_of_hashers * bits_per_hasher;
+        let byte_count = bit_count.div_ceil(8);
+        // Grow the bits chunk by chunk like read_from, so that a hostile header cannot allocate
+        // more memory than the peer sends
+        let mut bytes = Vec::new();
+        let mut hasher = checksum_hasher();
+        while bytes.len() < byte_count {
+            let start = bytes.len();
+            bytes.resize((start + CHUNK_WORDS * 8).min(byte_count), 0);
+            reader.read_exact(&mut bytes[start..]).await?;
+            hasher.update(&bytes[start..]);
+        }
+        let mut checksum = [0u8; CHECKSUM_LENGTH];
+        reader.read_exact(&mut checksum).await?;
+        verify_digest(hasher.digest(), &checksum)?;
+        Ok(
+            Self::from_bitset(Bitset::from_bytes(bit_count, bytes), number_of_hashers)
+                .with_layout_of_bits(layout),
+        )
+    }
+
+    /// Write the filter to *writer* in the format of *write_to*, compressed with *compression*,
+    /// e.g., to ship snapshots of filters whose bits compress well because they are sparsely
+    /// filled.
+    ///
+    /// The filter is compressed while it is written, without buffering the whole file. *writer*
+    /// is not flushed.
+    ///
+    /// Only available with the *gzip* or *zstd* feature.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,Compression,StableBloomFilterDefault};
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut filter = StableBloomFilterDefault::new(100_000, 0.001);
+    ///     filter.insert(&"Hello!");
+    ///
+    ///     # #[cfg(feature = "gzip")]
+    ///     let compression = Compression::Gzip;
+    ///     # #[cfg(not(feature = "gzip"))]
+    ///     # let compression = Compression::Zstd;
+    ///     let mut bytes = Vec::new();
+    ///     filter.save_compressed(&mut bytes, compression)?;
+    ///     assert!(bytes.len() < filter.to_bytes().len() / 100);
+    ///
+    ///     let filter = StableBloomFilterDefault::load_compressed(&bytes[..])?;
+    ///     assert_eq!(true, filter.contains(&"Hello!"));
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    pub fn save_compressed<W>(&self, writer: W, compression: Compression) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        match compression {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+                self.write_to(&mut encoder)?;
+                encoder.finish()?;
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => {
+                let mut encoder = zstd::stream::write::Encoder::new(writer, 0)?;
+                self.write_to(&mut encoder)?;
+                encoder.finish()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a filter that has been written with *save_compressed* from *reader*, decompressing it
+    /// while it is read.
+    ///
+    /// The compression is detected from the first bytes of *reader*. Uncompressed filters written
+    /// with *write_to* are read as well, e.g., to migrate to compressed snapshots.
+    ///
+    /// Only available with the *gzip* or *zstd* feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as *read_from*. Filters compressed with a compression whose feature
+    /// is not enabled fail with *ReadFilterError::InvalidMagic*, and corrupted compressed data
+    /// fails with *ReadFilterError::Io*.
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    pub fn load_compressed<R>(mut reader: R) -> Result<Self, ReadFilterError>
+    where
+        R: std::io::Read,
+    {
+        let mut magic = [0u8; 4];
+        std::io::Read::read_exact(&mut reader, &mut magic)?;
+        let reader = std::io::Read::chain(&magic[..], reader);
+        match Compression::detect(&magic) {
+            #[cfg(feature = "gzip")]
+            Some(Compression::Gzip) => Self::read_from(flate2::read::GzDecoder::new(reader)),
+            #[cfg(feature = "zstd")]
+            Some(Compression::Zstd) => Self::read_from(zstd::stream::read::Decoder::new(reader)?),
+            None => Self::read_from(reader),
+        }
+    }
+
+    pub(crate) fn generate_hashes<T>(data: &T) -> (u64, u64)
     where
         T: Hash,
     {
-        let (hash_a, hash_b) = self.generate_hashes(&data);
+        let mut hasher = H1::default();
+        data.hash(&mut hasher);
+        let hash_a = hasher.finish();
+
+        let mut hasher = H2::default();
+        data.hash(&mut hasher);
+        let hash_b = hasher.finish();
+
+        (hash_a, hash_b)
+    }
 
+    /// Target false positive probability of the saturation policy *OnSaturation::Error*, if any.
+    fn refused_false_positive_probability(&self) -> Option<f64> {
+        match self.saturation {
+            Some((target, OnSaturation::Error)) => Some(target),
+            _ => None,
+        }
+    }
+
+    /// Number of bits that are set, if an insertion has to be checked by *warn_if_saturated*.
+    fn ones_before_insertion(&self) -> Option<usize> {
+        match self.saturation {
+            Some((_, OnSaturation::Warn(_))) => Some(self.bitset.count_ones()),
+            _ => None,
+        }
+    }
+
+    /// Call the function of the saturation policy *OnSaturation::Warn* if the approximate false
+    /// positive probability has been within the target with *previous_ones* bits set, and
+    /// exceeds it now.
+    fn warn_if_saturated(&self, previous_ones: Option<usize>) {
+        let (target, warn, previous_ones) = match (self.saturation, previous_ones) {
+            (Some((target, OnSaturation::Warn(warn))), Some(previous_ones)) => {
+                (target, warn, previous_ones)
+            }
+            _ => return,
+        };
+        let false_positive_probability =
+            |ones| self.false_positive_probability_for(self.element_count_for_ones(ones));
+        let current = false_positive_probability(self.bitset.count_ones());
+        if current > target && false_positive_probability(previous_ones) <= target {
+            warn(current);
+        }
+    }
+
+    /// Check whether setting the bits of an element with the given hashes keeps the approximate
+    /// number of elements within the strict capacity, if any, and the approximate false positive
+    /// probability within the target of the saturation policy *OnSaturation::Error*, if any.
+    pub(crate) fn check_capacity(
+        &self,
+        (hash_a, hash_b): (u64, u64),
+    ) -> Result<(), CapacityExceeded> {
+        if !self.limits_insertions() {
+            return Ok(());
+        }
+        // The probes of the layout IndexLayout::Global may hit the same bit, which is set only once
+        let new_ones = (0..self.number_of_hashers)
+            .filter(|&i| {
+                let index = self.bit_of(i, hash_a, hash_b);
+                !self.bitset.get(index) && (0..i).all(|j| self.bit_of(j, hash_a, hash_b) != index)
+            })
+            .count();
+        self.check_new_ones(new_ones)
+    }
+
+    /// Whether a strict capacity or the saturation policy *OnSaturation::Error* may refuse
+    /// insertions.
+    fn limits_insertions(&self) -> bool {
+        self.strict_capacity.is_some() || self.refused_false_positive_probability().is_some()
+    }
+
+    /// Check whether setting *new_ones* more bits keeps the filter within the strict capacity and
+    /// the target false positive probability, see *check_capacity*.
+    fn check_new_ones(&self, new_ones: usize) -> Result<(), CapacityExceeded> {
+        let element_count = self.element_count_for_ones(self.bitset.count_ones() + new_ones);
+        let exceeds_capacity = self
+            .strict_capacity
+            .is_some_and(|capacity| element_count > capacity as f64);
+        let exceeds_false_positive_probability = self
+            .refused_false_positive_probability()
+            .is_some_and(|target| self.false_positive_probability_for(element_count) > target);
+        if exceeds_capacity || exceeds_false_positive_probability {
+            Err(CapacityExceeded)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Approximate number of elements for *ones* bits set, discounting the noise.
+    fn element_count_for_ones(&self, ones: usize) -> f64 {
+        approximate_element_count_with_noise(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            ones,
+            self.noise,
+        )
+    }
+
+    /// Approximate false positive probability for *element_count* elements and the noise.
+    fn false_positive_probability_for(&self, element_count: f64) -> f64 {
+        approximate_false_positive_probability_with_noise(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            element_count,
+            self.noise,
+        )
+    }
+
+    fn set_hashes(&mut self, (hash_a, hash_b): (u64, u64)) {
         for i in 0..self.number_of_hashers {
-            self.bitset
-                .set(Self::index(i, self.bits_per_hasher, hash_a, hash_b), true);
+            self.bitset.set(self.bit_of(i, hash_a, hash_b), true);
+        }
+    }
+
+    /// Append the indices of the bits of an element with the given hashes to *indices*.
+    pub(crate) fn push_indices(&self, (hash_a, hash_b): (u64, u64), indices: &mut Vec<usize>) {
+        indices.extend((0..self.number_of_hashers).map(|i| self.bit_of(i, hash_a, hash_b)));
+    }
+
+    /// Set the bits of *inserts* elements at once, e.g., of a committed InsertBatch, unless they
+    /// would exceed the strict capacity or the target of the saturation policy
+    /// *OnSaturation::Error* together, in which case no bit is set.
+    pub(crate) fn insert_indices(
+        &mut self,
+        indices: &mut Vec<usize>,
+        inserts: usize,
+    ) -> Result<(), CapacityExceeded> {
+        self.check_indices(indices)?;
+        let previous_ones = self.ones_before_insertion();
+        self.set_indices(indices);
+        self.warn_if_saturated(previous_ones);
+        self.metrics.record_inserts(inserts);
+        Ok(())
+    }
+
+    /// Check whether setting the bits *indices* keeps the filter within the strict capacity and
+    /// the target of the saturation policy *OnSaturation::Error*, see *insert_indices*.
+    pub(crate) fn check_indices(&self, indices: &mut Vec<usize>) -> Result<(), CapacityExceeded> {
+        if !self.limits_insertions() {
+            return Ok(());
+        }
+        indices.sort_unstable();
+        indices.dedup();
+        let new_ones = indices.iter().filter(|&&i| !self.bitset.get(i)).count();
+        self.check_new_ones(new_ones)
+    }
+
+    fn set_indices(&mut self, indices: &mut Vec<usize>) {
+        for &index in indices.iter() {
+            self.bitset.set(index, true);
         }
+        indices.clear();
+    }
+
+    /// Index of the bit of the *i*-th simulated hash function of this filter, see
+    /// *IndexLayout::bit_index*.
+    fn bit_of(&self, i: usize, hash_a: u64, hash_b: u64) -> usize {
+        self.layout.bit_index(
+            i,
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            hash_a,
+            hash_b,
+        )
+    }
+
+    /// Index of the bit of the *i*-th simulated hash function of the layout
+    /// *IndexLayout::Partitioned*, see *bit_index*.
+    pub(crate) fn index(i: usize, bits_per_hash: usize, hash_a: u64, hash_b: u64) -> usize {
+        bit_index(i, bits_per_hash, hash_a, hash_b)
+    }
+}
+
+/// Probability that a bit is set at random in the union of filters with the noise *a* and *b*.
+fn union_noise(a: f64, b: f64) -> f64 {
+    a + b - a * b
+}
+
+/// Index of the bit of the *i*-th simulated hash function of a filter with *bits_per_hash* bits
+/// per hash function for the base hashes *hash_a* and *hash_b*. The hash is reduced as u64, so
+/// that the bits of a filter are the same on 32-bit targets, e.g., wasm32.
+///
+/// Reducing a 64-bit hash modulo *bits_per_hash* maps *2^64 mod bits_per_hash* offsets to one
+/// more hash than the others, so no offset is more likely than another by more than a factor
+/// of *1 + bits_per_hash / 2^64*. This is far below what any false positive measurement can
+/// resolve, and mapping with 128-bit multiplication (fastrange) has a bias of the same size.
+/// The modulo is part of the documented hashing scheme, see *test_vectors*.
+///
+/// If *bits_per_hash* is a power of two, the modulo is computed with a bitmask, which gives the
+/// same bits without a division, see *KMBloomFilter::new_power_of_two*.
+pub(crate) fn bit_index(i: usize, bits_per_hash: usize, hash_a: u64, hash_b: u64) -> usize {
+    let offset = reduce(simulated_hash(i, hash_a, hash_b), bits_per_hash);
+    // Cannot overflow: i is less than the number of hashers and offset less than bits_per_hash,
+    // so the index is less than the number of bits, which every constructor checks to fit into
+    // usize (see params::checked_bit_count)
+    debug_assert!(i
+        .checked_mul(bits_per_hash)
+        .and_then(|start| start.checked_add(bits_per_hash))
+        .is_some());
+    i * bits_per_hash + offset
+}
+
+/// Index of the bit of the *i*-th simulated hash function of a filter with the layout
+/// *IndexLayout::Global* and *bit_count* bits, which is reduced like the offset of *bit_index*.
+pub(crate) fn global_bit_index(i: usize, bit_count: usize, hash_a: u64, hash_b: u64) -> usize {
+    reduce(simulated_hash(i, hash_a, hash_b), bit_count)
+}
+
+/// Hash of the *i*-th simulated hash function for the base hashes *hash_a* and *hash_b*.
+fn simulated_hash(i: usize, hash_a: u64, hash_b: u64) -> u64 {
+    hash_a.wrapping_add((i as u64).wrapping_mul(hash_b))
+}
+
+/// *hash* modulo *modulus*, computed with a bitmask if *modulus* is a power of two.
+fn reduce(hash: u64, modulus: usize) -> usize {
+    let modulus = modulus as u64;
+    let reduced = if modulus.is_power_of_two() {
+        hash & (modulus - 1)
+    } else {
+        hash % modulus
+    };
+    reduced as usize
+}
+
+impl<H1, H2> Debug for KMBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "KMBloomFilter{{{:?}}}", self.bitset)
+    }
+}
+
+/// One-line summary of the configuration and the estimates of the filter, e.g., for logs.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+///
+/// let mut filter = StableBloomFilterDefault::new(1_000, 0.01);
+/// filter.insert_all(0..100);
+/// assert_eq!(
+///     "KMBloomFilter: 7 hashers, 9590 bits (7.05% set), about 100.1 elements, \
+///      false positive probability about 8.65e-9",
+///     filter.to_string()
+/// );
+/// ```
+impl<H1, H2> Display for KMBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_summary(
+            f,
+            "KMBloomFilter",
+            self.number_of_hashers,
+            &self.bitset,
+            self.approximate_element_count(),
+            self.approximate_current_false_positive_probability(),
+        )
+    }
+}
+
+/// Write the one-line summary of the Display output of the filters with a bitset.
+pub(crate) fn write_summary(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    number_of_hashers: usize,
+    bitset: &Bitset,
+    element_count: f64,
+    false_positive_probability: f64,
+) -> fmt::Result {
+    write!(
+        f,
+        "{}: {} hashers, {} bits ({:.2}% set), about {:.1} elements, \
+         false positive probability about {:.2e}",
+        name,
+        number_of_hashers,
+        bitset.len(),
+        100.0 * bitset.count_ones() as f64 / bitset.len() as f64,
+        element_count,
+        false_positive_probability
+    )
+}
+
+/// Two bloom filters are equal if they have the same configuration and the same bits are set.
+///
+/// Bloom filters containing the same elements are always equal. Bloom filters containing different
+/// elements are usually not equal, but they can be, e.g., if an element of one filter is a false
+/// positive of the other.
+impl<H1, H2> PartialEq for KMBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_configuration(other) && self.bitset == other.bitset
+    }
+}
+
+impl<H1, H2> Eq for KMBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+}
+
+impl<H1, H2> BloomFilter for KMBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn insert<T>(&mut self, data: &T)
+    where
+        T: Hash,
+    {
+        self.insert_hashed(&self.hash(data));
     }
 
     fn contains<T>(&self, data: &T) -> bool
     where
         T: Hash,
     {
-        let (hash_a, hash_b) = self.generate_hashes(data);
+        self.contains_hashed(&self.hash(data))
+    }
 
-        for i in 0..self.number_of_hashers {
-            if !self
-                .bitset
-                .get(Self::index(i, self.bits_per_hasher, hash_a, hash_b))
-            {
-                return false;
+    fn check_and_insert<T>(&mut self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        self.check_and_insert_hashed(&self.hash(data))
+    }
+
+    fn insert_all<T, I>(&mut self, iter: I)
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        if self.limits_insertions() {
+            // The capacity has to be checked before every single insertion
+            for data in iter {
+                self.insert(&data);
+            }
+            return;
+        }
+        let previous_ones = self.ones_before_insertion();
+        let batch_length = INSERT_BATCH_SIZE * self.number_of_hashers;
+        let mut indices = Vec::with_capacity(batch_length);
+        let mut inserts = 0;
+        for data in iter {
+            inserts += 1;
+            let (hash_a, hash_b) = Self::generate_hashes(&data);
+            indices.extend((0..self.number_of_hashers).map(|i| self.bit_of(i, hash_a, hash_b)));
+            if indices.len() >= batch_length {
+                self.set_indices(&mut indices);
+            }
+        }
+        self.set_indices(&mut indices);
+        self.warn_if_saturated(previous_ones);
+        self.metrics.record_inserts(inserts);
+    }
+}
+
+impl<T, H1, H2> Extend<T> for KMBloomFilter<H1, H2>
+where
+    T: Hash,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.insert_all(iter);
+    }
+}
+
+/// Number of distinct elements of a set from which drawing *sample_length* elements at random is
+/// expected to yield *distinct_count* distinct elements, where distinct_count < sample_length.
+///
+/// The expected number of distinct elements *n(1 - e^(-s/n))* of *s* draws from *n* elements
+/// grows with *n*, so *n* is found by bisection.
+fn population_size(sample_length: f64, distinct_count: f64) -> f64 {
+    let expected_distinct =
+        |population: f64| population * (1.0 - math::exp(-sample_length / population));
+    let mut low = distinct_count;
+    let mut high = distinct_count * 2.0;
+    while expected_distinct(high) < distinct_count {
+        low = high;
+        high *= 2.0;
+    }
+    for _ in 0..64 {
+        let middle = (low + high) / 2.0;
+        if expected_distinct(middle) < distinct_count {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    high
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BloomFilter, StableBloomFilterDefault};
+
+    // The bit pattern of a StableBloomFilterDefault must never change. If this test fails, filters
+    // persisted with an older release can no longer be queried.
+    #[test]
+    fn stable_bloom_filter_bit_pattern() {
+        let mut bloom_filter = StableBloomFilterDefault::new(10, 0.01);
+        bloom_filter.insert(&"Hello!");
+        bloom_filter.insert(&42u64);
+        bloom_filter.insert(&vec![1u8, 2, 3]);
+
+        let ones: Vec<usize> = (0..bloom_filter.bitset.len())
+            .filter(|&i| bloom_filter.bitset.get(i))
+            .collect();
+        assert_eq!(7, bloom_filter.number_of_hashers);
+        assert_eq!(14, bloom_filter.bits_per_hasher);
+        assert_eq!(
+            vec![3, 10, 16, 17, 24, 29, 36, 42, 43, 48, 60, 62, 69, 74, 82, 83, 86, 88, 95],
+            ones
+        );
+
+        let mut indices = bloom_filter.probe_indices(&"Hello!");
+        indices.extend(bloom_filter.probe_indices(&42u64));
+        indices.extend(bloom_filter.probe_indices(&vec![1u8, 2, 3]));
+        indices.sort_unstable();
+        indices.dedup();
+        assert_eq!(ones, indices);
+    }
+
+    // Measure the false positive rate of the modulo reduction of *index* and of the 128-bit
+    // multiplication of fastrange for a partition size that is not a power of two. Both are within
+    // the sampling error of the expected rate, since the bias of either is at most 2^-50 here.
+    #[test]
+    fn index_reduction_has_no_measurable_bias() {
+        use crate::{bitset::Bitset, math, params};
+
+        let number_of_hashers = 7;
+        let mut filter = StableBloomFilterDefault::with_parameters(7 * 1_401, number_of_hashers);
+        let bits_per_hasher = filter.bits_per_hasher;
+        assert_eq!(1_401, bits_per_hasher);
+        assert!(!bits_per_hasher.is_power_of_two());
+        let fastrange_index = |i: usize, hash_a: u64, hash_b: u64| {
+            let hash = hash_a.wrapping_add((i as u64).wrapping_mul(hash_b));
+            i * bits_per_hasher + ((hash as u128 * bits_per_hasher as u128) >> 64) as usize
+        };
+
+        let mut fastrange_bitset = Bitset::new(filter.bitset.len());
+        for element in 0..1_000u64 {
+            filter.insert(&element);
+            let (hash_a, hash_b) = filter.hash(&element).hashes();
+            for i in 0..number_of_hashers {
+                fastrange_bitset.set(fastrange_index(i, hash_a, hash_b), true);
+            }
+        }
+
+        const QUERIES: u64 = 200_000;
+        let queries = 1_000..1_000 + QUERIES;
+        let modulo_rate = queries
+            .clone()
+            .filter(|query| filter.contains(query))
+            .count() as f64
+            / QUERIES as f64;
+        let fastrange_rate = queries
+            .clone()
+            .filter(|query| {
+                let (hash_a, hash_b) = filter.hash(query).hashes();
+                (0..number_of_hashers)
+                    .all(|i| fastrange_bitset.get(fastrange_index(i, hash_a, hash_b)))
+            })
+            .count() as f64
+            / QUERIES as f64;
+        let expected = params::approximate_false_positive_probability(
+            number_of_hashers,
+            bits_per_hasher,
+            1_000.0,
+        );
+
+        // Four standard errors of 200,000 queries at an expected rate of about 0.8%
+        let tolerance = 4.0 * math::sqrt(expected * (1.0 - expected) / QUERIES as f64);
+        assert!((modulo_rate - expected).abs() < tolerance);
+        assert!((fastrange_rate - expected).abs() < tolerance);
+    }
+
+    // The index of the last hasher of filters whose bits fill almost all of usize, or that have
+    // more than 2^48 bits per hasher, must be exact and in bounds.
+    #[test]
+    fn bit_index_is_exact_at_the_boundary_of_usize() {
+        use super::bit_index;
+
+        let hashes = [
+            (u64::MAX, 0),
+            (u64::MAX, u64::MAX),
+            (0x8000_0000_0000_0001, 3),
+        ];
+        for &number_of_hashers in [1usize, 2, 7].iter() {
+            // 2^49 - 3 bits per hasher on 64-bit targets
+            for &bits_per_hash in [usize::MAX / number_of_hashers, (usize::MAX >> 15) - 2].iter() {
+                for &(hash_a, hash_b) in hashes.iter() {
+                    let i = number_of_hashers - 1;
+                    let hash = hash_a.wrapping_add((i as u64).wrapping_mul(hash_b));
+                    let expected =
+                        i as u128 * bits_per_hash as u128 + (hash % bits_per_hash as u64) as u128;
+                    let index = bit_index(i, bits_per_hash, hash_a, hash_b);
+                    assert_eq!(expected, index as u128);
+                    assert!((index as u128) < number_of_hashers as u128 * bits_per_hash as u128);
+                }
             }
         }
+    }
 
-        return true;
+    #[cfg(feature = "prefetch")]
+    #[test]
+    fn contains_prefetched_matches_bits() {
+        // More hashers than fit into one batch
+        for &number_of_hashers in [1, 7, 16, 17, 40].iter() {
+            let mut filter = StableBloomFilterDefault::with_parameters(4_000, number_of_hashers);
+            filter.insert_all(0..100);
+            for i in 0..1_000 {
+                let (hash_a, hash_b) = filter.hash(&i).hashes();
+                let expected = filter
+                    .probe_indices(&i)
+                    .into_iter()
+                    .all(|index| filter.bitset.get(index));
+                assert_eq!(expected, filter.contains_prefetched(hash_a, hash_b));
+                assert_eq!(i < 100 || expected, filter.contains(&i));
+            }
+        }
     }
 }
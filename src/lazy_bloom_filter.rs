@@ -0,0 +1,257 @@
+use std::{
+    convert::TryFrom,
+    fmt::{self, Debug},
+    fs::File,
+    hash::{Hash, Hasher},
+    io::{Read, Seek, SeekFrom},
+    marker::PhantomData,
+    path::Path,
+    sync::{Mutex, OnceLock},
+};
+
+use crate::{
+    bitset::bit_is_set,
+    file_format::{
+        check_length, checksum_hasher, read_header, verify_digest, CHECKSUM_LENGTH, HEADER_LENGTH,
+    },
+    HashedKey, KMBloomFilter, ReadFilterError,
+};
+
+/// Number of bytes of the bits that are read at once when a chunk is accessed for the first time.
+const CHUNK_LENGTH: usize = 1 << 20;
+
+/// Read-only KMBloomFilter that reads its bits from a file written by *KMBloomFilter::write_to*
+/// on first access, e.g., to start a service quickly although it may never query a large filter.
+///
+/// *open* only reads and validates the header and checks the length of the file. The bits are
+/// read in chunks of 1 MiB when *contains* accesses a chunk for the first time, and kept in memory
+/// afterwards. *warm_up* reads all chunks that have not been read yet and verifies the checksum of
+/// the bits, e.g., in the background after the service has started.
+///
+/// In contrast to MmapBloomFilter, the bits are read with regular file I/O into memory that
+/// belongs to the filter, so the filter works on every platform and is not affected if the file
+/// is replaced while it is open. Chunks that are read before *warm_up* has verified the checksum
+/// may be corrupted without being detected.
+///
+/// The hashers *H1* and *H2* must be the same hashers that were used to create the filter (see
+/// *MmapBloomFilter*).
+///
+/// Only available with the *std* feature.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,LazyBloomFilter,StableBloomFilterDefault,StableXxh3Hasher,StableXxh64Hasher};
+/// use std::fs::File;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let path = std::env::temp_dir().join("lazy_bloom_filter_doc_example.bin");
+///
+///     let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+///     filter.insert(&"Hello!");
+///     filter.write_to(File::create(&path)?)?;
+///
+///     // Only the header is read
+///     let filter: LazyBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+///         LazyBloomFilter::open(&path)?;
+///     assert_eq!(false, filter.is_warm());
+///
+///     // The chunks are read on first access, or all at once by warm_up
+///     assert_eq!(true, filter.contains(&"Hello!"));
+///     filter.warm_up()?;
+///     assert_eq!(true, filter.is_warm());
+///     Ok(())
+/// }
+/// ```
+pub struct LazyBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    number_of_hashers: usize,
+    bits_per_hasher: usize,
+    byte_count: usize,
+    file: Mutex<File>,
+    chunks: Vec<OnceLock<Box<[u8]>>>,
+    // Phantom data for saving which concrete Hasher types are used
+    _phantom: PhantomData<fn() -> (H1, H2)>,
+}
+
+impl<H1, H2> LazyBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Open the filter stored in the file at *path* without reading its bits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the header of the file does not describe a filter in the format of
+    /// *KMBloomFilter::write_to*, if the filter has been written with different hashers, or if
+    /// the length of the file does not match its header. Errors of opening the file are forwarded
+    /// as *ReadFilterError::Io*. The checksum is only verified by *warm_up*.
+    pub fn open<P>(path: P) -> Result<Self, ReadFilterError>
+    where
+        P: AsRef<Path>,
+    {
+        let mut file = File::open(path)?;
+        let (number_of_hashers, bits_per_hasher) = read_header::<H1, H2, _>(&mut file)?;
+        let length = file.metadata()?.len();
+        let byte_count = check_length(
+            number_of_hashers,
+            bits_per_hasher,
+            usize::try_from(length).unwrap_or(usize::MAX),
+        )?;
+        Ok(Self {
+            number_of_hashers,
+            bits_per_hasher,
+            byte_count,
+            file: Mutex::new(file),
+            chunks: (0..byte_count.div_ceil(CHUNK_LENGTH))
+                .map(|_| OnceLock::new())
+                .collect(),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Read all chunks that have not been read yet and verify the checksum of the bits.
+    ///
+    /// # Errors
+    ///
+    /// Returns *ReadFilterError::ChecksumMismatch* if the checksum does not match, e.g., because
+    /// the file is corrupted, and forwards errors of reading the file as *ReadFilterError::Io*.
+    pub fn warm_up(&self) -> Result<(), ReadFilterError> {
+        let mut hasher = checksum_hasher();
+        for index in 0..self.chunks.len() {
+            hasher.update(self.try_chunk(index)?);
+        }
+        let mut checksum = [0u8; CHECKSUM_LENGTH];
+        self.read_at(HEADER_LENGTH + self.byte_count, &mut checksum)?;
+        verify_digest(hasher.digest(), &checksum)
+    }
+
+    /// Whether all chunks have been read, e.g., by *warm_up*.
+    pub fn is_warm(&self) -> bool {
+        self.chunks.iter().all(|chunk| chunk.get().is_some())
+    }
+
+    /// Check whether data is contained in the bloom filter.
+    ///
+    /// See *BloomFilter::contains* for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a chunk that is accessed for the first time cannot be read. Call *warm_up* to
+    /// read all chunks and handle the errors instead.
+    pub fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        let (hash_a, hash_b) = KMBloomFilter::<H1, H2>::generate_hashes(data);
+        self.contains_hashed(&HashedKey::from_hashes(hash_a, hash_b))
+    }
+
+    /// Check whether the element *key* has been computed for is contained in the bloom filter.
+    ///
+    /// See *KMBloomFilter::hash* for how to compute a key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a chunk that is accessed for the first time cannot be read.
+    pub fn contains_hashed(&self, key: &HashedKey<H1, H2>) -> bool {
+        let (hash_a, hash_b) = key.hashes();
+        (0..self.number_of_hashers).all(|i| {
+            let index = KMBloomFilter::<H1, H2>::index(i, self.bits_per_hasher, hash_a, hash_b);
+            let chunk_bits = CHUNK_LENGTH * 8;
+            bit_is_set(self.chunk(index / chunk_bits), index % chunk_bits)
+        })
+    }
+
+    /// Check whether the raw bytes *bytes* are contained in the bloom filter.
+    ///
+    /// See *KMBloomFilter::insert_bytes* for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a chunk that is accessed for the first time cannot be read.
+    pub fn contains_bytes(&self, bytes: &[u8]) -> bool {
+        self.contains_hashed(&HashedKey::from_bytes(bytes))
+    }
+
+    /// Check whether *text* is contained in the bloom filter.
+    ///
+    /// See *KMBloomFilter::insert_str* for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a chunk that is accessed for the first time cannot be read.
+    pub fn contains_str(&self, text: &str) -> bool {
+        self.contains_bytes(text.as_bytes())
+    }
+
+    /// Check whether an element with the base hashes *hash_a* and *hash_b* is contained in the
+    /// bloom filter.
+    ///
+    /// See *KMBloomFilter::insert_hash64* for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a chunk that is accessed for the first time cannot be read.
+    pub fn contains_hash64(&self, hash_a: u64, hash_b: u64) -> bool {
+        self.contains_hashed(&HashedKey::from_hashes(hash_a, hash_b))
+    }
+
+    /// Total number of bits of the filter.
+    pub fn bit_count(&self) -> usize {
+        self.number_of_hashers * self.bits_per_hasher
+    }
+
+    /// Number of hash functions used for every element.
+    pub fn hasher_count(&self) -> usize {
+        self.number_of_hashers
+    }
+
+    fn chunk(&self, index: usize) -> &[u8] {
+        match self.try_chunk(index) {
+            Ok(chunk) => chunk,
+            Err(error) => panic!("unable to read the bits of a lazy bloom filter: {}", error),
+        }
+    }
+
+    /// The bytes of chunk *index*, which are read from the file on first access.
+    fn try_chunk(&self, index: usize) -> std::io::Result<&[u8]> {
+        if let Some(chunk) = self.chunks[index].get() {
+            return Ok(chunk);
+        }
+        let start = index * CHUNK_LENGTH;
+        let mut bytes = vec![0u8; CHUNK_LENGTH.min(self.byte_count - start)];
+        self.read_at(HEADER_LENGTH + start, &mut bytes)?;
+        // Another thread may have read the chunk in the meantime, in which case its bytes are kept
+        Ok(self.chunks[index].get_or_init(|| bytes.into_boxed_slice()))
+    }
+
+    fn read_at(&self, offset: usize, bytes: &mut [u8]) -> std::io::Result<()> {
+        let mut file = self.file.lock().unwrap_or_else(|error| error.into_inner());
+        file.seek(SeekFrom::Start(offset as u64))?;
+        file.read_exact(bytes)
+    }
+}
+
+impl<H1, H2> Debug for LazyBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "LazyBloomFilter{{number_of_hashers: {}, bits_per_hasher: {}, loaded_chunks: {}/{}}}",
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.chunks
+                .iter()
+                .filter(|chunk| chunk.get().is_some())
+                .count(),
+            self.chunks.len()
+        )
+    }
+}
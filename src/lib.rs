@@ -2,18 +2,18 @@
 //! structure for filtering elements. The data structure is based on the ideas presented by Burton
 //! Howard Bloom and is therefore known as bloom filter:
 //! > Burton H. Bloom. 1970. Space/time trade-offs in hash coding with allowable errors. Commun.
-//! ACM 13, 7 (July 1970), 422–426. DOI: [https://doi.org/10.1145/362686.362692](https://doi.org/10.1145/362686.362692)
+//! > ACM 13, 7 (July 1970), 422–426. DOI: [https://doi.org/10.1145/362686.362692](https://doi.org/10.1145/362686.362692)
 //!
 //! # Overview
 //! Basic description taken from [Wikipedia](https://en.wikipedia.org/wiki/Bloom_filter):
 //!
 //! > "A Bloom filter is a space-efficient probabilistic data structure, conceived by Burton Howard
-//! Bloom in 1970, that is used to test whether an element is a member of a set. False positive
-//! matches are possible, but false negatives are not – in other words, a query returns either
-//! "possibly in set" or "definitely not in set". Elements can be added to the set, but not removed
-//! (though this can be addressed with the counting Bloom filter variant); the more items added, the
-//! larger the probability of false positives." ("Bloom filter". Definition, para. 1. In Wikipedia.
-//! Retrieved December 02, 2020, from https://en.wikipedia.org/wiki/Bloom_filter)
+//! > Bloom in 1970, that is used to test whether an element is a member of a set. False positive
+//! > matches are possible, but false negatives are not – in other words, a query returns either
+//! > "possibly in set" or "definitely not in set". Elements can be added to the set, but not removed
+//! > (though this can be addressed with the counting Bloom filter variant); the more items added, the
+//! > larger the probability of false positives." ("Bloom filter". Definition, para. 1. In Wikipedia.
+//! > Retrieved December 02, 2020, from https://en.wikipedia.org/wiki/Bloom_filter)
 //!
 //! # Bloom Filter Implementations
 //! The library offers two basic types of bloom filter implementations.
@@ -28,12 +28,260 @@
 //! simulated as *g_i(x) = h_1(x) + i* \* *h_2(x)*.
 //!
 //!  > \[1\] Kirsch A., Mitzenmacher M. (2006) Less Hashing, Same Performance: Building a Better Bloom Filter.
-//! In: Azar Y., Erlebach T. (eds) Algorithms – ESA 2006. ESA 2006. Lecture Notes in Computer Science, vol 4168.
-//! Springer, Berlin, Heidelberg. https://doi.org/10.1007/11841036_42
+//! > In: Azar Y., Erlebach T. (eds) Algorithms – ESA 2006. ESA 2006. Lecture Notes in Computer Science, vol 4168.
+//! > Springer, Berlin, Heidelberg. https://doi.org/10.1007/11841036_42
+//!
+//! Replicas of a filter can be kept in sync with *KMBloomFilter::delta* and *apply_delta*, which
+//! only transfer the bits that have been set since a snapshot (see *BitsetDelta*).
+//!
+//! Every simulated hash function sets bits in its own range of the bits of the filter.
+//! *KMBloomFilter::with_index_layout* selects *IndexLayout::Global* instead, where every hash
+//! function addresses all bits like a classic bloom filter, e.g., to exchange filters with other
+//! implementations. The false positive probability is slightly lower for the same memory.
+//!
+//! BuildHasherBloomFilter simulates its hash functions the same way, but hashes with two
+//! *BuildHasher*s that are stored in the filter, e.g., the *RandomState* of an application.
 //!
 //! ## Seeded Bloom Filter (SeededBloomFilter)
 //! A bloom filter that uses a single Hasher that can be seeded to simulate an arbitrary number of hash functions.
-//! Internally, the implementation uses [ahash::AHasher](https://crates.io/crates/ahash).
+//! SeededBloomFilter uses [ahash::AHasher](https://crates.io/crates/ahash).
+//! SingleHasherBloomFilter accepts any hasher that can be created from keys (see KeyedHasher),
+//! e.g., a keyed SipHash or HighwayHash.
+//!
+//! ## Counting Bloom Filter (CountingBloomFilter)
+//! A variant of the KMBloomFilter that stores a counter instead of a single bit for each index,
+//! which makes it possible to remove elements and to approximate how often an element has been
+//! inserted. The width of the counters (4, 8, or 16 bits) can be chosen to trade memory for a
+//! lower chance of saturated counters.
+//!
+//! ## Rotating Bloom Filter (RotatingBloomFilter)
+//! A sliding window of KMBloomFilter generations. New elements are inserted into the newest
+//! generation, and the oldest generation is dropped whenever the filter advances, either manually or
+//! based on time. This makes it possible to filter, e.g., the elements of the last few hours. With
+//! the *std* feature, the window can be written and read again, e.g., to resume it after a restart.
+//!
+//! ## Generational Bloom Filter (GenerationalBloomFilter)
+//! A current and a previous KMBloomFilter generation. Elements that are used again, i.e., touched,
+//! in the current generation are retained when the filter advances, and all others are dropped
+//! with the previous generation, e.g., to admit only recently used keys to a cache.
+//!
+//! ## Sharded Bloom Filter (ShardedBloomFilter)
+//! One logical filter split into independent KMBloomFilter shards. Every element is routed to one
+//! shard by its hash values, so each shard can be stored, loaded, and merged on its own, e.g., if
+//! the filter is too large for a single allocation.
+//!
+//! ## Sparse Bloom Filter (SparseBloomFilter)
+//! A KMBloomFilter that stores the indices of its set bits in roaring-like containers while few
+//! bits are set, and converts itself to dense bits once they take less memory. This keeps filters
+//! that are sized for many more elements than they usually hold small.
+//!
+//! ## Roaring Bloom Filter (RoaringBloomFilter)
+//! A KMBloomFilter that stores its set bits in a roaring bitmap, so unions and intersections of
+//! many sparsely filled filters only touch their set bits. Requires the *roaring* feature.
+//!
+//! ## Expiring Bloom Filter (ExpiringBloomFilter)
+//! A RotatingBloomFilter that rotates its generations based on a time-to-live, so that it contains
+//! every element for at least the time-to-live after its insertion, and expires it shortly after.
+//! Requires the *std* feature.
+//!
+//! ## Migrating Bloom Filter (MigratingBloomFilter)
+//! A wrapper that answers queries from an old and a new filter, and inserts new elements into the
+//! new one only. This makes it possible to move to a filter of another configuration online, e.g.,
+//! to grow the capacity, and to retire the old filter manually or after a time-to-live.
+//!
+//! ## Exact Count Bloom Filter (ExactCountBloomFilter)
+//! A wrapper that counts the inserted elements that have not been contained before, instead of
+//! estimating their number from the set bits, whose error grows once a filter is more than half
+//! full.
+//!
+//! ## Filter With Exceptions (FilterWithExceptions)
+//! A wrapper with a small, exact set of elements that are known to be false positives of the
+//! wrapped filter, e.g., to stop a deny-list filter from blocking keys that have been confirmed
+//! not to be on the list, without rebuilding it. Requires the *std* feature.
+//!
+//! ## Attenuated Bloom Filter (AttenuatedBloomFilter)
+//! An array of KMBloomFilter levels by distance, e.g., the number of hops in a peer-to-peer
+//! network. Elements are inserted into the level of their distance, and a lookup returns the
+//! nearest level that may contain an element, which tells where to route a query. The levels of a
+//! node are built by merging the filters of its neighbors one level further away.
+//!
+//! ## Filter Cascade (FilterCascade)
+//! A chain of KMBloomFilter levels built from a set of included and a set of excluded elements,
+//! where every level contains the false positives of the previous level. The cascade has no false
+//! positives for the elements it has been built from, e.g., to publish an allow-list or a
+//! revocation list for a known universe of keys.
+//!
+//! # Other Filter Implementations
+//!
+//! ## Cuckoo Filter (CuckooFilter)
+//! A filter that stores small fingerprints of the inserted elements in a cuckoo hash table, as
+//! described by [Fan et al.](https://doi.org/10.1145/2674005.2674994). Like the counting bloom
+//! filter, it supports removing elements, but it needs less space for low false positive
+//! probabilities. The fingerprint width and the number of fingerprints per bucket can be configured.
+//! In contrast to bloom filters, a cuckoo filter can run full.
+//!
+//! ## D-left Counting Filter (DLeftCountingFilter)
+//! A counting filter based on d-left hashing, as described by
+//! [Bonomi et al.](https://doi.org/10.1007/11841036_61). It stores a fingerprint and a small counter
+//! for each element in one of four candidate buckets. It supports removing elements like the
+//! counting bloom filter, but needs about half the memory. Like a cuckoo filter, it can run full.
+//!
+//! ## Quotient Filter (QuotientFilter)
+//! A filter that stores fingerprints in a compact hash table with linear probing, as described by
+//! [Bender et al.](https://doi.org/10.14778/2350229.2350275). It supports removing elements,
+//! iterating the stored fingerprints in order, and merging filters. A full filter can be doubled
+//! in size without the original elements, at the cost of a doubled false positive probability.
+//!
+//! ## Golomb-coded Set (GcsFilter, GolombCodedSet)
+//! A GcsFilter records the hashes of the inserted elements and encodes them as a read-only
+//! GolombCodedSet, which needs less space than a bloom filter with the same false positive
+//! probability, e.g., about 20% less for a false positive probability of 0.0001. Queries decode the set from its beginning, so golomb-coded sets are meant for
+//! distributing static sets over the network rather than for fast lookups.
+//!
+//! ## Xor Filter (XorFilter)
+//! An immutable filter for static sets, as described by [Graf and Lemire](https://doi.org/10.1145/3376122).
+//! It is built once from all elements (or from the hashes recorded by a GcsFilter) and needs about
+//! 1.23 bytes per element for a false positive probability of about 1 / 256.
+//!
+//! ## Bloomier Filter (BloomMap)
+//! An immutable approximate map that is built like an xor filter and returns the value of a key
+//! from a small set of values, e.g., the shard that owns it. Keys that have not been inserted map
+//! to no value, except with a configurable false positive probability.
+//!
+//! # Distinct Counting
+//!
+//! ## HyperLogLog (DistinctCounter)
+//! Approximates the number of distinct elements with a relative standard error that does not
+//! depend on the number of elements, as described by Flajolet et al. Use it next to a bloom filter
+//! whose approximate element count is not accurate enough, e.g., because the filter is nearly
+//! saturated.
+//!
+//! # no_std
+//! The crate can be used without the standard library by disabling the default feature *std*.
+//! Only the *alloc* crate is required in this case. DefaultBloomFilter is not available without
+//! *std*, because it uses *std::collections::hash_map::DefaultHasher*. All other filters, including
+//! StableBloomFilterDefault, can be used as usual.
+//! ```toml
+//! [dependencies]
+//! bloom_filter_simple = { version = "0.1", default-features = false }
+//! ```
+//!
+//! # SIMD
+//! With the *simd* feature, merging bitsets with *union_with* and *intersect_with* and counting
+//! the ones of the result use AVX2 on x86_64 CPUs that support it, which is detected at runtime.
+//! Other CPUs fall back to the portable implementation. The feature requires *std*.
+//!
+//! # Prefetching
+//! With the *prefetch* feature, *KMBloomFilter::contains* computes the bit indices of up to 16
+//! hashers at once, prefetches their words on x86_64 CPUs, and then tests them without branching
+//! on every bit. The memory accesses of a query overlap instead of following each other, which
+//! reduces the latency of queries of filters that do not fit into the CPU caches, at the cost of
+//! always checking all bits of a batch, even if the first one is unset.
+//!
+//! # WebAssembly
+//! The crate compiles for *wasm32-unknown-unknown* and does not need *getrandom*: ahash only
+//! draws random keys on operating systems that provide random numbers, and
+//! *SeededBloomFilter::new_random* takes the random number generator from the caller. The bits of
+//! the bloom filters are computed with 64-bit arithmetic on every target, so a filter built by a 64-bit
+//! backend gives the same answers in a browser. Pass StableBloomFilterDefault filters between both
+//! as bytes with *KMBloomFilter::to_bytes* and *KMBloomFilter::from_bytes*.
+//!
+//! *std::time::Instant* panics on *wasm32-unknown-unknown*, so ExpiringBloomFilter and the
+//! time-based rotation of RotatingBloomFilter and MigratingBloomFilter cannot be used there. Call
+//! *RotatingBloomFilter::advance* and *MigratingBloomFilter::retire_old* instead.
+//!
+//! # Interoperability
+//! Filters with stable hashers, e.g., StableBloomFilterDefault, can be built by implementations
+//! in other languages. The module *test_vectors* documents the hashing scheme and the file format
+//! of *KMBloomFilter::write_to*, generates test vectors for them, and validates filters built from
+//! the vectors.
+//!
+//! Filters of other bloom filter libraries can be queried and built with read-only types that
+//! reproduce their hashing schemes and bit layouts. With the *interop-bloomfilter* feature,
+//! SipBloomFilter imports and exports the bitmaps of the *bloomfilter* crate, and with the
+//! *interop-redisbloom* feature, RedisBloomFilter imports and exports the *BF.SCANDUMP* chunks of
+//! RedisBloom, e.g., to check a Redis filter in a batch job without querying the server.
+//!
+//! # C interface
+//! With the *ffi* feature, the module *ffi* exports C functions to create, query, serialize, and
+//! release StableBloomFilterDefault filters, and `include/bloom_filter_simple.h` declares them, so
+//! services in other languages can use the same implementation and file format. The feature
+//! requires *std*.
+//!
+//! # Python bindings
+//! With the *python* feature, the crate is a Python module, built with maturin, whose classes
+//! *DefaultBloomFilter* and *StableBloomFilter* wrap DefaultBloomFilter and
+//! StableBloomFilterDefault. They insert and query strings, bytes, and integers, serialize and
+//! load filters in the format of *KMBloomFilter::write_to*, compute unions, and estimate the
+//! number of elements, e.g., to read the filters of a Rust pipeline in a notebook. The feature
+//! requires *std*.
+//!
+//! # Async persistence
+//! With the *tokio* feature, *KMBloomFilter::save_to* and *KMBloomFilter::load_from* write and
+//! read filters in the format of *write_to* with tokio's *AsyncWrite* and *AsyncRead*. The bits are
+//! processed in chunks, so checkpointing a large filter does not block the threads of the
+//! executor while the file or socket is not ready. The feature requires *std*.
+//!
+//! # Compressed persistence
+//! With the *gzip* or *zstd* feature, *KMBloomFilter::save_compressed* and
+//! *KMBloomFilter::load_compressed* write and read filters in the format of *write_to*, compressed
+//! with gzip or Zstandard while they are streamed. Sparsely filled filters compress well, e.g.,
+//! filters that are shipped as snapshots long before they reach their capacity. Both features
+//! require *std*.
+//!
+//! # Write-ahead log
+//! With the *std* feature, WalBloomFilter appends the hashes of every inserted element to a log
+//! file that starts with a snapshot of the filter, and *WalBloomFilter::recover* replays the log
+//! after a crash. *WalBloomFilter::checkpoint* replaces the log with a new snapshot. This loses no
+//! insertions between two snapshots, e.g., in a deduplication service.
+//!
+//! # Lazy loading
+//! With the *std* feature, LazyBloomFilter opens a filter that has been written with
+//! *KMBloomFilter::write_to* by reading and validating only its header. The bits are read in
+//! chunks when they are accessed for the first time, and *LazyBloomFilter::warm_up* reads the
+//! remaining chunks and verifies the checksum, e.g., to start a service quickly although it may
+//! never query a filter of several gigabytes. With the *mmap* feature, MmapBloomFilter maps the
+//! file instead.
+//!
+//! # Shared memory
+//! With the *shm* feature, ShmBloomFilter stores the bits of a filter in a named POSIX
+//! shared-memory segment on unix, e.g., to let a writer daemon insert elements while reader
+//! processes query the same live filter. Every process maps the segment and sets and tests the
+//! bits with atomic operations, so the filter is neither copied nor sent over a socket. The
+//! feature requires *std*.
+//!
+//! # Privacy
+//! With the *rand* feature, *KMBloomFilter::add_noise* and *KMBloomFilter::with_noise* set a
+//! fraction of random bits, so that a filter that is shared with a partner does not reveal for
+//! certain whether an element has been inserted. The estimates of the filter account for the
+//! noise.
+//!
+//! # Request deduplication
+//! With the *tower* feature, BloomDedupLayer is a tower middleware, e.g., for axum, that records
+//! the idempotency keys of requests in a RotatingBloomFilter and marks or rejects requests whose
+//! key has recently been seen. The feature requires *std*.
+//!
+//! # Metrics
+//! With the *metrics* feature, every KMBloomFilter counts its insertions, queries, and positive
+//! queries. *KMBloomFilter::stats* returns a snapshot of the counters together with the estimated
+//! number of false positives and the fill ratio, e.g., to export the hit rate and saturation of a
+//! filter to a monitoring system. The filters built from KMBloomFilter, e.g., the shards of a
+//! ShardedBloomFilter, keep their own counters.
+//!
+//! # Errors
+//! Every fallible operation returns a specific error type, e.g., *CuckooFilterFull*, and all of
+//! them convert into BloomFilterError, so code that uses several filters can propagate their
+//! errors with `?` and a single error type. The operations of Bitset panic on invalid indices and
+//! bitsets of different lengths; their checked variants, e.g., *Bitset::try_set* and
+//! *Bitset::try_union*, return a BloomFilterError instead.
+//!
+//! # Thread safety
+//! All filters are *Send* and *Sync*, regardless of whether their hashers are, because they never
+//! store a hasher. Queries only need shared references, so a filter that is no longer modified can
+//! be shared between threads, e.g., in an *Arc*, or frozen with *KMBloomFilter::freeze* into a
+//! FrozenBloomFilter that is cheap to clone and cannot be modified anymore. Insertions need a mutable reference; use
+//! SyncBloomFilter (with *std*) to insert from multiple threads concurrently, or
+//! *KMBloomFilter::par_from_iter* (with *rayon*) to build a filter in parallel.
 //!
 //! # Examples
 //! In the following, you can find simple examples of how to initialize and use the different bloom filter types.
@@ -44,10 +292,17 @@
 //! different hash functions for use by KMBloomFilter, and this combination yielded the best results
 //! with respect to the filter's false positive probability.
 //!
-//! We recommend using DefaultBloomFilter for quickly getting started.
+//! We recommend using DefaultBloomFilter for quickly getting started. If you want to persist a
+//! filter, use StableBloomFilterDefault instead, whose hash values do not change between processes,
+//! platforms, or releases of this crate. For reproducible tests, e.g., snapshot tests or golden
+//! files, *DefaultBloomFilter::new_deterministic* returns such a filter.
 //! ```
+//! # #[cfg(feature = "std")]
 //! use bloom_filter_simple::{BloomFilter,DefaultBloomFilter};
 //!
+//! # #[cfg(not(feature = "std"))]
+//! # fn main() {}
+//! # #[cfg(feature = "std")]
 //! fn main() {
 //!     // We plan on storing at most 10,000 elements
 //!     let desired_capacity = 10_000;
@@ -74,6 +329,24 @@
 //! }
 //! ```
 //!
+//! If only the capacity matters, *with_capacity* uses a false positive probability of 1%. The
+//! false positive probability can also be given as a FalsePositiveProbability, which is validated
+//! to be in the interval (0,1) when it is created, e.g., when it is read from a configuration.
+//! ```
+//! # #[cfg(feature = "std")]
+//! use bloom_filter_simple::{DefaultBloomFilter,FalsePositiveProbability};
+//!
+//! # #[cfg(not(feature = "std"))]
+//! # fn main() {}
+//! # #[cfg(feature = "std")]
+//! # fn main() {
+//! let quick = DefaultBloomFilter::with_capacity(10_000);
+//!
+//! let desired_fp_probability = FalsePositiveProbability::new(0.0001).unwrap();
+//! let configured = DefaultBloomFilter::new(10_000, desired_fp_probability);
+//! # }
+//! ```
+//!
 //! ## KMBloomFilter
 //! Initialization and application of a KMBloomFilter.
 //! ```
@@ -142,19 +415,160 @@
 //! }
 //! ```
 
-use std::{collections::hash_map::DefaultHasher, hash::Hash};
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+use core::hash::Hash;
+#[cfg(feature = "std")]
+use std::collections::hash_map::DefaultHasher;
 
+mod attenuated_bloom_filter;
 mod bitset;
+mod bitset_delta;
+mod bloom_filter_view;
+mod bloom_map;
+mod build_hasher_bloom_filter;
+mod counters;
+mod counting_bloom_filter;
+mod cuckoo_filter;
+mod d_left_counting_filter;
+#[cfg(feature = "tower")]
+mod dedup_layer;
+mod distinct_counter;
+mod error;
+mod exact_count_bloom_filter;
+#[cfg(feature = "std")]
+mod expiring_bloom_filter;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+mod file_format;
+mod filter_cascade;
+#[cfg(feature = "std")]
+mod filter_with_exceptions;
+mod frozen_bloom_filter;
+mod generational_bloom_filter;
+mod golomb_coded_set;
+mod hashed_key;
+mod insert_batch;
 mod km_bloom_filter;
+#[cfg(feature = "std")]
+mod lazy_bloom_filter;
+mod math;
+mod metrics;
+mod migrating_bloom_filter;
+#[cfg(feature = "mmap")]
+mod mmap_bloom_filter;
+mod packed_array;
+pub mod params;
+mod probabilistic_set;
+#[cfg(feature = "python")]
+mod python;
+mod quotient_filter;
+#[cfg(feature = "interop-redisbloom")]
+mod redis_bloom_filter;
+#[cfg(feature = "roaring")]
+mod roaring_bloom_filter;
+mod rotating_bloom_filter;
 mod seeded_bloom_filter;
+mod sharded_bloom_filter;
+#[cfg(all(unix, feature = "shm"))]
+mod shm_bloom_filter;
+mod simd;
+#[cfg(feature = "interop-bloomfilter")]
+mod sip_bloom_filter;
+mod sparse_bloom_filter;
+mod stable_hashers;
+#[cfg(feature = "io")]
+mod streaming;
+#[cfg(feature = "std")]
+mod sync_bloom_filter;
+pub mod test_vectors;
+#[cfg(feature = "std")]
+mod wal_bloom_filter;
+mod xor_filter;
 
-pub use km_bloom_filter::KMBloomFilter;
-pub use seeded_bloom_filter::SeededBloomFilter;
+use params::{
+    approximate_difference_count, approximate_element_count, approximate_element_count_bounds,
+    approximate_false_positive_probability, approximate_intersection_count,
+    approximate_jaccard_similarity,
+};
+
+pub use attenuated_bloom_filter::AttenuatedBloomFilter;
+pub use bitset::Bitset;
+pub use bitset_delta::{BitsetDelta, InvalidBitsetDelta};
+pub use bloom_filter_view::BloomFilterView;
+pub use bloom_map::BloomMap;
+pub use build_hasher_bloom_filter::BuildHasherBloomFilter;
+pub use counters::{Counters, Counters16, Counters4, Counters8};
+pub use counting_bloom_filter::CountingBloomFilter;
+pub use cuckoo_filter::{CuckooFilter, CuckooFilterFull};
+pub use d_left_counting_filter::{DLeftCountingFilter, DLeftCountingFilterFull};
+#[cfg(feature = "tower")]
+pub use dedup_layer::{BloomDedup, BloomDedupLayer, OnDuplicate};
+pub use distinct_counter::DistinctCounter;
+pub use error::BloomFilterError;
+pub use exact_count_bloom_filter::ExactCountBloomFilter;
+#[cfg(feature = "std")]
+pub use expiring_bloom_filter::ExpiringBloomFilter;
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub use file_format::Compression;
+#[cfg(feature = "std")]
+pub use file_format::ReadFilterError;
+pub use filter_cascade::{FilterCascade, FilterCascadeConflict};
+#[cfg(feature = "std")]
+pub use filter_with_exceptions::FilterWithExceptions;
+pub use frozen_bloom_filter::FrozenBloomFilter;
+pub use generational_bloom_filter::GenerationalBloomFilter;
+pub use golomb_coded_set::{GcsFilter, GolombCodedSet, InvalidGolombCodedSet};
+pub use hashed_key::HashedKey;
+pub use insert_batch::InsertBatch;
+pub use km_bloom_filter::{CapacityExceeded, IndexLayout, KMBloomFilter, MergeError, OnSaturation};
+#[cfg(feature = "std")]
+pub use lazy_bloom_filter::LazyBloomFilter;
+#[cfg(feature = "metrics")]
+pub use metrics::FilterStats;
+pub use migrating_bloom_filter::MigratingBloomFilter;
+#[cfg(feature = "mmap")]
+pub use mmap_bloom_filter::MmapBloomFilter;
+pub use params::{FalsePositiveProbability, InvalidFalsePositiveProbability, InvalidFilterParams};
+pub use probabilistic_set::ProbabilisticSet;
+pub use quotient_filter::{QuotientFilter, QuotientFilterFull};
+#[cfg(feature = "interop-redisbloom")]
+pub use redis_bloom_filter::{InvalidRedisBloomChunk, RedisBloomFilter};
+#[cfg(feature = "roaring")]
+pub use roaring_bloom_filter::RoaringBloomFilter;
+pub use rotating_bloom_filter::RotatingBloomFilter;
+pub use seeded_bloom_filter::{KeyedHasher, SeededBloomFilter, SingleHasherBloomFilter};
+pub use sharded_bloom_filter::ShardedBloomFilter;
+#[cfg(all(unix, feature = "shm"))]
+pub use shm_bloom_filter::ShmBloomFilter;
+#[cfg(feature = "interop-bloomfilter")]
+pub use sip_bloom_filter::{InvalidSipBloomFilter, SipBloomFilter};
+pub use sparse_bloom_filter::SparseBloomFilter;
+pub use stable_hashers::{
+    StableXxh3Hasher, StableXxh64Hasher, STABLE_XXH3_SEED, STABLE_XXH64_SEED,
+};
+#[cfg(feature = "std")]
+pub use sync_bloom_filter::SyncBloomFilter;
+#[cfg(feature = "std")]
+pub use wal_bloom_filter::{WalBloomFilter, WalInsertError};
+pub use xor_filter::XorFilter;
 
 /**
  A default implementation of KMBloomFilter using ahash::AHasher and collections::hash_map::DefaultHasher.
 
  DefaultBloomFilter is implemented as a type definition `type DefaultBloomFilter = KMBloomFilter<ahash::AHasher, DefaultHasher>;`
+ It is only available with the *std* feature, which is enabled by default.
+
+ The hash values of ahash::AHasher depend on the features of *ahash* that are enabled in the
+ dependency graph and may differ in every process. Use *new_deterministic* if the bits of a filter
+ must be reproducible.
+
+ Measured with tests/accuracy.rs, the false positive rate of filters filled to capacity was 0.97 to
+ 1.06 times the desired false positive probability for capacities of 1,000 to 100,000 elements and
+ probabilities of 0.1 to 0.001, within the statistical tolerance of the test.
  # Examples
  ```
  use bloom_filter_simple::{DefaultBloomFilter,BloomFilter};
@@ -173,8 +587,116 @@ pub use seeded_bloom_filter::SeededBloomFilter;
  }
  ```
 */
+#[cfg(feature = "std")]
 pub type DefaultBloomFilter = KMBloomFilter<ahash::AHasher, DefaultHasher>;
 
+/**
+ A KMBloomFilter using hashers whose hash values are stable across processes, platforms, and
+ releases of this crate.
+
+ The filter uses XXH3 and XXH64 with the fixed seeds [STABLE_XXH3_SEED] and [STABLE_XXH64_SEED]
+ (see [StableXxh3Hasher] and [StableXxh64Hasher]). In contrast to DefaultBloomFilter, a filter
+ built in one process will give the same answers when its bits are restored in another process,
+ on another platform, or with a later release of this crate. Use it for filters that are persisted.
+
+ StableBloomFilterDefault is implemented as a type definition `type StableBloomFilterDefault = KMBloomFilter<StableXxh3Hasher, StableXxh64Hasher>;`
+
+ Measured with tests/accuracy.rs, the false positive rate of filters filled to capacity was 0.94 to
+ 1.05 times the desired false positive probability for capacities of 1,000 to 100,000 elements and
+ probabilities of 0.1 to 0.001, within the statistical tolerance of the test.
+ # Examples
+ ```
+ use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+
+ let mut bloom_filter = StableBloomFilterDefault::new(1_000, 0.0001);
+ bloom_filter.insert(&"Hello!");
+
+ assert!(bloom_filter.contains(&"Hello!"));
+ ```
+*/
+pub type StableBloomFilterDefault = KMBloomFilter<StableXxh3Hasher, StableXxh64Hasher>;
+
+/**
+ A KMBloomFilter using XXH3 and Rust's collections::hash_map::DefaultHasher.
+
+ XxDefaultBloomFilter is implemented as a type definition `type XxDefaultBloomFilter = KMBloomFilter<xxhash_rust::xxh3::Xxh3, DefaultHasher>;`
+ It is only available with the *std* feature. The hash values of XXH3 are stable, but the ones of
+ DefaultHasher may change between Rust versions, so use StableBloomFilterDefault for filters that
+ are persisted.
+
+ Measured with tests/accuracy.rs, the false positive rate of filters filled to capacity was 0.92 to
+ 1.10 times the desired false positive probability for capacities of 1,000 to 100,000 elements and
+ probabilities of 0.1 to 0.001, within the statistical tolerance of the test.
+ # Examples
+ ```
+ use bloom_filter_simple::{BloomFilter,XxDefaultBloomFilter};
+
+ let mut bloom_filter = XxDefaultBloomFilter::new(1_000, 0.0001);
+ bloom_filter.insert(&"Hello!");
+
+ assert!(bloom_filter.contains(&"Hello!"));
+ ```
+*/
+#[cfg(feature = "std")]
+pub type XxDefaultBloomFilter = KMBloomFilter<xxhash_rust::xxh3::Xxh3, DefaultHasher>;
+
+/**
+ A KMBloomFilter using XXH3 and XXH64 with their default seed zero.
+
+ XxBloomFilter is implemented as a type definition `type XxBloomFilter = KMBloomFilter<xxhash_rust::xxh3::Xxh3, xxhash_rust::xxh64::Xxh64>;`
+ Like StableBloomFilterDefault, it is available without *std* and its hash values are stable, but
+ it uses the default seeds, so its bits differ from the ones of StableBloomFilterDefault.
+
+ Measured with tests/accuracy.rs, the false positive rate of filters filled to capacity was 0.97 to
+ 1.08 times the desired false positive probability for capacities of 1,000 to 100,000 elements and
+ probabilities of 0.1 to 0.001, within the statistical tolerance of the test.
+ # Examples
+ ```
+ use bloom_filter_simple::{BloomFilter,XxBloomFilter};
+
+ let mut bloom_filter = XxBloomFilter::new(1_000, 0.0001);
+ bloom_filter.insert(&"Hello!");
+
+ assert!(bloom_filter.contains(&"Hello!"));
+ ```
+*/
+pub type XxBloomFilter = KMBloomFilter<xxhash_rust::xxh3::Xxh3, xxhash_rust::xxh64::Xxh64>;
+
+#[cfg(feature = "std")]
+impl DefaultBloomFilter {
+    /// Initialize a new bloom filter like *new* whose bits are the same in every run, e.g., for
+    /// snapshot tests or golden files.
+    ///
+    /// The keys of the AHasher of DefaultBloomFilter are fixed constants only as long as no crate
+    /// in the dependency graph enables the *std* or *compile-time-rng* feature of *ahash*.
+    /// Otherwise, they are drawn randomly in every process or build, so the bits of a
+    /// DefaultBloomFilter cannot be reproduced. Hence, this function returns a
+    /// StableBloomFilterDefault, which hashes with XXH3 and XXH64 and the fixed, documented seeds
+    /// [STABLE_XXH3_SEED] and [STABLE_XXH64_SEED].
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0 or if desired_false_positive_probability is not in the
+    /// interval (0,1).
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,DefaultBloomFilter};
+    ///
+    /// let mut filter = DefaultBloomFilter::new_deterministic(1_000, 0.01);
+    /// filter.insert(&"Hello!");
+    ///
+    /// // The same bits in every run
+    /// assert_eq!(vec![500, 2_714, 3_772, 4_830, 5_888, 8_102, 9_160], filter.probe_indices(&"Hello!"));
+    /// ```
+    pub fn new_deterministic(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> StableBloomFilterDefault {
+        StableBloomFilterDefault::new(desired_capacity, desired_false_positive_probability)
+    }
+}
+
 /// This trait defines the basic functionality supported by the bloom filters in this library.
 ///
 pub trait BloomFilter {
@@ -188,8 +710,10 @@ pub trait BloomFilter {
     /// # Examples
     /// How *insert* of a type implementing BloomFilter might be used:
     /// ```
+    /// # #[cfg(feature = "std")]
     /// use bloom_filter_simple::{BloomFilter, DefaultBloomFilter};
     ///
+    /// # #[cfg(feature = "std")]
     /// fn bloom_filter_insert() {
     ///     let mut bloom_filter = DefaultBloomFilter::new(5, 0.001);
     ///     bloom_filter.insert(&"Hello!");
@@ -220,7 +744,9 @@ pub trait BloomFilter {
     /// # Examples
     /// How contains of a type implementing BloomFilter might be used:
     /// ```
+    /// # #[cfg(feature = "std")]
     /// use bloom_filter_simple::{BloomFilter, DefaultBloomFilter};
+    /// # #[cfg(feature = "std")]
     /// fn bloom_filter_insert() {
     ///     let mut bloom_filter = DefaultBloomFilter::new(5, 0.001);
     ///     bloom_filter.insert(&"Hello!");
@@ -231,44 +757,283 @@ pub trait BloomFilter {
     /// }
     /// ```
     fn contains<T: Hash>(&self, data: &T) -> bool;
-}
 
-/// Calculate the optimal bit count to satisfy the desired constraints.
-/// Formula taken from Sagi Kedmi:
-/// > S. Kedmi, ["Bloom Filters for the Perplexed"](https://sagi.io/bloom-filters-for-the-perplexed/), July 2017 [Accessed: 02.12.2020]
-fn optimal_bit_count(desired_capacity: usize, desired_false_positive_probability: f64) -> usize {
-    (-(desired_capacity as f64 * desired_false_positive_probability.ln()) / (2.0f64.ln().powi(2)))
-        .ceil() as usize
+    /// Insert all elements of an iterator into the filter.
+    ///
+    /// The result is the same as calling *insert* for each element. The filters in this crate
+    /// override this method to compute the indices of a whole batch of elements first and to set
+    /// them afterwards. Separating the hashing from the memory accesses lets the CPU overlap the
+    /// cache misses of large filters, which makes bulk inserts noticeably faster.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "std")]
+    /// use bloom_filter_simple::{BloomFilter, DefaultBloomFilter};
+    ///
+    /// # #[cfg(feature = "std")]
+    /// fn bloom_filter_insert_all() {
+    ///     let mut bloom_filter = DefaultBloomFilter::new(1_000, 0.001);
+    ///     bloom_filter.insert_all(0..1_000);
+    ///     bloom_filter.insert_all(&["Hello!", "Goodbye!"]);
+    ///
+    ///     assert_eq!(true, bloom_filter.contains(&500));
+    ///     assert_eq!(true, bloom_filter.contains(&"Hello!"));
+    /// }
+    /// ```
+    fn insert_all<T, I>(&mut self, iter: I)
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        for data in iter {
+            self.insert(&data);
+        }
+    }
+
+    /// Insert data into the filter and return whether it has been contained in the filter before,
+    /// e.g., to deduplicate a stream of elements.
+    ///
+    /// The result is the same as calling *contains* and then *insert*, so it can be a false
+    /// positive, i.e., true for an element that has not been inserted before. KMBloomFilter and
+    /// the filters built on it override this method to hash the element only once, and to check
+    /// and set every bit in the same pass.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter, StableBloomFilterDefault};
+    ///
+    /// let mut bloom_filter = StableBloomFilterDefault::new(1_000, 0.001);
+    /// let mut unique = 0;
+    /// for word in ["apple", "banana", "apple", "cherry", "banana"].iter() {
+    ///     if !bloom_filter.check_and_insert(word) {
+    ///         unique += 1;
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(3, unique);
+    /// ```
+    fn check_and_insert<T: Hash>(&mut self, data: &T) -> bool {
+        let contained = self.contains(data);
+        self.insert(data);
+        contained
+    }
+
+    /// Insert every line of *reader* into the filter as *str* and return the number of lines.
+    ///
+    /// Lines are split like *BufRead::lines* does, i.e., at '\n' with an optional preceding '\r'.
+    /// The lines are streamed through a single buffer, so reading files that are larger than the
+    /// available memory is not a problem.
+    ///
+    /// Only available with the *io* feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind *InvalidData* if a line is not valid UTF-8, and forwards errors
+    /// of *reader*. The lines read before the error remain inserted.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter, DefaultBloomFilter};
+    ///
+    /// let mut bloom_filter = DefaultBloomFilter::new(1_000, 0.001);
+    /// let count = bloom_filter.insert_lines("apple\nbanana\r\ncherry".as_bytes()).unwrap();
+    ///
+    /// assert_eq!(3, count);
+    /// assert_eq!(true, bloom_filter.contains(&"banana"));
+    /// ```
+    #[cfg(feature = "io")]
+    fn insert_lines<R: std::io::BufRead>(&mut self, reader: R) -> std::io::Result<usize> {
+        streaming::insert_delimited(self, reader, b'\n', true)
+    }
+
+    /// Insert every key of *reader* into the filter as *str* and return the number of keys. Keys
+    /// are separated by *delimiter*.
+    ///
+    /// Only available with the *io* feature. See *insert_lines* for details.
+    #[cfg(feature = "io")]
+    fn insert_delimited<R: std::io::BufRead>(
+        &mut self,
+        reader: R,
+        delimiter: u8,
+    ) -> std::io::Result<usize> {
+        streaming::insert_delimited(self, reader, delimiter, false)
+    }
 }
 
-/// Calculate the optimal number of hashers to satisfy the desired constraints.
-/// Formula taken from Sagi Kedmi:
-/// > S. Kedmi, ["Bloom Filters for the Perplexed"](https://sagi.io/bloom-filters-for-the-perplexed/), July 2017 [Accessed: 02.12.2020]
-fn optimal_number_of_hashers(desired_capacity: usize, bit_count: usize) -> usize {
-    ((bit_count as f64 / desired_capacity as f64) * 2.0f64.ln()).round() as usize
+/// Object-safe companion of BloomFilter for elements given as bytes, which makes it possible to
+/// choose the filter implementation at runtime, e.g., as *Box<dyn DynBloomFilter>*.
+///
+/// BloomFilter cannot be used as a trait object because its methods are generic over the element
+/// type. DynBloomFilter is implemented for every BloomFilter and forwards the bytes as a byte
+/// slice: *dyn_insert(key)* is the same as *insert(&key)*, so elements inserted as *&[u8]* or
+/// *Vec<u8>* through the generic methods are found by *dyn_contains*, and vice versa. Strings have
+/// to be passed as *str* to the generic methods, because they hash differently than their bytes.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{DynBloomFilter,QuotientFilter,StableBloomFilterDefault,StableXxh3Hasher};
+///
+/// fn create_filter(name: &str) -> Box<dyn DynBloomFilter + Send + Sync> {
+///     match name {
+///         "quotient" => Box::new(QuotientFilter::<StableXxh3Hasher>::new(1_000, 0.001)),
+///         _ => Box::new(StableBloomFilterDefault::new(1_000, 0.001)),
+///     }
+/// }
+///
+/// fn main() {
+///     for name in &["bloom", "quotient"] {
+///         let mut filter = create_filter(name);
+///         filter.dyn_insert(b"Hello!");
+///         assert_eq!(true, filter.dyn_contains(b"Hello!"));
+///         assert_eq!(false, filter.dyn_contains(b"Goodbye!"));
+///     }
+/// }
+/// ```
+pub trait DynBloomFilter {
+    /// Insert the element *key* into the filter.
+    ///
+    /// See *BloomFilter::insert* for details.
+    fn dyn_insert(&mut self, key: &[u8]);
+
+    /// Check whether the element *key* is contained in the filter.
+    ///
+    /// See *BloomFilter::contains* for details.
+    fn dyn_contains(&self, key: &[u8]) -> bool;
 }
 
-/// Approximate number of elements stored.
-/// Formula taken from Wikipedia:
-/// > Wikipedia, ["Bloom filter"](https://en.wikipedia.org/wiki/Bloom_filter#Approximating_the_number_of_items_in_a_Bloom_filter) [Accessed: 02.12.2020]
-fn approximate_element_count(
-    number_of_hashers: usize,
-    bits_per_hasher: usize,
-    number_of_ones: usize,
-) -> f64 {
-    -(bits_per_hasher as f64)
-        * (1.0 - (number_of_ones as f64) / ((number_of_hashers * bits_per_hasher) as f64)).ln()
+impl<F> DynBloomFilter for F
+where
+    F: BloomFilter,
+{
+    fn dyn_insert(&mut self, key: &[u8]) {
+        self.insert(&key);
+    }
+
+    fn dyn_contains(&self, key: &[u8]) -> bool {
+        self.contains(&key)
+    }
 }
 
-/// Return the current approximate false positive probability which depends on the current
-/// number of elements in the filter.
-/// Formula taken from Sagi Kedmi:
-/// > S. Kedmi, ["Bloom Filters for the Perplexed"](https://sagi.io/bloom-filters-for-the-perplexed/), July 2017 [Accessed: 02.12.2020]
-fn approximate_false_positive_probability(
-    number_of_hashers: usize,
-    bits_per_hasher: usize,
-    element_count: f64,
-) -> f64 {
-    (1.0 - std::f64::consts::E.powf(-element_count / bits_per_hasher as f64))
-        .powf(number_of_hashers as f64)
+/// Number of elements whose indices are computed before they are set in a single batch by
+/// *insert_all*.
+const INSERT_BATCH_SIZE: usize = 4096;
+
+/// Create a filter with *new* that is sized for the number of elements of *iter* and insert them.
+///
+/// If the size hint of *iter* is not exact, the elements are buffered to count them first.
+fn filter_from_iter<F, N, T, I>(iter: I, new: N) -> F
+where
+    F: BloomFilter,
+    N: FnOnce(usize) -> F,
+    T: Hash,
+    I: IntoIterator<Item = T>,
+{
+    let iter = iter.into_iter();
+    match iter.size_hint() {
+        (lower, Some(upper)) if lower == upper => {
+            let mut filter = new(lower.max(1));
+            filter.insert_all(iter);
+            filter
+        }
+        _ => {
+            let elements: alloc::vec::Vec<T> = iter.collect();
+            let mut filter = new(elements.len().max(1));
+            filter.insert_all(elements);
+            filter
+        }
+    }
 }
+
+// Static assertions that all filters are Send and Sync, even with hashers that are neither
+const _: fn() = || {
+    struct LocalHasher(core::cell::Cell<u64>, core::marker::PhantomData<*const ()>);
+
+    impl Default for LocalHasher {
+        fn default() -> Self {
+            Self(core::cell::Cell::new(0), core::marker::PhantomData)
+        }
+    }
+
+    impl core::hash::Hasher for LocalHasher {
+        fn finish(&self) -> u64 {
+            self.0.get()
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            for &byte in bytes {
+                self.0.set(self.0.get().rotate_left(8) ^ byte as u64);
+            }
+        }
+    }
+
+    impl KeyedHasher for LocalHasher {
+        fn with_keys(key_a: u128, _key_b: u128) -> Self {
+            Self(
+                core::cell::Cell::new(key_a as u64),
+                core::marker::PhantomData,
+            )
+        }
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Bitset>();
+    assert_send_sync::<BitsetDelta>();
+    assert_send_sync::<KMBloomFilter<LocalHasher, LocalHasher>>();
+    assert_send_sync::<
+        BuildHasherBloomFilter<
+            core::hash::BuildHasherDefault<LocalHasher>,
+            core::hash::BuildHasherDefault<LocalHasher>,
+        >,
+    >();
+    assert_send_sync::<SeededBloomFilter>();
+    assert_send_sync::<SingleHasherBloomFilter<LocalHasher>>();
+    assert_send_sync::<CountingBloomFilter<LocalHasher, LocalHasher, Counters4>>();
+    assert_send_sync::<RotatingBloomFilter<LocalHasher, LocalHasher>>();
+    assert_send_sync::<GenerationalBloomFilter<LocalHasher, LocalHasher>>();
+    assert_send_sync::<ShardedBloomFilter<LocalHasher, LocalHasher>>();
+    assert_send_sync::<SparseBloomFilter<LocalHasher, LocalHasher>>();
+    #[cfg(feature = "roaring")]
+    assert_send_sync::<RoaringBloomFilter<LocalHasher, LocalHasher>>();
+    #[cfg(feature = "interop-bloomfilter")]
+    assert_send_sync::<SipBloomFilter>();
+    #[cfg(feature = "interop-redisbloom")]
+    assert_send_sync::<RedisBloomFilter>();
+    assert_send_sync::<ExactCountBloomFilter<KMBloomFilter<LocalHasher, LocalHasher>>>();
+    assert_send_sync::<
+        MigratingBloomFilter<KMBloomFilter<LocalHasher, LocalHasher>, QuotientFilter<LocalHasher>>,
+    >();
+    assert_send_sync::<FrozenBloomFilter<LocalHasher, LocalHasher>>();
+    assert_send_sync::<AttenuatedBloomFilter<LocalHasher, LocalHasher>>();
+    assert_send_sync::<FilterCascade<LocalHasher, LocalHasher>>();
+    assert_send_sync::<ProbabilisticSet<str, LocalHasher, LocalHasher>>();
+    assert_send_sync::<HashedKey<LocalHasher, LocalHasher>>();
+    assert_send_sync::<InsertBatch<'static, LocalHasher, LocalHasher>>();
+    assert_send_sync::<BloomFilterView<'static, LocalHasher, LocalHasher>>();
+    assert_send_sync::<CuckooFilter<LocalHasher>>();
+    assert_send_sync::<DLeftCountingFilter<LocalHasher>>();
+    assert_send_sync::<QuotientFilter<LocalHasher>>();
+    assert_send_sync::<GcsFilter<LocalHasher>>();
+    assert_send_sync::<GolombCodedSet<LocalHasher>>();
+    assert_send_sync::<XorFilter<LocalHasher>>();
+    assert_send_sync::<BloomMap<u32, LocalHasher>>();
+    assert_send_sync::<DistinctCounter<LocalHasher>>();
+    assert_send_sync::<BloomFilterError>();
+    #[cfg(feature = "std")]
+    assert_send_sync::<ExpiringBloomFilter<LocalHasher, LocalHasher>>();
+    #[cfg(feature = "std")]
+    assert_send_sync::<LazyBloomFilter<LocalHasher, LocalHasher>>();
+    #[cfg(feature = "std")]
+    assert_send_sync::<WalBloomFilter<LocalHasher, LocalHasher>>();
+    #[cfg(feature = "std")]
+    assert_send_sync::<SyncBloomFilter<KMBloomFilter<LocalHasher, LocalHasher>>>();
+    #[cfg(feature = "std")]
+    assert_send_sync::<FilterWithExceptions<KMBloomFilter<LocalHasher, LocalHasher>, u64>>();
+    #[cfg(feature = "tower")]
+    assert_send_sync::<
+        BloomDedupLayer<fn(&u64) -> Option<u64>, u64, u64, u64, LocalHasher, LocalHasher>,
+    >();
+    #[cfg(feature = "mmap")]
+    assert_send_sync::<MmapBloomFilter<LocalHasher, LocalHasher>>();
+    #[cfg(all(unix, feature = "shm"))]
+    assert_send_sync::<ShmBloomFilter<LocalHasher, LocalHasher>>();
+};
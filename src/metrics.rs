@@ -0,0 +1,120 @@
+//! Counters of the insertions and queries of a filter.
+//!
+//! The counters are only kept with the *metrics* feature. Without it, *Metrics* has no fields and
+//! recording an operation compiles to nothing.
+
+#[cfg(feature = "metrics")]
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/// Snapshot of the counters of a filter, e.g., to export them to a monitoring system.
+///
+/// Only available with the *metrics* feature.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,StableBloomFilterDefault};
+///
+/// let mut filter = StableBloomFilterDefault::new(1_000, 0.01);
+/// filter.insert_all(0..500);
+/// for i in 0..1_000 {
+///     filter.contains(&i);
+/// }
+///
+/// let stats = filter.stats();
+/// assert_eq!(500, stats.inserts);
+/// assert_eq!(1_000, stats.queries);
+/// assert!(stats.positives >= 500);
+/// assert!(stats.estimated_false_positives < 10.0);
+/// assert!(stats.hit_rate() >= 0.5);
+/// ```
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FilterStats {
+    /// Number of inserted elements, including elements that have been inserted before.
+    pub inserts: u64,
+    /// Number of queries.
+    pub queries: u64,
+    /// Number of queries that reported the element as contained.
+    pub positives: u64,
+    /// Approximate number of positive queries that were false positives.
+    ///
+    /// The estimate assumes that the negative queries are the share *1 - p* of all queries for
+    /// elements that have not been inserted, where *p* is the current approximate false positive
+    /// probability of the filter. Hence, it overestimates the false positives of queries that have
+    /// been answered while the filter had fewer elements.
+    pub estimated_false_positives: f64,
+    /// Fraction of bits that are set at the time of the snapshot, i.e., the saturation of the
+    /// filter.
+    pub fill_ratio: f64,
+}
+
+#[cfg(feature = "metrics")]
+impl FilterStats {
+    /// Fraction of queries that reported the element as contained, or 0 if there have not been any
+    /// queries.
+    pub fn hit_rate(&self) -> f64 {
+        if self.queries == 0 {
+            0.0
+        } else {
+            self.positives as f64 / self.queries as f64
+        }
+    }
+
+    /// Snapshot of *metrics* for a filter with the given current approximate false positive
+    /// probability and fill ratio.
+    pub(crate) fn new(metrics: &Metrics, false_positive_probability: f64, fill_ratio: f64) -> Self {
+        let inserts = metrics.inserts.load(Ordering::Relaxed);
+        let queries = metrics.queries.load(Ordering::Relaxed);
+        let positives = metrics.positives.load(Ordering::Relaxed);
+        let negatives = queries.saturating_sub(positives) as f64;
+        let estimated_false_positives = if false_positive_probability < 1.0 {
+            negatives * false_positive_probability / (1.0 - false_positive_probability)
+        } else {
+            positives as f64
+        };
+        Self {
+            inserts,
+            queries,
+            positives,
+            estimated_false_positives: estimated_false_positives.min(positives as f64),
+            fill_ratio,
+        }
+    }
+}
+
+/// Counters of a filter, which are atomic because queries only borrow the filter.
+#[derive(Default)]
+pub(crate) struct Metrics {
+    #[cfg(feature = "metrics")]
+    inserts: AtomicU64,
+    #[cfg(feature = "metrics")]
+    queries: AtomicU64,
+    #[cfg(feature = "metrics")]
+    positives: AtomicU64,
+}
+
+impl Metrics {
+    #[inline]
+    pub fn record_inserts(&self, _count: usize) {
+        #[cfg(feature = "metrics")]
+        self.inserts.fetch_add(_count as u64, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn record_query(&self, _positive: bool) {
+        #[cfg(feature = "metrics")]
+        {
+            self.queries.fetch_add(1, Ordering::Relaxed);
+            if _positive {
+                self.positives.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn reset(&self) {
+        self.inserts.store(0, Ordering::Relaxed);
+        self.queries.store(0, Ordering::Relaxed);
+        self.positives.store(0, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,256 @@
+use core::{
+    fmt::{self, Debug},
+    hash::Hash,
+};
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+use crate::BloomFilter;
+
+/// Bloom filter that migrates from an old filter to a new one while both are in use, e.g., to grow
+/// the capacity of a filter online.
+///
+/// New elements are only inserted into the new filter, and *contains* checks both filters until
+/// the old filter is retired. Retire the old filter with *retire_old* once the elements it
+/// contains are no longer needed, or automatically after a time-to-live (see *with_ttl*), e.g.,
+/// the time after which all elements of interest have been inserted again. Old and new filter may
+/// be of different types and configurations, so the elements of the old filter are never copied.
+///
+/// Both filters contribute to the false positive probability while the old filter is in use. Use
+/// *contains_and_migrate* to copy elements that are still queried into the new filter.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,MigratingBloomFilter,StableBloomFilterDefault};
+///
+/// fn main() {
+///     let mut old = StableBloomFilterDefault::new(1_000, 0.001);
+///     old.insert(&"Hello!");
+///
+///     // The old filter is full, so continue with a larger one
+///     let mut filter = MigratingBloomFilter::new(old, StableBloomFilterDefault::new(10_000, 0.001));
+///     filter.insert(&"World!");
+///     assert_eq!(true, filter.contains(&"Hello!"));
+///     assert_eq!(true, filter.contains(&"World!"));
+///
+///     // Elements that have only been inserted into the old filter are dropped with it
+///     filter.retire_old();
+///     assert_eq!(false, filter.contains(&"Hello!"));
+///     assert_eq!(true, filter.contains(&"World!"));
+/// }
+/// ```
+pub struct MigratingBloomFilter<Old, New>
+where
+    Old: BloomFilter,
+    New: BloomFilter,
+{
+    old: Option<Old>,
+    new: New,
+    #[cfg(feature = "std")]
+    retirement: Option<Instant>,
+}
+
+impl<Old, New> MigratingBloomFilter<Old, New>
+where
+    Old: BloomFilter,
+    New: BloomFilter,
+{
+    /// Initialize a new instance of MigratingBloomFilter that answers queries from *old* and *new*,
+    /// and inserts new elements into *new*.
+    pub fn new(old: Old, new: New) -> Self {
+        Self {
+            old: Some(old),
+            new,
+            #[cfg(feature = "std")]
+            retirement: None,
+        }
+    }
+
+    /// Retire the old filter automatically once *ttl* has passed, starting now.
+    ///
+    /// The old filter is not queried after *ttl* has passed, even if *insert* has not been called
+    /// since then to drop it.
+    ///
+    /// Only available with the *std* feature.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,MigratingBloomFilter,StableBloomFilterDefault};
+    /// use std::time::Duration;
+    ///
+    /// // Every element of interest is inserted again within a day
+    /// let mut filter = MigratingBloomFilter::new(
+    ///     StableBloomFilterDefault::new(1_000, 0.001),
+    ///     StableBloomFilterDefault::new(10_000, 0.001),
+    /// )
+    /// .with_ttl(Duration::from_secs(24 * 60 * 60));
+    ///
+    /// filter.insert(&"Hello!");
+    /// assert_eq!(true, filter.is_migrating());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.retirement = Some(Instant::now() + ttl);
+        self
+    }
+
+    /// Whether the old filter is still queried, i.e., it has neither been retired nor has its
+    /// time-to-live passed.
+    pub fn is_migrating(&self) -> bool {
+        self.old_filter().is_some()
+    }
+
+    /// The old filter, or None if it is no longer queried.
+    pub fn old_filter(&self) -> Option<&Old> {
+        if self.is_retirement_due() {
+            None
+        } else {
+            self.old.as_ref()
+        }
+    }
+
+    /// The new filter, which all new elements are inserted into.
+    pub fn new_filter(&self) -> &New {
+        &self.new
+    }
+
+    /// Drop the old filter, and return it if it has still been in use.
+    pub fn retire_old(&mut self) -> Option<Old> {
+        let due = self.is_retirement_due();
+        #[cfg(feature = "std")]
+        {
+            self.retirement = None;
+        }
+        let old = self.old.take();
+        if due {
+            None
+        } else {
+            old
+        }
+    }
+
+    /// Drop the old filter if its time-to-live has passed, and return whether it has been dropped.
+    ///
+    /// The old filter is never queried after its time-to-live, even if this method has not been
+    /// called. *insert* calls this method automatically to free the memory of the old filter.
+    #[cfg(feature = "std")]
+    pub fn retire_if_due(&mut self) -> bool {
+        if self.old.is_some() && self.is_retirement_due() {
+            self.retire_old();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Check whether *data* is contained, and insert it into the new filter if it is only
+    /// contained in the old filter, so that it is still contained after the old filter has been
+    /// retired.
+    ///
+    /// Elements that are false positives of the old filter are copied as well.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,MigratingBloomFilter,StableBloomFilterDefault};
+    ///
+    /// let mut old = StableBloomFilterDefault::new(1_000, 0.001);
+    /// old.insert(&"Hello!");
+    ///
+    /// let mut filter = MigratingBloomFilter::new(old, StableBloomFilterDefault::new(10_000, 0.001));
+    /// assert_eq!(true, filter.contains_and_migrate(&"Hello!"));
+    ///
+    /// filter.retire_old();
+    /// assert_eq!(true, filter.contains(&"Hello!"));
+    /// ```
+    pub fn contains_and_migrate<T>(&mut self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        if self.new.contains(data) {
+            return true;
+        }
+        let contained = self.old_filter().is_some_and(|old| old.contains(data));
+        if contained {
+            self.new.insert(data);
+        }
+        contained
+    }
+
+    /// Return the new filter, dropping the old one.
+    pub fn into_new_filter(self) -> New {
+        self.new
+    }
+
+    fn is_retirement_due(&self) -> bool {
+        #[cfg(feature = "std")]
+        {
+            self.retirement
+                .is_some_and(|retirement| Instant::now() >= retirement)
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            false
+        }
+    }
+}
+
+impl<Old, New> Debug for MigratingBloomFilter<Old, New>
+where
+    Old: BloomFilter + Debug,
+    New: BloomFilter + Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "MigratingBloomFilter{{old: {:?}, new: {:?}}}",
+            self.old_filter(),
+            self.new
+        )
+    }
+}
+
+impl<Old, New> BloomFilter for MigratingBloomFilter<Old, New>
+where
+    Old: BloomFilter,
+    New: BloomFilter,
+{
+    /// Insert data into the new filter.
+    fn insert<T>(&mut self, data: &T)
+    where
+        T: Hash,
+    {
+        #[cfg(feature = "std")]
+        self.retire_if_due();
+        self.new.insert(data);
+    }
+
+    /// Check whether data is contained in the new filter, or in the old filter if it is still in
+    /// use.
+    fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        self.new.contains(data) || self.old_filter().is_some_and(|old| old.contains(data))
+    }
+
+    fn insert_all<T, I>(&mut self, iter: I)
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        #[cfg(feature = "std")]
+        self.retire_if_due();
+        self.new.insert_all(iter);
+    }
+}
+
+impl<T, Old, New> Extend<T> for MigratingBloomFilter<Old, New>
+where
+    T: Hash,
+    Old: BloomFilter,
+    New: BloomFilter,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.insert_all(iter);
+    }
+}
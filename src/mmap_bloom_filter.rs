@@ -0,0 +1,202 @@
+use std::{
+    fmt::{self, Debug},
+    fs::File,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    path::Path,
+};
+
+use memmap2::Mmap;
+
+use crate::{
+    file_format::{CHECKSUM_LENGTH, HEADER_LENGTH},
+    BloomFilterView, HashedKey, ReadFilterError,
+};
+
+/// Read-only KMBloomFilter that answers queries directly from a memory-mapped file written by
+/// *KMBloomFilter::write_to*.
+///
+/// The bits are not loaded into memory. Instead, the operating system loads the pages of the file
+/// on demand when they are accessed by *contains*, and the pages are shared
+/// between all processes that map the same file. This makes it possible to query filters that are
+/// larger than the available memory, and to share a single copy of a large filter between many
+/// processes.
+///
+/// The hashers *H1* and *H2* must be the same hashers that were used to create the filter, and
+/// their hash values must not change between processes (e.g., the hashers of
+/// StableBloomFilterDefault). Otherwise, *open* fails with *ReadFilterError::HasherMismatch*.
+///
+/// An MmapBloomFilter answers queries like a BloomFilterView over the whole file (see *view*).
+///
+/// MmapBloomFilter is only available with the *mmap* feature.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,MmapBloomFilter,StableBloomFilterDefault,StableXxh3Hasher,StableXxh64Hasher};
+/// use std::fs::File;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let path = std::env::temp_dir().join("mmap_bloom_filter_doc_example.bin");
+///
+///     let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+///     filter.insert(&"Hello!");
+///     filter.write_to(File::create(&path)?)?;
+///
+///     let filter: MmapBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+///         MmapBloomFilter::open(&path)?;
+///     assert_eq!(true, filter.contains(&"Hello!"));
+///     assert_eq!(false, filter.contains(&"Goodbye!"));
+///     Ok(())
+/// }
+/// ```
+pub struct MmapBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    number_of_hashers: usize,
+    bits_per_hasher: usize,
+    mmap: Mmap,
+    // Phantom data for saving which concrete Hasher types are used
+    _phantom: PhantomData<fn() -> (H1, H2)>,
+}
+
+impl<H1, H2> MmapBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Open the filter stored in the file at *path*.
+    ///
+    /// Opening the filter reads the whole file once to verify its checksum. Afterwards, the pages
+    /// of the file are only loaded again when they are accessed.
+    ///
+    /// The file must not be modified while the filter is open. Modifying it, e.g., by writing a new
+    /// version of the filter to the same path instead of replacing the file, results in undefined
+    /// behavior.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file does not contain a filter in the format of
+    /// *KMBloomFilter::write_to*, if the filter has been written with different hashers, or if its
+    /// checksum does not match. Errors of opening and mapping the file are forwarded as
+    /// *ReadFilterError::Io*.
+    pub fn open<P>(path: P) -> Result<Self, ReadFilterError>
+    where
+        P: AsRef<Path>,
+    {
+        let file = File::open(path)?;
+        // Safety: the caller guarantees that the file is not modified while it is mapped
+        let mmap = unsafe { Mmap::map(&file)? };
+        let (number_of_hashers, bits_per_hasher) =
+            BloomFilterView::<H1, H2>::read_from(&mmap)?.configuration();
+        Ok(Self {
+            number_of_hashers,
+            bits_per_hasher,
+            mmap,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// A view of the filter that borrows the mapped bytes, e.g., to pass the filter to code that
+    /// queries views.
+    pub fn view(&self) -> BloomFilterView<'_, H1, H2> {
+        BloomFilterView::new(
+            &self.mmap[HEADER_LENGTH..self.mmap.len() - CHECKSUM_LENGTH],
+            self.number_of_hashers,
+            self.bits_per_hasher,
+        )
+    }
+
+    /// Check whether data is contained in the bloom filter.
+    ///
+    /// See *BloomFilter::contains* for details.
+    pub fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        self.view().contains(data)
+    }
+
+    /// Check whether the element *key* has been computed for is contained in the bloom filter.
+    ///
+    /// See *KMBloomFilter::hash* for how to compute a key.
+    pub fn contains_hashed(&self, key: &HashedKey<H1, H2>) -> bool {
+        self.view().contains_hashed(key)
+    }
+
+    /// Check whether the raw bytes *bytes* are contained in the bloom filter.
+    ///
+    /// See *KMBloomFilter::insert_bytes* for details.
+    pub fn contains_bytes(&self, bytes: &[u8]) -> bool {
+        self.view().contains_bytes(bytes)
+    }
+
+    /// Check whether *text* is contained in the bloom filter.
+    ///
+    /// See *KMBloomFilter::insert_str* for details.
+    pub fn contains_str(&self, text: &str) -> bool {
+        self.view().contains_str(text)
+    }
+
+    /// Check whether an element with the base hashes *hash_a* and *hash_b* is contained in the
+    /// bloom filter.
+    ///
+    /// See *KMBloomFilter::insert_hash64* for details.
+    pub fn contains_hash64(&self, hash_a: u64, hash_b: u64) -> bool {
+        self.view().contains_hash64(hash_a, hash_b)
+    }
+
+    /// Approximate number of elements stored.
+    ///
+    /// In contrast to *contains*, this reads the whole file.
+    pub fn approximate_element_count(&self) -> f64 {
+        self.view().approximate_element_count()
+    }
+
+    /// Return the current approximate false positive probability which depends on the current
+    /// number of elements in the filter.
+    ///
+    /// In contrast to *contains*, this reads the whole file.
+    pub fn approximate_current_false_positive_probability(&self) -> f64 {
+        self.view().approximate_current_false_positive_probability()
+    }
+
+    /// Fraction of bits that are set, given as a value in the interval [0,1].
+    ///
+    /// In contrast to *contains*, this reads the whole file.
+    pub fn fill_ratio(&self) -> f64 {
+        self.view().fill_ratio()
+    }
+
+    /// Total number of bits of the filter.
+    pub fn bit_count(&self) -> usize {
+        self.number_of_hashers * self.bits_per_hasher
+    }
+
+    /// Number of hash functions used for every element.
+    pub fn hasher_count(&self) -> usize {
+        self.number_of_hashers
+    }
+
+    /// Whether the fill ratio has reached *threshold*.
+    ///
+    /// In contrast to *contains*, this reads the whole file.
+    pub fn is_saturated(&self, threshold: f64) -> bool {
+        self.view().is_saturated(threshold)
+    }
+}
+
+impl<H1, H2> Debug for MmapBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "MmapBloomFilter{{number_of_hashers: {}, bits_per_hasher: {}}}",
+            self.number_of_hashers, self.bits_per_hasher
+        )
+    }
+}
@@ -0,0 +1,119 @@
+use alloc::{vec, vec::Vec};
+
+/// Array of unsigned integers with a fixed width of 1 to 32 bits, packed densely into 64-bit words.
+#[derive(PartialEq, Eq)]
+pub(crate) struct PackedArray {
+    words: Vec<u64>,
+    bits: u32,
+    length: usize,
+}
+
+impl PackedArray {
+    pub fn new(length: usize, bits: u32) -> Self {
+        if bits == 0 || bits > 32 {
+            panic!("unsupported width of packed values: {} bits", bits);
+        }
+        Self {
+            words: vec![0; (length * bits as usize).div_ceil(64)],
+            bits,
+            length,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    fn mask(&self) -> u64 {
+        (1u64 << self.bits) - 1
+    }
+
+    pub fn get(&self, index: usize) -> u32 {
+        if index >= self.len() {
+            panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                self.len(),
+                index,
+            )
+        }
+        let bit_index = index * self.bits as usize;
+        let word_index = bit_index / 64;
+        let offset = (bit_index % 64) as u32;
+        let mut value = self.words[word_index] >> offset;
+        if offset + self.bits > 64 {
+            value |= self.words[word_index + 1] << (64 - offset);
+        }
+        (value & self.mask()) as u32
+    }
+
+    pub fn set(&mut self, index: usize, value: u32) {
+        if index >= self.len() {
+            panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                self.len(),
+                index,
+            )
+        }
+        let mask = self.mask();
+        let value = value as u64 & mask;
+        let bit_index = index * self.bits as usize;
+        let word_index = bit_index / 64;
+        let offset = (bit_index % 64) as u32;
+        self.words[word_index] = (self.words[word_index] & !(mask << offset)) | (value << offset);
+        if offset + self.bits > 64 {
+            let shift = 64 - offset;
+            self.words[word_index + 1] =
+                (self.words[word_index + 1] & !(mask >> shift)) | (value >> shift);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_spanning_two_words() {
+        let mut array = PackedArray::new(20, 7);
+        for i in 0..20 {
+            array.set(i, (i as u32 * 13) % 128);
+        }
+        for i in 0..20 {
+            assert_eq!((i as u32 * 13) % 128, array.get(i));
+        }
+        assert_eq!(3, array.words.len());
+    }
+
+    #[test]
+    fn values_are_truncated_to_width() {
+        let mut array = PackedArray::new(3, 4);
+        array.set(1, 0xFF);
+        assert_eq!(0, array.get(0));
+        assert_eq!(0x0F, array.get(1));
+        assert_eq!(0, array.get(2));
+    }
+
+    #[test]
+    fn full_width_values() {
+        let mut array = PackedArray::new(5, 32);
+        array.set(0, u32::MAX);
+        array.set(3, 0xDEAD_BEEF);
+        assert_eq!(u32::MAX, array.get(0));
+        assert_eq!(0, array.get(1));
+        assert_eq!(0xDEAD_BEEF, array.get(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn must_get_with_correct_index() {
+        PackedArray::new(4, 9).get(4);
+    }
+}
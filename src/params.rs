@@ -0,0 +1,762 @@
+//! Formulas for the parameters of bloom filters.
+//!
+//! The filters of this crate derive their number of bits and hashers from a desired capacity and
+//! a desired false positive probability with these formulas. They can be used to calculate the
+//! size of a filter before creating it.
+//!
+//! # Examples
+//! ```
+//! use bloom_filter_simple::params::FilterParams;
+//!
+//! let params = FilterParams::for_capacity(1_000_000, 0.001);
+//! assert_eq!(10, params.number_of_hashers());
+//! assert!(params.byte_count() < 2 * 1024 * 1024);
+//! // Inserting more elements than the desired capacity increases the false positive probability
+//! assert!(params.false_positive_probability_at(2_000_000.0) > 0.01);
+//! ```
+
+use core::{
+    convert::TryFrom,
+    fmt::{self, Display},
+};
+
+use crate::math;
+
+/// False positive probability used by the constructors that only take a desired capacity, e.g.,
+/// *KMBloomFilter::with_capacity*.
+pub const DEFAULT_FALSE_POSITIVE_PROBABILITY: f64 = 0.01;
+
+/// Number of standard deviations of the number of ones that the bounds cover, which corresponds to
+/// a confidence of about 95%.
+const CONFIDENCE_DEVIATIONS: f64 = 1.96;
+
+/// Parameters of a bloom filter that guarantees that the false positive rate is less than
+/// *desired_false_positive_probability* for up to *desired_capacity* elements.
+///
+/// KMBloomFilter, SeededBloomFilter, and CountingBloomFilter use these parameters for the same
+/// desired capacity and desired false positive probability. A CountingBloomFilter stores a counter
+/// instead of a bit, i.e., it needs *bit_count* counters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FilterParams {
+    desired_capacity: usize,
+    desired_false_positive_probability: f64,
+    number_of_hashers: usize,
+    bits_per_hasher: usize,
+    formula: Formula,
+}
+
+/// Formula for the false positive probability that the parameters of a bloom filter are
+/// calculated with (see *FilterParams::for_capacity_with*).
+///
+/// The filters of this crate use a partitioned layout: every hasher sets one of its own
+/// *bits_per_hasher* bits. In a classic, unpartitioned filter, every hasher sets one of all bits.
+/// Both have the same false positive probability to a first approximation, which the classic
+/// formulas are based on. The exact probability of a partitioned filter is slightly higher, and
+/// the classic formulas round the optimal number of hashers, so filters with classic parameters
+/// can exceed the desired false positive probability slightly at their desired capacity.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::params::{FilterParams,Formula};
+///
+/// let classic = FilterParams::for_capacity(1_000, 0.01);
+/// assert!(classic.false_positive_probability_at(1_000.0) > 0.01);
+///
+/// let partitioned = FilterParams::for_capacity_with(1_000, 0.01, Formula::Partitioned);
+/// assert!(partitioned.false_positive_probability_at(1_000.0) <= 0.01);
+/// assert!(partitioned.bit_count() > classic.bit_count());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Formula {
+    /// The approximations of *optimal_bit_count*, *optimal_number_of_hashers*, and
+    /// *approximate_false_positive_probability*, which the constructors of the filters use.
+    #[default]
+    Classic,
+    /// The exact false positive probability of a partitioned filter, such as KMBloomFilter,
+    /// SeededBloomFilter, and CountingBloomFilter: *(1 - (1 - 1/m)^n)^k* for *k* hashers with
+    /// *m* bits each and *n* elements.
+    Partitioned,
+    /// The exact false positive probability of an unpartitioned filter with *b* bits, e.g., to
+    /// size a filter with the layout *IndexLayout::Global* or a filter of another library:
+    /// *(1 - (1 - 1/b)^(kn))^k*.
+    Unpartitioned,
+}
+
+impl FilterParams {
+    /// Calculate the parameters of a bloom filter for *desired_capacity* elements with a false
+    /// positive probability of less than *desired_false_positive_probability*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0, if desired_false_positive_probability is not in the
+    /// interval (0,1), or if the number of bits does not fit into usize (see
+    /// *try_for_capacity*).
+    pub fn for_capacity(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self {
+        match Self::try_for_capacity(desired_capacity, desired_false_positive_probability) {
+            Ok(params) => params,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Calculate the parameters like *for_capacity*, but return an error instead of panicking for
+    /// invalid arguments, e.g., arguments that are passed in from a configuration file.
+    ///
+    /// # Errors
+    ///
+    /// Returns *InvalidFilterParams::EmptyCapacity* if desired_capacity == 0,
+    /// *InvalidFilterParams::FalsePositiveProbability* if desired_false_positive_probability is
+    /// not in the interval (0,1), e.g., if it is NaN, and *InvalidFilterParams::BitCountOverflow*
+    /// if the number of bits does not fit into usize.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{params::FilterParams,InvalidFilterParams};
+    ///
+    /// assert!(FilterParams::try_for_capacity(1_000, 0.01).is_ok());
+    /// assert_eq!(
+    ///     Err(InvalidFilterParams::EmptyCapacity),
+    ///     FilterParams::try_for_capacity(0, 0.01)
+    /// );
+    /// assert!(matches!(
+    ///     FilterParams::try_for_capacity(usize::MAX, 1e-9),
+    ///     Err(InvalidFilterParams::BitCountOverflow { .. })
+    /// ));
+    /// ```
+    pub fn try_for_capacity(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Result<Self, InvalidFilterParams> {
+        if desired_capacity == 0 {
+            return Err(InvalidFilterParams::EmptyCapacity);
+        }
+        let desired_false_positive_probability =
+            FalsePositiveProbability::new(desired_false_positive_probability.into())?.get();
+        let overflow = InvalidFilterParams::BitCountOverflow {
+            desired_capacity,
+            desired_false_positive_probability,
+        };
+        let bit_count = optimal_bit_count(desired_capacity, desired_false_positive_probability);
+        // The conversion of the bit count to usize saturates at usize::MAX
+        if bit_count == usize::MAX {
+            return Err(overflow);
+        }
+        let number_of_hashers = optimal_number_of_hashers(desired_capacity, bit_count);
+        let bits_per_hasher = bit_count.div_ceil(number_of_hashers);
+        if number_of_hashers.checked_mul(bits_per_hasher).is_none() {
+            return Err(overflow);
+        }
+        Ok(Self {
+            desired_capacity,
+            desired_false_positive_probability,
+            number_of_hashers,
+            bits_per_hasher,
+            formula: Formula::Classic,
+        })
+    }
+
+    /// Calculate the parameters of a bloom filter for *desired_capacity* elements with the
+    /// false positive probability of *formula*.
+    ///
+    /// With *Formula::Classic*, this is *for_capacity*. The exact formulas guarantee that
+    /// *false_positive_probability_at* the desired capacity does not exceed
+    /// *desired_false_positive_probability*. They try the number of hashers of the classic
+    /// formulas and its neighbors, and choose the one that needs the fewest bits. For the
+    /// filters of this crate, use *Formula::Partitioned* and create the filter with
+    /// *with_parameters*, e.g., *KMBloomFilter::with_parameters(params.bit_count(),
+    /// params.number_of_hashers())*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0 or if desired_false_positive_probability is not in the
+    /// interval (0,1).
+    pub fn for_capacity_with(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+        formula: Formula,
+    ) -> Self {
+        let classic = Self::for_capacity(desired_capacity, desired_false_positive_probability);
+        if formula == Formula::Classic {
+            return classic;
+        }
+        let probability = classic.desired_false_positive_probability;
+        let around = classic.number_of_hashers.max(1);
+        let (number_of_hashers, bits_per_hasher) = (around.saturating_sub(1).max(1)..=around + 1)
+            .map(|number_of_hashers| {
+                let bits_per_hasher = exact_bits_per_hasher(
+                    desired_capacity,
+                    probability,
+                    number_of_hashers,
+                    formula,
+                );
+                (number_of_hashers, bits_per_hasher)
+            })
+            .min_by_key(|&(number_of_hashers, bits_per_hasher)| number_of_hashers * bits_per_hasher)
+            .unwrap();
+        Self {
+            number_of_hashers,
+            bits_per_hasher,
+            formula,
+            ..classic
+        }
+    }
+
+    /// The desired capacity the parameters have been calculated for.
+    pub fn desired_capacity(&self) -> usize {
+        self.desired_capacity
+    }
+
+    /// The desired false positive probability the parameters have been calculated for.
+    pub fn desired_false_positive_probability(&self) -> f64 {
+        self.desired_false_positive_probability
+    }
+
+    /// Number of hash functions used for every element.
+    pub fn number_of_hashers(&self) -> usize {
+        self.number_of_hashers
+    }
+
+    /// Number of bits in the range of each hash function.
+    pub fn bits_per_hasher(&self) -> usize {
+        self.bits_per_hasher
+    }
+
+    /// Total number of bits of the filter.
+    ///
+    /// The number is slightly larger than *optimal_bit_count*, because every hash function gets
+    /// the same number of bits.
+    pub fn bit_count(&self) -> usize {
+        self.number_of_hashers * self.bits_per_hasher
+    }
+
+    /// Number of bytes a KMBloomFilter or SeededBloomFilter needs to store its bits.
+    pub fn byte_count(&self) -> usize {
+        self.bit_count().div_ceil(64) * 8
+    }
+
+    /// The formula the parameters have been calculated with.
+    pub fn formula(&self) -> Formula {
+        self.formula
+    }
+
+    /// False positive probability after *element_count* elements have been inserted, calculated
+    /// with the formula of the parameters.
+    pub fn false_positive_probability_at(&self, element_count: f64) -> f64 {
+        let k = self.number_of_hashers as f64;
+        match self.formula {
+            Formula::Classic => approximate_false_positive_probability(
+                self.number_of_hashers,
+                self.bits_per_hasher,
+                element_count,
+            ),
+            Formula::Partitioned => math::powf(
+                1.0 - math::powf(1.0 - 1.0 / self.bits_per_hasher as f64, element_count),
+                k,
+            ),
+            Formula::Unpartitioned => math::powf(
+                1.0 - math::powf(1.0 - 1.0 / self.bit_count() as f64, k * element_count),
+                k,
+            ),
+        }
+    }
+}
+
+/// Smallest number of bits per hasher for which the exact false positive probability of *formula*
+/// at *desired_capacity* elements does not exceed *desired_false_positive_probability*.
+///
+/// Solves *(1 - (1 - 1/m)^(n')) ^ k = p* for *m*, where *n'* is the number of bits set among *m*
+/// bits: the *n* elements of one hasher in a partitioned filter, or the *kn* bits of all hashers in
+/// an unpartitioned one. An unpartitioned filter needs *k* times the resulting bits in total.
+fn exact_bits_per_hasher(
+    desired_capacity: usize,
+    desired_false_positive_probability: f64,
+    number_of_hashers: usize,
+    formula: Formula,
+) -> usize {
+    let fill_ratio = math::powf(
+        desired_false_positive_probability,
+        1.0 / number_of_hashers as f64,
+    );
+    let bits_set = match formula {
+        Formula::Unpartitioned => (number_of_hashers * desired_capacity) as f64,
+        _ => desired_capacity as f64,
+    };
+    // (1 - 1/m)^(n') = 1 - fill_ratio, so 1/m = 1 - e^x with x = ln(1 - fill_ratio) / n'. x is
+    // tiny for large filters, where 1 - e^x loses precision, so its Taylor series is used instead.
+    let x = math::ln(1.0 - fill_ratio) / bits_set;
+    let one_probability = if x > -1e-4 {
+        -x * (1.0 + x / 2.0 + x * x / 6.0)
+    } else {
+        1.0 - math::exp(x)
+    };
+    let bits = math::ceil(1.0 / one_probability).max(1.0) as usize;
+    match formula {
+        Formula::Unpartitioned => bits.div_ceil(number_of_hashers),
+        _ => bits,
+    }
+}
+
+/// A false positive probability that is guaranteed to be in the interval (0,1).
+///
+/// All constructors that take a desired false positive probability accept either an *f64*, which
+/// makes them panic if it is not in (0,1), or a FalsePositiveProbability, which has been validated
+/// before. Validating the probability where it is read, e.g., from a configuration file, catches
+/// invalid values and swapped arguments early.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{FalsePositiveProbability,StableBloomFilterDefault};
+///
+/// let probability = FalsePositiveProbability::new(0.001).unwrap();
+/// let filter = StableBloomFilterDefault::new(1_000, probability);
+/// assert_eq!(10, filter.hasher_count());
+///
+/// // A capacity in place of the probability is rejected
+/// assert!(FalsePositiveProbability::new(1_000.0).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct FalsePositiveProbability(f64);
+
+impl FalsePositiveProbability {
+    /// Validate that *probability* is in the interval (0,1).
+    pub fn new(probability: f64) -> Result<Self, InvalidFalsePositiveProbability> {
+        if probability > 0.0 && probability < 1.0 {
+            Ok(Self(probability))
+        } else {
+            Err(InvalidFalsePositiveProbability(probability))
+        }
+    }
+
+    /// The probability as a value in the interval (0,1).
+    pub fn get(self) -> f64 {
+        self.0
+    }
+}
+
+/// The default false positive probability of 1%, see *DEFAULT_FALSE_POSITIVE_PROBABILITY*.
+impl Default for FalsePositiveProbability {
+    fn default() -> Self {
+        Self(DEFAULT_FALSE_POSITIVE_PROBABILITY)
+    }
+}
+
+impl TryFrom<f64> for FalsePositiveProbability {
+    type Error = InvalidFalsePositiveProbability;
+
+    fn try_from(probability: f64) -> Result<Self, Self::Error> {
+        Self::new(probability)
+    }
+}
+
+impl From<FalsePositiveProbability> for f64 {
+    fn from(probability: FalsePositiveProbability) -> Self {
+        probability.0
+    }
+}
+
+impl Display for FalsePositiveProbability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Error returned by *FalsePositiveProbability::new* for a probability that is not in the interval
+/// (0,1). It contains the rejected probability.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidFalsePositiveProbability(pub f64);
+
+impl Display for InvalidFalsePositiveProbability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unsupported false positive probability of {}, it must be in (0, 1)",
+            self.0
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidFalsePositiveProbability {}
+
+/// Error returned by *FilterParams::try_for_capacity* and the *try_new* constructors of the filters
+/// for arguments no filter can be created for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InvalidFilterParams {
+    /// The desired capacity is zero.
+    EmptyCapacity,
+    /// The desired false positive probability is not in the interval (0,1).
+    FalsePositiveProbability(InvalidFalsePositiveProbability),
+    /// The number of bits for the desired capacity and false positive probability does not fit
+    /// into usize.
+    BitCountOverflow {
+        desired_capacity: usize,
+        desired_false_positive_probability: f64,
+    },
+}
+
+impl Display for InvalidFilterParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidFilterParams::EmptyCapacity => write!(f, "an empty bloom filter is not defined"),
+            InvalidFilterParams::FalsePositiveProbability(error) => error.fmt(f),
+            InvalidFilterParams::BitCountOverflow {
+                desired_capacity,
+                desired_false_positive_probability,
+            } => write!(
+                f,
+                "the number of bits of a bloom filter for {} elements with a false positive \
+                 probability of {} overflows usize",
+                desired_capacity, desired_false_positive_probability
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidFilterParams {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InvalidFilterParams::FalsePositiveProbability(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<InvalidFalsePositiveProbability> for InvalidFilterParams {
+    fn from(error: InvalidFalsePositiveProbability) -> Self {
+        InvalidFilterParams::FalsePositiveProbability(error)
+    }
+}
+
+/// Convert *probability* to an *f64* and panic if it is not in the interval (0,1).
+pub(crate) fn checked_false_positive_probability(probability: impl Into<f64>) -> f64 {
+    match FalsePositiveProbability::new(probability.into()) {
+        Ok(probability) => probability.get(),
+        Err(error) => panic!("{}", error),
+    }
+}
+
+/// Calculate the optimal bit count to satisfy the desired constraints.
+/// Formula taken from Sagi Kedmi:
+/// > S. Kedmi, ["Bloom Filters for the Perplexed"](https://sagi.io/bloom-filters-for-the-perplexed/), July 2017 [Accessed: 02.12.2020]
+pub fn optimal_bit_count(
+    desired_capacity: usize,
+    desired_false_positive_probability: f64,
+) -> usize {
+    math::ceil(
+        -(desired_capacity as f64 * math::ln(desired_false_positive_probability))
+            / (math::ln(2.0) * math::ln(2.0)),
+    ) as usize
+}
+
+/// Calculate the optimal number of hashers to satisfy the desired constraints.
+/// Formula taken from Sagi Kedmi:
+/// > S. Kedmi, ["Bloom Filters for the Perplexed"](https://sagi.io/bloom-filters-for-the-perplexed/), July 2017 [Accessed: 02.12.2020]
+///
+/// The result is at least one, e.g., for desired false positive probabilities above 0.7, where
+/// the optimum rounds to zero.
+pub fn optimal_number_of_hashers(desired_capacity: usize, bit_count: usize) -> usize {
+    let number_of_hashers =
+        math::round((bit_count as f64 / desired_capacity as f64) * math::ln(2.0)) as usize;
+    number_of_hashers.max(1)
+}
+
+/// Number of fractional bits of the fixed-point numbers of the const parameter functions.
+const FRACTION_BITS: u32 = 48;
+
+/// ln(2) with *FRACTION_BITS* fractional bits, rounded down.
+const LN_2: u128 = 195_103_586_505_167;
+
+/// Calculate *optimal_bit_count* at compile time for a desired false positive probability of
+/// *1 / one_in*, e.g., 1,000 for 0.001.
+///
+/// The logarithms are computed in fixed-point arithmetic and rounded up, so the bit count is
+/// never less than the one of *optimal_bit_count*, and at most one bit more.
+///
+/// # Panics
+///
+/// Panics if desired_capacity == 0 or one_in < 2. In a const context, this is a compile error.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{params,BloomFilter,StableBloomFilterDefault};
+///
+/// const BITS: usize = params::optimal_bit_count_const(10_000, 1_000);
+/// const HASHERS: usize = params::optimal_number_of_hashers_const(10_000, BITS);
+///
+/// // E.g., the size of a static buffer for the bytes of the filter
+/// static BUFFER: [u8; BITS.div_ceil(8)] = [0; BITS.div_ceil(8)];
+/// let mut filter = StableBloomFilterDefault::with_parameters(BITS, HASHERS);
+/// filter.insert(&1);
+/// assert_eq!(10, HASHERS);
+/// assert!(filter.bit_count() >= BITS);
+/// assert!(BUFFER.len() * 8 >= BITS);
+/// ```
+pub const fn optimal_bit_count_const(desired_capacity: usize, one_in: u64) -> usize {
+    if desired_capacity == 0 {
+        panic!("an empty bloom filter is not defined");
+    }
+    if one_in < 2 {
+        panic!("unsupported false positive probability, it must be in (0, 1)");
+    }
+    // -n * ln(p) / ln(2)^2 = n * log2(one_in) / ln(2)
+    let numerator = desired_capacity as u128 * log2_fixed_ceil(one_in);
+    numerator.div_ceil(LN_2) as usize
+}
+
+/// Calculate *optimal_number_of_hashers* at compile time.
+///
+/// The result may differ from *optimal_number_of_hashers* if *bit_count / desired_capacity *
+/// ln(2)* is within about 1e-14 of a half, where both round to nearest.
+///
+/// # Panics
+///
+/// Panics if desired_capacity == 0. In a const context, this is a compile error.
+pub const fn optimal_number_of_hashers_const(desired_capacity: usize, bit_count: usize) -> usize {
+    if desired_capacity == 0 {
+        panic!("an empty bloom filter is not defined");
+    }
+    let denominator = (desired_capacity as u128) << FRACTION_BITS;
+    let number_of_hashers = ((bit_count as u128 * LN_2 + denominator / 2) / denominator) as usize;
+    if number_of_hashers == 0 {
+        1
+    } else {
+        number_of_hashers
+    }
+}
+
+/// log2(x) for x >= 1 with *FRACTION_BITS* fractional bits, rounded up.
+///
+/// The fractional bits are computed by squaring the mantissa: every squaring doubles the
+/// logarithm, and its integer part is the next bit.
+const fn log2_fixed_ceil(x: u64) -> u128 {
+    // Mantissa in [1, 2) with 62 fractional bits
+    const ONE: u128 = 1 << 62;
+    let integer = 63 - x.leading_zeros();
+    let mut mantissa = (x as u128) << (62 - integer);
+    let mut log = (integer as u128) << FRACTION_BITS;
+    let mut bit = 1u128 << FRACTION_BITS;
+    while bit > 1 {
+        bit >>= 1;
+        mantissa = (mantissa * mantissa) >> 62;
+        if mantissa >= 2 * ONE {
+            mantissa >>= 1;
+            log |= bit;
+        }
+    }
+    // Truncating the squares rounds down, which is covered by a margin of a few units
+    log + 4
+}
+
+/// Calculate the smallest bit count that satisfies the desired constraints if every element is
+/// hashed with exactly *number_of_hashers* hash functions, e.g., to trade a higher bit count for
+/// fewer probes per lookup than *optimal_number_of_hashers*.
+///
+/// The bit count is a multiple of *number_of_hashers*. Solves the formula of
+/// *approximate_false_positive_probability* for the number of bits per hasher.
+///
+/// # Panics
+///
+/// Panics if number_of_hashers == 0 or if desired_false_positive_probability is not in the
+/// interval (0,1).
+pub fn bit_count_for_hashers(
+    desired_capacity: usize,
+    desired_false_positive_probability: impl Into<f64>,
+    number_of_hashers: usize,
+) -> usize {
+    if number_of_hashers == 0 {
+        panic!("a bloom filter requires at least one hasher");
+    }
+    let desired_false_positive_probability =
+        checked_false_positive_probability(desired_false_positive_probability);
+    let fill_ratio = math::powf(
+        desired_false_positive_probability,
+        1.0 / number_of_hashers as f64,
+    );
+    let bits_per_hasher = math::ceil(-(desired_capacity as f64) / math::ln(1.0 - fill_ratio));
+    number_of_hashers * bits_per_hasher as usize
+}
+
+/// Number of bits per hasher of a filter with at least *bit_count* bits and *number_of_hashers*
+/// hashers.
+///
+/// # Panics
+///
+/// Panics if bit_count == 0, number_of_hashers == 0, or if rounding the bit count up to a multiple
+/// of *number_of_hashers* overflows usize.
+pub(crate) fn bits_per_hasher(bit_count: usize, number_of_hashers: usize) -> usize {
+    if bit_count == 0 {
+        panic!("an empty bloom filter is not defined");
+    }
+    if number_of_hashers == 0 {
+        panic!("a bloom filter requires at least one hasher");
+    }
+    let bits_per_hasher = bit_count.div_ceil(number_of_hashers);
+    checked_bit_count(number_of_hashers, bits_per_hasher);
+    bits_per_hasher
+}
+
+/// Number of bits of a filter with *number_of_hashers* hashers of *bits_per_hasher* bits each.
+///
+/// Every bit index of a filter is less than its number of bits, so checking once that the number
+/// of bits fits into usize guarantees that *KMBloomFilter::index* never overflows.
+///
+/// # Panics
+///
+/// Panics if the number of bits does not fit into usize.
+pub(crate) fn checked_bit_count(number_of_hashers: usize, bits_per_hasher: usize) -> usize {
+    match number_of_hashers.checked_mul(bits_per_hasher) {
+        Some(bit_count) => bit_count,
+        None => bit_count_overflow(number_of_hashers, bits_per_hasher),
+    }
+}
+
+/// Panic because a filter with *number_of_hashers* hashers of at least *bits_per_hasher* bits
+/// each would have more than usize::MAX bits.
+pub(crate) fn bit_count_overflow(number_of_hashers: usize, bits_per_hasher: usize) -> ! {
+    panic!(
+        "unable to create a bloom filter with {} hashers of at least {} bits each, which needs more than usize::MAX bits",
+        number_of_hashers, bits_per_hasher
+    )
+}
+
+/// Approximate number of elements stored.
+/// Formula taken from Wikipedia:
+/// > Wikipedia, ["Bloom filter"](https://en.wikipedia.org/wiki/Bloom_filter#Approximating_the_number_of_items_in_a_Bloom_filter) [Accessed: 02.12.2020]
+pub fn approximate_element_count(
+    number_of_hashers: usize,
+    bits_per_hasher: usize,
+    number_of_ones: usize,
+) -> f64 {
+    element_count_for_ones(number_of_hashers, bits_per_hasher, number_of_ones as f64)
+}
+
+/// Approximate number of elements stored in a filter in which every bit has additionally been set
+/// with probability *noise*, see *KMBloomFilter::add_noise*.
+///
+/// A bit is unset if neither the elements nor the noise have set it, so the fraction of unset bits
+/// is *(1 - noise)* times the fraction without noise, and the noise adds *-m ln(1 - noise)* to
+/// the count of *approximate_element_count*. The count is at least 0.
+pub fn approximate_element_count_with_noise(
+    number_of_hashers: usize,
+    bits_per_hasher: usize,
+    number_of_ones: usize,
+    noise: f64,
+) -> f64 {
+    (approximate_element_count(number_of_hashers, bits_per_hasher, number_of_ones)
+        + bits_per_hasher as f64 * math::ln(1.0 - noise))
+    .max(0.0)
+}
+
+/// Lower and upper bound of an approximate 95% confidence interval for the number of elements
+/// stored, which contains the value of *approximate_element_count*.
+///
+/// The bounds are the approximate element counts for the number of ones plus or minus 1.96
+/// standard deviations (see *ones_count_variance*), where the variance is evaluated at the
+/// approximate element count. The upper bound is infinite if the interval reaches a filter with
+/// all bits set.
+pub fn approximate_element_count_bounds(
+    number_of_hashers: usize,
+    bits_per_hasher: usize,
+    number_of_ones: usize,
+) -> (f64, f64) {
+    let element_count =
+        approximate_element_count(number_of_hashers, bits_per_hasher, number_of_ones);
+    let deviation = CONFIDENCE_DEVIATIONS
+        * math::sqrt(ones_count_variance(
+            number_of_hashers,
+            bits_per_hasher,
+            element_count,
+        ));
+    let bit_count = (number_of_hashers * bits_per_hasher) as f64;
+    let lower_ones = (number_of_ones as f64 - deviation).max(0.0);
+    let upper_ones = (number_of_ones as f64 + deviation).min(bit_count);
+    (
+        element_count_for_ones(number_of_hashers, bits_per_hasher, lower_ones),
+        element_count_for_ones(number_of_hashers, bits_per_hasher, upper_ones),
+    )
+}
+
+/// Variance of the number of ones after *element_count* elements have been inserted into a filter
+/// in which every hasher sets one of its own *bits_per_hasher* bits per element.
+///
+/// For each hasher, the number of zeros *Z* among its *m* bits after *n* insertions has the
+/// variance *Var(Z) = m(m-1)(1-2/m)^n + m(1-1/m)^n - m^2(1-1/m)^(2n)*. The hashers are treated as
+/// independent.
+pub fn ones_count_variance(
+    number_of_hashers: usize,
+    bits_per_hasher: usize,
+    element_count: f64,
+) -> f64 {
+    if bits_per_hasher < 2 {
+        return 0.0;
+    }
+    let m = bits_per_hasher as f64;
+    let one_empty = math::powf(1.0 - 1.0 / m, element_count);
+    let two_empty = math::powf(1.0 - 2.0 / m, element_count);
+    let variance = m * (m - 1.0) * two_empty + m * one_empty - m * m * one_empty * one_empty;
+    number_of_hashers as f64 * variance.max(0.0)
+}
+
+fn element_count_for_ones(number_of_hashers: usize, bits_per_hasher: usize, ones: f64) -> f64 {
+    -(bits_per_hasher as f64)
+        * math::ln(1.0 - ones / ((number_of_hashers * bits_per_hasher) as f64))
+}
+
+/// Approximate number of elements in the intersection of two filters from the approximate element
+/// counts of both filters and of their union.
+/// Formula taken from Swamidass and Baldi:
+/// > S. J. Swamidass, P. Baldi, "Mathematical correction for fingerprint similarity measures to improve chemical retrieval", Journal of Chemical Information and Modeling 47(3), 2007, 952–964. DOI: [https://doi.org/10.1021/ci600526a](https://doi.org/10.1021/ci600526a)
+pub fn approximate_intersection_count(count_a: f64, count_b: f64, count_union: f64) -> f64 {
+    (count_a + count_b - count_union).max(0.0)
+}
+
+/// Approximate Jaccard similarity *|A ∩ B| / |A ∪ B|* of two filters from the approximate element
+/// counts of both filters and of their union, given as a value in the interval [0,1].
+///
+/// The similarity of two empty filters is 1.
+pub fn approximate_jaccard_similarity(count_a: f64, count_b: f64, count_union: f64) -> f64 {
+    if count_union <= 0.0 {
+        return 1.0;
+    }
+    (approximate_intersection_count(count_a, count_b, count_union) / count_union).min(1.0)
+}
+
+/// Approximate number of elements of filter A that are not contained in filter B from the
+/// approximate element counts of B and of the union of both filters:
+/// *n(A \\ B) = n(A ∪ B) - n(B)*.
+pub fn approximate_difference_count(count_b: f64, count_union: f64) -> f64 {
+    (count_union - count_b).max(0.0)
+}
+
+/// Return the current approximate false positive probability which depends on the current
+/// number of elements in the filter.
+/// Formula taken from Sagi Kedmi:
+/// > S. Kedmi, ["Bloom Filters for the Perplexed"](https://sagi.io/bloom-filters-for-the-perplexed/), July 2017 [Accessed: 02.12.2020]
+pub fn approximate_false_positive_probability(
+    number_of_hashers: usize,
+    bits_per_hasher: usize,
+    element_count: f64,
+) -> f64 {
+    math::powf(
+        1.0 - math::exp(-element_count / bits_per_hasher as f64),
+        number_of_hashers as f64,
+    )
+}
+
+/// Return the approximate false positive probability of a filter with *element_count* elements
+/// in which every bit has additionally been set with probability *noise*, see
+/// *KMBloomFilter::add_noise*.
+pub fn approximate_false_positive_probability_with_noise(
+    number_of_hashers: usize,
+    bits_per_hasher: usize,
+    element_count: f64,
+    noise: f64,
+) -> f64 {
+    math::powf(
+        1.0 - (1.0 - noise) * math::exp(-element_count / bits_per_hasher as f64),
+        number_of_hashers as f64,
+    )
+}
@@ -0,0 +1,142 @@
+use core::{
+    borrow::Borrow,
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use crate::{math, BloomFilter, KMBloomFilter, StableXxh3Hasher, StableXxh64Hasher};
+
+/// Set of elements of type *T* that is backed by a KMBloomFilter, e.g., to give call sites a typed
+/// set API instead of a filter that accepts any hashable type.
+///
+/// A KMBloomFilter hashes whatever it is given, so a filter built for URLs happily answers queries
+/// for u64 keys, and the answers are meaningless. ProbabilisticSet only accepts *T* and the
+/// borrowed forms of *T*, so such mistakes are compile errors. Like the wrapped filter,
+/// *contains* may return false positives, but never false negatives.
+///
+/// The filter hashes with StableXxh3Hasher and StableXxh64Hasher by default, see
+/// StableBloomFilterDefault.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::ProbabilisticSet;
+///
+/// let mut visited: ProbabilisticSet<String> = ProbabilisticSet::new(1_000, 0.001);
+/// visited.insert(&"https://example.com/".to_string());
+///
+/// assert_eq!(true, visited.contains("https://example.com/"));
+/// assert_eq!(false, visited.contains("https://example.org/"));
+/// assert_eq!(1, visited.len_estimate());
+/// ```
+///
+/// Queries with another type do not compile:
+/// ```compile_fail
+/// use bloom_filter_simple::ProbabilisticSet;
+///
+/// let visited: ProbabilisticSet<String> = ProbabilisticSet::new(1_000, 0.001);
+/// visited.contains(&42u64);
+/// ```
+pub struct ProbabilisticSet<T, H1 = StableXxh3Hasher, H2 = StableXxh64Hasher>
+where
+    T: Hash + ?Sized,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    filter: KMBloomFilter<H1, H2>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T, H1, H2> ProbabilisticSet<T, H1, H2>
+where
+    T: Hash + ?Sized,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Initialize a new instance of ProbabilisticSet that guarantees that the false positive rate
+    /// is less than *desired_false_positive_probability* for up to *desired_capacity* elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0 or if desired_false_positive_probability is not in the
+    /// interval (0,1).
+    pub fn new(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self {
+        Self {
+            filter: KMBloomFilter::new(desired_capacity, desired_false_positive_probability),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Insert *data* into the set.
+    pub fn insert(&mut self, data: &T) {
+        self.filter.insert(&data);
+    }
+
+    /// Whether *data* might be contained in the set.
+    ///
+    /// *data* can be any borrowed form of the element type, e.g., `&str` for *String* elements,
+    /// because borrowed forms hash like the element itself.
+    pub fn contains<Q>(&self, data: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + ?Sized,
+    {
+        self.filter.contains(&data)
+    }
+
+    /// Approximate number of distinct elements in the set, see
+    /// *KMBloomFilter::approximate_element_count*.
+    pub fn len_estimate(&self) -> usize {
+        math::round(self.filter.approximate_element_count()) as usize
+    }
+
+    /// Current approximate false positive probability of *contains*, which depends on the current
+    /// number of elements in the set.
+    ///
+    /// The probability is given as a value in the interval [0,1]
+    pub fn fp_rate(&self) -> f64 {
+        self.filter.approximate_current_false_positive_probability()
+    }
+
+    /// Remove all elements.
+    pub fn clear(&mut self) {
+        self.filter.clear();
+    }
+
+    /// The filter that backs the set.
+    pub fn filter(&self) -> &KMBloomFilter<H1, H2> {
+        &self.filter
+    }
+
+    /// Return the filter that backs the set.
+    pub fn into_inner(self) -> KMBloomFilter<H1, H2> {
+        self.filter
+    }
+}
+
+impl<T, H1, H2> Debug for ProbabilisticSet<T, H1, H2>
+where
+    T: Hash + ?Sized,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ProbabilisticSet{{filter: {:?}}}", self.filter)
+    }
+}
+
+impl<'a, T, H1, H2> Extend<&'a T> for ProbabilisticSet<T, H1, H2>
+where
+    T: Hash + ?Sized + 'a,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        for data in iter {
+            self.insert(data);
+        }
+    }
+}
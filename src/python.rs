@@ -0,0 +1,250 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::PathBuf,
+};
+
+use pyo3::{
+    exceptions::PyValueError,
+    prelude::*,
+    pybacked::{PyBackedBytes, PyBackedStr},
+    types::PyBytes,
+};
+
+use crate::{
+    params::DEFAULT_FALSE_POSITIVE_PROBABILITY, BloomFilter, DefaultBloomFilter, KMBloomFilter,
+    ReadFilterError, StableBloomFilterDefault,
+};
+
+/// Key of a Python filter. Strings and bytes are inserted like *KMBloomFilter::insert_str* and
+/// *KMBloomFilter::insert_bytes*, and integers like a u64 or i64 with *BloomFilter::insert*, so
+/// Python finds the keys that a Rust pipeline has inserted this way.
+#[derive(FromPyObject)]
+enum Key {
+    Text(PyBackedStr),
+    Bytes(PyBackedBytes),
+    Unsigned(u64),
+    Signed(i64),
+}
+
+impl Key {
+    fn insert_into<H1, H2>(&self, filter: &mut KMBloomFilter<H1, H2>)
+    where
+        H1: std::hash::Hasher + Default,
+        H2: std::hash::Hasher + Default,
+    {
+        match self {
+            Key::Text(text) => filter.insert_str(text),
+            Key::Bytes(bytes) => filter.insert_bytes(bytes),
+            Key::Unsigned(integer) => filter.insert(integer),
+            Key::Signed(integer) => filter.insert(integer),
+        }
+    }
+
+    fn is_contained_in<H1, H2>(&self, filter: &KMBloomFilter<H1, H2>) -> bool
+    where
+        H1: std::hash::Hasher + Default,
+        H2: std::hash::Hasher + Default,
+    {
+        match self {
+            Key::Text(text) => filter.contains_str(text),
+            Key::Bytes(bytes) => filter.contains_bytes(bytes),
+            Key::Unsigned(integer) => filter.contains(integer),
+            Key::Signed(integer) => filter.contains(integer),
+        }
+    }
+}
+
+/// Errors of reading a filter become an *OSError* for I/O errors and a *ValueError* otherwise.
+fn read_error(error: ReadFilterError) -> PyErr {
+    match error {
+        ReadFilterError::Io(error) => error.into(),
+        error => PyValueError::new_err(error.to_string()),
+    }
+}
+
+// Defines a Python class that wraps a KMBloomFilter with the given hashers
+macro_rules! py_bloom_filter {
+    ($name:ident, $python_name:literal, $filter:ty) => {
+        #[pyclass(name = $python_name, module = "bloom_filter_simple")]
+        struct $name($filter);
+
+        #[pymethods]
+        impl $name {
+            #[new]
+            #[pyo3(signature = (
+                        desired_capacity,
+                        desired_false_positive_probability = DEFAULT_FALSE_POSITIVE_PROBABILITY
+                    ))]
+            fn new(
+                desired_capacity: usize,
+                desired_false_positive_probability: f64,
+            ) -> PyResult<Self> {
+                <$filter>::try_new(desired_capacity, desired_false_positive_probability)
+                    .map(Self)
+                    .map_err(|error| PyValueError::new_err(error.to_string()))
+            }
+
+            fn insert(&mut self, key: Key) {
+                key.insert_into(&mut self.0);
+            }
+
+            fn contains(&self, key: Key) -> bool {
+                key.is_contained_in(&self.0)
+            }
+
+            fn __contains__(&self, key: Key) -> bool {
+                key.is_contained_in(&self.0)
+            }
+
+            fn union(&self, other: PyRef<'_, Self>) -> PyResult<Self> {
+                if !self.0.eq_configuration(&other.0) {
+                    return Err(PyValueError::new_err(
+                        "unable to union bloom filters with different configurations",
+                    ));
+                }
+                Ok(Self(self.0.union(&other.0)))
+            }
+
+            fn __or__(&self, other: PyRef<'_, Self>) -> PyResult<Self> {
+                self.union(other)
+            }
+
+            fn approximate_element_count(&self) -> f64 {
+                self.0.approximate_element_count()
+            }
+
+            fn approximate_current_false_positive_probability(&self) -> f64 {
+                self.0.approximate_current_false_positive_probability()
+            }
+
+            fn fill_ratio(&self) -> f64 {
+                self.0.bitset().count_ones() as f64 / self.0.bit_count() as f64
+            }
+
+            fn bit_count(&self) -> usize {
+                self.0.bit_count()
+            }
+
+            fn hasher_count(&self) -> usize {
+                self.0.hasher_count()
+            }
+
+            fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+                PyBytes::new(py, &self.0.to_bytes())
+            }
+
+            #[staticmethod]
+            fn from_bytes(bytes: &[u8]) -> PyResult<Self> {
+                <$filter>::from_bytes(bytes).map(Self).map_err(read_error)
+            }
+
+            fn save(&self, path: PathBuf) -> PyResult<()> {
+                let mut writer = BufWriter::new(File::create(path)?);
+                self.0.write_to(&mut writer)?;
+                Ok(writer.flush()?)
+            }
+
+            #[staticmethod]
+            fn load(path: PathBuf) -> PyResult<Self> {
+                let reader = BufReader::new(File::open(path)?);
+                <$filter>::read_from(reader).map(Self).map_err(read_error)
+            }
+
+            fn __repr__(&self) -> String {
+                format!(
+                    "{}(hasher_count={}, bit_count={}, approximate_element_count={:.1})",
+                    $python_name,
+                    self.0.hasher_count(),
+                    self.0.bit_count(),
+                    self.0.approximate_element_count()
+                )
+            }
+        }
+    };
+}
+
+py_bloom_filter!(
+    PyDefaultBloomFilter,
+    "DefaultBloomFilter",
+    DefaultBloomFilter
+);
+py_bloom_filter!(
+    PyStableBloomFilter,
+    "StableBloomFilter",
+    StableBloomFilterDefault
+);
+
+/// The Python module *bloom_filter_simple*, see the *python* feature.
+#[pymodule]
+fn bloom_filter_simple(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyDefaultBloomFilter>()?;
+    module.add_class::<PyStableBloomFilter>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::{ffi::c_str, types::IntoPyDict};
+
+    use super::*;
+
+    #[test]
+    fn python_filters_find_the_keys_of_rust_filters() {
+        let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+        filter.insert_str("Hello!");
+        filter.insert_bytes(b"\x00\x01");
+        filter.insert(&42u64);
+        filter.insert(&-7i64);
+        let path = std::env::temp_dir().join("bloom_filter_simple_python_test.bin");
+        filter.write_to(File::create(&path).unwrap()).unwrap();
+
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "bloom_filter_simple").unwrap();
+            bloom_filter_simple(&module).unwrap();
+            let locals = [
+                ("bfs", module.into_any()),
+                ("path", (&path).into_pyobject(py).unwrap().into_any()),
+            ]
+            .into_py_dict(py)
+            .unwrap();
+            py.run(
+                c_str!(
+                    r#"
+filter = bfs.StableBloomFilter.load(path)
+assert "Hello!" in filter and b"\x00\x01" in filter and 42 in filter and -7 in filter
+assert "Goodbye!" not in filter and 43 not in filter
+
+other = bfs.StableBloomFilter(1_000, 0.001)
+other.insert("Goodbye!")
+union = filter | other
+assert union.contains("Hello!") and union.contains("Goodbye!")
+assert round(union.approximate_element_count()) == 5
+assert bfs.StableBloomFilter.from_bytes(union.to_bytes()).contains("Goodbye!")
+
+try:
+    filter.union(bfs.StableBloomFilter(10, 0.1))
+    raise AssertionError("filters with different configurations are merged")
+except ValueError:
+    pass
+try:
+    bfs.DefaultBloomFilter.load(path)
+    raise AssertionError("filters with different hashers are loaded")
+except ValueError:
+    pass
+try:
+    bfs.DefaultBloomFilter(0)
+    raise AssertionError("filters without capacity are created")
+except ValueError:
+    pass
+"#
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+        });
+        std::fs::remove_file(&path).unwrap();
+    }
+}
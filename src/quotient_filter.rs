@@ -0,0 +1,735 @@
+use alloc::vec::Vec;
+use core::{
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use crate::{
+    math, packed_array::PackedArray, params::checked_false_positive_probability, BloomFilter,
+};
+
+/// Maximum load factor that is assumed when deriving the number of slots from the desired
+/// capacity. Lookups and insertions slow down quickly at higher load factors, because clusters of
+/// shifted remainders get long.
+const MAX_LOAD_FACTOR: f64 = 0.75;
+
+/// Largest supported number of quotient bits, i.e., the filter has at most *2^32* slots.
+const MAX_QUOTIENT_BITS: u32 = 32;
+
+/// Largest supported number of remainder bits, since a slot stores the remainder and three
+/// metadata bits in at most 32 bits.
+const MAX_REMAINDER_BITS: u32 = 29;
+
+/// Metadata bits of a slot, which are stored below the remainder.
+///
+/// *OCCUPIED* belongs to the slot itself: it is set if the slot is the canonical slot of a stored
+/// quotient, i.e., the quotient has a run of remainders somewhere in the cluster. *CONTINUATION*
+/// and *SHIFTED* belong to the remainder in the slot: the remainder is not the first of its run,
+/// and the remainder is not stored in its canonical slot, respectively.
+const OCCUPIED: u32 = 0b001;
+const CONTINUATION: u32 = 0b010;
+const SHIFTED: u32 = 0b100;
+const METADATA_BITS: u32 = 3;
+
+/// Error returned by *QuotientFilter::try_insert* if all slots of the filter are occupied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotientFilterFull;
+
+impl Display for QuotientFilterFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the quotient filter is full")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for QuotientFilterFull {}
+
+/// Quotient filter as described by Bender et al.:
+///
+/// > Michael A. Bender, Martin Farach-Colton, Rob Johnson, Russell Kraner, Bradley C. Kuszmaul,
+/// > Dzejla Medjedovic, Pablo Montes, Pradeep Shetty, Richard P. Spillane, and Erez Zadok. 2012.
+/// > Don't Thrash: How to Cache Your Hash on Flash. Proceedings of the VLDB Endowment 5, 11,
+/// > 1627–1637. DOI: [https://doi.org/10.14778/2350229.2350275](https://doi.org/10.14778/2350229.2350275)
+///
+/// A quotient filter stores a fingerprint of *quotient_bits + remainder_bits* bits of each
+/// inserted element in a compact hash table with *2^quotient_bits* slots. The quotient selects the
+/// canonical slot of the fingerprint, and the remainder is stored in that slot, or shifted to one
+/// of the following slots, together with three metadata bits.
+///
+/// Like a cuckoo filter, a quotient filter supports removing elements. In addition, the stored
+/// fingerprints can be iterated in order, filters can be merged, and a filter can be doubled in
+/// size with *grow* without rehashing the original elements: every doubling moves one bit of each
+/// fingerprint from the remainder to the quotient, so that the false positive probability doubles
+/// as well.
+///
+/// The filter uses a single hash function *H*. In contrast to bloom filters, a quotient filter can
+/// run full. Use *try_insert* if you cannot guarantee that the desired capacity is never exceeded.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,QuotientFilter,StableXxh3Hasher};
+///
+/// fn main() {
+///     // We plan on storing at most 10,000 elements
+///     let desired_capacity = 10_000;
+///     // We want to assure that the chance of a false positive is less than 0.0001.
+///     let desired_fp_probability = 0.0001;
+///
+///     let mut filter: QuotientFilter<StableXxh3Hasher> =
+///         QuotientFilter::new(desired_capacity, desired_fp_probability);
+///
+///     filter.insert(&5i32);
+///     filter.insert(&"Some text");
+///     assert_eq!(true, filter.contains(&5));
+///     assert_eq!(true, filter.contains(&"Some text"));
+///
+///     // Elements can be removed again.
+///     assert_eq!(true, filter.remove(&5));
+///     assert_eq!(false, filter.contains(&5));
+///
+///     // The filter can be doubled in size without the original elements.
+///     filter.grow();
+///     assert_eq!(true, filter.contains(&"Some text"));
+/// }
+/// ```
+pub struct QuotientFilter<H>
+where
+    H: Hasher + Default,
+{
+    slots: PackedArray,
+    quotient_bits: u32,
+    remainder_bits: u32,
+    element_count: usize,
+    // Phantom data for saving which concrete Hasher type is used
+    _phantom: PhantomData<fn() -> H>,
+}
+
+impl<H> QuotientFilter<H>
+where
+    H: Hasher + Default,
+{
+    /// Initialize a new instance of QuotientFilter that guarantees that the false positive rate
+    /// is less than *desired_false_positive_probability* for up to *desired_capacity*
+    /// elements.
+    ///
+    /// The number of slots is chosen such that the load factor stays below 0.75 for the desired
+    /// capacity, and the fingerprint width is derived from the desired false positive probability.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0, if desired_false_positive_probability is not in the
+    /// interval (0,1), or if it requires remainders wider than 29 bits.
+    pub fn new(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self {
+        let desired_false_positive_probability =
+            checked_false_positive_probability(desired_false_positive_probability);
+        if desired_capacity == 0 {
+            panic!("an empty quotient filter is not defined");
+        }
+        let slot_count = math::ceil(desired_capacity as f64 / MAX_LOAD_FACTOR) as usize;
+        let quotient_bits = slot_count.max(2).next_power_of_two().trailing_zeros();
+        // A lookup matches one of the stored fingerprints with a probability of about
+        // desired_capacity / 2^fingerprint_bits
+        let fingerprint_bits = math::ceil(
+            math::ln(desired_capacity as f64 / desired_false_positive_probability) / math::ln(2.0),
+        ) as u32;
+        let remainder_bits = fingerprint_bits.saturating_sub(quotient_bits).max(1);
+        Self::with_parameters(quotient_bits, remainder_bits)
+    }
+
+    /// Initialize a new instance of QuotientFilter with *2^quotient_bits* slots and fingerprints of
+    /// *quotient_bits + remainder_bits* bits.
+    ///
+    /// The false positive probability of a filter with *n* elements is approximately
+    /// *n / 2^(quotient_bits + remainder_bits)*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if quotient_bits is not in the interval [1, 32], or if remainder_bits is not in the
+    /// interval [1, 29].
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,QuotientFilter,StableXxh3Hasher};
+    ///
+    /// fn main() {
+    ///     // 1,024 slots and 16-bit fingerprints
+    ///     let mut filter: QuotientFilter<StableXxh3Hasher> = QuotientFilter::with_parameters(10, 6);
+    ///     filter.insert(&"Hello!");
+    ///     assert_eq!(true, filter.contains(&"Hello!"));
+    ///     assert_eq!(16, filter.fingerprint_bits());
+    /// }
+    /// ```
+    pub fn with_parameters(quotient_bits: u32, remainder_bits: u32) -> Self {
+        if quotient_bits == 0 || quotient_bits > MAX_QUOTIENT_BITS {
+            panic!(
+                "unsupported quotient width of {} bits, it must be in [1, {}]",
+                quotient_bits, MAX_QUOTIENT_BITS
+            );
+        }
+        if remainder_bits == 0 || remainder_bits > MAX_REMAINDER_BITS {
+            panic!(
+                "unsupported remainder width of {} bits, it must be in [1, {}]",
+                remainder_bits, MAX_REMAINDER_BITS
+            );
+        }
+        Self {
+            slots: PackedArray::new(1 << quotient_bits, remainder_bits + METADATA_BITS),
+            quotient_bits,
+            remainder_bits,
+            element_count: 0,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Insert data into the filter, or return an error if all slots are occupied.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{QuotientFilter,QuotientFilterFull,StableXxh3Hasher};
+    ///
+    /// fn main() {
+    ///     let mut filter: QuotientFilter<StableXxh3Hasher> = QuotientFilter::with_parameters(2, 8);
+    ///     for i in 0..4 {
+    ///         assert_eq!(Ok(()), filter.try_insert(&i));
+    ///     }
+    ///     assert_eq!(Err(QuotientFilterFull), filter.try_insert(&4));
+    ///
+    ///     // Doubling the size makes room for more elements
+    ///     filter.grow();
+    ///     assert_eq!(Ok(()), filter.try_insert(&4));
+    /// }
+    /// ```
+    pub fn try_insert<T>(&mut self, data: &T) -> Result<(), QuotientFilterFull>
+    where
+        T: Hash,
+    {
+        let fingerprint = self.fingerprint(data);
+        self.insert_fingerprint(fingerprint)
+    }
+
+    /// Remove data from the filter.
+    ///
+    /// Returns whether a fingerprint of *data* has been found and removed.
+    ///
+    /// Only remove elements that have been inserted before. Removing an element that has not been
+    /// inserted, but is reported as contained due to a false positive, removes the fingerprint of
+    /// another element and can therefore cause false negatives.
+    pub fn remove<T>(&mut self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        let fingerprint = self.fingerprint(data);
+        self.remove_fingerprint(fingerprint)
+    }
+
+    /// Double the number of slots without rehashing the original elements.
+    ///
+    /// Every fingerprint keeps its width, but one bit of its remainder becomes part of its
+    /// quotient. Therefore, the filter can hold twice as many elements afterwards, but the false
+    /// positive probability for a given number of elements doubles.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the remainders have a single bit only, or if the filter already has *2^32* slots.
+    pub fn grow(&mut self) {
+        if self.remainder_bits == 1 {
+            panic!("unable to grow a quotient filter with single-bit remainders");
+        }
+        let mut grown = Self::with_parameters(self.quotient_bits + 1, self.remainder_bits - 1);
+        for fingerprint in self.fingerprints() {
+            // The grown filter has twice as many slots, so it cannot run full
+            let _ = grown.insert_fingerprint(fingerprint);
+        }
+        *self = grown;
+    }
+
+    /// Iterate over the fingerprints of all stored elements in ascending order.
+    ///
+    /// Fingerprints are *fingerprint_bits* wide. Elements that have been inserted multiple times,
+    /// and different elements with the same fingerprint, appear multiple times.
+    pub fn fingerprints(&self) -> impl Iterator<Item = u64> {
+        let slot_count = self.slot_count();
+        let mut fingerprints = Vec::with_capacity(self.element_count);
+        // Decode the clusters starting at a slot that does not belong to a cluster that wraps
+        // around the end of the table
+        let start = (0..slot_count)
+            .find(|&slot| self.slots.get(slot) & SHIFTED == 0)
+            .unwrap_or(0);
+        let mut quotient = start;
+        for offset in 0..slot_count {
+            let slot = (start + offset) % slot_count;
+            let entry = self.slots.get(slot);
+            if is_empty(entry) {
+                continue;
+            }
+            if entry & SHIFTED == 0 {
+                quotient = slot;
+            } else if entry & CONTINUATION == 0 {
+                quotient = self.next_occupied(quotient);
+            }
+            fingerprints.push(((quotient as u64) << self.remainder_bits) | self.remainder(slot));
+        }
+        // The quotients only wrap around once, at the end of the table
+        let wrap = (1..fingerprints.len())
+            .find(|&i| fingerprints[i] < fingerprints[i - 1])
+            .unwrap_or(0);
+        fingerprints.rotate_left(wrap);
+        fingerprints.into_iter()
+    }
+
+    /// Creates a filter that contains all elements of this filter and 'other'.
+    ///
+    /// The filter is grown until it can hold the elements of both filters at a load factor of at
+    /// most 0.75, if possible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fingerprint width of 'self' and 'other' differ, or if the elements of both
+    /// filters do not fit into a filter with single-bit remainders.
+    pub fn union(&self, other: &Self) -> Self {
+        if self.fingerprint_bits() != other.fingerprint_bits() {
+            panic!("unable to union quotient filters with different configurations");
+        }
+        let mut quotient_bits = self.quotient_bits.max(other.quotient_bits);
+        let element_count = self.element_count + other.element_count;
+        while quotient_bits < MAX_QUOTIENT_BITS
+            && quotient_bits + 1 < self.fingerprint_bits()
+            && element_count as f64 > (1usize << quotient_bits) as f64 * MAX_LOAD_FACTOR
+        {
+            quotient_bits += 1;
+        }
+        let mut union =
+            Self::with_parameters(quotient_bits, self.fingerprint_bits() - quotient_bits);
+        for fingerprint in self.fingerprints().chain(other.fingerprints()) {
+            if let Err(error) = union.insert_fingerprint(fingerprint) {
+                panic!("{}", error);
+            }
+        }
+        union
+    }
+
+    /// Insert all elements of 'other' into this filter, growing this filter if necessary.
+    ///
+    /// See *union* for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fingerprint width of 'self' and 'other' differ, or if the elements of both
+    /// filters do not fit into a filter with single-bit remainders.
+    pub fn union_with(&mut self, other: &Self) {
+        *self = self.union(other);
+    }
+
+    /// Fraction of occupied slots, given as a value in the interval [0,1].
+    pub fn fill_ratio(&self) -> f64 {
+        self.element_count as f64 / self.slot_count() as f64
+    }
+
+    /// Total number of bits used for remainders and metadata.
+    pub fn bit_count(&self) -> usize {
+        self.slot_count() * self.slots.bits() as usize
+    }
+
+    /// Whether the fill ratio has reached *threshold*, i.e., the filter should be grown.
+    pub fn is_saturated(&self, threshold: f64) -> bool {
+        self.fill_ratio() >= threshold
+    }
+
+    /// Remove all elements from the filter.
+    ///
+    /// The memory of the filter is reused, which makes clearing a filter much cheaper than creating
+    /// a new one with the same configuration.
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.element_count = 0;
+    }
+
+    /// Number of elements stored in the filter.
+    ///
+    /// In contrast to bloom filters, the number of elements is known exactly. Elements that have
+    /// been inserted multiple times are counted multiple times.
+    pub fn len(&self) -> usize {
+        self.element_count
+    }
+
+    /// Whether no elements are stored in the filter.
+    pub fn is_empty(&self) -> bool {
+        self.element_count == 0
+    }
+
+    /// Number of fingerprints the filter can store at most.
+    pub fn slot_count(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Whether all slots are occupied, i.e., the next insertion will fail.
+    pub fn is_full(&self) -> bool {
+        self.element_count == self.slot_count()
+    }
+
+    /// Number of fingerprint bits that select the canonical slot of a fingerprint.
+    pub fn quotient_bits(&self) -> u32 {
+        self.quotient_bits
+    }
+
+    /// Number of fingerprint bits that are stored in a slot.
+    pub fn remainder_bits(&self) -> u32 {
+        self.remainder_bits
+    }
+
+    /// Width of the fingerprints in bits, which does not change when the filter grows.
+    pub fn fingerprint_bits(&self) -> u32 {
+        self.quotient_bits + self.remainder_bits
+    }
+
+    /// Return the current approximate false positive probability which depends on the current
+    /// number of elements in the filter.
+    ///
+    /// A lookup matches each stored fingerprint with a probability of *1 / 2^fingerprint_bits*.
+    ///
+    /// The probability is given as a value in the interval [0,1]
+    pub fn approximate_current_false_positive_probability(&self) -> f64 {
+        1.0 - math::powf(
+            1.0 - 1.0 / math::powf(2.0, self.fingerprint_bits() as f64),
+            self.element_count as f64,
+        )
+    }
+
+    fn fingerprint<T>(&self, data: &T) -> u64
+    where
+        T: Hash,
+    {
+        let mut hasher = H::default();
+        data.hash(&mut hasher);
+        hasher.finish() >> (64 - self.fingerprint_bits())
+    }
+
+    fn split(&self, fingerprint: u64) -> (usize, u64) {
+        (
+            (fingerprint >> self.remainder_bits) as usize,
+            fingerprint & ((1 << self.remainder_bits) - 1),
+        )
+    }
+
+    fn insert_fingerprint(&mut self, fingerprint: u64) -> Result<(), QuotientFilterFull> {
+        if self.is_full() {
+            return Err(QuotientFilterFull);
+        }
+        let (quotient, remainder) = self.split(fingerprint);
+        let mut entry = (remainder as u32) << METADATA_BITS;
+        let canonical = self.slots.get(quotient);
+        self.element_count += 1;
+        if is_empty(canonical) {
+            self.slots.set(quotient, entry | OCCUPIED);
+            return Ok(());
+        }
+
+        self.slots.set(quotient, canonical | OCCUPIED);
+        let run_start = self.run_start(quotient);
+        let mut slot = run_start;
+        if canonical & OCCUPIED != 0 {
+            // Keep the run sorted, and insert equal remainders after the existing ones
+            while self.remainder(slot) <= remainder {
+                slot = self.next(slot);
+                if !self.is_continuation(slot) {
+                    break;
+                }
+            }
+            if slot == run_start {
+                // The remainder becomes the first of the run
+                self.slots
+                    .set(run_start, self.slots.get(run_start) | CONTINUATION);
+            } else {
+                entry |= CONTINUATION;
+            }
+        }
+        if slot != quotient {
+            entry |= SHIFTED;
+        }
+        self.shift_right(slot, entry);
+        Ok(())
+    }
+
+    fn contains_fingerprint(&self, fingerprint: u64) -> bool {
+        let (quotient, remainder) = self.split(fingerprint);
+        if !self.is_occupied(quotient) {
+            return false;
+        }
+        let mut slot = self.run_start(quotient);
+        loop {
+            let stored = self.remainder(slot);
+            if stored >= remainder {
+                // Runs are sorted by remainder
+                return stored == remainder;
+            }
+            slot = self.next(slot);
+            if !self.is_continuation(slot) {
+                return false;
+            }
+        }
+    }
+
+    fn remove_fingerprint(&mut self, fingerprint: u64) -> bool {
+        let (quotient, remainder) = self.split(fingerprint);
+        if !self.is_occupied(quotient) {
+            return false;
+        }
+        let run_start = self.run_start(quotient);
+        let mut slot = run_start;
+        while self.remainder(slot) != remainder {
+            if self.remainder(slot) > remainder {
+                return false;
+            }
+            slot = self.next(slot);
+            if !self.is_continuation(slot) {
+                return false;
+            }
+        }
+
+        let next = self.next(slot);
+        let next_is_continuation = self.slots.get(next) & CONTINUATION != 0;
+        if slot == run_start {
+            if next_is_continuation {
+                // The following remainder becomes the first of the run
+                self.slots.set(next, self.slots.get(next) & !CONTINUATION);
+            } else {
+                // The run is empty
+                self.slots
+                    .set(quotient, self.slots.get(quotient) & !OCCUPIED);
+            }
+        }
+        self.shift_left(slot, quotient, slot == run_start && next_is_continuation);
+        self.element_count -= 1;
+        true
+    }
+
+    /// Store *entry* in *slot*, and shift the following remainders of the cluster one slot to the
+    /// right until an empty slot is reached.
+    fn shift_right(&mut self, mut slot: usize, entry: u32) {
+        let mut entry = entry;
+        loop {
+            let previous = self.slots.get(slot);
+            // The occupied bit stays with the slot
+            self.slots
+                .set(slot, (entry & !OCCUPIED) | (previous & OCCUPIED));
+            if is_empty(previous) {
+                return;
+            }
+            entry = previous | SHIFTED;
+            slot = self.next(slot);
+        }
+    }
+
+    /// Remove the remainder in *slot*, whose quotient is *quotient*, and shift the following
+    /// remainders of the cluster one slot to the left. *first_of_run* tells whether the following
+    /// remainder has become the first of its run with quotient *quotient*.
+    fn shift_left(&mut self, mut slot: usize, quotient: usize, first_of_run: bool) {
+        let mut quotient = quotient;
+        let mut first_of_run = first_of_run;
+        loop {
+            let next = self.next(slot);
+            let entry = self.slots.get(next);
+            let occupied = self.slots.get(slot) & OCCUPIED;
+            // Remainders in their canonical slot start a new cluster and stay
+            if entry & SHIFTED == 0 {
+                self.slots.set(slot, occupied);
+                return;
+            }
+            if entry & CONTINUATION == 0 && !first_of_run {
+                quotient = self.next_occupied(quotient);
+            }
+            first_of_run = false;
+            let mut moved = entry & !(OCCUPIED | SHIFTED);
+            if quotient != slot {
+                moved |= SHIFTED;
+            }
+            self.slots.set(slot, moved | occupied);
+            slot = next;
+        }
+    }
+
+    /// Slot of the first remainder of the run of *quotient*, or the slot the run would start at if
+    /// the quotient has no remainders yet. The occupied bit of *quotient* must be set.
+    fn run_start(&self, quotient: usize) -> usize {
+        // Find the start of the cluster
+        let mut canonical = quotient;
+        while self.slots.get(canonical) & SHIFTED != 0 {
+            canonical = self.previous(canonical);
+        }
+        // Skip the runs of all quotients of the cluster before *quotient*
+        let mut slot = canonical;
+        while canonical != quotient {
+            loop {
+                slot = self.next(slot);
+                if !self.is_continuation(slot) {
+                    break;
+                }
+            }
+            canonical = self.next_occupied(canonical);
+        }
+        slot
+    }
+
+    fn next_occupied(&self, quotient: usize) -> usize {
+        let mut quotient = self.next(quotient);
+        while !self.is_occupied(quotient) {
+            quotient = self.next(quotient);
+        }
+        quotient
+    }
+
+    fn is_occupied(&self, slot: usize) -> bool {
+        self.slots.get(slot) & OCCUPIED != 0
+    }
+
+    fn is_continuation(&self, slot: usize) -> bool {
+        self.slots.get(slot) & CONTINUATION != 0
+    }
+
+    fn remainder(&self, slot: usize) -> u64 {
+        (self.slots.get(slot) >> METADATA_BITS) as u64
+    }
+
+    fn next(&self, slot: usize) -> usize {
+        (slot + 1) & (self.slot_count() - 1)
+    }
+
+    fn previous(&self, slot: usize) -> usize {
+        slot.wrapping_sub(1) & (self.slot_count() - 1)
+    }
+}
+
+/// Whether a slot stores no remainder. Every stored remainder has at least one metadata bit set,
+/// because a remainder in its canonical slot sets the occupied bit of that slot.
+fn is_empty(entry: u32) -> bool {
+    entry & (OCCUPIED | CONTINUATION | SHIFTED) == 0
+}
+
+impl<H> Debug for QuotientFilter<H>
+where
+    H: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "QuotientFilter{{quotient_bits: {}, remainder_bits: {}, length: {}}}",
+            self.quotient_bits, self.remainder_bits, self.element_count
+        )
+    }
+}
+
+/// Two quotient filters are equal if they have the same configuration and store the same
+/// fingerprints.
+///
+/// In contrast to cuckoo filters, the layout of a quotient filter does not depend on the order of
+/// insertions, so filters that contain the same elements are equal.
+impl<H> PartialEq for QuotientFilter<H>
+where
+    H: Hasher + Default,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.quotient_bits == other.quotient_bits
+            && self.remainder_bits == other.remainder_bits
+            && self.element_count == other.element_count
+            && self.slots == other.slots
+    }
+}
+
+impl<H> Eq for QuotientFilter<H> where H: Hasher + Default {}
+
+impl<H> BloomFilter for QuotientFilter<H>
+where
+    H: Hasher + Default,
+{
+    /// Insert data into the filter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filter is full. Use *try_insert* to handle this case, or *grow* the filter
+    /// before it runs full.
+    fn insert<T>(&mut self, data: &T)
+    where
+        T: Hash,
+    {
+        if let Err(error) = self.try_insert(data) {
+            panic!("{}", error);
+        }
+    }
+
+    fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        self.contains_fingerprint(self.fingerprint(data))
+    }
+}
+
+impl<T, H> Extend<T> for QuotientFilter<H>
+where
+    T: Hash,
+    H: Hasher + Default,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.insert_all(iter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StableXxh3Hasher;
+    use alloc::collections::BTreeMap;
+
+    /// Insert and remove fingerprints directly, and compare the filter with a multiset after every
+    /// operation. Two of three operations are insertions, so the small tables are nearly full most of
+    /// the time, which produces long clusters that wrap around the end of the table.
+    #[test]
+    fn operations_match_multiset() {
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        for &(quotient_bits, remainder_bits) in &[(3, 2), (4, 3), (6, 1), (7, 3)] {
+            let mut filter: QuotientFilter<StableXxh3Hasher> =
+                QuotientFilter::with_parameters(quotient_bits, remainder_bits);
+            let mut expected: BTreeMap<u64, usize> = BTreeMap::new();
+            let fingerprint_count = 1u64 << (quotient_bits + remainder_bits);
+            for _ in 0..2_000 {
+                // xorshift64
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                let fingerprint = (state >> 8) % fingerprint_count;
+                if state.is_multiple_of(3) {
+                    let contained = expected.contains_key(&fingerprint);
+                    assert_eq!(contained, filter.remove_fingerprint(fingerprint));
+                    if contained {
+                        let count = expected.get_mut(&fingerprint).unwrap();
+                        *count -= 1;
+                        if *count == 0 {
+                            expected.remove(&fingerprint);
+                        }
+                    }
+                } else if filter.insert_fingerprint(fingerprint).is_ok() {
+                    *expected.entry(fingerprint).or_insert(0) += 1;
+                } else {
+                    assert_eq!(filter.slot_count(), filter.len());
+                }
+
+                let all: Vec<u64> = expected
+                    .iter()
+                    .flat_map(|(&fingerprint, &count)| core::iter::repeat_n(fingerprint, count))
+                    .collect();
+                assert_eq!(all, filter.fingerprints().collect::<Vec<_>>());
+                assert_eq!(all.len(), filter.len());
+                for fingerprint in 0..fingerprint_count {
+                    assert_eq!(
+                        expected.contains_key(&fingerprint),
+                        filter.contains_fingerprint(fingerprint)
+                    );
+                }
+            }
+        }
+    }
+}
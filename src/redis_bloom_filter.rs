@@ -0,0 +1,564 @@
+use alloc::{vec, vec::Vec};
+use core::{
+    convert::{TryFrom, TryInto},
+    f64::consts::LN_2,
+    fmt::{self, Debug, Display},
+};
+
+use crate::{math, CapacityExceeded};
+
+/// Number of bytes of the header of a dumped chain before its links: the number of elements
+/// (u64), the number of links (u32), the options (u32), and the growth factor (u32).
+const HEADER_LENGTH: usize = 20;
+
+/// Number of bytes of a dumped link: the number of bytes (u64), bits (u64), and elements (u64),
+/// the error rate (f64), the bits per element (f64), the number of hash functions (u32), the
+/// capacity (u64), and the power of two of the number of bits (u8).
+const LINK_LENGTH: usize = 53;
+
+/// Maximum number of bytes of the chunks of *BF.SCANDUMP*.
+const MAX_CHUNK_LENGTH: usize = 10 * 1024 * 1024;
+
+/// Option of a chain whose links do not round their number of bits up to a power of two.
+const OPTION_NO_ROUND: u32 = 1;
+/// Option of a chain whose elements are hashed with MurmurHash64A instead of MurmurHash2.
+const OPTION_FORCE_64: u32 = 4;
+/// Option of a chain that does not add links once its last link is full.
+const OPTION_NO_SCALING: u32 = 8;
+
+/// Growth factor of the capacity of the links of a chain that is created with *BF.RESERVE*.
+const DEFAULT_GROWTH: u32 = 2;
+
+/// Seed of the first MurmurHash64A hash value of an element.
+const MURMUR_HASH_64A_SEED: u64 = 0xc6a4_a793_5bd1_e995;
+/// Seed of the first MurmurHash2 hash value of an element.
+const MURMUR_HASH_2_SEED: u32 = 0x9747_b28c;
+
+/// Error returned by *RedisBloomFilter::from_header*, *RedisBloomFilter::load_chunk*, and
+/// *RedisBloomFilter::from_scandump* if a chunk is not a valid chunk of *BF.SCANDUMP*.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidRedisBloomChunk;
+
+impl Display for InvalidRedisBloomChunk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid RedisBloom SCANDUMP chunk")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidRedisBloomChunk {}
+
+/// One bloom filter of the chain of a RedisBloomFilter.
+#[derive(Clone)]
+struct Link {
+    bits: Vec<u8>,
+    number_of_bits: u64,
+    size: u64,
+    error: f64,
+    bits_per_element: f64,
+    number_of_hashes: u32,
+    capacity: u64,
+    n2: u8,
+}
+
+impl Link {
+    /// Initialize an empty link like *bloom_init* of RedisBloom with the option
+    /// *BLOOM_OPT_NOROUND*, i.e., with the number of bits rounded up to a multiple of 64.
+    fn with_capacity(capacity: u64, error: f64) -> Self {
+        let bits_per_element = -math::ln(error) / (LN_2 * LN_2);
+        let number_of_bits = (capacity as f64 * bits_per_element) as u64;
+        let number_of_bytes = number_of_bits.div_ceil(64) * 8;
+        Self {
+            bits: vec![
+                0;
+                usize::try_from(number_of_bytes)
+                    .expect("the bits of a RedisBloomFilter must fit into memory")
+            ],
+            number_of_bits: number_of_bytes * 8,
+            size: 0,
+            error,
+            bits_per_element,
+            number_of_hashes: math::ceil(LN_2 * bits_per_element) as u32,
+            capacity,
+            n2: 0,
+        }
+    }
+
+    /// Bit of the hash function i for the hash values a and b, as computed by *bloom_check_h*.
+    fn bit_of(&self, i: u32, a: u64, b: u64) -> u64 {
+        let modulus = if self.n2 > 0 {
+            1 << self.n2
+        } else {
+            self.number_of_bits
+        };
+        a.wrapping_add((i as u64).wrapping_mul(b)) % modulus
+    }
+
+    fn contains(&self, a: u64, b: u64) -> bool {
+        (0..self.number_of_hashes).all(|i| {
+            let index = self.bit_of(i, a, b);
+            self.bits[(index / 8) as usize] & (1 << (index % 8)) != 0
+        })
+    }
+
+    fn insert(&mut self, a: u64, b: u64) {
+        for i in 0..self.number_of_hashes {
+            let index = self.bit_of(i, a, b);
+            self.bits[(index / 8) as usize] |= 1 << (index % 8);
+        }
+    }
+
+    fn read(bytes: &[u8]) -> Result<Self, InvalidRedisBloomChunk> {
+        let number_of_bytes = read_u64(bytes, 0);
+        let number_of_bits = read_u64(bytes, 8);
+        let n2 = bytes[52];
+        let number_of_hashes = read_u32(bytes, 40);
+        if number_of_bits == 0
+            || (number_of_bits - 1) / 8 >= number_of_bytes
+            || n2 > 63
+            || (n2 > 0 && 1u64 << n2 > number_of_bits)
+            || number_of_hashes == 0
+        {
+            return Err(InvalidRedisBloomChunk);
+        }
+        Ok(Self {
+            bits: vec![0; usize::try_from(number_of_bytes).map_err(|_| InvalidRedisBloomChunk)?],
+            number_of_bits,
+            size: read_u64(bytes, 16),
+            error: f64::from_bits(read_u64(bytes, 24)),
+            bits_per_element: f64::from_bits(read_u64(bytes, 32)),
+            number_of_hashes,
+            capacity: read_u64(bytes, 44),
+            n2,
+        })
+    }
+
+    fn write(&self, header: &mut Vec<u8>) {
+        header.extend_from_slice(&(self.bits.len() as u64).to_le_bytes());
+        header.extend_from_slice(&self.number_of_bits.to_le_bytes());
+        header.extend_from_slice(&self.size.to_le_bytes());
+        header.extend_from_slice(&self.error.to_bits().to_le_bytes());
+        header.extend_from_slice(&self.bits_per_element.to_bits().to_le_bytes());
+        header.extend_from_slice(&self.number_of_hashes.to_le_bytes());
+        header.extend_from_slice(&self.capacity.to_le_bytes());
+        header.push(self.n2);
+    }
+}
+
+/// Read-only bloom filter with the bits and the hashing scheme of a scalable bloom filter of
+/// [RedisBloom](https://github.com/RedisBloom/RedisBloom), e.g., to query a filter of a Redis
+/// server offline after dumping it with *BF.SCANDUMP*.
+///
+/// A RedisBloom filter is a chain of bloom filters, the links, where every link is added once the
+/// previous one is full. An element is contained if any link contains it. With the option
+/// *BLOOM_OPT_FORCE64*, which *BF.RESERVE* and *BF.ADD* set, the hash values of an element are
+/// `a = MurmurHash64A(element, 0xc6a4a7935bd1e995)` and `b = MurmurHash64A(element, a)`, and
+/// without it `a = MurmurHash2(element, 0x9747b28c)` and `b = MurmurHash2(element, a)`. The hash
+/// function *i* of a link is `(a + i * b) % m` with wrapping arithmetic, where *m* is 2^n2 if the
+/// link rounds its number of bits up to a power of two, and its number of bits otherwise. The
+/// bits are stored starting with the least significant bit of every byte.
+///
+/// *BF.SCANDUMP* returns the header of the chain with the iterator 1, and then the bits of the
+/// links in chunks of at most 10 MiB, each with the iterator that *BF.LOADCHUNK* expects for it.
+/// Import a filter with *from_scandump*, or with *from_header* and *load_chunk*, and export one
+/// with *from_elements* and *scandump*. MurmurHash64A and MurmurHash2 read the elements in the
+/// byte order of the platform, so the filters are only compatible with servers on little-endian
+/// platforms, e.g., x86-64 and AArch64.
+///
+/// Only available with the *interop-redisbloom* feature.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::RedisBloomFilter;
+///
+/// fn main() {
+///     // Export a filter, e.g., to restore it on a server with BF.LOADCHUNK
+///     let exported = RedisBloomFilter::from_elements(1_000, 0.01, &["Hello", "World"]).unwrap();
+///     let chunks = exported.scandump();
+///
+///     // Import the replies of BF.SCANDUMP
+///     let filter = RedisBloomFilter::from_scandump(chunks).unwrap();
+///     assert_eq!(true, filter.contains("Hello"));
+///     assert_eq!(false, filter.contains("Goodbye"));
+///     assert_eq!(2, filter.len());
+/// }
+/// ```
+#[derive(Clone)]
+pub struct RedisBloomFilter {
+    size: u64,
+    options: u32,
+    growth: u32,
+    links: Vec<Link>,
+}
+
+impl RedisBloomFilter {
+    /// Initialize a filter without set bits from the header of a chain, i.e., the chunk of
+    /// *BF.SCANDUMP* with the iterator 1. Load the bits with *load_chunk*.
+    ///
+    /// Returns *InvalidRedisBloomChunk* if header is not a valid header of a chain.
+    pub fn from_header(header: &[u8]) -> Result<Self, InvalidRedisBloomChunk> {
+        if header.len() < HEADER_LENGTH {
+            return Err(InvalidRedisBloomChunk);
+        }
+        let number_of_links = read_u32(header, 8) as usize;
+        if number_of_links == 0
+            || (number_of_links as u64) * (LINK_LENGTH as u64)
+                != (header.len() - HEADER_LENGTH) as u64
+        {
+            return Err(InvalidRedisBloomChunk);
+        }
+        let links = header[HEADER_LENGTH..]
+            .chunks_exact(LINK_LENGTH)
+            .map(Link::read)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            size: read_u64(header, 0),
+            options: read_u32(header, 12),
+            growth: read_u32(header, 16),
+            links,
+        })
+    }
+
+    /// Load a chunk of the bits of the filter that *BF.SCANDUMP* has returned with iterator.
+    ///
+    /// Returns *InvalidRedisBloomChunk* if data is empty or does not fit into the link at the
+    /// position of iterator, like *BF.LOADCHUNK*.
+    pub fn load_chunk(&mut self, iterator: i64, data: &[u8]) -> Result<(), InvalidRedisBloomChunk> {
+        // The iterator of a chunk is the position after it, starting with 1
+        let position = u64::try_from(iterator)
+            .ok()
+            .and_then(|iterator| iterator.checked_sub(data.len() as u64 + 1))
+            .filter(|_| !data.is_empty())
+            .ok_or(InvalidRedisBloomChunk)?;
+        let mut start = 0;
+        for link in &mut self.links {
+            let length = link.bits.len() as u64;
+            if position < start + length {
+                let offset = (position - start) as usize;
+                if data.len() > link.bits.len() - offset {
+                    return Err(InvalidRedisBloomChunk);
+                }
+                link.bits[offset..offset + data.len()].copy_from_slice(data);
+                return Ok(());
+            }
+            start += length;
+        }
+        Err(InvalidRedisBloomChunk)
+    }
+
+    /// Initialize a filter from the replies of *BF.SCANDUMP*, i.e., pairs of iterators and
+    /// chunks that start with the header. A pair with the iterator 0, which ends the dump, is
+    /// ignored.
+    ///
+    /// Returns *InvalidRedisBloomChunk* if the first chunk is not a valid header or another
+    /// chunk cannot be loaded with *load_chunk*.
+    pub fn from_scandump<I, B>(chunks: I) -> Result<Self, InvalidRedisBloomChunk>
+    where
+        I: IntoIterator<Item = (i64, B)>,
+        B: AsRef<[u8]>,
+    {
+        let mut chunks = chunks.into_iter();
+        let mut filter = match chunks.next() {
+            Some((1, header)) => Self::from_header(header.as_ref())?,
+            _ => return Err(InvalidRedisBloomChunk),
+        };
+        for (iterator, data) in chunks.filter(|(iterator, _)| *iterator != 0) {
+            filter.load_chunk(iterator, data.as_ref())?;
+        }
+        Ok(filter)
+    }
+
+    /// Build a filter that contains the elements of iter like *BF.RESERVE* with the option
+    /// *NONSCALING*, followed by *BF.ADD* for every element, e.g., to restore it on a Redis server
+    /// with *BF.LOADCHUNK* and the chunks of *scandump*.
+    ///
+    /// Returns *CapacityExceeded* if iter has more than capacity elements that are not contained
+    /// when they are added, like *BF.ADD*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if capacity == 0 or if error_rate is not in the interval (0,1).
+    pub fn from_elements<T, I>(
+        capacity: u64,
+        error_rate: f64,
+        iter: I,
+    ) -> Result<Self, CapacityExceeded>
+    where
+        T: AsRef<[u8]>,
+        I: IntoIterator<Item = T>,
+    {
+        assert!(
+            capacity > 0,
+            "capacity of a RedisBloomFilter must be greater than 0"
+        );
+        assert!(
+            error_rate > 0.0 && error_rate < 1.0,
+            "error_rate of a RedisBloomFilter must be in the interval (0,1)"
+        );
+        let mut filter = Self {
+            size: 0,
+            options: OPTION_NO_ROUND | OPTION_FORCE_64 | OPTION_NO_SCALING,
+            growth: DEFAULT_GROWTH,
+            links: vec![Link::with_capacity(capacity, error_rate)],
+        };
+        for element in iter {
+            let (a, b) = filter.hash(element.as_ref());
+            if filter.links[0].contains(a, b) {
+                continue;
+            }
+            if filter.size >= capacity {
+                return Err(CapacityExceeded);
+            }
+            filter.links[0].insert(a, b);
+            filter.links[0].size += 1;
+            filter.size += 1;
+        }
+        Ok(filter)
+    }
+
+    /// Check whether data is contained in the filter, like *BF.EXISTS*.
+    ///
+    /// The result can be a false positive, but never a false negative.
+    pub fn contains<T>(&self, data: &T) -> bool
+    where
+        T: AsRef<[u8]> + ?Sized,
+    {
+        let (a, b) = self.hash(data.as_ref());
+        self.links.iter().rev().any(|link| link.contains(a, b))
+    }
+
+    /// The chunks of *BF.SCANDUMP* for the filter with their iterators, i.e., the header with the
+    /// iterator 1, followed by the bits of the links. The final reply of *BF.SCANDUMP* with the
+    /// iterator 0 is not included.
+    pub fn scandump(&self) -> Vec<(i64, Vec<u8>)> {
+        self.scandump_with_chunk_length(MAX_CHUNK_LENGTH)
+    }
+
+    /// Number of elements that have been added to the filter, i.e., the value of *BF.CARD*.
+    pub fn len(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns true if no element has been added to the filter.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Number of bloom filters of the chain of the filter.
+    pub fn number_of_links(&self) -> usize {
+        self.links.len()
+    }
+
+    fn scandump_with_chunk_length(&self, max_chunk_length: usize) -> Vec<(i64, Vec<u8>)> {
+        let mut header = Vec::with_capacity(HEADER_LENGTH + self.links.len() * LINK_LENGTH);
+        header.extend_from_slice(&self.size.to_le_bytes());
+        header.extend_from_slice(&(self.links.len() as u32).to_le_bytes());
+        header.extend_from_slice(&self.options.to_le_bytes());
+        header.extend_from_slice(&self.growth.to_le_bytes());
+        for link in &self.links {
+            link.write(&mut header);
+        }
+        let mut chunks = vec![(1, header)];
+        let mut iterator = 1;
+        for link in &self.links {
+            for chunk in link.bits.chunks(max_chunk_length) {
+                iterator += chunk.len() as i64;
+                chunks.push((iterator, chunk.to_vec()));
+            }
+        }
+        chunks
+    }
+
+    /// Hash values a and b of data, as computed by *SBChain_GetHash*.
+    fn hash(&self, data: &[u8]) -> (u64, u64) {
+        if self.options & OPTION_FORCE_64 != 0 {
+            let a = murmur_hash_64a(data, MURMUR_HASH_64A_SEED);
+            (a, murmur_hash_64a(data, a))
+        } else {
+            let a = murmur_hash_2(data, MURMUR_HASH_2_SEED);
+            (a as u64, murmur_hash_2(data, a) as u64)
+        }
+    }
+}
+
+impl Debug for RedisBloomFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RedisBloomFilter{{len: {}, number_of_links: {}, options: {}}}",
+            self.size,
+            self.links.len(),
+            self.options
+        )
+    }
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// MurmurHash64A by Austin Appleby, which reads 8-byte blocks in little-endian byte order.
+fn murmur_hash_64a(data: &[u8], seed: u64) -> u64 {
+    const M: u64 = 0xc6a4_a793_5bd1_e995;
+    const R: u32 = 47;
+    let mut h = seed ^ (data.len() as u64).wrapping_mul(M);
+    let mut blocks = data.chunks_exact(8);
+    for block in &mut blocks {
+        let mut k = u64::from_le_bytes(block.try_into().unwrap());
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h ^= k;
+        h = h.wrapping_mul(M);
+    }
+    let tail = blocks.remainder();
+    if !tail.is_empty() {
+        for (i, &byte) in tail.iter().enumerate() {
+            h ^= (byte as u64) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+    h ^= h >> R;
+    h = h.wrapping_mul(M);
+    h ^ (h >> R)
+}
+
+/// MurmurHash2 by Austin Appleby, which reads 4-byte blocks in little-endian byte order.
+fn murmur_hash_2(data: &[u8], seed: u32) -> u32 {
+    const M: u32 = 0x5bd1_e995;
+    const R: u32 = 24;
+    let mut h = seed ^ data.len() as u32;
+    let mut blocks = data.chunks_exact(4);
+    for block in &mut blocks {
+        let mut k = u32::from_le_bytes(block.try_into().unwrap());
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h = h.wrapping_mul(M);
+        h ^= k;
+    }
+    let tail = blocks.remainder();
+    if !tail.is_empty() {
+        for (i, &byte) in tail.iter().enumerate() {
+            h ^= (byte as u32) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^ (h >> 15)
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    /// Verification value of SMHasher, which hashes the prefixes of the bytes 0..256 with the
+    /// seeds 256 to 1, and then the concatenation of the hash values with the seed 0.
+    fn smhasher_verification<const N: usize>(hash: impl Fn(&[u8], u32) -> [u8; N]) -> u32 {
+        let key: Vec<u8> = (0..=255).collect();
+        let hashes: Vec<u8> = (0..256)
+            .flat_map(|i| hash(&key[..i], 256 - i as u32))
+            .collect();
+        let verification = hash(&hashes, 0);
+        u32::from_le_bytes(verification[..4].try_into().unwrap())
+    }
+
+    #[test]
+    fn murmur_hashes_match_smhasher_verification_values() {
+        assert_eq!(
+            0x1F0D_3804,
+            smhasher_verification(|data, seed| murmur_hash_64a(data, seed as u64).to_le_bytes())
+        );
+        assert_eq!(
+            0x2786_4C1E,
+            smhasher_verification(|data, seed| murmur_hash_2(data, seed).to_le_bytes())
+        );
+    }
+
+    /// Header of a chain with one link of 1024 bits that are rounded to a power of two, i.e.,
+    /// n2 = 10, and hashed with MurmurHash2, written out field by field.
+    fn rounded_header() -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend_from_slice(&1u64.to_le_bytes());
+        header.extend_from_slice(&1u32.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&2u32.to_le_bytes());
+        header.extend_from_slice(&128u64.to_le_bytes());
+        header.extend_from_slice(&1024u64.to_le_bytes());
+        header.extend_from_slice(&1u64.to_le_bytes());
+        header.extend_from_slice(&0.01f64.to_le_bytes());
+        header.extend_from_slice(&9.585f64.to_le_bytes());
+        header.extend_from_slice(&7u32.to_le_bytes());
+        header.extend_from_slice(&106u64.to_le_bytes());
+        header.push(10);
+        header
+    }
+
+    #[test]
+    fn rounded_links_use_murmur_hash_2_and_power_of_two() {
+        let element = b"redis";
+        let a = murmur_hash_2(element, MURMUR_HASH_2_SEED) as u64;
+        let b = murmur_hash_2(element, a as u32) as u64;
+        let mut bits = vec![0u8; 128];
+        for i in 0..7u64 {
+            let index = (a + i * b) & 1023;
+            bits[(index / 8) as usize] |= 1 << (index % 8);
+        }
+
+        let mut filter = RedisBloomFilter::from_header(&rounded_header()).unwrap();
+        assert_eq!(false, filter.contains(element));
+        filter.load_chunk(129, &bits).unwrap();
+        assert_eq!(true, filter.contains(element));
+        assert_eq!(1, filter.len());
+        assert_eq!(rounded_header(), filter.scandump()[0].1);
+    }
+
+    #[test]
+    fn scandump_splits_links_into_chunks() {
+        let mut filter =
+            RedisBloomFilter::from_elements(1_000, 0.001, (0..500u32).map(|i| i.to_le_bytes()))
+                .unwrap();
+        let mut second = filter.links[0].clone();
+        second.bits.iter_mut().for_each(|byte| *byte = 0);
+        second.insert(1, 2);
+        filter.links.push(second);
+
+        let chunks = filter.scandump_with_chunk_length(1_000);
+        let length = filter.links[0].bits.len() as i64;
+        assert_eq!(1, chunks[0].0);
+        assert_eq!(1_001, chunks[1].0);
+        assert_eq!(1 + length, chunks[2].0);
+        assert_eq!(1 + length + 1_000, chunks[3].0);
+        assert!(chunks.iter().all(|(_, chunk)| chunk.len() <= 1_000));
+
+        let loaded = RedisBloomFilter::from_scandump(chunks).unwrap();
+        assert_eq!(2, loaded.number_of_links());
+        assert_eq!(true, (0..500u32).all(|i| loaded.contains(&i.to_le_bytes())));
+        assert_eq!(true, loaded.links[1].contains(1, 2));
+    }
+
+    #[test]
+    fn invalid_chunks_are_rejected() {
+        let header = rounded_header();
+        assert!(RedisBloomFilter::from_header(&header[..header.len() - 1]).is_err());
+        let mut filter = RedisBloomFilter::from_header(&header).unwrap();
+        assert_eq!(Err(InvalidRedisBloomChunk), filter.load_chunk(1, &[0]));
+        assert_eq!(
+            Err(InvalidRedisBloomChunk),
+            filter.load_chunk(130, &[0; 129])
+        );
+        assert_eq!(
+            Err(InvalidRedisBloomChunk),
+            filter.load_chunk(130, &[0; 128])
+        );
+        assert_eq!(Err(InvalidRedisBloomChunk), filter.load_chunk(2, &[]));
+        assert_eq!(Ok(()), filter.load_chunk(129, &[0; 128]));
+        assert!(RedisBloomFilter::from_scandump(vec![(2, header)]).is_err());
+    }
+}
@@ -0,0 +1,369 @@
+use alloc::vec::Vec;
+use core::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use roaring::{MultiOps, RoaringBitmap};
+
+use crate::{
+    approximate_element_count, approximate_false_positive_probability, bitset::Bitset,
+    params::FilterParams, BloomFilter, HashedKey, IndexLayout, KMBloomFilter,
+};
+
+/// Bloom filter with the bits of a KMBloomFilter that stores its set bits in a roaring bitmap,
+/// e.g., to intersect thousands of sparsely filled filters in analytics jobs.
+///
+/// A roaring bitmap splits the bits into containers of 2^16 bits and stores every container as
+/// a sorted array of the indices of its set bits, as a bitmap, or as runs of set bits, whichever
+/// is smallest. Unions and intersections of sparse filters therefore only touch the set bits, and
+/// *intersect_all* and *union_all* combine many filters at once, starting with the smallest ones.
+/// Inserting and querying elements is slower than with a KMBloomFilter, because every bit is
+/// looked up in its container.
+///
+/// The bits of the filter are the same as the bits of a KMBloomFilter with the same hashers and
+/// configuration, see *from_dense* and *to_dense*. The filter holds at most 2^32 bits.
+///
+/// Only available with the *roaring* feature.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,RoaringBloomFilter,StableXxh3Hasher,StableXxh64Hasher};
+///
+/// fn main() {
+///     let days: Vec<RoaringBloomFilter<StableXxh3Hasher, StableXxh64Hasher>> = (0..30)
+///         .map(|day| {
+///             let mut filter = RoaringBloomFilter::new(1_000_000, 0.001);
+///             // User 42 is active every day
+///             filter.insert(&42);
+///             filter.insert_all(day * 1_000..day * 1_000 + 500);
+///             filter
+///         })
+///         .collect();
+///
+///     let every_day = RoaringBloomFilter::intersect_all(&days).unwrap();
+///     assert_eq!(true, every_day.contains(&42));
+///     assert_eq!(false, every_day.contains(&7));
+/// }
+/// ```
+pub struct RoaringBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    number_of_hashers: usize,
+    bits_per_hasher: usize,
+    bits: RoaringBitmap,
+    // Phantom data for saving which concrete Hasher types are used
+    _phantom: PhantomData<fn() -> (H1, H2)>,
+}
+
+impl<H1, H2> RoaringBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Initialize a new, empty instance of RoaringBloomFilter with the configuration of
+    /// *KMBloomFilter::new*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0, if desired_false_positive_probability is not in the
+    /// interval (0,1), or if the filter would need more than 2^32 bits.
+    pub fn new(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self {
+        let params =
+            FilterParams::for_capacity(desired_capacity, desired_false_positive_probability);
+        Self::with_bits(
+            params.number_of_hashers(),
+            params.bits_per_hasher(),
+            RoaringBitmap::new(),
+        )
+    }
+
+    /// Initialize a RoaringBloomFilter with the bits of *filter*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if *filter* has more than 2^32 bits, or the index layout *IndexLayout::Global*,
+    /// since a roaring bloom filter addresses the bits of every hasher separately.
+    pub fn from_dense(filter: &KMBloomFilter<H1, H2>) -> Self {
+        if filter.index_layout() != IndexLayout::Partitioned {
+            panic!("unable to store a k-m bloom filter with the global index layout in a roaring bloom filter");
+        }
+        let number_of_hashers = filter.hasher_count();
+        let bits =
+            RoaringBitmap::from_sorted_iter(filter.bitset().iter_ones().map(|index| index as u32))
+                .expect("indices of set bits are sorted");
+        Self::with_bits(
+            number_of_hashers,
+            filter.bit_count() / number_of_hashers,
+            bits,
+        )
+    }
+
+    /// Return the filter as KMBloomFilter, e.g., to write it with *KMBloomFilter::write_to*.
+    pub fn to_dense(&self) -> KMBloomFilter<H1, H2> {
+        let mut bitset = Bitset::new(self.bit_count());
+        for index in self.bits.iter() {
+            bitset.set(index as usize, true);
+        }
+        KMBloomFilter::from_bitset(bitset, self.number_of_hashers)
+    }
+
+    fn with_bits(number_of_hashers: usize, bits_per_hasher: usize, bits: RoaringBitmap) -> Self {
+        if (number_of_hashers * bits_per_hasher) as u64 > 1 << 32 {
+            panic!(
+                "unable to store {} bits in a roaring bloom filter, which holds at most 2^32 bits",
+                number_of_hashers * bits_per_hasher
+            );
+        }
+        Self {
+            number_of_hashers,
+            bits_per_hasher,
+            bits,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Insert the element *key* has been computed for.
+    ///
+    /// See *KMBloomFilter::hash* for how to compute a key.
+    pub fn insert_hashed(&mut self, key: &HashedKey<H1, H2>) {
+        let (hash_a, hash_b) = key.hashes();
+        for i in 0..self.number_of_hashers {
+            self.bits.insert(self.index(i, hash_a, hash_b));
+        }
+    }
+
+    /// Check whether the element *key* has been computed for is contained in the filter.
+    pub fn contains_hashed(&self, key: &HashedKey<H1, H2>) -> bool {
+        let (hash_a, hash_b) = key.hashes();
+        (0..self.number_of_hashers).all(|i| self.bits.contains(self.index(i, hash_a, hash_b)))
+    }
+
+    fn index(&self, i: usize, hash_a: u64, hash_b: u64) -> u32 {
+        KMBloomFilter::<H1, H2>::index(i, self.bits_per_hasher, hash_a, hash_b) as u32
+    }
+
+    /// Total number of bits of the filter, regardless of how many of them are stored.
+    pub fn bit_count(&self) -> usize {
+        self.number_of_hashers * self.bits_per_hasher
+    }
+
+    /// Number of hash functions used for every element.
+    pub fn hasher_count(&self) -> usize {
+        self.number_of_hashers
+    }
+
+    /// Number of set bits.
+    pub fn count_ones(&self) -> usize {
+        self.bits.len() as usize
+    }
+
+    /// The roaring bitmap of the set bits, e.g., to serialize it with the *std* feature of the
+    /// *roaring* crate.
+    pub fn bitmap(&self) -> &RoaringBitmap {
+        &self.bits
+    }
+
+    /// Approximate number of elements stored (see *KMBloomFilter::approximate_element_count*).
+    pub fn approximate_element_count(&self) -> f64 {
+        approximate_element_count(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.count_ones(),
+        )
+    }
+
+    /// Return the current approximate false positive probability which depends on the current
+    /// number of elements in the filter.
+    pub fn approximate_current_false_positive_probability(&self) -> f64 {
+        approximate_false_positive_probability(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.approximate_element_count(),
+        )
+    }
+
+    /// Remove all elements from the filter.
+    pub fn clear(&mut self) {
+        self.bits.clear();
+    }
+
+    /// Creates a union of this bloom filter and *other*, which contains all elements of both
+    /// filters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filters have a different number of hashers or bits per hasher.
+    pub fn union(&self, other: &Self) -> Self {
+        self.check_configuration(other, "union");
+        Self::with_bits(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            &self.bits | &other.bits,
+        )
+    }
+
+    /// Adds all elements of *other* to this bloom filter in place, see *union*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filters have a different number of hashers or bits per hasher.
+    pub fn union_with(&mut self, other: &Self) {
+        self.check_configuration(other, "union");
+        self.bits |= &other.bits;
+    }
+
+    /// Creates an intersection of this bloom filter and *other*, which contains the elements of
+    /// both filters, and a higher rate of false positives than a filter into which only those
+    /// elements have been inserted (see *KMBloomFilter::intersect*).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filters have a different number of hashers or bits per hasher.
+    pub fn intersect(&self, other: &Self) -> Self {
+        self.check_configuration(other, "intersect");
+        Self::with_bits(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            &self.bits & &other.bits,
+        )
+    }
+
+    /// Keeps only the bits that are also set in *other* in place, see *intersect*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filters have a different number of hashers or bits per hasher.
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.check_configuration(other, "intersect");
+        self.bits &= &other.bits;
+    }
+
+    /// Creates a union of all *filters*, or returns None if there are no filters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filters have a different number of hashers or bits per hasher.
+    pub fn union_all<'a, I>(filters: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = &'a Self>,
+        H1: 'a,
+        H2: 'a,
+    {
+        Self::combine_all(filters, "union", |bitmaps| bitmaps.union())
+    }
+
+    /// Creates an intersection of all *filters*, or returns None if there are no filters.
+    ///
+    /// The bitmaps are intersected starting with the smallest one, so the intersection of many
+    /// sparse filters only visits the set bits that are left.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filters have a different number of hashers or bits per hasher.
+    pub fn intersect_all<'a, I>(filters: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = &'a Self>,
+        H1: 'a,
+        H2: 'a,
+    {
+        Self::combine_all(filters, "intersect", |bitmaps| bitmaps.intersection())
+    }
+
+    fn combine_all<'a, I, F>(filters: I, operation: &str, combine: F) -> Option<Self>
+    where
+        I: IntoIterator<Item = &'a Self>,
+        F: FnOnce(Vec<&'a RoaringBitmap>) -> RoaringBitmap,
+        H1: 'a,
+        H2: 'a,
+    {
+        let filters: Vec<&Self> = filters.into_iter().collect();
+        let first = filters.first()?;
+        for filter in filters.iter() {
+            first.check_configuration(filter, operation);
+        }
+        Some(Self::with_bits(
+            first.number_of_hashers,
+            first.bits_per_hasher,
+            combine(filters.iter().map(|filter| &filter.bits).collect()),
+        ))
+    }
+
+    fn check_configuration(&self, other: &Self, operation: &str) {
+        if self.number_of_hashers != other.number_of_hashers
+            || self.bits_per_hasher != other.bits_per_hasher
+        {
+            panic!(
+                "unable to {} roaring bloom filters with different configurations",
+                operation
+            );
+        }
+    }
+}
+
+impl<H1, H2> Clone for RoaringBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn clone(&self) -> Self {
+        Self::with_bits(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.bits.clone(),
+        )
+    }
+}
+
+impl<H1, H2> Debug for RoaringBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RoaringBloomFilter{{number_of_hashers: {}, bits_per_hasher: {}, bits: {:?}}}",
+            self.number_of_hashers, self.bits_per_hasher, self.bits
+        )
+    }
+}
+
+impl<H1, H2> BloomFilter for RoaringBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn insert<T>(&mut self, data: &T)
+    where
+        T: Hash,
+    {
+        let (hash_a, hash_b) = KMBloomFilter::<H1, H2>::generate_hashes(data);
+        self.insert_hashed(&HashedKey::from_hashes(hash_a, hash_b));
+    }
+
+    fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        let (hash_a, hash_b) = KMBloomFilter::<H1, H2>::generate_hashes(data);
+        self.contains_hashed(&HashedKey::from_hashes(hash_a, hash_b))
+    }
+}
+
+impl<T, H1, H2> Extend<T> for RoaringBloomFilter<H1, H2>
+where
+    T: Hash,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.insert_all(iter);
+    }
+}
@@ -0,0 +1,426 @@
+use alloc::collections::VecDeque;
+use core::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+};
+#[cfg(feature = "std")]
+use std::{
+    io,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(feature = "std")]
+use crate::ReadFilterError;
+use crate::{BloomFilter, KMBloomFilter};
+
+/// Bloom filter over a sliding window of generations, e.g., to remove duplicates among the
+/// elements of the last few hours.
+///
+/// A RotatingBloomFilter consists of a fixed number of KMBloomFilter generations. New elements are
+/// always inserted into the newest generation, and *contains* checks all generations. Each call of
+/// *advance* drops the oldest generation and starts a new, empty one. An element is therefore
+/// contained for at least *number_of_generations - 1* and at most *number_of_generations*
+/// rotations after it has been inserted.
+///
+/// Generations can also be rotated based on time (see *with_rotation_interval*), which requires
+/// the *std* feature.
+///
+/// Every generation contributes to the false positive probability. Use
+/// *desired_false_positive_probability / number_of_generations* per generation to keep the false
+/// positive probability of the whole window below *desired_false_positive_probability*.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,RotatingBloomFilter};
+/// use ahash::AHasher;
+/// use std::collections::hash_map::DefaultHasher;
+///
+/// fn main() {
+///     // Three generations of up to 1,000 elements each
+///     let mut filter: RotatingBloomFilter<AHasher, DefaultHasher> =
+///         RotatingBloomFilter::new(3, 1_000, 0.001);
+///
+///     filter.insert(&"Hello!");
+///     filter.advance();
+///     filter.advance();
+///     assert_eq!(true, filter.contains(&"Hello!"));
+///
+///     // The generation containing "Hello!" has been dropped
+///     filter.advance();
+///     assert_eq!(false, filter.contains(&"Hello!"));
+/// }
+/// ```
+pub struct RotatingBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    // The oldest generation is at the front, the newest at the back
+    generations: VecDeque<KMBloomFilter<H1, H2>>,
+    #[cfg(feature = "std")]
+    rotation: Option<Rotation>,
+}
+
+#[cfg(feature = "std")]
+struct Rotation {
+    interval: Duration,
+    last_rotation: Instant,
+}
+
+impl<H1, H2> RotatingBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Initialize a new instance of RotatingBloomFilter with *number_of_generations* generations,
+    /// each of which guarantees that the false positive rate is less than
+    /// *desired_false_positive_probability* for up to *desired_capacity* elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if number_of_generations == 0, desired_capacity == 0, or if
+    /// desired_false_positive_probability is not in the interval (0,1).
+    pub fn new(
+        number_of_generations: usize,
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self {
+        let desired_false_positive_probability = desired_false_positive_probability.into();
+        if number_of_generations == 0 {
+            panic!("a rotating bloom filter requires at least one generation");
+        }
+        Self {
+            generations: (0..number_of_generations)
+                .map(|_| KMBloomFilter::new(desired_capacity, desired_false_positive_probability))
+                .collect(),
+            #[cfg(feature = "std")]
+            rotation: None,
+        }
+    }
+
+    /// Rotate the generations automatically every *interval*, starting now.
+    ///
+    /// Elements inserted into a generation expire once it has been dropped, even if *insert* has
+    /// not been called since then to perform the rotation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if *interval* is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,RotatingBloomFilter};
+    /// use ahash::AHasher;
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::time::Duration;
+    ///
+    /// // Remove duplicates among the elements of the last 23-24 hours
+    /// let mut filter: RotatingBloomFilter<AHasher, DefaultHasher> =
+    ///     RotatingBloomFilter::new(24, 10_000, 0.0001)
+    ///         .with_rotation_interval(Duration::from_secs(60 * 60));
+    ///
+    /// filter.insert(&"Hello!");
+    /// assert_eq!(true, filter.contains(&"Hello!"));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_rotation_interval(mut self, interval: Duration) -> Self {
+        if interval == Duration::from_secs(0) {
+            panic!("the rotation interval of a rotating bloom filter must not be zero");
+        }
+        self.rotation = Some(Rotation {
+            interval,
+            last_rotation: Instant::now(),
+        });
+        self
+    }
+
+    /// The interval in which the generations are rotated automatically, if any.
+    ///
+    /// Only available with the *std* feature.
+    #[cfg(feature = "std")]
+    pub fn rotation_interval(&self) -> Option<Duration> {
+        self.rotation.as_ref().map(|rotation| rotation.interval)
+    }
+
+    /// Drop the oldest generation and start a new, empty one.
+    ///
+    /// The memory of the oldest generation is reused for the new generation.
+    pub fn advance(&mut self) {
+        if let Some(mut oldest) = self.generations.pop_front() {
+            oldest.clear();
+            self.generations.push_back(oldest);
+        }
+    }
+
+    /// Perform all rotations that are due according to the rotation interval and return their
+    /// number.
+    ///
+    /// Does nothing if no rotation interval has been set. *insert* calls this method
+    /// automatically.
+    #[cfg(feature = "std")]
+    pub fn rotate_if_due(&mut self) -> usize {
+        let due = self.due_rotations();
+        for _ in 0..due.min(self.generations.len()) {
+            self.advance();
+        }
+        if let Some(rotation) = self.rotation.as_mut() {
+            if due > 0 {
+                rotation.last_rotation = Instant::now()
+                    - Duration::from_nanos(
+                        (rotation.last_rotation.elapsed().as_nanos() % rotation.interval.as_nanos())
+                            as u64,
+                    );
+            }
+        }
+        due
+    }
+
+    /// Write the window of the filter to *writer*, e.g., to resume it after a restart: the number
+    /// of generations as a little-endian u32, the rotation interval and the time of the last
+    /// rotation in nanoseconds since the Unix epoch as little-endian u64s, both zero without a
+    /// rotation interval, and all generations from the oldest to the newest in the format of
+    /// *KMBloomFilter::write_to*.
+    ///
+    /// Only available with the *std* feature.
+    #[cfg(feature = "std")]
+    pub fn write_to<W>(&self, mut writer: W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        let (interval, last_rotation) = match &self.rotation {
+            Some(rotation) => (
+                rotation.interval.as_nanos(),
+                SystemTime::now()
+                    .checked_sub(rotation.last_rotation.elapsed())
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map_or(0, |time| time.as_nanos()),
+            ),
+            None => (0, 0),
+        };
+        writer.write_all(&(self.generations.len() as u32).to_le_bytes())?;
+        writer.write_all(&(interval.min(u64::MAX as u128) as u64).to_le_bytes())?;
+        writer.write_all(&(last_rotation.min(u64::MAX as u128) as u64).to_le_bytes())?;
+        for generation in self.generations.iter() {
+            generation.write_to(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    /// Read a filter that has been written with *write_to* from *reader*, and perform the
+    /// rotations that have become due since the filter has been written, so that a restarted
+    /// service resumes the same window.
+    ///
+    /// The time of the last rotation is compared with the system clock. If it lies in the future,
+    /// e.g., because the clock of the machine that wrote the filter ran ahead, the filter assumes
+    /// that the last rotation has happened just now, so that no element expires early.
+    ///
+    /// Only available with the *std* feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of *KMBloomFilter::read_from* for every generation, and
+    /// *ReadFilterError::Io* with *io::ErrorKind::InvalidData* if the filter has no generations or
+    /// its generations have different configurations, see *KMBloomFilter::eq_configuration*.
+    #[cfg(feature = "std")]
+    pub fn read_from<R>(mut reader: R) -> Result<Self, ReadFilterError>
+    where
+        R: io::Read,
+    {
+        let mut count = [0u8; 4];
+        let mut interval = [0u8; 8];
+        let mut last_rotation = [0u8; 8];
+        reader.read_exact(&mut count)?;
+        reader.read_exact(&mut interval)?;
+        reader.read_exact(&mut last_rotation)?;
+        // The generations are read one by one, so a hostile count cannot allocate more
+        // generations than the input contains
+        let mut generations: VecDeque<KMBloomFilter<H1, H2>> = VecDeque::new();
+        for _ in 0..u32::from_le_bytes(count) {
+            let generation = KMBloomFilter::read_from(&mut reader)?;
+            if generations
+                .front()
+                .is_some_and(|first| !first.eq_configuration(&generation))
+            {
+                return Err(invalid_data(
+                    "the generations of a rotating bloom filter have different configurations",
+                ));
+            }
+            generations.push_back(generation);
+        }
+        if generations.is_empty() {
+            return Err(invalid_data(
+                "a rotating bloom filter requires at least one generation",
+            ));
+        }
+        let mut filter = Self {
+            generations,
+            rotation: None,
+        };
+        let interval = Duration::from_nanos(u64::from_le_bytes(interval));
+        if interval > Duration::from_secs(0) {
+            let last_rotation =
+                UNIX_EPOCH + Duration::from_nanos(u64::from_le_bytes(last_rotation));
+            let elapsed = SystemTime::now()
+                .duration_since(last_rotation)
+                .unwrap_or_default();
+            let due = (elapsed.as_nanos() / interval.as_nanos()).min(usize::MAX as u128) as usize;
+            for _ in 0..due.min(filter.generations.len()) {
+                filter.advance();
+            }
+            // The clock of Instant may start after the last rotation, e.g., after a reboot, in
+            // which case the next rotation is delayed rather than performed early
+            let since_rotation =
+                Duration::from_nanos((elapsed.as_nanos() % interval.as_nanos()) as u64);
+            filter.rotation = Some(Rotation {
+                interval,
+                last_rotation: Instant::now()
+                    .checked_sub(since_rotation)
+                    .unwrap_or_else(Instant::now),
+            });
+        }
+        Ok(filter)
+    }
+
+    /// Number of generations of the filter.
+    pub fn number_of_generations(&self) -> usize {
+        self.generations.len()
+    }
+
+    /// Approximate number of elements stored in all generations that have not expired.
+    ///
+    /// Elements inserted into multiple generations are counted multiple times.
+    pub fn approximate_element_count(&self) -> f64 {
+        self.active_generations()
+            .map(|generation| generation.approximate_element_count())
+            .sum()
+    }
+
+    /// Return the current approximate false positive probability which depends on the current
+    /// number of elements in the generations that have not expired.
+    ///
+    /// The probability is given as a value in the interval [0,1]
+    pub fn approximate_current_false_positive_probability(&self) -> f64 {
+        1.0 - self
+            .active_generations()
+            .map(|generation| 1.0 - generation.approximate_current_false_positive_probability())
+            .product::<f64>()
+    }
+
+    /// Remove all elements from all generations.
+    pub fn clear(&mut self) {
+        for generation in self.generations.iter_mut() {
+            generation.clear();
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn due_rotations(&self) -> usize {
+        match &self.rotation {
+            Some(rotation) => (rotation.last_rotation.elapsed().as_nanos()
+                / rotation.interval.as_nanos())
+            .min(usize::MAX as u128) as usize,
+            None => 0,
+        }
+    }
+
+    /// Generations that have not been dropped yet, including the ones that are due to be dropped by
+    /// a time-based rotation.
+    fn active_generations(&self) -> impl Iterator<Item = &KMBloomFilter<H1, H2>> {
+        #[cfg(feature = "std")]
+        let expired = self.due_rotations();
+        #[cfg(not(feature = "std"))]
+        let expired = 0;
+        self.generations.iter().skip(expired)
+    }
+
+    fn newest_generation(&mut self) -> &mut KMBloomFilter<H1, H2> {
+        #[cfg(feature = "std")]
+        self.rotate_if_due();
+        self.generations
+            .back_mut()
+            .expect("a rotating bloom filter has at least one generation")
+    }
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn invalid_data(message: &str) -> ReadFilterError {
+    ReadFilterError::Io(io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+impl<H1, H2> Debug for RotatingBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RotatingBloomFilter{{generations: [")?;
+        for (i, generation) in self.generations.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{:?}", generation)?;
+        }
+        write!(f, "]}}")
+    }
+}
+
+impl<H1, H2> BloomFilter for RotatingBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Insert data into the newest generation.
+    fn insert<T>(&mut self, data: &T)
+    where
+        T: Hash,
+    {
+        self.newest_generation().insert(data);
+    }
+
+    /// Check whether data is contained in any generation that has not expired.
+    fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        let key = self.generations[0].hash(data);
+        self.active_generations()
+            .any(|generation| generation.contains_hashed(&key))
+    }
+
+    fn insert_all<T, I>(&mut self, iter: I)
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        self.newest_generation().insert_all(iter);
+    }
+
+    /// Insert data into the newest generation and return whether it has been contained in any
+    /// generation that has not expired, hashing it only once.
+    fn check_and_insert<T>(&mut self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        let key = self.generations[0].hash(data);
+        let contained = self.newest_generation().check_and_insert_hashed(&key);
+        // All generations are active after newest_generation has rotated the expired ones out
+        let older_generations = self.generations.len() - 1;
+        contained
+            || self
+                .generations
+                .iter()
+                .take(older_generations)
+                .any(|generation| generation.contains_hashed(&key))
+    }
+}
+
+impl<T, H1, H2> Extend<T> for RotatingBloomFilter<H1, H2>
+where
+    T: Hash,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.insert_all(iter);
+    }
+}
@@ -1,22 +1,97 @@
 use crate::{
-    approximate_element_count, approximate_false_positive_probability, bitset::Bitset,
-    optimal_bit_count, optimal_number_of_hashers, BloomFilter,
+    approximate_difference_count, approximate_element_count, approximate_element_count_bounds,
+    approximate_false_positive_probability, approximate_intersection_count,
+    approximate_jaccard_similarity,
+    bitset::Bitset,
+    filter_from_iter,
+    km_bloom_filter::write_summary,
+    params::{self, FilterParams, DEFAULT_FALSE_POSITIVE_PROBABILITY},
+    BloomFilter, INSERT_BATCH_SIZE,
 };
 use ahash::AHasher;
-use std::fmt::Debug;
-use std::hash::{Hash, Hasher};
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display};
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+
+/// Hasher that can be created from keys, which makes it possible to simulate an arbitrary number
+/// of hash functions with differently keyed instances of a single hasher (see
+/// SingleHasherBloomFilter).
+///
+/// Implement this trait to use a keyed hasher whose guarantees are required, e.g., a keyed SipHash
+/// or HighwayHash. Hashers with smaller keys use a part of the keys, e.g., SipHash uses *key_a* as
+/// its 128-bit key.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,KeyedHasher,SingleHasherBloomFilter};
+/// use core::hash::Hasher;
+/// use xxhash_rust::xxh64::Xxh64;
+///
+/// struct KeyedXxh64(Xxh64);
+///
+/// impl Hasher for KeyedXxh64 {
+///     fn finish(&self) -> u64 {
+///         self.0.finish()
+///     }
+///
+///     fn write(&mut self, bytes: &[u8]) {
+///         self.0.write(bytes)
+///     }
+/// }
+///
+/// impl KeyedHasher for KeyedXxh64 {
+///     fn with_keys(key_a: u128, _key_b: u128) -> Self {
+///         Self(Xxh64::new(key_a as u64))
+///     }
+/// }
+///
+/// let mut filter: SingleHasherBloomFilter<KeyedXxh64> =
+///     SingleHasherBloomFilter::with_seeds(1_000, 0.001, 0x1234, 0x5678);
+/// filter.insert(&"Hello!");
+/// assert_eq!(true, filter.contains(&"Hello!"));
+/// ```
+pub trait KeyedHasher: Hasher {
+    /// Create a hasher with the keys *key_a* and *key_b*. Hashers with different keys must compute
+    /// independent hash values.
+    fn with_keys(key_a: u128, key_b: u128) -> Self;
+}
+
+impl KeyedHasher for AHasher {
+    fn with_keys(key_a: u128, key_b: u128) -> Self {
+        AHasher::new_with_keys(key_a, key_b)
+    }
+}
 
 /// A bloom filter that uses a single Hasher that can be seeded to simulate an arbitrary number
 /// of hash functions.
 ///
-/// Internally, the implementation uses *ahash::AHasher*.
-pub struct SeededBloomFilter {
+/// The hasher *H* of the *i*-th simulated hash function is created with keys that are derived from
+/// the seeds of the filter (see *hasher_keys*). Filters created with *new* use fixed seeds, so that
+/// all of them map the same element to the same bits. SeededBloomFilter is the filter that uses
+/// *ahash::AHasher*.
+pub struct SingleHasherBloomFilter<H>
+where
+    H: KeyedHasher,
+{
     number_of_hashers: usize,
     bitset: Bitset,
     bits_per_hasher: usize,
+    seeds: (u128, u128),
+    // Phantom data for saving which concrete Hasher type is used
+    _phantom: PhantomData<fn() -> H>,
 }
 
-impl SeededBloomFilter {
+/// A SingleHasherBloomFilter that uses *ahash::AHasher*.
+///
+/// SeededBloomFilter is implemented as a type definition
+/// `type SeededBloomFilter = SingleHasherBloomFilter<ahash::AHasher>;`
+pub type SeededBloomFilter = SingleHasherBloomFilter<AHasher>;
+
+impl<H> SingleHasherBloomFilter<H>
+where
+    H: KeyedHasher,
+{
     /// Initialize a new instance of SeededBloomFilter that guarantees that the false positive rate
     /// is less than *desired_false_positive_probability* for up to *desired_capacity*
     /// elements.
@@ -26,7 +101,8 @@ impl SeededBloomFilter {
     ///
     /// # Panics
     ///
-    /// Panics if desired_capacity == 0
+    /// Panics if desired_capacity == 0 or if desired_false_positive_probability is not in the
+    /// interval (0,1).
     ///
     /// # Examples
     /// ```
@@ -43,18 +119,197 @@ impl SeededBloomFilter {
     ///     let mut filter = SeededBloomFilter::new(desired_capacity, desired_fp_probability);
     /// }
     /// ```
-    pub fn new(desired_capacity: usize, desired_false_positive_probability: f64) -> Self {
-        if desired_capacity == 0 {
-            panic!("an empty bloom filter is not defined");
+    pub fn new(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self {
+        let params =
+            FilterParams::for_capacity(desired_capacity, desired_false_positive_probability);
+        Self {
+            bitset: Bitset::new(params.bit_count()),
+            number_of_hashers: params.number_of_hashers(),
+            bits_per_hasher: params.bits_per_hasher(),
+            seeds: (0, 0),
+            _phantom: PhantomData,
         }
-        let bit_count = optimal_bit_count(desired_capacity, desired_false_positive_probability);
-        let number_of_hashers = optimal_number_of_hashers(desired_capacity, bit_count);
-        let bits_per_hasher = (bit_count as f64 / number_of_hashers as f64).ceil() as usize;
+    }
+
+    /// Initialize a new instance of SeededBloomFilter for up to *desired_capacity* elements with the
+    /// default false positive probability of 1% (see
+    /// *params::DEFAULT_FALSE_POSITIVE_PROBABILITY*).
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0
+    pub fn with_capacity(desired_capacity: usize) -> Self {
+        Self::new(desired_capacity, DEFAULT_FALSE_POSITIVE_PROBABILITY)
+    }
+
+    /// Initialize a new instance of SeededBloomFilter with at least *bit_count* bits that hashes
+    /// every element with exactly *number_of_hashers* hash functions, instead of deriving both
+    /// from a desired capacity and false positive probability.
+    ///
+    /// Fewer hash functions mean fewer probes per lookup at the cost of a higher false positive
+    /// probability for the same number of bits. *params::bit_count_for_hashers* calculates the
+    /// number of bits that keeps the false positive probability for a given number of hashers.
+    /// The bits are split evenly between the hashers, so the bit count is rounded up to a
+    /// multiple of *number_of_hashers*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if bit_count == 0, number_of_hashers == 0, or if rounding bit_count up to a multiple
+    /// of number_of_hashers overflows usize.
+    pub fn with_parameters(bit_count: usize, number_of_hashers: usize) -> Self {
+        let bits_per_hasher = params::bits_per_hasher(bit_count, number_of_hashers);
         Self {
-            bitset: Bitset::new(bits_per_hasher * number_of_hashers),
+            bitset: Bitset::new(number_of_hashers * bits_per_hasher),
             number_of_hashers,
             bits_per_hasher,
+            seeds: (0, 0),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Initialize a new instance of SeededBloomFilter like *new*, but with the seeds *seed_a* and
+    /// *seed_b* for the keys of the hashers.
+    ///
+    /// Filters with different seeds map the same element to different bits, which makes it hard
+    /// to craft elements that collide in a filter. Filters can only be combined and compared if
+    /// they use the same seeds, so persist the seeds together with the filter (see *seeds*).
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0 or if desired_false_positive_probability is not in the
+    /// interval (0,1).
+    pub fn with_seeds(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+        seed_a: u128,
+        seed_b: u128,
+    ) -> Self {
+        Self {
+            seeds: (seed_a, seed_b),
+            ..Self::new(desired_capacity, desired_false_positive_probability)
+        }
+    }
+
+    /// Initialize a new instance of SeededBloomFilter like *new*, but with seeds that are derived
+    /// from the single seed *base_seed* (see *with_seeds*).
+    ///
+    /// The seeds are the first four outputs of the SplitMix64 generator seeded with *base_seed*:
+    /// the first and second output are the lower and upper half of *seed_a*, and the third and
+    /// fourth output are the lower and upper half of *seed_b*. Keep *base_seed* secret if the seeds
+    /// are meant to prevent crafted collisions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0 or if desired_false_positive_probability is not in the
+    /// interval (0,1).
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,SeededBloomFilter};
+    ///
+    /// let mut filter = SeededBloomFilter::with_base_seed(1_000, 0.001, 0x5eed);
+    /// filter.insert(&"Hello!");
+    ///
+    /// // Deployments with the same base seed create compatible filters
+    /// let mut other = SeededBloomFilter::with_base_seed(1_000, 0.001, 0x5eed);
+    /// other.insert(&"Hello!");
+    /// assert_eq!(filter, other);
+    /// ```
+    pub fn with_base_seed(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+        base_seed: u64,
+    ) -> Self {
+        let output = |n: u64| splitmix64(base_seed, n) as u128;
+        Self::with_seeds(
+            desired_capacity,
+            desired_false_positive_probability,
+            output(1) | output(2) << 64,
+            output(3) | output(4) << 64,
+        )
+    }
+
+    /// Initialize a new instance of SeededBloomFilter like *new*, but with two seeds drawn from
+    /// *rng* (see *with_seeds*).
+    ///
+    /// The two seeds are guaranteed to differ from each other.
+    ///
+    /// Only available with the *rand* feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0 or if desired_false_positive_probability is not in the
+    /// interval (0,1).
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,SeededBloomFilter};
+    ///
+    /// let mut filter = SeededBloomFilter::new_random(1_000, 0.001, &mut rand::thread_rng());
+    /// filter.insert(&"Hello!");
+    ///
+    /// let (seed_a, seed_b) = filter.seeds();
+    /// assert_ne!(seed_a, seed_b);
+    /// assert_eq!(true, filter.contains(&"Hello!"));
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn new_random<R>(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+        rng: &mut R,
+    ) -> Self
+    where
+        R: rand::Rng + ?Sized,
+    {
+        let seed_a: u128 = rng.gen();
+        let mut seed_b: u128 = rng.gen();
+        while seed_b == seed_a {
+            seed_b = rng.gen();
         }
+        Self::with_seeds(
+            desired_capacity,
+            desired_false_positive_probability,
+            seed_a,
+            seed_b,
+        )
+    }
+
+    /// The seeds of the hashers of this filter.
+    pub fn seeds(&self) -> (u128, u128) {
+        self.seeds
+    }
+
+    /// The two keys of the hasher of the *i*-th simulated hash function.
+    ///
+    /// Every key is derived from the corresponding seed (see *seeds*) by applying SplitMix64 to
+    /// both of its 64-bit halves: the lower and upper half of the key are the *(i + 1)*-th outputs
+    /// of the SplitMix64 generators seeded with the lower and upper half of the seed. In contrast to
+    /// combining the seeds with *i* directly, the keys of different hash functions and of similar
+    /// seeds have no bits in common that an attacker could exploit.
+    pub fn hasher_keys(&self, i: usize) -> (u128, u128) {
+        hasher_keys(self.seeds, i)
+    }
+
+    /// Create a new SeededBloomFilter for the elements of *iter* that guarantees a false positive rate
+    /// of less than *desired_false_positive_probability* and insert all elements.
+    ///
+    /// The desired capacity is the number of elements of *iter*. It is taken from the size hint of
+    /// *iter* if the hint is exact. Otherwise, the elements are collected first to count them.
+    /// Use *new* and *extend* if you want to leave room for more elements.
+    pub fn from_iter_with_fp<T, I>(
+        iter: I,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        filter_from_iter(iter, |desired_capacity| {
+            Self::new(desired_capacity, desired_false_positive_probability)
+        })
     }
 
     /// Approximate number of elements stored.
@@ -82,6 +337,216 @@ impl SeededBloomFilter {
         )
     }
 
+    /// Lower and upper bound of an approximate 95% confidence interval for the number of elements
+    /// stored, based on the variance of the number of bits that are set.
+    ///
+    /// The upper bound is infinite if the filter is (almost) full. See
+    /// *params::approximate_element_count_bounds* for details.
+    pub fn approximate_element_count_bounds(&self) -> (f64, f64) {
+        approximate_element_count_bounds(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.bitset.count_ones(),
+        )
+    }
+
+    /// Lower and upper bound of an approximate 95% confidence interval for the current false
+    /// positive probability, i.e., the false positive probabilities for the bounds of
+    /// *approximate_element_count_bounds*.
+    pub fn approximate_current_false_positive_probability_bounds(&self) -> (f64, f64) {
+        let (lower, upper) = self.approximate_element_count_bounds();
+        (
+            approximate_false_positive_probability(
+                self.number_of_hashers,
+                self.bits_per_hasher,
+                lower,
+            ),
+            approximate_false_positive_probability(
+                self.number_of_hashers,
+                self.bits_per_hasher,
+                upper,
+            ),
+        )
+    }
+
+    /// Approximate number of elements stored in the union of this bloom filter and 'other', without
+    /// creating the union.
+    ///
+    /// The approximation is equal to calling 'approximate_element_count' on the result of 'union'.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,SeededBloomFilter};
+    ///
+    /// fn main() {
+    ///     let mut filter_one = SeededBloomFilter::new(1_000, 0.0001);
+    ///     let mut filter_two = SeededBloomFilter::new(1_000, 0.0001);
+    ///
+    ///     for i in 0..100 {
+    ///         filter_one.insert(&i);
+    ///     }
+    ///     for i in 50..150 {
+    ///         filter_two.insert(&i);
+    ///     }
+    ///
+    ///     // There are 150 distinct elements in both filters
+    ///     assert!((filter_one.approximate_union_count(&filter_two) - 150.0).abs() < 5.0);
+    ///     // Of which 50 elements are contained in both filters
+    ///     assert!((filter_one.approximate_intersection_count(&filter_two) - 50.0).abs() < 5.0);
+    /// }
+    /// ```
+    pub fn approximate_union_count(&self, other: &Self) -> f64 {
+        if !self.eq_configuration(other) {
+            panic!("unable to union seeded bloom filters with different configurations");
+        }
+        approximate_element_count(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.bitset.count_ones_union(&other.bitset),
+        )
+    }
+
+    /// Approximate number of elements stored in both this bloom filter and 'other', without creating
+    /// the intersection.
+    ///
+    /// In contrast to calling 'approximate_element_count' on the result of 'intersect', which
+    /// overestimates the number of elements, the approximation is computed from the approximate
+    /// element counts of both filters and their union:
+    /// *n(A ∩ B) = n(A) + n(B) - n(A ∪ B)*.
+    ///
+    /// Formula taken from Swamidass and Baldi:
+    /// > S. J. Swamidass, P. Baldi, "Mathematical correction for fingerprint similarity measures to improve chemical retrieval", Journal of Chemical Information and Modeling 47(3), 2007, 952–964. DOI: [https://doi.org/10.1021/ci600526a](https://doi.org/10.1021/ci600526a)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    pub fn approximate_intersection_count(&self, other: &Self) -> f64 {
+        if !self.eq_configuration(other) {
+            panic!("unable to intersect seeded bloom filters with different configurations");
+        }
+        approximate_intersection_count(
+            self.approximate_element_count(),
+            other.approximate_element_count(),
+            self.approximate_union_count(other),
+        )
+    }
+
+    /// Approximate number of elements stored in this bloom filter but not in 'other', without
+    /// creating the difference.
+    ///
+    /// The approximation is computed from the approximate element counts of 'other' and of the
+    /// union of both filters: *n(A \\ B) = n(A ∪ B) - n(B)*. Like all approximations of this
+    /// crate, it is only reliable as long as both filters hold at most their desired capacity.
+    /// Calling 'approximate_element_count' on the result of 'difference' gives no meaningful result.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,SeededBloomFilter};
+    ///
+    /// fn main() {
+    ///     let mut shard = SeededBloomFilter::new(1_000, 0.001);
+    ///     let mut aggregate = SeededBloomFilter::new(1_000, 0.001);
+    ///
+    ///     shard.insert_all(0..150);
+    ///     aggregate.insert_all(0..100);
+    ///
+    ///     // The shard contains about 50 elements that are missing in the aggregate
+    ///     assert!((shard.approximate_difference_count(&aggregate) - 50.0).abs() < 5.0);
+    /// }
+    /// ```
+    pub fn approximate_difference_count(&self, other: &Self) -> f64 {
+        if !self.eq_configuration(other) {
+            panic!("unable to subtract seeded bloom filters with different configurations");
+        }
+        approximate_difference_count(
+            other.approximate_element_count(),
+            self.approximate_union_count(other),
+        )
+    }
+
+    /// Approximate Jaccard similarity of the elements stored in this bloom filter and 'other',
+    /// i.e., *|A ∩ B| / |A ∪ B|*, given as a value in the interval [0,1].
+    ///
+    /// The similarity is computed from the numbers of set bits of both filters and of their union,
+    /// see *approximate_intersection_count*. The similarity of two empty filters is 1. The estimate
+    /// is accurate as long as the union holds at most the desired capacity of the filters, e.g.,
+    /// the absolute error is typically below 0.01 for unions of tens of thousands of elements. It
+    /// grows as the union exceeds the capacity and the filters saturate. For small similarities,
+    /// the intersection is the difference of two large counts, so the relative error is large.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::{BloomFilter,SeededBloomFilter};
+    ///
+    /// fn main() {
+    ///     let mut filter_one = SeededBloomFilter::new(10_000, 0.001);
+    ///     let mut filter_two = SeededBloomFilter::new(10_000, 0.001);
+    ///
+    ///     filter_one.insert_all(0..3_000);
+    ///     filter_two.insert_all(1_000..4_000);
+    ///
+    ///     // 2,000 of 4,000 distinct elements are contained in both filters
+    ///     assert!((filter_one.jaccard_similarity(&filter_two) - 0.5).abs() < 0.02);
+    /// }
+    /// ```
+    pub fn jaccard_similarity(&self, other: &Self) -> f64 {
+        if !self.eq_configuration(other) {
+            panic!("unable to compare seeded bloom filters with different configurations");
+        }
+        approximate_jaccard_similarity(
+            self.approximate_element_count(),
+            other.approximate_element_count(),
+            self.approximate_union_count(other),
+        )
+    }
+
+    /// Creates a bloom filter in which exactly the bits are set that are set in this bloom filter
+    /// but not in 'other' (bitwise *A AND NOT B*).
+    ///
+    /// In contrast to *union* and *intersect*, the result is **not** a bloom filter of the elements
+    /// that have been inserted into this filter but not into 'other':
+    /// * 'contains' of the result returns false for all elements inserted into 'other', even if
+    ///   they have been inserted into this filter, too.
+    /// * 'contains' of the result can return false for elements that have only been inserted into
+    ///   this filter, i.e., the result has false negatives. This happens whenever a bit of such an
+    ///   element is also set in 'other', which gets more likely the fuller 'other' is.
+    ///
+    /// Use the result to analyze which bits are new, not as a filter. To estimate the number of
+    /// elements in the difference, use *approximate_difference_count*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    pub fn difference(&self, other: &Self) -> Self {
+        if !self.eq_configuration(other) {
+            panic!("unable to subtract seeded bloom filters with different configurations");
+        }
+        Self {
+            number_of_hashers: self.number_of_hashers,
+            bitset: self.bitset.difference(&other.bitset),
+            bits_per_hasher: self.bits_per_hasher,
+            seeds: self.seeds,
+            _phantom: PhantomData,
+        }
+    }
+
     /// Creates a union of this bloom filter and 'other', which means 'contains' of the resulting
     /// bloom filter will always return true for elements inserted in either this bloom filter or in
     /// 'other' before creation.
@@ -90,9 +555,9 @@ impl SeededBloomFilter {
     ///
     /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
     /// differ.
-    /// 
+    ///
     /// # Examples
-    /// 
+    ///
     /// Union of two bloom filters with the same configuration.
     /// ```
     /// use bloom_filter_simple::{BloomFilter,SeededBloomFilter};
@@ -102,22 +567,22 @@ impl SeededBloomFilter {
     ///     let desired_capacity = 10_000;
     ///     let desired_fp_probability = 0.0001;
     ///
-    ///     // We initialize two new SeededBloomFilter 
+    ///     // We initialize two new SeededBloomFilter
     ///     let mut filter_one = SeededBloomFilter::new(desired_capacity, desired_fp_probability);
     ///     let mut filter_two = SeededBloomFilter::new(desired_capacity, desired_fp_probability);
-    /// 
+    ///
     ///     // Insert elements into the first filter
     ///     filter_one.insert(&0);
     ///     filter_one.insert(&1);
-    /// 
+    ///
     ///     // Insert elements into the second filter
     ///     filter_two.insert(&2);
     ///     filter_two.insert(&3);
-    ///     
+    ///
     ///     // Now we retrieve the union of both filters
     ///     let filter_union = filter_one.union(&filter_two);
     ///
-    ///     // The union will return true for a 'contains' check for the elements inserted 
+    ///     // The union will return true for a 'contains' check for the elements inserted
     ///     // previously into at least one of the constituent filters.
     ///     assert_eq!(true, filter_union.contains(&0));
     ///     assert_eq!(true, filter_union.contains(&1));
@@ -127,15 +592,33 @@ impl SeededBloomFilter {
     /// ```
     pub fn union(&self, other: &Self) -> Self {
         if !self.eq_configuration(other) {
-            panic!("unable to union k-m bloom filters with different configurations");
+            panic!("unable to union seeded bloom filters with different configurations");
         }
         Self {
             number_of_hashers: self.number_of_hashers,
             bitset: self.bitset.union(&other.bitset),
             bits_per_hasher: self.bits_per_hasher,
+            seeds: self.seeds,
+            _phantom: PhantomData,
         }
     }
 
+    /// Adds all elements of 'other' to this bloom filter, so that it becomes the union of both
+    /// filters (see *union*).
+    ///
+    /// In contrast to *union*, no new filter is allocated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    pub fn union_with(&mut self, other: &Self) {
+        if !self.eq_configuration(other) {
+            panic!("unable to union seeded bloom filters with different configurations");
+        }
+        self.bitset.union_with(&other.bitset);
+    }
+
     /// Creates a intersection of this bloom filter and 'other', which means 'contains' of the resulting
     /// bloom filter will always return true for elements inserted both in this bloom filter and in
     /// 'other' before creation.
@@ -152,7 +635,7 @@ impl SeededBloomFilter {
     /// differ.
     ///
     /// # Examples
-    /// 
+    ///
     /// Intersection of two bloom filters with the same configuration.
     /// ```
     /// use bloom_filter_simple::{BloomFilter,SeededBloomFilter};
@@ -162,22 +645,22 @@ impl SeededBloomFilter {
     ///     let desired_capacity = 10_000;
     ///     let desired_fp_probability = 0.0001;
     ///
-    ///     // We initialize two new SeededBloomFilter 
+    ///     // We initialize two new SeededBloomFilter
     ///     let mut filter_one = SeededBloomFilter::new(desired_capacity, desired_fp_probability);
     ///     let mut filter_two = SeededBloomFilter::new(desired_capacity, desired_fp_probability);
-    /// 
+    ///
     ///     // Insert elements into the first filter
     ///     filter_one.insert(&0);
     ///     filter_one.insert(&1);
-    /// 
+    ///
     ///     // Insert elements into the second filter
     ///     filter_two.insert(&1);
     ///     filter_two.insert(&2);
-    ///     
+    ///
     ///     // Now we retrieve the intersection of both filters
     ///     let filter_intersection = filter_one.intersect(&filter_two);
     ///
-    ///     // The intersection will return true for a 'contains' check for the elements inserted 
+    ///     // The intersection will return true for a 'contains' check for the elements inserted
     ///     // previously into both constituent filters.
     ///     assert_eq!(false, filter_intersection.contains(&0));
     ///     assert_eq!(true, filter_intersection.contains(&1));
@@ -186,46 +669,291 @@ impl SeededBloomFilter {
     /// ```
     pub fn intersect(&self, other: &Self) -> Self {
         if !self.eq_configuration(other) {
-            panic!("unable to intersect k-m bloom filters with different configurations");
+            panic!("unable to intersect seeded bloom filters with different configurations");
         }
         Self {
             number_of_hashers: self.number_of_hashers,
             bitset: self.bitset.intersect(&other.bitset),
             bits_per_hasher: self.bits_per_hasher,
+            seeds: self.seeds,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Turns this bloom filter into the intersection of both filters (see *intersect*).
+    ///
+    /// In contrast to *intersect*, no new filter is allocated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    pub fn intersect_with(&mut self, other: &Self) {
+        if !self.eq_configuration(other) {
+            panic!("unable to intersect seeded bloom filters with different configurations");
+        }
+        self.bitset.intersect_with(&other.bitset);
+    }
+
+    /// Fraction of bits that are set, given as a value in the interval [0,1].
+    ///
+    /// With the optimal number of hashers, about half of the bits are set once the filter holds
+    /// its desired capacity. The fill ratio keeps growing with every insertion, and the false
+    /// positive probability grows with it.
+    pub fn fill_ratio(&self) -> f64 {
+        self.bitset.count_ones() as f64 / self.bitset.len() as f64
+    }
+
+    /// Fill ratio of each of *buckets* consecutive regions of the bits of the filter (see
+    /// *Bitset::density_histogram*).
+    ///
+    /// Every hasher sets bits in its own region of *bit_count / hasher_count* bits, so the fill
+    /// ratios of all regions are close to *fill_ratio* if the hashers spread the elements evenly.
+    /// Regions that are much fuller than others indicate hashers that cluster similar elements,
+    /// and raise the false positive probability above its approximation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if buckets == 0 or buckets > bit_count.
+    pub fn density_histogram(&self, buckets: usize) -> Vec<f64> {
+        self.bitset.density_histogram(buckets)
+    }
+
+    /// Total number of bits of the filter.
+    pub fn bit_count(&self) -> usize {
+        self.bitset.len()
+    }
+
+    /// The bits of the filter, e.g., to compare them with the bits of another filter.
+    ///
+    /// The bits of the *i*-th simulated hash function are the bits from *i \* bit_count /
+    /// hasher_count* (inclusive) to *(i + 1) \* bit_count / hasher_count* (exclusive).
+    pub fn bitset(&self) -> &Bitset {
+        &self.bitset
+    }
+
+    /// Indices of the bits that *data* is mapped to, one for every simulated hash function.
+    ///
+    /// The index of the *i*-th hash function is *i \* m + h_i mod m*, where *m* is the number of
+    /// bits per hash function and *h_i* is the hash value of a hasher *H* with the keys of
+    /// *hasher_keys(i)*.
+    pub fn probe_indices<T>(&self, data: &T) -> Vec<usize>
+    where
+        T: Hash,
+    {
+        (0..self.number_of_hashers)
+            .map(|i| Self::index(i, self.bits_per_hasher, self.seeds, data))
+            .collect()
+    }
+
+    /// Insert the raw bytes *bytes*.
+    ///
+    /// In contrast to *insert*, the bytes are written to the hashers as they are, without the
+    /// length prefix of the *Hash* implementation of slices.
+    ///
+    /// Elements inserted with *insert_bytes* are in general only found by *contains_bytes*.
+    pub fn insert_bytes(&mut self, bytes: &[u8]) {
+        self.insert(&RawBytes(bytes));
+    }
+
+    /// Check whether the raw bytes *bytes* have been inserted with *insert_bytes*.
+    pub fn contains_bytes(&self, bytes: &[u8]) -> bool {
+        self.contains(&RawBytes(bytes))
+    }
+
+    /// Insert the UTF-8 bytes of *text*, which is the same element as *insert_bytes* of
+    /// *text.as_bytes()*.
+    ///
+    /// See *KMBloomFilter::insert_str* for details.
+    ///
+    /// # Examples
+    /// ```
+    /// use bloom_filter_simple::SeededBloomFilter;
+    ///
+    /// let mut filter = SeededBloomFilter::new(1_000, 0.001);
+    /// filter.insert_str("Hello!");
+    ///
+    /// assert_eq!(true, filter.contains_str("Hello!"));
+    /// assert_eq!(true, filter.contains_bytes(b"Hello!"));
+    /// ```
+    pub fn insert_str(&mut self, text: &str) {
+        self.insert_bytes(text.as_bytes());
+    }
+
+    /// Check whether *text* has been inserted with *insert_str* (or as its UTF-8 bytes with
+    /// *insert_bytes*).
+    pub fn contains_str(&self, text: &str) -> bool {
+        self.contains_bytes(text.as_bytes())
+    }
+
+    /// Number of hash functions used for every element.
+    pub fn hasher_count(&self) -> usize {
+        self.number_of_hashers
+    }
+
+    /// Whether the fill ratio has reached *threshold*, i.e., the filter should be replaced by a
+    /// larger or empty one.
+    pub fn is_saturated(&self, threshold: f64) -> bool {
+        self.fill_ratio() >= threshold
+    }
+
+    /// Remove all elements from the bloom filter.
+    ///
+    /// The memory of the filter is reused, which makes clearing a filter much cheaper than creating
+    /// a new one with the same configuration.
+    pub fn clear(&mut self) {
+        self.bitset.clear();
+    }
+
+    /// Whether all elements of this bloom filter are probably contained in 'other'.
+    ///
+    /// The check is exact on the level of bits: if it returns false, this bloom filter certainly
+    /// contains an element that has not been inserted into 'other'. If it returns true, all elements
+    /// of this bloom filter are reported as contained by 'other', i.e., 'other.union(self)' equals
+    /// 'other'.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        if !self.eq_configuration(other) {
+            panic!("unable to compare seeded bloom filters with different configurations");
         }
+        self.bitset.is_subset(&other.bitset)
     }
 
-    /// Checks whether two bloom filters were created with the same desired capacity and desired false
-    /// positive probability.
+    /// Whether this bloom filter and 'other' probably have no element in common.
+    ///
+    /// Every element sets exactly one bit in the range of bits of each hasher. If the filters have
+    /// no bit in common in the range of any hasher, they certainly share no element. Otherwise,
+    /// the filters are considered disjoint if the approximate number of common elements (see
+    /// *approximate_intersection_count*) is less than one half.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the desired capacity or desired false positive probability of 'self' and 'other'
+    /// differ.
+    pub fn is_disjoint_probably(&self, other: &Self) -> bool {
+        if !self.eq_configuration(other) {
+            panic!("unable to compare seeded bloom filters with different configurations");
+        }
+        let certainly_disjoint = (0..self.number_of_hashers).any(|i| {
+            !self.bitset.intersects_in(
+                &other.bitset,
+                i * self.bits_per_hasher,
+                (i + 1) * self.bits_per_hasher,
+            )
+        });
+        certainly_disjoint || self.approximate_intersection_count(other) < 0.5
+    }
+
+    /// Checks whether two bloom filters were created with the same desired capacity, desired false
+    /// positive probability, and seeds.
     pub fn eq_configuration(&self, other: &Self) -> bool {
         self.number_of_hashers == other.number_of_hashers
             && self.bits_per_hasher == other.bits_per_hasher
+            && self.seeds == other.seeds
     }
 
-    fn index<T>(i: usize, bits_per_hash: usize, data: &T) -> usize
+    fn set_indices(&mut self, indices: &mut Vec<usize>) {
+        for &index in indices.iter() {
+            self.bitset.set(index, true);
+        }
+        indices.clear();
+    }
+
+    fn index<T>(i: usize, bits_per_hash: usize, seeds: (u128, u128), data: &T) -> usize
     where
         T: Hash,
     {
-        let mut hasher = AHasher::new_with_keys(i as u128, i as u128);
+        let (key_a, key_b) = hasher_keys(seeds, i);
+        let mut hasher = H::with_keys(key_a, key_b);
         data.hash(&mut hasher);
-        i * bits_per_hash + hasher.finish() as usize % bits_per_hash
+        i * bits_per_hash + (hasher.finish() % bits_per_hash as u64) as usize
     }
 }
 
-impl Debug for SeededBloomFilter {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// Bytes whose hash is written to the hasher as they are, without a length prefix.
+struct RawBytes<'a>(&'a [u8]);
+
+impl Hash for RawBytes<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write(self.0);
+    }
+}
+
+/// Keys of the hasher of the *i*-th simulated hash function, see *SingleHasherBloomFilter::hasher_keys*.
+fn hasher_keys((seed_a, seed_b): (u128, u128), i: usize) -> (u128, u128) {
+    let key = |seed: u128| {
+        let n = i as u64 + 1;
+        splitmix64(seed as u64, n) as u128 | (splitmix64((seed >> 64) as u64, n) as u128) << 64
+    };
+    (key(seed_a), key(seed_b))
+}
+
+/// The *n*-th output of the SplitMix64 generator seeded with *seed*, counting from one.
+fn splitmix64(seed: u64, n: u64) -> u64 {
+    let mut z = seed.wrapping_add(n.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl<H> Debug for SingleHasherBloomFilter<H>
+where
+    H: KeyedHasher,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "SeededBloomFilter{{{:?}}}", self.bitset)
     }
 }
 
-impl BloomFilter for SeededBloomFilter {
+/// One-line summary of the configuration and the estimates of the filter, see the
+/// implementation of KMBloomFilter.
+impl<H> Display for SingleHasherBloomFilter<H>
+where
+    H: KeyedHasher,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_summary(
+            f,
+            "SeededBloomFilter",
+            self.number_of_hashers,
+            &self.bitset,
+            self.approximate_element_count(),
+            self.approximate_current_false_positive_probability(),
+        )
+    }
+}
+
+/// Two bloom filters are equal if they have the same configuration and the same bits are set.
+///
+/// See the implementation of KMBloomFilter for details.
+impl<H> PartialEq for SingleHasherBloomFilter<H>
+where
+    H: KeyedHasher,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_configuration(other) && self.bitset == other.bitset
+    }
+}
+
+impl<H> Eq for SingleHasherBloomFilter<H> where H: KeyedHasher {}
+
+impl<H> BloomFilter for SingleHasherBloomFilter<H>
+where
+    H: KeyedHasher,
+{
     fn insert<T>(&mut self, data: &T)
     where
         T: Hash,
     {
         for i in 0..self.number_of_hashers {
-            self.bitset
-                .set(Self::index(i, self.bits_per_hasher, &data), true);
+            self.bitset.set(
+                Self::index(i, self.bits_per_hasher, self.seeds, &data),
+                true,
+            );
         }
     }
 
@@ -234,11 +962,43 @@ impl BloomFilter for SeededBloomFilter {
         T: Hash,
     {
         for i in 0..self.number_of_hashers {
-            if !self.bitset.get(Self::index(i, self.bits_per_hasher, &data)) {
+            if !self
+                .bitset
+                .get(Self::index(i, self.bits_per_hasher, self.seeds, &data))
+            {
                 return false;
             }
         }
 
-        return true;
+        true
+    }
+
+    fn insert_all<T, I>(&mut self, iter: I)
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        let batch_length = INSERT_BATCH_SIZE * self.number_of_hashers;
+        let mut indices = Vec::with_capacity(batch_length);
+        for data in iter {
+            indices.extend(
+                (0..self.number_of_hashers)
+                    .map(|i| Self::index(i, self.bits_per_hasher, self.seeds, &data)),
+            );
+            if indices.len() >= batch_length {
+                self.set_indices(&mut indices);
+            }
+        }
+        self.set_indices(&mut indices);
+    }
+}
+
+impl<T, H> Extend<T> for SingleHasherBloomFilter<H>
+where
+    T: Hash,
+    H: KeyedHasher,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.insert_all(iter);
     }
 }
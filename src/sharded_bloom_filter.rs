@@ -0,0 +1,288 @@
+use alloc::vec::Vec;
+use core::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+};
+
+use crate::{math, BloomFilter, HashedKey, KMBloomFilter};
+
+/// One logical bloom filter that is split into independent KMBloomFilter shards, e.g., because it
+/// is too large for a single allocation or a single machine.
+///
+/// Every element is routed to exactly one shard by its hash values, see *shard_index*. The routing
+/// only depends on the hash values of *H1* and *H2* and on the number of shards, so it is the same
+/// in every process if the hashers are stable (e.g., the hashers of StableBloomFilterDefault). This
+/// makes it possible to persist each shard on its own with *KMBloomFilter::write_to*, to load only
+/// some of the shards on a node, and to route keys to the node that holds their shard.
+///
+/// Sharded filters with the same number of shards and the same configuration can be merged shard
+/// by shard, in parallel with the *rayon* feature.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{
+///     BloomFilter,KMBloomFilter,ShardedBloomFilter,StableXxh3Hasher,StableXxh64Hasher
+/// };
+///
+/// # #[cfg(not(feature = "std"))]
+/// # fn main() {}
+/// # #[cfg(feature = "std")]
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut filter: ShardedBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+///         ShardedBloomFilter::new(8, 100_000, 0.001);
+///     filter.insert_all(0..100_000);
+///
+///     // Persist every shard on its own and reassemble the filter
+///     let mut files = Vec::new();
+///     for shard in filter.shards() {
+///         let mut bytes = Vec::new();
+///         shard.write_to(&mut bytes)?;
+///         files.push(bytes);
+///     }
+///     let shards = files
+///         .iter()
+///         .map(|bytes| KMBloomFilter::read_from(&bytes[..]))
+///         .collect::<Result<Vec<_>, _>>()?;
+///     let filter: ShardedBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+///         ShardedBloomFilter::from_shards(shards);
+///
+///     assert_eq!(true, filter.contains(&42));
+///     assert_eq!(true, filter.shards()[filter.shard_index(&42)].contains(&42));
+///     Ok(())
+/// }
+/// ```
+pub struct ShardedBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    shards: Vec<KMBloomFilter<H1, H2>>,
+}
+
+impl<H1, H2> ShardedBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Initialize a new instance of ShardedBloomFilter with *number_of_shards* shards that
+    /// together guarantee that the false positive rate is less than
+    /// *desired_false_positive_probability* for up to *desired_capacity* elements.
+    ///
+    /// The elements are not spread perfectly evenly, so each shard is created for its share of
+    /// the desired capacity plus three standard deviations of the number of elements it receives.
+    ///
+    /// # Panics
+    ///
+    /// Panics if number_of_shards == 0, desired_capacity == 0, or if
+    /// desired_false_positive_probability is not in the interval (0,1).
+    pub fn new(
+        number_of_shards: usize,
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self {
+        let desired_false_positive_probability = desired_false_positive_probability.into();
+        if number_of_shards == 0 {
+            panic!("a sharded bloom filter requires at least one shard");
+        }
+        let share = desired_capacity.div_ceil(number_of_shards);
+        let shard_capacity = share + math::ceil(3.0 * math::sqrt(share as f64)) as usize;
+        Self {
+            shards: (0..number_of_shards)
+                .map(|_| KMBloomFilter::new(shard_capacity, desired_false_positive_probability))
+                .collect(),
+        }
+    }
+
+    /// Initialize a new instance of ShardedBloomFilter with the given shards, e.g., shards that
+    /// have been read with *KMBloomFilter::read_from*.
+    ///
+    /// Elements are routed to shards by their position in *shards*, so the order of the shards
+    /// must be kept.
+    ///
+    /// # Panics
+    ///
+    /// Panics if *shards* is empty.
+    pub fn from_shards(shards: Vec<KMBloomFilter<H1, H2>>) -> Self {
+        if shards.is_empty() {
+            panic!("a sharded bloom filter requires at least one shard");
+        }
+        Self { shards }
+    }
+
+    /// Index of the shard that *data* is routed to.
+    pub fn shard_index<T>(&self, data: &T) -> usize
+    where
+        T: Hash,
+    {
+        self.shard_index_hashed(&self.shards[0].hash(data))
+    }
+
+    /// Index of the shard that the element *key* has been computed for is routed to.
+    ///
+    /// The shard is chosen by mixing both hashes of *key* with the finalizer of MurmurHash3, so
+    /// that it is independent of the bits of the element within the shard.
+    pub fn shard_index_hashed(&self, key: &HashedKey<H1, H2>) -> usize {
+        let (hash_a, hash_b) = key.hashes();
+        let mut hash = hash_a ^ hash_b.rotate_left(32);
+        hash = (hash ^ (hash >> 33)).wrapping_mul(0xff51_afd7_ed55_8ccd);
+        hash = (hash ^ (hash >> 33)).wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+        hash ^= hash >> 33;
+        // Map the hash to the shards without a division
+        ((hash as u128 * self.shards.len() as u128) >> 64) as usize
+    }
+
+    /// Number of shards of the filter.
+    pub fn number_of_shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// The shards of the filter, e.g., to persist them.
+    pub fn shards(&self) -> &[KMBloomFilter<H1, H2>] {
+        &self.shards
+    }
+
+    /// Return the shards of the filter.
+    pub fn into_shards(self) -> Vec<KMBloomFilter<H1, H2>> {
+        self.shards
+    }
+
+    /// Approximate number of elements stored in all shards.
+    pub fn approximate_element_count(&self) -> f64 {
+        self.shards
+            .iter()
+            .map(|shard| shard.approximate_element_count())
+            .sum()
+    }
+
+    /// Return the current approximate false positive probability, i.e., the average false positive
+    /// probability of the shards.
+    ///
+    /// The probability is given as a value in the interval [0,1]
+    pub fn approximate_current_false_positive_probability(&self) -> f64 {
+        self.shards
+            .iter()
+            .map(|shard| shard.approximate_current_false_positive_probability())
+            .sum::<f64>()
+            / self.shards.len() as f64
+    }
+
+    /// Insert all elements of 'other' into this filter by merging every shard with the
+    /// corresponding shard of 'other'.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of shards or the configuration of the shards of 'self' and 'other'
+    /// differ.
+    pub fn union_with(&mut self, other: &Self) {
+        self.check_shards(other);
+        for (shard, other) in self.shards.iter_mut().zip(other.shards.iter()) {
+            shard.union_with(other);
+        }
+    }
+
+    /// Like *union_with*, but merges the shards in parallel on the threads of the current rayon
+    /// thread pool.
+    ///
+    /// Only available with the *rayon* feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of shards or the configuration of the shards of 'self' and 'other'
+    /// differ.
+    #[cfg(feature = "rayon")]
+    pub fn par_union_with(&mut self, other: &Self) {
+        use rayon::iter::{
+            IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator,
+            ParallelIterator,
+        };
+
+        self.check_shards(other);
+        self.shards
+            .par_iter_mut()
+            .zip(other.shards.par_iter())
+            .for_each(|(shard, other)| shard.union_with(other));
+    }
+
+    /// Remove all elements from all shards.
+    pub fn clear(&mut self) {
+        for shard in self.shards.iter_mut() {
+            shard.clear();
+        }
+    }
+
+    fn check_shards(&self, other: &Self) {
+        if self.shards.len() != other.shards.len() {
+            panic!("unable to union sharded bloom filters with different configurations");
+        }
+    }
+}
+
+impl<H1, H2> Debug for ShardedBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ShardedBloomFilter{{shards: [")?;
+        for (i, shard) in self.shards.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{:?}", shard)?;
+        }
+        write!(f, "]}}")
+    }
+}
+
+impl<H1, H2> PartialEq for ShardedBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.shards == other.shards
+    }
+}
+
+impl<H1, H2> Eq for ShardedBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+}
+
+impl<H1, H2> BloomFilter for ShardedBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Insert data into its shard.
+    fn insert<T>(&mut self, data: &T)
+    where
+        T: Hash,
+    {
+        let key = self.shards[0].hash(data);
+        let index = self.shard_index_hashed(&key);
+        self.shards[index].insert_hashed(&key);
+    }
+
+    /// Check whether data is contained in its shard.
+    fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        let key = self.shards[0].hash(data);
+        self.shards[self.shard_index_hashed(&key)].contains_hashed(&key)
+    }
+}
+
+impl<T, H1, H2> Extend<T> for ShardedBloomFilter<H1, H2>
+where
+    T: Hash,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.insert_all(iter);
+    }
+}
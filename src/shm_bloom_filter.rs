@@ -0,0 +1,429 @@
+use std::{
+    ffi::CString,
+    fmt::{self, Debug},
+    fs::File,
+    hash::{Hash, Hasher},
+    io,
+    marker::PhantomData,
+    os::unix::io::FromRawFd,
+    slice,
+    sync::atomic::{AtomicU32, AtomicU64, Ordering},
+};
+
+use memmap2::{MmapOptions, MmapRaw};
+
+use crate::{
+    approximate_element_count, approximate_false_positive_probability,
+    file_format::{read_header, write_header, HEADER_LENGTH},
+    params::FilterParams,
+    Bitset, HashedKey, IndexLayout, KMBloomFilter, ReadFilterError,
+};
+
+/// Offset of the words of the bitset in the segment. The header of *KMBloomFilter::write_to*
+/// comes first, and the words start at the next cache line.
+const WORDS_OFFSET: usize = 64;
+
+const WORD_BITS: usize = 64;
+
+/// KMBloomFilter whose bits are stored in a named POSIX shared-memory segment, e.g., to let a
+/// writer daemon insert elements while reader processes query the same live filter, without
+/// sockets or copies of the filter.
+///
+/// *create* creates a new segment and *open* attaches to an existing one. Every handle, in every
+/// process, can insert and query concurrently: the bits are set and tested with atomic operations
+/// on the words of the segment, so insertions only need a shared reference, and an element is
+/// contained for every handle once its insertion has returned. A query that races with the
+/// insertion of the same element may not find it yet.
+///
+/// The segment starts with the header of *KMBloomFilter::write_to*, followed by the words of the
+/// bits in native byte order, so it can only be shared between processes on the same machine. The
+/// hashers *H1* and *H2* must be the same in all processes, and their hash values must not change
+/// between processes (e.g., the hashers of StableBloomFilterDefault). Otherwise, *open* fails with
+/// *ReadFilterError::HasherMismatch*.
+///
+/// The segment outlives the handles and the processes that created it, until it is removed with
+/// *unlink*. It must only be accessed through ShmBloomFilter.
+///
+/// ShmBloomFilter is only available on unix with the *shm* feature.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{ShmBloomFilter,StableXxh3Hasher,StableXxh64Hasher};
+///
+/// type SharedFilter = ShmBloomFilter<StableXxh3Hasher, StableXxh64Hasher>;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let name = format!("/bloom_filter_simple_doc_example_{}", std::process::id());
+///
+///     // Writer process
+///     let writer = SharedFilter::create(&name, 1_000, 0.001)?;
+///     writer.insert(&"Hello!");
+///
+///     // Reader process
+///     let reader = SharedFilter::open(&name)?;
+///     assert_eq!(true, reader.contains(&"Hello!"));
+///     writer.insert(&"Goodbye!");
+///     assert_eq!(true, reader.contains(&"Goodbye!"));
+///
+///     SharedFilter::unlink(&name)?;
+///     Ok(())
+/// }
+/// ```
+pub struct ShmBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    number_of_hashers: usize,
+    bits_per_hasher: usize,
+    name: String,
+    mmap: MmapRaw,
+    // Phantom data for saving which concrete Hasher types are used
+    _phantom: PhantomData<fn() -> (H1, H2)>,
+}
+
+impl<H1, H2> ShmBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Create a new, empty filter for up to *desired_capacity* elements with a false positive
+    /// probability of less than *desired_false_positive_probability* in the shared-memory segment
+    /// *name*, e.g., `/sessions`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind *AlreadyExists* if the segment already exists, and the errors of
+    /// creating and mapping the segment otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics for the arguments *KMBloomFilter::new* panics for.
+    pub fn create(
+        name: &str,
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> io::Result<Self> {
+        let params =
+            FilterParams::for_capacity(desired_capacity, desired_false_positive_probability);
+        Self::create_segment(name, params.number_of_hashers(), params.bits_per_hasher())
+    }
+
+    /// Create a filter in the shared-memory segment *name* with the configuration and the bits of
+    /// *filter*, e.g., to share a filter that has been loaded from a checkpoint.
+    ///
+    /// # Errors
+    ///
+    /// See *create*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if *filter* has the index layout *IndexLayout::Global*, since a shared filter
+    /// addresses the bits of every hasher separately.
+    pub fn create_from(name: &str, filter: &KMBloomFilter<H1, H2>) -> io::Result<Self> {
+        if filter.index_layout() != IndexLayout::Partitioned {
+            panic!("unable to share a k-m bloom filter with the global index layout");
+        }
+        let shared = Self::create_segment(
+            name,
+            filter.hasher_count(),
+            filter.bit_count() / filter.hasher_count(),
+        )?;
+        for index in filter.bitset().iter_ones() {
+            shared.set(index);
+        }
+        Ok(shared)
+    }
+
+    /// Attach to the filter in the existing shared-memory segment *name*.
+    ///
+    /// # Errors
+    ///
+    /// Returns *ReadFilterError::InvalidMagic* if the segment does not contain a filter, e.g.,
+    /// because its creator has not initialized it yet, *ReadFilterError::HasherMismatch* if the
+    /// filter has been created with different hashers, and *ReadFilterError::InvalidLength* if the
+    /// length of the segment does not match the header. Errors of opening and mapping the segment
+    /// are forwarded as *ReadFilterError::Io*.
+    pub fn open(name: &str) -> Result<Self, ReadFilterError> {
+        let file = shm_open(name, libc::O_RDWR)?;
+        let length = file.metadata()?.len() as usize;
+        if length < WORDS_OFFSET {
+            return Err(ReadFilterError::InvalidMagic);
+        }
+        let mmap = MmapOptions::new().map_raw(&file)?;
+
+        // Synchronizes with the store of the magic bytes in *create_segment*, which publishes the
+        // rest of the header. Safety: the mapping is page-aligned and longer than the header
+        let magic = unsafe { &*(mmap.as_ptr() as *const AtomicU32) }.load(Ordering::Acquire);
+        let mut header = [0u8; HEADER_LENGTH];
+        header[..4].copy_from_slice(&magic.to_ne_bytes());
+        // Safety: the header is only written before the magic bytes are published
+        header[4..].copy_from_slice(unsafe {
+            slice::from_raw_parts(mmap.as_ptr().add(4), HEADER_LENGTH - 4)
+        });
+        let (number_of_hashers, bits_per_hasher) = read_header::<H1, H2, _>(&mut &header[..])?;
+
+        let expected = segment_length(number_of_hashers * bits_per_hasher);
+        if length != expected {
+            return Err(ReadFilterError::InvalidLength {
+                expected,
+                found: length,
+            });
+        }
+        Ok(Self {
+            number_of_hashers,
+            bits_per_hasher,
+            name: name.to_string(),
+            mmap,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Remove the shared-memory segment *name*. Processes that are attached to it keep their
+    /// mapping, but the segment can no longer be opened.
+    pub fn unlink(name: &str) -> io::Result<()> {
+        let name = segment_name(name)?;
+        // Safety: the name is a valid C string
+        if unsafe { libc::shm_unlink(name.as_ptr()) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Name of the shared-memory segment of the filter.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Insert data into the filter.
+    ///
+    /// See *BloomFilter::insert* for details.
+    pub fn insert<T>(&self, data: &T)
+    where
+        T: Hash,
+    {
+        let (hash_a, hash_b) = KMBloomFilter::<H1, H2>::generate_hashes(data);
+        self.insert_hash64(hash_a, hash_b);
+    }
+
+    /// Insert the element *key* has been computed for.
+    ///
+    /// See *KMBloomFilter::hash* for how to compute a key.
+    pub fn insert_hashed(&self, key: &HashedKey<H1, H2>) {
+        let (hash_a, hash_b) = key.hashes();
+        self.insert_hash64(hash_a, hash_b);
+    }
+
+    /// Insert an element with the base hashes *hash_a* and *hash_b*.
+    ///
+    /// See *KMBloomFilter::insert_hash64* for details.
+    pub fn insert_hash64(&self, hash_a: u64, hash_b: u64) {
+        for i in 0..self.number_of_hashers {
+            self.set(KMBloomFilter::<H1, H2>::index(
+                i,
+                self.bits_per_hasher,
+                hash_a,
+                hash_b,
+            ));
+        }
+    }
+
+    /// Check whether data is contained in the bloom filter.
+    ///
+    /// See *BloomFilter::contains* for details.
+    pub fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        let (hash_a, hash_b) = KMBloomFilter::<H1, H2>::generate_hashes(data);
+        self.contains_hash64(hash_a, hash_b)
+    }
+
+    /// Check whether the element *key* has been computed for is contained in the bloom filter.
+    ///
+    /// See *KMBloomFilter::hash* for how to compute a key.
+    pub fn contains_hashed(&self, key: &HashedKey<H1, H2>) -> bool {
+        let (hash_a, hash_b) = key.hashes();
+        self.contains_hash64(hash_a, hash_b)
+    }
+
+    /// Check whether an element with the base hashes *hash_a* and *hash_b* is contained in the
+    /// bloom filter.
+    ///
+    /// See *KMBloomFilter::insert_hash64* for details.
+    pub fn contains_hash64(&self, hash_a: u64, hash_b: u64) -> bool {
+        (0..self.number_of_hashers).all(|i| {
+            self.get(KMBloomFilter::<H1, H2>::index(
+                i,
+                self.bits_per_hasher,
+                hash_a,
+                hash_b,
+            ))
+        })
+    }
+
+    /// Approximate number of elements stored.
+    ///
+    /// In contrast to *contains*, this reads all bits of the filter.
+    pub fn approximate_element_count(&self) -> f64 {
+        approximate_element_count(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.count_ones(),
+        )
+    }
+
+    /// Return the current approximate false positive probability which depends on the current
+    /// number of elements in the filter.
+    ///
+    /// In contrast to *contains*, this reads all bits of the filter.
+    pub fn approximate_current_false_positive_probability(&self) -> f64 {
+        approximate_false_positive_probability(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.approximate_element_count(),
+        )
+    }
+
+    /// Fraction of bits that are set, given as a value in the interval [0,1].
+    ///
+    /// In contrast to *contains*, this reads all bits of the filter.
+    pub fn fill_ratio(&self) -> f64 {
+        self.count_ones() as f64 / self.bit_count() as f64
+    }
+
+    /// Total number of bits of the filter.
+    pub fn bit_count(&self) -> usize {
+        self.number_of_hashers * self.bits_per_hasher
+    }
+
+    /// Number of hash functions used for every element.
+    pub fn hasher_count(&self) -> usize {
+        self.number_of_hashers
+    }
+
+    /// Return a KMBloomFilter with a copy of the current bits, e.g., to checkpoint the filter with
+    /// *KMBloomFilter::write_to*.
+    ///
+    /// Elements that are inserted concurrently may be copied partially, i.e., they may or may not
+    /// be contained in the copy.
+    pub fn to_filter(&self) -> KMBloomFilter<H1, H2> {
+        let words = self
+            .words()
+            .iter()
+            .map(|word| word.load(Ordering::Relaxed))
+            .collect();
+        KMBloomFilter::from_bitset(
+            Bitset::from_words(words, self.bit_count()),
+            self.number_of_hashers,
+        )
+    }
+
+    fn create_segment(
+        name: &str,
+        number_of_hashers: usize,
+        bits_per_hasher: usize,
+    ) -> io::Result<Self> {
+        let file = shm_open(name, libc::O_RDWR | libc::O_CREAT | libc::O_EXCL)?;
+        // Remove the segment again if it cannot be initialized
+        let initialize = || {
+            file.set_len(segment_length(number_of_hashers * bits_per_hasher) as u64)?;
+            let mmap = MmapOptions::new().map_raw(&file)?;
+            let mut header = Vec::with_capacity(HEADER_LENGTH);
+            write_header::<H1, H2, _>(
+                &mut header,
+                number_of_hashers,
+                bits_per_hasher,
+                IndexLayout::Partitioned,
+            )?;
+            // Safety: the segment is new and nobody accesses the header before the magic bytes
+            // are published
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    header[4..].as_ptr(),
+                    mmap.as_mut_ptr().add(4),
+                    HEADER_LENGTH - 4,
+                );
+                let magic = u32::from_ne_bytes([header[0], header[1], header[2], header[3]]);
+                (*(mmap.as_mut_ptr() as *const AtomicU32)).store(magic, Ordering::Release);
+            }
+            Ok(mmap)
+        };
+        match initialize() {
+            Ok(mmap) => Ok(Self {
+                number_of_hashers,
+                bits_per_hasher,
+                name: name.to_string(),
+                mmap,
+                _phantom: PhantomData,
+            }),
+            Err(error) => {
+                let _ = Self::unlink(name);
+                Err(error)
+            }
+        }
+    }
+
+    fn words(&self) -> &[AtomicU64] {
+        // Safety: the mapping is page-aligned, at least as long as the words, and lives as long as
+        // self. All processes only access the words atomically.
+        unsafe {
+            slice::from_raw_parts(
+                self.mmap.as_ptr().add(WORDS_OFFSET) as *const AtomicU64,
+                self.bit_count().div_ceil(WORD_BITS),
+            )
+        }
+    }
+
+    fn set(&self, index: usize) {
+        // Bits are only ever set, so the order of the updates does not matter
+        self.words()[index / WORD_BITS].fetch_or(1u64 << (index % WORD_BITS), Ordering::Relaxed);
+    }
+
+    fn get(&self, index: usize) -> bool {
+        let mask = 1u64 << (index % WORD_BITS);
+        self.words()[index / WORD_BITS].load(Ordering::Relaxed) & mask == mask
+    }
+
+    fn count_ones(&self) -> usize {
+        self.words()
+            .iter()
+            .map(|word| word.load(Ordering::Relaxed).count_ones() as usize)
+            .sum()
+    }
+}
+
+impl<H1, H2> Debug for ShmBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ShmBloomFilter{{name: {:?}, number_of_hashers: {}, bits_per_hasher: {}}}",
+            self.name, self.number_of_hashers, self.bits_per_hasher
+        )
+    }
+}
+
+/// Size of the segment of a filter with *bit_count* bits in bytes.
+fn segment_length(bit_count: usize) -> usize {
+    WORDS_OFFSET + bit_count.div_ceil(WORD_BITS) * (WORD_BITS / 8)
+}
+
+fn segment_name(name: &str) -> io::Result<CString> {
+    CString::new(name).map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))
+}
+
+/// Open the shared-memory segment *name* with the flags *flags* as a file.
+fn shm_open(name: &str, flags: libc::c_int) -> io::Result<File> {
+    let name = segment_name(name)?;
+    // Safety: the name is a valid C string
+    let fd = unsafe { libc::shm_open(name.as_ptr(), flags, 0o600 as libc::c_uint) };
+    if fd == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    // Safety: the file descriptor has just been opened and is not owned by anything else
+    Ok(unsafe { File::from_raw_fd(fd) })
+}
@@ -0,0 +1,183 @@
+//! Word-wise operations on the bits of a Bitset that also count the set bits of the result.
+//!
+//! Combining two filters and counting the ones of the result are fused into a single pass over
+//! the words. With the *simd* feature, the operations use AVX2 on x86_64 if the CPU supports it,
+//! which is detected at runtime. Otherwise, they fall back to scalar code.
+
+/// Number of set bits in *words*.
+pub(crate) fn count_ones(words: &[u64]) -> usize {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    {
+        if std::is_x86_feature_detected!("avx2") {
+            // Safety: the CPU supports AVX2
+            return unsafe { avx2::count_ones(words) };
+        }
+    }
+    scalar_count_ones(words)
+}
+
+/// Set all bits of *words* that are set in *other* and return the number of set bits of the
+/// result.
+pub(crate) fn union_with(words: &mut [u64], other: &[u64]) -> usize {
+    assert_eq!(words.len(), other.len());
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    {
+        if std::is_x86_feature_detected!("avx2") {
+            // Safety: the CPU supports AVX2, and both slices have the same length
+            return unsafe { avx2::union_with(words, other) };
+        }
+    }
+    scalar_combine(words, other, |a, b| a | b)
+}
+
+/// Clear all bits of *words* that are not set in *other* and return the number of set bits of the
+/// result.
+pub(crate) fn intersect_with(words: &mut [u64], other: &[u64]) -> usize {
+    assert_eq!(words.len(), other.len());
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    {
+        if std::is_x86_feature_detected!("avx2") {
+            // Safety: the CPU supports AVX2, and both slices have the same length
+            return unsafe { avx2::intersect_with(words, other) };
+        }
+    }
+    scalar_combine(words, other, |a, b| a & b)
+}
+
+fn scalar_count_ones(words: &[u64]) -> usize {
+    words.iter().map(|w| w.count_ones() as usize).sum()
+}
+
+fn scalar_combine<F>(words: &mut [u64], other: &[u64], combine: F) -> usize
+where
+    F: Fn(u64, u64) -> u64,
+{
+    let mut ones = 0;
+    for (a, &b) in words.iter_mut().zip(other.iter()) {
+        *a = combine(*a, b);
+        ones += a.count_ones() as usize;
+    }
+    ones
+}
+
+/// AVX2 implementations, which process four words at a time and count the set bits with the
+/// nibble lookup of Muła et al.:
+///
+/// > Wojciech Muła, Nathan Kurz, and Daniel Lemire. 2018. Faster Population Counts Using AVX2
+/// > Instructions. The Computer Journal 61, 1, 111–120. DOI: [https://doi.org/10.1093/comjnl/bxx046](https://doi.org/10.1093/comjnl/bxx046)
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod avx2 {
+    use core::arch::x86_64::*;
+
+    use super::{scalar_combine, scalar_count_ones};
+
+    /// Number of words in a 256-bit vector.
+    const LANES: usize = 4;
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn count_ones(words: &[u64]) -> usize {
+        let chunks = words.len() / LANES;
+        let mut total = _mm256_setzero_si256();
+        for chunk in 0..chunks {
+            let vector = _mm256_loadu_si256(words.as_ptr().add(chunk * LANES) as *const __m256i);
+            total = _mm256_add_epi64(total, popcount(vector));
+        }
+        sum(total) + scalar_count_ones(&words[chunks * LANES..])
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn union_with(words: &mut [u64], other: &[u64]) -> usize {
+        let chunks = words.len() / LANES;
+        let mut total = _mm256_setzero_si256();
+        for chunk in 0..chunks {
+            let a = words.as_mut_ptr().add(chunk * LANES) as *mut __m256i;
+            let b = other.as_ptr().add(chunk * LANES) as *const __m256i;
+            let result = _mm256_or_si256(_mm256_loadu_si256(a), _mm256_loadu_si256(b));
+            _mm256_storeu_si256(a, result);
+            total = _mm256_add_epi64(total, popcount(result));
+        }
+        let rest = chunks * LANES;
+        sum(total) + scalar_combine(&mut words[rest..], &other[rest..], |a, b| a | b)
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn intersect_with(words: &mut [u64], other: &[u64]) -> usize {
+        let chunks = words.len() / LANES;
+        let mut total = _mm256_setzero_si256();
+        for chunk in 0..chunks {
+            let a = words.as_mut_ptr().add(chunk * LANES) as *mut __m256i;
+            let b = other.as_ptr().add(chunk * LANES) as *const __m256i;
+            let result = _mm256_and_si256(_mm256_loadu_si256(a), _mm256_loadu_si256(b));
+            _mm256_storeu_si256(a, result);
+            total = _mm256_add_epi64(total, popcount(result));
+        }
+        let rest = chunks * LANES;
+        sum(total) + scalar_combine(&mut words[rest..], &other[rest..], |a, b| a & b)
+    }
+
+    /// Number of set bits of each 64-bit lane of *vector*.
+    #[target_feature(enable = "avx2")]
+    unsafe fn popcount(vector: __m256i) -> __m256i {
+        let lookup = _mm256_setr_epi8(
+            0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4, 0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2,
+            3, 3, 4,
+        );
+        let low_mask = _mm256_set1_epi8(0x0f);
+        let low = _mm256_and_si256(vector, low_mask);
+        let high = _mm256_and_si256(_mm256_srli_epi16(vector, 4), low_mask);
+        let bytes = _mm256_add_epi8(
+            _mm256_shuffle_epi8(lookup, low),
+            _mm256_shuffle_epi8(lookup, high),
+        );
+        // Sum the counts of the eight bytes of each lane
+        _mm256_sad_epu8(bytes, _mm256_setzero_si256())
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn sum(vector: __m256i) -> usize {
+        let mut lanes = [0u64; LANES];
+        _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, vector);
+        lanes.iter().sum::<u64>() as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Words of various lengths, so that both the vectorized part and the remainder are covered.
+    fn words(length: usize, seed: u64) -> Vec<u64> {
+        let mut state = seed;
+        (0..length)
+            .map(|_| {
+                // xorshift64
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state
+            })
+            .collect()
+    }
+
+    #[test]
+    fn operations_match_scalar_implementation() {
+        for length in 0..40 {
+            let a = words(length, 0x9e37_79b9_7f4a_7c15 + length as u64);
+            let b = words(length, 0x2545_f491_4f6c_dd1d + length as u64);
+            assert_eq!(scalar_count_ones(&a), count_ones(&a));
+
+            let mut union = a.clone();
+            let ones = union_with(&mut union, &b);
+            let expected: Vec<u64> = a.iter().zip(b.iter()).map(|(a, b)| a | b).collect();
+            assert_eq!(expected, union);
+            assert_eq!(scalar_count_ones(&expected), ones);
+
+            let mut intersection = a.clone();
+            let ones = intersect_with(&mut intersection, &b);
+            let expected: Vec<u64> = a.iter().zip(b.iter()).map(|(a, b)| a & b).collect();
+            assert_eq!(expected, intersection);
+            assert_eq!(scalar_count_ones(&expected), ones);
+        }
+        assert_eq!(64 * 9, count_ones(&[u64::MAX; 9]));
+    }
+}
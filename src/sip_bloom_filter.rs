@@ -0,0 +1,267 @@
+use alloc::{vec, vec::Vec};
+use core::{
+    convert::TryFrom,
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+};
+
+use siphasher::sip::SipHasher13;
+
+/// Modulus of the hash values of the third and all further hash functions of the *bloomfilter*
+/// crate, the largest prime below 2^64.
+const HASH_MODULUS: u64 = 0xffff_ffff_ffff_ffc5;
+
+/// Error returned by *SipBloomFilter::from_existing* if the parameters do not describe a valid
+/// filter of the *bloomfilter* crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidSipBloomFilter;
+
+impl Display for InvalidSipBloomFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid bloomfilter crate bitmap or parameters")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidSipBloomFilter {}
+
+/// Read-only bloom filter with the bits and the hashing scheme of a filter of the
+/// [*bloomfilter*](https://crates.io/crates/bloomfilter) crate, version 1, e.g., to query filters
+/// that another service has built with that crate.
+///
+/// The *bloomfilter* crate hashes every element with two SipHash-1-3 hashers with the keys
+/// *sip_keys*. The first two hash functions are the hash values *h0* and *h1* themselves, and the
+/// hash function *k* ≥ 2 is `(h0 + k * h1) % 0xffff_ffff_ffff_ffc5` with wrapping arithmetic. The
+/// bit of a hash value is the hash value modulo the number of bits, and the bits are stored
+/// starting with the most significant bit of every byte.
+///
+/// Import a filter with the values of *Bloom::bitmap*, *Bloom::number_of_bits*,
+/// *Bloom::number_of_hash_functions*, and *Bloom::sip_keys*, and export one with *from_elements*
+/// and the accessors of the same names, whose values *Bloom::from_existing* accepts.
+///
+/// Elements are hashed with their *Hash* implementation, so they are only found if they hash to
+/// the same bytes as in the other process, e.g., strings and byte slices. The hash values of
+/// integers depend on the byte order of the platform, and those of *usize* also on its width.
+///
+/// Only available with the *interop-bloomfilter* feature.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::SipBloomFilter;
+///
+/// fn main() {
+///     let sip_keys = [(1, 2), (3, 4)];
+///     // Export a filter, e.g., to load it with Bloom::from_existing of the bloomfilter crate
+///     let exported = SipBloomFilter::from_elements(8_192, 7, sip_keys, &["Hello", "World"]);
+///     let bitmap = exported.bitmap().to_vec();
+///
+///     // Import the values of Bloom::bitmap, Bloom::number_of_bits,
+///     // Bloom::number_of_hash_functions, and Bloom::sip_keys
+///     let filter = SipBloomFilter::from_existing(&bitmap, 8_192, 7, sip_keys).unwrap();
+///     assert_eq!(true, filter.contains("Hello"));
+///     assert_eq!(false, filter.contains("Goodbye"));
+/// }
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct SipBloomFilter {
+    bitmap: Vec<u8>,
+    number_of_bits: u64,
+    number_of_hash_functions: u32,
+    sip_keys: [(u64, u64); 2],
+}
+
+impl SipBloomFilter {
+    /// Initialize a filter from the bits and the parameters of a filter of the *bloomfilter*
+    /// crate, i.e., the values of *Bloom::bitmap*, *Bloom::number_of_bits*,
+    /// *Bloom::number_of_hash_functions*, and *Bloom::sip_keys*.
+    ///
+    /// Returns *InvalidSipBloomFilter* if number_of_bits or number_of_hash_functions is 0, or if
+    /// bitmap does not have exactly one byte for every 8 bits.
+    pub fn from_existing(
+        bitmap: &[u8],
+        number_of_bits: u64,
+        number_of_hash_functions: u32,
+        sip_keys: [(u64, u64); 2],
+    ) -> Result<Self, InvalidSipBloomFilter> {
+        if number_of_bits == 0
+            || number_of_hash_functions == 0
+            || bitmap.len() as u64 != number_of_bits.div_ceil(8)
+        {
+            return Err(InvalidSipBloomFilter);
+        }
+        Ok(Self {
+            bitmap: bitmap.to_vec(),
+            number_of_bits,
+            number_of_hash_functions,
+            sip_keys,
+        })
+    }
+
+    /// Build a filter with the given parameters that contains the elements of iter, e.g., to
+    /// export it to a service that loads it with *Bloom::from_existing* of the *bloomfilter*
+    /// crate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if number_of_bits or number_of_hash_functions is 0, or if the filter would need
+    /// more than *usize::MAX* bytes.
+    pub fn from_elements<T, I>(
+        number_of_bits: u64,
+        number_of_hash_functions: u32,
+        sip_keys: [(u64, u64); 2],
+        iter: I,
+    ) -> Self
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        assert!(
+            number_of_bits > 0,
+            "number_of_bits of a SipBloomFilter must be greater than 0"
+        );
+        assert!(
+            number_of_hash_functions > 0,
+            "number_of_hash_functions of a SipBloomFilter must be greater than 0"
+        );
+        let bytes = usize::try_from(number_of_bits.div_ceil(8))
+            .expect("the bitmap of a SipBloomFilter must fit into memory");
+        let mut filter = Self {
+            bitmap: vec![0; bytes],
+            number_of_bits,
+            number_of_hash_functions,
+            sip_keys,
+        };
+        for element in iter {
+            let (h0, h1) = filter.hash(&element);
+            for k in 0..number_of_hash_functions {
+                let index = filter.bit_of(k, h0, h1);
+                filter.bitmap[(index / 8) as usize] |= 0x80 >> (index % 8);
+            }
+        }
+        filter
+    }
+
+    /// Check whether data is contained in the filter.
+    ///
+    /// The result can be a false positive, but never a false negative for elements that hash to
+    /// the same bytes as in the process that has built the filter.
+    pub fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash + ?Sized,
+    {
+        let (h0, h1) = self.hash(data);
+        (0..self.number_of_hash_functions).all(|k| {
+            let index = self.bit_of(k, h0, h1);
+            self.bitmap[(index / 8) as usize] & (0x80 >> (index % 8)) != 0
+        })
+    }
+
+    /// The bits of the filter in the layout of *Bloom::bitmap*, starting with the most
+    /// significant bit of the first byte.
+    pub fn bitmap(&self) -> &[u8] {
+        &self.bitmap
+    }
+
+    /// Number of bits of the filter.
+    pub fn number_of_bits(&self) -> u64 {
+        self.number_of_bits
+    }
+
+    /// Number of hash functions of the filter.
+    pub fn number_of_hash_functions(&self) -> u32 {
+        self.number_of_hash_functions
+    }
+
+    /// Keys of the two SipHash-1-3 hashers of the filter.
+    pub fn sip_keys(&self) -> [(u64, u64); 2] {
+        self.sip_keys
+    }
+
+    /// Hash values of the two SipHash-1-3 hashers of data.
+    fn hash<T>(&self, data: &T) -> (u64, u64)
+    where
+        T: Hash + ?Sized,
+    {
+        let [(key0, key1), (key2, key3)] = self.sip_keys;
+        let mut hasher = SipHasher13::new_with_keys(key0, key1);
+        data.hash(&mut hasher);
+        let h0 = hasher.finish();
+        let mut hasher = SipHasher13::new_with_keys(key2, key3);
+        data.hash(&mut hasher);
+        (h0, hasher.finish())
+    }
+
+    /// Bit of the hash function k for the hash values h0 and h1.
+    fn bit_of(&self, k: u32, h0: u64, h1: u64) -> u64 {
+        let hash = match k {
+            0 => h0,
+            1 => h1,
+            _ => h0.wrapping_add((k as u64).wrapping_mul(h1)) % HASH_MODULUS,
+        };
+        hash % self.number_of_bits
+    }
+}
+
+impl Debug for SipBloomFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "SipBloomFilter{{number_of_bits: {}, number_of_hash_functions: {}}}",
+            self.number_of_bits, self.number_of_hash_functions
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bit of the hash function k of the *bloomfilter* crate, written out with SipHasher13 of the
+    /// *siphasher* crate that the *bloomfilter* crate uses.
+    fn reference_bit(data: &str, k: u64, bits: u64, sip_keys: [(u64, u64); 2]) -> u64 {
+        let hashes: Vec<u64> = sip_keys
+            .iter()
+            .map(|&(key0, key1)| {
+                let mut hasher = SipHasher13::new_with_keys(key0, key1);
+                data.hash(&mut hasher);
+                hasher.finish()
+            })
+            .collect();
+        let hash = if k < 2 {
+            hashes[k as usize]
+        } else {
+            ((hashes[0] as u128 + k as u128 * hashes[1] as u128) as u64) % HASH_MODULUS
+        };
+        hash % bits
+    }
+
+    #[test]
+    fn bits_match_reference_scheme() {
+        let sip_keys = [(0x0706_0504_0302_0100, 0x0f0e_0d0c_0b0a_0908), (17, 42)];
+        let filter = SipBloomFilter::from_elements(1_000, 9, sip_keys, ["interop"]);
+        let mut bitmap = vec![0u8; 125];
+        for k in 0..9 {
+            let index = reference_bit("interop", k, 1_000, sip_keys);
+            bitmap[(index / 8) as usize] |= 1 << (7 - index % 8);
+        }
+        assert_eq!(bitmap, filter.bitmap());
+    }
+
+    #[test]
+    fn from_existing_validates_parameters() {
+        let sip_keys = [(1, 2), (3, 4)];
+        assert!(SipBloomFilter::from_existing(&[0; 2], 9, 3, sip_keys).is_ok());
+        assert_eq!(
+            Err(InvalidSipBloomFilter),
+            SipBloomFilter::from_existing(&[0; 2], 17, 3, sip_keys)
+        );
+        assert_eq!(
+            Err(InvalidSipBloomFilter),
+            SipBloomFilter::from_existing(&[0; 2], 16, 0, sip_keys)
+        );
+        assert_eq!(
+            Err(InvalidSipBloomFilter),
+            SipBloomFilter::from_existing(&[], 0, 3, sip_keys)
+        );
+    }
+}
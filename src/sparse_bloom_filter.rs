@@ -0,0 +1,276 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    mem,
+};
+
+use crate::{
+    approximate_element_count, approximate_false_positive_probability, bitset::Bitset,
+    params::FilterParams, BloomFilter, KMBloomFilter,
+};
+
+/// Number of low bits of a bit index that are stored in a container. The remaining high bits
+/// select the container.
+const CONTAINER_BITS: u32 = 16;
+
+/// Bloom filter with the bits of a KMBloomFilter that only stores the indices of its set bits
+/// while few bits are set, e.g., for many filters that are sized for millions of elements but
+/// mostly hold a few hundred.
+///
+/// The set bits are stored like the array containers of a roaring bitmap: the bits are split into
+/// containers of 2^16 bits, and every container that has a set bit stores the sorted lower 16 bits
+/// of their indices, which takes 2 bytes per set bit. Once this takes as much memory as the bits
+/// of the filter, i.e., once 1/16 of the bits are set, the filter converts itself to a
+/// KMBloomFilter and stays dense from then on.
+///
+/// The bits of the filter are the same as the bits of a KMBloomFilter with the same hashers and
+/// configuration, so *into_dense* returns a filter that can be combined with other KMBloomFilters
+/// or written with *KMBloomFilter::write_to*. Inserting and querying a sparse filter searches the
+/// containers, which is slower than testing the bits of a dense filter.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{BloomFilter,SparseBloomFilter,StableXxh3Hasher,StableXxh64Hasher};
+///
+/// fn main() {
+///     // Sized for a million elements, but only needs memory for the elements it holds
+///     let mut filter: SparseBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+///         SparseBloomFilter::new(1_000_000, 0.001);
+///     filter.insert_all(0..500);
+///     assert_eq!(true, filter.is_sparse());
+///     assert_eq!(true, filter.contains(&42));
+///
+///     // The filter converts itself to dense bits as it fills up
+///     filter.insert_all(500..200_000);
+///     assert_eq!(false, filter.is_sparse());
+///     assert_eq!(true, filter.contains(&42));
+/// }
+/// ```
+pub struct SparseBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    number_of_hashers: usize,
+    bits_per_hasher: usize,
+    bits: Bits<H1, H2>,
+}
+
+enum Bits<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    Sparse(SparseBits),
+    Dense(KMBloomFilter<H1, H2>),
+}
+
+impl<H1, H2> SparseBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Initialize a new, sparse instance of SparseBloomFilter with the configuration of
+    /// *KMBloomFilter::new*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if desired_capacity == 0 or if desired_false_positive_probability is not in the
+    /// interval (0,1).
+    pub fn new(
+        desired_capacity: usize,
+        desired_false_positive_probability: impl Into<f64>,
+    ) -> Self {
+        let params =
+            FilterParams::for_capacity(desired_capacity, desired_false_positive_probability);
+        Self {
+            number_of_hashers: params.number_of_hashers(),
+            bits_per_hasher: params.bits_per_hasher(),
+            bits: Bits::Sparse(SparseBits::default()),
+        }
+    }
+
+    /// Whether the filter still stores the indices of its set bits rather than all bits.
+    pub fn is_sparse(&self) -> bool {
+        matches!(self.bits, Bits::Sparse(_))
+    }
+
+    /// Total number of bits of the filter, regardless of how many of them are stored.
+    pub fn bit_count(&self) -> usize {
+        self.number_of_hashers * self.bits_per_hasher
+    }
+
+    /// Number of hash functions used for every element.
+    pub fn hasher_count(&self) -> usize {
+        self.number_of_hashers
+    }
+
+    /// Number of set bits.
+    pub fn count_ones(&self) -> usize {
+        match &self.bits {
+            Bits::Sparse(bits) => bits.ones,
+            Bits::Dense(filter) => filter.bitset().count_ones(),
+        }
+    }
+
+    /// Approximate number of elements stored (see *KMBloomFilter::approximate_element_count*).
+    pub fn approximate_element_count(&self) -> f64 {
+        approximate_element_count(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.count_ones(),
+        )
+    }
+
+    /// Return the current approximate false positive probability which depends on the current
+    /// number of elements in the filter.
+    pub fn approximate_current_false_positive_probability(&self) -> f64 {
+        approximate_false_positive_probability(
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.approximate_element_count(),
+        )
+    }
+
+    /// Return the filter as KMBloomFilter, converting a sparse filter to dense bits.
+    pub fn into_dense(self) -> KMBloomFilter<H1, H2> {
+        match self.bits {
+            Bits::Sparse(bits) => bits.to_dense(self.number_of_hashers, self.bits_per_hasher),
+            Bits::Dense(filter) => filter,
+        }
+    }
+
+    /// Convert the filter to dense bits if the set bits take as much memory as all bits.
+    fn convert_if_dense(&mut self) {
+        let bit_count = self.bit_count();
+        let bits = match &mut self.bits {
+            Bits::Sparse(bits) if bits.ones * 16 >= bit_count => mem::take(bits),
+            _ => return,
+        };
+        self.bits = Bits::Dense(bits.to_dense(self.number_of_hashers, self.bits_per_hasher));
+    }
+}
+
+impl<H1, H2> Debug for SparseBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.bits {
+            Bits::Sparse(bits) => write!(
+                f,
+                "SparseBloomFilter{{sparse: {:?}}}",
+                bits.iter_ones().collect::<Vec<_>>()
+            ),
+            Bits::Dense(filter) => write!(f, "SparseBloomFilter{{dense: {:?}}}", filter),
+        }
+    }
+}
+
+impl<H1, H2> BloomFilter for SparseBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Insert data into the filter, and convert the filter to dense bits if it is no longer sparse.
+    fn insert<T>(&mut self, data: &T)
+    where
+        T: Hash,
+    {
+        match &mut self.bits {
+            Bits::Sparse(bits) => {
+                let (hash_a, hash_b) = KMBloomFilter::<H1, H2>::generate_hashes(data);
+                for i in 0..self.number_of_hashers {
+                    bits.set(KMBloomFilter::<H1, H2>::index(
+                        i,
+                        self.bits_per_hasher,
+                        hash_a,
+                        hash_b,
+                    ));
+                }
+            }
+            Bits::Dense(filter) => filter.insert(data),
+        }
+        self.convert_if_dense();
+    }
+
+    fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        match &self.bits {
+            Bits::Sparse(bits) => {
+                let (hash_a, hash_b) = KMBloomFilter::<H1, H2>::generate_hashes(data);
+                (0..self.number_of_hashers).all(|i| {
+                    bits.get(KMBloomFilter::<H1, H2>::index(
+                        i,
+                        self.bits_per_hasher,
+                        hash_a,
+                        hash_b,
+                    ))
+                })
+            }
+            Bits::Dense(filter) => filter.contains(data),
+        }
+    }
+}
+
+impl<T, H1, H2> Extend<T> for SparseBloomFilter<H1, H2>
+where
+    T: Hash,
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.insert_all(iter);
+    }
+}
+
+/// Indices of the set bits, stored as the sorted lower bits of the indices in every container.
+#[derive(Default)]
+struct SparseBits {
+    containers: BTreeMap<usize, Vec<u16>>,
+    ones: usize,
+}
+
+impl SparseBits {
+    fn get(&self, index: usize) -> bool {
+        self.containers
+            .get(&(index >> CONTAINER_BITS))
+            .is_some_and(|container| container.binary_search(&(index as u16)).is_ok())
+    }
+
+    fn set(&mut self, index: usize) {
+        let container = self.containers.entry(index >> CONTAINER_BITS).or_default();
+        if let Err(position) = container.binary_search(&(index as u16)) {
+            container.insert(position, index as u16);
+            self.ones += 1;
+        }
+    }
+
+    fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.containers.iter().flat_map(|(&high, container)| {
+            container
+                .iter()
+                .map(move |&low| (high << CONTAINER_BITS) | low as usize)
+        })
+    }
+
+    fn to_dense<H1, H2>(
+        &self,
+        number_of_hashers: usize,
+        bits_per_hasher: usize,
+    ) -> KMBloomFilter<H1, H2>
+    where
+        H1: Hasher + Default,
+        H2: Hasher + Default,
+    {
+        let mut bitset = Bitset::new(number_of_hashers * bits_per_hasher);
+        for index in self.iter_ones() {
+            bitset.set(index, true);
+        }
+        KMBloomFilter::from_bitset(bitset, number_of_hashers)
+    }
+}
@@ -0,0 +1,145 @@
+use core::hash::Hasher;
+
+use xxhash_rust::{xxh3::Xxh3, xxh64::Xxh64};
+
+/// Seed of the [StableXxh3Hasher]. This value is part of the stable hashing scheme and will never
+/// change.
+pub const STABLE_XXH3_SEED: u64 = 0x5bd1_e995_b10f_11e5;
+
+/// Seed of the [StableXxh64Hasher]. This value is part of the stable hashing scheme and will never
+/// change.
+pub const STABLE_XXH64_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Hasher based on XXH3 (64 bit) with the fixed seed [STABLE_XXH3_SEED].
+///
+/// In contrast to the other default hashers used in this crate, the hash values are stable across
+/// processes, platforms, and releases of this crate. All integers are hashed in little-endian byte
+/// order, and *usize*/*isize* values (which includes the length prefixes of slices) are always
+/// hashed as 64-bit integers, regardless of the target's pointer width.
+///
+/// The hash value still depends on the *Hash* implementation of the hashed type. The
+/// implementations of the standard library for integers, strings, and slices have been stable for
+/// years, but there is no formal guarantee. Use byte slices or integers as keys if you want to be
+/// on the safe side.
+#[derive(Clone)]
+pub struct StableXxh3Hasher(Xxh3);
+
+/// Hasher based on XXH64 with the fixed seed [STABLE_XXH64_SEED].
+///
+/// See [StableXxh3Hasher] for the stability guarantees.
+#[derive(Clone)]
+pub struct StableXxh64Hasher(Xxh64);
+
+impl Default for StableXxh3Hasher {
+    fn default() -> Self {
+        Self(Xxh3::with_seed(STABLE_XXH3_SEED))
+    }
+}
+
+impl Default for StableXxh64Hasher {
+    fn default() -> Self {
+        Self(Xxh64::new(STABLE_XXH64_SEED))
+    }
+}
+
+// Implements Hasher so that the hashed bytes do not depend on the platform's endianness or
+// pointer width.
+macro_rules! impl_stable_hasher {
+    ($hasher:ty) => {
+        impl Hasher for $hasher {
+            fn finish(&self) -> u64 {
+                self.0.finish()
+            }
+
+            fn write(&mut self, bytes: &[u8]) {
+                self.0.write(bytes)
+            }
+
+            fn write_u16(&mut self, i: u16) {
+                self.write(&i.to_le_bytes())
+            }
+
+            fn write_u32(&mut self, i: u32) {
+                self.write(&i.to_le_bytes())
+            }
+
+            fn write_u64(&mut self, i: u64) {
+                self.write(&i.to_le_bytes())
+            }
+
+            fn write_u128(&mut self, i: u128) {
+                self.write(&i.to_le_bytes())
+            }
+
+            fn write_usize(&mut self, i: usize) {
+                self.write_u64(i as u64)
+            }
+
+            fn write_isize(&mut self, i: isize) {
+                self.write_u64(i as i64 as u64)
+            }
+        }
+    };
+}
+
+impl_stable_hasher!(StableXxh3Hasher);
+impl_stable_hasher!(StableXxh64Hasher);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::hash::Hash;
+
+    fn hash<H: Hasher + Default, T: Hash>(data: &T) -> u64 {
+        let mut hasher = H::default();
+        data.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // These values must never change. If this test fails, filters persisted with an older release
+    // can no longer be queried.
+    #[test]
+    fn stable_xxh3_test_vectors() {
+        assert_eq!(0x2b0e_c080_d517_04ed, hash::<StableXxh3Hasher, _>(&0u64));
+        assert_eq!(0x74cc_7ee7_7e53_9849, hash::<StableXxh3Hasher, _>(&42usize));
+        assert_eq!(
+            0x1db1_e9be_0eb3_1b4c,
+            hash::<StableXxh3Hasher, _>(&"Hello!")
+        );
+        assert_eq!(
+            0x77e4_e554_f199_09be,
+            hash::<StableXxh3Hasher, _>(&vec![1u8, 2, 3])
+        );
+    }
+
+    // These values must never change. If this test fails, filters persisted with an older release
+    // can no longer be queried.
+    #[test]
+    fn stable_xxh64_test_vectors() {
+        assert_eq!(0x1722_e35e_bbc1_e9a0, hash::<StableXxh64Hasher, _>(&0u64));
+        assert_eq!(
+            0xf92a_4389_bd62_ee57,
+            hash::<StableXxh64Hasher, _>(&42usize)
+        );
+        assert_eq!(
+            0xbb7f_e33c_4535_1d8e,
+            hash::<StableXxh64Hasher, _>(&"Hello!")
+        );
+        assert_eq!(
+            0x7699_75a6_2183_4b73,
+            hash::<StableXxh64Hasher, _>(&vec![1u8, 2, 3])
+        );
+    }
+
+    #[test]
+    fn usize_is_hashed_like_u64() {
+        assert_eq!(
+            hash::<StableXxh3Hasher, _>(&42u64),
+            hash::<StableXxh3Hasher, _>(&42usize)
+        );
+        assert_eq!(
+            hash::<StableXxh64Hasher, _>(&42u64),
+            hash::<StableXxh64Hasher, _>(&42usize)
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use std::io::{BufRead, Error, ErrorKind, Result};
+
+use crate::BloomFilter;
+
+/// Insert the keys of *reader*, which are separated by *delimiter*, into *filter* and return the
+/// number of inserted keys.
+///
+/// A single buffer is reused for all keys. Keys are inserted as *str*, so that they can be looked
+/// up with string slices. If *strip_carriage_return* is set, a trailing '\r' is removed from every
+/// key, too.
+pub(crate) fn insert_delimited<F, R>(
+    filter: &mut F,
+    mut reader: R,
+    delimiter: u8,
+    strip_carriage_return: bool,
+) -> Result<usize>
+where
+    F: BloomFilter + ?Sized,
+    R: BufRead,
+{
+    let mut buffer = Vec::new();
+    let mut count = 0;
+    loop {
+        buffer.clear();
+        if reader.read_until(delimiter, &mut buffer)? == 0 {
+            return Ok(count);
+        }
+        if buffer.last() == Some(&delimiter) {
+            buffer.pop();
+        }
+        if strip_carriage_return && buffer.last() == Some(&b'\r') {
+            buffer.pop();
+        }
+        let key = std::str::from_utf8(&buffer).map_err(|error| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("key {} is not valid UTF-8: {}", count + 1, error),
+            )
+        })?;
+        filter.insert(&key);
+        count += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StableBloomFilterDefault;
+
+    #[test]
+    fn keys_are_stripped() {
+        let mut filter = StableBloomFilterDefault::new(10, 0.001);
+        let count = insert_delimited(&mut filter, &b"a\r\nb\n\nc"[..], b'\n', true).unwrap();
+        assert_eq!(4, count);
+        assert!(filter.contains(&"a"));
+        assert!(filter.contains(&"b"));
+        assert!(filter.contains(&""));
+        assert!(filter.contains(&"c"));
+        assert!(!filter.contains(&"a\r"));
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let mut filter = StableBloomFilterDefault::new(10, 0.001);
+        let error = insert_delimited(&mut filter, &b"a,\xff,b"[..], b',', false).unwrap_err();
+        assert_eq!(ErrorKind::InvalidData, error.kind());
+        assert!(filter.contains(&"a"));
+    }
+}
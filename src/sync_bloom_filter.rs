@@ -0,0 +1,186 @@
+use std::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    sync::{PoisonError, RwLock},
+};
+
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::BloomFilter;
+
+/// Seed of the hasher that assigns elements to shards. It differs from the seeds of the stable
+/// hashers, so that the shard of an element is independent of its bits within the shard.
+const SHARD_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// Bloom filter that can be shared between threads, e.g., in an *Arc*, and that allows concurrent
+/// insertions.
+///
+/// A SyncBloomFilter consists of several independent shards, each of which is a filter of type
+/// *F* behind its own lock. Every element is assigned to one shard by its hash value, so
+/// insertions of different elements mostly lock different shards and rarely wait for each other.
+/// Concurrent *contains* calls never wait for each other.
+///
+/// Each shard receives about *1 / number_of_shards* of the elements. Create the shards with a
+/// desired capacity of a little more than *desired_capacity / number_of_shards* to keep the false
+/// positive probability below the desired false positive probability.
+///
+/// If all insertions happen before the filter is shared, use *KMBloomFilter::par_from_iter*
+/// instead, which does not need locks. SyncBloomFilter is only available with the *std* feature.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{StableBloomFilterDefault,SyncBloomFilter};
+/// use std::{sync::Arc, thread};
+///
+/// fn main() {
+///     let filter = Arc::new(SyncBloomFilter::new(16, || {
+///         StableBloomFilterDefault::new(100_000 / 16 + 1_000, 0.001)
+///     }));
+///
+///     let threads: Vec<_> = (0..4)
+///         .map(|t| {
+///             let filter = Arc::clone(&filter);
+///             thread::spawn(move || {
+///                 for i in t * 25_000..(t + 1) * 25_000 {
+///                     filter.insert(&i);
+///                 }
+///             })
+///         })
+///         .collect();
+///     for thread in threads {
+///         thread.join().unwrap();
+///     }
+///
+///     assert_eq!(true, filter.contains(&42));
+///     assert_eq!(true, filter.contains(&99_999));
+/// }
+/// ```
+pub struct SyncBloomFilter<F>
+where
+    F: BloomFilter,
+{
+    shards: Vec<RwLock<F>>,
+}
+
+impl<F> SyncBloomFilter<F>
+where
+    F: BloomFilter,
+{
+    /// Initialize a new instance of SyncBloomFilter with *number_of_shards* shards, each of which
+    /// is created by *new_shard*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if number_of_shards == 0
+    pub fn new<S>(number_of_shards: usize, new_shard: S) -> Self
+    where
+        S: FnMut() -> F,
+    {
+        Self::from_shards(
+            core::iter::repeat_with(new_shard)
+                .take(number_of_shards)
+                .collect(),
+        )
+    }
+
+    /// Initialize a new instance of SyncBloomFilter with the given shards, e.g., the shards
+    /// returned by *into_shards*.
+    ///
+    /// Elements are assigned to shards by their position in *shards*, so the order of the shards
+    /// must be kept.
+    ///
+    /// # Panics
+    ///
+    /// Panics if *shards* is empty.
+    pub fn from_shards(shards: Vec<F>) -> Self {
+        if shards.is_empty() {
+            panic!("a sync bloom filter requires at least one shard");
+        }
+        Self {
+            shards: shards.into_iter().map(RwLock::new).collect(),
+        }
+    }
+
+    /// Insert *data* into its shard.
+    ///
+    /// Only the shard of *data* is locked. A poisoned lock is ignored, because an interrupted
+    /// insertion at most leaves some bits of the element unset.
+    pub fn insert<T>(&self, data: &T)
+    where
+        T: Hash,
+    {
+        self.shards[self.shard_index(data)]
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(data);
+    }
+
+    /// Insert all elements of *iter*.
+    pub fn insert_all<T, I>(&self, iter: I)
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        for data in iter {
+            self.insert(&data);
+        }
+    }
+
+    /// Check whether *data* is contained in its shard.
+    pub fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        self.shards[self.shard_index(data)]
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(data)
+    }
+
+    /// Insert *data* into its shard and return whether it has been contained before, see
+    /// *BloomFilter::check_and_insert*.
+    ///
+    /// The shard is locked once for both, so two threads that insert the same new element at the
+    /// same time cannot both see it as new.
+    pub fn check_and_insert<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        self.shards[self.shard_index(data)]
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .check_and_insert(data)
+    }
+
+    /// Number of shards of the filter.
+    pub fn number_of_shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Return the shards, e.g., to persist them.
+    pub fn into_shards(self) -> Vec<F> {
+        self.shards
+            .into_iter()
+            .map(|shard| shard.into_inner().unwrap_or_else(PoisonError::into_inner))
+            .collect()
+    }
+
+    fn shard_index<T>(&self, data: &T) -> usize
+    where
+        T: Hash,
+    {
+        let mut hasher = Xxh3::with_seed(SHARD_SEED);
+        data.hash(&mut hasher);
+        // Map the hash to the shards without a division
+        ((hasher.finish() as u128 * self.shards.len() as u128) >> 64) as usize
+    }
+}
+
+impl<F> Debug for SyncBloomFilter<F>
+where
+    F: BloomFilter,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SyncBloomFilter{{shards: {}}}", self.shards.len())
+    }
+}
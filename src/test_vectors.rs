@@ -0,0 +1,357 @@
+//! Test vectors of the hashing scheme of KMBloomFilter, e.g., to build filters in other languages
+//! that this crate can load and query.
+//!
+//! # Hashing scheme
+//!
+//! A filter with *k* hashers and *m* bits per hasher has *k * m* bits. An element that is inserted
+//! with *insert_bytes* or *insert_str* sets the following bits:
+//! 1. The bytes are hashed as they are, without a length prefix, with both hashers: *hash_a* is
+//!    the 64-bit hash of *H1*, *hash_b* the 64-bit hash of *H2*. For StableBloomFilterDefault,
+//!    *hash_a* is XXH3 (64 bit) with seed STABLE_XXH3_SEED and *hash_b* is XXH64 with seed
+//!    STABLE_XXH64_SEED.
+//! 2. For every hasher *i* in *0..k*, the bit with index *i * m + (hash_a + i * hash_b) mod m* is
+//!    set, where *hash_a + i * hash_b* is computed with wrapping 64-bit unsigned arithmetic.
+//!    With the layout *IndexLayout::Global*, the bit with index *(hash_a + i * hash_b) mod (k * m)*
+//!    is set instead, as in most other bloom filter implementations.
+//!
+//! Elements inserted with *insert* are hashed with their *Hash* implementation instead, which adds
+//! type-specific bytes, e.g., length prefixes. Use *insert_bytes* for elements that are shared with
+//! other languages.
+//!
+//! *KMBloomFilter::write_to* writes the following, with all integers in little-endian byte order:
+//! 1. The magic bytes `BFKM` and the format version as u32: 1 for the default layout
+//!    *IndexLayout::Partitioned*, 2 for the layout *IndexLayout::Global*.
+//! 2. The identifiers of *H1* and *H2* as u64, which are their hashes of the bytes
+//!    `bloom_filter_simple`.
+//! 3. *k* and *m* as u64.
+//! 4. The *k * m* bits, where bit *j* is bit *j mod 8* (counting from the least significant bit)
+//!    of byte *j / 8*. The unused bits of the last byte are zero.
+//! 5. The XXH64 hash with seed 0 of the bytes of step 4 as u64.
+//!
+//! # Validating another implementation
+//!
+//! *generate* computes the hashes and bit indices of the *canonical_inputs*, which cover all
+//! length classes of XXH3 and XXH64. Another implementation compares its hashes and indices with
+//! the vectors, inserts the inputs into a filter with the same configuration, and writes the
+//! filter. *validate* checks that the filter, loaded with *KMBloomFilter::read_from*, contains all
+//! inputs at the expected bits.
+//!
+//! # Examples
+//! ```
+//! use bloom_filter_simple::{test_vectors,StableBloomFilterDefault,StableXxh3Hasher,StableXxh64Hasher};
+//!
+//! let vectors = test_vectors::generate::<StableXxh3Hasher, StableXxh64Hasher>(7, 1_000);
+//! for vector in vectors.iter() {
+//!     // Print one line per vector, e.g., "input=48656c6c6f21 hash_a=... hash_b=... indices=..."
+//!     println!("{}", vector);
+//! }
+//!
+//! // A filter built by another implementation from the canonical inputs
+//! let mut filter = StableBloomFilterDefault::with_parameters(7_000, 7);
+//! for input in test_vectors::canonical_inputs() {
+//!     filter.insert_bytes(&input);
+//! }
+//! assert_eq!(Ok(()), test_vectors::validate(&filter, &vectors));
+//! ```
+
+use alloc::{vec, vec::Vec};
+use core::{
+    fmt::{self, Display},
+    hash::Hasher,
+};
+
+use crate::{HashedKey, IndexLayout, KMBloomFilter};
+
+/// Lengths of the generated inputs of *canonical_inputs*, which cover the different code paths of
+/// XXH3 (0, 1-3, 4-8, 9-16, 17-128, 129-240, and more than 240 bytes) and XXH64 (less than and
+/// multiples of 32 bytes).
+const CANONICAL_LENGTHS: [usize; 17] = [
+    1, 2, 3, 4, 7, 8, 9, 16, 17, 31, 32, 33, 128, 129, 240, 241, 1_000,
+];
+
+/// Hashes and bit indices of an input, as computed by KMBloomFilter with the hashers *H1* and
+/// *H2*.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestVector {
+    /// The bytes that are hashed.
+    pub input: Vec<u8>,
+    /// Hash of *input* with the first hasher.
+    pub hash_a: u64,
+    /// Hash of *input* with the second hasher.
+    pub hash_b: u64,
+    /// Indices of the bits that are set for *input*, one per hasher.
+    pub indices: Vec<usize>,
+}
+
+impl TestVector {
+    /// Compute the test vector of *input* for a KMBloomFilter with the hashers *H1* and *H2*,
+    /// *number_of_hashers* hashers, and *bits_per_hasher* bits per hasher.
+    ///
+    /// # Panics
+    ///
+    /// Panics if bits_per_hasher == 0.
+    pub fn new<H1, H2>(input: &[u8], number_of_hashers: usize, bits_per_hasher: usize) -> Self
+    where
+        H1: Hasher + Default,
+        H2: Hasher + Default,
+    {
+        Self::with_layout::<H1, H2>(
+            input,
+            number_of_hashers,
+            bits_per_hasher,
+            IndexLayout::Partitioned,
+        )
+    }
+
+    /// Compute the test vector of *input* like *new*, but for a KMBloomFilter with the index
+    /// layout *layout*.
+    ///
+    /// # Panics
+    ///
+    /// Panics if bits_per_hasher == 0.
+    pub fn with_layout<H1, H2>(
+        input: &[u8],
+        number_of_hashers: usize,
+        bits_per_hasher: usize,
+        layout: IndexLayout,
+    ) -> Self
+    where
+        H1: Hasher + Default,
+        H2: Hasher + Default,
+    {
+        if bits_per_hasher == 0 {
+            panic!("unable to compute test vectors for a filter without bits");
+        }
+        let (hash_a, hash_b) = HashedKey::<H1, H2>::from_bytes(input).hashes();
+        Self {
+            input: input.to_vec(),
+            hash_a,
+            hash_b,
+            indices: (0..number_of_hashers)
+                .map(|i| layout.bit_index(i, number_of_hashers, bits_per_hasher, hash_a, hash_b))
+                .collect(),
+        }
+    }
+}
+
+/// Writes the vector as a single line of space-separated fields: the input as lowercase hex
+/// digits, both hashes as 16 lowercase hex digits, and the comma-separated decimal indices, e.g.,
+/// `input=0102 hash_a=00000000000000ff hash_b=0000000000000100 indices=12,345`.
+impl Display for TestVector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "input=")?;
+        for byte in self.input.iter() {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(
+            f,
+            " hash_a={:016x} hash_b={:016x} indices=",
+            self.hash_a, self.hash_b
+        )?;
+        for (i, index) in self.indices.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", index)?;
+        }
+        Ok(())
+    }
+}
+
+/// Error returned by *validate* if a filter does not match a test vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestVectorMismatch {
+    /// The hashes of the input of vector *vector* differ from the hashes of the filter's hashers.
+    Hashes { vector: usize },
+    /// The bit indices of vector *vector* differ from the indices of the filter, e.g., because the
+    /// vector has been generated for another configuration.
+    Indices { vector: usize },
+    /// The filter does not contain the input of vector *vector*.
+    NotContained { vector: usize },
+}
+
+impl Display for TestVectorMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TestVectorMismatch::Hashes { vector } => {
+                write!(f, "the hashes of test vector {} differ", vector)
+            }
+            TestVectorMismatch::Indices { vector } => {
+                write!(f, "the bit indices of test vector {} differ", vector)
+            }
+            TestVectorMismatch::NotContained { vector } => {
+                write!(f, "the filter does not contain test vector {}", vector)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TestVectorMismatch {}
+
+/// Inputs of the canonical test vectors: the empty input, a few ASCII and UTF-8 strings, the
+/// little-endian bytes of the u64 42, and the bytes *0, 1, 2, ...* (wrapping at 256) of lengths
+/// that cover all code paths of XXH3 and XXH64.
+///
+/// The inputs will never change, so they can be hard-coded by other implementations.
+pub fn canonical_inputs() -> Vec<Vec<u8>> {
+    let mut inputs = vec![
+        Vec::new(),
+        b"Hello!".to_vec(),
+        b"bloom_filter_simple".to_vec(),
+        "Grüße, 世界".as_bytes().to_vec(),
+        42u64.to_le_bytes().to_vec(),
+    ];
+    inputs.extend(
+        CANONICAL_LENGTHS
+            .iter()
+            .map(|&length| (0..length).map(|i| i as u8).collect()),
+    );
+    inputs
+}
+
+/// Compute the test vectors of the *canonical_inputs* for a KMBloomFilter with the hashers *H1*
+/// and *H2*, *number_of_hashers* hashers, and *bits_per_hasher* bits per hasher.
+///
+/// # Panics
+///
+/// Panics if bits_per_hasher == 0.
+pub fn generate<H1, H2>(number_of_hashers: usize, bits_per_hasher: usize) -> Vec<TestVector>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    generate_with_layout::<H1, H2>(number_of_hashers, bits_per_hasher, IndexLayout::Partitioned)
+}
+
+/// Compute the test vectors of the *canonical_inputs* like *generate*, but for a KMBloomFilter
+/// with the index layout *layout*.
+///
+/// # Panics
+///
+/// Panics if bits_per_hasher == 0.
+pub fn generate_with_layout<H1, H2>(
+    number_of_hashers: usize,
+    bits_per_hasher: usize,
+    layout: IndexLayout,
+) -> Vec<TestVector>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    canonical_inputs()
+        .iter()
+        .map(|input| {
+            TestVector::with_layout::<H1, H2>(input, number_of_hashers, bits_per_hasher, layout)
+        })
+        .collect()
+}
+
+/// Check that *filter* computes the hashes and bit indices of all *vectors*, and that all of their
+/// bits are set, i.e., that the inputs of the vectors have been inserted with *insert_bytes* or an
+/// equivalent implementation.
+///
+/// # Errors
+///
+/// Returns the first mismatch, identified by the position of the vector in *vectors*.
+pub fn validate<H1, H2>(
+    filter: &KMBloomFilter<H1, H2>,
+    vectors: &[TestVector],
+) -> Result<(), TestVectorMismatch>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    let bits_per_hasher = filter.bit_count() / filter.hasher_count();
+    for (position, vector) in vectors.iter().enumerate() {
+        let expected = TestVector::with_layout::<H1, H2>(
+            &vector.input,
+            filter.hasher_count(),
+            bits_per_hasher,
+            filter.index_layout(),
+        );
+        if (expected.hash_a, expected.hash_b) != (vector.hash_a, vector.hash_b) {
+            return Err(TestVectorMismatch::Hashes { vector: position });
+        }
+        if expected.indices != vector.indices {
+            return Err(TestVectorMismatch::Indices { vector: position });
+        }
+        if !vector.indices.iter().all(|&index| filter.bitset()[index]) {
+            return Err(TestVectorMismatch::NotContained { vector: position });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StableXxh3Hasher, StableXxh64Hasher, STABLE_XXH3_SEED, STABLE_XXH64_SEED};
+    use xxhash_rust::{xxh3, xxh64};
+
+    // These values must never change. If this test fails, filters built by other implementations
+    // from the documented scheme can no longer be queried.
+    #[test]
+    fn stable_test_vector() {
+        let vector = TestVector::new::<StableXxh3Hasher, StableXxh64Hasher>(b"Hello!", 3, 100);
+        let hash_a = xxh3::xxh3_64_with_seed(b"Hello!", STABLE_XXH3_SEED);
+        let hash_b = xxh64::xxh64(b"Hello!", STABLE_XXH64_SEED);
+        assert_eq!((hash_a, hash_b), (vector.hash_a, vector.hash_b));
+        for (i, &index) in vector.indices.iter().enumerate() {
+            let hash = hash_a.wrapping_add((i as u64).wrapping_mul(hash_b));
+            assert_eq!(i * 100 + (hash % 100) as usize, index);
+        }
+        assert_eq!(
+            "input=48656c6c6f21 hash_a=78abd2a638d67d3f hash_b=e237d0f5602711ac indices=55,147,239",
+            vector.to_string()
+        );
+    }
+
+    #[test]
+    fn stable_global_layout_test_vector() {
+        let vector = TestVector::with_layout::<StableXxh3Hasher, StableXxh64Hasher>(
+            b"Hello!",
+            3,
+            100,
+            IndexLayout::Global,
+        );
+        for (i, &index) in vector.indices.iter().enumerate() {
+            let hash = vector
+                .hash_a
+                .wrapping_add((i as u64).wrapping_mul(vector.hash_b));
+            assert_eq!((hash % 300) as usize, index);
+        }
+        assert_eq!(
+            "input=48656c6c6f21 hash_a=78abd2a638d67d3f hash_b=e237d0f5602711ac indices=255,247,239",
+            vector.to_string()
+        );
+
+        let mut filter =
+            KMBloomFilter::<StableXxh3Hasher, StableXxh64Hasher>::with_parameters(7_000, 7)
+                .with_index_layout(IndexLayout::Global);
+        for input in canonical_inputs() {
+            filter.insert_bytes(&input);
+        }
+        let vectors = generate_with_layout::<StableXxh3Hasher, StableXxh64Hasher>(
+            7,
+            1_000,
+            IndexLayout::Global,
+        );
+        assert_eq!(Ok(()), validate(&filter, &vectors));
+        assert_eq!(
+            Err(TestVectorMismatch::Indices { vector: 0 }),
+            validate(
+                &filter,
+                &generate::<StableXxh3Hasher, StableXxh64Hasher>(7, 1_000)
+            )
+        );
+    }
+
+    #[test]
+    fn canonical_inputs_are_distinct() {
+        let inputs = canonical_inputs();
+        for (i, input) in inputs.iter().enumerate() {
+            assert!(inputs[i + 1..].iter().all(|other| other != input));
+        }
+    }
+}
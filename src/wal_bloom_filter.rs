@@ -0,0 +1,340 @@
+use std::{
+    convert::TryInto,
+    ffi::OsString,
+    fmt::{self, Debug},
+    fs::{self, File, OpenOptions},
+    hash::{Hash, Hasher},
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    file_format::{CHECKSUM_LENGTH, HEADER_LENGTH},
+    BloomFilter, CapacityExceeded, HashedKey, KMBloomFilter, ReadFilterError,
+};
+
+/// Length of a record of the log: the base hashes of an element as two little-endian u64s.
+const RECORD_LENGTH: usize = 16;
+
+/// KMBloomFilter that appends every insertion to a write-ahead log, so that no insertion is lost
+/// if the process crashes between two snapshots, e.g., in a deduplication service.
+///
+/// The log file starts with a snapshot of the filter in the format of *KMBloomFilter::write_to*,
+/// followed by one record for every element that has been inserted since: the base hashes of the
+/// element as two little-endian u64s, see *KMBloomFilter::insert_hash64*. *insert* writes the
+/// record to the file before it sets the bits, and *recover* reads the snapshot and replays the
+/// records after a crash. A record that has only been written partially is dropped. *checkpoint*
+/// replaces the log with a snapshot of the current filter, so that the log does not grow forever.
+///
+/// A filter with a strict capacity or the saturation policy *OnSaturation::Error* refuses an
+/// insertion before its record is written, so the log only holds inserted elements. The log does
+/// not store these limits, so *recover* returns a filter without a strict capacity and with the
+/// default saturation policy.
+///
+/// The records are written to the file without buffering, so they survive a crash of the
+/// process. Call *sync* to make them survive a crash of the operating system as well. The
+/// hashers *H1* and *H2* must have stable hash values, e.g., the hashers of
+/// StableBloomFilterDefault, because *recover* rejects logs written with different hashers.
+///
+/// Only available with the *std* feature.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::{StableBloomFilterDefault,StableXxh3Hasher,StableXxh64Hasher,WalBloomFilter};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let path = std::env::temp_dir().join("wal_bloom_filter_doc_example.wal");
+///
+///     let mut filter = WalBloomFilter::create(&path, StableBloomFilterDefault::new(1_000, 0.001))?;
+///     filter.insert(&"Hello!")?;
+///     drop(filter);
+///
+///     // After a crash, the log is replayed into the snapshot
+///     let filter: WalBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+///         WalBloomFilter::recover(&path)?;
+///     assert_eq!(true, filter.contains(&"Hello!"));
+///     assert_eq!(1, filter.log_len());
+///     Ok(())
+/// }
+/// ```
+pub struct WalBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    filter: KMBloomFilter<H1, H2>,
+    log: File,
+    path: PathBuf,
+    log_len: usize,
+}
+
+impl<H1, H2> WalBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    /// Create a log at *path* that starts with a snapshot of *filter*, replacing an existing file.
+    ///
+    /// # Errors
+    ///
+    /// Forwards errors of writing the snapshot.
+    pub fn create<P>(path: P, filter: KMBloomFilter<H1, H2>) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let log = write_snapshot(&path, &filter)?;
+        Ok(Self {
+            filter,
+            log,
+            path,
+            log_len: 0,
+        })
+    }
+
+    /// Read the snapshot of the log at *path* and replay its records, e.g., after a crash, and
+    /// continue to append to the log.
+    ///
+    /// A record at the end of the log that has only been written partially is removed from the
+    /// file. The strict capacity and the saturation policy of the filter the log has been created
+    /// with are not restored, see *KMBloomFilter::read_from*.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of *KMBloomFilter::read_from* for the snapshot, and forwards errors of
+    /// reading and truncating the file as *ReadFilterError::Io*.
+    pub fn recover<P>(path: P) -> Result<Self, ReadFilterError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let mut log = OpenOptions::new().read(true).write(true).open(&path)?;
+        let mut reader = BufReader::new(&mut log);
+        let mut filter = KMBloomFilter::<H1, H2>::read_from(&mut reader)?;
+        let mut log_len = 0;
+        let mut record = [0u8; RECORD_LENGTH];
+        loop {
+            match reader.read_exact(&mut record) {
+                Ok(()) => {}
+                Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(error) => return Err(error.into()),
+            }
+            let (hash_a, hash_b) = record.split_at(8);
+            filter.insert_hash64(
+                u64::from_le_bytes(hash_a.try_into().expect("a record holds two hashes")),
+                u64::from_le_bytes(hash_b.try_into().expect("a record holds two hashes")),
+            );
+            log_len += 1;
+        }
+        drop(reader);
+        let snapshot_length = HEADER_LENGTH + filter.bit_count().div_ceil(8) + CHECKSUM_LENGTH;
+        log.set_len((snapshot_length + log_len * RECORD_LENGTH) as u64)?;
+        log.seek(SeekFrom::End(0))?;
+        Ok(Self {
+            filter,
+            log,
+            path,
+            log_len,
+        })
+    }
+
+    /// Append data to the log and insert it into the filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns *WalInsertError::CapacityExceeded* without writing the log if the filter refuses
+    /// data, see *KMBloomFilter::try_insert*, and forwards errors of writing the log. In both
+    /// cases, data is not inserted.
+    pub fn insert<T>(&mut self, data: &T) -> Result<(), WalInsertError>
+    where
+        T: Hash,
+    {
+        let key = self.filter.hash(data);
+        self.insert_hashed(&key)
+    }
+
+    /// Append the element *key* has been computed for to the log and insert it into the filter.
+    ///
+    /// See *KMBloomFilter::hash* for how to compute a key.
+    ///
+    /// # Errors
+    ///
+    /// Returns *WalInsertError::CapacityExceeded* without writing the log if the filter refuses
+    /// the element, see *KMBloomFilter::try_insert_hashed*, and forwards errors of writing the
+    /// log. In both cases, the element is not inserted.
+    pub fn insert_hashed(&mut self, key: &HashedKey<H1, H2>) -> Result<(), WalInsertError> {
+        self.filter.check_capacity(key.hashes())?;
+        self.log.write_all(&record(key))?;
+        self.filter.try_insert_hashed(key)?;
+        self.log_len += 1;
+        Ok(())
+    }
+
+    /// Append all elements of *iter* to the log with a single write and insert them into the
+    /// filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns *WalInsertError::CapacityExceeded* without writing the log if the filter refuses
+    /// the elements together, see *KMBloomFilter::try_insert*, and forwards errors of writing the
+    /// log. In both cases, no element is inserted. A crash during the write may leave some of the
+    /// elements in the log, which are inserted by *recover*.
+    pub fn insert_all<T, I>(&mut self, iter: I) -> Result<(), WalInsertError>
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        let keys: Vec<HashedKey<H1, H2>> = iter
+            .into_iter()
+            .map(|data| self.filter.hash(&data))
+            .collect();
+        let mut indices = Vec::new();
+        let mut records = Vec::with_capacity(keys.len() * RECORD_LENGTH);
+        for key in keys.iter() {
+            self.filter.push_indices(key.hashes(), &mut indices);
+            records.extend_from_slice(&record(key));
+        }
+        self.filter.check_indices(&mut indices)?;
+        self.log.write_all(&records)?;
+        self.filter.insert_indices(&mut indices, keys.len())?;
+        self.log_len += keys.len();
+        Ok(())
+    }
+
+    /// Check whether data is contained in the bloom filter.
+    pub fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        self.filter.contains(data)
+    }
+
+    /// Check whether the element *key* has been computed for is contained in the bloom filter.
+    pub fn contains_hashed(&self, key: &HashedKey<H1, H2>) -> bool {
+        self.filter.contains_hashed(key)
+    }
+
+    /// The filter with all elements of the snapshot and the log.
+    pub fn filter(&self) -> &KMBloomFilter<H1, H2> {
+        &self.filter
+    }
+
+    /// Number of records in the log since the last snapshot.
+    pub fn log_len(&self) -> usize {
+        self.log_len
+    }
+
+    /// Replace the log with a snapshot of the current filter.
+    ///
+    /// The snapshot is written to a temporary file next to the log, which then replaces the log,
+    /// so a crash during the checkpoint leaves either the old or the new log.
+    ///
+    /// # Errors
+    ///
+    /// Forwards errors of writing the snapshot, in which case the old log is kept.
+    pub fn checkpoint(&mut self) -> io::Result<()> {
+        self.log = write_snapshot(&self.path, &self.filter)?;
+        self.log_len = 0;
+        Ok(())
+    }
+
+    /// Flush the log to the storage device, so that the records survive a crash of the operating
+    /// system or a power failure.
+    ///
+    /// # Errors
+    ///
+    /// Forwards errors of *File::sync_data*.
+    pub fn sync(&self) -> io::Result<()> {
+        self.log.sync_data()
+    }
+
+    /// Return the filter, e.g., after the last checkpoint.
+    pub fn into_filter(self) -> KMBloomFilter<H1, H2> {
+        self.filter
+    }
+}
+
+/// Error returned when inserting into a WalBloomFilter.
+#[derive(Debug)]
+pub enum WalInsertError {
+    /// Writing the log failed.
+    Io(io::Error),
+    /// The filter refused the insertion, see *KMBloomFilter::try_insert*.
+    CapacityExceeded(CapacityExceeded),
+}
+
+impl fmt::Display for WalInsertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WalInsertError::Io(error) => {
+                write!(f, "unable to write the write-ahead log: {}", error)
+            }
+            WalInsertError::CapacityExceeded(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for WalInsertError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WalInsertError::Io(error) => Some(error),
+            WalInsertError::CapacityExceeded(error) => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for WalInsertError {
+    fn from(error: io::Error) -> Self {
+        WalInsertError::Io(error)
+    }
+}
+
+impl From<CapacityExceeded> for WalInsertError {
+    fn from(error: CapacityExceeded) -> Self {
+        WalInsertError::CapacityExceeded(error)
+    }
+}
+
+/// Record of the log for the element *key* has been computed for.
+fn record<H1, H2>(key: &HashedKey<H1, H2>) -> [u8; RECORD_LENGTH]
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    let (hash_a, hash_b) = key.hashes();
+    let mut record = [0u8; RECORD_LENGTH];
+    record[..8].copy_from_slice(&hash_a.to_le_bytes());
+    record[8..].copy_from_slice(&hash_b.to_le_bytes());
+    record
+}
+
+/// Write a snapshot of *filter* to a temporary file that replaces the file at *path*, and return
+/// the file opened for appending records.
+fn write_snapshot<H1, H2>(path: &Path, filter: &KMBloomFilter<H1, H2>) -> io::Result<File>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    let mut temporary_path = OsString::from(path);
+    temporary_path.push(".tmp");
+    let mut writer = BufWriter::new(File::create(&temporary_path)?);
+    filter.write_to(&mut writer)?;
+    let file = writer.into_inner().map_err(|error| error.into_error())?;
+    file.sync_all()?;
+    fs::rename(&temporary_path, path)?;
+    OpenOptions::new().append(true).open(path)
+}
+
+impl<H1, H2> Debug for WalBloomFilter<H1, H2>
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "WalBloomFilter{{path: {:?}, log_len: {}, filter: {:?}}}",
+            self.path, self.log_len, self.filter
+        )
+    }
+}
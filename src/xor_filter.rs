@@ -0,0 +1,269 @@
+use alloc::{vec, vec::Vec};
+use core::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+/// Number of slots per element is *SLOT_FACTOR* plus a constant of *EXTRA_SLOTS* slots, which keeps
+/// the construction of small filters from failing.
+const SLOT_FACTOR: f64 = 1.23;
+const EXTRA_SLOTS: usize = 32;
+
+/// Immutable xor filter with 8-bit fingerprints as described by Graf and Lemire:
+///
+/// > Thomas Mueller Graf and Daniel Lemire. 2020. Xor Filters: Faster and Smaller Than Bloom and
+/// > Cuckoo Filters. ACM J. Exp. Algorithmics 25, Article 1.5 (2020). DOI: [https://doi.org/10.1145/3376122](https://doi.org/10.1145/3376122)
+///
+/// Every element is mapped to three slots, one in each third of the filter, and the fingerprints
+/// are assigned so that the XOR of the three slots equals the fingerprint of the element. The
+/// filter needs about 1.23 bytes per element and has a false positive probability of about
+/// 1 / 256, which makes it smaller and faster than a bloom filter with the same false positive
+/// probability.
+///
+/// Elements cannot be added after construction. Use an xor filter if the set is static, e.g., by
+/// recording the elements in a GcsFilter and building the xor filter from its hashes with
+/// *GcsFilter::to_xor_filter*.
+///
+/// # Examples
+/// ```
+/// use bloom_filter_simple::XorFilter;
+/// use ahash::AHasher;
+///
+/// fn main() {
+///     let filter: XorFilter<AHasher> = XorFilter::from_elements(0..10_000);
+///
+///     assert_eq!(true, filter.contains(&5));
+///     assert_eq!(true, filter.contains(&9_999));
+///     assert!(filter.byte_count() < 13_000);
+/// }
+/// ```
+pub struct XorFilter<H>
+where
+    H: Hasher + Default,
+{
+    fingerprints: Vec<u8>,
+    layout: XorLayout,
+    length: usize,
+    // Phantom data for saving which concrete Hasher type is used
+    _phantom: PhantomData<fn() -> H>,
+}
+
+impl<H> XorFilter<H>
+where
+    H: Hasher + Default,
+{
+    /// Build an xor filter that contains all elements of *iter*.
+    ///
+    /// Construction hashes the elements with *H* and collects the hashes first, so it temporarily
+    /// needs memory for all hashes.
+    pub fn from_elements<T, I>(iter: I) -> Self
+    where
+        T: Hash,
+        I: IntoIterator<Item = T>,
+    {
+        Self::from_hashes(iter.into_iter().map(|data| {
+            let mut hasher = H::default();
+            data.hash(&mut hasher);
+            hasher.finish()
+        }))
+    }
+
+    /// Build an xor filter that contains the elements whose hashes, as returned by *H*, are given
+    /// by *hashes*, e.g., because they have been computed elsewhere.
+    ///
+    /// Duplicate hashes are ignored.
+    pub fn from_hashes<I>(hashes: I) -> Self
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        let mut hashes: Vec<u64> = hashes.into_iter().collect();
+        hashes.sort_unstable();
+        hashes.dedup();
+
+        let (layout, stack) = XorLayout::build(&hashes);
+        let mut filter = Self {
+            fingerprints: vec![0; layout.slot_count()],
+            layout,
+            length: hashes.len(),
+            _phantom: PhantomData,
+        };
+
+        // Assign the fingerprints in reverse peeling order, so that every slot is assigned after
+        // the other two slots of its element have their final value
+        for &(hash, slot) in stack.iter().rev() {
+            let [a, b, c] = filter.layout.slots(hash);
+            filter.fingerprints[slot] = fingerprint(hash)
+                ^ filter.fingerprints[a]
+                ^ filter.fingerprints[b]
+                ^ filter.fingerprints[c];
+        }
+        filter
+    }
+
+    /// Check whether data is contained in the filter.
+    ///
+    /// The result can be a false positive with a probability of about 1 / 256, but never a false
+    /// negative.
+    pub fn contains<T>(&self, data: &T) -> bool
+    where
+        T: Hash,
+    {
+        let mut hasher = H::default();
+        data.hash(&mut hasher);
+        self.contains_hash64(hasher.finish())
+    }
+
+    /// Check whether an element for which *H* returns *hash* is contained in the filter.
+    pub fn contains_hash64(&self, hash: u64) -> bool {
+        if self.length == 0 {
+            return false;
+        }
+        let hash = self.layout.mix(hash);
+        let [a, b, c] = self.layout.slots(hash);
+        fingerprint(hash) == self.fingerprints[a] ^ self.fingerprints[b] ^ self.fingerprints[c]
+    }
+
+    /// Number of distinct elements the filter has been built for.
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Whether the filter has been built for an empty set.
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Number of bytes used for fingerprints.
+    pub fn byte_count(&self) -> usize {
+        self.fingerprints.len()
+    }
+
+    /// Return the approximate false positive probability of the filter.
+    ///
+    /// The probability is given as a value in the interval [0,1]
+    pub fn approximate_false_positive_probability(&self) -> f64 {
+        1.0 / 256.0
+    }
+}
+
+impl<H> Debug for XorFilter<H>
+where
+    H: Hasher + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "XorFilter{{length: {}, bytes: {}}}",
+            self.length,
+            self.fingerprints.len()
+        )
+    }
+}
+
+/// Slots of the elements of an xor filter, i.e., the seed that the hashes of the elements are
+/// mixed with and the length of each of the three blocks of slots. XorFilter and BloomMap share
+/// the layout and differ in what they store in the slots.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct XorLayout {
+    seed: u64,
+    block_length: usize,
+}
+
+impl XorLayout {
+    /// Find a layout for the distinct *hashes* in which all elements can be peeled. Returns the
+    /// layout together with the mixed hashes of the elements and their slot in peeling order.
+    pub(crate) fn build(hashes: &[u64]) -> (Self, Vec<(u64, usize)>) {
+        let capacity = (SLOT_FACTOR * hashes.len() as f64) as usize + EXTRA_SLOTS;
+        let mut layout = Self {
+            seed: 0,
+            block_length: capacity / 3,
+        };
+        let mut rng_state = 0x726b_2b9d_438b_9d4d;
+        loop {
+            layout.seed = splitmix64(&mut rng_state);
+            if let Some(stack) = layout.peel(hashes) {
+                return (layout, stack);
+            }
+        }
+    }
+
+    /// Number of slots of the three blocks.
+    pub(crate) fn slot_count(&self) -> usize {
+        3 * self.block_length
+    }
+
+    /// Peel the hypergraph of the mixed hashes, i.e., repeatedly remove an element that is the only
+    /// one mapped to one of its slots. Returns the removed elements together with their slot in
+    /// removal order, or none if not all elements could be removed.
+    fn peel(&self, hashes: &[u64]) -> Option<Vec<(u64, usize)>> {
+        let slot_count = self.slot_count();
+        // XOR of the mixed hashes mapped to each slot, which reveals the remaining element of a
+        // slot with a count of one
+        let mut xor_masks = vec![0u64; slot_count];
+        let mut counts = vec![0u32; slot_count];
+        for &hash in hashes {
+            let hash = self.mix(hash);
+            for slot in self.slots(hash) {
+                xor_masks[slot] ^= hash;
+                counts[slot] += 1;
+            }
+        }
+
+        let mut queue: Vec<usize> = (0..slot_count).filter(|&slot| counts[slot] == 1).collect();
+        let mut stack = Vec::with_capacity(hashes.len());
+        while let Some(slot) = queue.pop() {
+            if counts[slot] != 1 {
+                continue;
+            }
+            let hash = xor_masks[slot];
+            stack.push((hash, slot));
+            for other in self.slots(hash) {
+                xor_masks[other] ^= hash;
+                counts[other] -= 1;
+                if counts[other] == 1 {
+                    queue.push(other);
+                }
+            }
+        }
+
+        if stack.len() == hashes.len() {
+            Some(stack)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn mix(&self, hash: u64) -> u64 {
+        // Finalizer of MurmurHash3
+        let mut hash = hash.wrapping_add(self.seed);
+        hash = (hash ^ (hash >> 33)).wrapping_mul(0xff51_afd7_ed55_8ccd);
+        hash = (hash ^ (hash >> 33)).wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+        hash ^ (hash >> 33)
+    }
+
+    pub(crate) fn slots(&self, hash: u64) -> [usize; 3] {
+        [
+            reduce(hash as u32, self.block_length),
+            self.block_length + reduce(hash.rotate_left(21) as u32, self.block_length),
+            2 * self.block_length + reduce(hash.rotate_left(42) as u32, self.block_length),
+        ]
+    }
+}
+
+/// Map *value* to the interval [0, n) without a division.
+fn reduce(value: u32, n: usize) -> usize {
+    ((value as u64 * n as u64) >> 32) as usize
+}
+
+fn fingerprint(hash: u64) -> u8 {
+    (hash ^ (hash >> 32)) as u8
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut value = *state;
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
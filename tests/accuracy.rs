@@ -0,0 +1,220 @@
+//! Accuracy regression harness: measures the false positive rate of KMBloomFilter for a matrix of
+//! hasher pairs, capacities, and false positive targets, and compares it with the rate expected
+//! from the filled bits. Only built with the *accuracy-tests* feature:
+//!
+//! ```sh
+//! cargo test --release --features accuracy-tests --test accuracy
+//! ```
+//!
+//! Every configuration is checked twice, so that a regression of the hashers is reported where it
+//! happens:
+//! * Insertion: the number of set bits is within *TOLERANCE_DEVIATIONS* standard deviations of its
+//!   expected value for random hashes (see *params::ones_count_variance*).
+//! * Queries: the number of false positives among keys that have not been inserted is within
+//!   *TOLERANCE_DEVIATIONS* standard deviations of the binomial distribution with the false
+//!   positive probability of the filled bits, i.e., the product of the fill ratios of all hashers.
+//!
+//! The results of all configurations are written as a JSON array to the file given by the
+//! environment variable *ACCURACY_REPORT*, or to `accuracy-report.json` in the temporary directory
+//! of the integration tests, before the checks are asserted.
+
+use std::{collections::hash_map::DefaultHasher, env, fmt::Write as _, fs, hash::Hasher};
+
+use bloom_filter_simple::{
+    params, BloomFilter, KMBloomFilter, StableXxh3Hasher, StableXxh64Hasher,
+};
+use rand::{prelude::StdRng, Rng, SeedableRng};
+use xxhash_rust::{xxh3::Xxh3, xxh64::Xxh64};
+
+const CAPACITIES: [usize; 3] = [1_000, 10_000, 100_000];
+const FALSE_POSITIVE_PROBABILITIES: [f64; 3] = [0.1, 0.01, 0.001];
+
+/// Number of false positives that are expected per configuration, which determines the number of
+/// queries.
+const EXPECTED_FALSE_POSITIVES: f64 = 2_000.0;
+const MIN_QUERIES: usize = 100_000;
+
+/// Width of the accepted intervals in standard deviations. Checks of correct hashers fail with a
+/// probability of about 1e-5 each.
+const TOLERANCE_DEVIATIONS: f64 = 4.5;
+
+struct Measurement {
+    hashers: &'static str,
+    capacity: usize,
+    target_false_positive_probability: f64,
+    number_of_hashers: usize,
+    bits_per_hasher: usize,
+    ones: usize,
+    expected_ones: f64,
+    ones_deviation: f64,
+    queries: usize,
+    false_positives: usize,
+    expected_false_positive_probability: f64,
+    theoretical_false_positive_probability: f64,
+}
+
+impl Measurement {
+    fn measured_false_positive_probability(&self) -> f64 {
+        self.false_positives as f64 / self.queries as f64
+    }
+
+    fn false_positives_deviation(&self) -> f64 {
+        let p = self.expected_false_positive_probability;
+        (self.queries as f64 * p * (1.0 - p)).sqrt()
+    }
+
+    fn ones_within_tolerance(&self) -> bool {
+        (self.ones as f64 - self.expected_ones).abs() <= TOLERANCE_DEVIATIONS * self.ones_deviation
+    }
+
+    fn false_positives_within_tolerance(&self) -> bool {
+        let expected = self.queries as f64 * self.expected_false_positive_probability;
+        (self.false_positives as f64 - expected).abs()
+            <= TOLERANCE_DEVIATIONS * self.false_positives_deviation()
+    }
+
+    fn to_json(&self) -> String {
+        format!(
+            concat!(
+                "{{\"hashers\": \"{}\", \"capacity\": {}, \"target_fp\": {}, ",
+                "\"number_of_hashers\": {}, \"bits_per_hasher\": {}, \"ones\": {}, ",
+                "\"expected_ones\": {:.1}, \"ones_within_tolerance\": {}, \"queries\": {}, ",
+                "\"false_positives\": {}, \"measured_fp\": {:e}, \"expected_fp\": {:e}, ",
+                "\"theoretical_fp\": {:e}, \"fp_within_tolerance\": {}}}"
+            ),
+            self.hashers,
+            self.capacity,
+            self.target_false_positive_probability,
+            self.number_of_hashers,
+            self.bits_per_hasher,
+            self.ones,
+            self.expected_ones,
+            self.ones_within_tolerance(),
+            self.queries,
+            self.false_positives,
+            self.measured_false_positive_probability(),
+            self.expected_false_positive_probability,
+            self.theoretical_false_positive_probability,
+            self.false_positives_within_tolerance(),
+        )
+    }
+}
+
+/// Insert *capacity* random keys into a filter with the hashers *H1* and *H2*, and query random
+/// keys that have not been inserted.
+fn measure<H1, H2>(
+    hashers: &'static str,
+    capacity: usize,
+    false_positive_probability: f64,
+    seed: u64,
+) -> Measurement
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut filter: KMBloomFilter<H1, H2> =
+        KMBloomFilter::new(capacity, false_positive_probability);
+    // Inserted keys are even and queried keys are odd, so no query is a true positive
+    for _ in 0..capacity {
+        filter.insert(&(rng.gen::<u64>() & !1));
+    }
+
+    let number_of_hashers = filter.hasher_count();
+    let bits_per_hasher = filter.bit_count() / number_of_hashers;
+    let mut ones_per_hasher = vec![0usize; number_of_hashers];
+    for index in filter.bitset().iter_ones() {
+        ones_per_hasher[index / bits_per_hasher] += 1;
+    }
+    let expected_false_positive_probability = ones_per_hasher
+        .iter()
+        .map(|&ones| ones as f64 / bits_per_hasher as f64)
+        .product::<f64>();
+    let one_empty = (1.0 - 1.0 / bits_per_hasher as f64).powf(capacity as f64);
+
+    let theoretical_false_positive_probability = params::approximate_false_positive_probability(
+        number_of_hashers,
+        bits_per_hasher,
+        capacity as f64,
+    );
+    let queries = ((EXPECTED_FALSE_POSITIVES / theoretical_false_positive_probability) as usize)
+        .max(MIN_QUERIES);
+    let false_positives = (0..queries)
+        .filter(|_| filter.contains(&(rng.gen::<u64>() | 1)))
+        .count();
+
+    Measurement {
+        hashers,
+        capacity,
+        target_false_positive_probability: false_positive_probability,
+        number_of_hashers,
+        bits_per_hasher,
+        ones: filter.bitset().count_ones(),
+        expected_ones: (number_of_hashers * bits_per_hasher) as f64 * (1.0 - one_empty),
+        ones_deviation: params::ones_count_variance(
+            number_of_hashers,
+            bits_per_hasher,
+            capacity as f64,
+        )
+        .sqrt(),
+        queries,
+        false_positives,
+        expected_false_positive_probability,
+        theoretical_false_positive_probability,
+    }
+}
+
+fn measure_all<H1, H2>(hashers: &'static str, measurements: &mut Vec<Measurement>)
+where
+    H1: Hasher + Default,
+    H2: Hasher + Default,
+{
+    for &capacity in CAPACITIES.iter() {
+        for &false_positive_probability in FALSE_POSITIVE_PROBABILITIES.iter() {
+            let seed = measurements.len() as u64;
+            measurements.push(measure::<H1, H2>(
+                hashers,
+                capacity,
+                false_positive_probability,
+                seed,
+            ));
+        }
+    }
+}
+
+#[test]
+fn false_positive_rate_matches_theory() {
+    let mut measurements = Vec::new();
+    measure_all::<StableXxh3Hasher, StableXxh64Hasher>(
+        "StableXxh3Hasher+StableXxh64Hasher",
+        &mut measurements,
+    );
+    measure_all::<ahash::AHasher, DefaultHasher>("AHasher+DefaultHasher", &mut measurements);
+    measure_all::<DefaultHasher, Xxh64>("DefaultHasher+Xxh64", &mut measurements);
+    measure_all::<Xxh3, fnv::FnvHasher>("Xxh3+FnvHasher", &mut measurements);
+    measure_all::<Xxh3, DefaultHasher>("Xxh3+DefaultHasher", &mut measurements);
+    measure_all::<Xxh3, Xxh64>("Xxh3+Xxh64", &mut measurements);
+    measure_all::<DefaultHasher, fnv::FnvHasher>("DefaultHasher+FnvHasher", &mut measurements);
+
+    let mut report = String::from("[\n");
+    for (i, measurement) in measurements.iter().enumerate() {
+        let separator = if i + 1 < measurements.len() { "," } else { "" };
+        writeln!(report, "  {}{}", measurement.to_json(), separator).unwrap();
+    }
+    report.push_str("]\n");
+    let path = env::var("ACCURACY_REPORT")
+        .unwrap_or_else(|_| format!("{}/accuracy-report.json", env!("CARGO_TARGET_TMPDIR")));
+    fs::write(&path, &report).unwrap();
+    println!("Accuracy report written to {}", path);
+
+    let failures: Vec<String> = measurements
+        .iter()
+        .filter(|m| !m.ones_within_tolerance() || !m.false_positives_within_tolerance())
+        .map(Measurement::to_json)
+        .collect();
+    assert!(
+        failures.is_empty(),
+        "configurations outside of the tolerance:\n{}",
+        failures.join("\n")
+    );
+}
@@ -1,10 +1,33 @@
-use std::{collections::hash_map::DefaultHasher, hash::Hasher};
+#![allow(clippy::bool_assert_comparison)]
 
-use bloom_filter_simple::{BloomFilter, DefaultBloomFilter, KMBloomFilter, SeededBloomFilter};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use bloom_filter_simple::{
+    test_vectors, AttenuatedBloomFilter, Bitset, BitsetDelta, BloomFilter, BloomFilterError,
+    BloomFilterView, BloomMap, BuildHasherBloomFilter, CapacityExceeded, Counters16, Counters4,
+    Counters8, CountingBloomFilter, CuckooFilter, CuckooFilterFull, DLeftCountingFilter,
+    DLeftCountingFilterFull, DistinctCounter, DynBloomFilter, ExactCountBloomFilter,
+    FalsePositiveProbability, FilterCascade, FilterCascadeConflict, FrozenBloomFilter, GcsFilter,
+    GenerationalBloomFilter, GolombCodedSet, IndexLayout, InvalidFalsePositiveProbability,
+    InvalidFilterParams, InvalidGolombCodedSet, KMBloomFilter, KeyedHasher, MergeError,
+    MigratingBloomFilter, OnSaturation, ProbabilisticSet, QuotientFilter, QuotientFilterFull,
+    RotatingBloomFilter, SeededBloomFilter, ShardedBloomFilter, SingleHasherBloomFilter,
+    SparseBloomFilter, StableBloomFilterDefault, StableXxh3Hasher, StableXxh64Hasher, XorFilter,
+    STABLE_XXH3_SEED, STABLE_XXH64_SEED,
+};
+#[cfg(feature = "std")]
+use bloom_filter_simple::{
+    test_vectors::TestVectorMismatch, DefaultBloomFilter, ExpiringBloomFilter,
+    FilterWithExceptions, ReadFilterError, SyncBloomFilter,
+};
 use rand::{distributions::Uniform, prelude::StdRng, Rng, SeedableRng};
-use xxhash_rust::xxh3;
+use xxhash_rust::{xxh3, xxh64};
 
 #[test]
+#[cfg(feature = "std")]
 fn bloom_filter() {
     let mut bloom_filter = DefaultBloomFilter::new(3, 0.7);
 
@@ -49,6 +72,69 @@ fn false_positive_probability_seeded() {
     );
 }
 
+/// XXH64 keyed with the lower 64 bits of the first key.
+struct KeyedXxh64(xxh64::Xxh64);
+
+impl Hasher for KeyedXxh64 {
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes)
+    }
+}
+
+impl KeyedHasher for KeyedXxh64 {
+    fn with_keys(key_a: u128, _key_b: u128) -> Self {
+        Self(xxh64::Xxh64::new(key_a as u64))
+    }
+}
+
+#[test]
+fn false_positive_probability_single_hasher_xxh64() {
+    let desired_capacity = 1_000_000;
+    let false_positive_probability = 0.001;
+    let relative_error_margin = 0.06;
+    let bloom_filter: SingleHasherBloomFilter<KeyedXxh64> =
+        SingleHasherBloomFilter::with_base_seed(desired_capacity, false_positive_probability, 42);
+
+    test_seeded_bloom_filter_probability(
+        desired_capacity,
+        false_positive_probability,
+        bloom_filter,
+        relative_error_margin,
+    );
+}
+
+#[test]
+fn single_hasher_bloom_filter_uses_keyed_hasher() {
+    let mut filter: SingleHasherBloomFilter<KeyedXxh64> =
+        SingleHasherBloomFilter::with_seeds(1_000, 0.001, 1, 2);
+    filter.insert_all(0..500);
+    assert!((0..500).all(|i| filter.contains(&i)));
+
+    // Every probe is the hash value of the keyed hasher of the simulated hash function
+    let bits_per_hasher = filter.bit_count() / filter.hasher_count();
+    for (i, index) in filter.probe_indices(&7).into_iter().enumerate() {
+        let (key_a, key_b) = filter.hasher_keys(i);
+        let mut hasher = KeyedXxh64::with_keys(key_a, key_b);
+        7.hash(&mut hasher);
+        assert_eq!(
+            i * bits_per_hasher + hasher.finish() as usize % bits_per_hasher,
+            index
+        );
+    }
+
+    // SeededBloomFilter is the filter with AHasher
+    let mut seeded_filter = SeededBloomFilter::new(1_000, 0.001);
+    let mut ahash_filter: SingleHasherBloomFilter<ahash::AHasher> =
+        SingleHasherBloomFilter::new(1_000, 0.001);
+    seeded_filter.insert(&"Hello!");
+    ahash_filter.insert(&"Hello!");
+    assert_eq!(seeded_filter, ahash_filter);
+}
+
 #[test]
 fn false_positive_probability_test_default_fnv() {
     let desired_capacity = 1_000_000;
@@ -97,6 +183,36 @@ fn false_positive_probability_xx_default() {
     );
 }
 
+#[test]
+fn false_positive_probability_stable() {
+    let desired_capacity = 1_000_000;
+    let false_positive_probability = 0.001;
+    let relative_error_margin = 0.01;
+    let bloom_filter = StableBloomFilterDefault::new(desired_capacity, false_positive_probability);
+
+    test_bloom_filter_probability(
+        desired_capacity,
+        false_positive_probability,
+        bloom_filter,
+        relative_error_margin,
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn default_bloom_filter_new_deterministic_test() {
+    let mut filter = DefaultBloomFilter::new_deterministic(1_000, 0.01);
+    let mut stable = StableBloomFilterDefault::new(1_000, 0.01);
+    filter.insert_all(0..500);
+    stable.insert_all(0..500);
+
+    assert_eq!(stable, filter);
+    assert_eq!(
+        vec![500, 2_714, 3_772, 4_830, 5_888, 8_102, 9_160],
+        filter.probe_indices(&"Hello!")
+    );
+}
+
 #[test]
 fn false_positive_probability_test_random_default_fnv() {
     let desired_capacity = 1_000_000;
@@ -237,12 +353,14 @@ fn test_bloom_filter_probability_random<H1, H2>(
     assert!(true_checks <= (desired_capacity as f64 * (1.0 + allowed_probability)) as usize);
 }
 
-fn test_seeded_bloom_filter_probability(
+fn test_seeded_bloom_filter_probability<H>(
     desired_capacity: usize,
     false_positive_probability: f64,
-    mut bloom_filter: SeededBloomFilter,
+    mut bloom_filter: SingleHasherBloomFilter<H>,
     relative_error_margin: f64,
-) {
+) where
+    H: KeyedHasher,
+{
     let allowed_probability = false_positive_probability * (1.0 + relative_error_margin);
     for i in 0..desired_capacity {
         bloom_filter.insert(&i);
@@ -274,6 +392,7 @@ fn test_seeded_bloom_filter_probability(
 }
 
 #[test]
+#[cfg(feature = "std")]
 fn test_bloom_filter_with_strings() {
     let mut bloom_filter = DefaultBloomFilter::new(1000, 0.001);
 
@@ -293,6 +412,7 @@ fn test_bloom_filter_with_strings() {
 }
 
 #[test]
+#[cfg(feature = "std")]
 #[ignore]
 fn insert_and_check_its_there_with_millions_of_values() {
     let n_values = 10_000_000;
@@ -430,3 +550,3748 @@ fn km_bloom_filter_intersect_test() {
     );
     assert!(true_checks <= (desired_capacity as f64 * (1.0 + allowed_probability)) as usize);
 }
+
+#[test]
+fn counting_bloom_filter_remove_test() {
+    let desired_capacity = 10_000;
+    let false_positive_probability = 0.001;
+    let mut bloom_filter: CountingBloomFilter<ahash::AHasher, DefaultHasher, Counters4> =
+        CountingBloomFilter::new(desired_capacity, false_positive_probability);
+
+    for i in 0..desired_capacity {
+        bloom_filter.insert(&i);
+    }
+    for i in 0..(desired_capacity / 2) {
+        assert!(bloom_filter.remove(&i));
+    }
+
+    // Removing elements must never cause false negatives for the remaining elements
+    for i in (desired_capacity / 2)..desired_capacity {
+        assert!(bloom_filter.contains(&i));
+    }
+    let false_positives = (0..(desired_capacity / 2))
+        .filter(|i| bloom_filter.contains(i))
+        .count();
+    assert!(false_positives < desired_capacity / 100);
+}
+
+#[test]
+fn counting_bloom_filter_approximate_count_test() {
+    let mut bloom_filter_8: CountingBloomFilter<ahash::AHasher, DefaultHasher, Counters8> =
+        CountingBloomFilter::new(1000, 0.001);
+    let mut bloom_filter_16: CountingBloomFilter<ahash::AHasher, DefaultHasher, Counters16> =
+        CountingBloomFilter::new(1000, 0.001);
+
+    for _ in 0..300 {
+        bloom_filter_8.insert(&"frequent");
+        bloom_filter_16.insert(&"frequent");
+    }
+    bloom_filter_8.insert(&"rare");
+    bloom_filter_16.insert(&"rare");
+
+    // 8-bit counters saturate at 255, 16-bit counters do not
+    assert_eq!(255, bloom_filter_8.approximate_count(&"frequent"));
+    assert_eq!(300, bloom_filter_16.approximate_count(&"frequent"));
+    assert_eq!(1, bloom_filter_8.approximate_count(&"rare"));
+    assert_eq!(0, bloom_filter_16.approximate_count(&"missing"));
+
+    // Saturated counters are never decremented
+    bloom_filter_8.remove(&"frequent");
+    bloom_filter_16.remove(&"frequent");
+    assert_eq!(255, bloom_filter_8.approximate_count(&"frequent"));
+    assert_eq!(299, bloom_filter_16.approximate_count(&"frequent"));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn approximate_union_and_intersection_count_test() {
+    let desired_capacity = 100_000;
+    let false_positive_probability = 0.001;
+    let mut bloom_filter_a = DefaultBloomFilter::new(desired_capacity, false_positive_probability);
+    let mut bloom_filter_b = DefaultBloomFilter::new(desired_capacity, false_positive_probability);
+    let mut seeded_filter_a = SeededBloomFilter::new(desired_capacity, false_positive_probability);
+    let mut seeded_filter_b = SeededBloomFilter::new(desired_capacity, false_positive_probability);
+
+    for i in 0..60_000 {
+        bloom_filter_a.insert(&i);
+        seeded_filter_a.insert(&i);
+    }
+    for i in 40_000..100_000 {
+        bloom_filter_b.insert(&i);
+        seeded_filter_b.insert(&i);
+    }
+
+    let union_count = bloom_filter_a.approximate_union_count(&bloom_filter_b);
+    let intersection_count = bloom_filter_a.approximate_intersection_count(&bloom_filter_b);
+    assert!((union_count - 100_000.0).abs() < 2_000.0);
+    assert!((intersection_count - 20_000.0).abs() < 2_000.0);
+    assert_eq!(
+        union_count,
+        bloom_filter_a
+            .union(&bloom_filter_b)
+            .approximate_element_count()
+    );
+
+    let union_count = seeded_filter_a.approximate_union_count(&seeded_filter_b);
+    let intersection_count = seeded_filter_a.approximate_intersection_count(&seeded_filter_b);
+    assert!((union_count - 100_000.0).abs() < 2_000.0);
+    assert!((intersection_count - 20_000.0).abs() < 2_000.0);
+}
+
+#[test]
+fn jaccard_similarity_test() {
+    let desired_capacity = 100_000;
+    let false_positive_probability = 0.001;
+    for &(size, overlap) in [
+        (10_000, 0),
+        (50_000, 10_000),
+        (60_000, 20_000),
+        (50_000, 49_000),
+    ]
+    .iter()
+    {
+        let mut bloom_filter_a =
+            StableBloomFilterDefault::new(desired_capacity, false_positive_probability);
+        let mut bloom_filter_b =
+            StableBloomFilterDefault::new(desired_capacity, false_positive_probability);
+        let mut seeded_filter_a =
+            SeededBloomFilter::new(desired_capacity, false_positive_probability);
+        let mut seeded_filter_b =
+            SeededBloomFilter::new(desired_capacity, false_positive_probability);
+        let offset = size - overlap;
+        bloom_filter_a.insert_all(0..size);
+        seeded_filter_a.insert_all(0..size);
+        bloom_filter_b.insert_all(offset..offset + size);
+        seeded_filter_b.insert_all(offset..offset + size);
+
+        let expected = overlap as f64 / (size + offset) as f64;
+        let similarity = bloom_filter_a.jaccard_similarity(&bloom_filter_b);
+        assert!(
+            (similarity - expected).abs() < 0.01,
+            "{} != {}",
+            similarity,
+            expected
+        );
+        assert_eq!(
+            similarity,
+            bloom_filter_b.jaccard_similarity(&bloom_filter_a)
+        );
+        let similarity = seeded_filter_a.jaccard_similarity(&seeded_filter_b);
+        assert!(
+            (similarity - expected).abs() < 0.01,
+            "{} != {}",
+            similarity,
+            expected
+        );
+    }
+
+    let empty = StableBloomFilterDefault::new(desired_capacity, false_positive_probability);
+    assert_eq!(1.0, empty.jaccard_similarity(&empty));
+    let mut filter = StableBloomFilterDefault::new(desired_capacity, false_positive_probability);
+    filter.insert(&1);
+    assert_eq!(1.0, filter.jaccard_similarity(&filter));
+    assert_eq!(0.0, filter.jaccard_similarity(&empty));
+}
+
+#[test]
+#[should_panic(expected = "unable to compare k-m bloom filters with different configurations")]
+fn jaccard_similarity_of_different_configurations_panics() {
+    let filter_one = StableBloomFilterDefault::new(1_000, 0.01);
+    let filter_two = StableBloomFilterDefault::new(2_000, 0.01);
+    filter_one.jaccard_similarity(&filter_two);
+}
+
+#[test]
+fn overlap_matrix_matches_pairwise_intersection_counts() {
+    let filters: Vec<StableBloomFilterDefault> = (0..4)
+        .map(|user| {
+            let mut filter = StableBloomFilterDefault::new(10_000, 0.001);
+            filter.insert_all(user * 1_000..user * 1_000 + 3_000);
+            filter
+        })
+        .collect();
+
+    let matrix = StableBloomFilterDefault::overlap_matrix(&filters);
+    assert_eq!(4, matrix.len());
+    for (i, row) in matrix.iter().enumerate() {
+        assert_eq!(4, row.len());
+        assert_eq!(filters[i].approximate_element_count(), row[i]);
+        for (j, &overlap) in row.iter().enumerate().filter(|&(j, _)| j != i) {
+            assert_eq!(
+                filters[i].approximate_intersection_count(&filters[j]),
+                overlap
+            );
+            assert_eq!(matrix[j][i], overlap);
+        }
+    }
+    assert!((matrix[0][1] - 2_000.0).abs() < 200.0);
+    assert!((matrix[0][2] - 1_000.0).abs() < 200.0);
+    assert!(matrix[0][3].abs() < 200.0);
+
+    assert_eq!(
+        true,
+        StableBloomFilterDefault::overlap_matrix(&filters[..0]).is_empty()
+    );
+}
+
+#[test]
+#[should_panic(expected = "unable to intersect k-m bloom filters with different configurations")]
+fn overlap_matrix_of_different_configurations_panics() {
+    StableBloomFilterDefault::overlap_matrix(&[
+        StableBloomFilterDefault::new(1_000, 0.01),
+        StableBloomFilterDefault::new(2_000, 0.01),
+    ]);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn insert_all_test() {
+    let desired_capacity = 20_000;
+    let false_positive_probability = 0.001;
+    let mut bloom_filter = DefaultBloomFilter::new(desired_capacity, false_positive_probability);
+    let mut bloom_filter_all =
+        DefaultBloomFilter::new(desired_capacity, false_positive_probability);
+    let mut seeded_filter = SeededBloomFilter::new(desired_capacity, false_positive_probability);
+    let mut seeded_filter_all =
+        SeededBloomFilter::new(desired_capacity, false_positive_probability);
+    let mut counting_filter: CountingBloomFilter<ahash::AHasher, DefaultHasher> =
+        CountingBloomFilter::new(desired_capacity, false_positive_probability);
+    let mut counting_filter_all: CountingBloomFilter<ahash::AHasher, DefaultHasher> =
+        CountingBloomFilter::new(desired_capacity, false_positive_probability);
+
+    for i in 0..desired_capacity {
+        bloom_filter.insert(&i);
+        seeded_filter.insert(&i);
+        counting_filter.insert(&i);
+    }
+    bloom_filter_all.insert_all(0..desired_capacity);
+    seeded_filter_all.extend(0..desired_capacity);
+    counting_filter_all.extend(0..desired_capacity);
+
+    assert_eq!(bloom_filter, bloom_filter_all);
+    assert_eq!(seeded_filter, seeded_filter_all);
+    assert_eq!(counting_filter, counting_filter_all);
+}
+
+#[test]
+fn cuckoo_filter_false_positive_probability_test() {
+    let desired_capacity = 100_000;
+    let false_positive_probability = 0.001;
+    let mut cuckoo_filter: CuckooFilter<xxh3::Xxh3> =
+        CuckooFilter::new(desired_capacity, false_positive_probability);
+
+    for i in 0..desired_capacity {
+        cuckoo_filter.insert(&i);
+    }
+    assert_eq!(desired_capacity, cuckoo_filter.len());
+    assert!(
+        cuckoo_filter.approximate_current_false_positive_probability()
+            <= false_positive_probability
+    );
+    for i in 0..desired_capacity {
+        assert!(cuckoo_filter.contains(&i));
+    }
+
+    let false_positives = (desired_capacity..(desired_capacity * 2))
+        .filter(|i| cuckoo_filter.contains(i))
+        .count();
+    println!(
+        "Tested false positive probability: {}",
+        false_positives as f64 / desired_capacity as f64
+    );
+    assert!(false_positives as f64 <= desired_capacity as f64 * false_positive_probability);
+}
+
+#[test]
+fn cuckoo_filter_remove_test() {
+    let desired_capacity = 10_000;
+    let mut cuckoo_filter: CuckooFilter<ahash::AHasher> =
+        CuckooFilter::with_parameters(desired_capacity, 16, 2);
+
+    for i in 0..desired_capacity {
+        cuckoo_filter.insert(&i);
+    }
+    for i in 0..(desired_capacity / 2) {
+        assert!(cuckoo_filter.remove(&i));
+    }
+    assert_eq!(desired_capacity / 2, cuckoo_filter.len());
+
+    // Removing elements must never cause false negatives for the remaining elements
+    for i in (desired_capacity / 2)..desired_capacity {
+        assert!(cuckoo_filter.contains(&i));
+    }
+    let false_positives = (0..(desired_capacity / 2))
+        .filter(|i| cuckoo_filter.contains(i))
+        .count();
+    assert!(false_positives < desired_capacity / 100);
+}
+
+#[test]
+fn cuckoo_filter_full_test() {
+    let mut cuckoo_filter: CuckooFilter<ahash::AHasher> = CuckooFilter::with_parameters(100, 12, 4);
+
+    let mut inserted = Vec::new();
+    for i in 0..1_000 {
+        match cuckoo_filter.try_insert(&i) {
+            Ok(()) => inserted.push(i),
+            Err(error) => {
+                assert_eq!(CuckooFilterFull, error);
+                break;
+            }
+        }
+    }
+    assert!(cuckoo_filter.is_full());
+    assert!(inserted.len() >= 100);
+    assert!(inserted.len() <= cuckoo_filter.slot_count() + 1);
+
+    // No inserted element gets lost, even if the filter ran full
+    for i in inserted.iter() {
+        assert!(cuckoo_filter.contains(i));
+    }
+
+    // Removing elements makes room for the next insertion
+    let mut removed = 0;
+    while cuckoo_filter.is_full() {
+        assert!(cuckoo_filter.remove(&inserted[removed]));
+        removed += 1;
+    }
+    assert!(cuckoo_filter.try_insert(&"new element").is_ok());
+    assert!(cuckoo_filter.contains(&"new element"));
+    for i in inserted.iter().skip(removed) {
+        assert!(cuckoo_filter.contains(i));
+    }
+}
+
+#[test]
+fn quotient_filter_false_positive_probability_test() {
+    let desired_capacity = 100_000;
+    let false_positive_probability = 0.001;
+    let mut quotient_filter: QuotientFilter<xxh3::Xxh3> =
+        QuotientFilter::new(desired_capacity, false_positive_probability);
+
+    for i in 0..desired_capacity {
+        quotient_filter.insert(&i);
+    }
+    assert_eq!(desired_capacity, quotient_filter.len());
+    assert!(quotient_filter.fill_ratio() <= 0.75);
+    assert!(
+        quotient_filter.approximate_current_false_positive_probability()
+            <= false_positive_probability
+    );
+    for i in 0..desired_capacity {
+        assert!(quotient_filter.contains(&i));
+    }
+
+    let false_positives = (desired_capacity..(desired_capacity * 2))
+        .filter(|i| quotient_filter.contains(i))
+        .count();
+    println!(
+        "Tested false positive probability: {}",
+        false_positives as f64 / desired_capacity as f64
+    );
+    assert!(false_positives as f64 <= desired_capacity as f64 * false_positive_probability);
+}
+
+#[test]
+fn quotient_filter_remove_test() {
+    let desired_capacity = 10_000;
+    let mut quotient_filter: QuotientFilter<ahash::AHasher> =
+        QuotientFilter::new(desired_capacity, 0.001);
+
+    for i in 0..desired_capacity {
+        quotient_filter.insert(&i);
+    }
+    // Duplicates are stored, so removing one of them keeps the element
+    quotient_filter.insert(&0);
+    assert!(quotient_filter.remove(&0));
+    assert!(quotient_filter.contains(&0));
+
+    for i in 0..(desired_capacity / 2) {
+        assert!(quotient_filter.remove(&i));
+    }
+    assert_eq!(desired_capacity / 2, quotient_filter.len());
+
+    // Removing elements must never cause false negatives for the remaining elements
+    for i in (desired_capacity / 2)..desired_capacity {
+        assert!(quotient_filter.contains(&i));
+    }
+    let false_positives = (0..(desired_capacity / 2))
+        .filter(|i| quotient_filter.contains(i))
+        .count();
+    assert!(false_positives < desired_capacity / 100);
+}
+
+#[test]
+fn quotient_filter_grow_test() {
+    let mut quotient_filter: QuotientFilter<xxh3::Xxh3> = QuotientFilter::with_parameters(6, 14);
+
+    let mut inserted = Vec::new();
+    for i in 0..1_000 {
+        match quotient_filter.try_insert(&i) {
+            Ok(()) => inserted.push(i),
+            Err(error) => {
+                assert_eq!(QuotientFilterFull, error);
+                assert!(quotient_filter.is_full());
+                quotient_filter.grow();
+                quotient_filter.insert(&i);
+                inserted.push(i);
+            }
+        }
+    }
+    assert_eq!(1_024, quotient_filter.slot_count());
+    assert_eq!(10, quotient_filter.quotient_bits());
+    assert_eq!(10, quotient_filter.remainder_bits());
+    assert_eq!(20, quotient_filter.fingerprint_bits());
+    assert_eq!(1_000, quotient_filter.len());
+
+    // Growing keeps all elements, even though it does not know them
+    for i in inserted.iter() {
+        assert!(quotient_filter.contains(i));
+    }
+
+    // Growing keeps the fingerprints, so it is equivalent to inserting into a larger filter
+    let mut large_filter: QuotientFilter<xxh3::Xxh3> = QuotientFilter::with_parameters(10, 10);
+    large_filter.insert_all(&inserted);
+    assert_eq!(large_filter, quotient_filter);
+}
+
+#[test]
+fn quotient_filter_union_test() {
+    let mut filter_a: QuotientFilter<xxh3::Xxh3> = QuotientFilter::with_parameters(8, 12);
+    let mut filter_b: QuotientFilter<xxh3::Xxh3> = QuotientFilter::with_parameters(9, 11);
+    filter_a.insert_all(0..150);
+    filter_b.insert_all(100..300);
+
+    // Fingerprints are iterated in order
+    let fingerprints: Vec<u64> = filter_a.fingerprints().collect();
+    assert_eq!(150, fingerprints.len());
+    assert!(fingerprints.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(fingerprints.iter().all(|&f| f < 1 << 20));
+
+    let union = filter_a.union(&filter_b);
+    assert_eq!(350, union.len());
+    assert!(union.fill_ratio() <= 0.75);
+    assert_eq!(20, union.fingerprint_bits());
+    for i in 0..300 {
+        assert!(union.contains(&i));
+    }
+    let mut merged: Vec<u64> = filter_a
+        .fingerprints()
+        .chain(filter_b.fingerprints())
+        .collect();
+    merged.sort_unstable();
+    assert_eq!(merged, union.fingerprints().collect::<Vec<_>>());
+
+    // The layout does not depend on the order of insertions
+    assert_eq!(union, filter_b.union(&filter_a));
+    filter_a.union_with(&filter_b);
+    assert_eq!(union, filter_a);
+}
+
+#[test]
+#[should_panic(expected = "unable to union quotient filters with different configurations")]
+fn quotient_filter_union_with_different_fingerprints_panics() {
+    let filter_a: QuotientFilter<xxh3::Xxh3> = QuotientFilter::with_parameters(8, 12);
+    let filter_b: QuotientFilter<xxh3::Xxh3> = QuotientFilter::with_parameters(8, 11);
+    filter_a.union(&filter_b);
+}
+
+#[test]
+fn d_left_counting_filter_false_positive_probability_test() {
+    let desired_capacity = 100_000;
+    let false_positive_probability = 0.001;
+    let mut d_left_filter: DLeftCountingFilter<xxh3::Xxh3> =
+        DLeftCountingFilter::new(desired_capacity, false_positive_probability);
+
+    for i in 0..desired_capacity {
+        d_left_filter.insert(&i);
+    }
+    assert!(
+        d_left_filter.approximate_current_false_positive_probability()
+            <= false_positive_probability
+    );
+    for i in 0..desired_capacity {
+        assert!(d_left_filter.contains(&i));
+    }
+
+    let false_positives = (desired_capacity..(desired_capacity * 2))
+        .filter(|i| d_left_filter.contains(i))
+        .count();
+    println!(
+        "Tested false positive probability: {}",
+        false_positives as f64 / desired_capacity as f64
+    );
+    assert!(false_positives as f64 <= desired_capacity as f64 * false_positive_probability);
+
+    // The same guarantee with 4-bit counters requires about twice the memory
+    let counting_filter: CountingBloomFilter<ahash::AHasher, DefaultHasher, Counters4> =
+        CountingBloomFilter::new(desired_capacity, false_positive_probability);
+    assert!(d_left_filter.bit_count() * 3 < counting_filter.counter_count() * 4 * 2);
+}
+
+#[test]
+fn d_left_counting_filter_remove_test() {
+    let desired_capacity = 10_000;
+    let mut d_left_filter: DLeftCountingFilter<ahash::AHasher> =
+        DLeftCountingFilter::with_parameters(desired_capacity, 16, 3);
+
+    for i in 0..desired_capacity {
+        d_left_filter.insert(&i);
+    }
+    d_left_filter.insert(&0);
+    assert_eq!(2, d_left_filter.approximate_count(&0));
+    for i in 0..(desired_capacity / 2) {
+        assert!(d_left_filter.remove(&i));
+    }
+    assert_eq!(true, d_left_filter.contains(&0));
+    assert!(d_left_filter.remove(&0));
+    assert_eq!(0, d_left_filter.approximate_count(&0));
+
+    // Removing elements must never cause false negatives for the remaining elements
+    for i in (desired_capacity / 2)..desired_capacity {
+        assert!(d_left_filter.contains(&i));
+    }
+    let false_positives = (0..(desired_capacity / 2))
+        .filter(|i| d_left_filter.contains(i))
+        .count();
+    assert!(false_positives < desired_capacity / 100);
+
+    // Saturated counters are never decremented
+    for _ in 0..10 {
+        d_left_filter.insert(&"frequent");
+    }
+    assert_eq!(7, d_left_filter.approximate_count(&"frequent"));
+    for _ in 0..10 {
+        assert!(d_left_filter.remove(&"frequent"));
+    }
+    assert_eq!(true, d_left_filter.contains(&"frequent"));
+}
+
+#[test]
+fn d_left_counting_filter_full_test() {
+    let mut d_left_filter: DLeftCountingFilter<ahash::AHasher> =
+        DLeftCountingFilter::with_parameters(100, 12, 2);
+
+    let mut inserted = Vec::new();
+    for i in 0..1_000 {
+        match d_left_filter.try_insert(&i) {
+            Ok(()) => inserted.push(i),
+            Err(error) => {
+                assert_eq!(DLeftCountingFilterFull, error);
+                break;
+            }
+        }
+    }
+    assert!(inserted.len() >= 100);
+    // Elements with the same fingerprint in the same bucket share a cell
+    assert!(d_left_filter.len() <= inserted.len());
+    assert!(d_left_filter.len() <= d_left_filter.cell_count());
+    for i in inserted.iter() {
+        assert!(d_left_filter.contains(i));
+    }
+
+    d_left_filter.clear();
+    assert_eq!(true, d_left_filter.is_empty());
+    assert_eq!(0.0, d_left_filter.fill_ratio());
+    for i in inserted.iter() {
+        assert_eq!(false, d_left_filter.contains(i));
+    }
+}
+
+#[test]
+fn golomb_coded_set_false_positive_probability_test() {
+    let desired_capacity = 2_000;
+    let false_positive_probability = 0.001;
+    let mut gcs_filter: GcsFilter<StableXxh3Hasher> = GcsFilter::new(false_positive_probability);
+    gcs_filter.extend(0..desired_capacity);
+    assert_eq!(desired_capacity, gcs_filter.len());
+
+    let gcs: GolombCodedSet<StableXxh3Hasher> =
+        GolombCodedSet::from_bytes(gcs_filter.to_gcs().as_bytes().to_vec()).unwrap();
+    assert_eq!(desired_capacity, gcs.len());
+    assert!(gcs.approximate_false_positive_probability() <= false_positive_probability);
+    for i in 0..desired_capacity {
+        assert!(gcs.contains(&i));
+    }
+
+    let tested = 10_000;
+    let false_positives = (desired_capacity..(desired_capacity + tested))
+        .filter(|i| gcs.contains(i))
+        .count();
+    assert!(false_positives as f64 <= tested as f64 * false_positive_probability * 2.0);
+
+    // The encoded set is smaller than a bloom filter with the same false positive probability
+    let bloom_filter = StableBloomFilterDefault::new(desired_capacity, false_positive_probability);
+    assert!((gcs.byte_count() as f64) < bloom_filter.bit_count() as f64 / 8.0 * 0.85);
+}
+
+#[test]
+fn golomb_coded_set_hash64_test() {
+    let mut gcs_filter: GcsFilter<StableXxh3Hasher> = GcsFilter::new(0.01);
+    gcs_filter.insert_hash64(xxh3::xxh3_64_with_seed(b"Hello!", STABLE_XXH3_SEED));
+    gcs_filter.insert(&42);
+    let gcs = gcs_filter.to_gcs();
+    assert_eq!(
+        true,
+        gcs.contains_hash64(xxh3::xxh3_64_with_seed(b"Hello!", STABLE_XXH3_SEED))
+    );
+    assert_eq!(true, gcs.contains(&42));
+}
+
+#[test]
+fn golomb_coded_set_empty_and_invalid_test() {
+    let gcs_filter: GcsFilter<StableXxh3Hasher> = GcsFilter::new(0.01);
+    let gcs = gcs_filter.to_gcs();
+    assert_eq!(true, gcs.is_empty());
+    assert_eq!(false, gcs.contains(&1));
+
+    let restored: GolombCodedSet<StableXxh3Hasher> =
+        GolombCodedSet::from_bytes(gcs.as_bytes().to_vec()).unwrap();
+    assert_eq!(false, restored.contains(&1));
+
+    assert_eq!(
+        InvalidGolombCodedSet,
+        GolombCodedSet::<StableXxh3Hasher>::from_bytes(vec![1, 0, 0]).unwrap_err()
+    );
+    let mut bytes = gcs.as_bytes().to_vec();
+    bytes[8] = 0;
+    assert!(GolombCodedSet::<StableXxh3Hasher>::from_bytes(bytes).is_err());
+}
+
+#[test]
+fn xor_filter_false_positive_probability_test() {
+    let desired_capacity = 100_000;
+    let xor_filter: XorFilter<xxh3::Xxh3> = XorFilter::from_elements(0..desired_capacity);
+    assert_eq!(desired_capacity, xor_filter.len());
+    assert!(xor_filter.byte_count() <= (desired_capacity as f64 * 1.23) as usize + 32);
+    for i in 0..desired_capacity {
+        assert!(xor_filter.contains(&i));
+    }
+
+    let false_positives = (desired_capacity..(desired_capacity * 2))
+        .filter(|i| xor_filter.contains(i))
+        .count();
+    println!(
+        "Tested false positive probability: {}",
+        false_positives as f64 / desired_capacity as f64
+    );
+    assert!(
+        false_positives as f64
+            <= desired_capacity as f64 * xor_filter.approximate_false_positive_probability() * 1.2
+    );
+}
+
+#[test]
+fn xor_filter_from_recorded_hashes_test() {
+    let mut gcs_filter: GcsFilter<ahash::AHasher> = GcsFilter::new(0.01);
+    gcs_filter.extend(0..1_000);
+    // Duplicates are only stored once
+    gcs_filter.extend(0..1_000);
+    let xor_filter = gcs_filter.to_xor_filter();
+    assert_eq!(1_000, xor_filter.len());
+    for i in 0..1_000 {
+        assert!(xor_filter.contains(&i));
+    }
+
+    let xor_filter: XorFilter<ahash::AHasher> = XorFilter::from_elements(vec![7, 7, 7]);
+    assert_eq!(1, xor_filter.len());
+    assert_eq!(true, xor_filter.contains(&7));
+
+    let xor_filter: XorFilter<ahash::AHasher> = XorFilter::from_hashes(Vec::new());
+    assert_eq!(true, xor_filter.is_empty());
+    assert_eq!(false, xor_filter.contains(&7));
+}
+
+#[test]
+fn bloom_map_routes_keys_to_shards() {
+    let shard_count = 16;
+    let pairs = (0..10_000u32).map(|key| (key, key % shard_count));
+    let map: BloomMap<u32, xxh3::Xxh3> = BloomMap::from_pairs(pairs, 0.01);
+    assert_eq!(10_000, map.len());
+    assert_eq!(16, map.values().len());
+    assert_eq!(7, map.fingerprint_bits());
+    for key in 0..10_000u32 {
+        assert_eq!(Some(&(key % shard_count)), map.get(&key));
+    }
+
+    let false_positives = (10_000..110_000u32)
+        .filter(|key| map.get(key).is_some())
+        .count();
+    assert!(
+        false_positives as f64 <= 100_000.0 * map.approximate_false_positive_probability() * 1.2
+    );
+
+    // The last value of a key is kept
+    let map: BloomMap<&str, ahash::AHasher> =
+        BloomMap::from_pairs(vec![(1, "a"), (2, "b"), (1, "c")], 0.001);
+    assert_eq!(2, map.len());
+    assert_eq!(Some(&"c"), map.get(&1));
+    assert_eq!(Some(&"b"), map.get(&2));
+
+    let map: BloomMap<u8, ahash::AHasher> = BloomMap::from_hashes(Vec::new(), 0.001);
+    assert_eq!(true, map.is_empty());
+    assert_eq!(None, map.get(&1));
+}
+
+#[test]
+#[should_panic]
+fn bloom_map_with_too_many_values_panics() {
+    let _: BloomMap<u32, ahash::AHasher> =
+        BloomMap::from_pairs((0..1_000u32).map(|i| (i, i)), 1e-8);
+}
+
+#[test]
+fn dyn_bloom_filter_test() {
+    let mut filters: Vec<Box<dyn DynBloomFilter>> = vec![
+        Box::new(StableBloomFilterDefault::new(1_000, 0.001)),
+        Box::new(SeededBloomFilter::with_seeds(1_000, 0.001, 1, 2)),
+        Box::new(CountingBloomFilter::<xxh3::Xxh3, xxh64::Xxh64>::new(
+            1_000, 0.001,
+        )),
+        Box::new(ShardedBloomFilter::<StableXxh3Hasher, StableXxh64Hasher>::new(4, 1_000, 0.001)),
+        Box::new(CuckooFilter::<xxh3::Xxh3>::new(1_000, 0.001)),
+        Box::new(QuotientFilter::<xxh3::Xxh3>::new(1_000, 0.001)),
+    ];
+    for filter in filters.iter_mut() {
+        for i in 0..500u32 {
+            filter.dyn_insert(&i.to_le_bytes());
+        }
+        for i in 0..500u32 {
+            assert_eq!(true, filter.dyn_contains(&i.to_le_bytes()));
+        }
+        let false_positives = (500..1_500u32)
+            .filter(|i| filter.dyn_contains(&i.to_le_bytes()))
+            .count();
+        assert!(false_positives < 10);
+    }
+
+    // Byte keys are compatible with the generic methods for byte slices and vectors
+    let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+    filter.insert(&b"generic".to_vec());
+    filter.dyn_insert(b"dynamic");
+    assert_eq!(true, filter.dyn_contains(b"generic"));
+    assert_eq!(true, filter.contains(&&b"dynamic"[..]));
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn parallel_construction_test() {
+    use rayon::prelude::*;
+
+    let desired_capacity = 100_000;
+    let elements: Vec<usize> = (0..desired_capacity).collect();
+    let parallel_filter =
+        StableBloomFilterDefault::par_from_iter(elements.par_iter(), desired_capacity, 0.001);
+    let mut sequential_filter = StableBloomFilterDefault::new(desired_capacity, 0.001);
+    sequential_filter.insert_all(elements.iter());
+
+    assert_eq!(sequential_filter, parallel_filter);
+    for element in elements.iter() {
+        assert_eq!(true, parallel_filter.contains(&element));
+    }
+}
+
+#[test]
+#[cfg(feature = "io")]
+fn streaming_construction_test() {
+    let keys: Vec<String> = (0..10_000).map(|i| format!("keyword-{}", i)).collect();
+
+    let lines = keys.join("\n");
+    let filter = DefaultBloomFilter::from_lines(lines.as_bytes(), keys.len(), 0.001).unwrap();
+    for key in keys.iter() {
+        assert_eq!(true, filter.contains(&key.as_str()));
+    }
+
+    let delimited = keys.join("\0");
+    let filter =
+        DefaultBloomFilter::from_delimited(delimited.as_bytes(), b'\0', keys.len(), 0.001).unwrap();
+    for key in keys.iter() {
+        assert_eq!(true, filter.contains(&key.as_str()));
+    }
+
+    let mut counting_filter: CountingBloomFilter<ahash::AHasher, DefaultHasher> =
+        CountingBloomFilter::new(10, 0.001);
+    assert_eq!(
+        3,
+        counting_filter
+            .insert_lines("a\na\nb\n".as_bytes())
+            .unwrap()
+    );
+    assert_eq!(2, counting_filter.approximate_count(&"a"));
+}
+
+#[test]
+fn seeded_bloom_filter_seeds_test() {
+    let mut filter_one = SeededBloomFilter::with_seeds(1_000, 0.001, 1, 2);
+    let mut filter_two = SeededBloomFilter::with_seeds(1_000, 0.001, 1, 2);
+    let mut filter_three = SeededBloomFilter::with_seeds(1_000, 0.001, 3, 4);
+    filter_one.insert(&"Hello!");
+    filter_two.insert(&"Hello!");
+    filter_three.insert(&"Hello!");
+
+    assert_eq!((1, 2), filter_one.seeds());
+    assert_eq!(filter_one, filter_two);
+    assert_eq!(false, filter_one.eq_configuration(&filter_three));
+    assert_ne!(filter_one, filter_three);
+    assert_eq!(true, filter_three.contains(&"Hello!"));
+
+    // The default seeds are kept for filters created with new
+    let mut filter_default = SeededBloomFilter::new(1_000, 0.001);
+    let mut filter_zero = SeededBloomFilter::with_seeds(1_000, 0.001, 0, 0);
+    filter_default.insert(&"Hello!");
+    filter_zero.insert(&"Hello!");
+    assert_eq!(filter_default, filter_zero);
+}
+
+#[test]
+fn seeded_bloom_filter_base_seed_test() {
+    let filter_one = SeededBloomFilter::with_base_seed(10_000, 0.001, 1);
+    let filter_two = SeededBloomFilter::with_base_seed(10_000, 0.001, 2);
+    assert_eq!(
+        SeededBloomFilter::with_base_seed(10_000, 0.001, 1).seeds(),
+        filter_one.seeds()
+    );
+    assert_ne!(filter_one.seeds(), filter_two.seeds());
+
+    // The hasher keys are the outputs of SplitMix64, whose first output for the seed 0 is known
+    let first_output = 0xe220_a839_7b1d_cdafu128;
+    let keys = SeededBloomFilter::new(10, 0.01).hasher_keys(0);
+    assert_eq!(
+        (
+            first_output | first_output << 64,
+            first_output | first_output << 64
+        ),
+        keys
+    );
+
+    // Filters with similar base seeds and the hashers of one filter are independent, i.e., no more
+    // probes coincide than for random indices
+    let matching_probes = |a: &SeededBloomFilter, b: &SeededBloomFilter, hasher_b: usize| {
+        (0..10_000)
+            .filter(|i| {
+                let bits = a.bit_count() / a.hasher_count();
+                a.probe_indices(i)[0] % bits == b.probe_indices(i)[hasher_b] % bits
+            })
+            .count()
+    };
+    assert!(matching_probes(&filter_one, &filter_two, 0) < 30);
+    assert!(matching_probes(&filter_one, &filter_one, 1) < 30);
+    let similar_seeds_one = SeededBloomFilter::with_seeds(10_000, 0.001, 1, 2);
+    let similar_seeds_two = SeededBloomFilter::with_seeds(10_000, 0.001, 3, 2);
+    assert!(matching_probes(&similar_seeds_one, &similar_seeds_two, 0) < 30);
+}
+
+#[test]
+#[should_panic(expected = "different configurations")]
+fn seeded_bloom_filter_union_with_different_seeds() {
+    let filter_one = SeededBloomFilter::with_seeds(1_000, 0.001, 1, 2);
+    let filter_two = SeededBloomFilter::with_seeds(1_000, 0.001, 3, 4);
+    filter_one.union(&filter_two);
+}
+
+#[test]
+#[cfg(feature = "rand")]
+fn seeded_bloom_filter_random_seeds_test() {
+    let mut filter = SeededBloomFilter::new_random(1_000, 0.001, &mut StdRng::seed_from_u64(3));
+    let (seed_a, seed_b) = filter.seeds();
+    assert_ne!(seed_a, seed_b);
+    filter.insert(&"Hello!");
+    assert_eq!(true, filter.contains(&"Hello!"));
+
+    // Seeds are drawn again if they are equal
+    struct RepeatingRng(Vec<u64>);
+    impl rand::RngCore for RepeatingRng {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+        fn next_u64(&mut self) -> u64 {
+            self.0.remove(0)
+        }
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for byte in dest.iter_mut() {
+                *byte = self.next_u32() as u8;
+            }
+        }
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+    let mut rng = RepeatingRng(vec![1, 1, 1, 1, 2, 2]);
+    let filter = SeededBloomFilter::new_random(1_000, 0.001, &mut rng);
+    assert_eq!((1 << 64 | 1, 2 << 64 | 2), filter.seeds());
+}
+
+#[test]
+#[cfg(feature = "rand")]
+fn km_bloom_filter_noise_test() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut filter = StableBloomFilterDefault::new(10_000, 0.01).with_noise(0.1, &mut rng);
+    assert_eq!(0.1, filter.noise_fraction());
+    let fill_ratio = filter.bitset().count_ones() as f64 / filter.bit_count() as f64;
+    assert!((fill_ratio - 0.1).abs() < 0.01);
+    assert!(filter.approximate_element_count().abs() < 100.0);
+
+    filter.insert_all(0..5_000);
+    for i in 0..5_000 {
+        assert_eq!(true, filter.contains(&i));
+    }
+    assert!((filter.approximate_element_count() - 5_000.0).abs() < 250.0);
+    let (lower, upper) = filter.approximate_element_count_bounds();
+    assert!(lower < 5_000.0 && 5_000.0 < upper);
+
+    // The estimated false positive probability accounts for the noise
+    let expected = filter.approximate_current_false_positive_probability();
+    let false_positives = (5_000..105_000).filter(|i| filter.contains(i)).count();
+    let measured = false_positives as f64 / 100_000.0;
+    assert!((measured - expected).abs() < 0.2 * expected);
+    let mut noiseless = StableBloomFilterDefault::new(10_000, 0.01);
+    noiseless.insert_all(0..5_000);
+    assert!(expected > 2.0 * noiseless.approximate_current_false_positive_probability());
+
+    // Noise of a union is the probability that either filter has set a bit at random
+    let other = StableBloomFilterDefault::new(10_000, 0.01).with_noise(0.2, &mut rng);
+    assert!((filter.union(&other).noise_fraction() - 0.28).abs() < 1e-9);
+    filter.clear();
+    assert_eq!(0.0, filter.noise_fraction());
+}
+
+#[test]
+#[cfg(feature = "rand")]
+#[should_panic(expected = "unable to add noise")]
+fn km_bloom_filter_noise_out_of_range() {
+    StableBloomFilterDefault::new(1_000, 0.01).add_noise(1.0, &mut StdRng::seed_from_u64(1));
+}
+
+#[test]
+fn difference_test() {
+    let mut shard = StableBloomFilterDefault::new(10_000, 0.001);
+    let mut aggregate = StableBloomFilterDefault::new(10_000, 0.001);
+    shard.insert_all(0..3_000);
+    aggregate.insert_all(1_000..5_000);
+
+    let approximate_difference = shard.approximate_difference_count(&aggregate);
+    assert!((approximate_difference - 1_000.0).abs() < 50.0);
+    assert!(aggregate.approximate_difference_count(&aggregate) < 1.0);
+
+    let difference = shard.difference(&aggregate);
+    // Elements of the other filter are never contained in the difference
+    for i in 1_000..5_000 {
+        assert_eq!(false, difference.contains(&i));
+    }
+    // Bits of the difference are set in the first filter, but not in the second one
+    assert_eq!(true, difference.is_subset(&shard));
+    assert_eq!(true, difference.is_disjoint_probably(&aggregate));
+
+    let mut seeded_shard = SeededBloomFilter::new(10_000, 0.001);
+    let mut seeded_aggregate = SeededBloomFilter::new(10_000, 0.001);
+    seeded_shard.insert_all(0..3_000);
+    seeded_aggregate.insert_all(1_000..5_000);
+    assert!((seeded_shard.approximate_difference_count(&seeded_aggregate) - 1_000.0).abs() < 50.0);
+    let difference = seeded_shard.difference(&seeded_aggregate);
+    assert_eq!(true, difference.is_subset(&seeded_shard));
+    assert!(difference.bitset().count_ones() > 0);
+}
+
+#[test]
+fn compact_test() {
+    let new = || {
+        let mut filter = StableBloomFilterDefault::new_compactable(100_000, 0.001, 8);
+        filter.insert_all(0..5_000);
+        filter
+    };
+    let filter = new();
+    assert_eq!(0, filter.bit_count() % (8 * filter.hasher_count()));
+    let false_positive_probability = filter.approximate_current_false_positive_probability();
+
+    let compacted = new().compact(4);
+    assert_eq!(filter.bit_count() / 4, compacted.bit_count());
+    assert_eq!(filter.hasher_count(), compacted.hasher_count());
+    for i in 0..5_000 {
+        assert_eq!(true, compacted.contains(&i));
+    }
+    assert!(
+        compacted.approximate_current_false_positive_probability() > false_positive_probability
+    );
+    assert!((compacted.approximate_element_count() - 5_000.0).abs() < 250.0);
+
+    // Folding in steps gives the same result, and inserting the elements again changes nothing
+    assert_eq!(compacted, new().compact(2).compact(2));
+    let mut reinserted = new().compact(4);
+    reinserted.insert_all(0..5_000);
+    assert_eq!(compacted, reinserted);
+    assert_eq!(filter, new().compact(1));
+}
+
+#[test]
+fn power_of_two_filters_mask_indices() {
+    let mut filter = StableBloomFilterDefault::new_power_of_two(10_000, 0.001);
+    let bits_per_hasher = filter.bit_count() / filter.hasher_count();
+    assert!(bits_per_hasher.is_power_of_two());
+    assert!(filter.bit_count() >= StableBloomFilterDefault::new(10_000, 0.001).bit_count());
+    filter.insert_all(0..10_000);
+    assert_eq!(true, (0..10_000).all(|i| filter.contains(&i)));
+    assert!(filter.approximate_current_false_positive_probability() < 0.001);
+
+    // The bitmask selects the same bits as the modulo of the hashing scheme
+    let mut expected =
+        StableBloomFilterDefault::with_parameters(filter.bit_count(), filter.hasher_count());
+    expected.insert_all(0..10_000);
+    assert_eq!(expected, filter);
+    let vectors = test_vectors::generate::<StableXxh3Hasher, StableXxh64Hasher>(
+        filter.hasher_count(),
+        bits_per_hasher,
+    );
+    for vector in vectors.iter() {
+        for (i, &index) in vector.indices.iter().enumerate() {
+            let hash = vector
+                .hash_a
+                .wrapping_add((i as u64).wrapping_mul(vector.hash_b));
+            assert_eq!(
+                i * bits_per_hasher + (hash % bits_per_hasher as u64) as usize,
+                index
+            );
+        }
+    }
+}
+
+// Filters whose bits do not fit into usize are rejected before any index can overflow
+#[test]
+#[should_panic(expected = "needs more than usize::MAX bits")]
+fn with_parameters_beyond_usize_panics() {
+    StableBloomFilterDefault::with_parameters(usize::MAX, 2);
+}
+
+#[cfg(target_pointer_width = "64")]
+#[test]
+#[should_panic(expected = "needs more than usize::MAX bits")]
+fn power_of_two_beyond_usize_panics() {
+    // A single hasher with more than 2^63 bits
+    StableBloomFilterDefault::new_power_of_two(usize::MAX / 2, 0.5);
+}
+
+#[test]
+#[should_panic(expected = "unable to compact")]
+fn compact_by_non_dividing_factor_panics() {
+    StableBloomFilterDefault::new_compactable(1_000, 0.01, 2).compact(4);
+}
+
+#[test]
+fn global_index_layout_addresses_all_bits() {
+    let new = || StableBloomFilterDefault::new(10_000, 0.01).with_index_layout(IndexLayout::Global);
+    let mut filter = new();
+    assert_eq!(IndexLayout::Global, filter.index_layout());
+    filter.insert_all(0..10_000);
+    assert_eq!(true, (0..10_000).all(|i| filter.contains(&i)));
+    let false_positives = (10_000..110_000).filter(|i| filter.contains(i)).count();
+    assert!(
+        false_positives < 1_200,
+        "{} false positives",
+        false_positives
+    );
+    assert!((filter.approximate_element_count() - 10_000.0).abs() < 300.0);
+
+    // Every hash function addresses all bits with the documented hashing scheme
+    let mut hasher = StableXxh3Hasher::default();
+    "Hello!".hash(&mut hasher);
+    let hash_a = hasher.finish();
+    let mut hasher = StableXxh64Hasher::default();
+    "Hello!".hash(&mut hasher);
+    let hash_b = hasher.finish();
+    let expected: Vec<usize> = (0..filter.hasher_count())
+        .map(|i| {
+            (hash_a.wrapping_add((i as u64).wrapping_mul(hash_b)) % filter.bit_count() as u64)
+                as usize
+        })
+        .collect();
+    assert_eq!(expected, filter.probe_indices(&"Hello!"));
+
+    // The layout is part of the configuration
+    let partitioned = StableBloomFilterDefault::new(10_000, 0.01);
+    assert_eq!(false, filter.eq_configuration(&partitioned));
+    assert_eq!(true, filter.eq_configuration(&new()));
+
+    // Compacting, freezing, and persisting keep the layout
+    let mut compactable = StableBloomFilterDefault::new_compactable(10_000, 0.01, 4)
+        .with_index_layout(IndexLayout::Global);
+    compactable.insert_all(0..1_000);
+    let compacted = compactable.compact(4);
+    assert_eq!(true, (0..1_000).all(|i| compacted.contains(&i)));
+    let mut reinserted =
+        StableBloomFilterDefault::with_parameters(compacted.bit_count(), compacted.hasher_count())
+            .with_index_layout(IndexLayout::Global);
+    reinserted.insert_all(0..1_000);
+    assert_eq!(reinserted, compacted);
+
+    let thawed = new().freeze().thaw();
+    assert_eq!(IndexLayout::Global, thawed.index_layout());
+
+    #[cfg(feature = "std")]
+    {
+        let bytes = filter.to_bytes();
+        assert_eq!(2u32.to_le_bytes(), bytes[4..8]);
+        assert_eq!(
+            filter,
+            StableBloomFilterDefault::from_bytes(&bytes).unwrap()
+        );
+        assert_eq!(
+            filter,
+            StableBloomFilterDefault::read_from(&bytes[..]).unwrap()
+        );
+        assert!(matches!(
+            BloomFilterView::<StableXxh3Hasher, StableXxh64Hasher>::read_from(&bytes),
+            Err(ReadFilterError::UnsupportedVersion(2))
+        ));
+        assert_eq!(1u32.to_le_bytes(), partitioned.to_bytes()[4..8]);
+    }
+}
+
+#[test]
+fn global_index_layout_counts_colliding_probes_once() {
+    // With as many hashers as bits, the probes of an element hit the same bits
+    let new =
+        || StableBloomFilterDefault::with_parameters(8, 8).with_index_layout(IndexLayout::Global);
+    let element = (0..)
+        .find(|i| {
+            let mut indices = new().probe_indices(i);
+            indices.sort_unstable();
+            indices.dedup();
+            indices.len() <= 4
+        })
+        .unwrap();
+
+    // Four bits of eight set hold about 0.7 elements, while eight would exceed any capacity
+    let mut filter = new().with_strict_capacity(1);
+    assert_eq!(Ok(()), filter.try_insert(&element));
+    assert_eq!(true, filter.contains(&element));
+    let mut filter = new().with_saturation_policy(0.5, OnSaturation::Error);
+    assert_eq!(Ok(()), filter.try_insert(&element));
+    assert_eq!(true, filter.contains(&element));
+}
+
+#[test]
+#[should_panic(expected = "unable to union k-m bloom filters with different configurations")]
+fn union_of_different_index_layouts_panics() {
+    let global = StableBloomFilterDefault::new(1_000, 0.01).with_index_layout(IndexLayout::Global);
+    global.union(&StableBloomFilterDefault::new(1_000, 0.01));
+}
+
+#[test]
+#[should_panic(expected = "unable to change the index layout")]
+fn changing_index_layout_of_filled_filter_panics() {
+    let mut filter = StableBloomFilterDefault::new(1_000, 0.01);
+    filter.insert(&1);
+    filter.with_index_layout(IndexLayout::Global);
+}
+
+#[test]
+fn probe_indices_test() {
+    let filter = StableBloomFilterDefault::new(1_000, 0.01);
+    let bits_per_hasher = filter.bit_count() / filter.hasher_count();
+
+    // Reproduce the indices from the documented hashing scheme
+    let mut hasher = StableXxh3Hasher::default();
+    "Hello!".hash(&mut hasher);
+    let hash_a = hasher.finish();
+    let mut hasher = StableXxh64Hasher::default();
+    "Hello!".hash(&mut hasher);
+    let hash_b = hasher.finish();
+    let expected: Vec<usize> = (0..filter.hasher_count())
+        .map(|i| {
+            i * bits_per_hasher
+                + (hash_a.wrapping_add((i as u64).wrapping_mul(hash_b)) % bits_per_hasher as u64)
+                    as usize
+        })
+        .collect();
+    assert_eq!(expected, filter.probe_indices(&"Hello!"));
+
+    let counting_filter: CountingBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        CountingBloomFilter::new(1_000, 0.01);
+    assert_eq!(expected, counting_filter.probe_indices(&"Hello!"));
+
+    let mut seeded_filter = SeededBloomFilter::new(1_000, 0.01);
+    seeded_filter.insert(&"Hello!");
+    let mut indices = seeded_filter.probe_indices(&"Hello!");
+    assert_eq!(seeded_filter.hasher_count(), indices.len());
+    indices.sort_unstable();
+    indices.dedup();
+    let ones: Vec<usize> = seeded_filter.bitset().iter_ones().collect();
+    assert_eq!(ones, indices);
+}
+
+#[test]
+fn approximate_bounds_test() {
+    // The bounds are 95% confidence intervals, so nearly all filters cover the true element count
+    let mut covered = 0;
+    for sample in 0..100 {
+        let mut filter = StableBloomFilterDefault::new(10_000, 0.01);
+        filter.insert_all(sample * 10_000..sample * 10_000 + 8_000);
+        let (lower, upper) = filter.approximate_element_count_bounds();
+        assert!(lower <= filter.approximate_element_count());
+        assert!(filter.approximate_element_count() <= upper);
+        if (lower..=upper).contains(&8_000.0) {
+            covered += 1;
+        }
+
+        let (lower, upper) = filter.approximate_current_false_positive_probability_bounds();
+        assert!(lower <= filter.approximate_current_false_positive_probability());
+        assert!(filter.approximate_current_false_positive_probability() <= upper);
+    }
+    assert!(covered >= 85);
+
+    let mut filter = SeededBloomFilter::new(1_000, 0.01);
+    filter.insert_all(0..500);
+    let (lower, upper) = filter.approximate_element_count_bounds();
+    assert!(lower < 500.0 && 500.0 < upper);
+
+    let mut filter: CountingBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        CountingBloomFilter::new(1_000, 0.01);
+    assert_eq!((0.0, 0.0), filter.approximate_element_count_bounds());
+    filter.insert_all(0..500);
+    let (lower, upper) = filter.approximate_element_count_bounds();
+    assert!(lower < 500.0 && 500.0 < upper);
+
+    // A full filter has no upper bound
+    let mut filter = StableBloomFilterDefault::new(10, 0.1);
+    filter.insert_all(0..1_000);
+    assert_eq!(f64::INFINITY, filter.approximate_element_count_bounds().1);
+    assert_eq!(
+        1.0,
+        filter
+            .approximate_current_false_positive_probability_bounds()
+            .1
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn union_and_intersect_in_place() {
+    let mut filter_one = DefaultBloomFilter::new(1_000, 0.01);
+    let mut filter_two = DefaultBloomFilter::new(1_000, 0.01);
+    filter_one.insert_all(0..600);
+    filter_two.insert_all(400..1_000);
+    let union = filter_one.union(&filter_two);
+    let intersection = filter_one.intersect(&filter_two);
+
+    let mut union_with = DefaultBloomFilter::new(1_000, 0.01);
+    union_with.union_with(&filter_one);
+    union_with.union_with(&filter_two);
+    let mut intersect_with = DefaultBloomFilter::new(1_000, 0.01);
+    intersect_with.union_with(&filter_one);
+    intersect_with.intersect_with(&filter_two);
+
+    assert_eq!(union, union_with);
+    assert_eq!(intersection, intersect_with);
+
+    let mut seeded_one = SeededBloomFilter::new(1_000, 0.01);
+    let mut seeded_two = SeededBloomFilter::new(1_000, 0.01);
+    seeded_one.insert_all(0..600);
+    seeded_two.insert_all(400..1_000);
+    let seeded_union = seeded_one.union(&seeded_two);
+    let seeded_intersection = seeded_one.intersect(&seeded_two);
+    let mut seeded_two_copy = SeededBloomFilter::new(1_000, 0.01);
+    seeded_two_copy.union_with(&seeded_two);
+    seeded_two.union_with(&seeded_one);
+    seeded_two_copy.intersect_with(&seeded_one);
+    assert_eq!(seeded_union, seeded_two);
+    assert_eq!(seeded_intersection, seeded_two_copy);
+
+    let mut counting_one: CountingBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        CountingBloomFilter::new(1_000, 0.01);
+    let mut counting_two: CountingBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        CountingBloomFilter::new(1_000, 0.01);
+    counting_one.insert_all(0..600);
+    counting_two.insert_all(400..1_000);
+    let counting_union = counting_one.union(&counting_two);
+    let counting_intersection = counting_one.intersect(&counting_two);
+    let mut counting_two_copy = counting_two.union(&counting_two);
+    counting_two.union_with(&counting_one);
+    counting_two_copy.intersect_with(&counting_one);
+    assert_eq!(counting_union, counting_two);
+    assert_eq!(counting_intersection, counting_two_copy);
+}
+
+#[test]
+fn union_and_intersect_parity_across_filter_types() {
+    let mut km_one = StableBloomFilterDefault::new(1_000, 0.01);
+    let mut km_two = StableBloomFilterDefault::new(1_000, 0.01);
+    let mut seeded_one = SeededBloomFilter::new(1_000, 0.01);
+    let mut seeded_two = SeededBloomFilter::new(1_000, 0.01);
+    let mut counting_one: CountingBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        CountingBloomFilter::new(1_000, 0.01);
+    let mut counting_two: CountingBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        CountingBloomFilter::new(1_000, 0.01);
+    km_one.insert_all(0..600);
+    seeded_one.insert_all(0..600);
+    counting_one.insert_all(0..600);
+    km_two.insert_all(400..1_000);
+    seeded_two.insert_all(400..1_000);
+    counting_two.insert_all(400..1_000);
+
+    let km_union = km_one.union(&km_two);
+    let km_intersection = km_one.intersect(&km_two);
+    let seeded_union = seeded_one.union(&seeded_two);
+    let seeded_intersection = seeded_one.intersect(&seeded_two);
+    let counting_union = counting_one.union(&counting_two);
+    let counting_intersection = counting_one.intersect(&counting_two);
+
+    // All filters contain the union and the intersection of the elements
+    for i in 0..1_000 {
+        assert_eq!(true, km_union.contains(&i));
+        assert_eq!(true, seeded_union.contains(&i));
+        assert_eq!(true, counting_union.contains(&i));
+    }
+    for i in 400..600 {
+        assert_eq!(true, km_intersection.contains(&i));
+        assert_eq!(true, seeded_intersection.contains(&i));
+        assert_eq!(true, counting_intersection.contains(&i));
+    }
+    // Counting filters with the same hashers give the same answers as k-m bloom filters, also for
+    // false positives, and seeded bloom filters about as many false positives
+    let positives = |contains: &dyn Fn(&u64) -> bool| (1_000..11_000).filter(contains).count();
+    assert_eq!(
+        positives(&|i| km_union.contains(i)),
+        positives(&|i| counting_union.contains(i))
+    );
+    assert_eq!(
+        true,
+        (0..11_000u64).all(|i| km_intersection.contains(&i) == counting_intersection.contains(&i))
+    );
+    assert!(positives(&|i| seeded_union.contains(i)) < 300);
+    assert!(positives(&|i| seeded_intersection.contains(i)) < 300);
+    assert_eq!(
+        km_union.approximate_element_count(),
+        counting_union.approximate_element_count()
+    );
+
+    // Elements that have been inserted into both filters keep the larger count in the union
+    counting_one.insert(&500);
+    let mut counting_union = counting_one.union(&counting_two);
+    assert_eq!(2, counting_union.approximate_count(&500));
+    assert_eq!(true, counting_union.remove(&500));
+    assert_eq!(true, counting_union.remove(&500));
+    assert_eq!(false, counting_union.contains(&500));
+}
+
+#[test]
+#[cfg(feature = "std")]
+#[should_panic(expected = "different configurations")]
+fn union_with_requires_same_configuration() {
+    let mut filter_one = DefaultBloomFilter::new(1_000, 0.01);
+    let filter_two = DefaultBloomFilter::new(2_000, 0.01);
+    filter_one.union_with(&filter_two);
+}
+
+#[test]
+#[should_panic(
+    expected = "unable to intersect counting bloom filters with different configurations"
+)]
+fn counting_bloom_filter_intersect_requires_same_configuration() {
+    let filter_one: CountingBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        CountingBloomFilter::new(1_000, 0.01);
+    let filter_two: CountingBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        CountingBloomFilter::new(2_000, 0.01);
+    filter_one.intersect(&filter_two);
+}
+
+#[test]
+fn merge_many_equals_pairwise_union() {
+    let shards: Vec<StableBloomFilterDefault> = (0..8)
+        .map(|shard| {
+            let mut filter = StableBloomFilterDefault::new(1_000, 0.01);
+            filter.insert_all(shard * 100..(shard + 1) * 100);
+            filter
+        })
+        .collect();
+    let mut expected = StableBloomFilterDefault::new(1_000, 0.01);
+    for shard in shards.iter() {
+        expected = expected.union(shard);
+    }
+
+    let copies = shards.iter().map(|shard| {
+        StableBloomFilterDefault::from_bitset(shard.bitset().clone(), shard.hasher_count())
+    });
+    let merged = StableBloomFilterDefault::merge_many(copies).unwrap();
+    assert_eq!(expected.bitset(), merged.bitset());
+    assert!((0..800).all(|element| merged.contains(&element)));
+
+    assert_eq!(
+        Err(MergeError::NoFilters),
+        StableBloomFilterDefault::merge_many(Vec::new()).map(|_| ())
+    );
+    let mismatched = shards
+        .into_iter()
+        .take(3)
+        .chain(Some(StableBloomFilterDefault::new(2_000, 0.01)));
+    assert_eq!(
+        Err(MergeError::ConfigurationMismatch { filter: 3 }),
+        StableBloomFilterDefault::merge_many(mismatched).map(|_| ())
+    );
+}
+
+#[test]
+#[cfg(feature = "rand")]
+fn merge_many_combines_noise_like_union_with() {
+    let mut rng = StdRng::seed_from_u64(11);
+    let shards: Vec<StableBloomFilterDefault> = [0.1, 0.2, 0.05]
+        .iter()
+        .map(|&fraction| StableBloomFilterDefault::new(1_000, 0.01).with_noise(fraction, &mut rng))
+        .collect();
+    let mut expected = StableBloomFilterDefault::new(1_000, 0.01);
+    for shard in shards.iter() {
+        expected.union_with(shard);
+    }
+
+    let merged = StableBloomFilterDefault::merge_many(shards).unwrap();
+    assert!((expected.noise_fraction() - merged.noise_fraction()).abs() < 1e-12);
+    assert!((0.316 - merged.noise_fraction()).abs() < 1e-9);
+}
+
+#[test]
+fn km_bloom_filter_delta_syncs_replica() {
+    let mut primary = StableBloomFilterDefault::new(10_000, 0.01);
+    let mut replica = StableBloomFilterDefault::new(10_000, 0.01);
+    for round in 0..10 {
+        let snapshot =
+            StableBloomFilterDefault::from_bitset(primary.bitset().clone(), primary.hasher_count());
+        primary.insert_all(round * 1_000..(round + 1) * 1_000);
+        let delta = StableBloomFilterDefault::delta(&snapshot, &primary);
+        assert_eq!(
+            primary.bitset().count_ones() - snapshot.bitset().count_ones(),
+            delta.count_ones()
+        );
+
+        let delta = BitsetDelta::from_bytes(delta.as_bytes().to_vec()).unwrap();
+        replica.apply_delta(&delta);
+        assert_eq!(primary, replica);
+    }
+    assert_eq!(true, (0..10_000).all(|i| replica.contains(&i)));
+}
+
+#[test]
+#[should_panic(expected = "unable to apply a delta of")]
+fn km_bloom_filter_apply_delta_requires_same_bit_count() {
+    let old = StableBloomFilterDefault::new(1_000, 0.01);
+    let mut new = StableBloomFilterDefault::new(1_000, 0.01);
+    new.insert(&1);
+    let delta = StableBloomFilterDefault::delta(&old, &new);
+    StableBloomFilterDefault::new(2_000, 0.01).apply_delta(&delta);
+}
+
+#[cfg(feature = "roaring")]
+#[test]
+fn roaring_bloom_filter_has_bits_of_km_bloom_filter() {
+    use bloom_filter_simple::RoaringBloomFilter;
+
+    let mut dense = Vec::new();
+    let mut roaring: Vec<RoaringBloomFilter<StableXxh3Hasher, StableXxh64Hasher>> = Vec::new();
+    for day in 0..10 {
+        let mut filter = StableBloomFilterDefault::new(100_000, 0.01);
+        filter.insert_all(0..100);
+        filter.insert_all(day * 1_000..day * 1_000 + 500);
+        let mut roaring_filter = RoaringBloomFilter::new(100_000, 0.01);
+        roaring_filter.insert_all(0..100);
+        roaring_filter.insert_all(day * 1_000..day * 1_000 + 500);
+        assert_eq!(filter.bit_count(), roaring_filter.bit_count());
+        assert_eq!(filter.bitset().count_ones(), roaring_filter.count_ones());
+        assert_eq!(filter, roaring_filter.to_dense());
+        assert_eq!(
+            roaring_filter.bitmap(),
+            RoaringBloomFilter::from_dense(&filter).bitmap()
+        );
+        dense.push(filter);
+        roaring.push(roaring_filter);
+    }
+
+    // Combining the bitmaps sets the same bits as combining the dense filters
+    let mut union = dense[0].union(&dense[1]);
+    let mut intersection = dense[0].intersect(&dense[1]);
+    for filter in dense.iter().skip(2) {
+        union.union_with(filter);
+        intersection.intersect_with(filter);
+    }
+    assert_eq!(
+        union,
+        RoaringBloomFilter::union_all(&roaring).unwrap().to_dense()
+    );
+    assert_eq!(
+        intersection,
+        RoaringBloomFilter::intersect_all(&roaring)
+            .unwrap()
+            .to_dense()
+    );
+    assert_eq!(
+        dense[0].union(&dense[1]),
+        roaring[0].union(&roaring[1]).to_dense()
+    );
+    assert_eq!(
+        dense[0].intersect(&dense[1]),
+        roaring[0].intersect(&roaring[1]).to_dense()
+    );
+    assert_eq!(
+        true,
+        (0..100).all(|i| RoaringBloomFilter::intersect_all(&roaring)
+            .unwrap()
+            .contains(&i))
+    );
+    assert_eq!(
+        true,
+        RoaringBloomFilter::intersect_all(&roaring[..0]).is_none()
+    );
+}
+
+#[cfg(feature = "roaring")]
+#[test]
+#[should_panic(
+    expected = "unable to intersect roaring bloom filters with different configurations"
+)]
+fn roaring_bloom_filter_intersect_requires_same_configuration() {
+    use bloom_filter_simple::RoaringBloomFilter;
+
+    let filter: RoaringBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        RoaringBloomFilter::new(1_000, 0.01);
+    RoaringBloomFilter::intersect_all(vec![&filter, &RoaringBloomFilter::new(2_000, 0.01)]);
+}
+
+#[cfg(feature = "interop-bloomfilter")]
+#[test]
+fn sip_bloom_filter_round_trips_bloomfilter_parameters() {
+    use bloom_filter_simple::{InvalidSipBloomFilter, SipBloomFilter};
+
+    let keys: Vec<String> = (0..1_000).map(|i| format!("key-{}", i)).collect();
+    let sip_keys = [(0x0123_4567_89ab_cdef, 0xfedc_ba98_7654_3210), (1, 2)];
+    let exported = SipBloomFilter::from_elements(9_600, 7, sip_keys, &keys);
+    assert_eq!(1_200, exported.bitmap().len());
+
+    let filter = SipBloomFilter::from_existing(
+        exported.bitmap(),
+        exported.number_of_bits(),
+        exported.number_of_hash_functions(),
+        exported.sip_keys(),
+    )
+    .unwrap();
+    assert_eq!(exported, filter);
+    assert_eq!(true, keys.iter().all(|key| filter.contains(key.as_str())));
+    let false_positives = (0..1_000)
+        .filter(|i| filter.contains(&format!("other-{}", i)))
+        .count();
+    assert!(false_positives < 50);
+
+    let error = SipBloomFilter::from_existing(&[0; 10], 9_600, 7, sip_keys).unwrap_err();
+    assert_eq!(InvalidSipBloomFilter, error);
+    assert!(matches!(
+        BloomFilterError::from(error),
+        BloomFilterError::InvalidSipBloomFilter
+    ));
+}
+
+#[cfg(feature = "interop-redisbloom")]
+#[test]
+fn redis_bloom_filter_round_trips_scandump() {
+    use bloom_filter_simple::{InvalidRedisBloomChunk, RedisBloomFilter};
+
+    let keys: Vec<String> = (0..10_000).map(|i| format!("key-{}", i)).collect();
+    let exported = RedisBloomFilter::from_elements(10_000, 0.01, &keys).unwrap();
+    // Like BF.ADD, keys that are false positives when they are added are not counted
+    assert!(exported.len() <= 10_000 && exported.len() > 9_900);
+    let chunks = exported.scandump();
+    assert_eq!(2, chunks.len());
+    assert_eq!(1, chunks[0].0);
+    assert_eq!(1 + chunks[1].1.len() as i64, chunks[1].0);
+
+    let mut filter = RedisBloomFilter::from_header(&chunks[0].1).unwrap();
+    assert_eq!(false, filter.contains("key-0"));
+    for (iterator, data) in &chunks[1..] {
+        filter.load_chunk(*iterator, data).unwrap();
+    }
+    assert_eq!(1, filter.number_of_links());
+    assert_eq!(true, keys.iter().all(|key| filter.contains(key)));
+    let false_positives = (0..10_000)
+        .filter(|i| filter.contains(&format!("other-{}", i)))
+        .count();
+    assert!(false_positives < 200);
+
+    let error = filter
+        .load_chunk(chunks[1].0 + 1, &chunks[1].1)
+        .unwrap_err();
+    assert_eq!(InvalidRedisBloomChunk, error);
+    assert!(matches!(
+        BloomFilterError::from(error),
+        BloomFilterError::InvalidRedisBloomChunk
+    ));
+}
+
+#[cfg(feature = "interop-redisbloom")]
+#[test]
+fn redis_bloom_filter_is_non_scaling() {
+    use bloom_filter_simple::RedisBloomFilter;
+
+    let filter = RedisBloomFilter::from_elements(2, 0.01, ["a", "b", "a", "b"]).unwrap();
+    assert_eq!(2, filter.len());
+    assert_eq!(
+        CapacityExceeded,
+        RedisBloomFilter::from_elements(2, 0.01, ["a", "b", "c"]).unwrap_err()
+    );
+}
+
+#[test]
+fn sparse_bloom_filter_has_bits_of_km_bloom_filter() {
+    let mut sparse: SparseBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        SparseBloomFilter::new(10_000, 0.01);
+    let mut dense = StableBloomFilterDefault::new(10_000, 0.01);
+    assert_eq!(dense.bit_count(), sparse.bit_count());
+    assert_eq!(dense.hasher_count(), sparse.hasher_count());
+
+    let mut converted_at = None;
+    for i in 0..2_000 {
+        sparse.insert(&i);
+        dense.insert(&i);
+        assert_eq!(dense.bitset().count_ones(), sparse.count_ones());
+        if converted_at.is_none() && !sparse.is_sparse() {
+            converted_at = Some(i);
+            assert!(sparse.count_ones() * 16 >= sparse.bit_count());
+        }
+        if i == 100 {
+            // Queries of a sparse filter test the same bits
+            assert_eq!(true, sparse.is_sparse());
+            assert_eq!(
+                true,
+                (0..20_000).all(|j| sparse.contains(&j) == dense.contains(&j))
+            );
+        }
+    }
+    // 1/16 of the bits are set by about 1/10 of the capacity
+    let converted_at = converted_at.unwrap();
+    assert!(converted_at > 500 && converted_at < 1_500);
+    assert_eq!(
+        dense.approximate_element_count(),
+        sparse.approximate_element_count()
+    );
+    assert_eq!(true, (0..2_000).all(|i| sparse.contains(&i)));
+    assert_eq!(dense, sparse.into_dense());
+
+    let mut sparse: SparseBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        SparseBloomFilter::new(10_000, 0.01);
+    sparse.insert_all(0..100);
+    let mut dense = StableBloomFilterDefault::new(10_000, 0.01);
+    dense.insert_all(0..100);
+    assert_eq!(true, sparse.is_sparse());
+    assert_eq!(dense.bitset(), sparse.into_dense().bitset());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn filters_from_iterators() {
+    // Exact size hint
+    let km_filter = DefaultBloomFilter::from_iter_with_fp(0..1_000, 0.01);
+    assert_eq!(
+        DefaultBloomFilter::new(1_000, 0.01).bit_count(),
+        km_filter.bit_count()
+    );
+    assert_eq!(true, (0..1_000).all(|i| km_filter.contains(&i)));
+
+    // Inexact size hint
+    let seeded_filter =
+        SeededBloomFilter::from_iter_with_fp((0..2_000).filter(|i| i % 2 == 0), 0.01);
+    assert_eq!(
+        SeededBloomFilter::new(1_000, 0.01).bit_count(),
+        seeded_filter.bit_count()
+    );
+    assert_eq!(
+        true,
+        (0..2_000).step_by(2).all(|i| seeded_filter.contains(&i))
+    );
+
+    let counting_filter: CountingBloomFilter<ahash::AHasher, DefaultHasher> =
+        CountingBloomFilter::from_iter_with_fp(vec!["a", "b", "a"], 0.01);
+    assert_eq!(2, counting_filter.approximate_count(&"a"));
+
+    // Empty iterators create the smallest possible filter
+    let empty_filter = DefaultBloomFilter::from_iter_with_fp(Vec::<u32>::new(), 0.01);
+    assert_eq!(false, empty_filter.contains(&0));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn hashed_keys_are_reusable_across_filters() {
+    let mut shards: Vec<DefaultBloomFilter> = (0..16)
+        .map(|_| DefaultBloomFilter::new(1_000, 0.001))
+        .collect();
+    let mut counting_filter: CountingBloomFilter<ahash::AHasher, DefaultHasher> =
+        CountingBloomFilter::new(1_000, 0.001);
+
+    for i in 0..1_600 {
+        let key = shards[0].hash(&i);
+        assert_eq!(key, counting_filter.hash(&i));
+        shards[i % 16].insert_hashed(&key);
+        counting_filter.insert_hashed(&key);
+    }
+
+    for i in 0..1_600 {
+        let key = shards[0].hash(&i);
+        assert_eq!(true, shards[i % 16].contains(&i));
+        assert_eq!(true, shards[i % 16].contains_hashed(&key));
+        assert_eq!(true, counting_filter.contains(&i));
+        assert_eq!(true, counting_filter.contains_hashed(&key));
+    }
+
+    // Inserting a hashed key is equivalent to inserting the element
+    let mut filter_one = DefaultBloomFilter::new(1_000, 0.001);
+    let mut filter_two = DefaultBloomFilter::new(1_000, 0.001);
+    filter_one.insert(&"Hello!");
+    filter_two.insert_hashed(&filter_two.hash(&"Hello!"));
+    assert_eq!(filter_one, filter_two);
+}
+
+#[test]
+fn rotating_bloom_filter_window() {
+    let mut filter: RotatingBloomFilter<ahash::AHasher, DefaultHasher> =
+        RotatingBloomFilter::new(3, 1_000, 0.001);
+    assert_eq!(3, filter.number_of_generations());
+
+    for generation in 0..10 {
+        filter.insert_all(generation * 100..(generation + 1) * 100);
+        for i in 0..1_000 {
+            // Elements of the current and the two previous generations are contained
+            if i >= (generation.max(2) - 2) * 100 && i < (generation + 1) * 100 {
+                assert_eq!(true, filter.contains(&i));
+            }
+        }
+        filter.advance();
+    }
+    assert!(filter.approximate_element_count() > 150.0);
+    assert!(filter.approximate_element_count() < 250.0);
+    assert_eq!(false, (0..700).any(|i| filter.contains(&i)));
+
+    filter.clear();
+    assert_eq!(false, (700..1_000).any(|i| filter.contains(&i)));
+}
+
+#[test]
+fn generational_bloom_filter_retains_touched_elements() {
+    let mut filter: GenerationalBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        GenerationalBloomFilter::new(1_000, 0.001);
+    filter.insert_all(0..500);
+    assert_eq!(true, (0..500).all(|i| filter.contains_current(&i)));
+
+    filter.advance_generation();
+    assert_eq!(1, filter.generation());
+    assert_eq!(true, (0..500).all(|i| filter.contains(&i)));
+    assert_eq!(false, (0..500).any(|i| filter.contains_current(&i)));
+    assert_eq!(true, (0..250).all(|i| filter.touch(&i)));
+    filter.extend(500..750);
+    // Touched elements are contained in both generations, but counted once
+    assert!((filter.approximate_element_count() - 750.0).abs() < 20.0);
+
+    filter.advance_generation();
+    assert_eq!(true, (0..250).all(|i| filter.contains(&i)));
+    assert_eq!(true, (500..750).all(|i| filter.contains(&i)));
+    assert!((250..500).filter(|i| filter.contains(i)).count() < 5);
+    assert_eq!(false, filter.touch(&"Hello!"));
+    assert_eq!(false, filter.contains_current(&"Hello!"));
+
+    filter.clear();
+    assert_eq!(false, (0..750).any(|i| filter.contains(&i)));
+    assert_eq!(2, filter.generation());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn rotating_bloom_filter_rotates_with_time() {
+    let interval = std::time::Duration::from_millis(100);
+    let mut filter: RotatingBloomFilter<ahash::AHasher, DefaultHasher> =
+        RotatingBloomFilter::new(2, 1_000, 0.001).with_rotation_interval(interval);
+
+    filter.insert(&"Hello!");
+    assert_eq!(true, filter.contains(&"Hello!"));
+
+    // Expired generations are ignored even before the next insertion rotates them
+    std::thread::sleep(interval * 3);
+    assert_eq!(false, filter.contains(&"Hello!"));
+    assert!(filter.rotate_if_due() >= 2);
+    assert_eq!(0, filter.rotate_if_due());
+    assert_eq!(false, filter.contains(&"Hello!"));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn expiring_bloom_filter_expires_after_ttl() {
+    let ttl = std::time::Duration::from_millis(200);
+    let mut filter: ExpiringBloomFilter<ahash::AHasher, DefaultHasher> =
+        ExpiringBloomFilter::new(ttl, 3, 1_000, 0.001);
+    assert_eq!(ttl, filter.ttl());
+    assert_eq!(3, filter.number_of_generations());
+
+    let inserted = std::time::Instant::now();
+    filter.insert(&"Hello!");
+    assert_eq!(true, filter.contains(&"Hello!"));
+
+    // Elements are contained within the time-to-live, even if the generations rotate meanwhile
+    std::thread::sleep(ttl / 2);
+    filter.insert(&"World!");
+    if inserted.elapsed() < ttl {
+        assert_eq!(true, filter.contains(&"Hello!"));
+    }
+
+    // Elements expire at most one rotation interval after the time-to-live
+    std::thread::sleep(ttl * 2);
+    assert_eq!(false, filter.contains(&"Hello!"));
+    assert_eq!(false, filter.contains(&"World!"));
+    assert!(filter.expire() >= 1);
+    assert_eq!(0.0, filter.approximate_element_count());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn rotating_bloom_filter_resumes_window_after_restart() {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    let interval = Duration::from_secs(60 * 60);
+    let mut filter: RotatingBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        RotatingBloomFilter::new(3, 1_000, 0.001).with_rotation_interval(interval);
+    filter.insert(&"oldest");
+    filter.advance();
+    filter.insert(&"middle");
+    filter.advance();
+    filter.insert(&"newest");
+    let mut bytes = Vec::new();
+    filter.write_to(&mut bytes).unwrap();
+
+    let restored =
+        RotatingBloomFilter::<StableXxh3Hasher, StableXxh64Hasher>::read_from(&bytes[..]).unwrap();
+    assert_eq!(3, restored.number_of_generations());
+    assert_eq!(Some(interval), restored.rotation_interval());
+    assert_eq!(
+        true,
+        ["oldest", "middle", "newest"]
+            .iter()
+            .all(|data| restored.contains(data))
+    );
+
+    // Rotations that have become due while the filter was stored are performed when it is read
+    let with_last_rotation = |last_rotation: SystemTime| {
+        let mut bytes = bytes.clone();
+        let nanos = last_rotation.duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64;
+        bytes[12..20].copy_from_slice(&nanos.to_le_bytes());
+        RotatingBloomFilter::<StableXxh3Hasher, StableXxh64Hasher>::read_from(&bytes[..]).unwrap()
+    };
+    let restored = with_last_rotation(SystemTime::now() - interval * 3 / 2);
+    assert_eq!(false, restored.contains(&"oldest"));
+    assert_eq!(true, restored.contains(&"middle"));
+    assert_eq!(true, restored.contains(&"newest"));
+    let restored = with_last_rotation(SystemTime::now() - interval * 100);
+    assert_eq!(false, restored.contains(&"newest"));
+
+    // A last rotation in the future, e.g., from a skewed clock, does not expire any element
+    let restored = with_last_rotation(SystemTime::now() + interval * 100);
+    assert_eq!(true, restored.contains(&"oldest"));
+
+    // Filters without a rotation interval only rotate manually
+    let mut filter: RotatingBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        RotatingBloomFilter::new(2, 1_000, 0.001);
+    filter.insert(&"Hello!");
+    let mut bytes = Vec::new();
+    filter.write_to(&mut bytes).unwrap();
+    let restored =
+        RotatingBloomFilter::<StableXxh3Hasher, StableXxh64Hasher>::read_from(&bytes[..]).unwrap();
+    assert_eq!(None, restored.rotation_interval());
+    assert_eq!(true, restored.contains(&"Hello!"));
+
+    // Windows without generations are rejected
+    assert!(matches!(
+        RotatingBloomFilter::<StableXxh3Hasher, StableXxh64Hasher>::read_from(&[0u8; 20][..]),
+        Err(ReadFilterError::Io(error)) if error.kind() == std::io::ErrorKind::InvalidData
+    ));
+
+    // Generations with different index layouts are rejected
+    let mut mixed = [2u32.to_le_bytes().to_vec(), vec![0u8; 16]].concat();
+    StableBloomFilterDefault::new(1_000, 0.001)
+        .write_to(&mut mixed)
+        .unwrap();
+    StableBloomFilterDefault::new(1_000, 0.001)
+        .with_index_layout(IndexLayout::Global)
+        .write_to(&mut mixed)
+        .unwrap();
+    assert!(matches!(
+        RotatingBloomFilter::<StableXxh3Hasher, StableXxh64Hasher>::read_from(&mixed[..]),
+        Err(ReadFilterError::Io(error)) if error.kind() == std::io::ErrorKind::InvalidData
+    ));
+
+    // Expiring filters require a rotation interval
+    assert!(matches!(
+        ExpiringBloomFilter::<StableXxh3Hasher, StableXxh64Hasher>::read_from(
+            &[&[0u8; 8][..], &bytes].concat()[..]
+        ),
+        Err(ReadFilterError::Io(error)) if error.kind() == std::io::ErrorKind::InvalidData
+    ));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn expiring_bloom_filter_resumes_window_after_restart() {
+    let ttl = std::time::Duration::from_secs(10 * 60);
+    let mut filter: ExpiringBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        ExpiringBloomFilter::new(ttl, 6, 1_000, 0.001);
+    filter.insert(&"Hello!");
+    let mut bytes = Vec::new();
+    filter.write_to(&mut bytes).unwrap();
+
+    let restored =
+        ExpiringBloomFilter::<StableXxh3Hasher, StableXxh64Hasher>::read_from(&bytes[..]).unwrap();
+    assert_eq!(ttl, restored.ttl());
+    assert_eq!(6, restored.number_of_generations());
+    assert_eq!(true, restored.contains(&"Hello!"));
+}
+
+#[test]
+#[cfg(feature = "std")]
+#[should_panic(expected = "at least two generations")]
+fn expiring_bloom_filter_with_one_generation_panics() {
+    let _: ExpiringBloomFilter<ahash::AHasher, DefaultHasher> =
+        ExpiringBloomFilter::new(std::time::Duration::from_secs(1), 1, 1_000, 0.001);
+}
+
+#[test]
+fn exact_count_bloom_filter_test() {
+    // Twice the capacity, so that the filter is more than half full and some elements are false
+    // positives when they are inserted
+    let mut filter = ExactCountBloomFilter::new(StableBloomFilterDefault::new(1_000, 0.01));
+    filter.insert_all(0..2_000);
+    filter.extend(0..2_000);
+    let inserted_count = filter.inserted_count();
+    assert!(inserted_count <= 2_000 && inserted_count > 1_800);
+    assert!(filter.filter().fill_ratio() > 0.5);
+    assert!((0..2_000).all(|i| filter.contains(&i)));
+
+    // Elements are inserted even if they are contained, so counting filters record duplicates
+    let mut filter = ExactCountBloomFilter::new(CountingBloomFilter::<
+        StableXxh3Hasher,
+        StableXxh64Hasher,
+    >::new(1_000, 0.001));
+    filter.insert(&"Hello!");
+    filter.insert(&"Hello!");
+    assert_eq!(1, filter.inserted_count());
+    let mut counting_filter = filter.into_inner();
+    assert_eq!(true, counting_filter.remove(&"Hello!"));
+    assert_eq!(true, counting_filter.contains(&"Hello!"));
+}
+
+#[test]
+fn migrating_bloom_filter_test() {
+    let mut old = StableBloomFilterDefault::new(1_000, 0.001);
+    old.insert_all(0..1_000);
+    let mut filter =
+        MigratingBloomFilter::new(old, QuotientFilter::<StableXxh3Hasher>::new(10_000, 0.001));
+    filter.insert_all(1_000..2_000);
+    assert_eq!(true, filter.is_migrating());
+    assert!((0..2_000).all(|i| filter.contains(&i)));
+    assert_eq!(1_000, filter.new_filter().len());
+
+    // Elements that are still queried survive the retirement of the old filter
+    for i in 0..500 {
+        assert_eq!(true, filter.contains_and_migrate(&i));
+    }
+    assert_eq!(false, filter.contains_and_migrate(&"Not in filter"));
+    assert_eq!(1_500, filter.new_filter().len());
+
+    let old = filter.retire_old().unwrap();
+    assert_eq!(true, old.contains(&999));
+    assert_eq!(false, filter.is_migrating());
+    assert!((0..500).chain(1_000..2_000).all(|i| filter.contains(&i)));
+    assert!((500..1_000).filter(|i| filter.contains(i)).count() < 10);
+    assert_eq!(1_500, filter.into_new_filter().len());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn migrating_bloom_filter_retires_old_filter_after_ttl() {
+    let ttl = std::time::Duration::from_millis(100);
+    let mut old = StableBloomFilterDefault::new(1_000, 0.001);
+    old.insert(&"Hello!");
+    let mut filter =
+        MigratingBloomFilter::new(old, StableBloomFilterDefault::new(10_000, 0.001)).with_ttl(ttl);
+    let created = std::time::Instant::now();
+    filter.insert(&"World!");
+    if created.elapsed() < ttl {
+        assert_eq!(true, filter.contains(&"Hello!"));
+    }
+
+    // The old filter is not queried after the time-to-live, even before it has been dropped
+    std::thread::sleep(ttl * 2);
+    assert_eq!(false, filter.is_migrating());
+    assert_eq!(false, filter.contains(&"Hello!"));
+    assert_eq!(true, filter.contains(&"World!"));
+    assert_eq!(true, filter.retire_if_due());
+    assert_eq!(None, filter.retire_old());
+}
+
+#[test]
+fn distinct_counter_test() {
+    let mut counter: DistinctCounter<StableXxh3Hasher> = DistinctCounter::new(0.01);
+    assert_eq!(14, counter.precision());
+    assert_eq!(true, counter.is_empty());
+    assert_eq!(0.0, counter.approximate_distinct_count());
+
+    // Small counts are nearly exact
+    counter.insert_all(0..100);
+    assert!((counter.approximate_distinct_count() - 100.0).abs() < 2.0);
+
+    // Duplicates are not counted
+    counter.insert_all(0..100_000);
+    counter.insert_all(0..100_000);
+    let error = 3.0 * counter.relative_standard_error() * 100_000.0;
+    assert!((counter.approximate_distinct_count() - 100_000.0).abs() < error);
+
+    let mut other: DistinctCounter<StableXxh3Hasher> = DistinctCounter::new(0.01);
+    other.insert_all(50_000..150_000);
+    let union = counter.union(&other);
+    assert!((union.approximate_distinct_count() - 150_000.0).abs() < 1.5 * error);
+    counter.union_with(&other);
+    assert_eq!(union, counter);
+
+    counter.clear();
+    assert_eq!(true, counter.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "different configurations")]
+fn distinct_counter_union_with_different_precision_panics() {
+    let counter: DistinctCounter<StableXxh3Hasher> = DistinctCounter::with_precision(10);
+    counter.union(&DistinctCounter::with_precision(12));
+}
+
+#[test]
+fn check_and_insert_matches_contains_then_insert() {
+    for &layout in [IndexLayout::Partitioned, IndexLayout::Global].iter() {
+        let new = || StableBloomFilterDefault::new(1_000, 0.01).with_index_layout(layout);
+        let mut checked = new();
+        let mut expected = new();
+        // Duplicates, and more elements than the capacity to provoke false positives
+        for i in (0..3_000).map(|i| i % 2_000) {
+            let contained = expected.contains(&i);
+            expected.insert(&i);
+            assert_eq!(contained, checked.check_and_insert(&i));
+        }
+        assert_eq!(expected, checked);
+    }
+
+    let mut rotating: RotatingBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        RotatingBloomFilter::new(2, 1_000, 0.001);
+    assert_eq!(false, rotating.check_and_insert(&"old"));
+    rotating.advance();
+    // Elements of older generations are contained, and are inserted into the newest generation
+    assert_eq!(true, rotating.check_and_insert(&"old"));
+    assert_eq!(false, rotating.check_and_insert(&"new"));
+    assert_eq!(true, rotating.check_and_insert(&"new"));
+    rotating.advance();
+    assert_eq!(true, rotating.contains(&"old"));
+    assert_eq!(true, rotating.contains(&"new"));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn sync_bloom_filter_check_and_insert_reports_new_elements_once() {
+    let filter = std::sync::Arc::new(SyncBloomFilter::new(8, || {
+        StableBloomFilterDefault::new(10_000 / 8 + 1_000, 0.001)
+    }));
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let filter = std::sync::Arc::clone(&filter);
+            std::thread::spawn(move || (0..10_000).filter(|i| !filter.check_and_insert(i)).count())
+        })
+        .collect();
+    let new_elements: usize = threads
+        .into_iter()
+        .map(|thread| thread.join().unwrap())
+        .sum();
+
+    // Every element is new for exactly one thread, unless it is a false positive
+    assert!(new_elements <= 10_000);
+    assert!(new_elements > 9_950, "{} new elements", new_elements);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn sync_bloom_filter_concurrent_inserts() {
+    let filter = std::sync::Arc::new(SyncBloomFilter::new(8, || {
+        StableBloomFilterDefault::new(40_000 / 8 + 1_000, 0.001)
+    }));
+    assert_eq!(8, filter.number_of_shards());
+
+    let threads: Vec<_> = (0..4)
+        .map(|t| {
+            let filter = std::sync::Arc::clone(&filter);
+            std::thread::spawn(move || filter.insert_all(t * 10_000..(t + 1) * 10_000))
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    for i in 0..40_000 {
+        assert_eq!(true, filter.contains(&i));
+    }
+    let false_positives = (40_000..80_000).filter(|i| filter.contains(i)).count();
+    assert!(false_positives < 80);
+
+    // Elements are spread over all shards, and stay in their shard when the filter is rebuilt
+    let shards = std::sync::Arc::try_unwrap(filter).unwrap().into_shards();
+    for shard in shards.iter() {
+        assert!((shard.approximate_element_count() - 5_000.0).abs() < 500.0);
+    }
+    let filter = SyncBloomFilter::from_shards(shards);
+    assert_eq!(true, filter.contains(&12_345));
+}
+
+#[test]
+fn sharded_bloom_filter_test() {
+    let new = || -> ShardedBloomFilter<StableXxh3Hasher, StableXxh64Hasher> {
+        ShardedBloomFilter::new(16, 80_000, 0.001)
+    };
+    let mut filter_one = new();
+    let mut filter_two = new();
+    assert_eq!(16, filter_one.number_of_shards());
+    filter_one.insert_all(0..40_000);
+    filter_two.extend(40_000..80_000);
+
+    // Every element is contained in exactly the shard it is routed to
+    for i in (0..40_000).step_by(100) {
+        let shard = filter_one.shard_index(&i);
+        assert_eq!(true, filter_one.shards()[shard].contains(&i));
+    }
+    for shard in filter_one.shards() {
+        assert!((shard.approximate_element_count() - 2_500.0).abs() < 250.0);
+    }
+
+    let mut merged = new();
+    merged.union_with(&filter_one);
+    merged.union_with(&filter_two);
+    for i in 0..80_000 {
+        assert_eq!(true, merged.contains(&i));
+    }
+    assert!((merged.approximate_element_count() - 80_000.0).abs() < 1_000.0);
+    let false_positives = (80_000..180_000).filter(|i| merged.contains(i)).count();
+    assert!(false_positives < 150);
+    assert!(merged.approximate_current_false_positive_probability() < 0.001);
+
+    #[cfg(feature = "rayon")]
+    {
+        let mut par_merged = filter_one;
+        par_merged.par_union_with(&filter_two);
+        assert_eq!(merged, par_merged);
+    }
+
+    // The routing only depends on the hashes and the number of shards
+    let rebuilt = ShardedBloomFilter::from_shards(merged.into_shards());
+    assert_eq!(true, rebuilt.contains(&12_345));
+}
+
+#[test]
+#[should_panic(expected = "different configurations")]
+fn sharded_bloom_filter_union_with_different_shards_panics() {
+    let mut filter_one: ShardedBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        ShardedBloomFilter::new(4, 1_000, 0.01);
+    let filter_two = ShardedBloomFilter::new(8, 1_000, 0.01);
+    filter_one.union_with(&filter_two);
+}
+
+#[test]
+fn insert_raw_bytes_and_hashes() {
+    let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+    let mut counting_filter: CountingBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        CountingBloomFilter::new(1_000, 0.001);
+    for i in 0u32..500 {
+        filter.insert_bytes(&i.to_be_bytes());
+        counting_filter.insert_bytes(&i.to_be_bytes());
+    }
+    for i in 500u64..1_000 {
+        let bytes = i.to_le_bytes();
+        let hash_a = xxh3::xxh3_64_with_seed(&bytes, STABLE_XXH3_SEED);
+        let hash_b = xxh64::xxh64(&bytes, STABLE_XXH64_SEED);
+        filter.insert_hash64(hash_a, hash_b);
+        counting_filter.insert_hash64(hash_a, hash_b);
+    }
+
+    for i in 0u32..500 {
+        assert_eq!(true, filter.contains_bytes(&i.to_be_bytes()));
+        assert_eq!(true, counting_filter.contains_bytes(&i.to_be_bytes()));
+    }
+    for i in 500u64..1_000 {
+        // The stable hashers write integers as little-endian bytes without a prefix
+        assert_eq!(true, filter.contains(&i));
+        assert_eq!(true, filter.contains_bytes(&i.to_le_bytes()));
+        assert_eq!(true, counting_filter.contains(&i));
+    }
+
+    // Raw bytes are hashed without the length prefix of slices
+    let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+    filter.insert_bytes(b"Hello!");
+    assert_eq!(false, filter.contains(&b"Hello!".to_vec()));
+    assert_eq!(
+        true,
+        filter.contains_hash64(
+            xxh3::xxh3_64_with_seed(b"Hello!", STABLE_XXH3_SEED),
+            xxh64::xxh64(b"Hello!", STABLE_XXH64_SEED)
+        )
+    );
+}
+
+#[test]
+fn insert_str_hashes_utf8_bytes() {
+    let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+    let mut counting_filter: CountingBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        CountingBloomFilter::new(1_000, 0.001);
+    let mut seeded_filter = SeededBloomFilter::new(1_000, 0.001);
+    for i in 0..500 {
+        let text = format!("key-{}", i);
+        filter.insert_str(&text);
+        counting_filter.insert_str(&text);
+        seeded_filter.insert_str(&text);
+    }
+
+    for i in 0..500 {
+        let text = format!("key-{}", i);
+        assert_eq!(true, filter.contains_str(&text));
+        assert_eq!(true, filter.contains_bytes(text.as_bytes()));
+        assert_eq!(true, counting_filter.contains_str(&text));
+        assert_eq!(true, seeded_filter.contains_str(&text));
+        assert_eq!(true, seeded_filter.contains_bytes(text.as_bytes()));
+    }
+    #[cfg(feature = "std")]
+    {
+        let mut bytes = Vec::new();
+        filter.write_to(&mut bytes).unwrap();
+        let view: BloomFilterView<StableXxh3Hasher, StableXxh64Hasher> =
+            BloomFilterView::read_from(&bytes).unwrap();
+        assert_eq!(true, view.contains_str("key-42"));
+    }
+
+    // The Hash implementation of str writes a terminating byte, so the elements differ
+    assert_eq!(false, filter.contains(&"key-42"));
+    assert_eq!(false, seeded_filter.contains(&"key-42"));
+    assert!((500..1_000).all(|i| !filter.contains_str(&format!("key-{}", i))));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn equality_and_subsets() {
+    let mut filter_one = DefaultBloomFilter::new(1_000, 0.001);
+    let mut filter_two = DefaultBloomFilter::new(1_000, 0.001);
+    assert_eq!(filter_one, filter_two);
+    assert_ne!(filter_one, DefaultBloomFilter::new(2_000, 0.001));
+
+    filter_one.insert_all(0..100);
+    filter_two.insert_all((0..100).rev());
+    assert_eq!(filter_one, filter_two);
+
+    // A delta that contains nothing new
+    let mut delta = DefaultBloomFilter::new(1_000, 0.001);
+    delta.insert_all(10..20);
+    assert_eq!(true, delta.is_subset(&filter_one));
+    assert_eq!(false, filter_one.is_subset(&delta));
+    delta.insert(&1_000);
+    assert_eq!(false, delta.is_subset(&filter_one));
+
+    // Few elements rarely share a bit in the range of every hasher
+    let mut disjoint = DefaultBloomFilter::new(1_000, 0.001);
+    disjoint.insert_all(100..105);
+    assert_eq!(true, disjoint.is_disjoint_probably(&filter_one));
+    disjoint.insert_all(50..60);
+    assert_eq!(false, disjoint.is_disjoint_probably(&filter_one));
+
+    let mut seeded_one = SeededBloomFilter::new(1_000, 0.001);
+    let mut seeded_two = SeededBloomFilter::new(1_000, 0.001);
+    seeded_one.insert_all(0..100);
+    seeded_two.insert_all(0..50);
+    assert_ne!(seeded_one, seeded_two);
+    assert_eq!(true, seeded_two.is_subset(&seeded_one));
+    seeded_two.insert_all(50..100);
+    assert_eq!(seeded_one, seeded_two);
+
+    let mut counting_one: CountingBloomFilter<ahash::AHasher, DefaultHasher> =
+        CountingBloomFilter::new(1_000, 0.001);
+    let mut counting_two: CountingBloomFilter<ahash::AHasher, DefaultHasher> =
+        CountingBloomFilter::new(1_000, 0.001);
+    counting_one.insert(&1);
+    counting_two.insert(&1);
+    assert_eq!(counting_one, counting_two);
+    counting_two.insert(&1);
+    assert_ne!(counting_one, counting_two);
+
+    let mut cuckoo_one: CuckooFilter<ahash::AHasher> = CuckooFilter::new(1_000, 0.001);
+    let mut cuckoo_two: CuckooFilter<ahash::AHasher> = CuckooFilter::new(1_000, 0.001);
+    cuckoo_one.insert(&1);
+    assert_ne!(cuckoo_one, cuckoo_two);
+    cuckoo_two.insert(&1);
+    assert_eq!(cuckoo_one, cuckoo_two);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn strict_capacity_guards_false_positive_probability() {
+    let mut filter = DefaultBloomFilter::new(1_000, 0.01).with_strict_capacity(1_000);
+    let mut inserted = 0;
+    while filter.try_insert(&inserted).is_ok() {
+        inserted += 1;
+    }
+    // The approximate number of elements never exceeds the capacity, which keeps the approximate
+    // false positive probability at the desired one
+    assert!(inserted > 900 && inserted < 1_100);
+    assert!(filter.approximate_element_count() <= 1_000.0);
+    assert!(filter.approximate_current_false_positive_probability() < 0.0101);
+    assert_eq!(Err(CapacityExceeded), filter.try_insert(&inserted));
+    assert_eq!(false, filter.contains(&inserted));
+
+    // Elements that are already contained do not increase the number of elements
+    assert_eq!(Ok(()), filter.try_insert(&0));
+
+    // Clearing the filter makes room again
+    filter.clear();
+    assert_eq!(Ok(()), filter.try_insert(&inserted));
+
+    // Without a strict capacity, insertions never fail
+    let mut filter = DefaultBloomFilter::new(10, 0.01);
+    assert_eq!(true, (0..100).all(|i| filter.try_insert(&i).is_ok()));
+}
+
+#[test]
+#[cfg(feature = "std")]
+#[should_panic(expected = "capacity of the bloom filter has been exceeded")]
+fn strict_capacity_insert_all_panics() {
+    let mut filter = DefaultBloomFilter::new(100, 0.01).with_strict_capacity(100);
+    filter.insert_all(0..200);
+}
+
+#[test]
+fn insert_batch_is_applied_on_commit_only() {
+    let mut filter = StableBloomFilterDefault::new(1_000, 0.01);
+    let mut batch = filter.batch();
+    for i in 0..500 {
+        batch.insert(&i);
+    }
+    assert_eq!(500, batch.len());
+    drop(batch);
+    assert_eq!(0, filter.bitset().count_ones());
+
+    let key = filter.hash(&"Hello!");
+    let mut batch = filter.batch();
+    assert_eq!(true, batch.is_empty());
+    for i in 0..250 {
+        batch.insert(&i);
+    }
+    batch.insert_hashed(&key);
+    assert_eq!(Ok(()), batch.try_commit());
+    let mut expected = StableBloomFilterDefault::new(1_000, 0.01);
+    expected.insert_all(0..250);
+    expected.insert(&"Hello!");
+    assert_eq!(expected, filter);
+
+    // A batch that exceeds the strict capacity is refused as a whole
+    let mut filter = StableBloomFilterDefault::new(100, 0.01).with_strict_capacity(100);
+    filter.insert_all(0..50);
+    let mut batch = filter.batch();
+    for i in 50..200 {
+        batch.insert(&i);
+    }
+    assert_eq!(Err(CapacityExceeded), batch.try_commit());
+    assert_eq!(false, (50..200).any(|i| filter.contains(&i)));
+    let mut batch = filter.batch();
+    for i in 50..80 {
+        batch.insert(&i);
+    }
+    batch.commit();
+    assert_eq!(true, (0..80).all(|i| filter.contains(&i)));
+}
+
+#[test]
+fn saturation_policy_reports_exceeded_false_positive_probability() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static WARNINGS: AtomicUsize = AtomicUsize::new(0);
+    fn warn(false_positive_probability: f64) {
+        assert!(false_positive_probability > 0.01);
+        WARNINGS.fetch_add(1, Ordering::SeqCst);
+    }
+
+    // The warning is issued once, when the target is exceeded, and again after clearing
+    let mut filter = StableBloomFilterDefault::new(1_000, 0.01)
+        .with_saturation_policy(0.01, OnSaturation::Warn(warn));
+    filter.insert_all(0..900);
+    assert_eq!(0, WARNINGS.load(Ordering::SeqCst));
+    for i in 900..2_000 {
+        filter.insert(&i);
+    }
+    assert_eq!(1, WARNINGS.load(Ordering::SeqCst));
+    filter.clear();
+    filter.insert_all(0..2_000);
+    assert_eq!(2, WARNINGS.load(Ordering::SeqCst));
+
+    // Insertions that would exceed the target are refused
+    let mut filter = StableBloomFilterDefault::new(1_000, 0.01)
+        .with_saturation_policy(0.01, OnSaturation::Error);
+    let mut inserted = 0;
+    while filter.try_insert(&inserted).is_ok() {
+        inserted += 1;
+    }
+    assert!(inserted > 900 && inserted < 1_100);
+    assert!(filter.approximate_current_false_positive_probability() <= 0.01);
+    assert_eq!(Err(CapacityExceeded), filter.try_insert(&inserted));
+
+    // The default policy ignores saturation
+    let mut filter = StableBloomFilterDefault::new(10, 0.01)
+        .with_saturation_policy(0.01, OnSaturation::default());
+    assert_eq!(true, (0..100).all(|i| filter.try_insert(&i).is_ok()));
+}
+
+#[test]
+#[should_panic(expected = "capacity of the bloom filter has been exceeded")]
+fn saturation_policy_error_insert_all_panics() {
+    let mut filter =
+        StableBloomFilterDefault::new(100, 0.01).with_saturation_policy(0.01, OnSaturation::Error);
+    filter.insert_all(0..200);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn filter_params_match_filters() {
+    use bloom_filter_simple::params::{self, FilterParams};
+
+    let params = FilterParams::for_capacity(10_000, 0.001);
+    let filter = DefaultBloomFilter::new(10_000, 0.001);
+    let counting_filter: CountingBloomFilter<ahash::AHasher, DefaultHasher> =
+        CountingBloomFilter::new(10_000, 0.001);
+    assert_eq!(filter.bit_count(), params.bit_count());
+    assert_eq!(filter.hasher_count(), params.number_of_hashers());
+    assert_eq!(counting_filter.counter_count(), params.bit_count());
+    assert!(params.bit_count() >= params::optimal_bit_count(10_000, 0.001));
+    assert_eq!(
+        params.number_of_hashers(),
+        params::optimal_number_of_hashers(10_000, params::optimal_bit_count(10_000, 0.001))
+    );
+    assert_eq!(params.bit_count().div_ceil(64) * 8, params.byte_count());
+    assert!(params.false_positive_probability_at(10_000.0) < 0.001);
+    assert!(params.false_positive_probability_at(20_000.0) > 0.001);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn default_false_positive_probability() {
+    use bloom_filter_simple::params::DEFAULT_FALSE_POSITIVE_PROBABILITY;
+
+    assert_eq!(
+        DefaultBloomFilter::new(1_000, DEFAULT_FALSE_POSITIVE_PROBABILITY),
+        DefaultBloomFilter::with_capacity(1_000)
+    );
+    let counting_filter: CountingBloomFilter<ahash::AHasher, DefaultHasher> =
+        CountingBloomFilter::with_capacity(1_000);
+    assert_eq!(
+        true,
+        counting_filter.eq_configuration(&CountingBloomFilter::new(1_000, 0.01))
+    );
+    assert_eq!(
+        SeededBloomFilter::new(1_000, 0.01).bit_count(),
+        SeededBloomFilter::with_capacity(1_000).bit_count()
+    );
+    assert_eq!(
+        DEFAULT_FALSE_POSITIVE_PROBABILITY,
+        FalsePositiveProbability::default().get()
+    );
+
+    let gcs_filter: GcsFilter<StableXxh3Hasher> = GcsFilter::default();
+    assert_eq!(true, gcs_filter.is_empty());
+    let counter: DistinctCounter<StableXxh3Hasher> = DistinctCounter::default();
+    assert_eq!(14, counter.precision());
+}
+
+#[test]
+fn false_positive_probability_is_validated() {
+    use std::convert::TryFrom;
+
+    let probability = FalsePositiveProbability::new(0.001).unwrap();
+    assert_eq!(0.001, f64::from(probability));
+    assert_eq!(
+        StableBloomFilterDefault::new(1_000, 0.001),
+        StableBloomFilterDefault::new(1_000, probability)
+    );
+
+    for &invalid in &[0.0, 1.0, -0.5, 1_000.0, f64::NAN] {
+        assert_eq!(true, FalsePositiveProbability::new(invalid).is_err());
+        assert_eq!(true, FalsePositiveProbability::try_from(invalid).is_err());
+    }
+    assert_eq!(
+        "unsupported false positive probability of 1000, it must be in (0, 1)",
+        InvalidFalsePositiveProbability(1_000.0).to_string()
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+#[should_panic(expected = "it must be in (0, 1)")]
+fn invalid_false_positive_probability_panics() {
+    DefaultBloomFilter::new(1_000, 1.5);
+}
+
+#[test]
+fn try_new_rejects_invalid_parameters() {
+    use bloom_filter_simple::params::FilterParams;
+
+    assert_eq!(
+        StableBloomFilterDefault::new(1_000, 0.001),
+        StableBloomFilterDefault::try_new(1_000, 0.001).unwrap()
+    );
+    assert!(matches!(
+        StableBloomFilterDefault::try_new(0, 0.01),
+        Err(InvalidFilterParams::EmptyCapacity)
+    ));
+    for &invalid in &[0.0, 1.0, -0.5, 1_000.0, f64::NAN, f64::INFINITY] {
+        assert!(matches!(
+            StableBloomFilterDefault::try_new(1_000, invalid),
+            Err(InvalidFilterParams::FalsePositiveProbability(_))
+        ));
+    }
+    for &(capacity, probability) in &[(usize::MAX, 0.5), (usize::MAX / 4, 1e-9), (1 << 60, 1e-300)]
+    {
+        assert_eq!(
+            Err(InvalidFilterParams::BitCountOverflow {
+                desired_capacity: capacity,
+                desired_false_positive_probability: probability,
+            }),
+            FilterParams::try_for_capacity(capacity, probability)
+        );
+    }
+    let error: BloomFilterError = InvalidFilterParams::EmptyCapacity.into();
+    assert_eq!("an empty bloom filter is not defined", error.to_string());
+
+    // Probabilities close to one need a single hasher, not none
+    let filter = StableBloomFilterDefault::new(1_000, 0.9);
+    assert_eq!(1, filter.hasher_count());
+    assert!(filter.bit_count() > 0 && filter.bit_count() < 1_000);
+    assert_eq!(false, filter.contains(&1));
+}
+
+#[test]
+#[cfg(feature = "std")]
+#[should_panic(expected = "overflows usize")]
+fn overflowing_bit_count_panics() {
+    DefaultBloomFilter::new(usize::MAX, 1e-9);
+}
+
+#[test]
+fn explicit_number_of_hashers() {
+    use bloom_filter_simple::params::{self, FilterParams};
+
+    let filter = StableBloomFilterDefault::with_parameters(1_000, 3);
+    assert_eq!(3, filter.hasher_count());
+    assert_eq!(1_002, filter.bit_count());
+    let seeded_filter = SeededBloomFilter::with_parameters(1_000, 3);
+    assert_eq!(3, seeded_filter.hasher_count());
+    assert_eq!(1_002, seeded_filter.bit_count());
+    let counting_filter: CountingBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        CountingBloomFilter::with_parameters(1_000, 3);
+    assert_eq!(3, counting_filter.hasher_count());
+    assert_eq!(1_002, counting_filter.counter_count());
+
+    // With the optimal number of hashers, the bit count matches the one for the capacity
+    let optimal = FilterParams::for_capacity(10_000, 0.001);
+    let bit_count = params::bit_count_for_hashers(10_000, 0.001, optimal.number_of_hashers());
+    assert!((bit_count as f64 - optimal.bit_count() as f64).abs() < 0.01 * bit_count as f64);
+
+    // Fewer hashers need more bits for the same false positive probability
+    for number_of_hashers in 1..6 {
+        let bit_count = params::bit_count_for_hashers(10_000, 0.001, number_of_hashers);
+        assert!(bit_count > optimal.bit_count());
+        let mut filter = StableBloomFilterDefault::with_parameters(bit_count, number_of_hashers);
+        filter.insert_all(0..10_000);
+        assert!(filter.approximate_current_false_positive_probability() < 0.0011);
+        let false_positives = (10_000..110_000).filter(|i| filter.contains(i)).count();
+        assert!(false_positives < 150);
+    }
+}
+
+#[test]
+#[should_panic(expected = "at least one hasher")]
+fn explicit_number_of_hashers_zero_panics() {
+    StableBloomFilterDefault::with_parameters(1_000, 0);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn clear_filters() {
+    let mut km_filter = DefaultBloomFilter::new(100, 0.01);
+    let mut seeded_filter = SeededBloomFilter::new(100, 0.01);
+    let mut counting_filter: CountingBloomFilter<ahash::AHasher, DefaultHasher> =
+        CountingBloomFilter::new(100, 0.01);
+    let mut cuckoo_filter: CuckooFilter<ahash::AHasher> = CuckooFilter::with_parameters(8, 8, 4);
+    for i in 0..100 {
+        km_filter.insert(&i);
+        seeded_filter.insert(&i);
+        counting_filter.insert(&i);
+        let _ = cuckoo_filter.try_insert(&i);
+    }
+    assert_eq!(true, cuckoo_filter.is_full());
+
+    km_filter.clear();
+    seeded_filter.clear();
+    counting_filter.clear();
+    cuckoo_filter.clear();
+
+    for i in 0..100 {
+        assert_eq!(false, km_filter.contains(&i));
+        assert_eq!(false, seeded_filter.contains(&i));
+        assert_eq!(false, counting_filter.contains(&i));
+        assert_eq!(false, cuckoo_filter.contains(&i));
+    }
+    assert_eq!(0.0, km_filter.approximate_element_count());
+    assert_eq!(0.0, seeded_filter.approximate_element_count());
+    assert_eq!(0.0, counting_filter.approximate_element_count());
+    assert_eq!(true, cuckoo_filter.is_empty());
+    assert_eq!(false, cuckoo_filter.is_full());
+
+    // Cleared filters can be reused
+    km_filter.insert(&"reused");
+    cuckoo_filter.insert(&"reused");
+    assert_eq!(true, km_filter.contains(&"reused"));
+    assert_eq!(true, cuckoo_filter.contains(&"reused"));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn fill_ratio_and_saturation() {
+    let mut km_filter = DefaultBloomFilter::new(1_000, 0.01);
+    let mut seeded_filter = SeededBloomFilter::new(1_000, 0.01);
+    let mut counting_filter: CountingBloomFilter<ahash::AHasher, DefaultHasher> =
+        CountingBloomFilter::new(1_000, 0.01);
+    let mut cuckoo_filter: CuckooFilter<ahash::AHasher> = CuckooFilter::new(1_000, 0.01);
+    assert_eq!(0.0, km_filter.fill_ratio());
+    assert_eq!(0.0, seeded_filter.fill_ratio());
+    assert_eq!(0.0, counting_filter.fill_ratio());
+    assert_eq!(0.0, cuckoo_filter.fill_ratio());
+    assert_eq!(km_filter.hasher_count(), seeded_filter.hasher_count());
+    assert_eq!(km_filter.hasher_count(), counting_filter.hasher_count());
+    assert_eq!(km_filter.bit_count(), counting_filter.counter_count());
+    assert!(km_filter.bit_count() >= 9_585);
+
+    for i in 0..1_000 {
+        km_filter.insert(&i);
+        seeded_filter.insert(&i);
+        counting_filter.insert(&i);
+        cuckoo_filter.insert(&i);
+    }
+    // Optimally sized filters are about half full at their desired capacity
+    for fill_ratio in [
+        km_filter.fill_ratio(),
+        seeded_filter.fill_ratio(),
+        counting_filter.fill_ratio(),
+    ]
+    .iter()
+    {
+        assert!(*fill_ratio > 0.45 && *fill_ratio < 0.55);
+    }
+    assert_eq!(false, km_filter.is_saturated(0.6));
+    assert_eq!(false, cuckoo_filter.is_saturated(1.0));
+    assert_eq!(
+        1_000.0 / cuckoo_filter.slot_count() as f64,
+        cuckoo_filter.fill_ratio()
+    );
+
+    km_filter.insert_all(1_000..5_000);
+    assert_eq!(true, km_filter.is_saturated(0.6));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn write_and_read_km_bloom_filter() {
+    let mut filter = StableBloomFilterDefault::new(1_000, 0.01);
+    filter.insert_all(0..500);
+
+    let mut bytes = Vec::new();
+    filter.write_to(&mut bytes).unwrap();
+    let read = StableBloomFilterDefault::read_from(&bytes[..]).unwrap();
+
+    assert_eq!(true, filter.eq_configuration(&read));
+    for i in 0..500 {
+        assert_eq!(true, read.contains(&i));
+    }
+    assert_eq!(
+        filter.approximate_element_count(),
+        read.approximate_element_count()
+    );
+
+    // Truncated input is rejected
+    assert!(matches!(
+        StableBloomFilterDefault::read_from(&bytes[..bytes.len() - 1]),
+        Err(ReadFilterError::Io(_))
+    ));
+
+    // A header that claims far more bits than follow is rejected without allocating them
+    let mut huge = bytes[..40].to_vec();
+    huge[24..32].copy_from_slice(&1u64.to_le_bytes());
+    huge[32..40].copy_from_slice(&(1u64 << 62).to_le_bytes());
+    huge.extend_from_slice(&[0xff; 64]);
+    assert!(matches!(
+        StableBloomFilterDefault::read_from(&huge[..]),
+        Err(ReadFilterError::Io(_))
+    ));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn read_km_bloom_filter_validates_file_format() {
+    let mut filter = StableBloomFilterDefault::new(1_000, 0.01);
+    filter.insert_all(0..500);
+    let mut bytes = Vec::new();
+    filter.write_to(&mut bytes).unwrap();
+
+    // Different hashers, or the same hashers with different seeds
+    assert!(matches!(
+        KMBloomFilter::<xxh3::Xxh3, xxh64::Xxh64>::read_from(&bytes[..]),
+        Err(ReadFilterError::HasherMismatch)
+    ));
+    assert!(matches!(
+        KMBloomFilter::<StableXxh64Hasher, StableXxh3Hasher>::read_from(&bytes[..]),
+        Err(ReadFilterError::HasherMismatch)
+    ));
+
+    let mut invalid = bytes.clone();
+    invalid[0] = b'X';
+    assert!(matches!(
+        StableBloomFilterDefault::read_from(&invalid[..]),
+        Err(ReadFilterError::InvalidMagic)
+    ));
+
+    let mut invalid = bytes.clone();
+    invalid[4] = 3;
+    assert!(matches!(
+        StableBloomFilterDefault::read_from(&invalid[..]),
+        Err(ReadFilterError::UnsupportedVersion(3))
+    ));
+
+    let mut invalid = bytes.clone();
+    invalid[24..32].copy_from_slice(&0u64.to_le_bytes());
+    assert!(matches!(
+        StableBloomFilterDefault::read_from(&invalid[..]),
+        Err(ReadFilterError::InvalidHeader { .. })
+    ));
+
+    let mut invalid = bytes;
+    invalid[100] ^= 0x10;
+    assert!(matches!(
+        StableBloomFilterDefault::read_from(&invalid[..]),
+        Err(ReadFilterError::ChecksumMismatch)
+    ));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn km_bloom_filter_to_and_from_bytes() {
+    let mut filter = StableBloomFilterDefault::new(1_000, 0.01);
+    filter.insert_all(0..500);
+    let bytes = filter.to_bytes();
+    let mut written = Vec::new();
+    filter.write_to(&mut written).unwrap();
+    assert_eq!(written, bytes);
+
+    let restored = StableBloomFilterDefault::from_bytes(&bytes).unwrap();
+    assert_eq!(filter, restored);
+    assert_eq!(true, (0..500).all(|i| restored.contains(&i)));
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert!(matches!(
+        StableBloomFilterDefault::from_bytes(&trailing),
+        Err(ReadFilterError::InvalidLength { .. })
+    ));
+    assert!(matches!(
+        StableBloomFilterDefault::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(ReadFilterError::InvalidLength { .. })
+    ));
+    assert!(matches!(
+        StableBloomFilterDefault::from_bytes(&bytes[..10]),
+        Err(ReadFilterError::Io(_))
+    ));
+}
+
+#[test]
+#[cfg(feature = "tower")]
+fn dedup_layer_marks_and_rejects_duplicate_requests() {
+    use bloom_filter_simple::{BloomDedupLayer, OnDuplicate};
+    use futures_util::{
+        future::{self, Ready},
+        FutureExt,
+    };
+    use std::{
+        convert::Infallible,
+        task::{Context, Poll},
+    };
+    use tower_layer::Layer;
+    use tower_service::Service;
+
+    #[derive(Clone, Copy)]
+    struct Request {
+        key: Option<u64>,
+        duplicate: bool,
+    }
+
+    // Responds whether the request has been marked as duplicate
+    struct Handler;
+
+    impl Service<Request> for Handler {
+        type Response = &'static str;
+        type Error = Infallible;
+        type Future = Ready<Result<&'static str, Infallible>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: Request) -> Self::Future {
+            future::ready(Ok(if request.duplicate { "marked" } else { "new" }))
+        }
+    }
+
+    fn send<S: Service<Request, Response = &'static str, Error = Infallible>>(
+        service: &mut S,
+        key: Option<u64>,
+    ) -> &'static str {
+        let request = Request {
+            key,
+            duplicate: false,
+        };
+        service.call(request).now_or_never().unwrap().unwrap()
+    }
+
+    let mark = BloomDedupLayer::new(
+        RotatingBloomFilter::<StableXxh3Hasher, StableXxh64Hasher>::new(2, 1_000, 0.0001),
+        |request: &Request| request.key,
+        OnDuplicate::Mark(|request: &mut Request| request.duplicate = true),
+    );
+    let mut service = mark.layer(Handler);
+    assert_eq!("new", send(&mut service, Some(1)));
+    assert_eq!("marked", send(&mut service, Some(1)));
+    // Requests without a key are never duplicates
+    assert_eq!("new", send(&mut service, None));
+    assert_eq!("new", send(&mut service, None));
+
+    // Services of the same layer share the filter
+    let mut other = mark.layer(Handler);
+    assert_eq!("marked", send(&mut other, Some(1)));
+    assert_eq!("new", send(&mut other, Some(2)));
+    assert_eq!("marked", send(&mut service, Some(2)));
+
+    // Keys expire with the generation they have been recorded in
+    mark.filter().lock().unwrap().advance();
+    mark.filter().lock().unwrap().advance();
+    assert_eq!("new", send(&mut service, Some(1)));
+
+    let reject = BloomDedupLayer::new(
+        RotatingBloomFilter::<StableXxh3Hasher, StableXxh64Hasher>::new(2, 1_000, 0.0001),
+        |request: &Request| request.key,
+        OnDuplicate::Reject(|_: Request| "rejected"),
+    );
+    let mut service = reject.layer(Handler);
+    assert_eq!("new", send(&mut service, Some(1)));
+    assert_eq!("rejected", send(&mut service, Some(1)));
+    assert_eq!("new", send(&mut service, Some(2)));
+}
+
+#[test]
+#[cfg(feature = "tokio")]
+fn save_and_load_km_bloom_filter_async() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let mut filter = StableBloomFilterDefault::new(100_000, 0.01);
+    filter.insert_all(0..50_000);
+    let mut bytes = Vec::new();
+    filter.write_to(&mut bytes).unwrap();
+
+    runtime.block_on(async {
+        // The pipe is much smaller than the filter, so both sides have to yield repeatedly
+        let (writer, reader) = tokio::io::duplex(4_096);
+        let save = tokio::spawn(async move { filter.save_to(writer).await.map(|_| filter) });
+        let loaded = StableBloomFilterDefault::load_from(reader).await.unwrap();
+        let filter = save.await.unwrap().unwrap();
+        assert_eq!(filter, loaded);
+
+        // Both formats are the same
+        let mut saved = Vec::new();
+        filter.save_to(&mut saved).await.unwrap();
+        assert_eq!(bytes, saved);
+
+        let mut invalid = bytes.clone();
+        invalid[100] ^= 0x10;
+        assert!(matches!(
+            StableBloomFilterDefault::load_from(&invalid[..]).await,
+            Err(ReadFilterError::ChecksumMismatch)
+        ));
+        assert!(matches!(
+            StableBloomFilterDefault::load_from(&bytes[..bytes.len() - 1]).await,
+            Err(ReadFilterError::Io(_))
+        ));
+
+        let mut huge = bytes[..40].to_vec();
+        huge[32..40].copy_from_slice(&(1u64 << 60).to_le_bytes());
+        assert!(matches!(
+            StableBloomFilterDefault::load_from(&huge[..]).await,
+            Err(ReadFilterError::Io(_))
+        ));
+        assert!(matches!(
+            KMBloomFilter::<StableXxh64Hasher, StableXxh3Hasher>::load_from(&bytes[..]).await,
+            Err(ReadFilterError::HasherMismatch)
+        ));
+    });
+}
+
+#[test]
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+fn save_and_load_compressed_km_bloom_filter() {
+    use bloom_filter_simple::Compression;
+
+    let mut filter = StableBloomFilterDefault::new(100_000, 0.01);
+    filter.insert_all(0..10_000);
+    let bytes = filter.to_bytes();
+
+    let compressions = [
+        #[cfg(feature = "gzip")]
+        Compression::Gzip,
+        #[cfg(feature = "zstd")]
+        Compression::Zstd,
+    ];
+    for &compression in compressions.iter() {
+        let mut compressed = Vec::new();
+        filter
+            .save_compressed(&mut compressed, compression)
+            .unwrap();
+        // A tenth of the bits are set
+        assert!(compressed.len() < bytes.len() / 2);
+        assert_eq!(
+            filter,
+            StableBloomFilterDefault::load_compressed(&compressed[..]).unwrap()
+        );
+
+        assert!(matches!(
+            StableBloomFilterDefault::load_compressed(&compressed[..compressed.len() / 2]),
+            Err(ReadFilterError::Io(_))
+        ));
+        assert!(matches!(
+            KMBloomFilter::<StableXxh64Hasher, StableXxh3Hasher>::load_compressed(&compressed[..]),
+            Err(ReadFilterError::HasherMismatch)
+        ));
+    }
+
+    // Uncompressed filters are read as well
+    assert_eq!(
+        filter,
+        StableBloomFilterDefault::load_compressed(&bytes[..]).unwrap()
+    );
+    assert!(matches!(
+        StableBloomFilterDefault::load_compressed(&b"not a filter"[..]),
+        Err(ReadFilterError::InvalidMagic)
+    ));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn borrowed_bloom_filter_view() {
+    let mut filter = StableBloomFilterDefault::new(1_000, 0.01);
+    filter.insert_all(0..500);
+
+    // A view over the bits of the filter
+    let mut bits = Vec::new();
+    filter.bitset().write_bytes(&mut bits).unwrap();
+    let view: BloomFilterView<StableXxh3Hasher, StableXxh64Hasher> = BloomFilterView::new(
+        &bits,
+        filter.hasher_count(),
+        filter.bit_count() / filter.hasher_count(),
+    );
+    for i in 0..2_000 {
+        assert_eq!(filter.contains(&i), view.contains(&i));
+    }
+    assert_eq!(true, view.contains_hashed(&filter.hash(&42)));
+    assert_eq!(filter.bit_count(), view.bit_count());
+    assert_eq!(
+        filter.approximate_element_count(),
+        view.approximate_element_count()
+    );
+    assert_eq!(filter.fill_ratio(), view.fill_ratio());
+
+    // A view over a filter file that is embedded in a larger buffer
+    let mut archive = vec![0xab; 13];
+    filter.write_to(&mut archive).unwrap();
+    let end = archive.len();
+    archive.extend_from_slice(b"trailing data");
+    let view: BloomFilterView<StableXxh3Hasher, StableXxh64Hasher> =
+        BloomFilterView::read_from(&archive[13..end]).unwrap();
+    assert_eq!(bits, view.as_bytes());
+    assert_eq!(true, (0..500).all(|i| view.contains(&i)));
+
+    assert!(matches!(
+        BloomFilterView::<StableXxh3Hasher, StableXxh64Hasher>::read_from(&archive[13..]),
+        Err(ReadFilterError::InvalidLength { .. })
+    ));
+    assert!(matches!(
+        BloomFilterView::<StableXxh64Hasher, StableXxh3Hasher>::read_from(&archive[13..end]),
+        Err(ReadFilterError::HasherMismatch)
+    ));
+}
+
+#[test]
+#[should_panic(expected = "needs more than usize::MAX bits")]
+fn bloom_filter_view_with_too_many_bits_panics() {
+    BloomFilterView::<StableXxh3Hasher, StableXxh64Hasher>::new(&[0; 10], 2, usize::MAX);
+}
+
+#[test]
+#[should_panic(expected = "unable to view a bloom filter")]
+fn bloom_filter_view_with_wrong_length_panics() {
+    BloomFilterView::<StableXxh3Hasher, StableXxh64Hasher>::new(&[0; 10], 3, 100);
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn query_memory_mapped_km_bloom_filter() {
+    use bloom_filter_simple::{MmapBloomFilter, StableXxh3Hasher, StableXxh64Hasher};
+
+    let path = std::env::temp_dir().join("bloom_filter_simple_query_memory_mapped.bin");
+    let mut filter = StableBloomFilterDefault::new(1_000, 0.01);
+    filter.insert_all(0..500);
+    filter
+        .write_to(std::fs::File::create(&path).unwrap())
+        .unwrap();
+
+    let mapped: MmapBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        MmapBloomFilter::open(&path).unwrap();
+    for i in 0..1_000 {
+        assert_eq!(filter.contains(&i), mapped.contains(&i));
+    }
+    assert_eq!(
+        filter.approximate_element_count(),
+        mapped.approximate_element_count()
+    );
+    assert_eq!(mapped.bit_count(), mapped.view().bit_count());
+    assert_eq!(true, mapped.view().contains(&42));
+
+    // Files written with different hashers are rejected
+    assert!(matches!(
+        MmapBloomFilter::<xxh3::Xxh3, xxh64::Xxh64>::open(&path),
+        Err(ReadFilterError::HasherMismatch)
+    ));
+
+    // Corrupted files are rejected
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[100] ^= 0x10;
+    std::fs::write(&path, &bytes).unwrap();
+    assert!(matches!(
+        MmapBloomFilter::<StableXxh3Hasher, StableXxh64Hasher>::open(&path),
+        Err(ReadFilterError::ChecksumMismatch)
+    ));
+
+    // Truncated files are rejected
+    std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+    assert!(matches!(
+        MmapBloomFilter::<StableXxh3Hasher, StableXxh64Hasher>::open(&path),
+        Err(ReadFilterError::InvalidLength { .. })
+    ));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn wal_bloom_filter_recovers_insertions_after_crash() {
+    use bloom_filter_simple::WalBloomFilter;
+
+    let path = std::env::temp_dir().join("bloom_filter_simple_write_ahead_log.wal");
+    let mut filter =
+        WalBloomFilter::create(&path, StableBloomFilterDefault::new(1_000, 0.01)).unwrap();
+    let snapshot_length = std::fs::metadata(&path).unwrap().len();
+    filter.insert(&"Hello!").unwrap();
+    filter.insert_all(0..100).unwrap();
+    filter
+        .insert_hashed(&filter.filter().hash(&"World!"))
+        .unwrap();
+    assert_eq!(102, filter.log_len());
+    assert_eq!(
+        snapshot_length + 102 * 16,
+        std::fs::metadata(&path).unwrap().len()
+    );
+
+    // A crash interrupted the write of a record, which is dropped by recover
+    let expected = filter.into_filter();
+    let mut log = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    std::io::Write::write_all(&mut log, &[0xff; 7]).unwrap();
+    drop(log);
+    let mut filter: WalBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        WalBloomFilter::recover(&path).unwrap();
+    assert_eq!(102, filter.log_len());
+    assert_eq!(&expected, filter.filter());
+    assert_eq!(true, filter.contains(&"World!"));
+
+    // The recovered filter continues to append to the log
+    filter.insert(&100).unwrap();
+    let mut filter: WalBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        WalBloomFilter::recover(&path).unwrap();
+    assert_eq!(103, filter.log_len());
+    assert_eq!(true, (0..=100).all(|i| filter.contains(&i)));
+
+    // A checkpoint replaces the log with a snapshot
+    filter.checkpoint().unwrap();
+    filter.sync().unwrap();
+    assert_eq!(0, filter.log_len());
+    assert_eq!(snapshot_length, std::fs::metadata(&path).unwrap().len());
+    let filter: WalBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        WalBloomFilter::recover(&path).unwrap();
+    assert_eq!(0, filter.log_len());
+    assert_eq!(true, (0..=100).all(|i| filter.contains(&i)));
+
+    // Corrupted snapshots are rejected
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[50] ^= 0x01;
+    std::fs::write(&path, &bytes).unwrap();
+    assert!(matches!(
+        WalBloomFilter::<StableXxh3Hasher, StableXxh64Hasher>::recover(&path),
+        Err(ReadFilterError::ChecksumMismatch)
+    ));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn wal_bloom_filter_does_not_log_refused_insertions() {
+    use bloom_filter_simple::{WalBloomFilter, WalInsertError};
+
+    let path = std::env::temp_dir().join("bloom_filter_simple_write_ahead_log_capacity.wal");
+    let mut filter = WalBloomFilter::create(
+        &path,
+        StableBloomFilterDefault::new(1_000, 0.01).with_strict_capacity(100),
+    )
+    .unwrap();
+    let mut inserted = 0;
+    let refused = loop {
+        match filter.insert(&inserted) {
+            Ok(()) => inserted += 1,
+            Err(error) => break error,
+        }
+    };
+    assert!(matches!(refused, WalInsertError::CapacityExceeded(_)));
+    let log_length = std::fs::metadata(&path).unwrap().len();
+    assert!(matches!(
+        filter.insert_all(inserted..inserted + 10),
+        Err(WalInsertError::CapacityExceeded(_))
+    ));
+    assert_eq!(inserted, filter.log_len());
+    assert_eq!(log_length, std::fs::metadata(&path).unwrap().len());
+
+    // The refused elements are not replayed into the recovered filter
+    let expected = filter.into_filter();
+    let filter: WalBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        WalBloomFilter::recover(&path).unwrap();
+    assert_eq!(inserted, filter.log_len());
+    assert_eq!(&expected, filter.filter());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn lazy_bloom_filter_reads_bits_on_first_access() {
+    use bloom_filter_simple::{LazyBloomFilter, StableXxh3Hasher, StableXxh64Hasher};
+
+    let path = std::env::temp_dir().join("bloom_filter_simple_lazy_loading.bin");
+    // The bits of the filter span two chunks
+    let mut filter = StableBloomFilterDefault::new(1_000_000, 0.01);
+    filter.insert_all(0..500);
+    filter
+        .write_to(std::fs::File::create(&path).unwrap())
+        .unwrap();
+
+    let lazy: LazyBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        LazyBloomFilter::open(&path).unwrap();
+    assert_eq!(filter.bit_count(), lazy.bit_count());
+    assert_eq!(filter.hasher_count(), lazy.hasher_count());
+    assert_eq!(false, lazy.is_warm());
+    for i in 0..1_000 {
+        assert_eq!(filter.contains(&i), lazy.contains(&i));
+    }
+    assert_eq!(true, lazy.warm_up().is_ok());
+    assert_eq!(true, lazy.is_warm());
+    assert_eq!(true, lazy.contains_hashed(&filter.hash(&42)));
+
+    // Files written with different hashers are rejected when they are opened
+    assert!(matches!(
+        LazyBloomFilter::<xxh3::Xxh3, xxh64::Xxh64>::open(&path),
+        Err(ReadFilterError::HasherMismatch)
+    ));
+
+    // Corrupted bits are only detected by warm_up
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[100] ^= 0x10;
+    std::fs::write(&path, &bytes).unwrap();
+    let lazy = LazyBloomFilter::<StableXxh3Hasher, StableXxh64Hasher>::open(&path).unwrap();
+    assert!(matches!(
+        lazy.warm_up(),
+        Err(ReadFilterError::ChecksumMismatch)
+    ));
+
+    // Truncated files are rejected when they are opened
+    std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+    assert!(matches!(
+        LazyBloomFilter::<StableXxh3Hasher, StableXxh64Hasher>::open(&path),
+        Err(ReadFilterError::InvalidLength { .. })
+    ));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[cfg(feature = "metrics")]
+fn filter_stats_count_insertions_and_queries() {
+    let mut filter = StableBloomFilterDefault::new(10_000, 0.01);
+    assert_eq!(bloom_filter_simple::FilterStats::default(), filter.stats());
+
+    // Every way of inserting and looking up elements is counted
+    filter.insert_all(0..5_000);
+    filter.insert(&"Hello!");
+    filter.insert_bytes(b"Hello!");
+    assert!(filter.try_insert(&5_000).is_ok());
+    for i in 0..10_000 {
+        filter.contains(&i);
+    }
+    assert_eq!(true, filter.contains_bytes(b"Hello!"));
+    assert_eq!(true, filter.contains_hashed(&filter.hash(&"Hello!")));
+
+    let stats = filter.stats();
+    assert_eq!(5_003, stats.inserts);
+    assert_eq!(10_002, stats.queries);
+    let false_positives = (5_001..10_000).filter(|i| filter.contains(i)).count() as u64;
+    assert_eq!(5_003 + false_positives, stats.positives);
+    assert!((stats.estimated_false_positives - false_positives as f64).abs() < 30.0);
+    assert_eq!(filter.fill_ratio(), stats.fill_ratio);
+    assert!((stats.hit_rate() - 0.5).abs() < 0.01);
+
+    // Counters are kept by union_with, and start from zero for new filters
+    let other = StableBloomFilterDefault::new(10_000, 0.01);
+    assert_eq!(0, filter.union(&other).stats().inserts);
+    filter.union_with(&other);
+    assert_eq!(5_003, filter.stats().inserts);
+
+    filter.reset_stats();
+    let stats = filter.stats();
+    assert_eq!((0, 0, 0), (stats.inserts, stats.queries, stats.positives));
+    assert_eq!(0.0, stats.hit_rate());
+}
+
+#[test]
+fn errors_convert_into_bloom_filter_error() {
+    fn insert_into_both(
+        filter: &mut KMBloomFilter<StableXxh3Hasher, StableXxh64Hasher>,
+        cuckoo: &mut CuckooFilter<StableXxh3Hasher>,
+        data: u64,
+    ) -> Result<(), BloomFilterError> {
+        filter.try_insert(&data)?;
+        cuckoo.try_insert(&data)?;
+        Ok(())
+    }
+
+    let mut filter = StableBloomFilterDefault::new(100, 0.01).with_strict_capacity(100);
+    let mut cuckoo = CuckooFilter::new(10_000, 0.01);
+    let mut data = 0;
+    let error = loop {
+        if let Err(error) = insert_into_both(&mut filter, &mut cuckoo, data) {
+            break error;
+        }
+        data += 1;
+    };
+    assert!(matches!(error, BloomFilterError::CapacityExceeded));
+    assert_eq!(CapacityExceeded.to_string(), error.to_string());
+
+    #[cfg(feature = "std")]
+    {
+        let error = BloomFilterError::from(ReadFilterError::InvalidMagic);
+        assert!(std::error::Error::source(&error).is_some());
+    }
+    assert!(matches!(
+        Bitset::new(10).try_union(&Bitset::new(20)),
+        Err(BloomFilterError::LengthMismatch {
+            left: 10,
+            right: 20
+        })
+    ));
+}
+
+#[test]
+fn new_from_sample_sizes_filter_for_distinct_elements() {
+    // 1,000,000 events of 50,000 users in random order
+    let mut rng = StdRng::seed_from_u64(7);
+    let events: Vec<u64> = (0..1_000_000).map(|_| rng.gen_range(0, 50_000)).collect();
+    let mut filter =
+        StableBloomFilterDefault::new_from_sample(&events[..20_000], 0.01, events.len());
+    let exact = StableBloomFilterDefault::new(50_000, 0.01);
+    assert!(filter.bit_count() >= exact.bit_count());
+    assert!(filter.bit_count() < 2 * exact.bit_count());
+
+    filter.insert_all(&events);
+    assert!(filter.approximate_current_false_positive_probability() < 0.01);
+
+    // Without a sample, the hint is the capacity
+    let filter = StableBloomFilterDefault::new_from_sample(Vec::<u64>::new(), 0.01, 50_000);
+    assert_eq!(exact.bit_count(), filter.bit_count());
+    // A hint that is smaller than the sample is ignored
+    let filter = StableBloomFilterDefault::new_from_sample(0..50_000, 0.01, 10);
+    assert!(filter.bit_count() >= exact.bit_count());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn filter_with_exceptions_suppresses_marked_false_positives() {
+    let mut filter = FilterWithExceptions::new(StableBloomFilterDefault::new(100, 0.1));
+    filter.extend(0..100u64);
+    let false_positives: Vec<u64> = (100..10_000u64)
+        .filter(|data| filter.might_contain(data))
+        .collect();
+    assert!(!false_positives.is_empty());
+
+    for &data in false_positives.iter() {
+        assert_eq!(true, filter.mark_false_positive(data));
+    }
+    assert_eq!(false_positives.len(), filter.exception_count());
+    assert!((100..10_000u64).all(|data| !filter.might_contain(&data)));
+    assert!((0..100u64).all(|data| filter.might_contain(&data)));
+    // The wrapped filter is unchanged
+    assert!(false_positives
+        .iter()
+        .all(|data| filter.filter().contains(data)));
+
+    // Elements that are not contained need no exception
+    let absent = (10_000..).find(|data| !filter.might_contain(data)).unwrap();
+    assert_eq!(false, filter.mark_false_positive(absent));
+
+    // Inserting a false positive removes its exception
+    filter.insert(false_positives[0]);
+    assert_eq!(true, filter.might_contain(&false_positives[0]));
+    assert_eq!(false, filter.is_marked_false_positive(&false_positives[0]));
+
+    filter.clear_exceptions();
+    assert_eq!(0, filter.exception_count());
+    assert!(false_positives
+        .iter()
+        .all(|data| filter.might_contain(data)));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn filter_built_from_documented_scheme_passes_test_vectors() {
+    let (number_of_hashers, bits_per_hasher): (usize, usize) = (5, 997);
+    let vectors = test_vectors::generate::<StableXxh3Hasher, StableXxh64Hasher>(
+        number_of_hashers,
+        bits_per_hasher,
+    );
+    assert_eq!(test_vectors::canonical_inputs().len(), vectors.len());
+
+    // Build the file like another implementation would, only from the documented scheme
+    let mut bits = vec![0u8; (number_of_hashers * bits_per_hasher).div_ceil(8)];
+    for input in test_vectors::canonical_inputs() {
+        let hash_a = xxh3::xxh3_64_with_seed(&input, STABLE_XXH3_SEED);
+        let hash_b = xxh64::xxh64(&input, STABLE_XXH64_SEED);
+        for i in 0..number_of_hashers {
+            let hash = hash_a.wrapping_add((i as u64).wrapping_mul(hash_b));
+            let index = i * bits_per_hasher + (hash % bits_per_hasher as u64) as usize;
+            bits[index / 8] |= 1 << (index % 8);
+        }
+    }
+    let mut file = b"BFKM".to_vec();
+    file.extend_from_slice(&1u32.to_le_bytes());
+    file.extend_from_slice(
+        &xxh3::xxh3_64_with_seed(b"bloom_filter_simple", STABLE_XXH3_SEED).to_le_bytes(),
+    );
+    file.extend_from_slice(&xxh64::xxh64(b"bloom_filter_simple", STABLE_XXH64_SEED).to_le_bytes());
+    file.extend_from_slice(&(number_of_hashers as u64).to_le_bytes());
+    file.extend_from_slice(&(bits_per_hasher as u64).to_le_bytes());
+    file.extend_from_slice(&bits);
+    file.extend_from_slice(&xxh64::xxh64(&bits, 0).to_le_bytes());
+
+    let filter = StableBloomFilterDefault::read_from(&file[..]).unwrap();
+    assert_eq!(Ok(()), test_vectors::validate(&filter, &vectors));
+    assert_eq!(true, filter.contains_bytes(b"Hello!"));
+
+    // Vectors for another configuration or a filter without the inputs do not validate
+    let other = test_vectors::generate::<StableXxh3Hasher, StableXxh64Hasher>(5, 1_000);
+    assert_eq!(
+        Err(TestVectorMismatch::Indices { vector: 0 }),
+        test_vectors::validate(&filter, &other)
+    );
+    let empty = StableBloomFilterDefault::with_parameters(5 * 997, 5);
+    assert_eq!(
+        Err(TestVectorMismatch::NotContained { vector: 0 }),
+        test_vectors::validate(&empty, &vectors)
+    );
+    let mut tampered = vectors.clone();
+    tampered[3].hash_b ^= 1;
+    assert_eq!(
+        Err(TestVectorMismatch::Hashes { vector: 3 }),
+        test_vectors::validate(&filter, &tampered)
+    );
+}
+
+#[test]
+fn exact_formulas_guarantee_false_positive_probability() {
+    use bloom_filter_simple::params::{FilterParams, Formula};
+
+    for &capacity in [1usize, 10, 1_000, 100_000, 10_000_000].iter() {
+        for &probability in [0.5, 0.1, 0.01, 0.001, 1e-6].iter() {
+            let classic = FilterParams::for_capacity(capacity, probability);
+            for &formula in [Formula::Partitioned, Formula::Unpartitioned].iter() {
+                let params = FilterParams::for_capacity_with(capacity, probability, formula);
+                assert_eq!(formula, params.formula());
+                assert!(params.false_positive_probability_at(capacity as f64) <= probability);
+                // The exact formulas need at most a few percent more bits
+                assert!(params.bit_count() as f64 <= 1.1 * classic.bit_count() as f64 + 64.0);
+            }
+        }
+    }
+    assert_eq!(
+        FilterParams::for_capacity(1_000, 0.01),
+        FilterParams::for_capacity_with(1_000, 0.01, Formula::Classic)
+    );
+
+    // The measured false positive rate of a filter with partitioned parameters
+    let params = FilterParams::for_capacity_with(10_000, 0.01, Formula::Partitioned);
+    let mut filter =
+        StableBloomFilterDefault::with_parameters(params.bit_count(), params.number_of_hashers());
+    assert_eq!(params.bit_count(), filter.bit_count());
+    filter.insert_all(0..10_000);
+    let false_positives = (10_000..1_010_000)
+        .filter(|element| filter.contains(element))
+        .count();
+    assert!(false_positives < 11_000);
+}
+
+/// Hasher that returns the last integer written to it, which clusters sequential keys.
+#[derive(Default)]
+struct IdentityHasher(u64);
+
+impl Hasher for IdentityHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 << 8) | byte as u64;
+        }
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.0 = i;
+    }
+}
+
+#[test]
+fn density_histogram_reveals_clustering_hashers() {
+    let mut filter = StableBloomFilterDefault::new(10_000, 0.01);
+    filter.insert_all(0..10_000u64);
+    let histogram = filter.density_histogram(filter.hasher_count() * 4);
+    let mean = histogram.iter().sum::<f64>() / histogram.len() as f64;
+    assert!((mean - filter.fill_ratio()).abs() < 0.001);
+    assert!(histogram
+        .iter()
+        .all(|&ratio| (ratio - filter.fill_ratio()).abs() < 0.05));
+
+    // Sequential keys set a contiguous run of bits in every region of the first hasher
+    let mut filter: KMBloomFilter<IdentityHasher, IdentityHasher> =
+        KMBloomFilter::new(10_000, 0.01);
+    filter.insert_all(0..2_000u64);
+    let histogram = filter.density_histogram(filter.hasher_count() * 4);
+    assert!(histogram.contains(&0.0));
+    assert!(histogram.iter().any(|&ratio| ratio > 0.5));
+}
+
+#[test]
+fn const_parameters_match_floating_point_parameters() {
+    use bloom_filter_simple::params;
+
+    for &capacity in [1usize, 7, 1_000, 123_457, 10_000_000, 1 << 40].iter() {
+        for &one_in in [2u64, 3, 10, 100, 1_000, 65_536, 1_000_000, 1 << 40].iter() {
+            let bit_count = params::optimal_bit_count(capacity, 1.0 / one_in as f64);
+            let const_bit_count = params::optimal_bit_count_const(capacity, one_in);
+            assert!(
+                const_bit_count >= bit_count && const_bit_count <= bit_count + 1,
+                "{} elements, one in {}: {} != {}",
+                capacity,
+                one_in,
+                const_bit_count,
+                bit_count
+            );
+            assert_eq!(
+                params::optimal_number_of_hashers(capacity, bit_count),
+                params::optimal_number_of_hashers_const(capacity, bit_count)
+            );
+        }
+    }
+}
+
+#[test]
+fn frozen_filter_shares_bits_between_threads() {
+    let mut filter = StableBloomFilterDefault::new(1_000, 0.001);
+    filter.insert_all(0..1_000);
+    let expected = filter.bitset().clone();
+    let frozen: FrozenBloomFilter<StableXxh3Hasher, StableXxh64Hasher> = filter.freeze();
+    assert_eq!(&expected, frozen.bitset());
+
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let frozen = frozen.clone();
+            std::thread::spawn(move || (0..1_000).all(|i| frozen.contains(&i)))
+        })
+        .collect();
+    for reader in readers {
+        assert_eq!(true, reader.join().unwrap());
+    }
+    assert_eq!(
+        frozen.approximate_element_count(),
+        frozen.thaw().approximate_element_count()
+    );
+
+    let mut thawed = frozen.thaw();
+    thawed.insert(&"Hello!");
+    assert_eq!(true, thawed.contains(&"Hello!"));
+    assert_eq!(false, frozen.contains(&"Hello!"));
+    assert_eq!(frozen.hasher_count(), thawed.hasher_count());
+    assert_eq!(frozen.bit_count(), thawed.bit_count());
+}
+
+#[test]
+fn rebuild_without_removes_elements() {
+    let all: Vec<u64> = (0..1_000).collect();
+    let removed: Vec<u64> = (0..1_000).step_by(10).collect();
+    let mut filter = StableBloomFilterDefault::new(1_000, 0.01);
+    filter.insert_all(all.iter());
+
+    let rebuilt = filter.rebuild_without(removed.iter(), all.iter());
+    assert!(rebuilt.eq_configuration(&filter));
+    assert!(rebuilt.is_subset(&filter));
+    assert!(all
+        .iter()
+        .filter(|element| *element % 10 != 0)
+        .all(|element| rebuilt.contains(&element)));
+    // Removed elements are only contained as false positives
+    let false_positives = removed
+        .iter()
+        .filter(|element| rebuilt.contains(element))
+        .count();
+    assert!(false_positives < 5);
+
+    let mut expected = StableBloomFilterDefault::new(1_000, 0.01);
+    expected.insert_all(all.iter().filter(|element| *element % 10 != 0));
+    assert_eq!(expected.bitset(), rebuilt.bitset());
+}
+
+#[test]
+fn probabilistic_set_is_typed_facade_of_filter() {
+    let mut set: ProbabilisticSet<String> = ProbabilisticSet::new(1_000, 0.01);
+    let elements: Vec<String> = (0..500).map(|i| format!("element {}", i)).collect();
+    set.extend(elements.iter());
+
+    assert!(elements
+        .iter()
+        .all(|element| set.contains(element.as_str())));
+    assert!((set.len_estimate() as f64 - 500.0).abs() < 25.0);
+    assert!(set.fp_rate() > 0.0 && set.fp_rate() < 0.01);
+
+    // The set answers like a filter into which the same elements have been inserted
+    let mut filter = StableBloomFilterDefault::new(1_000, 0.01);
+    filter.insert_all(elements.iter());
+    assert_eq!(filter.bitset(), set.filter().bitset());
+
+    set.clear();
+    assert_eq!(0, set.len_estimate());
+    assert_eq!(0.0, set.fp_rate());
+}
+
+#[test]
+fn build_hasher_bloom_filter_matches_km_bloom_filter() {
+    use std::{
+        collections::hash_map::RandomState,
+        hash::{BuildHasher, BuildHasherDefault},
+    };
+
+    // With default build hashers, the bits are those of a KMBloomFilter
+    let mut filter: BuildHasherBloomFilter<
+        BuildHasherDefault<StableXxh3Hasher>,
+        BuildHasherDefault<StableXxh64Hasher>,
+    > = BuildHasherBloomFilter::new(1_000, 0.01);
+    filter.extend(0..1_000);
+    let mut expected = StableBloomFilterDefault::new(1_000, 0.01);
+    expected.insert_all(0..1_000);
+    assert_eq!(expected.bitset(), filter.bitset());
+    assert_eq!(expected.hasher_count(), filter.hasher_count());
+    assert_eq!(
+        expected.approximate_element_count(),
+        filter.approximate_element_count()
+    );
+
+    // Keyed build hashers keep their keys for the lifetime of the filter
+    let mut filter = BuildHasherBloomFilter::with_build_hashers(
+        1_000,
+        0.01,
+        RandomState::new(),
+        ahash::RandomState::with_seeds(1, 2, 3, 4),
+    );
+    filter.insert_all(0..1_000);
+    assert_eq!(true, (0..1_000).all(|i| filter.contains(&i)));
+    assert!((1_000..11_000).filter(|i| filter.contains(i)).count() < 200);
+    assert!(filter.approximate_current_false_positive_probability() < 0.013);
+    let (build_hasher_a, _) = filter.build_hashers();
+    assert_eq!(build_hasher_a.hash_one(42), build_hasher_a.hash_one(42));
+
+    filter.clear();
+    assert_eq!(false, filter.contains(&1));
+}
+
+#[cfg(all(unix, feature = "shm"))]
+#[test]
+fn shm_bloom_filter_is_shared_between_handles() {
+    use bloom_filter_simple::ShmBloomFilter;
+
+    type SharedFilter = ShmBloomFilter<StableXxh3Hasher, StableXxh64Hasher>;
+
+    let name = format!("/bloom_filter_simple_shm_test_{}", std::process::id());
+    let writer = SharedFilter::create(&name, 10_000, 0.01).unwrap();
+    let reader = SharedFilter::open(&name).unwrap();
+    assert_eq!(writer.bit_count(), reader.bit_count());
+    assert_eq!(writer.hasher_count(), reader.hasher_count());
+
+    // Concurrent writers through separate mappings
+    std::thread::scope(|scope| {
+        for thread in 0..4u64 {
+            let name = &name;
+            scope.spawn(move || {
+                let filter = SharedFilter::open(name).unwrap();
+                for i in 0..1_000 {
+                    filter.insert(&(thread * 1_000 + i));
+                }
+            });
+        }
+    });
+    for i in 0..4_000u64 {
+        assert_eq!(true, reader.contains(&i));
+    }
+
+    let mut filter = StableBloomFilterDefault::new(10_000, 0.01);
+    filter.insert_all(0..4_000u64);
+    assert_eq!(filter.bitset(), reader.to_filter().bitset());
+    assert_eq!(
+        filter.approximate_element_count(),
+        writer.approximate_element_count()
+    );
+
+    // Segments that exist are not replaced
+    assert_eq!(
+        std::io::ErrorKind::AlreadyExists,
+        SharedFilter::create(&name, 10_000, 0.01)
+            .unwrap_err()
+            .kind()
+    );
+    // Segments written with different hashers are rejected
+    assert!(matches!(
+        ShmBloomFilter::<xxh3::Xxh3, xxh64::Xxh64>::open(&name),
+        Err(ReadFilterError::HasherMismatch)
+    ));
+
+    SharedFilter::unlink(&name).unwrap();
+    assert!(matches!(
+        SharedFilter::open(&name),
+        Err(ReadFilterError::Io(_))
+    ));
+    // Existing handles keep their mapping
+    assert_eq!(true, writer.contains(&42u64));
+
+    let copy = SharedFilter::create_from(&name, &filter).unwrap();
+    assert_eq!(filter.bitset(), copy.to_filter().bitset());
+    SharedFilter::unlink(&name).unwrap();
+}
+
+#[test]
+fn attenuated_bloom_filter_routes_to_the_nearest_level() {
+    type Routes = AttenuatedBloomFilter<StableXxh3Hasher, StableXxh64Hasher>;
+
+    // A line of nodes 0 - 1 - 2 - 3, where node i stores the elements i * 100..i * 100 + 100
+    let mut nodes: Vec<Routes> = (0..4u32)
+        .map(|node| {
+            let mut routes = Routes::new(4, 1_000, 0.001);
+            for element in node * 100..node * 100 + 100 {
+                routes.insert_at(0, &element);
+            }
+            routes
+        })
+        .collect();
+    // Routes propagate from the end of the line towards node 0, one hop per merge
+    for i in (0..3).rev() {
+        let (near, far) = nodes.split_at_mut(i + 1);
+        near[i].merge_neighbor(&far[0]);
+    }
+
+    // The nearest level of node 0 is the distance to the node that stores an element, except for
+    // rare false positives of nearer levels
+    let misrouted = (0..400u32)
+        .filter(|&element| nodes[0].lookup(&element) != Some(element as usize / 100))
+        .count();
+    assert!(misrouted <= 4, "{} elements misrouted", misrouted);
+    assert_eq!(true, nodes[0].contains(&399u32));
+    assert_eq!(4, nodes[0].depth());
+    assert_eq!(None, nodes[3].lookup(&"unknown"));
+
+    nodes[0].clear();
+    assert_eq!(None, nodes[0].lookup(&0u32));
+}
+
+#[test]
+#[should_panic(expected = "unable to access level 3")]
+fn attenuated_bloom_filter_insert_beyond_depth_panics() {
+    let mut routes: AttenuatedBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+        AttenuatedBloomFilter::new(3, 1_000, 0.001);
+    routes.insert_at(3, &"Hello!");
+}
+
+#[test]
+fn filter_cascade_is_exact_for_its_universe() {
+    type Cascade = FilterCascade<StableXxh3Hasher, StableXxh64Hasher>;
+
+    let included: Vec<u64> = (0..2_000).map(|i| i * 7).collect();
+    let excluded: Vec<u64> = (0..50_000).map(|i| i * 7 + 3).collect();
+    let cascade = Cascade::build(&included, &excluded).unwrap();
+    assert!(cascade.levels().len() > 1);
+    assert_eq!(true, included.iter().all(|i| cascade.contains(i)));
+    assert_eq!(false, excluded.iter().any(|i| cascade.contains(i)));
+    // Elements outside of the universe are contained with about the false positive probability
+    // of level 0
+    let outside = (0..50_000)
+        .filter(|i| cascade.contains(&(i * 7 + 5)))
+        .count();
+    assert!(outside < 50_000 * 5 / 100);
+
+    #[cfg(feature = "std")]
+    {
+        let mut bytes = Vec::new();
+        cascade.write_to(&mut bytes).unwrap();
+        let restored = Cascade::read_from(&bytes[..]).unwrap();
+        assert_eq!(cascade.bit_count(), restored.bit_count());
+        assert_eq!(true, included.iter().all(|i| restored.contains(i)));
+        assert_eq!(false, excluded.iter().any(|i| restored.contains(i)));
+        assert!(Cascade::read_from(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    // Empty sets
+    let cascade = Cascade::build(&included, &[]).unwrap();
+    assert_eq!(1, cascade.levels().len());
+    assert_eq!(true, included.iter().all(|i| cascade.contains(i)));
+    let cascade = Cascade::build(&[], &excluded).unwrap();
+    assert_eq!(false, excluded.iter().any(|i| cascade.contains(i)));
+    assert_eq!(false, Cascade::from_levels(Vec::new()).contains(&0));
+
+    // Elements that are both included and excluded cannot be separated
+    assert_eq!(
+        FilterCascadeConflict,
+        Cascade::build(&[1, 2, 3], &[3, 4, 5]).unwrap_err()
+    );
+}
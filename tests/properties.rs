@@ -0,0 +1,227 @@
+//! Property-based tests for the invariants that hold for every input, e.g., that filters never
+//! return false negatives. The integration tests check the same properties for integer keys only.
+
+use std::{collections::hash_map::DefaultHasher, hash::Hash};
+
+#[cfg(feature = "std")]
+use bloom_filter_simple::DefaultBloomFilter;
+use bloom_filter_simple::{
+    Bitset, BitsetDelta, BloomFilter, CountingBloomFilter, GcsFilter, QuotientFilter,
+    RotatingBloomFilter, SeededBloomFilter, ShardedBloomFilter, SparseBloomFilter,
+    StableBloomFilterDefault, StableXxh3Hasher, StableXxh64Hasher, XorFilter,
+};
+use proptest::prelude::*;
+
+/// Arbitrary keys of the shapes that are typically inserted: raw bytes, strings, and tuples.
+#[derive(Debug, Clone, Hash)]
+enum Key {
+    Bytes(Vec<u8>),
+    Text(String),
+    Pair(u32, String),
+    Triple(i64, bool, Vec<u8>),
+}
+
+fn key() -> impl Strategy<Value = Key> {
+    prop_oneof![
+        prop::collection::vec(any::<u8>(), 0..32).prop_map(Key::Bytes),
+        ".{0,24}".prop_map(Key::Text),
+        (any::<u32>(), ".{0,8}").prop_map(|(a, b)| Key::Pair(a, b)),
+        (
+            any::<i64>(),
+            any::<bool>(),
+            prop::collection::vec(any::<u8>(), 0..8)
+        )
+            .prop_map(|(a, b, c)| Key::Triple(a, b, c)),
+    ]
+}
+
+fn keys() -> impl Strategy<Value = Vec<Key>> {
+    prop::collection::vec(key(), 0..200)
+}
+
+/// The capacity of the filters, which is exceeded by some of the generated sequences on purpose.
+const CAPACITY: usize = 100;
+
+fn assert_contains_all<F, T>(filter: &F, keys: &[T]) -> Result<(), TestCaseError>
+where
+    F: BloomFilter,
+    T: Hash + std::fmt::Debug,
+{
+    for key in keys {
+        prop_assert!(filter.contains(key), "false negative for {:?}", key);
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    #[cfg(feature = "std")]
+    fn km_bloom_filter_has_no_false_negatives(keys in keys()) {
+        let mut filter = DefaultBloomFilter::new(CAPACITY, 0.01);
+        filter.insert_all(&keys);
+        assert_contains_all(&filter, &keys)?;
+    }
+
+    #[test]
+    fn seeded_bloom_filter_has_no_false_negatives(
+        keys in keys(),
+        seed_a in any::<u128>(),
+        seed_b in any::<u128>(),
+    ) {
+        let mut filter = SeededBloomFilter::with_seeds(CAPACITY, 0.01, seed_a, seed_b);
+        filter.insert_all(&keys);
+        assert_contains_all(&filter, &keys)?;
+    }
+
+    #[test]
+    fn counting_bloom_filter_has_no_false_negatives_after_removal(
+        kept in keys(),
+        removed in keys(),
+    ) {
+        let mut filter: CountingBloomFilter<ahash::AHasher, DefaultHasher> =
+            CountingBloomFilter::new(CAPACITY, 0.01);
+        filter.insert_all(&kept);
+        filter.insert_all(&removed);
+        for key in &removed {
+            prop_assert!(filter.remove(key));
+        }
+        assert_contains_all(&filter, &kept)?;
+    }
+
+    #[test]
+    fn quotient_filter_has_no_false_negatives_after_removal_and_growth(
+        kept in keys(),
+        removed in keys(),
+    ) {
+        // Small enough to run full, so that the filter grows while inserting
+        let mut filter: QuotientFilter<StableXxh3Hasher> = QuotientFilter::with_parameters(4, 16);
+        for key in kept.iter().chain(&removed) {
+            if filter.try_insert(key).is_err() {
+                filter.grow();
+                filter.insert(key);
+            }
+        }
+        for key in &removed {
+            prop_assert!(filter.remove(key));
+        }
+        prop_assert_eq!(kept.len(), filter.len());
+        assert_contains_all(&filter, &kept)?;
+    }
+
+    #[test]
+    fn rotating_and_sharded_bloom_filters_have_no_false_negatives(keys in keys()) {
+        let mut rotating: RotatingBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+            RotatingBloomFilter::new(3, CAPACITY, 0.01);
+        let mut sharded: ShardedBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+            ShardedBloomFilter::new(4, CAPACITY, 0.01);
+        rotating.insert_all(&keys);
+        sharded.insert_all(&keys);
+        assert_contains_all(&rotating, &keys)?;
+        assert_contains_all(&sharded, &keys)?;
+    }
+
+    #[test]
+    fn sparse_bloom_filter_has_no_false_negatives(keys in keys()) {
+        // Small enough to become dense for some of the sequences
+        let mut filter: SparseBloomFilter<StableXxh3Hasher, StableXxh64Hasher> =
+            SparseBloomFilter::new(CAPACITY, 0.01);
+        filter.insert_all(&keys);
+        assert_contains_all(&filter, &keys)?;
+    }
+
+    #[test]
+    fn static_filters_have_no_false_negatives(keys in keys()) {
+        let mut filter: GcsFilter<StableXxh3Hasher> = GcsFilter::new(0.01);
+        filter.insert_all(&keys);
+        let gcs = filter.to_gcs();
+        let xor_filter: XorFilter<StableXxh3Hasher> = XorFilter::from_elements(&keys);
+        for key in &keys {
+            prop_assert!(gcs.contains(key), "false negative for {:?}", key);
+            prop_assert!(xor_filter.contains(key), "false negative for {:?}", key);
+        }
+    }
+
+    #[test]
+    fn union_contains_everything_either_filter_contains(
+        keys_a in keys(),
+        keys_b in keys(),
+        probes in keys(),
+    ) {
+        let mut filter_a = StableBloomFilterDefault::new(CAPACITY, 0.01);
+        let mut filter_b = StableBloomFilterDefault::new(CAPACITY, 0.01);
+        filter_a.insert_all(&keys_a);
+        filter_b.insert_all(&keys_b);
+
+        let union = filter_a.union(&filter_b);
+        prop_assert_eq!(&union, &filter_b.union(&filter_a));
+        prop_assert!(filter_a.is_subset(&union));
+        prop_assert!(filter_b.is_subset(&union));
+        assert_contains_all(&union, &keys_a)?;
+        assert_contains_all(&union, &keys_b)?;
+        for probe in &probes {
+            if filter_a.contains(probe) || filter_b.contains(probe) {
+                prop_assert!(union.contains(probe));
+            }
+        }
+
+        filter_a.union_with(&filter_b);
+        let union_with = filter_a;
+        prop_assert_eq!(&union, &union_with);
+    }
+
+    #[test]
+    fn intersection_contains_everything_both_filters_contain(
+        keys_a in keys(),
+        keys_b in keys(),
+        common in keys(),
+        probes in keys(),
+    ) {
+        let mut filter_a = StableBloomFilterDefault::new(CAPACITY, 0.01);
+        let mut filter_b = StableBloomFilterDefault::new(CAPACITY, 0.01);
+        filter_a.insert_all(keys_a.iter().chain(&common));
+        filter_b.insert_all(keys_b.iter().chain(&common));
+
+        let intersection = filter_a.intersect(&filter_b);
+        prop_assert_eq!(&intersection, &filter_b.intersect(&filter_a));
+        prop_assert!(intersection.is_subset(&filter_a));
+        prop_assert!(intersection.is_subset(&filter_b));
+        assert_contains_all(&intersection, &common)?;
+        for probe in &probes {
+            if intersection.contains(probe) {
+                prop_assert!(filter_a.contains(probe) && filter_b.contains(probe));
+            }
+        }
+
+        filter_a.intersect_with(&filter_b);
+        let intersect_with = filter_a;
+        prop_assert_eq!(&intersection, &intersect_with);
+    }
+
+    #[test]
+    fn applying_delta_results_in_union(
+        length in 1..2_000usize,
+        old_ones in prop::collection::vec(any::<prop::sample::Index>(), 0..200),
+        new_ones in prop::collection::vec(any::<prop::sample::Index>(), 0..200),
+    ) {
+        let mut old = Bitset::new(length);
+        let mut new = Bitset::new(length);
+        for index in &old_ones {
+            old.set(index.index(length), true);
+        }
+        for index in &new_ones {
+            new.set(index.index(length), true);
+        }
+
+        let delta = Bitset::delta(&old, &new);
+        let decoded = BitsetDelta::from_bytes(delta.as_bytes().to_vec()).unwrap();
+        prop_assert_eq!(&delta, &decoded);
+        prop_assert_eq!(
+            new.difference(&old).iter_ones().collect::<Vec<_>>(),
+            decoded.iter_ones().collect::<Vec<_>>()
+        );
+
+        let mut replica = old.clone();
+        replica.apply_delta(&decoded);
+        prop_assert_eq!(old.union(&new), replica);
+    }
+}